@@ -15,8 +15,8 @@ use gpui::{
     MouseButton, SharedString, TitlebarOptions, Window, WindowBounds, WindowOptions,
 };
 use gpui_navigator::{
-    init_router, middleware_fn, NavigationRequest, Navigator, Route, RouteMiddleware, RouterOutlet,
-    Transition,
+    init_router, middleware_fn, MiddlewareOrdering, NavigationRequest, Navigator, Route,
+    RouteMiddleware, RouterOutlet, Transition,
 };
 
 // ============================================================================
@@ -191,6 +191,9 @@ fn setup_routes(log: &MiddlewareLog, cx: &mut App) {
     let timing = TimingMiddleware::new(log.clone());
 
     init_router(cx, |router| {
+        // Nested routes' middleware always run inside their parents'
+        router.set_middleware_ordering(MiddlewareOrdering::ByDepthThenPriority);
+
         router.add_route(
             Route::new("/", |_, _, _| {
                 content_page(
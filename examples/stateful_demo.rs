@@ -2,16 +2,17 @@
 //!
 //! Demonstrates how to create stateful page components using `Route::component()`.
 //! This is the CORRECT way to build pages in GPUI - using Entity-based components
-//! that maintain state across navigation.
+//! that maintain state across navigation. The window shell (header, sidebar,
+//! debug panel) comes from `NavScaffold` (`scaffold` feature).
 
 #![allow(clippy::needless_pass_by_ref_mut)]
 
 use gpui::{
-    div, px, rgb, size, App, AppContext, Application, Bounds, Context, Entity, InteractiveElement,
-    IntoElement, MouseButton, ParentElement, Render, Styled, TitlebarOptions, Window, WindowBounds,
+    div, px, rgb, size, App, Application, Bounds, Context, InteractiveElement, IntoElement,
+    MouseButton, ParentElement, Render, Styled, TitlebarOptions, Window, WindowBounds,
     WindowOptions,
 };
-use gpui_navigator::{init_router, Navigator, Route, RouterOutlet, Transition};
+use gpui_navigator::{init_router, NavScaffold, Route, Transition};
 
 fn main() {
     Application::new().run(|cx: &mut App| {
@@ -20,18 +21,21 @@ fn main() {
             router.add_route(
                 Route::component("/", HomePage::new)
                     .name("home")
+                    .meta("title", "Home")
                     .transition(Transition::None),
             );
 
             router.add_route(
                 Route::component("/counter", CounterPage::new)
                     .name("counter")
+                    .meta("title", "Counter")
                     .transition(Transition::fade(300)),
             );
 
             router.add_route(
                 Route::component("/form", FormPage::new)
                     .name("form")
+                    .meta("title", "Form")
                     .transition(Transition::slide_left(400)),
             );
         });
@@ -48,7 +52,13 @@ fn main() {
                 }),
                 ..Default::default()
             },
-            |_, cx| cx.new(StatefulDemoApp::new),
+            |_, cx| {
+                NavScaffold::new()
+                    .title("Stateful Route Demo")
+                    .sidebar_from_routes("/")
+                    .with_debug_panel(true)
+                    .build(cx)
+            },
         )
         .unwrap();
 
@@ -56,67 +66,6 @@ fn main() {
     });
 }
 
-struct StatefulDemoApp {
-    outlet: Entity<RouterOutlet>,
-}
-
-impl StatefulDemoApp {
-    fn new(cx: &mut Context<'_, Self>) -> Self {
-        Self {
-            outlet: cx.new(|_| RouterOutlet::new()),
-        }
-    }
-}
-
-impl Render for StatefulDemoApp {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
-        div()
-            .flex()
-            .flex_col()
-            .size_full()
-            .bg(rgb(0x1e_1e_1e))
-            .text_color(rgb(0xff_ff_ff))
-            // Navigation bar
-            .child(
-                div()
-                    .flex()
-                    .gap_2()
-                    .p_4()
-                    .bg(rgb(0x2d_2d_2d))
-                    .border_b_1()
-                    .border_color(rgb(0x3e_3e_3e))
-                    .child(self.nav_button(cx, "/", "Home"))
-                    .child(self.nav_button(cx, "/counter", "Counter"))
-                    .child(self.nav_button(cx, "/form", "Form")),
-            )
-            // Router outlet
-            .child(div().flex_1().p_4().child(self.outlet.clone()))
-    }
-}
-
-impl StatefulDemoApp {
-    #[allow(clippy::unused_self)]
-    fn nav_button(&self, cx: &mut Context<'_, Self>, path: &str, label: &str) -> impl IntoElement {
-        let path = path.to_string();
-        let label = label.to_string();
-
-        div()
-            .px_4()
-            .py_2()
-            .bg(rgb(0x40_40_40))
-            .rounded_md()
-            .cursor_pointer()
-            .hover(|style| style.bg(rgb(0x50_50_50)))
-            .on_mouse_down(
-                MouseButton::Left,
-                cx.listener(move |_view, _event, _window, cx| {
-                    Navigator::push(cx, path.clone());
-                }),
-            )
-            .child(label)
-    }
-}
-
 // ============================================================================
 // Home Page - Simple static content
 // ============================================================================
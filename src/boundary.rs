@@ -0,0 +1,341 @@
+//! Per-route error boundaries.
+//!
+//! When a route of the match stack fails — its loader or a param validator
+//! rejects during navigation, or its builder is missing or panics during
+//! render — the failure is recorded as a [`RouteError`] on its
+//! [`MatchEntry`](crate::MatchEntry). The nearest route at or above it that
+//! declares [`Route::error_boundary`](crate::Route::error_boundary) renders
+//! the boundary in place of its own builder, so everything from that level
+//! down is replaced while the layouts above stay on screen. Without a
+//! boundary, failures go to the [`ErrorHandlers`](crate::ErrorHandlers) as
+//! before.
+//!
+//! The boundary receives a [`RetryHandle`] that runs the failed stage again
+//! for the failed entry, without a navigation:
+//!
+//! ```no_run
+//! use gpui_navigator::Route;
+//! use gpui::*;
+//!
+//! Route::new("/reports", |_, _cx, _params| div().into_any_element()).error_boundary(
+//!     |error, retry, _window, _cx| {
+//!         let retry = retry.clone();
+//!         div()
+//!             .id("report-error")
+//!             .child(error.to_string())
+//!             .child("Retry")
+//!             .on_click(move |_, _, cx| retry.retry(cx))
+//!             .into_any_element()
+//!     },
+//! );
+//! ```
+
+use crate::context::GlobalRouter;
+use crate::error::RouteError;
+use crate::route::Route;
+use gpui::{AnyElement, App, BorrowAppContext, Window};
+use std::sync::Arc;
+
+/// Renders a [`RouteError`] caught by [`Route::error_boundary`](crate::Route::error_boundary).
+pub type ErrorBoundaryFn =
+    Arc<dyn Fn(&RouteError, &RetryHandle, &mut Window, &mut App) -> AnyElement + Send + Sync>;
+
+/// Runs the failed stage of one match stack entry again.
+///
+/// Handed to error boundaries. A handle outlives its entry harmlessly:
+/// once the app navigated away, [`retry`](Self::retry) does nothing.
+#[derive(Debug, Clone)]
+pub struct RetryHandle {
+    depth: usize,
+    route: Arc<Route>,
+}
+
+impl RetryHandle {
+    pub(crate) const fn new(depth: usize, route: Arc<Route>) -> Self {
+        Self { depth, route }
+    }
+
+    /// Depth of the failed entry in the match stack.
+    #[must_use]
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Run the failed stage again.
+    ///
+    /// Loader and validator failures re-run the loader stage from the failed
+    /// entry down; render failures clear the error so the route builds again
+    /// on the next frame. The error stays cleared if the stage succeeds.
+    pub fn retry(&self, cx: &mut App) {
+        if cx.try_global::<GlobalRouter>().is_none() {
+            return;
+        }
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            router.retry_entry(self.depth, &self.route, cx);
+        });
+        cx.refresh_windows();
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::error::{ErrorHandlers, NavigationError};
+    use crate::{init_router, render_router_outlet, router_view, Navigator};
+    use gpui::{div, Context, IntoElement, ParentElement, Render, TestAppContext};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    struct Root;
+
+    impl Render for Root {
+        fn render(&mut self, _: &mut Window, _: &mut Context<'_, Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    /// A layout that logs `label` and renders its child outlet.
+    fn layout(log: &Log, path: &str, label: &'static str) -> Route {
+        let log = Arc::clone(log);
+        Route::new(path, move |window, cx, _| {
+            log.lock().unwrap().push(label.to_string());
+            div()
+                .child(render_router_outlet(window, cx, None))
+                .into_any_element()
+        })
+    }
+
+    /// A boundary that logs `label` and the error, and keeps the handle.
+    fn boundary(
+        log: &Log,
+        label: &'static str,
+        handle: &Arc<Mutex<Option<RetryHandle>>>,
+    ) -> impl Fn(&RouteError, &RetryHandle, &mut Window, &mut App) -> AnyElement {
+        let log = Arc::clone(log);
+        let handle = Arc::clone(handle);
+        move |error, retry, _, _| {
+            log.lock().unwrap().push(format!("{label}: {error}"));
+            *handle.lock().unwrap() = Some(retry.clone());
+            div().into_any_element()
+        }
+    }
+
+    fn failing_loader(_: &App, _: &crate::RouteParams) -> crate::LoaderResult<u32> {
+        Err(NavigationError::Custom {
+            message: "offline".into(),
+        })
+    }
+
+    fn render(cx: &mut TestAppContext, log: &Log) -> Vec<String> {
+        log.lock().unwrap().clear();
+        let window = cx.add_window(|_, _| Root);
+        window
+            .update(cx, |_, window, cx| {
+                let _ = router_view(window, cx);
+            })
+            .unwrap();
+        std::mem::take(&mut *log.lock().unwrap())
+    }
+
+    fn error_at(cx: &mut TestAppContext, depth: usize) -> Option<RouteError> {
+        cx.read(|cx| {
+            cx.global::<GlobalRouter>()
+                .match_stack()
+                .at_depth(depth)
+                .and_then(|entry| entry.error().cloned())
+        })
+    }
+
+    #[gpui::test]
+    fn test_errors_are_recorded_at_their_depth(cx: &mut TestAppContext) {
+        let log = Log::default();
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(layout(&log, "/", "root"));
+                router.add_route(
+                    layout(&log, "/orders", "orders")
+                        .child(
+                            Route::view("live", || div().into_any_element()).loader(failing_loader),
+                        )
+                        .child(
+                            Route::view(":id", || div().into_any_element()).validate_param(
+                                "id",
+                                |value| {
+                                    value
+                                        .parse::<u32>()
+                                        .map(drop)
+                                        .map_err(|_| "not a number".to_string())
+                                },
+                            ),
+                        ),
+                );
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/orders/abc"));
+        assert_eq!(error_at(cx, 0), None);
+        assert_eq!(
+            error_at(cx, 1),
+            Some(RouteError::InvalidParam {
+                name: "id".into(),
+                value: "abc".into(),
+                reason: "not a number".into(),
+            })
+        );
+        let above = cx.read(|cx| {
+            cx.global::<GlobalRouter>()
+                .match_stack()
+                .error_at_or_below(0)
+                .cloned()
+        });
+        assert_eq!(above, error_at(cx, 1));
+
+        cx.update(|cx| Navigator::push(cx, "/orders/live"));
+        assert_eq!(
+            error_at(cx, 1),
+            Some(RouteError::LoaderFailed {
+                message: "offline".into(),
+                source_kind: "custom",
+            })
+        );
+
+        // Navigating away starts from a clean stack
+        cx.update(|cx| Navigator::push(cx, "/orders/7"));
+        assert_eq!(error_at(cx, 1), None);
+    }
+
+    #[gpui::test]
+    fn test_nearest_boundary_renders_instead_of_subtree(cx: &mut TestAppContext) {
+        let log = Log::default();
+        let handle = Arc::default();
+        let broken = || Route::view("broken", || div().into_any_element()).loader(failing_loader);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    layout(&log, "/", "root")
+                        .error_boundary(boundary(&log, "outer", &handle))
+                        .child(
+                            layout(&log, "settings", "settings")
+                                .error_boundary(boundary(&log, "inner", &handle))
+                                .child(broken()),
+                        )
+                        .child(layout(&log, "reports", "reports").child(broken())),
+                );
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/settings/broken"));
+        assert_eq!(render(cx, &log), ["root", "inner: Loader failed: offline"]);
+
+        cx.update(|cx| Navigator::push(cx, "/reports/broken"));
+        assert_eq!(render(cx, &log), ["outer: Loader failed: offline"]);
+        let retry = handle.lock().unwrap().clone().unwrap();
+        assert_eq!(retry.depth(), 2);
+    }
+
+    #[gpui::test]
+    fn test_retry_reruns_loader_and_rebuilds(cx: &mut TestAppContext) {
+        static ONLINE: AtomicBool = AtomicBool::new(false);
+        let log = Log::default();
+        let handle = Arc::default();
+        let leaf_log = Arc::clone(&log);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    layout(&log, "/feed", "feed")
+                        .error_boundary(boundary(&log, "boundary", &handle))
+                        .child(
+                            Route::new("latest", move |_, _, _| {
+                                leaf_log.lock().unwrap().push("latest".into());
+                                div().into_any_element()
+                            })
+                            .loader(|cx, params| {
+                                if ONLINE.load(Ordering::SeqCst) {
+                                    Ok(7_u32)
+                                } else {
+                                    failing_loader(cx, params)
+                                }
+                            }),
+                        ),
+                );
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/feed/latest"));
+        assert_eq!(render(cx, &log), ["boundary: Loader failed: offline"]);
+
+        // Still failing: the error stays
+        let retry = handle.lock().unwrap().clone().unwrap();
+        cx.update(|cx| retry.retry(cx));
+        assert!(error_at(cx, 1).is_some());
+
+        ONLINE.store(true, Ordering::SeqCst);
+        cx.update(|cx| retry.retry(cx));
+        assert_eq!(error_at(cx, 1), None);
+        assert_eq!(cx.read(Navigator::loader_data::<u32>).as_deref(), Some(&7));
+        assert_eq!(render(cx, &log), ["feed", "latest"]);
+    }
+
+    #[gpui::test]
+    fn test_panicking_builder_is_caught_and_retried(cx: &mut TestAppContext) {
+        static BROKEN: AtomicBool = AtomicBool::new(true);
+        let log = Log::default();
+        let handle = Arc::default();
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    layout(&log, "/", "root")
+                        .error_boundary(boundary(&log, "boundary", &handle))
+                        .child(Route::new("chart", |_, _, _| {
+                            assert!(!BROKEN.load(Ordering::SeqCst), "no data");
+                            div().into_any_element()
+                        })),
+                );
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/chart"));
+        render(cx, &log);
+        assert!(matches!(error_at(cx, 1), Some(RouteError::Panicked { .. })));
+        // The next frame renders the boundary in place of the root's builder
+        let frame = render(cx, &log);
+        assert_eq!(frame.len(), 1);
+        assert!(
+            frame[0].starts_with("boundary: Route panicked"),
+            "{frame:?}"
+        );
+
+        BROKEN.store(false, Ordering::SeqCst);
+        let retry = handle.lock().unwrap().clone().unwrap();
+        cx.update(|cx| retry.retry(cx));
+        assert_eq!(error_at(cx, 1), None);
+        assert_eq!(render(cx, &log), ["root"]);
+        assert_eq!(error_at(cx, 1), None);
+    }
+
+    #[gpui::test]
+    fn test_errors_without_boundary_go_to_error_handlers(cx: &mut TestAppContext) {
+        let log = Log::default();
+        let handled = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&handled);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(layout(&log, "/", "root").child(
+                    Route::view("broken", || div().into_any_element()).loader(failing_loader),
+                ));
+                router.set_error_handlers(ErrorHandlers::new().on_error(move |_, error| {
+                    sink.lock().unwrap().push(error.to_string());
+                    div().into_any_element()
+                }));
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/broken"));
+        assert_eq!(render(cx, &log), ["root"]);
+        assert_eq!(*handled.lock().unwrap(), ["offline"]);
+        assert!(error_at(cx, 1).is_some());
+    }
+}
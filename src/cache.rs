@@ -1,22 +1,35 @@
-//! Route resolution caching.
+//! Route resolution and component caching.
 //!
-//! This module provides [`RouteCache`] — an LRU-based cache that avoids
-//! repeated route tree lookups during rendering. It is gated behind the
-//! `cache` feature flag and uses the [`lru`] crate internally.
+//! The router keeps two caches, both evicting the least recently used entry
+//! once full and both sized by one [`CachePolicy`]:
 //!
-//! Two independent LRU caches are maintained:
+//! - [`RouteCache`] avoids repeated route tree lookups during rendering. It
+//!   is gated behind the `cache` feature flag and uses the `lru` crate
+//!   internally. Its entries depend on the route table and the current
+//!   path, so it is cleared on route registration and navigation.
+//! - The component cache holds the entities built by `Route::component` and
+//!   `Route::component_with_params`, so component state survives navigating
+//!   away and back. It is always on and outlives navigations.
+//!
+//! [`GlobalRouter::set_cache_policy`](crate::GlobalRouter::set_cache_policy),
+//! [`GlobalRouter::clear_caches`](crate::GlobalRouter::clear_caches) and
+//! [`GlobalRouter::cache_stats`](crate::GlobalRouter::cache_stats) cover
+//! both caches.
+//!
+//! [`RouteCache`] maintains two independent LRU caches:
 //!
 //! - **Parent cache** — maps a full request path to the [`RouteId`] of the
 //!   parent route that owns it (e.g. `"/dashboard/analytics"` → `"/dashboard"`).
 //! - **Child cache** — maps an `(path, outlet_name)` pair to resolved
 //!   [`RouteParams`].
 //!
-//! [`CacheStats`] tracks hits, misses, and invalidations so you can monitor
-//! cache effectiveness at runtime.
+//! [`CacheStats`] tracks hits, misses, evictions and invalidations so you
+//! can monitor cache effectiveness at runtime.
 //!
 //! # Examples
 //!
 //! ```
+//! # #[cfg(feature = "cache")] {
 //! use gpui_navigator::cache::{RouteCache, RouteId};
 //!
 //! let mut cache = RouteCache::new();
@@ -24,12 +37,92 @@
 //!
 //! assert_eq!(cache.get_parent("/dashboard/analytics").unwrap().path, "/dashboard");
 //! assert_eq!(cache.stats().parent_hits, 1);
+//! # }
 //! ```
 
 use crate::route::Route;
-use crate::{debug_log, trace_log, RouteParams};
+#[cfg(feature = "cache")]
+use crate::{debug_log, RouteParams};
+use crate::{trace_log, warn_log};
+use gpui::AnyView;
+#[cfg(feature = "cache")]
 use lru::LruCache;
+use std::collections::{HashMap, VecDeque};
+#[cfg(feature = "cache")]
 use std::num::NonZeroUsize;
+use std::time::Instant;
+
+/// Capacities of the router's caches.
+///
+/// ```
+/// use gpui_navigator::cache::CachePolicy;
+///
+/// let policy = CachePolicy::new().route_capacity(200).component_capacity(32);
+/// assert_eq!(policy.component_capacity, 32);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// Entries per sub-cache of the route resolution cache
+    pub route_capacity: usize,
+    /// Unpinned component entities kept per partition
+    pub component_capacity: usize,
+    /// Pinned component entities allowed per partition, on top of
+    /// `component_capacity`
+    pub pinned_component_capacity: usize,
+}
+
+impl CachePolicy {
+    /// Default route resolution cache capacity.
+    pub const DEFAULT_ROUTE_CAPACITY: usize = 1000;
+    /// Default component cache capacity.
+    pub const DEFAULT_COMPONENT_CAPACITY: usize = 128;
+    /// Default number of pinned components allowed.
+    pub const DEFAULT_PINNED_COMPONENT_CAPACITY: usize = 16;
+
+    /// Create a policy with the default capacities.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            route_capacity: Self::DEFAULT_ROUTE_CAPACITY,
+            component_capacity: Self::DEFAULT_COMPONENT_CAPACITY,
+            pinned_component_capacity: Self::DEFAULT_PINNED_COMPONENT_CAPACITY,
+        }
+    }
+
+    /// Set the capacity of each route resolution sub-cache.
+    ///
+    /// Zero is treated as one.
+    #[must_use]
+    pub const fn route_capacity(mut self, capacity: usize) -> Self {
+        self.route_capacity = capacity;
+        self
+    }
+
+    /// Set how many component entities are kept.
+    ///
+    /// Zero is treated as one.
+    #[must_use]
+    pub const fn component_capacity(mut self, capacity: usize) -> Self {
+        self.component_capacity = capacity;
+        self
+    }
+
+    /// Set how many component entities may be pinned; zero disables pinning.
+    ///
+    /// Pinned entities are never evicted to make room and do not count
+    /// toward [`component_capacity`](Self::component_capacity).
+    #[must_use]
+    pub const fn pinned_component_capacity(mut self, capacity: usize) -> Self {
+        self.pinned_component_capacity = capacity;
+        self
+    }
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 /// Unique identifier for a route in the tree
 ///
@@ -57,6 +150,7 @@ impl RouteId {
 }
 
 /// Cache key for outlet resolution
+#[cfg(feature = "cache")]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct OutletCacheKey {
     path: String,
@@ -64,6 +158,7 @@ struct OutletCacheKey {
 }
 
 /// Cached result of finding a parent route
+#[cfg(feature = "cache")]
 #[derive(Debug, Clone)]
 struct ParentRouteCacheEntry {
     parent_route_id: RouteId,
@@ -84,8 +179,15 @@ pub struct CacheStats {
     pub child_hits: usize,
     /// Number of child-cache misses.
     pub child_misses: usize,
-    /// Number of full cache invalidations (via [`RouteCache::clear`]).
+    /// Number of full cache invalidations (via `RouteCache::clear`).
     pub invalidations: usize,
+    /// Number of component-cache hits.
+    pub component_hits: usize,
+    /// Number of component-cache misses.
+    pub component_misses: usize,
+    /// Number of components evicted to stay within capacity, or because
+    /// their route was replaced.
+    pub component_evictions: usize,
 }
 
 impl CacheStats {
@@ -115,6 +217,18 @@ impl CacheStats {
         }
     }
 
+    /// Return the component-cache hit rate as a value in `0.0..=1.0`.
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn component_hit_rate(&self) -> f64 {
+        let total = self.component_hits + self.component_misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.component_hits as f64 / total as f64
+        }
+    }
+
     /// Return the combined (parent + child) hit rate as a value in `0.0..=1.0`.
     #[allow(clippy::cast_precision_loss)]
     #[must_use]
@@ -137,6 +251,7 @@ impl CacheStats {
 ///
 /// The cache is automatically cleared on route registration and navigation
 /// to ensure consistency.
+#[cfg(feature = "cache")]
 #[derive(Debug)]
 pub struct RouteCache {
     parent_cache: LruCache<String, ParentRouteCacheEntry>,
@@ -144,13 +259,12 @@ pub struct RouteCache {
     stats: CacheStats,
 }
 
+#[cfg(feature = "cache")]
 impl RouteCache {
-    const DEFAULT_CAPACITY: usize = 1000;
-
     /// Create a cache with the default capacity (1000 entries per sub-cache).
     #[must_use]
     pub fn new() -> Self {
-        Self::with_capacity(Self::DEFAULT_CAPACITY)
+        Self::with_capacity(CachePolicy::DEFAULT_ROUTE_CAPACITY)
     }
 
     /// Create a cache with a custom per-sub-cache capacity.
@@ -248,6 +362,18 @@ impl RouteCache {
             .push(OutletCacheKey { path, outlet_name }, params);
     }
 
+    /// Change the per-sub-cache capacity, evicting least recently used
+    /// entries that no longer fit. Statistics are kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn resize(&mut self, capacity: usize) {
+        let cap = NonZeroUsize::new(capacity).expect("Cache capacity must be non-zero");
+        self.parent_cache.resize(cap);
+        self.child_cache.resize(cap);
+    }
+
     /// Return a reference to the current cache statistics.
     #[must_use]
     pub const fn stats(&self) -> &CacheStats {
@@ -278,12 +404,14 @@ impl RouteCache {
     }
 }
 
+#[cfg(feature = "cache")]
 impl Default for RouteCache {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[cfg(feature = "cache")]
 impl Clone for RouteCache {
     fn clone(&self) -> Self {
         let parent_cap = self.parent_cache.cap();
@@ -296,7 +424,275 @@ impl Clone for RouteCache {
     }
 }
 
-#[cfg(test)]
+/// A component cache entry, as listed by
+/// [`GlobalRouter::component_cache_entries`](crate::GlobalRouter::component_cache_entries).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentCacheEntryInfo {
+    /// Cache key
+    pub key: String,
+    /// Whether the entry is exempt from eviction
+    pub pinned: bool,
+    /// When the entry was stored or last rendered
+    pub last_access: Instant,
+    /// Pattern of the route that built the entity; `None` for entries
+    /// stored with [`cache_component`](crate::GlobalRouter::cache_component)
+    pub route_pattern: Option<String>,
+}
+
+/// One entity held by the [`ComponentCache`].
+#[derive(Clone)]
+struct CachedComponent {
+    view: AnyView,
+    route_pattern: Option<String>,
+    pinned: bool,
+    last_access: Instant,
+}
+
+/// LRU cache of the component entities built by component routes.
+///
+/// Unlike `window.use_keyed_state()`, which is frame-scoped, entries persist
+/// across navigations so that component state survives when the user
+/// navigates away and back. Each partition has its own.
+///
+/// Pinned entries are skipped by eviction and counted against their own
+/// capacity; only [`remove`](Self::remove) drops them.
+#[derive(Clone)]
+pub(crate) struct ComponentCache {
+    entries: HashMap<String, CachedComponent>,
+    /// Keys from least to most recently used, pinned ones included.
+    order: VecDeque<String>,
+    capacity: usize,
+    pinned_capacity: usize,
+}
+
+impl ComponentCache {
+    pub(crate) fn new(policy: CachePolicy) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: policy.component_capacity.max(1),
+            pinned_capacity: policy.pinned_component_capacity,
+        }
+    }
+
+    /// Look up `key` without counting it as a use.
+    pub(crate) fn peek(&self, key: &str) -> Option<&AnyView> {
+        self.entries.get(key).map(|entry| &entry.view)
+    }
+
+    /// Look up `key`, marking it most recently used.
+    pub(crate) fn get(&mut self, key: &str) -> Option<AnyView> {
+        let entry = self.entries.get_mut(key)?;
+        entry.last_access = Instant::now();
+        let view = entry.view.clone();
+        self.touch(key);
+        Some(view)
+    }
+
+    /// Store `view` as most recently used, pinned if `pin` and the pinned
+    /// capacity allows, evicting the least recently used unpinned entries
+    /// beyond capacity. Returns the number evicted.
+    pub(crate) fn insert(
+        &mut self,
+        key: String,
+        view: AnyView,
+        route_pattern: Option<String>,
+        pin: bool,
+    ) -> usize {
+        let pinned = self.entries.get(&key).is_some_and(|entry| entry.pinned)
+            || (pin && self.pin_room(&key));
+        if pin && !pinned {
+            warn_log!(
+                "Pinned component capacity ({}) reached, '{}' cached unpinned",
+                self.pinned_capacity,
+                key
+            );
+        }
+        let entry = CachedComponent {
+            view,
+            route_pattern,
+            pinned,
+            last_access: Instant::now(),
+        };
+        if self.entries.insert(key.clone(), entry).is_some() {
+            self.touch(&key);
+        } else {
+            self.order.push_back(key);
+        }
+        self.enforce_capacity()
+    }
+
+    /// Whether [`insert_cold`](Self::insert_cold) would store `key`.
+    pub(crate) fn accepts_cold(&self, key: &str, pin: bool) -> bool {
+        !self.entries.contains_key(key)
+            && ((pin && self.pin_room(key)) || self.unpinned_len() < self.capacity)
+    }
+
+    /// Store `view` as least recently used, if the cache has room and does
+    /// not hold `key` yet — never evicting an entry. Returns whether stored.
+    pub(crate) fn insert_cold(
+        &mut self,
+        key: String,
+        view: AnyView,
+        route_pattern: Option<String>,
+        pin: bool,
+    ) -> bool {
+        if !self.accepts_cold(&key, pin) {
+            return false;
+        }
+        let entry = CachedComponent {
+            view,
+            route_pattern,
+            pinned: pin && self.pin_room(&key),
+            last_access: Instant::now(),
+        };
+        self.entries.insert(key.clone(), entry);
+        self.order.push_front(key);
+        true
+    }
+
+    /// Drop `key`, pinned or not.
+    pub(crate) fn remove(&mut self, key: &str) -> bool {
+        if self.entries.remove(key).is_none() {
+            return false;
+        }
+        self.order.retain(|k| k != key);
+        true
+    }
+
+    /// Drop the entries built by routes with one of the `patterns`, pinned
+    /// or not, and return how many were dropped.
+    pub(crate) fn remove_route_patterns(&mut self, patterns: &[&str]) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| {
+            !entry
+                .route_pattern
+                .as_deref()
+                .is_some_and(|pattern| patterns.contains(&pattern))
+        });
+        let entries = &self.entries;
+        self.order.retain(|key| entries.contains_key(key));
+        before - self.entries.len()
+    }
+
+    /// Exempt `key` from eviction. Returns `false` if it is not cached or
+    /// the pinned capacity is reached.
+    pub(crate) fn pin(&mut self, key: &str) -> bool {
+        if !self.entries.contains_key(key) || !self.pin_room(key) {
+            return false;
+        }
+        if let Some(entry) = self.entries.get_mut(key) {
+            entry.pinned = true;
+        }
+        true
+    }
+
+    /// Make `key` evictable again, evicting what no longer fits. Returns
+    /// whether it was pinned, and the number evicted.
+    pub(crate) fn unpin(&mut self, key: &str) -> (bool, usize) {
+        let Some(entry) = self.entries.get_mut(key).filter(|entry| entry.pinned) else {
+            return (false, 0);
+        };
+        entry.pinned = false;
+        (true, self.enforce_capacity())
+    }
+
+    pub(crate) fn is_pinned(&self, key: &str) -> bool {
+        self.entries.get(key).is_some_and(|entry| entry.pinned)
+    }
+
+    /// Apply the capacities of `policy`, unpinning the least recently used
+    /// pinned entries and evicting what no longer fits. Returns the number
+    /// evicted.
+    pub(crate) fn set_policy(&mut self, policy: CachePolicy) -> usize {
+        self.capacity = policy.component_capacity.max(1);
+        self.pinned_capacity = policy.pinned_component_capacity;
+        let mut excess = self.pinned_len().saturating_sub(self.pinned_capacity);
+        for key in &self.order {
+            if excess == 0 {
+                break;
+            }
+            if let Some(entry) = self.entries.get_mut(key).filter(|entry| entry.pinned) {
+                entry.pinned = false;
+                excess -= 1;
+            }
+        }
+        self.enforce_capacity()
+    }
+
+    /// List the entries, most recently used first.
+    pub(crate) fn entries(&self) -> Vec<ComponentCacheEntryInfo> {
+        self.order
+            .iter()
+            .rev()
+            .filter_map(|key| {
+                let entry = self.entries.get(key)?;
+                Some(ComponentCacheEntryInfo {
+                    key: key.clone(),
+                    pinned: entry.pinned,
+                    last_access: entry.last_access,
+                    route_pattern: entry.route_pattern.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Keys of the entries, in no particular order.
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn pinned_len(&self) -> usize {
+        self.entries.values().filter(|entry| entry.pinned).count()
+    }
+
+    fn unpinned_len(&self) -> usize {
+        self.entries.len() - self.pinned_len()
+    }
+
+    /// Whether `key` is pinned or one more entry may be.
+    fn pin_room(&self, key: &str) -> bool {
+        self.is_pinned(key) || self.pinned_len() < self.pinned_capacity
+    }
+
+    /// Move `key` to the most recently used end.
+    fn touch(&mut self, key: &str) {
+        if let Some(index) = self.order.iter().position(|k| k == key) {
+            if let Some(key) = self.order.remove(index) {
+                self.order.push_back(key);
+            }
+        }
+    }
+
+    /// Evict least recently used unpinned entries down to the capacity,
+    /// returning how many.
+    fn enforce_capacity(&mut self) -> usize {
+        let mut evicted = 0;
+        while self.unpinned_len() > self.capacity {
+            let Some(index) = self.order.iter().position(|key| !self.is_pinned(key)) else {
+                break;
+            };
+            let Some(oldest) = self.order.remove(index) else {
+                break;
+            };
+            self.entries.remove(&oldest);
+            trace_log!("Evicted component '{}'", oldest);
+            evicted += 1;
+        }
+        evicted
+    }
+}
+
+#[cfg(all(test, feature = "cache"))]
 mod tests {
     use super::*;
 
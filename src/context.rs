@@ -28,28 +28,82 @@
 //! ```
 
 #[cfg(feature = "cache")]
-use crate::cache::{CacheStats, RouteCache};
-use crate::error::{ErrorHandlers, NavigationResult};
-use crate::history::{HistoryEntry, HistoryState};
-use crate::lifecycle::NavigationAction;
-use crate::nested::trim_slashes;
-use crate::resolve::{resolve_match_stack, MatchStack};
-use crate::route::NamedRouteRegistry;
+use crate::cache::RouteCache;
+use crate::cache::{CachePolicy, CacheStats, ComponentCache, ComponentCacheEntryInfo};
+use crate::error::{
+    AlreadyRegisteredError, BatchNavigationError, ErrorHandlers, NavigationError, NavigationResult,
+    NotFoundBehavior, ReservedOutletError, RouteError, RouteTreeError, UrlForError,
+};
+#[cfg(feature = "explain")]
+use crate::explain::{
+    LifecycleDecision, LifecycleHook, NavigationExplanation, RedirectCause, RedirectHop,
+};
+use crate::extensions::Extensions;
+use crate::help::HelpEntry;
+#[cfg(feature = "transition")]
+use crate::hero::HeroRegistry;
+use crate::history::{HistoryEntry, HistoryState, ScrollIntent, ScrollRestoration};
+use crate::invalidation::{
+    gone_depth, CurrentRouteInvalidated, InvalidationPolicy, InvalidationReason, RouteInvalidatedFn,
+};
+use crate::lifecycle::{DirtyStateRegistry, NavigationAction};
+use crate::loader::LoaderData;
+use crate::logging::{NavigationId, NavigationSpan};
+use crate::nested::{build_child_path, normalize_path, trim_slashes};
+use crate::new_window::RouterWindow;
+use crate::notifications::{NotificationConfig, Notifications, RouterNotification};
+use crate::outlet_policy::{stack_owner, OutletPolicy, OwnerTag};
+use crate::params::{
+    decode_path_segment, encode_path_segment, ParamTypeError, ParamTypes, RouteParamType,
+};
+use crate::pattern::{path_matches, Pattern};
+use crate::preview::{HoverPreview, PreviewConstraints};
+use crate::progress::{NavigationProgress, NavigationStage, ProgressTracker};
+use crate::query::QueryError;
+use crate::refetch::RouteTasks;
+use crate::resolve::{
+    check_route_tree, resolve_match_stack, resolve_named_outlet, MatchEntry, MatchStack,
+};
+use crate::route::{is_key_or_dependency, NamedRouteRegistry};
+use crate::route_prefs::RoutePrefs;
+use crate::route_snapshot::{RouteSnapshot, RouteSnapshotHandle};
+use crate::route_state::RouteStateStore;
+use crate::state::{HistoryMutation, RouterSnapshot};
+use crate::timers::RouteTimers;
 #[cfg(feature = "transition")]
-use crate::transition::Transition;
+use crate::transition::{
+    NavigationSource, Transition, TransitionEvent, TransitionHookId, TransitionHooks,
+    TransitionSources,
+};
+use crate::warming::{recent_paths, Warming, WarmingStatus, WARM_INTERVAL};
 use crate::{
     debug_log, error_log, info_log, trace_log, warn_log, IntoRoute, Route, RouteParams, RouterState,
 };
-use gpui::{AnyView, App, BorrowAppContext, Global};
-use std::borrow::BorrowMut;
+use gpui::{
+    AnyElement, AnyView, AnyWindowHandle, App, AppContext as _, BorrowAppContext, Global, Window,
+    WindowHandle, WindowId, WindowOptions,
+};
+use std::any::Any;
+use std::borrow::{BorrowMut, Cow};
 use std::collections::HashMap;
+#[cfg(feature = "explain")]
+use std::ops::ControlFlow;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Maximum redirect depth to prevent infinite redirect loops.
 const MAX_REDIRECT_DEPTH: usize = 5;
 
-/// Maximum number of cached component views before FIFO eviction kicks in.
-const MAX_COMPONENT_CACHE: usize = 128;
+/// Maximum number of times a single guard may challenge one navigation.
+#[cfg(feature = "guard")]
+pub const MAX_CHALLENGE_ROUNDS: usize = 3;
+
+/// Default time an outlet keeps stale content before showing the loading page.
+#[cfg(feature = "transition")]
+const DEFAULT_STALE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Minimum interval between two prefetch attempts for the same path.
+const PREFETCH_DEBOUNCE: Duration = Duration::from_millis(250);
 
 // ============================================================================
 // NavigationRequest
@@ -69,6 +123,7 @@ const MAX_COMPONENT_CACHE: usize = 128;
 /// assert_eq!(request.to, "/dashboard");
 /// ```
 #[must_use]
+#[derive(Clone)]
 pub struct NavigationRequest {
     /// The path we're navigating from (if any)
     pub from: Option<String>,
@@ -78,6 +133,15 @@ pub struct NavigationRequest {
 
     /// Route parameters extracted from the path
     pub params: RouteParams,
+
+    /// Accumulated pattern of the resolved leaf route, once resolution ran
+    matched_pattern: Option<String>,
+
+    /// Answer to a pending guard challenge, visible to the challenging guard
+    challenge_response: Option<String>,
+
+    /// Scratch data left by guards and middleware for the destination
+    extensions: Extensions,
 }
 
 impl NavigationRequest {
@@ -87,6 +151,9 @@ impl NavigationRequest {
             from: None,
             to,
             params: RouteParams::new(),
+            matched_pattern: None,
+            challenge_response: None,
+            extensions: Extensions::default(),
         }
     }
 
@@ -96,6 +163,9 @@ impl NavigationRequest {
             from: Some(from),
             to,
             params: RouteParams::new(),
+            matched_pattern: None,
+            challenge_response: None,
+            extensions: Extensions::default(),
         }
     }
 
@@ -104,6 +174,38 @@ impl NavigationRequest {
         self.params = params;
         self
     }
+
+    /// Return the accumulated pattern of the matched leaf route
+    /// (e.g. `/users/:id`).
+    ///
+    /// Only available after the match stack has been resolved, i.e. in
+    /// `on_enter` lifecycle hooks and `after_navigation` middleware. Returns
+    /// `None` earlier in the pipeline or when nothing matched.
+    #[must_use]
+    pub fn matched_pattern(&self) -> Option<&str> {
+        self.matched_pattern.as_deref()
+    }
+
+    /// Return the app's answer to the challenge this guard issued.
+    ///
+    /// Set only while re-invoking the guard that returned
+    /// [`NavigationAction::Challenge`], after the app called
+    /// [`Navigator::answer_challenge`]. `None` on the first check.
+    #[must_use]
+    pub fn challenge_response(&self) -> Option<&str> {
+        self.challenge_response.as_deref()
+    }
+
+    /// Return the scratch data of this navigation.
+    ///
+    /// Guards and middleware insert values here; when the navigation
+    /// commits, they move onto the leaf
+    /// [`MatchEntry`](crate::MatchEntry::navigation_extensions). See
+    /// [`crate::extensions`].
+    #[must_use]
+    pub const fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
 }
 
 impl std::fmt::Debug for NavigationRequest {
@@ -112,10 +214,272 @@ impl std::fmt::Debug for NavigationRequest {
             .field("from", &self.from)
             .field("to", &self.to)
             .field("params", &self.params)
+            .field("matched_pattern", &self.matched_pattern)
+            .field("challenge_response", &self.challenge_response)
+            .field("extensions", &self.extensions)
             .finish_non_exhaustive()
     }
 }
 
+// ============================================================================
+// NavigationProbe
+// ============================================================================
+
+/// Outcome of a dry-run guard evaluation, see [`GlobalRouter::can_navigate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavigationProbe {
+    /// Whether navigating would land on the requested path
+    pub allowed: bool,
+    /// Why the navigation would be denied or redirected, if a guard said so
+    pub reason: Option<String>,
+    /// Path a guard redirect would land on instead
+    pub redirect: Option<String>,
+    /// Challenge a guard would issue first; the navigation is still allowed
+    pub challenge: Option<String>,
+}
+
+impl NavigationProbe {
+    const fn allowed() -> Self {
+        Self {
+            allowed: true,
+            reason: None,
+            redirect: None,
+            challenge: None,
+        }
+    }
+
+    const fn blocked(reason: String) -> Self {
+        Self {
+            allowed: false,
+            reason: Some(reason),
+            redirect: None,
+            challenge: None,
+        }
+    }
+}
+
+// ============================================================================
+// BatchOp
+// ============================================================================
+
+/// One step of a [`GlobalRouter::navigate_batch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOp {
+    /// Add a history entry for the path
+    Push(String),
+    /// Replace the current history entry with the path
+    Replace(String),
+}
+
+impl BatchOp {
+    /// The path the op navigates to.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Push(path) | Self::Replace(path) => path,
+        }
+    }
+
+    const fn op(&self) -> NavigateOp {
+        match self {
+            Self::Push(_) => NavigateOp::Push,
+            Self::Replace(_) => NavigateOp::Replace,
+        }
+    }
+}
+
+/// The error for batch op `index` to `path` that ended with `result`.
+fn batch_failure(index: usize, path: &str, result: NavigationResult) -> BatchNavigationError {
+    let error = match result.into_result() {
+        Err(error) => error,
+        Ok(path) => NavigationError::NavigationFailed {
+            message: format!("Batch op ended on '{path}'"),
+        },
+    };
+    BatchNavigationError {
+        index,
+        path: path.to_string(),
+        error,
+    }
+}
+
+// ============================================================================
+// CanonicalTarget
+// ============================================================================
+
+/// Location to build a canonical URL for with [`GlobalRouter::canonical_url`].
+#[derive(Debug, Clone, Copy)]
+pub enum CanonicalTarget<'a> {
+    /// The current history entry
+    Current,
+    /// A path, as it would be passed to `push`
+    Path(&'a str),
+    /// A named route filled with `params`
+    Named {
+        /// The route name
+        name: &'a str,
+        /// Values for the params its pattern declares
+        params: &'a RouteParams,
+    },
+}
+
+/// Normalize `path` and percent-encode each segment, decoding it first so
+/// already-encoded segments are not encoded twice.
+fn canonical_path(path: &str) -> String {
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| encode_path_segment(&decode_path_segment(segment)))
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
+// ============================================================================
+// RouteInfo
+// ============================================================================
+
+/// A registered route, as reported to
+/// [`GlobalRouter::set_on_route_registered`] hooks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteInfo {
+    /// Full path from the root (e.g. `/dashboard/:id/edit`)
+    pub full_path: String,
+    /// Route name, if set
+    pub name: Option<String>,
+    /// Nesting depth (top-level routes are 0)
+    pub depth: usize,
+    /// Named outlet the route renders in, for named-outlet children
+    pub outlet: Option<String>,
+}
+
+/// Hook invoked for each route registered through [`GlobalRouter::add_route`].
+pub type RouteRegisteredFn = Arc<dyn Fn(&RouteInfo) + Send + Sync>;
+
+/// Report `route` and its descendants to `hook`, parents first.
+fn notify_registered(
+    route: &Route,
+    parent_path: &str,
+    depth: usize,
+    outlet: Option<&str>,
+    hook: &dyn Fn(&RouteInfo),
+) {
+    let full_path = build_child_path(parent_path, &route.config.path).into_owned();
+    hook(&RouteInfo {
+        full_path: full_path.clone(),
+        name: route.config.name.clone(),
+        depth,
+        outlet: outlet.map(str::to_string),
+    });
+    for child in &route.children {
+        notify_registered(child, &full_path, depth + 1, None, hook);
+    }
+    let mut outlets: Vec<_> = route.named_children.iter().collect();
+    outlets.sort_by_key(|(name, _)| name.as_str());
+    for (name, children) in outlets {
+        for child in children {
+            notify_registered(child, &full_path, depth + 1, Some(name), hook);
+        }
+    }
+}
+
+// ============================================================================
+// PendingChallenge
+// ============================================================================
+
+/// A navigation suspended by a guard returning [`NavigationAction::Challenge`].
+///
+/// The app reads it via [`GlobalRouter::pending_challenge`] to render its own
+/// challenge UI, then resumes navigation with
+/// [`Navigator::answer_challenge`] or drops it with
+/// [`Navigator::cancel_challenge`]. At most one challenge is pending; any new
+/// navigation supersedes it.
+#[cfg(feature = "guard")]
+#[derive(Debug, Clone)]
+pub struct PendingChallenge {
+    path: String,
+    challenge_id: String,
+    payload: String,
+    step: GuardStep,
+    /// Scratch data the guards before the challenge left
+    extensions: Extensions,
+}
+
+#[cfg(feature = "guard")]
+impl PendingChallenge {
+    /// The path the suspended navigation targets.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Identifier of the challenge kind, as returned by the guard.
+    #[must_use]
+    pub fn challenge_id(&self) -> &str {
+        &self.challenge_id
+    }
+
+    /// Data the guard attached for rendering the challenge UI.
+    #[must_use]
+    pub fn payload(&self) -> &str {
+        &self.payload
+    }
+
+    /// How many times the guard has challenged this navigation (starting at 1).
+    #[must_use]
+    pub const fn round(&self) -> usize {
+        self.step.round
+    }
+}
+
+/// A guard collected for a navigation, with the keys that order it.
+#[cfg(feature = "guard")]
+struct CollectedGuard<'a> {
+    guard: &'a dyn crate::guards::RouteGuard,
+    priority: i32,
+    depth: usize,
+    index: usize,
+    /// Accumulated route path, without the leading slash.
+    route: String,
+}
+
+/// Where the router is in running a top-level navigation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum NavigationPhase {
+    /// Nothing ran since the `Navigator` last refreshed.
+    #[default]
+    Idle,
+    /// A navigation is running; `redirected` once it was redirected.
+    Running { redirected: bool },
+    /// A navigation, or a settle, ran every step; taken by the `Navigator`
+    /// refresh.
+    Finished,
+}
+
+/// Position in the guard pipeline a challenge resumes from.
+#[cfg(feature = "guard")]
+#[derive(Debug, Clone, Copy)]
+struct GuardStep {
+    guard_index: usize,
+    op: NavigateOp,
+    redirect_depth: usize,
+    round: usize,
+}
+
+/// Open batch level of a router, closed when dropped — even by a panic in
+/// the batch closure, so a caught panic does not leave routes deferred.
+struct BatchScope<'a>(&'a mut GlobalRouter);
+
+impl Drop for BatchScope<'_> {
+    fn drop(&mut self) {
+        self.0.batch_depth -= 1;
+        // Re-resolving while unwinding could panic again and abort; the
+        // pending flag makes the next table change flush instead.
+        if self.0.batch_depth == 0 && !std::thread::panicking() {
+            self.0.flush_batch();
+        }
+    }
+}
+
 // ============================================================================
 // GlobalRouter
 // ============================================================================
@@ -127,30 +491,148 @@ impl std::fmt::Debug for NavigationRequest {
 /// (guards -> middleware -> navigation -> middleware).
 #[derive(Clone)]
 pub struct GlobalRouter {
+    /// History, routes and the match stack resolved for the current path
     state: RouterState,
-    /// Pre-resolved route chain for the current path.
-    /// Built once per navigation, consumed by outlets during render.
-    match_stack: MatchStack,
     /// Previous match stack — used for transition exit animations.
     #[cfg(feature = "transition")]
     previous_stack: Option<MatchStack>,
+    /// When the transitions started with `previous_stack` end.
+    #[cfg(feature = "transition")]
+    previous_stack_expires: Option<Instant>,
+    /// Bumped whenever `previous_stack` changes, so a stale expiry timer
+    /// leaves a newer stack alone.
+    #[cfg(feature = "transition")]
+    previous_stack_token: u64,
     #[cfg(feature = "cache")]
     nested_cache: RouteCache,
     named_routes: NamedRouteRegistry,
     #[cfg(feature = "transition")]
     next_transition: Option<Transition>,
-    /// Cache for component entities created by `Route::component()`.
-    /// Unlike `window.use_keyed_state()` which is frame-scoped, this cache
-    /// persists across navigations so that component state survives when the
-    /// user navigates away and back.
-    ///
-    /// Capped at `MAX_COMPONENT_CACHE` entries. Oldest entries are evicted
-    /// when the limit is reached (FIFO order via `VecDeque` of keys).
-    component_cache: HashMap<String, AnyView>,
-    /// Insertion-order tracking for FIFO eviction of `component_cache`.
-    component_cache_order: std::collections::VecDeque<String>,
+    /// Request of the last committed navigation, consulted by outlets to pick
+    /// per-navigation transitions.
+    #[cfg(feature = "transition")]
+    last_navigation: Option<NavigationRequest>,
+    /// Kind of the last committed navigation.
+    #[cfg(feature = "transition")]
+    navigation_source: NavigationSource,
+    /// Which navigation sources animate.
+    #[cfg(feature = "transition")]
+    transition_sources: TransitionSources,
+    /// Keep rendering previous content in every outlet until the new route builds.
+    #[cfg(feature = "transition")]
+    stale_while_navigate: bool,
+    /// How long an outlet may hold stale content before swapping to loading.
+    #[cfg(feature = "transition")]
+    stale_timeout: Duration,
+    /// Hero bounds captured before navigations and the flights they start.
+    #[cfg(feature = "transition")]
+    heroes: HeroRegistry,
+    /// Hooks told about every committed navigation before it renders.
+    #[cfg(feature = "transition")]
+    transition_hooks: TransitionHooks,
+    /// Latest committed navigation not yet reported to `transition_hooks`.
+    #[cfg(feature = "transition")]
+    pending_transition: Option<TransitionEvent>,
+    /// Cache for component entities created by `Route::component()`, kept
+    /// across navigations so that component state survives.
+    component_cache: ComponentCache,
+    /// Capacities of `nested_cache` and every partition's `component_cache`.
+    cache_policy: CachePolicy,
+    /// Component cache counters; the route resolution counters live in
+    /// `nested_cache`.
+    component_stats: CacheStats,
     /// Custom error handlers for 404 and navigation errors.
     error_handlers: ErrorHandlers,
+    /// Whether unmatched paths render the 404 page or redirect.
+    not_found_behavior: NotFoundBehavior,
+    /// Whether navigations compute a scroll intent.
+    scroll_restoration: ScrollRestoration,
+    /// Scroll intent of the latest navigation.
+    scroll_intent: Option<ScrollIntent>,
+    /// Locales recognised as an optional leading path segment (`/fr/...`).
+    locales: Vec<String>,
+    /// Locale reported for paths that carry no locale prefix.
+    default_locale: Option<String>,
+    /// Scheme and host prefixed to canonical URLs (`myapp://app`).
+    url_base: Option<String>,
+    /// Last prefetch attempt per path, used to debounce hover prefetching.
+    prefetch_attempts: HashMap<String, Instant>,
+    /// Paths queued by `warm_from_history` and the progress made.
+    warming: Warming,
+    /// Navigation suspended by a guard challenge, awaiting an answer.
+    #[cfg(feature = "guard")]
+    pending_challenge: Option<PendingChallenge>,
+    /// Hook told about every route passed to `add_route`.
+    on_route_registered: Option<RouteRegisteredFn>,
+    /// Hook told when a route table change takes routes off the current path.
+    on_route_invalidated: Option<RouteInvalidatedFn>,
+    /// Where to go when that happens.
+    invalidation_policy: InvalidationPolicy,
+    /// Latest invalidation of the current path, until the next navigation.
+    route_invalidated: Option<CurrentRouteInvalidated>,
+    /// Route table change not yet re-resolved, checked for invalidation.
+    pending_invalidation: Option<InvalidationReason>,
+    /// Top-level routes switched off with `set_route_enabled`.
+    disabled_routes: Vec<Pattern>,
+    /// Invalidations whose follow-up awaits an app context, see `settle`.
+    unsettled: Vec<Unsettled>,
+    /// Stage of the navigation in flight, and the hook watching it.
+    progress: ProgressTracker,
+    /// Whether a navigation is in flight, or finished awaiting the refresh.
+    phase: NavigationPhase,
+    /// Latest noteworthy navigation outcome, once enabled.
+    notifications: Notifications,
+    /// Slots handed out by `use_route_state`, kept across navigations.
+    route_state: RouteStateStore,
+    /// Typed preferences keyed by route pattern, shared by all partitions.
+    route_prefs: RoutePrefs,
+    /// Named outlets reserved by `reserve_outlet_names`.
+    outlet_policy: OutletPolicy,
+    /// Refetch callbacks of the routes in the match stack.
+    route_tasks: RouteTasks,
+    /// Idle timeouts and auto-advances of the routes in the match stack.
+    route_timers: RouteTimers,
+    /// Link hovered for a preview, see `RouterLink::preview_on_hover`.
+    hover_preview: HoverPreview,
+    /// Values produced by the loaders of the routes in the match stack.
+    loader_data: LoaderData,
+    /// Param types registered with `register_param_type`.
+    param_types: ParamTypes,
+    /// How middleware of nested routes are ordered.
+    #[cfg(feature = "middleware")]
+    middleware_ordering: crate::middleware::MiddlewareOrdering,
+    /// Middleware groups switched off with `set_middleware_group_enabled`.
+    #[cfg(feature = "middleware")]
+    disabled_middleware_groups: std::collections::HashSet<String>,
+    /// Middleware to notify once the navigation in progress settles.
+    #[cfg(feature = "middleware")]
+    settlement: Settlement,
+    /// Latest push queued by `Navigator::defer_push`, run once the current
+    /// update completes.
+    deferred_push: Option<String>,
+    /// Nesting depth of `batch` calls; routes added while non-zero defer
+    /// cache invalidation and re-resolution.
+    batch_depth: usize,
+    /// Whether routes were added since the current batch last flushed.
+    batch_pending: bool,
+    /// Route on screen, mirrored for background threads.
+    route_snapshot: RouteSnapshotHandle,
+    /// Navigations committed so far, restores and partition switches included.
+    navigation_sequence: u64,
+    /// Key of the partition whose navigation state is live.
+    active_partition: String,
+    /// Navigation state of the windows opened by `push_new_window`, less
+    /// the one live.
+    window_states: HashMap<WindowId, WindowNavigation>,
+    /// Window whose own navigation state is live, if any.
+    live_window: Option<WindowId>,
+    /// The shared navigation state while a window's own is live.
+    parked_shared: Option<WindowNavigation>,
+    /// Navigation state of the partitions not currently active.
+    partitions: HashMap<String, Partition>,
+    /// Number of match stacks resolved by `re_resolve` and navigations, for tests.
+    #[cfg(test)]
+    resolutions: usize,
 }
 
 impl GlobalRouter {
@@ -166,19 +648,319 @@ impl GlobalRouter {
     /// The stack is built once per navigation, so this is O(1).
     #[must_use]
     pub const fn match_stack(&self) -> &MatchStack {
-        &self.match_stack
+        self.state.match_stack()
+    }
+
+    /// Install `stack` as the match stack without navigating.
+    ///
+    /// Test-only: available in this crate's tests and with the `test-util`
+    /// feature, so outlet rendering can be tested against a stack built with
+    /// [`resolve_match_stack`]. History, loaders and the
+    /// [`snapshot_handle`](Self::snapshot_handle) are left alone, so
+    /// [`current_path`](Self::current_path) keeps reporting the last
+    /// navigation. The next navigation or route change replaces the stack.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn set_match_stack_for_test(&mut self, stack: MatchStack) {
+        self.state.set_match_stack(stack);
+    }
+
+    /// Return the deepest entry of the match stack — the route on screen.
+    ///
+    /// [`current_route`](Self::current_route) and
+    /// [`current_match_immutable`](Self::current_match_immutable) are derived
+    /// from this entry.
+    #[must_use]
+    pub fn leaf(&self) -> Option<&MatchEntry> {
+        self.state.leaf()
+    }
+
+    /// Return the accumulated params of the match stack's leaf.
+    #[must_use]
+    pub fn leaf_params(&self) -> Option<&RouteParams> {
+        self.leaf().map(|leaf| &leaf.params)
     }
 
-    /// Get the previous match stack (for transition animations).
+    /// Get the match stack as it was right before the latest navigation.
+    ///
+    /// Every navigation that changes history overwrites it, including each
+    /// hop of a post-navigation redirect, so after two navigations within
+    /// one animation it holds the middle stack, not the one on screen when
+    /// the animation began. Outlets therefore do not read it for exit
+    /// content; each outlet keeps the content it last rendered instead.
+    ///
+    /// It lives only as long as the longest transition that navigation
+    /// started at a changed depth: it is never kept when every such depth
+    /// has [`Transition::None`], and is dropped once the transitions have
+    /// ended, releasing the old routes and params.
     #[cfg(feature = "transition")]
     #[must_use]
     pub const fn previous_stack(&self) -> Option<&MatchStack> {
         self.previous_stack.as_ref()
     }
 
+    /// Return when the transitions of the latest navigation end, while
+    /// [`previous_stack`](Self::previous_stack) is kept for them.
+    #[cfg(feature = "transition")]
+    #[must_use]
+    pub const fn previous_stack_expires(&self) -> Option<Instant> {
+        self.previous_stack_expires
+    }
+
+    /// Drop the [`previous_stack`](Self::previous_stack) now.
+    #[cfg(feature = "transition")]
+    pub fn clear_previous_stack(&mut self) {
+        self.previous_stack = None;
+        self.previous_stack_expires = None;
+        self.previous_stack_token += 1;
+    }
+
+    /// Longest transition the outlets at the depths that changed between
+    /// `previous` and the current match stack will run.
+    #[cfg(feature = "transition")]
+    fn exit_window(
+        &self,
+        previous: &MatchStack,
+        request: Option<&NavigationRequest>,
+        source: NavigationSource,
+    ) -> Duration {
+        self.state
+            .match_stack()
+            .entries()
+            .iter()
+            .filter(|entry| {
+                !previous.at_depth(entry.depth).is_some_and(|old| {
+                    Arc::ptr_eq(&old.route, &entry.route)
+                        && old.accumulated_url() == entry.accumulated_url()
+                })
+            })
+            .map(|entry| self.select_transition(entry, request, source).duration())
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Transition the outlet rendering `entry` runs for a navigation from `source`.
+    #[cfg(feature = "transition")]
+    fn select_transition(
+        &self,
+        entry: &MatchEntry,
+        request: Option<&NavigationRequest>,
+        source: NavigationSource,
+    ) -> Transition {
+        let configured = || {
+            request.map_or_else(
+                || entry.route.transition.active().clone(),
+                |request| entry.route.transition_for_request(request),
+            )
+        };
+        self.transition_sources
+            .select(source, self.next_transition.clone(), configured)
+    }
+
+    /// Describe the navigation from `from` that just replaced `previous`
+    /// with the current match stack, for the transition hooks.
+    #[cfg(feature = "transition")]
+    fn transition_event(
+        &self,
+        from: &str,
+        previous: &MatchStack,
+        request: &NavigationRequest,
+        source: NavigationSource,
+        duration: Duration,
+    ) -> TransitionEvent {
+        let current = self.state.match_stack();
+        let diverging_depth = (0..=previous.len().max(current.len()))
+            .find(
+                |&depth| match (previous.at_depth(depth), current.at_depth(depth)) {
+                    (Some(old), Some(new)) => {
+                        !Arc::ptr_eq(&old.route, &new.route)
+                            || old.accumulated_url() != new.accumulated_url()
+                    }
+                    _ => true,
+                },
+            )
+            .unwrap_or_default();
+        let transition = current
+            .at_depth(diverging_depth)
+            .map_or(Transition::None, |entry| {
+                self.select_transition(entry, Some(request), source)
+            });
+        TransitionEvent {
+            from_path: from.to_string(),
+            to_path: self.current_path().to_string(),
+            from_pattern: previous.leaf_pattern().map(str::to_string),
+            to_pattern: current.leaf_pattern().map(str::to_string),
+            diverging_depth,
+            transition,
+            duration,
+            source,
+        }
+    }
+
+    /// Keep `previous` as the [`previous_stack`](Self::previous_stack) for
+    /// `window`, or drop it right away if nothing animates.
+    #[cfg(feature = "transition")]
+    fn keep_previous_stack(&mut self, previous: MatchStack, window: Duration, cx: &App) {
+        self.clear_previous_stack();
+        if window.is_zero() {
+            return;
+        }
+        let token = self.previous_stack_token;
+        self.previous_stack = Some(previous);
+        self.previous_stack_expires = Some(cx.background_executor().now() + window);
+        cx.spawn(async move |cx| {
+            cx.background_executor().timer(window).await;
+            let _ = cx.update(|cx| {
+                cx.update_global::<Self, _>(|router, _| {
+                    if router.previous_stack_token == token {
+                        trace_log!("Transitions ended, dropping previous match stack");
+                        router.clear_previous_stack();
+                    }
+                });
+            });
+        })
+        .detach();
+    }
+
     /// Re-resolve the match stack after routes change.
     fn re_resolve(&mut self) {
-        self.match_stack = resolve_match_stack(self.state.routes(), self.state.current_path());
+        #[cfg(test)]
+        {
+            self.resolutions += 1;
+        }
+        let stack = self.resolve_current();
+        self.state.set_match_stack(stack);
+        self.publish_snapshot();
+    }
+
+    /// Mirror the current route into the [`snapshot_handle`](Self::snapshot_handle).
+    fn publish_snapshot(&self) {
+        let stack = self.state.match_stack();
+        self.route_snapshot.store(RouteSnapshot {
+            path: self.current_path().to_string(),
+            pattern: stack.leaf_pattern().map(str::to_string),
+            params: self.leaf_params().cloned().unwrap_or_default(),
+            sequence: self.navigation_sequence,
+            can_go_back: self.state.can_go_back(),
+            can_go_forward: self.state.can_go_forward(),
+        });
+    }
+
+    /// Resolve the match stack for the current path.
+    ///
+    /// A leading locale segment is stripped before matching and exposed to
+    /// every entry as the `locale` param.
+    fn resolve_current(&self) -> MatchStack {
+        self.resolve_path(self.state.current_path())
+    }
+
+    /// Resolve the match stack for an arbitrary `path` without navigating.
+    fn resolve_path(&self, path: &str) -> MatchStack {
+        let (locale, rest) = self.split_locale(path);
+        let mut stack = resolve_match_stack(&self.enabled_routes(), rest);
+        if let Some(locale) = locale.or(self.default_locale.as_deref()) {
+            stack.insert_param("locale", locale);
+        }
+        stack
+    }
+
+    /// Registered top-level routes, less those switched off with
+    /// [`set_route_enabled`](Self::set_route_enabled).
+    fn enabled_routes(&self) -> Cow<'_, [Arc<Route>]> {
+        if self.disabled_routes.is_empty() {
+            return Cow::Borrowed(self.state.routes());
+        }
+        Cow::Owned(
+            self.state
+                .routes()
+                .iter()
+                .filter(|route| self.is_enabled(route))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Whether the top-level `route` is not switched off.
+    fn is_enabled(&self, route: &Route) -> bool {
+        !self.disabled_routes.contains(route.config.pattern())
+    }
+
+    /// Call `hook` for every route registered from now on.
+    ///
+    /// Each [`add_route`](Self::add_route) reports the route and all of its
+    /// descendants depth-first, parents before children, regular children
+    /// before named-outlet children (outlets in name order). Routes added
+    /// before the hook was set are not replayed, so set it first:
+    ///
+    /// ```
+    /// use gpui::IntoElement;
+    /// use gpui_navigator::{GlobalRouter, Route};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let palette = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = Arc::clone(&palette);
+    ///
+    /// let mut router = GlobalRouter::new();
+    /// router.set_on_route_registered(move |info| {
+    ///     sink.lock().unwrap().push(info.full_path.clone());
+    /// });
+    /// router.add_route(Route::view("/settings", || gpui::div().into_any_element()));
+    ///
+    /// assert_eq!(*palette.lock().unwrap(), ["/settings"]);
+    /// ```
+    pub fn set_on_route_registered(&mut self, hook: impl Fn(&RouteInfo) + Send + Sync + 'static) {
+        self.on_route_registered = Some(Arc::new(hook));
+    }
+
+    /// Return the stage of the navigation in flight, if any.
+    ///
+    /// Cleared once the navigation completes, is blocked or challenged, or
+    /// is superseded. See [`progress`](crate::progress).
+    #[must_use]
+    pub const fn navigation_progress(&self) -> Option<&NavigationProgress> {
+        self.progress.current()
+    }
+
+    /// Call `hook` on every navigation progress change.
+    ///
+    /// The hook receives the new progress, or `None` when the slot clears,
+    /// including the stages a synchronous pipeline passes through within
+    /// one update.
+    pub fn set_on_navigation_progress(
+        &mut self,
+        hook: impl Fn(Option<&NavigationProgress>) + Send + Sync + 'static,
+    ) {
+        self.progress.set_hook(Arc::new(hook));
+    }
+
+    /// Keep the latest blocked, not-found or redirected navigation as a
+    /// [`RouterNotification`], as `config` selects.
+    ///
+    /// Read it with [`current_notification`](Self::current_notification), or
+    /// render it with [`router_notification_banner`](crate::router_notification_banner).
+    /// See [`notifications`](crate::notifications).
+    pub fn enable_outcome_notifications(&mut self, config: NotificationConfig) {
+        self.notifications.enable(config);
+    }
+
+    /// Stop recording navigation outcomes and drop the current one.
+    pub fn disable_outcome_notifications(&mut self) {
+        self.notifications.disable();
+    }
+
+    /// Return the latest noteworthy navigation outcome, until it is
+    /// dismissed, auto-dismissed or a navigation succeeds.
+    #[must_use]
+    pub fn current_notification(&self) -> Option<&RouterNotification> {
+        self.notifications.current(Instant::now())
+    }
+
+    /// Drop the current notification; returns whether there was one.
+    pub fn dismiss_notification(&mut self) -> bool {
+        self.notifications.dismiss()
+    }
+
+    pub(crate) fn notifications_mut(&mut self) -> &mut Notifications {
+        &mut self.notifications
     }
 
     /// Register a route and re-resolve the match stack.
@@ -186,8 +968,36 @@ impl GlobalRouter {
     /// If the route has a [`name`](crate::route::RouteConfig::name), it is
     /// also registered in the [`NamedRouteRegistry`] for URL generation via
     /// [`url_for`](Self::url_for).
+    ///
+    /// The [`set_on_route_registered`](Self::set_on_route_registered) hook,
+    /// if any, is told about the route and each of its descendants.
+    ///
+    /// Inside a [`batch`](Self::batch) the re-resolution is deferred to the
+    /// end of the batch.
+    ///
+    /// The route's tree is checked with [`check_route_tree`] first: a route
+    /// that is its own descendant is skipped, and routes nested too deep to
+    /// ever match are logged.
+    ///
+    /// A route whose pattern is identical to that of a registered top-level
+    /// route (`/users` and `users/` are) replaces it, children, guards and
+    /// all, so setup code may safely run twice. The replaced route's name is
+    /// unregistered if the new one differs, and the components its tree
+    /// cached are evicted. Use [`add_route_strict`](Self::add_route_strict)
+    /// to be told instead.
     pub fn add_route(&mut self, route: Route) {
-        if let Some(name) = &route.config.name {
+        if let Err(error) = check_route_tree(&route) {
+            if matches!(error, RouteTreeError::Cycle { .. }) {
+                error_log!("{}; route skipped", error);
+                return;
+            }
+            warn_log!("{}", error);
+        }
+        if let Some(hook) = &self.on_route_registered {
+            notify_registered(&route, "/", 0, None, hook.as_ref());
+        }
+        let name = route.config.name.clone();
+        if let Some(name) = &name {
             info_log!(
                 "Registered route '{}' (name: '{}')",
                 route.config.path,
@@ -198,1492 +1008,7259 @@ impl GlobalRouter {
         } else {
             info_log!("Registered route '{}'", route.config.path);
         }
-        self.state.add_route(route);
-        #[cfg(feature = "cache")]
-        self.nested_cache.clear();
-        // Re-resolve match stack after adding routes
-        self.re_resolve();
+        if let Some(old) = self.state.replace_route(route) {
+            info_log!("Replaced the earlier route '{}'", old.config.path);
+            if let Some(old_name) = &old.config.name {
+                if name.as_ref() != Some(old_name) {
+                    self.named_routes.remove(old_name);
+                }
+            }
+            self.evict_route_components(&old);
+            self.pending_invalidation
+                .get_or_insert(InvalidationReason::RouteReplaced);
+        }
+        self.table_changed();
     }
 
-    // ========================================================================
-    // Navigation pipeline
-    // ========================================================================
-
-    /// Navigate to a path, running the full guard/middleware pipeline.
+    /// Unregister the top-level route with the identical pattern of `path`,
+    /// children and all, and re-resolve.
     ///
-    /// Pipeline:
-    /// 1. Collect guards from matched route (+ ancestors)
-    /// 2. Check guards — if any denies/redirects, navigation is blocked
-    /// 3. Run `before_navigation` middleware
-    /// 4. Perform actual navigation
-    /// 5. Run `after_navigation` middleware
-    pub fn push(&mut self, path: String, cx: &App) -> NavigationResult {
-        self.navigate_with_pipeline(path, cx, NavigateOp::Push, 0)
+    /// Its name is unregistered and the components its tree cached are
+    /// evicted. If the current path resolved through it, the router reports
+    /// it; see [`invalidation`](crate::invalidation). Returns `false` if no
+    /// such route is registered.
+    pub fn remove_route(&mut self, path: &str) -> bool {
+        let Some(old) = self.state.remove_route(path) else {
+            return false;
+        };
+        info_log!("Removed route '{}'", old.config.path);
+        if let Some(name) = &old.config.name {
+            self.named_routes.remove(name);
+        }
+        self.disabled_routes
+            .retain(|pattern| pattern != old.config.pattern());
+        self.evict_route_components(&old);
+        self.pending_invalidation
+            .get_or_insert(InvalidationReason::RouteRemoved);
+        self.table_changed();
+        true
     }
 
-    /// Replace current path, running the full guard/middleware pipeline.
-    pub fn replace(&mut self, path: String, cx: &App) -> NavigationResult {
-        self.navigate_with_pipeline(path, cx, NavigateOp::Replace, 0)
+    /// Switch the top-level route with the identical pattern of `path` on
+    /// or off, children and all, and re-resolve.
+    ///
+    /// A disabled route stays registered, named and cached, but paths no
+    /// longer resolve through it and its guards do not run, as if it were
+    /// removed, until it is enabled again. If the current path resolved
+    /// through it, the router reports it; see
+    /// [`invalidation`](crate::invalidation). Returns `false` if no such
+    /// route is registered.
+    pub fn set_route_enabled(&mut self, path: &str, enabled: bool) -> bool {
+        let Some(slot) = Pattern::parse(path)
+            .ok()
+            .and_then(|pattern| self.state.registered(&pattern))
+        else {
+            return false;
+        };
+        let pattern = self.state.routes()[slot].config.pattern().clone();
+        let disabled = self.disabled_routes.iter().position(|p| *p == pattern);
+        match (enabled, disabled) {
+            (true, Some(index)) => {
+                self.disabled_routes.remove(index);
+            }
+            (false, None) => {
+                self.disabled_routes.push(pattern);
+                self.pending_invalidation
+                    .get_or_insert(InvalidationReason::Disabled);
+            }
+            _ => return true,
+        }
+        debug_log!(
+            "Route '{}' {}",
+            path,
+            if enabled { "enabled" } else { "disabled" }
+        );
+        self.table_changed();
+        true
     }
 
-    /// Go back in history, checking guards on the target route.
-    pub fn back(&mut self, cx: &App) -> Option<NavigationResult> {
-        let target = self.state.peek_back_path()?.to_string();
-        Some(self.navigate_with_pipeline(target, cx, NavigateOp::Back, 0))
+    /// Check whether the top-level route with the identical pattern of
+    /// `path` is registered and not switched off with
+    /// [`set_route_enabled`](Self::set_route_enabled).
+    #[must_use]
+    pub fn is_route_enabled(&self, path: &str) -> bool {
+        Pattern::parse(path)
+            .ok()
+            .and_then(|pattern| self.state.registered(&pattern))
+            .is_some_and(|slot| self.is_enabled(&self.state.routes()[slot]))
     }
 
-    /// Go forward in history, checking guards on the target route.
-    pub fn forward(&mut self, cx: &App) -> Option<NavigationResult> {
-        let target = self.state.peek_forward_path()?.to_string();
-        Some(self.navigate_with_pipeline(target, cx, NavigateOp::Forward, 0))
+    /// Re-resolve after a route table change, or at the end of the batch.
+    fn table_changed(&mut self) {
+        if self.batch_depth > 0 {
+            self.batch_pending = true;
+        } else {
+            self.routes_changed();
+        }
     }
 
-    /// Push a new path with associated [`HistoryState`] data, running the full pipeline.
+    /// Register a route unless one with the identical pattern is registered.
     ///
-    /// Allows attaching arbitrary key-value state (scroll position, form data, etc.)
-    /// to the history entry. The pipeline (guards, middleware) runs first; state
-    /// is only attached if navigation succeeds.
-    pub fn push_with_state(
-        &mut self,
-        path: String,
-        state: HistoryState,
-        cx: &App,
-    ) -> NavigationResult {
-        // Run the pipeline first (guards, middleware, etc.)
-        // We use the normal push pipeline, then retroactively attach state
-        let result = self.navigate_with_pipeline(path, cx, NavigateOp::Push, 0);
-        if matches!(result, NavigationResult::Success { .. }) {
-            // Attach state to the current history entry
-            let current_path = self.state.current_path().to_string();
-            self.state.replace_with_state(current_path, state);
+    /// Like [`add_route`](Self::add_route), for setup code that should never
+    /// register a route twice.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AlreadyRegisteredError`] and leaves the registered route in
+    /// place if its pattern is identical to `route`'s.
+    pub fn add_route_strict(&mut self, route: Route) -> Result<(), AlreadyRegisteredError> {
+        if let Some(slot) = self.state.registered(route.config.pattern()) {
+            let error = AlreadyRegisteredError {
+                path: self.state.routes()[slot].config.path.clone(),
+            };
+            warn_log!("{}; route '{}' skipped", error, route.config.path);
+            return Err(error);
         }
-        result
+        self.add_route(route);
+        Ok(())
     }
 
-    /// Replace current path with associated [`HistoryState`] data, running the full pipeline.
-    pub fn replace_with_state(
-        &mut self,
-        path: String,
-        state: HistoryState,
-        cx: &App,
-    ) -> NavigationResult {
-        let result = self.navigate_with_pipeline(path, cx, NavigateOp::Replace, 0);
-        if matches!(result, NavigationResult::Success { .. }) {
-            let current_path = self.state.current_path().to_string();
-            self.state.replace_with_state(current_path, state);
+    /// Drop the cached components of `route` and its descendants, in every
+    /// partition.
+    ///
+    /// Component cache entries record the path of the route that built
+    /// them, so a route elsewhere with the same relative path loses its
+    /// component too and rebuilds it on its next render.
+    fn evict_route_components(&mut self, route: &Route) {
+        fn component_paths<'a>(route: &'a Route, out: &mut Vec<&'a str>) {
+            if route.component_key.is_some() {
+                out.push(&route.config.path);
+            }
+            let named = route.named_children.values().flatten();
+            for child in route.children.iter().chain(named) {
+                component_paths(child, out);
+            }
         }
-        result
+
+        let mut paths = Vec::new();
+        component_paths(route, &mut paths);
+        if paths.is_empty() {
+            return;
+        }
+        let mut evicted = self.component_cache.remove_route_patterns(&paths);
+        for partition in self.partitions.values_mut() {
+            evicted += partition.component_cache.remove_route_patterns(&paths);
+        }
+        self.component_stats.component_evictions += evicted;
+        debug_log!(
+            "Evicted {} component(s) of replaced route '{}'",
+            evicted,
+            route.config.path
+        );
     }
 
-    /// Return the current [`HistoryEntry`] (path + optional state data).
-    #[must_use]
-    pub fn current_entry(&self) -> &HistoryEntry {
-        self.state.current_entry()
+    /// Register several routes with a single re-resolution.
+    ///
+    /// Equivalent to calling [`add_route`](Self::add_route) for each route
+    /// inside one [`batch`](Self::batch).
+    pub fn add_routes(&mut self, routes: impl IntoIterator<Item = Route>) {
+        self.batch(|router| {
+            for route in routes {
+                router.add_route(route);
+            }
+        });
     }
 
-    /// Core navigation method that runs the full pipeline.
-    fn navigate_with_pipeline(
+    /// Reserve named outlets for `owner`.
+    ///
+    /// Routes registered with [`add_route_tagged`](Self::add_route_tagged)
+    /// under another tag may no longer declare these outlets, and outlets of
+    /// such routes registered earlier stop resolving. Routes registered with
+    /// [`add_route`](Self::add_route) belong to [`OwnerTag::APP`] and are not
+    /// restricted.
+    pub fn reserve_outlet_names<I, S>(&mut self, names: I, owner: &OwnerTag)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for name in names {
+            self.outlet_policy.reserve(name.into(), owner.clone());
+        }
+        #[cfg(feature = "cache")]
+        self.nested_cache.clear();
+    }
+
+    /// Register a route on behalf of `tag`, e.g. a plugin.
+    ///
+    /// Like [`add_route`](Self::add_route), but the route is skipped if it or
+    /// one of its descendants declares a named outlet reserved for another
+    /// owner by [`reserve_outlet_names`](Self::reserve_outlet_names).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReservedOutletError`] naming the first offending outlet.
+    pub fn add_route_tagged(
         &mut self,
-        path: String,
-        cx: &App,
-        op: NavigateOp,
-        redirect_depth: usize,
-    ) -> NavigationResult {
-        if redirect_depth >= MAX_REDIRECT_DEPTH {
-            error_log!(
-                "Redirect loop detected (depth {}) navigating to '{}'",
-                redirect_depth,
-                path
-            );
-            return NavigationResult::Blocked {
-                reason: format!("Redirect loop detected (depth {redirect_depth}): target '{path}'"),
-                redirect: None,
-            };
+        mut route: Route,
+        tag: OwnerTag,
+    ) -> Result<(), ReservedOutletError> {
+        if let Err(error) = self.outlet_policy.check(&route, &tag) {
+            warn_log!("{}; route skipped", error);
+            return Err(error);
         }
+        route.owner = Some(tag);
+        self.add_route(route);
+        Ok(())
+    }
 
-        let from = self.current_path().to_string();
-        info_log!("Navigation {:?}: '{}' → '{}'", op, from, path);
+    /// Resolve the named outlet `name` at `depth` of the current match stack.
+    ///
+    /// Like [`resolve_named_outlet`], but yields nothing when the routes on
+    /// screen were registered under a tag the outlet is not reserved for.
+    #[must_use]
+    pub fn resolve_named_outlet(
+        &self,
+        depth: usize,
+        name: &str,
+    ) -> Option<(Arc<Route>, RouteParams)> {
+        let stack = self.match_stack();
+        let owner = stack_owner(stack);
+        if !self.outlet_policy.permits(name, &owner) {
+            trace_log!(
+                "Named outlet '{}' is reserved, not filled by '{}'",
+                name,
+                owner
+            );
+            return None;
+        }
+        resolve_named_outlet(stack, depth, name, self.current_path())
+    }
 
-        // Build request — used by guards, lifecycle hooks, and middleware
-        let request = NavigationRequest::with_from(path.clone(), from.clone());
+    /// Run `f` with route registration batched.
+    ///
+    /// Routes added inside `f` are registered immediately, but the nested
+    /// cache is cleared and the match stack re-resolved only once, when the
+    /// outermost batch ends — so [`match_stack`](Self::match_stack) is stale
+    /// until then. A navigation started inside `f` flushes the batch first
+    /// and sees every route added so far. [`init_router`] runs its
+    /// configure closure as a batch.
+    ///
+    /// ```
+    /// use gpui::IntoElement;
+    /// use gpui_navigator::{GlobalRouter, Route};
+    ///
+    /// let mut router = GlobalRouter::new();
+    /// router.batch(|router| {
+    ///     for name in ["inbox", "sent", "drafts"] {
+    ///         router.add_route(Route::view(format!("/{name}"), || gpui::div().into_any_element()));
+    ///     }
+    /// });
+    /// assert_eq!(router.state().routes().len(), 3);
+    /// ```
+    pub fn batch<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.batch_depth += 1;
+        let scope = BatchScope(self);
+        f(&mut *scope.0)
+    }
 
-        // Step 1: Run guards
-        #[cfg(feature = "guard")]
-        {
-            let guard_result = self.run_guards(cx, &request);
-            match guard_result {
-                NavigationAction::Continue => {}
-                NavigationAction::Deny { reason } => {
-                    warn_log!("Navigation to '{}' blocked: {}", path, reason);
-                    return NavigationResult::Blocked {
-                        reason,
-                        redirect: None,
-                    };
-                }
-                NavigationAction::Redirect { to, reason } => {
-                    debug_log!(
-                        "Guard redirecting from '{}' to '{}': {:?}",
-                        path,
-                        to,
-                        reason
-                    );
-                    return self.navigate_with_pipeline(
-                        to,
-                        cx,
-                        NavigateOp::Push,
-                        redirect_depth + 1,
-                    );
-                }
-            }
+    /// Apply route additions deferred by the current batch, if any.
+    fn flush_batch(&mut self) {
+        if std::mem::take(&mut self.batch_pending) {
+            self.routes_changed();
         }
+    }
 
-        // Step 2: Check if current route allows deactivation (lifecycle)
-        match self.run_lifecycle_can_deactivate(cx) {
-            NavigationAction::Continue => {}
-            NavigationAction::Deny { reason } => {
-                warn_log!(
-                    "Lifecycle can_deactivate blocked leaving '{}': {}",
-                    from,
-                    reason
-                );
-                return NavigationResult::Blocked {
-                    reason,
-                    redirect: None,
-                };
-            }
-            NavigationAction::Redirect { to, .. } => {
-                return self.navigate_with_pipeline(to, cx, NavigateOp::Push, redirect_depth + 1);
-            }
+    /// Invalidate caches and re-resolve after the route table changed.
+    fn routes_changed(&mut self) {
+        // Whatever a batch deferred is covered by this re-resolve
+        self.batch_pending = false;
+        #[cfg(feature = "cache")]
+        self.nested_cache.clear();
+        let pending = self.pending_invalidation.take();
+        let previous = pending.map(|reason| (reason, self.state.match_stack().clone()));
+        self.re_resolve();
+        self.re_resolve_parked();
+        if let Some((reason, previous)) = previous {
+            self.check_invalidated(&previous, reason);
         }
+    }
 
-        // Step 3: Run before middleware
-        #[cfg(feature = "middleware")]
-        self.run_middleware_before(cx, &request);
-
-        // Step 4: Run on_exit lifecycle on current route
-        if let NavigationAction::Deny { reason } = self.run_lifecycle_on_exit(cx) {
-            warn_log!("Lifecycle on_exit blocked leaving '{}': {}", from, reason);
-            return NavigationResult::Blocked {
-                reason,
-                redirect: None,
-            };
+    /// Report routes of the current path that `reason` took away, and move
+    /// as the [`InvalidationPolicy`] says.
+    fn check_invalidated(&mut self, previous: &MatchStack, reason: InvalidationReason) {
+        let routes = self.enabled_routes();
+        let lost = gone_depth(previous, self.state.match_stack(), |url| {
+            resolve_match_stack(&routes, url)
+        });
+        drop(routes);
+        let Some(lost_at_depth) = lost else {
+            return;
+        };
+        let event = CurrentRouteInvalidated {
+            path: self.current_path().to_string(),
+            lost_at_depth,
+            reason,
+        };
+        warn_log!(
+            "Current route '{}' lost its routes from depth {} ({:?})",
+            event.path,
+            event.lost_at_depth,
+            event.reason
+        );
+        if let Some(hook) = &self.on_route_invalidated {
+            hook(&event);
         }
+        self.unsettled.push(Unsettled {
+            protected: Self::protected_scopes(previous),
+            #[cfg(feature = "middleware")]
+            previous: previous.clone(),
+            #[cfg(feature = "middleware")]
+            event: event.clone(),
+        });
+        self.route_invalidated = Some(event);
 
-        // Step 5: Perform actual navigation + resolve match stack
-        let event = match self.perform_navigation(path, op) {
-            Ok(event) => event,
-            Err(result) => return result,
+        let moved = match self.invalidation_policy.clone() {
+            InvalidationPolicy::Stay => false,
+            InvalidationPolicy::NavigateTo(path) => {
+                self.state.replace_unresolved(path);
+                true
+            }
+            InvalidationPolicy::Back => self.state.back_unresolved().is_ok(),
         };
+        if moved {
+            info_log!("Left the invalidated route for '{}'", self.current_path());
+            self.navigation_sequence += 1;
+            self.re_resolve();
+        }
+    }
 
-        // Step 6: Run on_enter lifecycle on new route
-        match self.run_lifecycle_on_enter(cx, &request) {
-            NavigationAction::Continue => {}
-            NavigationAction::Deny { reason } => {
-                // Navigation already happened — log warning but don't revert
-                warn_log!(
-                    "Lifecycle on_enter denied entry to '{}': {}",
-                    event.to,
-                    reason
+    /// Finish handling the invalidations reported since the last call, now
+    /// that an app context is at hand.
+    ///
+    /// Route table changes happen without one, so the follow-up waits:
+    /// unsaved scopes of the routes that left are cleared, refetch tasks,
+    /// timers and loaders follow the new match stack as after a navigation,
+    /// and the middleware of the routes the path resolved to before hear of
+    /// the invalidation. The [`Navigator`] methods and [`router_view`](crate::router_view)
+    /// call this; app code holding the router through
+    /// [`UseRouter::update_router`] has it called on return.
+    pub fn settle(&mut self, cx: &App) {
+        if self.unsettled.is_empty() {
+            return;
+        }
+        for unsettled in std::mem::take(&mut self.unsettled) {
+            self.clear_exited_scopes(unsettled.protected, cx);
+            #[cfg(feature = "middleware")]
+            for mw in self.ordered_middleware(&unsettled.previous, true) {
+                trace_log!(
+                    "Middleware '{}' on_route_invalidated for '{}'",
+                    mw.name(),
+                    unsettled.event.path
                 );
-            }
-            NavigationAction::Redirect { to, .. } => {
-                return self.navigate_with_pipeline(to, cx, NavigateOp::Push, redirect_depth + 1);
+                mw.on_route_invalidated(cx, &unsettled.event);
             }
         }
+        self.route_tasks.sync(self.state.match_stack(), cx);
+        self.route_timers.sync(self.state.match_stack(), cx);
+        self.loader_data =
+            LoaderData::load(self.state.match_stack(), &self.param_types, cx, |_, _| {});
+        self.record_load_errors();
+        self.phase = NavigationPhase::Finished;
+    }
 
-        // Step 7: Run after middleware
-        #[cfg(feature = "middleware")]
-        self.run_middleware_after(cx, &request);
+    /// Whether invalidations await [`settle`](Self::settle).
+    pub(crate) fn is_unsettled(&self) -> bool {
+        !self.unsettled.is_empty()
+    }
 
-        info_log!(
-            "Navigation complete: '{}' (stack depth: {})",
-            event.to,
-            self.match_stack.len()
-        );
-        NavigationResult::Success { path: event.to }
+    /// Call `hook` when a route table change takes routes off the current
+    /// path. See [`invalidation`](crate::invalidation).
+    pub fn set_on_route_invalidated(
+        &mut self,
+        hook: impl Fn(&CurrentRouteInvalidated) + Send + Sync + 'static,
+    ) {
+        self.on_route_invalidated = Some(Arc::new(hook));
+    }
+
+    /// Choose where to go when a route table change takes routes off the
+    /// current path. Defaults to [`InvalidationPolicy::Stay`].
+    pub fn set_invalidation_policy(&mut self, policy: InvalidationPolicy) {
+        self.invalidation_policy = policy;
+    }
+
+    /// Return the latest invalidation of the current path, cleared by the
+    /// next navigation.
+    #[must_use]
+    pub const fn current_route_invalidated(&self) -> Option<&CurrentRouteInvalidated> {
+        self.route_invalidated.as_ref()
     }
 
     // ========================================================================
-    // Navigation execution
+    // Navigation pipeline
     // ========================================================================
 
-    /// Perform the actual history mutation, cache clear, and match stack resolution.
+    /// Navigate to a path, running the full guard/middleware pipeline.
     ///
-    /// Returns `Ok(RouteChangeEvent)` on success, `Err(NavigationResult)` if the
-    /// history operation fails unexpectedly.
-    fn perform_navigation(
-        &mut self,
-        path: String,
-        op: NavigateOp,
-    ) -> Result<crate::RouteChangeEvent, NavigationResult> {
-        #[cfg(feature = "cache")]
-        self.nested_cache.clear();
+    /// Pipeline:
+    /// 1. Collect guards from matched route (+ ancestors)
+    /// 2. Check guards — if any denies/redirects, navigation is blocked
+    /// 3. Run `before_navigation` middleware
+    /// 4. Perform actual navigation
+    /// 5. Run `after_navigation` middleware
+    pub fn push(&mut self, path: String, cx: &App) -> NavigationResult {
+        self.navigate_with_pipeline(path, cx, NavigateOp::Push, 0)
+    }
 
-        #[cfg(feature = "transition")]
-        {
-            self.previous_stack = Some(self.match_stack.clone());
-        }
+    /// Replace current path, running the full guard/middleware pipeline.
+    pub fn replace(&mut self, path: String, cx: &App) -> NavigationResult {
+        self.navigate_with_pipeline(path, cx, NavigateOp::Replace, 0)
+    }
 
-        let event = match op {
-            NavigateOp::Push => self.state.push(path),
-            NavigateOp::Replace => self.state.replace(path),
-            NavigateOp::Back => self.state.back().ok_or_else(|| {
-                error_log!("back() returned None after peek succeeded");
-                NavigationResult::Error(crate::error::NavigationError::NavigationFailed {
-                    message: "History back failed unexpectedly".into(),
-                })
-            })?,
-            NavigateOp::Forward => self.state.forward().ok_or_else(|| {
-                error_log!("forward() returned None after peek succeeded");
-                NavigationResult::Error(crate::error::NavigationError::NavigationFailed {
-                    message: "History forward failed unexpectedly".into(),
-                })
-            })?,
-        };
+    /// Go back in history, checking guards on the target route.
+    pub fn back(&mut self, cx: &App) -> Option<NavigationResult> {
+        let target = self.state.peek_back_path()?.to_string();
+        Some(self.navigate_with_pipeline(target, cx, NavigateOp::Back, 0))
+    }
 
-        self.match_stack = resolve_match_stack(self.state.routes(), self.state.current_path());
-        Ok(event)
+    /// Go back in history, or replace the current entry with `fallback`
+    /// when there is nothing to go back to.
+    ///
+    /// The usual close button: a page opened directly, e.g. from a deep
+    /// link, has no history to return to. A back navigation a guard blocks
+    /// does not fall back.
+    pub fn back_or(&mut self, fallback: String, cx: &App) -> NavigationResult {
+        self.back(cx).unwrap_or_else(|| {
+            debug_log!("Nothing to go back to, replacing with '{}'", fallback);
+            self.replace(fallback, cx)
+        })
     }
 
-    // ========================================================================
-    // Lifecycle hooks
-    // ========================================================================
+    /// Go forward in history, checking guards on the target route.
+    pub fn forward(&mut self, cx: &App) -> Option<NavigationResult> {
+        let target = self.state.peek_forward_path()?.to_string();
+        Some(self.navigate_with_pipeline(target, cx, NavigateOp::Forward, 0))
+    }
 
-    /// Run `can_deactivate` on the current route's lifecycle (if any).
-    fn run_lifecycle_can_deactivate(&self, cx: &App) -> NavigationAction {
-        if let Some(current_route) = self.state.current_route() {
-            if let Some(ref lifecycle) = current_route.lifecycle {
-                return lifecycle.can_deactivate(cx);
+    /// Apply `ops` in order as a single navigation.
+    ///
+    /// Each op runs its guards against the path the op before it left, but
+    /// only the last one runs the full pipeline: the match stack is resolved,
+    /// middleware and lifecycle hooks run and windows refresh once, for where
+    /// the batch ends. The ops before it only add their history entries, so
+    /// route redirects apply to the last op alone.
+    ///
+    /// An op that does not go through stops the batch: the history is put
+    /// back as it was and the error names the op. Before the last op, a
+    /// guard redirect or challenge counts as not going through. Returns the
+    /// path the batch ended on; an empty batch stays where it is.
+    ///
+    /// # Errors
+    ///
+    /// Returns the position and outcome of the first op that did not go
+    /// through.
+    pub fn navigate_batch(
+        &mut self,
+        mut ops: Vec<BatchOp>,
+        cx: &App,
+    ) -> Result<String, BatchNavigationError> {
+        let Some(last) = ops.pop() else {
+            return Ok(self.current_path().to_string());
+        };
+        self.flush_batch();
+        let before = self.state.snapshot();
+        let sequence = self.navigation_sequence;
+        // Forward entries the ops before the last drop; the last op evicts
+        // its own
+        let mut truncated = Vec::new();
+
+        for (index, op) in ops.iter().enumerate() {
+            match self.batch_step(op, cx) {
+                Ok(mutation) => truncated.extend(mutation.truncated),
+                Err(result) => {
+                    debug_log!("Batch stopped at op {} ('{}')", index, op.path());
+                    self.state.restore_unresolved(before);
+                    self.record_notification(op.path(), &result);
+                    return Err(batch_failure(index, op.path(), result));
+                }
             }
         }
-        NavigationAction::Continue
-    }
 
-    /// Run `on_exit` on the current route's lifecycle (if any).
-    fn run_lifecycle_on_exit(&self, cx: &App) -> NavigationAction {
-        if let Some(current_route) = self.state.current_route() {
-            if let Some(ref lifecycle) = current_route.lifecycle {
-                return lifecycle.on_exit(cx);
+        match self.navigate_with_pipeline(last.path().to_string(), cx, last.op(), 0) {
+            NavigationResult::Success { path } => {
+                if !truncated.is_empty() {
+                    self.evict_truncated_components(&truncated);
+                }
+                Ok(path)
+            }
+            result => {
+                // A last op that committed, e.g. to a not-found fallback,
+                // keeps its entry
+                if self.navigation_sequence == sequence {
+                    self.state.restore_unresolved(before);
+                }
+                Err(batch_failure(ops.len(), last.path(), result))
             }
         }
-        NavigationAction::Continue
     }
 
-    /// Run `on_enter` on the new route's lifecycle (if any).
-    fn run_lifecycle_on_enter(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
-        if let Some(leaf) = self.match_stack.leaf() {
-            if let Some(ref lifecycle) = leaf.route.lifecycle {
-                return lifecycle.on_enter(cx, request);
-            }
+    /// Run the guards of a batch op other than the last, then add its
+    /// history entry.
+    fn batch_step(&mut self, op: &BatchOp, cx: &App) -> Result<HistoryMutation, NavigationResult> {
+        let path = self
+            .apply_query_schema(self.localize_path(op.path().to_string()))
+            .map_err(|err| {
+                NavigationResult::Error(NavigationError::InvalidParams {
+                    message: err.to_string(),
+                })
+            })?;
+        if let Some(blocked) = self.batch_guards(cx, &path) {
+            return Err(blocked);
         }
-        NavigationAction::Continue
+        Ok(match op {
+            BatchOp::Push(_) => self.state.push_unresolved(path),
+            BatchOp::Replace(_) => self.state.replace_unresolved(path),
+        })
     }
 
-    /// Collect and run guards for the target path.
-    ///
-    /// Walks the route tree to find the target route, collecting guards from
-    /// every ancestor route along the way. Guards on parent routes also protect
-    /// child routes (e.g. an `AuthGuard` on `/dashboard` also guards `/dashboard/settings`).
+    /// Run the guards for a batch op to `path`; `None` lets it through.
     #[cfg(feature = "guard")]
-    fn run_guards(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
-        let path = trim_slashes(&request.to);
-        let mut guards: Vec<(&dyn crate::guards::RouteGuard, i32)> = Vec::new();
-
-        // Collect guards from matching routes (including ancestor routes)
-        for route in self.state.routes() {
-            Self::collect_guards_recursive(route, path, "", &mut guards);
-        }
+    fn batch_guards(&self, cx: &App, path: &str) -> Option<NavigationResult> {
+        let _guard_cache = crate::guards::GuardCacheScope::enter();
+        let request = self.build_request(path, self.current_path());
+        let (reason, redirect) = match self.run_guards(cx, &request, 0).0 {
+            NavigationAction::Continue => return None,
+            NavigationAction::Deny { reason } => (reason, None),
+            NavigationAction::Redirect { to, reason } => (
+                reason.unwrap_or_else(|| format!("Redirected to '{to}'")),
+                Some(to),
+            ),
+            NavigationAction::Challenge { challenge_id, .. } => (
+                format!("Challenge '{challenge_id}' cannot be answered mid-batch"),
+                None,
+            ),
+        };
+        Some(NavigationResult::Blocked { reason, redirect })
+    }
 
-        // Sort by priority (higher first)
-        guards.sort_by_key(|(_, prio)| std::cmp::Reverse(*prio));
+    #[cfg(not(feature = "guard"))]
+    #[allow(clippy::unused_self)]
+    const fn batch_guards(&self, _cx: &App, _path: &str) -> Option<NavigationResult> {
+        None
+    }
 
-        debug_log!("Collected {} guards for '{}'", guards.len(), path);
+    /// Capture the history stack, cursor and current path.
+    ///
+    /// Cheap: a clone of the history. Pass it to [`restore`](Self::restore)
+    /// to return to this point, e.g. after a modal workflow or between test
+    /// assertions.
+    #[must_use]
+    pub fn snapshot(&self) -> RouterSnapshot {
+        self.state.snapshot()
+    }
 
-        // Check each guard — first non-Continue result wins
-        for (guard, prio) in &guards {
-            let result = guard.check(cx, request);
-            trace_log!(
-                "Guard '{}' (priority {}) → {:?}",
-                guard.name(),
-                prio,
-                result
+    /// Re-apply a [`snapshot`](Self::snapshot) and re-resolve the match stack.
+    ///
+    /// A restore is trusted: guards, middleware and lifecycle hooks do not
+    /// run. The routes' refetch tasks, timers and loaders follow the restored
+    /// match stack as they do after a navigation.
+    pub fn restore(&mut self, snapshot: RouterSnapshot, cx: &App) {
+        info_log!("Restoring router snapshot at '{}'", snapshot.current_path());
+        #[cfg(feature = "cache")]
+        self.nested_cache.clear();
+        let protected = Self::protected_scopes(self.state.match_stack());
+        #[cfg(feature = "transition")]
+        let previous = std::mem::take(self.state.match_stack_mut());
+        self.state.restore_unresolved(snapshot);
+        self.navigation_sequence += 1;
+        self.route_invalidated = None;
+        self.re_resolve();
+        #[cfg(feature = "transition")]
+        {
+            let window = self.exit_window(
+                &previous,
+                self.last_navigation.as_ref(),
+                self.navigation_source,
             );
-            if !matches!(result, NavigationAction::Continue) {
-                debug_log!(
-                    "Guard '{}' blocked navigation to '{}'",
-                    guard.name(),
-                    request.to
-                );
-                return result;
-            }
+            self.keep_previous_stack(previous, window, cx);
         }
-
-        NavigationAction::Continue
-    }
-
-    /// Recursively walk the route tree, collecting guards from routes that match
-    /// the given path (as exact match or prefix).
-    #[cfg(feature = "guard")]
-    fn collect_guards_recursive<'a>(
-        route: &'a Arc<Route>,
-        path: &str,
-        accumulated: &str,
-        out: &mut Vec<(&'a dyn crate::guards::RouteGuard, i32)>,
-    ) {
-        walk_matching_routes(route, path, accumulated, &mut |r, _full| {
-            for guard in &r.guards {
-                out.push((guard.as_ref(), guard.priority()));
-            }
-        });
+        self.clear_exited_scopes(protected, cx);
+        self.route_tasks.sync(self.state.match_stack(), cx);
+        self.route_timers.sync(self.state.match_stack(), cx);
+        self.loader_data =
+            LoaderData::load(self.state.match_stack(), &self.param_types, cx, |_, _| {});
+        self.record_load_errors();
     }
 
-    /// Run `before_navigation` on all middleware attached to matching routes.
-    #[cfg(feature = "middleware")]
-    fn run_middleware_before(&self, cx: &App, request: &NavigationRequest) {
-        let path = trim_slashes(&request.to);
-        let mut middleware: Vec<(&dyn crate::middleware::RouteMiddleware, i32)> = Vec::new();
-
-        for route in self.state.routes() {
-            Self::collect_middleware_recursive(route, path, "", &mut middleware);
-        }
+    // ========================================================================
+    // Partitions
+    // ========================================================================
 
-        // Sort by priority (higher first for before)
-        middleware.sort_by_key(|(_, prio)| std::cmp::Reverse(*prio));
+    /// Key of the partition every router starts in.
+    pub const DEFAULT_PARTITION: &'static str = "default";
 
-        debug_log!(
-            "Running {} before-middleware for '{}'",
-            middleware.len(),
-            request.to
-        );
-        for (mw, _) in &middleware {
-            trace_log!(
-                "Middleware '{}' before_navigation for '{}'",
-                mw.name(),
-                request.to
-            );
-            mw.before_navigation(cx, request);
-        }
+    /// Create a partition: a separate navigation world starting at `/`.
+    ///
+    /// Partitions share the route table (and with it guards and middleware)
+    /// but each has its own history, component cache and route state. The
+    /// [`DEFAULT_PARTITION`](Self::DEFAULT_PARTITION) always exists. Returns
+    /// `false` if `key` is taken.
+    pub fn create_partition(&mut self, key: impl Into<String>) -> bool {
+        self.create_partition_from(key, RouterState::new().snapshot())
     }
 
-    /// Run `after_navigation` on all middleware attached to matching routes.
-    #[cfg(feature = "middleware")]
-    fn run_middleware_after(&self, cx: &App, request: &NavigationRequest) {
-        let path = trim_slashes(&request.to);
-        let mut middleware: Vec<(&dyn crate::middleware::RouteMiddleware, i32)> = Vec::new();
-
-        for route in self.state.routes() {
-            Self::collect_middleware_recursive(route, path, "", &mut middleware);
+    /// Create a partition whose history starts as `snapshot`.
+    ///
+    /// Pairs with [`partition_snapshot`](Self::partition_snapshot) to carry a
+    /// partition's history across sessions. Returns `false` if `key` is taken.
+    pub fn create_partition_from(
+        &mut self,
+        key: impl Into<String>,
+        snapshot: RouterSnapshot,
+    ) -> bool {
+        let key = key.into();
+        if key == self.active_partition || self.partitions.contains_key(&key) {
+            warn_log!("Partition '{}' already exists", key);
+            return false;
         }
-
-        // Sort by priority ascending for after (reverse of before — stack-like)
-        middleware.sort_by_key(|(_, prio)| *prio);
-
         debug_log!(
-            "Running {} after-middleware for '{}'",
-            middleware.len(),
-            request.to
+            "Created partition '{}' at '{}'",
+            key,
+            snapshot.current_path()
         );
-        for (mw, _) in &middleware {
-            trace_log!(
-                "Middleware '{}' after_navigation for '{}'",
-                mw.name(),
-                request.to
-            );
-            mw.after_navigation(cx, request);
-        }
+        let cache = ComponentCache::new(self.cache_policy);
+        self.partitions.insert(key, Partition::new(snapshot, cache));
+        true
     }
 
-    /// Recursively collect middleware from matching routes.
-    #[cfg(feature = "middleware")]
-    fn collect_middleware_recursive<'a>(
-        route: &'a Arc<Route>,
-        path: &str,
-        accumulated: &str,
-        out: &mut Vec<(&'a dyn crate::middleware::RouteMiddleware, i32)>,
-    ) {
-        walk_matching_routes(route, path, accumulated, &mut |r, _full| {
-            for mw in &r.middleware {
-                out.push((mw.as_ref(), mw.priority()));
+    /// Make `key` the active partition.
+    ///
+    /// Runs `can_deactivate` on every level of the current route first,
+    /// deepest first; if they let go, the current partition's history,
+    /// component cache and route state are stored and the target's are put
+    /// back, re-resolved against the shared routes as in
+    /// [`restore`](Self::restore). Switching to the active partition
+    /// succeeds without doing anything.
+    pub fn switch_partition(&mut self, key: &str, cx: &App) -> NavigationResult {
+        if key == self.active_partition {
+            return NavigationResult::Success {
+                path: self.state.current_path().to_string(),
+            };
+        }
+        if !self.partitions.contains_key(key) {
+            warn_log!("Cannot switch to unknown partition '{}'", key);
+            return NavigationResult::Error(NavigationError::NavigationFailed {
+                message: format!("Unknown partition '{key}'"),
+            });
+        }
+        match self.run_lifecycle_can_deactivate(cx, None) {
+            NavigationAction::Continue => {}
+            NavigationAction::Deny { reason } => {
+                warn_log!(
+                    "Lifecycle can_deactivate blocked switching to '{}': {}",
+                    key,
+                    reason
+                );
+                return NavigationResult::Blocked {
+                    reason,
+                    redirect: None,
+                };
             }
-        });
+            NavigationAction::Redirect { to, .. } => {
+                return NavigationResult::Blocked {
+                    reason: format!("can_deactivate redirected to '{to}'"),
+                    redirect: Some(to),
+                };
+            }
+            NavigationAction::Challenge { challenge_id, .. } => {
+                return NavigationResult::Blocked {
+                    reason: format!("Unsupported challenge '{challenge_id}' from can_deactivate"),
+                    redirect: None,
+                };
+            }
+        }
+        self.swap_partition(key, cx)
     }
 
-    // ========================================================================
-    // Named routes
-    // ========================================================================
-
-    /// Navigate to a named route, resolving the URL from `params`.
-    ///
-    /// Returns `None` if the name is not registered.
-    pub fn push_named(
-        &mut self,
-        name: &str,
-        params: &RouteParams,
-        cx: &App,
-    ) -> Option<NavigationResult> {
-        let url = if let Some(url) = self.named_routes.url_for(name, params) {
-            debug_log!("Named route '{}' resolved to '{}'", name, url);
-            url
-        } else {
-            warn_log!("Named route '{}' not found in registry", name);
-            return None;
+    /// Store the active partition and put `key`'s state back, without
+    /// asking the current route.
+    fn swap_partition(&mut self, key: &str, cx: &App) -> NavigationResult {
+        let Some(target) = self.partitions.remove(key) else {
+            return NavigationResult::Error(NavigationError::NavigationFailed {
+                message: format!("Unknown partition '{key}'"),
+            });
         };
-        Some(self.push(url, cx))
-    }
-
-    /// Generate a URL for a named route by substituting `params` into its pattern.
-    ///
-    /// Returns `None` if the name is not registered.
-    #[must_use]
-    pub fn url_for(&self, name: &str, params: &RouteParams) -> Option<String> {
-        self.named_routes.url_for(name, params)
-    }
-
-    // ========================================================================
-    // Accessors
-    // ========================================================================
-
-    /// Return the current navigation path.
-    #[must_use]
-    pub fn current_path(&self) -> &str {
-        self.state.current_path()
-    }
-
-    /// Get current route match (with caching, requires mutable).
-    pub fn current_match(&mut self) -> Option<crate::RouteMatch> {
-        self.state.current_match()
+        info_log!(
+            "Switching partition '{}' → '{}'",
+            self.active_partition,
+            key
+        );
+        let stored = Partition {
+            snapshot: self.state.snapshot(),
+            component_cache: std::mem::replace(&mut self.component_cache, target.component_cache),
+            route_state: std::mem::replace(&mut self.route_state, target.route_state),
+        };
+        let previous = std::mem::replace(&mut self.active_partition, key.to_string());
+        self.partitions.insert(previous, stored);
+        #[cfg(feature = "guard")]
+        {
+            self.pending_challenge = None;
+        }
+        self.restore(target.snapshot, cx);
+        NavigationResult::Success {
+            path: self.state.current_path().to_string(),
+        }
     }
 
-    /// Get current route match (immutable, no caching).
+    /// Key of the active partition.
     #[must_use]
-    pub fn current_match_immutable(&self) -> Option<crate::RouteMatch> {
-        self.state.current_match_immutable()
+    pub fn active_partition(&self) -> &str {
+        &self.active_partition
     }
 
-    /// Get the current matched Route.
+    /// Keys of every partition, the active one included, sorted.
     #[must_use]
-    pub fn current_route(&self) -> Option<&Arc<crate::route::Route>> {
-        self.state.current_route()
+    pub fn partition_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self
+            .partitions
+            .keys()
+            .map(String::as_str)
+            .chain(std::iter::once(self.active_partition.as_str()))
+            .collect();
+        keys.sort_unstable();
+        keys
     }
 
-    /// Check if can go back.
+    /// History of partition `key`, for [`create_partition_from`](Self::create_partition_from).
     #[must_use]
-    pub const fn can_go_back(&self) -> bool {
-        self.state.can_go_back()
+    pub fn partition_snapshot(&self, key: &str) -> Option<RouterSnapshot> {
+        if key == self.active_partition {
+            return Some(self.state.snapshot());
+        }
+        self.partitions
+            .get(key)
+            .map(|partition| partition.snapshot.clone())
     }
 
-    /// Check if can go forward.
-    #[must_use]
-    pub fn can_go_forward(&self) -> bool {
-        self.state.can_go_forward()
+    /// Drop an inactive partition with its history, component cache and
+    /// route state.
+    ///
+    /// The cached components are released, so their entities' release
+    /// observers run. The active partition cannot be dropped; returns
+    /// `false` for it and for unknown keys.
+    pub fn drop_partition(&mut self, key: &str) -> bool {
+        if key == self.active_partition {
+            warn_log!("Cannot drop the active partition '{}'", key);
+            return false;
+        }
+        let Some(partition) = self.partitions.remove(key) else {
+            return false;
+        };
+        debug_log!(
+            "Dropped partition '{}' ({} cached components)",
+            key,
+            partition.component_cache.len()
+        );
+        true
     }
 
-    /// Get mutable state reference.
-    pub fn state_mut(&mut self) -> &mut RouterState {
-        &mut self.state
-    }
+    // ========================================================================
+    // Windows
+    // ========================================================================
 
-    /// Get state reference.
+    /// Whether `window` was opened by [`Navigator::push_new_window`] and
+    /// navigates on its own. See [`new_window`](crate::new_window).
     #[must_use]
-    pub const fn state(&self) -> &RouterState {
-        &self.state
-    }
-
-    /// Get nested route cache (mutable).
-    #[cfg(feature = "cache")]
-    pub fn nested_cache_mut(&mut self) -> &mut RouteCache {
-        &mut self.nested_cache
+    pub fn has_window_state(&self, window: WindowId) -> bool {
+        self.live_window == Some(window) || self.window_states.contains_key(&window)
     }
 
-    /// Get nested route cache statistics.
-    #[cfg(feature = "cache")]
+    /// Current path of a window opened by [`Navigator::push_new_window`].
     #[must_use]
-    pub const fn cache_stats(&self) -> &CacheStats {
-        self.nested_cache.stats()
+    pub fn window_path(&self, window: WindowId) -> Option<&str> {
+        if self.live_window == Some(window) {
+            return Some(self.state.current_path());
+        }
+        self.window_states
+            .get(&window)
+            .map(|stored| stored.state.current_path())
     }
 
-    // ========================================================================
-    // Error handlers
-    // ========================================================================
-
-    /// Set custom error handlers for 404 and navigation errors.
-    pub fn set_error_handlers(&mut self, handlers: ErrorHandlers) {
-        self.error_handlers = handlers;
+    /// Navigate a fresh navigation state to `path`, for a window
+    /// [`Navigator::push_new_window`] is about to open.
+    ///
+    /// Guards are probed first, so a blocked target navigates nothing. The
+    /// shared state stays live throughout.
+    fn open_window_state(
+        &mut self,
+        path: String,
+        cx: &App,
+    ) -> (NavigationResult, Option<WindowNavigation>) {
+        let probe = self.can_navigate(cx, &path);
+        if !probe.allowed {
+            warn_log!("Not opening a window for blocked '{}'", path);
+            let reason = probe
+                .reason
+                .unwrap_or_else(|| format!("Guards block '{path}'"));
+            return (
+                NavigationResult::Blocked {
+                    reason,
+                    redirect: probe.redirect,
+                },
+                None,
+            );
+        }
+        self.make_live(None);
+        let mut opened = WindowNavigation::new(self.cache_policy);
+        self.swap_live(&mut opened);
+        let result = self.navigate_with_pipeline(path, cx, NavigateOp::Replace, 0);
+        self.swap_live(&mut opened);
+        self.publish_snapshot();
+        let opened = result.is_success().then_some(opened);
+        (result, opened)
     }
 
-    /// Get a reference to the current error handlers.
-    pub const fn error_handlers(&self) -> &ErrorHandlers {
-        &self.error_handlers
+    /// Keep `state` as the navigation state of the opened `window`.
+    fn adopt_window_state(&mut self, window: WindowId, state: WindowNavigation) {
+        debug_log!(
+            "Window {:?} navigates on its own at '{}'",
+            window,
+            state.state.current_path()
+        );
+        self.window_states.insert(window, state);
     }
 
-    // ========================================================================
-    // Component cache
-    // ========================================================================
-
-    /// Get a cached component view by key.
-    #[must_use]
-    pub fn get_cached_component(&self, key: &str) -> Option<&AnyView> {
-        self.component_cache.get(key)
+    /// Drop the navigation state of a closed window, releasing the
+    /// components it cached.
+    pub(crate) fn close_window_state(&mut self, window: WindowId) {
+        if self.live_window == Some(window) {
+            self.make_live(None);
+        }
+        if let Some(stored) = self.window_states.remove(&window) {
+            debug_log!(
+                "Dropped the state of window {:?} ({} cached components)",
+                window,
+                stored.component_cache.len()
+            );
+        }
     }
 
-    /// Store a component view in the cache, evicting the oldest entry if full.
-    pub fn cache_component(&mut self, key: String, view: AnyView) {
-        if !self.component_cache.contains_key(&key) {
-            // Evict oldest entries until we are under the limit
-            while self.component_cache.len() >= MAX_COMPONENT_CACHE {
-                if let Some(oldest_key) = self.component_cache_order.pop_front() {
-                    self.component_cache.remove(&oldest_key);
-                } else {
-                    break;
-                }
+    /// Put the navigation state of `window` live, or the shared one for
+    /// `None` and windows without their own. Returns whether a window's own
+    /// state is live now.
+    ///
+    /// Nothing runs: no lifecycle hooks, guards or loaders.
+    pub(crate) fn make_live(&mut self, window: Option<WindowId>) -> bool {
+        let window = window.filter(|id| self.has_window_state(*id));
+        if window == self.live_window {
+            return window.is_some();
+        }
+        let incoming = match window {
+            Some(id) => self.window_states.remove(&id),
+            None => self.parked_shared.take(),
+        };
+        let Some(mut stored) = incoming else {
+            return false;
+        };
+        self.swap_live(&mut stored);
+        match std::mem::replace(&mut self.live_window, window) {
+            Some(id) => {
+                self.window_states.insert(id, stored);
             }
-            self.component_cache_order.push_back(key.clone());
+            None => self.parked_shared = Some(stored),
         }
-        self.component_cache.insert(key, view);
-    }
-
-    // ========================================================================
-    // Transitions
-    // ========================================================================
-
-    /// Set transition for the next navigation.
-    #[cfg(feature = "transition")]
-    pub fn set_next_transition(&mut self, transition: Transition) {
-        self.next_transition = Some(transition);
+        if window.is_none() {
+            self.publish_snapshot();
+        }
+        window.is_some()
     }
 
-    /// Get and consume the next transition override.
-    #[cfg(feature = "transition")]
-    pub fn take_next_transition(&mut self) -> Option<Transition> {
-        self.next_transition.take()
+    /// Run `f` with the navigation state of `window` live, then put the
+    /// shared state back.
+    pub(crate) fn in_window<R>(&mut self, window: WindowId, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.make_live(Some(window));
+        let result = f(self);
+        self.make_live(None);
+        result
     }
 
-    /// Check if there's a transition override set.
-    #[cfg(feature = "transition")]
-    #[must_use]
-    pub const fn has_next_transition(&self) -> bool {
-        self.next_transition.is_some()
+    /// Exchange the live navigation state with `stored`.
+    fn swap_live(&mut self, stored: &mut WindowNavigation) {
+        use std::mem::swap;
+
+        self.state.swap_navigation(&mut stored.state);
+        swap(&mut self.component_cache, &mut stored.component_cache);
+        swap(&mut self.route_state, &mut stored.route_state);
+        swap(&mut self.loader_data, &mut stored.loader_data);
+        swap(&mut self.route_tasks, &mut stored.route_tasks);
+        swap(&mut self.route_timers, &mut stored.route_timers);
+        swap(&mut self.route_invalidated, &mut stored.route_invalidated);
+        swap(&mut self.scroll_intent, &mut stored.scroll_intent);
+        #[cfg(feature = "transition")]
+        {
+            swap(&mut self.previous_stack, &mut stored.previous_stack);
+            swap(
+                &mut self.previous_stack_expires,
+                &mut stored.previous_stack_expires,
+            );
+            swap(&mut self.last_navigation, &mut stored.last_navigation);
+            swap(&mut self.navigation_source, &mut stored.navigation_source);
+        }
     }
 
-    /// Clear transition override.
-    #[cfg(feature = "transition")]
-    pub fn clear_next_transition(&mut self) {
-        self.next_transition = None;
+    /// Re-resolve the navigation states not live against the current
+    /// routes. Invalidations are only reported for the live one.
+    fn re_resolve_parked(&mut self) {
+        let resolved: Vec<(Option<WindowId>, MatchStack)> = self
+            .window_states
+            .iter()
+            .map(|(id, stored)| (Some(*id), stored.state.current_path()))
+            .chain(
+                self.parked_shared
+                    .iter()
+                    .map(|stored| (None, stored.state.current_path())),
+            )
+            .map(|(id, path)| (id, self.resolve_path(path)))
+            .collect();
+        for (id, stack) in resolved {
+            let stored = match id {
+                Some(id) => self.window_states.get_mut(&id),
+                None => self.parked_shared.as_mut(),
+            };
+            if let Some(stored) = stored {
+                stored.state.set_match_stack(stack);
+            }
+        }
     }
 
-    /// Navigate with a specific transition.
-    #[cfg(feature = "transition")]
-    pub fn push_with_transition(
+    /// Push a new path with associated [`HistoryState`] data, running the full pipeline.
+    ///
+    /// Allows attaching arbitrary key-value state (scroll position, form data, etc.)
+    /// to the history entry. The pipeline (guards, middleware) runs first; state
+    /// is only attached if navigation succeeds.
+    pub fn push_with_state(
         &mut self,
         path: String,
-        transition: Transition,
+        state: HistoryState,
         cx: &App,
     ) -> NavigationResult {
-        self.set_next_transition(transition);
-        self.push(path, cx)
+        // Run the normal push pipeline, then attach state to the entry it
+        // committed without re-resolving
+        let result = self.navigate_with_pipeline(path, cx, NavigateOp::Push, 0);
+        if matches!(result, NavigationResult::Success { .. }) {
+            self.state.attach_state_to_current(state);
+        }
+        result
     }
 
-    /// Replace with a specific transition.
-    #[cfg(feature = "transition")]
-    pub fn replace_with_transition(
+    /// Replace current path with associated [`HistoryState`] data, running the full pipeline.
+    pub fn replace_with_state(
         &mut self,
         path: String,
-        transition: Transition,
+        state: HistoryState,
         cx: &App,
     ) -> NavigationResult {
-        self.set_next_transition(transition);
-        self.replace(path, cx)
+        let result = self.navigate_with_pipeline(path, cx, NavigateOp::Replace, 0);
+        if matches!(result, NavigationResult::Success { .. }) {
+            self.state.attach_state_to_current(state);
+        }
+        result
     }
-}
 
-impl Default for GlobalRouter {
-    fn default() -> Self {
-        Self {
-            state: RouterState::new(),
-            match_stack: MatchStack::new(),
-            #[cfg(feature = "transition")]
-            previous_stack: None,
-            #[cfg(feature = "cache")]
-            nested_cache: RouteCache::new(),
-            named_routes: NamedRouteRegistry::new(),
-            #[cfg(feature = "transition")]
-            next_transition: None,
-            component_cache: HashMap::new(),
-            component_cache_order: std::collections::VecDeque::new(),
-            error_handlers: ErrorHandlers::new(),
+    /// Push a new path carrying a typed one-shot payload, running the full pipeline.
+    ///
+    /// The payload is attached to the entry the navigation lands on (after any
+    /// redirects) and only if navigation succeeds; otherwise it is dropped.
+    /// The destination claims it with [`take_payload`](Self::take_payload).
+    pub fn push_with_payload<T: Any + Send + Sync>(
+        &mut self,
+        path: String,
+        payload: T,
+        cx: &App,
+    ) -> NavigationResult {
+        let result = self.navigate_with_pipeline(path, cx, NavigateOp::Push, 0);
+        if matches!(result, NavigationResult::Success { .. }) {
+            self.state.current_entry_mut().set_payload(payload);
         }
+        result
     }
-}
-
-impl Global for GlobalRouter {}
 
-// ============================================================================
-// Helper: path prefix matching with parameter support
-// ============================================================================
+    /// Remove and return the current entry's payload of type `T`.
+    ///
+    /// Payloads are one-shot: a second call returns `None`. An untaken payload
+    /// stays on its entry (and is found again after navigating back to it)
+    /// until the entry is dropped. See [`HistoryEntry::set_payload`].
+    pub fn take_payload<T: Any + Send + Sync>(&mut self) -> Option<T> {
+        self.state.current_entry_mut().take_payload()
+    }
 
-/// Walk the route tree, calling `visitor` on each route whose accumulated path
-/// is a prefix of `target_path`. The visitor receives the route and the full
-/// accumulated path.
-///
-/// This factored-out helper avoids duplicating tree-walk logic between guard
-/// collection and middleware collection.
-fn walk_matching_routes<'a>(
-    route: &'a Arc<Route>,
-    target_path: &str,
-    accumulated: &str,
-    visitor: &mut dyn FnMut(&'a Route, &str),
-) {
-    let route_path = trim_slashes(&route.config.path);
+    /// Return the path queued by [`Navigator::defer_push`], if it has not
+    /// run yet.
+    #[must_use]
+    pub fn deferred_push(&self) -> Option<&str> {
+        self.deferred_push.as_deref()
+    }
 
-    // Avoid allocations when possible by reusing the existing string
-    let full: std::borrow::Cow<'_, str> = if accumulated.is_empty() {
-        std::borrow::Cow::Borrowed(route_path)
-    } else if route_path.is_empty() {
-        std::borrow::Cow::Borrowed(accumulated)
-    } else {
-        std::borrow::Cow::Owned(format!("{accumulated}/{route_path}"))
-    };
+    /// Return the target of a navigation that has started but not finished.
+    ///
+    /// That is a path queued by [`Navigator::defer_push`], or else the path
+    /// of a navigation suspended by a guard challenge. Synchronous
+    /// navigations finish within the call, so between calls this is `None`
+    /// unless one of those is waiting.
+    #[must_use]
+    pub fn pending_navigation(&self) -> Option<&str> {
+        if let Some(path) = self.deferred_push.as_deref() {
+            return Some(path);
+        }
+        #[cfg(feature = "guard")]
+        if let Some(pending) = &self.pending_challenge {
+            return Some(pending.path());
+        }
+        None
+    }
 
-    if !full.is_empty() && !path_matches_prefix(target_path, &full) {
-        return;
+    /// Return the current [`HistoryEntry`] (path + optional state data).
+    #[must_use]
+    pub fn current_entry(&self) -> &HistoryEntry {
+        self.state.current_entry()
     }
 
-    visitor(route, &full);
+    /// Return the navigation currently suspended by a guard challenge, if any.
+    #[cfg(feature = "guard")]
+    #[must_use]
+    pub const fn pending_challenge(&self) -> Option<&PendingChallenge> {
+        self.pending_challenge.as_ref()
+    }
 
-    for child in route.get_children() {
-        walk_matching_routes(child, target_path, &full, visitor);
+    /// Answer the pending challenge and resume its navigation.
+    ///
+    /// Re-invokes the guard that issued the challenge with `response`
+    /// available through [`NavigationRequest::challenge_response`]. If it
+    /// continues, the remaining guards and the rest of the pipeline run as
+    /// usual; it may also deny, redirect, or challenge again (up to
+    /// `MAX_CHALLENGE_ROUNDS` times, after which navigation is denied).
+    ///
+    /// Returns `None` if no challenge is pending.
+    #[cfg(feature = "guard")]
+    pub fn answer_challenge(&mut self, response: String, cx: &App) -> Option<NavigationResult> {
+        let pending = self.pending_challenge.take()?;
+        self.progress.begin();
+        self.phase = NavigationPhase::Running { redirected: false };
+        let result = self.resume_challenge(&pending, response, cx);
+        self.progress.finish();
+        self.record_notification(&pending.path, &result);
+        #[cfg(feature = "middleware")]
+        self.settle_middleware(cx, &result);
+        self.phase = NavigationPhase::Finished;
+        Some(result)
     }
-}
 
-/// Check if `path` matches `prefix` as a route prefix (supports `:param` segments).
-///
-/// Uses iterators instead of collecting into `Vec`s to avoid allocation.
-///
-/// Examples:
-/// - `path_matches_prefix("dashboard/settings", "dashboard")` → true
-/// - `path_matches_prefix("dashboard", "dashboard")` → true
-/// - `path_matches_prefix("users/123", "users/:id")` → true
-/// - `path_matches_prefix("other", "dashboard")` → false
-fn path_matches_prefix(path: &str, prefix: &str) -> bool {
-    let mut path_segs = path.split('/').filter(|s| !s.is_empty());
-    let prefix_segs = prefix.split('/').filter(|s| !s.is_empty());
-
-    for pfs in prefix_segs {
-        let Some(ps) = path_segs.next() else {
-            // Path exhausted before prefix — not a prefix match
-            return false;
+    /// Re-run the challenging guard with `response`, then the rest of the
+    /// suspended pipeline.
+    #[cfg(feature = "guard")]
+    fn resume_challenge(
+        &mut self,
+        pending: &PendingChallenge,
+        response: String,
+        cx: &App,
+    ) -> NavigationResult {
+        let _span = NavigationSpan::enter(0);
+        let step = pending.step;
+        let from = self.current_path().to_string();
+        info_log!(
+            "Answering challenge '{}' for '{}' (round {})",
+            pending.challenge_id,
+            pending.path,
+            step.round
+        );
+
+        let mut request = self.build_request(&pending.path, &from);
+        #[cfg(feature = "middleware")]
+        {
+            self.settlement.request = Some(request.clone());
+        }
+        request.challenge_response = Some(response);
+        request.extensions = pending.extensions.clone();
+        self.progress.advance(&request.to, NavigationStage::Guards);
+        let action = self
+            .collect_guards(&request.to)
+            .get(step.guard_index)
+            .map_or(NavigationAction::Continue, |collected| {
+                collected.guard.check(cx, &request)
+            });
+        if let Some(result) = self.apply_guard_action(action, &request, step, cx) {
+            return result;
+        }
+
+        // The answer is meant for the challenging guard only
+        request.challenge_response = None;
+        let (action, guard_index) = self.run_guards(cx, &request, step.guard_index + 1);
+        let next = GuardStep {
+            guard_index,
+            round: 0,
+            ..step
         };
-        if pfs.starts_with(':') {
-            continue;
+        if let Some(result) = self.apply_guard_action(action, &request, next, cx) {
+            return result;
         }
-        if ps != pfs {
-            return false;
+
+        self.finish_navigation(request, &from, cx, step.op, step.redirect_depth)
+    }
+
+    /// Drop the pending challenge, leaving the current path unchanged.
+    ///
+    /// Returns `true` if a challenge was pending.
+    #[cfg(feature = "guard")]
+    pub fn cancel_challenge(&mut self) -> bool {
+        let cancelled = self.pending_challenge.take();
+        if let Some(ref pending) = cancelled {
+            debug_log!(
+                "Challenge '{}' for '{}' cancelled",
+                pending.challenge_id,
+                pending.path
+            );
         }
+        cancelled.is_some()
     }
 
-    true
-}
+    /// Core navigation method that runs the full pipeline.
+    ///
+    /// A top-level call (`redirect_depth == 0`) owns the progress slot: it
+    /// starts tracking and clears the slot however the pipeline ends.
+    fn navigate_with_pipeline(
+        &mut self,
+        path: String,
+        cx: &App,
+        op: NavigateOp,
+        redirect_depth: usize,
+    ) -> NavigationResult {
+        if redirect_depth > 0 {
+            self.phase = NavigationPhase::Running { redirected: true };
+            return self.run_pipeline(path, cx, op, redirect_depth);
+        }
+        // Invalidated routes left without being asked must not block this
+        self.settle(cx);
+        self.progress.begin();
+        self.phase = NavigationPhase::Running { redirected: false };
+        #[cfg(feature = "transition")]
+        {
+            self.pending_transition = None;
+        }
+        let requested = path.clone();
+        let result = self.run_pipeline(path, cx, op, 0);
+        self.progress.finish();
+        self.record_notification(&requested, &result);
+        #[cfg(feature = "middleware")]
+        self.settle_middleware(cx, &result);
+        // Last step: only now may the Navigator let windows render it
+        self.phase = NavigationPhase::Finished;
+        result
+    }
 
-// ============================================================================
-// Navigation operation type
-// ============================================================================
+    /// Record the outcome of a top-level navigation to `requested` for
+    /// [`current_notification`](Self::current_notification).
+    ///
+    /// Landing on a path no route matches succeeds, rendering the 404 page,
+    /// but is reported as not found.
+    fn record_notification(&mut self, requested: &str, result: &NavigationResult) {
+        let now = Instant::now();
+        let redirected = self.phase == NavigationPhase::Running { redirected: true };
+        if let NavigationResult::Success { path } = result {
+            if self.state.match_stack().is_empty() {
+                self.notifications.dismiss();
+                let not_found = NavigationResult::NotFound { path: path.clone() };
+                self.notifications
+                    .record(requested, &not_found, redirected, now);
+                return;
+            }
+        }
+        self.notifications
+            .record(requested, result, redirected, now);
+    }
 
-/// Internal enum for the kind of navigation to perform after pipeline checks.
-#[derive(Debug, Clone, Copy)]
-enum NavigateOp {
-    Push,
-    Replace,
-    Back,
-    Forward,
-}
+    /// Take the flag a finished navigation raises for the window refresh.
+    ///
+    /// The [`Navigator`] refreshes windows only when this returns `true`,
+    /// so no window renders a navigation before all of its steps ran.
+    pub(crate) fn take_refresh(&mut self) -> bool {
+        let finished = self.phase == NavigationPhase::Finished;
+        if finished {
+            self.phase = NavigationPhase::Idle;
+        }
+        finished
+    }
 
-// ============================================================================
-// UseRouter trait
-// ============================================================================
+    /// Run guards, then the rest of the pipeline (step 1).
+    fn run_pipeline(
+        &mut self,
+        path: String,
+        cx: &App,
+        op: NavigateOp,
+        redirect_depth: usize,
+    ) -> NavigationResult {
+        let _span = NavigationSpan::enter(redirect_depth);
+        #[cfg(feature = "guard")]
+        let _guard_cache = crate::guards::GuardCacheScope::enter();
+        self.flush_batch();
 
-/// Trait for accessing the global router from context.
-pub trait UseRouter {
-    /// Get reference to global router.
-    fn router(&self) -> &GlobalRouter;
+        if redirect_depth >= MAX_REDIRECT_DEPTH {
+            error_log!(
+                "Redirect loop detected (depth {}) navigating to '{}'",
+                redirect_depth,
+                path
+            );
+            return NavigationResult::Blocked {
+                reason: format!("Redirect loop detected (depth {redirect_depth}): target '{path}'"),
+                redirect: None,
+            };
+        }
 
-    /// Update global router.
-    fn update_router<F, R>(&mut self, f: F) -> R
-    where
-        F: FnOnce(&mut GlobalRouter, &mut App) -> R;
-}
+        // A new navigation supersedes any challenge still awaiting an answer
+        #[cfg(feature = "guard")]
+        if let Some(pending) = self.pending_challenge.take() {
+            debug_log!(
+                "Challenge '{}' for '{}' superseded",
+                pending.challenge_id,
+                pending.path
+            );
+        }
 
-impl UseRouter for App {
-    fn router(&self) -> &GlobalRouter {
-        self.global::<GlobalRouter>()
+        // History traversal replays stored paths verbatim; only new entries
+        // inherit the current locale.
+        let path = match op {
+            NavigateOp::Push | NavigateOp::Replace => {
+                match self.apply_query_schema(self.localize_path(path)) {
+                    Ok(path) => path,
+                    Err(err) => {
+                        warn_log!("Navigation rejected: {}", err);
+                        return NavigationResult::Error(NavigationError::InvalidParams {
+                            message: err.to_string(),
+                        });
+                    }
+                }
+            }
+            NavigateOp::Back | NavigateOp::Forward => path,
+        };
+
+        let from = self.current_path().to_string();
+        info_log!("Navigation {:?}: '{}' → '{}'", op, from, path);
+
+        // Build request — used by guards, lifecycle hooks, and middleware
+        let request = self.build_request(&path, &from);
+        #[cfg(feature = "middleware")]
+        if redirect_depth == 0 {
+            self.settlement.request = Some(request.clone());
+        }
+
+        // Route redirects decided from the matched params come before guards
+        if let Some((pattern, to)) = self.route_redirect(&path, cx) {
+            debug_log!(
+                "Route '{}' redirecting from '{}' to '{}'",
+                pattern,
+                path,
+                to
+            );
+            let op = match op {
+                NavigateOp::Replace => NavigateOp::Replace,
+                NavigateOp::Push | NavigateOp::Back | NavigateOp::Forward => NavigateOp::Push,
+            };
+            return self.navigate_with_pipeline(to, cx, op, redirect_depth + 1);
+        }
+
+        // Step 1: Run guards
+        #[cfg(feature = "guard")]
+        {
+            self.progress.advance(&request.to, NavigationStage::Guards);
+            let (action, guard_index) = self.run_guards(cx, &request, 0);
+            let step = GuardStep {
+                guard_index,
+                op,
+                redirect_depth,
+                round: 0,
+            };
+            if let Some(result) = self.apply_guard_action(action, &request, step, cx) {
+                return result;
+            }
+        }
+
+        self.finish_navigation(request, &from, cx, op, redirect_depth)
     }
 
-    fn update_router<F, R>(&mut self, f: F) -> R
-    where
-        F: FnOnce(&mut GlobalRouter, &mut Self) -> R,
-    {
-        self.update_global(f)
+    /// Filter the query of `path` through the [`Route::query_schema`] of the
+    /// deepest matched route that declares one.
+    fn apply_query_schema(&self, path: String) -> Result<String, QueryError> {
+        let Some((base, query)) = path.split_once('?') else {
+            return Ok(path);
+        };
+        let stack = self.resolve_path(base);
+        let Some((schema, violation)) = stack.entries().iter().rev().find_map(|entry| {
+            let schema = entry.route.query_schema.as_ref()?;
+            Some((schema, entry.route.query_violation))
+        }) else {
+            return Ok(path);
+        };
+        let kept = schema.apply(query, violation)?;
+        if kept == query {
+            Ok(path)
+        } else if kept.is_empty() {
+            Ok(base.to_string())
+        } else {
+            Ok(format!("{base}?{kept}"))
+        }
     }
-}
 
-// ============================================================================
-// init_router
-// ============================================================================
+    /// Pattern and target of the first [`Route::redirect_if`] in the match
+    /// stack of `path` that redirects, root first.
+    fn route_redirect(&self, path: &str, cx: &App) -> Option<(String, String)> {
+        self.resolve_path(path).entries().iter().find_map(|entry| {
+            let redirect = entry.route.redirect_if.as_ref()?;
+            let to = redirect(cx, &entry.params)?;
+            Some((entry.accumulated_pattern().to_string(), to))
+        })
+    }
 
-/// Initialize global router with routes.
-///
-/// # Example
-///
-/// ```ignore
-/// use gpui_navigator::{init_router, Route};
-///
-/// init_router(cx, |router| {
-///     router.add_route(Route::new("/", |_, _cx, _params| gpui::div()));
-///     router.add_route(Route::new("/users/:id", |_, _cx, _params| gpui::div()));
-/// });
-/// ```
-pub fn init_router<F>(cx: &mut App, configure: F)
-where
-    F: FnOnce(&mut GlobalRouter),
-{
-    let mut router = GlobalRouter::new();
-    configure(&mut router);
-    cx.set_global(router);
-}
+    /// Run the pipeline after guards have passed (steps 2–7).
+    fn finish_navigation(
+        &mut self,
+        mut request: NavigationRequest,
+        from: &str,
+        cx: &App,
+        op: NavigateOp,
+        redirect_depth: usize,
+    ) -> NavigationResult {
+        if let Some(result) = self.leave_current(&request, from, cx, redirect_depth) {
+            return result;
+        }
 
-/// Navigate to a path using the global router and refresh all windows.
-///
-/// This is a convenience shortcut equivalent to
-/// `cx.update_global::<GlobalRouter, _>(|r, cx| r.push(path, cx))`.
-pub fn navigate(cx: &mut App, path: impl Into<String>) {
-    let path = path.into();
-    cx.update_global::<GlobalRouter, _>(|router, cx| {
-        router.push(path, cx);
-    });
-    cx.refresh_windows();
-}
+        // Step 5: Perform actual navigation + resolve match stack
+        self.progress
+            .advance(&request.to, NavigationStage::Resolving);
+        let (HistoryMutation { event, .. }, previous) =
+            match self.perform_navigation(request.to.clone(), op, &request.extensions) {
+                Ok(performed) => performed,
+                Err(result) => return result,
+            };
+        #[cfg(feature = "transition")]
+        self.begin_transition(from, previous, &request, op, redirect_depth, cx);
+        #[cfg(not(feature = "transition"))]
+        drop(previous);
+        self.sync_route_state(&event.to, op, cx);
 
-/// Return the current path from the global router.
-pub fn current_path(cx: &App) -> String {
-    cx.router().current_path().to_string()
-}
+        if self.state.match_stack().is_empty() {
+            if let Some(result) = self.handle_not_found(&event.to, cx, redirect_depth) {
+                return result;
+            }
+        }
 
-// ============================================================================
-// NavigatorHandle
-// ============================================================================
+        request.matched_pattern = self.state.match_stack().leaf_pattern().map(str::to_string);
 
-/// Handle returned by [`Navigator::of`] for fluent chained navigation.
-///
-/// Each method consumes and returns `self`, allowing patterns like:
-///
-/// ```ignore
-/// Navigator::of(cx)
-///     .push("/users")
-///     .push("/users/42");
-/// ```
-#[must_use]
+        if let Some(result) = self.enter_current(&request, &event.to, cx, redirect_depth) {
+            return result;
+        }
+
+        // Step 7: Run after middleware
+        #[cfg(feature = "middleware")]
+        self.run_middleware_after(cx, &request);
+
+        info_log!(
+            "Navigation complete: '{}' (stack depth: {})",
+            event.to,
+            self.state.match_stack().len()
+        );
+        #[cfg(feature = "transition")]
+        {
+            self.navigation_source = self.source_of(op, redirect_depth);
+            self.last_navigation = Some(request);
+            self.heroes.begin(Instant::now());
+        }
+        NavigationResult::Success { path: event.to }
+    }
+
+    /// Leave the levels the navigation drops (steps 2–4): check they allow
+    /// deactivation, run before middleware, then their `on_exit` hooks.
+    ///
+    /// Returns the outcome when leaving is blocked or redirected.
+    fn leave_current(
+        &mut self,
+        request: &NavigationRequest,
+        from: &str,
+        cx: &App,
+        redirect_depth: usize,
+    ) -> Option<NavigationResult> {
+        // Step 2: Check if the levels being left allow deactivation (lifecycle)
+        self.progress
+            .advance(&request.to, NavigationStage::Lifecycle);
+        let target = self.resolve_path(&request.to);
+        match self.run_lifecycle_can_deactivate(cx, Some(&target)) {
+            NavigationAction::Continue => {}
+            NavigationAction::Deny { reason } => {
+                warn_log!(
+                    "Lifecycle can_deactivate blocked leaving '{}': {}",
+                    from,
+                    reason
+                );
+                return Some(NavigationResult::Blocked {
+                    reason,
+                    redirect: None,
+                });
+            }
+            NavigationAction::Redirect { to, .. } => {
+                return Some(self.navigate_with_pipeline(
+                    to,
+                    cx,
+                    NavigateOp::Push,
+                    redirect_depth + 1,
+                ));
+            }
+            NavigationAction::Challenge { challenge_id, .. } => {
+                warn_log!(
+                    "Lifecycle can_deactivate issued challenge '{}'; only guards can challenge",
+                    challenge_id
+                );
+                return Some(NavigationResult::Blocked {
+                    reason: format!("Unsupported challenge '{challenge_id}' from can_deactivate"),
+                    redirect: None,
+                });
+            }
+        }
+
+        // Step 3: Run before middleware
+        #[cfg(feature = "middleware")]
+        self.run_middleware_before(cx, request);
+
+        // Step 4: Run on_exit lifecycle on the levels being left
+        if let NavigationAction::Deny { reason } = self.run_lifecycle_on_exit(cx, &target) {
+            warn_log!("Lifecycle on_exit blocked leaving '{}': {}", from, reason);
+            return Some(NavigationResult::Blocked {
+                reason,
+                redirect: None,
+            });
+        }
+        self.run_on_deactivate(cx);
+        None
+    }
+
+    /// Queue the transition event for the hooks and keep the previous stack
+    /// rendered for its exit animation.
+    #[cfg(feature = "transition")]
+    fn begin_transition(
+        &mut self,
+        from: &str,
+        previous: MatchStack,
+        request: &NavigationRequest,
+        op: NavigateOp,
+        redirect_depth: usize,
+        cx: &App,
+    ) {
+        let source = self.source_of(op, redirect_depth);
+        let window = self.exit_window(&previous, Some(request), source);
+        if !self.transition_hooks.is_empty() {
+            let event = self.transition_event(from, &previous, request, source, window);
+            self.pending_transition = Some(event);
+        }
+        self.keep_previous_stack(previous, window, cx);
+    }
+
+    /// Bring per-route state in line with the new match stack: scroll
+    /// intent, tasks, timers and loader data.
+    fn sync_route_state(&mut self, to: &str, op: NavigateOp, cx: &App) {
+        self.scroll_intent = self.compute_scroll_intent(op);
+        self.route_tasks.sync(self.state.match_stack(), cx);
+        self.route_timers.sync(self.state.match_stack(), cx);
+        let progress = &mut self.progress;
+        self.loader_data = LoaderData::load(
+            self.state.match_stack(),
+            &self.param_types,
+            cx,
+            |completed, total| {
+                progress.advance(to, NavigationStage::Loading { completed, total });
+            },
+        );
+        self.record_load_errors();
+    }
+
+    /// Enter the new levels (step 6): activate them and run their
+    /// `on_enter` hooks.
+    ///
+    /// Returns the outcome when a hook redirects; a denial only warns, as the
+    /// navigation has already happened.
+    fn enter_current(
+        &mut self,
+        request: &NavigationRequest,
+        to: &str,
+        cx: &App,
+        redirect_depth: usize,
+    ) -> Option<NavigationResult> {
+        // Step 6: Run on_enter lifecycle on new route
+        self.progress.advance(to, NavigationStage::Committing);
+        self.run_on_activate(cx);
+        match self.run_lifecycle_on_enter(cx, request) {
+            NavigationAction::Continue => {}
+            NavigationAction::Deny { reason } => {
+                // Navigation already happened — log warning but don't revert
+                warn_log!("Lifecycle on_enter denied entry to '{}': {}", to, reason);
+            }
+            NavigationAction::Redirect { to, .. } => {
+                return Some(self.navigate_with_pipeline(
+                    to,
+                    cx,
+                    NavigateOp::Push,
+                    redirect_depth + 1,
+                ));
+            }
+            NavigationAction::Challenge { challenge_id, .. } => {
+                warn_log!(
+                    "Lifecycle on_enter issued challenge '{}'; only guards can challenge",
+                    challenge_id
+                );
+            }
+        }
+        None
+    }
+
+    /// Classify a navigation committing now, for [`TransitionSources`].
+    #[cfg(feature = "transition")]
+    const fn source_of(&self, op: NavigateOp, redirect_depth: usize) -> NavigationSource {
+        match op {
+            _ if self.last_navigation.is_none() => NavigationSource::Initial,
+            _ if redirect_depth > 0 => NavigationSource::Redirect,
+            NavigateOp::Push => NavigationSource::Push,
+            NavigateOp::Replace => NavigationSource::Replace,
+            NavigateOp::Back => NavigationSource::Back,
+            NavigateOp::Forward => NavigationSource::Forward,
+        }
+    }
+
+    /// Turn a guard's verdict into a pipeline outcome.
+    ///
+    /// Returns `None` when navigation should proceed past the guards, or the
+    /// final result otherwise. A challenge suspends the pipeline by storing a
+    /// [`PendingChallenge`], unless its guard has exhausted
+    /// [`MAX_CHALLENGE_ROUNDS`].
+    #[cfg(feature = "guard")]
+    fn apply_guard_action(
+        &mut self,
+        action: NavigationAction,
+        request: &NavigationRequest,
+        step: GuardStep,
+        cx: &App,
+    ) -> Option<NavigationResult> {
+        match action {
+            NavigationAction::Continue => None,
+            NavigationAction::Deny { reason } => {
+                warn_log!("Navigation to '{}' blocked: {}", request.to, reason);
+                Some(NavigationResult::Blocked {
+                    reason,
+                    redirect: None,
+                })
+            }
+            NavigationAction::Redirect { to, reason } => {
+                debug_log!(
+                    "Guard redirecting from '{}' to '{}': {:?}",
+                    request.to,
+                    to,
+                    reason
+                );
+                Some(self.navigate_with_pipeline(to, cx, NavigateOp::Push, step.redirect_depth + 1))
+            }
+            NavigationAction::Challenge {
+                challenge_id,
+                payload,
+            } => {
+                let round = step.round + 1;
+                if round > MAX_CHALLENGE_ROUNDS {
+                    warn_log!(
+                        "Challenge '{}' for '{}' failed after {} rounds",
+                        challenge_id,
+                        request.to,
+                        MAX_CHALLENGE_ROUNDS
+                    );
+                    return Some(NavigationResult::Blocked {
+                        reason: format!(
+                            "Challenge '{challenge_id}' failed after {MAX_CHALLENGE_ROUNDS} rounds"
+                        ),
+                        redirect: None,
+                    });
+                }
+                debug_log!(
+                    "Guard challenged navigation to '{}' with '{}' (round {})",
+                    request.to,
+                    challenge_id,
+                    round
+                );
+                self.pending_challenge = Some(PendingChallenge {
+                    path: request.to.clone(),
+                    challenge_id: challenge_id.clone(),
+                    payload,
+                    step: GuardStep { round, ..step },
+                    extensions: request.extensions.clone(),
+                });
+                Some(NavigationResult::Challenged {
+                    path: request.to.clone(),
+                    challenge_id,
+                })
+            }
+        }
+    }
+
+    /// Apply [`NotFoundBehavior`] after navigating to an unmatched `path`.
+    ///
+    /// Returns `None` to keep the unmatched path (rendering the 404 page).
+    fn handle_not_found(
+        &mut self,
+        path: &str,
+        cx: &App,
+        redirect_depth: usize,
+    ) -> Option<NavigationResult> {
+        let NotFoundBehavior::Redirect(fallback) = &self.not_found_behavior else {
+            return None;
+        };
+        let fallback = self.localize_path(fallback.clone());
+        if fallback == path {
+            // The fallback itself is unmatched — stop instead of looping
+            warn_log!("Not-found fallback '{}' matches no route", fallback);
+            return Some(NavigationResult::NotFound {
+                path: path.to_string(),
+            });
+        }
+        debug_log!("No route matches '{}', redirecting to '{}'", path, fallback);
+        Some(self.navigate_with_pipeline(fallback, cx, NavigateOp::Replace, redirect_depth + 1))
+    }
+
+    // ========================================================================
+    // Navigation execution
+    // ========================================================================
+
+    /// Perform the actual history mutation, cache clear, and match stack resolution.
+    ///
+    /// The request's `extensions` move onto the new leaf entry.
+    ///
+    /// Returns `Ok` with the history mutation and the replaced match stack,
+    /// or `Err` if there was no entry to go back or forward to.
+    fn perform_navigation(
+        &mut self,
+        path: String,
+        op: NavigateOp,
+        extensions: &Extensions,
+    ) -> Result<(HistoryMutation, MatchStack), NavigationResult> {
+        #[cfg(feature = "cache")]
+        self.nested_cache.clear();
+
+        let mutation = match op {
+            NavigateOp::Push => Ok(self.state.push_unresolved(path)),
+            NavigateOp::Replace => Ok(self.state.replace_unresolved(path)),
+            NavigateOp::Back => self.state.back_unresolved(),
+            NavigateOp::Forward => self.state.forward_unresolved(),
+        }
+        .map_err(|error| NavigationResult::Error(error.into()))?;
+
+        #[cfg(test)]
+        {
+            self.resolutions += 1;
+        }
+        let mut stack = self.resolve_current();
+        stack.set_leaf_extensions(extensions.clone());
+        let previous = std::mem::replace(self.state.match_stack_mut(), stack);
+        self.navigation_sequence += 1;
+        self.route_invalidated = None;
+        self.publish_snapshot();
+        if mutation.truncated_forward() {
+            self.evict_truncated_components(&mutation.truncated);
+        }
+        Ok((mutation, previous))
+    }
+
+    /// Drop cached components that only truncated forward entries could reach.
+    ///
+    /// Component cache keys are derived from the [`MatchEntry::key`] of the
+    /// level a component route rendered at (without the params for
+    /// `Route::component`), not the URL, so each truncated path is
+    /// resolved and mapped to the keys of the component routes in its match
+    /// stack. Keys that a path still in history also produces are kept — a
+    /// shared layout or a param-less component must survive. Entries a
+    /// `Route::component_with_dep` route cached under the key go too,
+    /// whatever dependency value they were built for.
+    fn evict_truncated_components(&mut self, truncated: &[String]) {
+        let keys_for = |router: &Self, path: &str| -> Vec<String> {
+            router
+                .resolve_path(path)
+                .entries()
+                .iter()
+                .filter_map(|entry| entry.route.component_cache_key(entry))
+                .collect()
+        };
+
+        let live: std::collections::HashSet<String> = self
+            .state
+            .history()
+            .entries()
+            .iter()
+            .flat_map(|entry| keys_for(self, &entry.path))
+            .collect();
+
+        for path in truncated {
+            for key in keys_for(self, path) {
+                if live.contains(&key) {
+                    continue;
+                }
+                let cached: Vec<String> = self
+                    .component_cache
+                    .keys()
+                    .filter(|cached| is_key_or_dependency(cached, &key))
+                    .map(str::to_string)
+                    .collect();
+                for cached in cached {
+                    if self.component_cache.is_pinned(&cached) {
+                        continue;
+                    }
+                    if self.component_cache.remove(&cached) {
+                        debug_log!(
+                            "Evicted component '{}' of truncated path '{}'",
+                            cached,
+                            path
+                        );
+                    }
+                }
+            }
+        }
+
+        self.evict_truncated_route_state(truncated);
+    }
+
+    /// Drop URL-keyed route state that only truncated forward entries could reach.
+    ///
+    /// Works like the component eviction above, on the accumulated URL of
+    /// every level of the match stack. Pattern-keyed slots are shared by
+    /// all params of a route and are never dropped here.
+    fn evict_truncated_route_state(&mut self, truncated: &[String]) {
+        let urls_for = |router: &Self, path: &str| -> Vec<String> {
+            router
+                .resolve_path(path)
+                .entries()
+                .iter()
+                .map(|entry| entry.accumulated_url().to_string())
+                .collect()
+        };
+
+        let live: std::collections::HashSet<String> = self
+            .state
+            .history()
+            .entries()
+            .iter()
+            .flat_map(|entry| urls_for(self, &entry.path))
+            .collect();
+
+        for path in truncated {
+            for url in urls_for(self, path) {
+                if !live.contains(&url) {
+                    self.route_state.remove_url(&url);
+                }
+            }
+        }
+    }
+
+    // ========================================================================
+    // Lifecycle hooks
+    // ========================================================================
+
+    /// Run `can_deactivate` on every level leaving for `target`, deepest
+    /// first, until one does not continue.
+    fn run_lifecycle_can_deactivate(
+        &self,
+        cx: &App,
+        target: Option<&MatchStack>,
+    ) -> NavigationAction {
+        leaving_entries(self.state.match_stack(), target)
+            .filter_map(|entry| entry.route.lifecycle.as_deref())
+            .map(|lifecycle| lifecycle.can_deactivate(cx))
+            .find(|action| !action.is_continue())
+            .unwrap_or(NavigationAction::Continue)
+    }
+
+    /// Run `on_exit` on every level leaving for `target`, deepest first,
+    /// until one does not continue.
+    fn run_lifecycle_on_exit(&self, cx: &App, target: &MatchStack) -> NavigationAction {
+        leaving_entries(self.state.match_stack(), Some(target))
+            .filter_map(|entry| entry.route.lifecycle.as_deref())
+            .map(|lifecycle| lifecycle.on_exit(cx))
+            .find(|action| !action.is_continue())
+            .unwrap_or(NavigationAction::Continue)
+    }
+
+    /// Run the `on_deactivate` callback of the current leaf (if any).
+    fn run_on_deactivate(&self, cx: &App) {
+        if let Some(leaf) = self.leaf() {
+            if let Some(deactivate) = &leaf.route.on_deactivate {
+                deactivate(cx);
+            }
+        }
+    }
+
+    /// Run the `on_activate` callback of the new leaf (if any).
+    fn run_on_activate(&self, cx: &App) {
+        if let Some(leaf) = self.leaf() {
+            if let Some(activate) = &leaf.route.on_activate {
+                activate(cx, &leaf.params);
+            }
+        }
+    }
+
+    /// Collect the `protect_unsaved` scopes of routes in `stack`.
+    fn protected_scopes(stack: &MatchStack) -> Vec<(usize, Arc<Route>)> {
+        stack
+            .entries()
+            .iter()
+            .filter(|entry| entry.route.unsaved_scope.is_some())
+            .map(|entry| (entry.depth, Arc::clone(&entry.route)))
+            .collect()
+    }
+
+    /// Clear dirty scopes of protected routes that left the match stack
+    /// without their lifecycle being asked, e.g. on a restore or a route
+    /// table change.
+    fn clear_exited_scopes(&self, protected: Vec<(usize, Arc<Route>)>, cx: &App) {
+        for (depth, route) in protected {
+            let still_matched = self
+                .state
+                .match_stack()
+                .at_depth(depth)
+                .is_some_and(|entry| Arc::ptr_eq(&entry.route, &route));
+            if let (false, Some(scope)) = (still_matched, route.unsaved_scope.as_deref()) {
+                debug_log!(
+                    "Route '{}' exited, clearing scope '{}'",
+                    route.config.path,
+                    scope
+                );
+                DirtyStateRegistry::mark_clean(cx, scope);
+            }
+        }
+    }
+
+    /// Run `on_enter` on the new leaf's lifecycle (if any).
+    fn run_lifecycle_on_enter(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+        if let Some(leaf) = self.leaf() {
+            if let Some(ref lifecycle) = leaf.route.lifecycle {
+                return lifecycle.on_enter(cx, request);
+            }
+        }
+        NavigationAction::Continue
+    }
+
+    /// Collect the guards protecting the target path, in evaluation order.
+    ///
+    /// Walks the route tree to find the target route, collecting guards from
+    /// every ancestor route along the way. Guards on parent routes also protect
+    /// child routes (e.g. an `AuthGuard` on `/dashboard` also guards `/dashboard/settings`),
+    /// while [`Route::children_guard`] guards only apply when the target lies
+    /// below the route that declares them.
+    ///
+    /// The order is deterministic: priority (desc), route depth (asc),
+    /// declaration index on the route (asc), then accumulated route pattern.
+    /// Copies of a [deduplicated](crate::RouteGuard::deduplicate) guard after
+    /// the first are dropped.
+    #[cfg(feature = "guard")]
+    fn collect_guards(&self, path: &str) -> Vec<CollectedGuard<'_>> {
+        let path = trim_slashes(self.split_locale(path).1);
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let mut guards: Vec<CollectedGuard<'_>> = Vec::new();
+
+        // Collect guards from matching routes (including ancestor routes)
+        for route in self.state.routes().iter().filter(|r| self.is_enabled(r)) {
+            Self::collect_guards_recursive(route, &segments, "", &mut guards);
+        }
+
+        guards.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then(a.depth.cmp(&b.depth))
+                .then(a.index.cmp(&b.index))
+                .then_with(|| a.route.cmp(&b.route))
+        });
+
+        // Deduplicated guards run at their first position only
+        let mut seen = std::collections::HashSet::new();
+        guards.retain(|collected| {
+            let guard = collected.guard;
+            !guard.deduplicate() || seen.insert((guard.name(), guard.dedup_key()))
+        });
+
+        debug_log!("Collected {} guards for '{}'", guards.len(), path);
+        guards
+    }
+
+    /// Return the order in which guards would run for a navigation to `path`.
+    ///
+    /// Useful for debugging which guard wins when several share a priority.
+    #[cfg(feature = "guard")]
+    #[must_use]
+    pub fn guard_order(&self, path: &str) -> Vec<crate::guards::GuardOrderEntry> {
+        self.collect_guards(path)
+            .into_iter()
+            .map(|collected| crate::guards::GuardOrderEntry {
+                name: collected.guard.name(),
+                priority: collected.priority,
+                depth: collected.depth,
+                index: collected.index,
+                route: format!("/{}", collected.route),
+            })
+            .collect()
+    }
+
+    /// Dry-run the guards for a navigation to `path`.
+    ///
+    /// Guards are collected and ordered exactly as [`push`](Self::push) would,
+    /// and guard redirects are followed up to the redirect limit, but each
+    /// guard answers through [`RouteGuard::probe`](crate::RouteGuard::probe)
+    /// and nothing else runs: middleware, lifecycle hooks, history and any
+    /// pending challenge are left untouched. The cost is one probe per guard
+    /// on the way, cheap enough to call on every render.
+    ///
+    /// Only guards are consulted, so a `can_deactivate` hook of the current
+    /// route can still block the real navigation. Without the `guard`
+    /// feature every path is allowed.
+    #[must_use]
+    pub fn can_navigate(&self, cx: &App, path: &str) -> NavigationProbe {
+        let from = self.current_path();
+        let mut target = self.localize_path(path.to_string());
+        // Reason of the first redirect, once a guard redirected
+        let mut redirected: Option<Option<String>> = None;
+
+        for _ in 0..MAX_REDIRECT_DEPTH {
+            let request = self.build_request(&target, from);
+            match self.probe_guards(cx, &request) {
+                NavigationAction::Continue => {
+                    let Some(reason) = redirected else {
+                        return NavigationProbe::allowed();
+                    };
+                    return NavigationProbe {
+                        allowed: false,
+                        reason,
+                        redirect: Some(target),
+                        challenge: None,
+                    };
+                }
+                NavigationAction::Deny { reason } => return NavigationProbe::blocked(reason),
+                NavigationAction::Redirect { to, reason } => {
+                    redirected.get_or_insert(reason);
+                    target = self.localize_path(to);
+                }
+                NavigationAction::Challenge { challenge_id, .. } => {
+                    return NavigationProbe {
+                        challenge: Some(challenge_id),
+                        ..NavigationProbe::allowed()
+                    };
+                }
+            }
+        }
+
+        NavigationProbe::blocked(format!(
+            "Redirect loop detected (depth {MAX_REDIRECT_DEPTH}): target '{target}'"
+        ))
+    }
+
+    /// Probe the guards for `request` in pipeline order; first non-`Continue` wins.
+    #[cfg(feature = "guard")]
+    fn probe_guards(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+        self.collect_guards(&request.to)
+            .into_iter()
+            .map(|collected| collected.guard.probe(cx, request))
+            .find(|action| !matches!(action, NavigationAction::Continue))
+            .unwrap_or(NavigationAction::Continue)
+    }
+
+    #[cfg(not(feature = "guard"))]
+    #[allow(clippy::unused_self)]
+    const fn probe_guards(&self, _cx: &App, _request: &NavigationRequest) -> NavigationAction {
+        NavigationAction::Continue
+    }
+
+    /// Explain what a navigation to `path` would do, without performing it.
+    ///
+    /// Walks the pipeline as [`push`](Self::push) would: guards in evaluation
+    /// order, `can_deactivate` of the levels being left, match stack
+    /// resolution with [`NotFoundBehavior`], then `on_enter` of the route
+    /// being entered, following redirects up to the redirect limit. Guards
+    /// answer through [`RouteGuard::probe`](crate::RouteGuard::probe) and
+    /// lifecycle hooks through
+    /// [`probe_can_deactivate`](crate::RouteLifecycle::probe_can_deactivate)
+    /// and [`probe_enter`](crate::RouteLifecycle::probe_enter); history,
+    /// middleware, loaders and any pending challenge are left untouched.
+    ///
+    /// Unlike [`can_navigate`](Self::can_navigate) this covers the whole
+    /// pipeline and records every decision, see [`crate::explain`].
+    #[cfg(feature = "explain")]
+    #[must_use]
+    pub fn explain_navigation(&self, cx: &App, path: &str) -> NavigationExplanation {
+        let mut explanation = NavigationExplanation::new(path);
+        let mut from = self.current_path().to_string();
+        let mut target = self.localize_path(path.to_string());
+        // Stack whose levels' `can_deactivate` answer the next hop
+        let mut current = self.state.match_stack().clone();
+
+        for _ in 0..MAX_REDIRECT_DEPTH {
+            let request = self.build_request(&target, &from);
+            let (to, cause) = match self.explain_hop(cx, &request, &mut current, &mut explanation) {
+                ControlFlow::Continue(hop) => hop,
+                ControlFlow::Break(outcome) => {
+                    explanation.outcome = outcome;
+                    explanation.patterns = self.explained_patterns(&target);
+                    return explanation;
+                }
+            };
+            if cause.commits() {
+                from.clone_from(&target);
+            }
+            let to = self.localize_path(to);
+            explanation.hops.push(RedirectHop {
+                from: std::mem::replace(&mut target, to.clone()),
+                to,
+                cause,
+            });
+        }
+
+        explanation.outcome = NavigationResult::Blocked {
+            reason: format!(
+                "Redirect loop detected (depth {MAX_REDIRECT_DEPTH}): target '{target}'"
+            ),
+            redirect: None,
+        };
+        explanation.patterns = self.explained_patterns(&target);
+        explanation
+    }
+
+    /// Explain one pass of the pipeline for `request`.
+    ///
+    /// Continues with the redirect to follow, or breaks with the outcome.
+    #[cfg(feature = "explain")]
+    fn explain_hop(
+        &self,
+        cx: &App,
+        request: &NavigationRequest,
+        current: &mut MatchStack,
+        explanation: &mut NavigationExplanation,
+    ) -> ControlFlow<NavigationResult, (String, RedirectCause)> {
+        if let Some((pattern, to)) = self.route_redirect(&request.to, cx) {
+            return ControlFlow::Continue((to, RedirectCause::Route { pattern }));
+        }
+
+        // Step 1: guards
+        match self.explain_guards(cx, request, explanation) {
+            NavigationAction::Continue => {}
+            NavigationAction::Deny { reason } => {
+                return ControlFlow::Break(NavigationResult::Blocked {
+                    reason,
+                    redirect: None,
+                });
+            }
+            NavigationAction::Redirect { to, reason } => {
+                let guard = explanation
+                    .guards
+                    .last()
+                    .map_or("", |decision| decision.guard);
+                return ControlFlow::Continue((to, RedirectCause::Guard { guard, reason }));
+            }
+            NavigationAction::Challenge { challenge_id, .. } => {
+                return ControlFlow::Break(NavigationResult::Challenged {
+                    path: request.to.clone(),
+                    challenge_id,
+                });
+            }
+        }
+
+        // Step 2: can_deactivate of the levels being left
+        let target = self.resolve_path(&request.to);
+        for entry in leaving_entries(current, Some(&target)) {
+            let Some(lifecycle) = entry.route.lifecycle.as_deref() else {
+                continue;
+            };
+            let action = lifecycle.probe_can_deactivate(cx);
+            explanation.lifecycle.push(LifecycleDecision {
+                path: request.to.clone(),
+                route: entry.accumulated_pattern().to_string(),
+                hook: LifecycleHook::CanDeactivate,
+                action: action.clone(),
+            });
+            match action {
+                NavigationAction::Continue => {}
+                NavigationAction::Deny { reason } => {
+                    return ControlFlow::Break(NavigationResult::Blocked {
+                        reason,
+                        redirect: None,
+                    });
+                }
+                NavigationAction::Redirect { to, reason } => {
+                    return ControlFlow::Continue((to, RedirectCause::CanDeactivate { reason }));
+                }
+                NavigationAction::Challenge { challenge_id, .. } => {
+                    return ControlFlow::Break(NavigationResult::Blocked {
+                        reason: format!(
+                            "Unsupported challenge '{challenge_id}' from can_deactivate"
+                        ),
+                        redirect: None,
+                    });
+                }
+            }
+        }
+
+        // Step 5: resolution, from here on the navigation is committed
+        *current = target;
+        let Some(entry) = current.leaf() else {
+            return match &self.not_found_behavior {
+                NotFoundBehavior::Redirect(fallback)
+                    if self.localize_path(fallback.clone()) == request.to =>
+                {
+                    ControlFlow::Break(NavigationResult::NotFound {
+                        path: request.to.clone(),
+                    })
+                }
+                NotFoundBehavior::Redirect(fallback) => {
+                    ControlFlow::Continue((fallback.clone(), RedirectCause::NotFound))
+                }
+                NotFoundBehavior::Render => ControlFlow::Break(NavigationResult::Success {
+                    path: request.to.clone(),
+                }),
+            };
+        };
+
+        // Step 6: on_enter of the route being entered; only a redirect
+        // changes the outcome once the navigation happened
+        if let Some(lifecycle) = entry.route.lifecycle.as_deref() {
+            let mut request = request.clone();
+            request.matched_pattern = Some(entry.accumulated_pattern().to_string());
+            let action = lifecycle.probe_enter(cx, &request);
+            explanation.lifecycle.push(LifecycleDecision {
+                path: request.to,
+                route: entry.accumulated_pattern().to_string(),
+                hook: LifecycleHook::OnEnter,
+                action: action.clone(),
+            });
+            if let NavigationAction::Redirect { to, reason } = action {
+                return ControlFlow::Continue((to, RedirectCause::OnEnter { reason }));
+            }
+        }
+
+        ControlFlow::Break(NavigationResult::Success {
+            path: request.to.clone(),
+        })
+    }
+
+    /// Probe the guards for `request` in pipeline order, recording each answer.
+    #[cfg(all(feature = "explain", feature = "guard"))]
+    fn explain_guards(
+        &self,
+        cx: &App,
+        request: &NavigationRequest,
+        explanation: &mut NavigationExplanation,
+    ) -> NavigationAction {
+        for collected in self.collect_guards(&request.to) {
+            let action = collected.guard.probe(cx, request);
+            explanation.guards.push(crate::explain::GuardDecision {
+                path: request.to.clone(),
+                guard: collected.guard.name(),
+                route: format!("/{}", collected.route),
+                action: action.clone(),
+            });
+            if !action.is_continue() {
+                return action;
+            }
+        }
+        NavigationAction::Continue
+    }
+
+    #[cfg(all(feature = "explain", not(feature = "guard")))]
+    #[allow(clippy::unused_self)]
+    const fn explain_guards(
+        &self,
+        _cx: &App,
+        _request: &NavigationRequest,
+        _explanation: &mut NavigationExplanation,
+    ) -> NavigationAction {
+        NavigationAction::Continue
+    }
+
+    /// Accumulated patterns of the match stack `path` would render.
+    #[cfg(feature = "explain")]
+    fn explained_patterns(&self, path: &str) -> Vec<String> {
+        self.resolve_path(path)
+            .entries()
+            .iter()
+            .map(|entry| entry.accumulated_pattern().to_string())
+            .collect()
+    }
+
+    /// Run guards for the target path, starting at position `start`.
+    ///
+    /// Returns the first non-`Continue` result together with the position of
+    /// the guard that produced it, so a challenge can resume at that guard.
+    #[cfg(feature = "guard")]
+    fn run_guards(
+        &self,
+        cx: &App,
+        request: &NavigationRequest,
+        start: usize,
+    ) -> (NavigationAction, usize) {
+        let guards = self.collect_guards(&request.to);
+
+        // Check each guard — first non-Continue result wins
+        for (index, collected) in guards.iter().enumerate().skip(start) {
+            let guard = collected.guard;
+            let result = crate::guards::check_cached(guard, cx, request);
+            trace_log!(
+                "Guard '{}' (priority {}, depth {}, index {}) → {:?}",
+                guard.name(),
+                collected.priority,
+                collected.depth,
+                collected.index,
+                result
+            );
+            if !matches!(result, NavigationAction::Continue) {
+                debug_log!(
+                    "Guard '{}' blocked navigation to '{}'",
+                    guard.name(),
+                    request.to
+                );
+                return (result, index);
+            }
+        }
+
+        (NavigationAction::Continue, guards.len())
+    }
+
+    /// Recursively walk the route tree, collecting guards from routes that match
+    /// the given path (as exact match or prefix).
+    #[cfg(feature = "guard")]
+    fn collect_guards_recursive<'a>(
+        route: &'a Arc<Route>,
+        segments: &[&str],
+        accumulated: &str,
+        out: &mut Vec<CollectedGuard<'a>>,
+    ) {
+        walk_matching_routes(
+            route,
+            segments,
+            accumulated,
+            0,
+            &mut |r, full, depth, exact| {
+                // Children-only guards rank after the route's own guards
+                let children = r.children_guards.iter().filter(|_| !exact);
+                for (index, guard) in r.guards.iter().chain(children).enumerate() {
+                    out.push(CollectedGuard {
+                        guard: guard.as_ref(),
+                        priority: guard.priority(),
+                        depth,
+                        index,
+                        route: full.to_string(),
+                    });
+                }
+            },
+        );
+    }
+
+    /// Run `before_navigation` on the middleware of the target's match stack.
+    ///
+    /// Records each of them so it hears how the navigation settles.
+    #[cfg(feature = "middleware")]
+    fn run_middleware_before(&mut self, cx: &App, request: &NavigationRequest) {
+        let stack = self.resolve_path(&request.to);
+        let middleware = self.ordered_middleware(&stack, false);
+
+        debug_log!(
+            "Running {} before-middleware for '{}'",
+            middleware.len(),
+            request.to
+        );
+        for mw in middleware {
+            trace_log!(
+                "Middleware '{}' before_navigation for '{}'",
+                mw.name(),
+                request.to
+            );
+            mw.before_navigation(cx, request);
+        }
+        for entry in stack.entries() {
+            for (index, mw) in entry.route.middleware.iter().enumerate() {
+                if self.middleware_enabled(mw.as_ref()) {
+                    self.settlement.record(&entry.route, index);
+                }
+            }
+        }
+    }
+
+    /// Run `after_navigation` on the middleware of the target's match stack.
+    #[cfg(feature = "middleware")]
+    fn run_middleware_after(&self, cx: &App, request: &NavigationRequest) {
+        let stack = self.resolve_path(&request.to);
+        let middleware = self.ordered_middleware(&stack, true);
+
+        debug_log!(
+            "Running {} after-middleware for '{}'",
+            middleware.len(),
+            request.to
+        );
+        for mw in middleware {
+            trace_log!(
+                "Middleware '{}' after_navigation for '{}'",
+                mw.name(),
+                request.to
+            );
+            mw.after_navigation(cx, request);
+        }
+    }
+
+    /// Tell every middleware whose `before_navigation` ran during the
+    /// navigation how it settled, in reverse order of their first run.
+    ///
+    /// Called once per top-level navigation, after redirects and fallbacks
+    /// resolved, with the request the navigation started from.
+    #[cfg(feature = "middleware")]
+    fn settle_middleware(&mut self, cx: &App, result: &NavigationResult) {
+        let Settlement { request, ran } = std::mem::take(&mut self.settlement);
+        let Some(request) = request else {
+            return;
+        };
+        let error = result.clone().into_result().err();
+        for (route, index) in ran.iter().rev() {
+            let mw = route.middleware[*index].as_ref();
+            match &error {
+                None => mw.on_navigation_complete(cx, &request, result),
+                Some(error) => mw.on_navigation_failed(cx, &request, error),
+            }
+        }
+    }
+
+    /// Middleware of every route in `stack`, in the order the `before` (or,
+    /// with `after`, the `after`) phase runs them.
+    #[cfg(feature = "middleware")]
+    fn ordered_middleware<'a>(
+        &self,
+        stack: &'a MatchStack,
+        after: bool,
+    ) -> Vec<&'a dyn crate::middleware::RouteMiddleware> {
+        let mut middleware: Vec<_> = stack
+            .entries()
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .route
+                    .middleware
+                    .iter()
+                    .map(move |mw| (mw.as_ref(), mw.priority(), entry.depth))
+            })
+            .filter(|(mw, _, _)| self.middleware_enabled(*mw))
+            .collect();
+        self.middleware_ordering.sort(&mut middleware, after);
+        middleware.into_iter().map(|(mw, _, _)| mw).collect()
+    }
+
+    /// Choose how middleware of nested routes are ordered.
+    ///
+    /// Defaults to [`MiddlewareOrdering::ByPriority`].
+    #[cfg(feature = "middleware")]
+    pub fn set_middleware_ordering(&mut self, ordering: crate::middleware::MiddlewareOrdering) {
+        self.middleware_ordering = ordering;
+    }
+
+    /// Return how middleware of nested routes are ordered.
+    #[cfg(feature = "middleware")]
+    #[must_use]
+    pub const fn middleware_ordering(&self) -> crate::middleware::MiddlewareOrdering {
+        self.middleware_ordering
+    }
+
+    /// Switch the middleware group `name` on or off.
+    ///
+    /// Middleware of a disabled group are skipped by every navigation until
+    /// it is enabled again. Groups start enabled; ungrouped middleware always
+    /// run. See [`RouteMiddleware::group`](crate::RouteMiddleware::group).
+    #[cfg(feature = "middleware")]
+    pub fn set_middleware_group_enabled(&mut self, name: impl Into<String>, enabled: bool) {
+        let name = name.into();
+        debug_log!(
+            "Middleware group '{}' {}",
+            name,
+            if enabled { "enabled" } else { "disabled" }
+        );
+        if enabled {
+            self.disabled_middleware_groups.remove(&name);
+        } else {
+            self.disabled_middleware_groups.insert(name);
+        }
+    }
+
+    /// Check whether the middleware group `name` is enabled.
+    #[cfg(feature = "middleware")]
+    #[must_use]
+    pub fn is_middleware_group_enabled(&self, name: &str) -> bool {
+        !self.disabled_middleware_groups.contains(name)
+    }
+
+    /// Whether `mw` runs: ungrouped, or in an enabled group.
+    #[cfg(feature = "middleware")]
+    fn middleware_enabled(&self, mw: &dyn crate::middleware::RouteMiddleware) -> bool {
+        mw.group()
+            .map_or(true, |group| self.is_middleware_group_enabled(group))
+    }
+
+    // ========================================================================
+    // Named routes
+    // ========================================================================
+
+    /// Navigate to a named route, resolving the URL from `params`.
+    ///
+    /// Returns `None` if the name is not registered or `params` lacks a
+    /// param its pattern declares.
+    pub fn push_named(
+        &mut self,
+        name: &str,
+        params: &RouteParams,
+        cx: &App,
+    ) -> Option<NavigationResult> {
+        let url = match self.named_routes.url_for_checked(name, params) {
+            Ok(url) => {
+                debug_log!("Named route '{}' resolved to '{}'", name, url);
+                url
+            }
+            Err(missing) if missing.is_empty() => {
+                warn_log!("Named route '{}' not found in registry", name);
+                return None;
+            }
+            Err(missing) => {
+                warn_log!(
+                    "Named route '{}' is missing params: {}",
+                    name,
+                    missing.join(", ")
+                );
+                return None;
+            }
+        };
+        Some(self.push(url, cx))
+    }
+
+    /// Generate a URL for a named route by substituting `params` into its pattern.
+    ///
+    /// If the current path carries a locale prefix, the generated URL is
+    /// prefixed with the same locale.
+    ///
+    /// Returns `None` if the name is not registered or `params` lacks a
+    /// param its pattern declares.
+    #[must_use]
+    pub fn url_for(&self, name: &str, params: &RouteParams) -> Option<String> {
+        self.url_for_checked(name, params).ok()
+    }
+
+    /// Generate a URL for a named route, reporting missing params.
+    ///
+    /// # Errors
+    ///
+    /// See [`NamedRouteRegistry::url_for_checked`].
+    pub fn url_for_checked(&self, name: &str, params: &RouteParams) -> Result<String, Vec<String>> {
+        self.named_routes
+            .url_for_checked(name, params)
+            .map(|url| self.localize_path(url))
+    }
+
+    /// Set the scheme and host [`canonical_url`](Self::canonical_url) prefixes.
+    ///
+    /// [`open_url`](Self::open_url) strips it again. A trailing slash is
+    /// dropped, so `myapp://app/` and `myapp://app` are the same base.
+    pub fn set_url_base(&mut self, base: impl Into<String>) {
+        let base = base.into();
+        self.url_base = Some(base.trim_end_matches('/').to_string());
+    }
+
+    /// Return the base set with [`set_url_base`](Self::set_url_base).
+    #[must_use]
+    pub fn url_base(&self) -> Option<&str> {
+        self.url_base.as_deref()
+    }
+
+    /// Build the canonical URL of `target`, suitable for sharing.
+    ///
+    /// The path is normalized and each segment percent-encoded; named
+    /// routes are filled from their registered pattern. Targets other than
+    /// [`CanonicalTarget::Current`] get the current locale prefix, as with
+    /// [`url_for`](Self::url_for). The [`url_base`](Self::url_base), if set,
+    /// is prefixed last. Passing the result to [`open_url`](Self::open_url)
+    /// resolves the same match stack as the target.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UrlForError::UnknownName`] or [`UrlForError::MissingParams`]
+    /// for a named target that cannot be filled, and [`UrlForError::NoMatch`]
+    /// when the path matches no route.
+    pub fn canonical_url(&self, target: CanonicalTarget<'_>) -> Result<String, UrlForError> {
+        let path = match target {
+            CanonicalTarget::Current => canonical_path(self.current_path()),
+            CanonicalTarget::Path(path) => canonical_path(&self.localize_path(path.to_string())),
+            CanonicalTarget::Named { name, params } => {
+                let Some(pattern) = self.named_routes.pattern(name) else {
+                    return Err(UrlForError::UnknownName {
+                        name: name.to_string(),
+                    });
+                };
+                let url =
+                    pattern
+                        .fill_encoded(params)
+                        .map_err(|missing| UrlForError::MissingParams {
+                            name: name.to_string(),
+                            params: missing.names,
+                        })?;
+                canonical_path(&self.localize_path(url))
+            }
+        };
+        if self.resolve_path(&path).is_empty() {
+            return Err(UrlForError::NoMatch { path });
+        }
+        let base = self.url_base.as_deref().unwrap_or_default();
+        Ok(format!("{base}{path}"))
+    }
+
+    /// Navigate to a URL built by [`canonical_url`](Self::canonical_url).
+    ///
+    /// The [`url_base`](Self::url_base) is stripped before pushing; a bare
+    /// path is pushed as it is. A URL with another scheme or host is not
+    /// navigated to and yields [`NavigationError::NavigationFailed`].
+    pub fn open_url(&mut self, url: &str, cx: &App) -> NavigationResult {
+        let path = self
+            .url_base
+            .as_deref()
+            .and_then(|base| url.strip_prefix(base))
+            .filter(|path| path.is_empty() || path.starts_with('/'))
+            .unwrap_or(url);
+        if path.contains("://") {
+            warn_log!("open_url: '{}' does not belong to this app", url);
+            return NavigationResult::Error(NavigationError::NavigationFailed {
+                message: format!("URL '{url}' does not belong to this app"),
+            });
+        }
+        self.push(normalize_path(path).into_owned(), cx)
+    }
+
+    // ========================================================================
+    // Locales
+    // ========================================================================
+
+    /// Register the locales recognised as an optional leading path segment.
+    ///
+    /// Once set, `/fr/dashboard` resolves against the registered `/dashboard`
+    /// route with a `locale` param of `"fr"`. Routes are registered without
+    /// the locale segment.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// router.set_locales(["en", "fr"]);
+    /// router.add_route(Route::new("/dashboard", dashboard));
+    /// router.push("/fr/dashboard".into(), cx); // locale = "fr"
+    /// ```
+    pub fn set_locales<I, S>(&mut self, locales: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.locales = locales.into_iter().map(Into::into).collect();
+        self.re_resolve();
+    }
+
+    /// Set the locale reported for paths without a locale prefix.
+    ///
+    /// Paths in the default locale stay unprefixed: navigating within them
+    /// and generating URLs from them does not add a locale segment.
+    pub fn set_default_locale(&mut self, locale: impl Into<String>) {
+        self.default_locale = Some(locale.into());
+        self.re_resolve();
+    }
+
+    /// Return the registered locales.
+    #[must_use]
+    pub fn locales(&self) -> &[String] {
+        &self.locales
+    }
+
+    /// Return the locale of the current path.
+    ///
+    /// This is the path's locale prefix if it has one, otherwise the default
+    /// locale (if set).
+    #[must_use]
+    pub fn current_locale(&self) -> Option<&str> {
+        self.split_locale(self.state.current_path())
+            .0
+            .or(self.default_locale.as_deref())
+    }
+
+    /// Build the [`NavigationRequest`] handed to guards, lifecycle hooks, and
+    /// middleware, carrying the target's `locale` param when locales are set.
+    fn build_request(&self, path: &str, from: &str) -> NavigationRequest {
+        let mut request = NavigationRequest::with_from(path.to_string(), from.to_string());
+        if let Some(locale) = self.split_locale(path).0.or(self.default_locale.as_deref()) {
+            request.params.insert("locale", locale);
+        }
+        request
+    }
+
+    /// Split a leading locale segment off `path`.
+    ///
+    /// Returns the locale (if the first segment is a registered locale) and
+    /// the remaining path to match against the route table.
+    fn split_locale<'p>(&self, path: &'p str) -> (Option<&'p str>, &'p str) {
+        if self.locales.is_empty() {
+            return (None, path);
+        }
+        let trimmed = path.trim_start_matches('/');
+        let (first, rest) = trimmed
+            .find('/')
+            .map_or((trimmed, ""), |pos| trimmed.split_at(pos));
+        if self.locales.iter().any(|l| l == first) {
+            (Some(first), rest)
+        } else {
+            (None, path)
+        }
+    }
+
+    /// Prefix `path` with the current path's locale, unless it already
+    /// carries one or the current path has no locale prefix.
+    fn localize_path(&self, path: String) -> String {
+        if self.split_locale(&path).0.is_some() {
+            return path;
+        }
+        match self.split_locale(self.state.current_path()).0 {
+            Some(locale) => {
+                let rest = path.trim_start_matches('/');
+                if rest.is_empty() {
+                    format!("/{locale}")
+                } else {
+                    format!("/{locale}/{rest}")
+                }
+            }
+            None => path,
+        }
+    }
+
+    // ========================================================================
+    // Accessors
+    // ========================================================================
+
+    /// Return the id of the navigation currently running, if any.
+    ///
+    /// Set while guards, middleware and lifecycle hooks of a navigation run,
+    /// so app-level logging can join the router's navigation span. This is
+    /// an associated function because [`GlobalRouter`] itself is not
+    /// readable from inside those hooks.
+    #[must_use]
+    pub fn current_navigation_id() -> Option<NavigationId> {
+        crate::logging::current_navigation()
+    }
+
+    /// Return the current navigation path.
+    #[must_use]
+    pub fn current_path(&self) -> &str {
+        self.state.current_path()
+    }
+
+    /// Return a handle to the current route that background threads can read.
+    ///
+    /// The handle is cheap to clone and never touches GPUI; see
+    /// [`route_snapshot`](crate::route_snapshot) for when it updates.
+    #[must_use]
+    pub fn snapshot_handle(&self) -> RouteSnapshotHandle {
+        self.route_snapshot.clone()
+    }
+
+    /// Deprecated mutable lookup, now the same as
+    /// [`current_match_immutable`](Self::current_match_immutable).
+    #[deprecated(
+        since = "0.2.0",
+        note = "Use `leaf()` / `leaf_params()`; the match is derived from the match stack"
+    )]
+    pub fn current_match(&mut self) -> Option<crate::RouteMatch> {
+        self.current_match_immutable()
+    }
+
+    /// Get the [`RouteMatch`](crate::RouteMatch) of the match stack's leaf.
+    #[must_use]
+    pub fn current_match_immutable(&self) -> Option<crate::RouteMatch> {
+        self.state.current_match_immutable()
+    }
+
+    /// Get the route of the match stack's leaf — the deepest matched route.
+    #[must_use]
+    pub fn current_route(&self) -> Option<&Arc<crate::route::Route>> {
+        self.state.current_route()
+    }
+
+    /// Get every route of the match stack, root to leaf.
+    ///
+    /// Unlike [`current_route`](Self::current_route), this includes the
+    /// layouts above the leaf, so a component can read inherited config
+    /// from its ancestors. Empty when nothing matched.
+    #[must_use]
+    pub fn current_route_chain(&self) -> Vec<Arc<Route>> {
+        self.match_stack()
+            .entries()
+            .iter()
+            .map(|entry| Arc::clone(&entry.route))
+            .collect()
+    }
+
+    /// List every navigable route for a generated help screen.
+    ///
+    /// See [`help_entries`](crate::help::help_entries) for what each entry
+    /// carries and how entries are grouped.
+    #[must_use]
+    pub fn help_entries(&self) -> Vec<HelpEntry> {
+        crate::help::help_entries(&self.enabled_routes())
+    }
+
+    /// Check if can go back.
+    #[must_use]
+    pub const fn can_go_back(&self) -> bool {
+        self.state.can_go_back()
+    }
+
+    /// Check if can go forward.
+    #[must_use]
+    pub fn can_go_forward(&self) -> bool {
+        self.state.can_go_forward()
+    }
+
+    /// Get mutable state reference.
+    pub fn state_mut(&mut self) -> &mut RouterState {
+        &mut self.state
+    }
+
+    /// Get state reference.
+    #[must_use]
+    pub const fn state(&self) -> &RouterState {
+        &self.state
+    }
+
+    /// Get nested route cache (mutable).
+    #[cfg(feature = "cache")]
+    pub fn nested_cache_mut(&mut self) -> &mut RouteCache {
+        &mut self.nested_cache
+    }
+
+    /// Statistics of the route resolution cache and the component cache.
+    ///
+    /// The route resolution counters stay zero without the `cache` feature.
+    #[must_use]
+    pub fn cache_stats(&self) -> CacheStats {
+        #[cfg(feature = "cache")]
+        let base = self.nested_cache.stats().clone();
+        #[cfg(not(feature = "cache"))]
+        let base = CacheStats::default();
+        CacheStats {
+            component_hits: self.component_stats.component_hits,
+            component_misses: self.component_stats.component_misses,
+            component_evictions: self.component_stats.component_evictions,
+            ..base
+        }
+    }
+
+    /// Return the capacities of the router's caches.
+    #[must_use]
+    pub const fn cache_policy(&self) -> CachePolicy {
+        self.cache_policy
+    }
+
+    /// Resize the route resolution cache and the component cache of every
+    /// partition, evicting the least recently used entries that no longer fit.
+    ///
+    /// Lowering the pinned capacity unpins the least recently used pinned
+    /// components first.
+    ///
+    /// ```
+    /// use gpui_navigator::{CachePolicy, GlobalRouter};
+    ///
+    /// let mut router = GlobalRouter::new();
+    /// router.set_cache_policy(CachePolicy::new().component_capacity(16));
+    /// assert_eq!(router.cache_policy().component_capacity, 16);
+    /// ```
+    pub fn set_cache_policy(&mut self, policy: CachePolicy) {
+        #[cfg(feature = "cache")]
+        self.nested_cache.resize(policy.route_capacity.max(1));
+        let mut evicted = self.component_cache.set_policy(policy);
+        for partition in self.partitions.values_mut() {
+            evicted += partition.component_cache.set_policy(policy);
+        }
+        self.component_stats.component_evictions += evicted;
+        self.cache_policy = policy;
+    }
+
+    /// Drop every cached route resolution and the cached components of
+    /// every partition.
+    ///
+    /// Components are rebuilt, with fresh state, the next time their route
+    /// renders.
+    pub fn clear_caches(&mut self) {
+        #[cfg(feature = "cache")]
+        self.nested_cache.clear();
+        self.component_cache.clear();
+        for partition in self.partitions.values_mut() {
+            partition.component_cache.clear();
+        }
+        debug_log!("Cleared route and component caches");
+    }
+
+    // ========================================================================
+    // Error handlers
+    // ========================================================================
+
+    /// Set custom error handlers for 404 and navigation errors.
+    pub fn set_error_handlers(&mut self, handlers: ErrorHandlers) {
+        self.error_handlers = handlers;
+    }
+
+    /// Get a reference to the current error handlers.
+    pub const fn error_handlers(&self) -> &ErrorHandlers {
+        &self.error_handlers
+    }
+
+    /// Choose what happens when a navigation matches no route.
+    ///
+    /// With [`NotFoundBehavior::Redirect`], the unmatched path is replaced by
+    /// the fallback path. If the fallback matches nothing either, the router
+    /// stops there and renders the 404 page.
+    pub fn set_not_found_behavior(&mut self, behavior: NotFoundBehavior) {
+        self.not_found_behavior = behavior;
+    }
+
+    /// Get the current not-found behavior.
+    #[must_use]
+    pub const fn not_found_behavior(&self) -> &NotFoundBehavior {
+        &self.not_found_behavior
+    }
+
+    // ========================================================================
+    // Scroll restoration
+    // ========================================================================
+
+    /// Choose whether navigations compute a [`ScrollIntent`].
+    ///
+    /// With [`ScrollRestoration::Auto`], back / forward produce
+    /// [`ScrollIntent::Restore`] with the offset saved on the entry (or
+    /// [`ScrollIntent::Top`] if none was saved), and push / replace produce
+    /// [`ScrollIntent::Top`]. With [`ScrollRestoration::Manual`] no intent is
+    /// produced.
+    pub fn set_scroll_restoration(&mut self, mode: ScrollRestoration) {
+        self.scroll_restoration = mode;
+    }
+
+    /// Get the current scroll restoration mode.
+    #[must_use]
+    pub const fn scroll_restoration(&self) -> ScrollRestoration {
+        self.scroll_restoration
+    }
+
+    /// Return the scroll intent of the latest navigation, if one was computed.
+    #[must_use]
+    pub const fn scroll_intent(&self) -> Option<ScrollIntent> {
+        self.scroll_intent
+    }
+
+    /// Save the scroll offset of the current entry, for [`ScrollIntent::Restore`].
+    ///
+    /// Call it when the container scrolls or before navigating away; the
+    /// offset is kept in the entry's [`HistoryState`].
+    pub fn save_scroll(&mut self, x: f32, y: f32) {
+        self.state.current_entry_mut().set_scroll_offset(x, y);
+    }
+
+    /// Intent for a navigation of kind `op` that just landed.
+    fn compute_scroll_intent(&self, op: NavigateOp) -> Option<ScrollIntent> {
+        if self.scroll_restoration == ScrollRestoration::Manual {
+            return None;
+        }
+        let intent = match op {
+            NavigateOp::Push | NavigateOp::Replace => ScrollIntent::Top,
+            NavigateOp::Back | NavigateOp::Forward => self
+                .current_entry()
+                .scroll_offset()
+                .map_or(ScrollIntent::Top, |(x, y)| ScrollIntent::Restore(x, y)),
+        };
+        trace_log!("Scroll intent for {:?}: {:?}", op, intent);
+        Some(intent)
+    }
+
+    // ========================================================================
+    // Route state
+    // ========================================================================
+
+    /// Drop every slot handed out by [`use_route_state`](crate::use_route_state)
+    /// and [`use_route_state_shared`](crate::use_route_state_shared).
+    pub fn clear_route_state(&mut self) {
+        self.route_state.clear();
+    }
+
+    /// Return the typed preferences keyed by route pattern.
+    ///
+    /// See [`use_route_pref`](crate::use_route_pref) for the preferences of
+    /// the current route.
+    #[must_use]
+    pub const fn route_prefs(&self) -> &RoutePrefs {
+        &self.route_prefs
+    }
+
+    /// Return the typed preferences keyed by route pattern, for writing.
+    pub fn route_prefs_mut(&mut self) -> &mut RoutePrefs {
+        &mut self.route_prefs
+    }
+
+    /// Drop the preferences of patterns no registered route has, returning
+    /// those patterns in sorted order.
+    ///
+    /// Preferences outlive route table changes, so a renamed route leaves
+    /// its old ones behind; they are harmless until this is called.
+    pub fn prune_orphaned_prefs(&mut self) -> Vec<String> {
+        let live = crate::route_prefs::route_patterns(self.state.routes());
+        let pruned = self.route_prefs.prune(&live);
+        if !pruned.is_empty() {
+            debug_log!("Pruned route preferences of {:?}", pruned);
+        }
+        pruned
+    }
+
+    /// Return the value of type `T` loaded for the current match stack.
+    ///
+    /// When several routes in the stack load a `T`, the one nearest the
+    /// leaf wins. See [`Route::loader`].
+    #[must_use]
+    pub fn loader_data<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.loader_data.get()
+    }
+
+    /// Return the value of type `T` guards or middleware left for the
+    /// navigation that produced the current leaf.
+    ///
+    /// See [`NavigationRequest::extensions`].
+    #[must_use]
+    pub fn navigation_extension<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.leaf()?.navigation_extensions().get()
+    }
+
+    /// Register `T` as the type of the param named [`T::KEY`](RouteParamType::KEY).
+    ///
+    /// From the next navigation on, a route capturing that param checks it
+    /// with [`T::parse`](RouteParamType::parse) before its
+    /// [`validate_param`](Route::validate_param) validators and loader; a
+    /// value that does not parse fails the route with
+    /// [`RouteError::InvalidParam`](crate::RouteError::InvalidParam) before
+    /// its builder runs. [`param`](Self::param) reads the value. A type
+    /// registered earlier under the same key is replaced.
+    pub fn register_param_type<T: RouteParamType>(&mut self) {
+        if let Some(replaced) = self.param_types.register::<T>() {
+            warn_log!(
+                "Param type '{}' replaces '{}' for param '{}'",
+                std::any::type_name::<T>(),
+                replaced,
+                T::KEY
+            );
+        }
+    }
+
+    /// Return the leaf's param of type `T`, parsed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParamTypeError::NotRegistered`] if `T` was not passed to
+    /// [`register_param_type`](Self::register_param_type), otherwise the
+    /// error of [`RouteParams::get_typed`].
+    pub fn param<T: RouteParamType>(&self) -> Result<T, ParamTypeError> {
+        let empty = RouteParams::new();
+        self.param_types.get(self.leaf_params().unwrap_or(&empty))
+    }
+
+    pub(crate) fn loader_error(&self, route: &Route) -> Option<&NavigationError> {
+        self.loader_data.error_for(route)
+    }
+
+    /// Copy the loader stage's errors onto the match stack entries.
+    fn record_load_errors(&mut self) {
+        let stack = self.state.match_stack_mut();
+        for depth in 0..stack.len() {
+            let stale = stack
+                .at_depth(depth)
+                .and_then(MatchEntry::error)
+                .is_some_and(RouteError::is_load_error);
+            if stale {
+                stack.set_error(depth, None);
+            }
+        }
+        for (depth, error) in self.loader_data.route_errors() {
+            stack.set_error(depth, Some(error.clone()));
+        }
+    }
+
+    /// Record a render failure of `route` at `depth`.
+    ///
+    /// Returns whether the entry's error changed.
+    pub(crate) fn record_render_error(
+        &mut self,
+        depth: usize,
+        route: &Route,
+        error: Option<RouteError>,
+    ) -> bool {
+        let stack = self.state.match_stack_mut();
+        let same_route = stack
+            .at_depth(depth)
+            .is_some_and(|entry| std::ptr::eq(Arc::as_ptr(&entry.route), route));
+        same_route && stack.set_error(depth, error)
+    }
+
+    /// Run the failed stage of the entry at `depth` again, if it still
+    /// holds `route`. See [`RetryHandle::retry`](crate::RetryHandle::retry).
+    pub(crate) fn retry_entry(&mut self, depth: usize, route: &Arc<Route>, cx: &App) {
+        let error = self
+            .state
+            .match_stack()
+            .at_depth(depth)
+            .filter(|entry| Arc::ptr_eq(&entry.route, route))
+            .and_then(MatchEntry::error)
+            .cloned();
+        let Some(error) = error else {
+            debug_log!("Retry of '{}' ignored, nothing to retry", route.config.path);
+            return;
+        };
+        info_log!("Retrying route '{}' after: {}", route.config.path, error);
+        if error.is_load_error() {
+            self.loader_data
+                .retry_from(self.state.match_stack(), depth, &self.param_types, cx);
+            self.record_load_errors();
+        } else {
+            self.state.match_stack_mut().set_error(depth, None);
+        }
+    }
+
+    pub(crate) const fn route_tasks(&self) -> &RouteTasks {
+        &self.route_tasks
+    }
+
+    pub(crate) const fn route_timers(&self) -> &RouteTimers {
+        &self.route_timers
+    }
+
+    pub(crate) fn route_timers_mut(&mut self) -> &mut RouteTimers {
+        &mut self.route_timers
+    }
+
+    pub(crate) const fn hover_preview(&self) -> &HoverPreview {
+        &self.hover_preview
+    }
+
+    pub(crate) fn hover_preview_mut(&mut self) -> &mut HoverPreview {
+        &mut self.hover_preview
+    }
+
+    pub(crate) const fn route_state(&self) -> &RouteStateStore {
+        &self.route_state
+    }
+
+    pub(crate) fn route_state_mut(&mut self) -> &mut RouteStateStore {
+        &mut self.route_state
+    }
+
+    // ========================================================================
+    // Component cache
+    // ========================================================================
+
+    /// Get a cached component view by key, without marking it as used.
+    #[must_use]
+    pub fn get_cached_component(&self, key: &str) -> Option<&AnyView> {
+        self.component_cache.peek(key)
+    }
+
+    /// Get a cached component view by key for rendering, marking it most
+    /// recently used and counting a hit or miss.
+    pub(crate) fn use_cached_component(&mut self, key: &str) -> Option<AnyView> {
+        let view = self.component_cache.get(key);
+        if view.is_some() {
+            self.component_stats.component_hits += 1;
+        } else {
+            self.component_stats.component_misses += 1;
+        }
+        view
+    }
+
+    /// Store a component view in the cache, evicting the least recently used
+    /// entry if full.
+    pub fn cache_component(&mut self, key: String, view: AnyView) {
+        self.component_stats.component_evictions +=
+            self.component_cache.insert(key, view, None, false);
+    }
+
+    /// Store the component view `route_pattern` built, pinned if the route
+    /// asked for it via [`Route::pin_component`].
+    pub(crate) fn cache_route_component(
+        &mut self,
+        key: String,
+        view: AnyView,
+        route_pattern: &str,
+        pin: bool,
+    ) {
+        let pattern = Some(route_pattern.to_string());
+        self.component_stats.component_evictions +=
+            self.component_cache.insert(key, view, pattern, pin);
+    }
+
+    /// Exempt the cached component `key` from eviction.
+    ///
+    /// Pinned components do not count toward
+    /// [`CachePolicy::component_capacity`]; at most
+    /// [`CachePolicy::pinned_component_capacity`] may be pinned per
+    /// partition. Returns `false` if `key` is not cached or that many are
+    /// pinned already.
+    pub fn pin_component(&mut self, key: &str) -> bool {
+        let pinned = self.component_cache.pin(key);
+        if pinned {
+            debug_log!("Pinned component '{}'", key);
+        }
+        pinned
+    }
+
+    /// Make the cached component `key` evictable again, evicting the least
+    /// recently used components if the cache is over capacity.
+    ///
+    /// Returns whether `key` was pinned.
+    pub fn unpin_component(&mut self, key: &str) -> bool {
+        let (was_pinned, evicted) = self.component_cache.unpin(key);
+        self.component_stats.component_evictions += evicted;
+        was_pinned
+    }
+
+    /// Drop the cached component `key`, pinned or not.
+    ///
+    /// The entity is released once nothing else holds it, running its
+    /// `observe_release` callbacks. Returns whether `key` was cached.
+    pub fn evict_component(&mut self, key: &str) -> bool {
+        let evicted = self.component_cache.remove(key);
+        if evicted {
+            debug_log!("Evicted component '{}'", key);
+        }
+        evicted
+    }
+
+    /// List the component cache of the active partition, most recently used
+    /// first, e.g. for a devtools view.
+    #[must_use]
+    pub fn component_cache_entries(&self) -> Vec<ComponentCacheEntryInfo> {
+        self.component_cache.entries()
+    }
+
+    /// Warm the component cache for `path` without navigating.
+    ///
+    /// Resolves `path` and builds every `Route::component` /
+    /// `Route::component_with_params` entry in its match stack that is not
+    /// cached yet, so a later navigation renders the cached entities
+    /// instantly. Plain builder routes are skipped, as are guards and
+    /// middleware — nothing about the current navigation state changes.
+    ///
+    /// Repeated calls for the same path within a short debounce window are
+    /// ignored, which keeps hover-driven prefetching cheap.
+    ///
+    /// Returns the number of component entities created.
+    pub fn prefetch(window: &mut Window, cx: &mut App, path: &str) -> usize {
+        if cx.try_global::<Self>().is_none() {
+            return 0;
+        }
+        let stack = cx.update_global::<Self, _>(|router, _| {
+            router
+                .begin_prefetch(path, Instant::now())
+                .then(|| router.resolve_path(path))
+        });
+        let Some(stack) = stack else {
+            trace_log!("Prefetch of '{}' debounced", path);
+            return 0;
+        };
+
+        let mut created = 0;
+        for entry in stack.entries() {
+            let Some(key) = entry.route.component_cache_key_in(cx, entry) else {
+                continue;
+            };
+            if cx.global::<Self>().get_cached_component(&key).is_some() {
+                continue;
+            }
+            // Building a component route only creates and caches its entity
+            let _ = entry.build(window, cx);
+            created += 1;
+        }
+        debug_log!("Prefetched '{}': {} component(s) created", path, created);
+        created
+    }
+
+    /// Render what navigating to `path` would show, without navigating.
+    ///
+    /// Resolves `path` and builds its leaf route, or the whole chain with
+    /// [`PreviewConstraints::with_chain`], into a box of the constrained
+    /// size. History, the current match stack and the component cache are
+    /// untouched: component routes get a fresh entity. Guards are only
+    /// probed, as [`can_navigate`](Self::can_navigate) does.
+    ///
+    /// Returns `None` when `path` resolves to nothing, when the guards would
+    /// not let a navigation reach it, or when one of its guards returns
+    /// `false` from [`RouteGuard::allow_preview`](crate::RouteGuard::allow_preview).
+    /// See [`preview`](crate::preview).
+    pub fn render_preview(
+        window: &mut Window,
+        cx: &mut App,
+        path: &str,
+        constraints: PreviewConstraints,
+    ) -> Option<AnyElement> {
+        let stack = {
+            let router = cx.try_global::<Self>()?;
+            if !router.preview_allowed(cx, path) {
+                trace_log!("Preview of '{}' refused by its guards", path);
+                return None;
+            }
+            router.resolve_path(path)
+        };
+        if stack.is_empty() {
+            return None;
+        }
+        Some(crate::widgets::build_preview(
+            stack,
+            constraints,
+            window,
+            cx,
+        ))
+    }
+
+    /// Whether the guards of `path` let it be previewed.
+    fn preview_allowed(&self, cx: &App, path: &str) -> bool {
+        #[cfg(feature = "guard")]
+        if self
+            .collect_guards(path)
+            .iter()
+            .any(|collected| !collected.guard.allow_preview())
+        {
+            return false;
+        }
+        self.can_navigate(cx, path).allowed
+    }
+
+    /// Build the component entities of recently visited paths in the
+    /// background, so the first navigation back to them renders instantly.
+    ///
+    /// Takes up to `limit` distinct paths of the history, most recent first,
+    /// and handles one per executor tick. Entities go to the cold end of the
+    /// component cache and never evict anything; once the cache is full the
+    /// remaining paths create nothing. Paths that no longer resolve and
+    /// routes that are not component-based are skipped. Starting again
+    /// replaces a warming in progress; see
+    /// [`warming_status`](Self::warming_status) and
+    /// [`cancel_warming`](Self::cancel_warming).
+    pub fn warm_from_history(cx: &mut App, limit: usize) {
+        if cx.try_global::<Self>().is_none() {
+            return;
+        }
+        let token = cx.update_global::<Self, _>(|router, _| {
+            let paths = recent_paths(router.state.history(), limit);
+            debug_log!("Warming component cache from {} path(s)", paths.len());
+            router.warming.start(paths)
+        });
+        cx.spawn(async move |cx| loop {
+            cx.background_executor().timer(WARM_INTERVAL).await;
+            let more = cx.update(|cx| Self::warm_next(cx, token));
+            if !matches!(more, Ok(true)) {
+                break;
+            }
+        })
+        .detach();
+    }
+
+    /// Warm the next queued path of run `token`; returns whether paths remain.
+    fn warm_next(cx: &mut App, token: u64) -> bool {
+        if cx.try_global::<Self>().is_none() {
+            return false;
+        }
+        let next = cx.update_global::<Self, _>(|router, _| {
+            let path = router.warming.next(token)?;
+            let stack = router.resolve_path(&path);
+            Some((path, stack))
+        });
+        let Some((path, stack)) = next else {
+            return false;
+        };
+        if stack.is_empty() {
+            trace_log!("Warming skipped '{}': no longer resolves", path);
+        }
+
+        let mut created = 0;
+        for entry in stack.entries() {
+            let Some(key) = entry.route.component_cache_key_in(cx, entry) else {
+                continue;
+            };
+            let pin = entry.route.pins_component();
+            if !cx.global::<Self>().component_cache.accepts_cold(&key, pin) {
+                continue;
+            }
+            let Some(view) = entry.route.create_component(cx, &entry.params) else {
+                continue;
+            };
+            let pattern = Some(entry.route.config.path.clone());
+            cx.update_global::<Self, _>(|router, _| {
+                if router.component_cache.insert_cold(key, view, pattern, pin) {
+                    created += 1;
+                }
+            });
+        }
+        trace_log!("Warmed '{}': {} component(s) created", path, created);
+        cx.update_global::<Self, _>(|router, _| router.warming.finish_path(token, created))
+    }
+
+    /// Progress of the latest [`warm_from_history`](Self::warm_from_history),
+    /// e.g. for a splash indicator.
+    #[must_use]
+    pub const fn warming_status(&self) -> WarmingStatus {
+        self.warming.status()
+    }
+
+    /// Stop warming; entities already created stay cached.
+    pub fn cancel_warming(&mut self) {
+        self.warming.cancel();
+    }
+
+    /// Record a prefetch attempt for `path` at `now`.
+    ///
+    /// Returns `false` if the previous attempt for the same path happened
+    /// less than [`PREFETCH_DEBOUNCE`] ago.
+    fn begin_prefetch(&mut self, path: &str, now: Instant) -> bool {
+        self.prefetch_attempts
+            .retain(|_, at| now.saturating_duration_since(*at) < PREFETCH_DEBOUNCE);
+        if self.prefetch_attempts.contains_key(path) {
+            return false;
+        }
+        self.prefetch_attempts.insert(path.to_string(), now);
+        true
+    }
+
+    // ========================================================================
+    // Transitions
+    // ========================================================================
+
+    /// Set transition for the next navigation.
+    #[cfg(feature = "transition")]
+    pub fn set_next_transition(&mut self, transition: Transition) {
+        self.next_transition = Some(transition);
+    }
+
+    /// Get and consume the next transition override.
+    #[cfg(feature = "transition")]
+    pub fn take_next_transition(&mut self) -> Option<Transition> {
+        self.next_transition.take()
+    }
+
+    /// Return the request of the last navigation that committed.
+    ///
+    /// Outlets pass it to [`Route::transition_for_request`] so routes using
+    /// [`Route::transition_for`] animate according to where navigation went.
+    #[cfg(feature = "transition")]
+    #[must_use]
+    pub const fn last_navigation(&self) -> Option<&NavigationRequest> {
+        self.last_navigation.as_ref()
+    }
+
+    /// Return the kind of the last committed navigation.
+    ///
+    /// [`NavigationSource::Initial`] until the first navigation after init
+    /// has committed, and for that navigation itself.
+    #[cfg(feature = "transition")]
+    #[must_use]
+    pub const fn navigation_source(&self) -> NavigationSource {
+        self.navigation_source
+    }
+
+    /// Choose which navigation sources animate.
+    ///
+    /// Outlets consult it when picking a transition: suppressed sources
+    /// render instantly regardless of route config, while an explicit
+    /// [`push_with_transition`](Self::push_with_transition) still wins. The
+    /// first navigation after init never animates.
+    #[cfg(feature = "transition")]
+    pub fn set_transition_sources(&mut self, sources: TransitionSources) {
+        self.transition_sources = sources;
+    }
+
+    /// Return which navigation sources animate.
+    #[cfg(feature = "transition")]
+    #[must_use]
+    pub const fn transition_sources(&self) -> TransitionSources {
+        self.transition_sources
+    }
+
+    /// Call `hook` whenever a navigation commits, before its first frame
+    /// renders.
+    ///
+    /// The [`TransitionEvent`] names the depth where the match stack
+    /// diverged, the previous and new leaf patterns, the transition the
+    /// outlet there runs and how long the outlets animate, so chrome outside
+    /// the router can start its own animation with the same timing:
+    ///
+    /// ```ignore
+    /// router.on_transition(|event, cx| {
+    ///     if event.family_changed("/editor", "/dashboard") {
+    ///         Sidebar::collapse(event.duration, cx);
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// Hooks run in the order they were added, once per navigation (for
+    /// its final redirect hop), from the [`Navigator`] methods right before
+    /// they refresh windows: whatever a hook writes, the first frame showing
+    /// the new route sees. Blocked navigations, restores and partition
+    /// switches are not reported.
+    #[cfg(feature = "transition")]
+    pub fn on_transition(
+        &mut self,
+        hook: impl Fn(&TransitionEvent, &mut App) + Send + Sync + 'static,
+    ) -> TransitionHookId {
+        self.transition_hooks.add(Arc::new(hook))
+    }
+
+    /// Remove a hook added with [`on_transition`](Self::on_transition).
+    ///
+    /// Returns `false` if it was already removed.
+    #[cfg(feature = "transition")]
+    pub fn remove_transition_hook(&mut self, id: TransitionHookId) -> bool {
+        self.transition_hooks.remove(id)
+    }
+
+    /// Hold previous outlet content until the new route's first successful build.
+    ///
+    /// Applies to every route; [`Route::hold_previous`] enables it per route.
+    /// While holding, an outlet renders the previous match stack's entry at its
+    /// depth without animation. The configured transition starts when the
+    /// outlet swaps to the new content, not at navigation commit.
+    #[cfg(feature = "transition")]
+    pub fn set_stale_while_navigate(&mut self, enabled: bool) {
+        self.stale_while_navigate = enabled;
+    }
+
+    /// Check if outlets hold previous content for all routes.
+    #[cfg(feature = "transition")]
+    #[must_use]
+    pub const fn stale_while_navigate(&self) -> bool {
+        self.stale_while_navigate
+    }
+
+    /// Set how long an outlet may hold stale content.
+    ///
+    /// Once exceeded, the outlet swaps to the loading page so a route that
+    /// never builds cannot freeze the UI on old content. Defaults to 2 seconds.
+    #[cfg(feature = "transition")]
+    pub fn set_stale_timeout(&mut self, timeout: Duration) {
+        self.stale_timeout = timeout;
+    }
+
+    /// Return the stale-content timeout.
+    #[cfg(feature = "transition")]
+    #[must_use]
+    pub const fn stale_timeout(&self) -> Duration {
+        self.stale_timeout
+    }
+
+    /// Set how long a hero flies between its source and target bounds.
+    ///
+    /// Also bounds how long a captured hero waits for its target before the
+    /// navigation animates without it. Defaults to 300 ms.
+    #[cfg(feature = "transition")]
+    pub fn set_hero_duration(&mut self, duration: Duration) {
+        self.heroes.set_duration(duration);
+    }
+
+    /// Return the hero flight duration.
+    #[cfg(feature = "transition")]
+    #[must_use]
+    pub const fn hero_duration(&self) -> Duration {
+        self.heroes.duration()
+    }
+
+    #[cfg(feature = "transition")]
+    pub(crate) const fn heroes(&self) -> &HeroRegistry {
+        &self.heroes
+    }
+
+    #[cfg(feature = "transition")]
+    pub(crate) fn heroes_mut(&mut self) -> &mut HeroRegistry {
+        &mut self.heroes
+    }
+
+    /// Check if there's a transition override set.
+    #[cfg(feature = "transition")]
+    #[must_use]
+    pub const fn has_next_transition(&self) -> bool {
+        self.next_transition.is_some()
+    }
+
+    /// Clear transition override.
+    #[cfg(feature = "transition")]
+    pub fn clear_next_transition(&mut self) {
+        self.next_transition = None;
+    }
+
+    /// Navigate with a specific transition.
+    #[cfg(feature = "transition")]
+    pub fn push_with_transition(
+        &mut self,
+        path: String,
+        transition: Transition,
+        cx: &App,
+    ) -> NavigationResult {
+        self.set_next_transition(transition);
+        self.push(path, cx)
+    }
+
+    /// Replace with a specific transition.
+    #[cfg(feature = "transition")]
+    pub fn replace_with_transition(
+        &mut self,
+        path: String,
+        transition: Transition,
+        cx: &App,
+    ) -> NavigationResult {
+        self.set_next_transition(transition);
+        self.replace(path, cx)
+    }
+}
+
+impl Default for GlobalRouter {
+    fn default() -> Self {
+        Self {
+            state: RouterState::new(),
+            #[cfg(feature = "transition")]
+            previous_stack: None,
+            #[cfg(feature = "transition")]
+            previous_stack_expires: None,
+            #[cfg(feature = "transition")]
+            previous_stack_token: 0,
+            #[cfg(feature = "cache")]
+            nested_cache: RouteCache::new(),
+            named_routes: NamedRouteRegistry::new(),
+            #[cfg(feature = "transition")]
+            next_transition: None,
+            #[cfg(feature = "transition")]
+            last_navigation: None,
+            #[cfg(feature = "transition")]
+            navigation_source: NavigationSource::Initial,
+            #[cfg(feature = "transition")]
+            transition_sources: TransitionSources::default(),
+            #[cfg(feature = "transition")]
+            stale_while_navigate: false,
+            #[cfg(feature = "transition")]
+            stale_timeout: DEFAULT_STALE_TIMEOUT,
+            #[cfg(feature = "transition")]
+            heroes: HeroRegistry::default(),
+            #[cfg(feature = "transition")]
+            transition_hooks: TransitionHooks::default(),
+            #[cfg(feature = "transition")]
+            pending_transition: None,
+            component_cache: ComponentCache::new(CachePolicy::new()),
+            cache_policy: CachePolicy::new(),
+            component_stats: CacheStats::default(),
+            error_handlers: ErrorHandlers::new(),
+            not_found_behavior: NotFoundBehavior::Render,
+            scroll_restoration: ScrollRestoration::Manual,
+            scroll_intent: None,
+            locales: Vec::new(),
+            default_locale: None,
+            url_base: None,
+            prefetch_attempts: HashMap::new(),
+            warming: Warming::default(),
+            #[cfg(feature = "guard")]
+            pending_challenge: None,
+            on_route_registered: None,
+            on_route_invalidated: None,
+            invalidation_policy: InvalidationPolicy::Stay,
+            route_invalidated: None,
+            pending_invalidation: None,
+            disabled_routes: Vec::new(),
+            unsettled: Vec::new(),
+            progress: ProgressTracker::default(),
+            phase: NavigationPhase::Idle,
+            notifications: Notifications::default(),
+            route_state: RouteStateStore::default(),
+            route_prefs: RoutePrefs::default(),
+            outlet_policy: OutletPolicy::default(),
+            route_tasks: RouteTasks::default(),
+            route_timers: RouteTimers::default(),
+            hover_preview: HoverPreview::default(),
+            loader_data: LoaderData::default(),
+            param_types: ParamTypes::default(),
+            #[cfg(feature = "middleware")]
+            middleware_ordering: crate::middleware::MiddlewareOrdering::ByPriority,
+            #[cfg(feature = "middleware")]
+            disabled_middleware_groups: std::collections::HashSet::new(),
+            #[cfg(feature = "middleware")]
+            settlement: Settlement::default(),
+            deferred_push: None,
+            batch_depth: 0,
+            batch_pending: false,
+            route_snapshot: RouteSnapshotHandle::default(),
+            navigation_sequence: 0,
+            active_partition: Self::DEFAULT_PARTITION.to_string(),
+            partitions: HashMap::new(),
+            window_states: HashMap::new(),
+            live_window: None,
+            parked_shared: None,
+            #[cfg(test)]
+            resolutions: 0,
+        }
+    }
+}
+
+impl Global for GlobalRouter {}
+
+// ============================================================================
+// Middleware settlement
+// ============================================================================
+
+/// The top-level request of the navigation in progress, and the middleware
+/// whose `before_navigation` ran for it, by route and index.
+#[cfg(feature = "middleware")]
+#[derive(Clone, Default)]
+struct Settlement {
+    request: Option<NavigationRequest>,
+    ran: Vec<(Arc<Route>, usize)>,
+}
+
+#[cfg(feature = "middleware")]
+impl Settlement {
+    fn record(&mut self, route: &Arc<Route>, index: usize) {
+        let seen = self
+            .ran
+            .iter()
+            .any(|(r, i)| *i == index && Arc::ptr_eq(r, route));
+        if !seen {
+            self.ran.push((Arc::clone(route), index));
+        }
+    }
+}
+
+/// An invalidation whose follow-up awaits [`GlobalRouter::settle`].
+#[derive(Clone)]
+struct Unsettled {
+    /// Protected scopes of the match stack before the route table change.
+    protected: Vec<(usize, Arc<Route>)>,
+    /// The match stack before the change, whose middleware hear of `event`.
+    #[cfg(feature = "middleware")]
+    previous: MatchStack,
+    #[cfg(feature = "middleware")]
+    event: CurrentRouteInvalidated,
+}
+
+// ============================================================================
+// Partitions
+// ============================================================================
+
+/// Navigation state of a partition while another one is active.
+#[derive(Clone)]
+struct Partition {
+    snapshot: RouterSnapshot,
+    component_cache: ComponentCache,
+    route_state: RouteStateStore,
+}
+
+impl Partition {
+    fn new(snapshot: RouterSnapshot, component_cache: ComponentCache) -> Self {
+        Self {
+            snapshot,
+            component_cache,
+            route_state: RouteStateStore::default(),
+        }
+    }
+}
+
+/// Navigation state of a window opened by `push_new_window` while another
+/// is live. The routes of `state` are unused; the live state's are shared.
+#[derive(Clone)]
+struct WindowNavigation {
+    state: RouterState,
+    component_cache: ComponentCache,
+    route_state: RouteStateStore,
+    loader_data: LoaderData,
+    route_tasks: RouteTasks,
+    route_timers: RouteTimers,
+    route_invalidated: Option<CurrentRouteInvalidated>,
+    scroll_intent: Option<ScrollIntent>,
+    #[cfg(feature = "transition")]
+    previous_stack: Option<MatchStack>,
+    #[cfg(feature = "transition")]
+    previous_stack_expires: Option<Instant>,
+    #[cfg(feature = "transition")]
+    last_navigation: Option<NavigationRequest>,
+    #[cfg(feature = "transition")]
+    navigation_source: NavigationSource,
+}
+
+impl WindowNavigation {
+    fn new(cache_policy: CachePolicy) -> Self {
+        Self {
+            state: RouterState::new(),
+            component_cache: ComponentCache::new(cache_policy),
+            route_state: RouteStateStore::default(),
+            loader_data: LoaderData::default(),
+            route_tasks: RouteTasks::default(),
+            route_timers: RouteTimers::default(),
+            route_invalidated: None,
+            scroll_intent: None,
+            #[cfg(feature = "transition")]
+            previous_stack: None,
+            #[cfg(feature = "transition")]
+            previous_stack_expires: None,
+            #[cfg(feature = "transition")]
+            last_navigation: None,
+            #[cfg(feature = "transition")]
+            navigation_source: NavigationSource::Initial,
+        }
+    }
+}
+
+// ============================================================================
+// Helper: levels a navigation leaves
+// ============================================================================
+
+/// Entries of `current` that a navigation resolving to `target` leaves,
+/// deepest first: the leaf, which is left even for new params of the same
+/// route, and every ancestor `target` does not keep at its depth. Without a
+/// target, e.g. when switching partitions, every level is left.
+fn leaving_entries<'a>(
+    current: &'a MatchStack,
+    target: Option<&'a MatchStack>,
+) -> impl Iterator<Item = &'a MatchEntry> + 'a {
+    let leaf_depth = current.leaf().map(|leaf| leaf.depth);
+    current.entries().iter().rev().filter(move |entry| {
+        let kept = target
+            .and_then(|target| target.at_depth(entry.depth))
+            .is_some_and(|next| Arc::ptr_eq(&next.route, &entry.route));
+        Some(entry.depth) == leaf_depth || !kept
+    })
+}
+
+// ============================================================================
+// Helper: path prefix matching with parameter support
+// ============================================================================
+
+/// Walk the route tree, calling `visitor` on each route whose accumulated
+/// pattern is a prefix of the target's `remaining` segments (or matches all
+/// of them, for routes without children). The visitor receives the route,
+/// the full accumulated path, the route's depth in the tree (top-level =
+/// `depth`) and whether the route matches the target exactly rather than
+/// as an ancestor.
+///
+/// This factored-out helper avoids duplicating tree-walk logic between guard
+/// collection and middleware collection.
+fn walk_matching_routes<'a>(
+    route: &'a Arc<Route>,
+    remaining: &[&str],
+    accumulated: &str,
+    depth: usize,
+    visitor: &mut dyn FnMut(&'a Route, &str, usize, bool),
+) {
+    let Some(consumed) = route.config.pattern().prefix_len(remaining) else {
+        return;
+    };
+    let rest = &remaining[consumed..];
+
+    // A route without children is a page, not a layout: it only covers the
+    // path it matches exactly. Without this a `/` page would guard every path.
+    if route.children.is_empty() && route.named_children.is_empty() && !rest.is_empty() {
+        return;
+    }
+
+    let route_path = trim_slashes(&route.config.path);
+    // Avoid allocations when possible by reusing the existing string
+    let full: Cow<'_, str> = if accumulated.is_empty() {
+        std::borrow::Cow::Borrowed(route_path)
+    } else if route_path.is_empty() {
+        std::borrow::Cow::Borrowed(accumulated)
+    } else {
+        std::borrow::Cow::Owned(format!("{accumulated}/{route_path}"))
+    };
+
+    visitor(route, &full, depth, rest.is_empty());
+
+    for child in route.get_children() {
+        walk_matching_routes(child, rest, &full, depth + 1, visitor);
+    }
+}
+
+// ============================================================================
+// Navigation operation type
+// ============================================================================
+
+/// Internal enum for the kind of navigation to perform after pipeline checks.
+#[derive(Debug, Clone, Copy)]
+enum NavigateOp {
+    Push,
+    Replace,
+    Back,
+    Forward,
+}
+
+// ============================================================================
+// UseRouter trait
+// ============================================================================
+
+/// Trait for accessing the global router from context.
+pub trait UseRouter {
+    /// Get reference to global router.
+    fn router(&self) -> &GlobalRouter;
+
+    /// Update global router.
+    ///
+    /// Route table changes made by `f` are [settled](GlobalRouter::settle)
+    /// before it returns.
+    fn update_router<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut GlobalRouter, &mut App) -> R;
+}
+
+impl UseRouter for App {
+    fn router(&self) -> &GlobalRouter {
+        self.global::<GlobalRouter>()
+    }
+
+    fn update_router<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut GlobalRouter, &mut Self) -> R,
+    {
+        self.update_global(|router, cx| {
+            let result = f(router, cx);
+            router.settle(cx);
+            result
+        })
+    }
+}
+
+// ============================================================================
+// init_router
+// ============================================================================
+
+/// Initialize global router with routes.
+///
+/// `configure` runs as a [`GlobalRouter::batch`], so the match stack is
+/// resolved once after all routes are registered.
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::{init_router, Route};
+///
+/// init_router(cx, |router| {
+///     router.add_route(Route::new("/", |_, _cx, _params| gpui::div()));
+///     router.add_route(Route::new("/users/:id", |_, _cx, _params| gpui::div()));
+/// });
+/// ```
+pub fn init_router<F>(cx: &mut App, configure: F)
+where
+    F: FnOnce(&mut GlobalRouter),
+{
+    let mut router = GlobalRouter::new();
+    router.batch(configure);
+    cx.set_global(router);
+    cx.update_global::<GlobalRouter, _>(|router, cx| {
+        router.route_tasks.sync(router.state.match_stack(), cx);
+        router.route_timers.sync(router.state.match_stack(), cx);
+        router.loader_data = LoaderData::load(
+            router.state.match_stack(),
+            &router.param_types,
+            cx,
+            |_, _| {},
+        );
+        router.record_load_errors();
+    });
+}
+
+/// Navigate to a path using the global router and refresh all windows.
+///
+/// This is a convenience shortcut equivalent to
+/// `cx.update_global::<GlobalRouter, _>(|r, cx| r.push(path, cx))`.
+pub fn navigate(cx: &mut App, path: impl Into<String>) {
+    let path = path.into();
+    cx.update_global::<GlobalRouter, _>(|router, cx| {
+        router.push(path, cx);
+    });
+    refresh_after_navigation(cx);
+}
+
+/// Return the current path from the global router.
+pub fn current_path(cx: &App) -> String {
+    cx.router().current_path().to_string()
+}
+
+/// Dry-run the guards for `path` without navigating.
+///
+/// See [`GlobalRouter::can_navigate`].
+pub fn can_navigate(cx: &App, path: &str) -> NavigationProbe {
+    cx.router().can_navigate(cx, path)
+}
+
+/// Take the router's post-navigation flag; see [`GlobalRouter::take_refresh`].
+///
+/// Runs the [`GlobalRouter::on_transition`] hooks of the navigation that
+/// raised it, ahead of the refresh.
+fn navigation_finished(cx: &mut App) -> bool {
+    let finished = cx.try_global::<GlobalRouter>().is_some()
+        && cx.update_global::<GlobalRouter, _>(|router, cx| {
+            router.settle(cx);
+            router.take_refresh()
+        });
+    #[cfg(feature = "transition")]
+    if finished {
+        run_transition_hooks(cx);
+    }
+    finished
+}
+
+/// Report the navigation that just committed to the transition hooks.
+#[cfg(feature = "transition")]
+fn run_transition_hooks(cx: &mut App) {
+    let pending = cx.update_global::<GlobalRouter, _>(|router, _| {
+        let event = router.pending_transition.take()?;
+        Some((event, router.transition_hooks.snapshot()))
+    });
+    let Some((event, hooks)) = pending else {
+        return;
+    };
+    trace_log!(
+        "Transition hooks: '{}' → '{}' at depth {}",
+        event.from_path,
+        event.to_path,
+        event.diverging_depth
+    );
+    for hook in hooks {
+        hook(&event, cx);
+    }
+}
+
+/// Refresh every window for the navigation that just finished, if any.
+fn refresh_after_navigation(cx: &mut App) {
+    if navigation_finished(cx) {
+        cx.refresh_windows();
+    }
+}
+
+/// Refresh only `window`, if it is still open.
+fn refresh_window(cx: &mut App, window: AnyWindowHandle) {
+    if window.update(cx, |_, window, _| window.refresh()).is_err() {
+        debug_log!(
+            "Window {:?} is closed, nothing to refresh",
+            window.window_id()
+        );
+    }
+}
+
+// ============================================================================
+// NavigatorHandle
+// ============================================================================
+
+/// Handle returned by [`Navigator::of`] for fluent chained navigation.
+///
+/// Each method consumes and returns `self`, allowing patterns like:
+///
+/// ```ignore
+/// Navigator::of(cx)
+///     .push("/users")
+///     .push("/users/42");
+/// ```
+#[must_use]
 pub struct NavigatorHandle<'a, C: BorrowAppContext> {
     cx: &'a mut C,
 }
 
-impl<C: BorrowAppContext + BorrowMut<App>> NavigatorHandle<'_, C> {
-    /// Navigate to a new path.
-    pub fn push(self, route: impl IntoRoute) -> Self {
-        let descriptor = route.into_route();
-        self.cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.push(descriptor.path, app);
+impl<C: BorrowAppContext + BorrowMut<App>> NavigatorHandle<'_, C> {
+    /// Navigate to a new path.
+    pub fn push(self, route: impl IntoRoute) -> Self {
+        let descriptor = route.into_route();
+        self.cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.push(descriptor.path, app);
+        });
+        refresh_after_navigation(self.cx.borrow_mut());
+        self
+    }
+
+    /// Replace current path without adding to history.
+    pub fn replace(self, route: impl IntoRoute) -> Self {
+        let descriptor = route.into_route();
+        self.cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.replace(descriptor.path, app);
+        });
+        refresh_after_navigation(self.cx.borrow_mut());
+        self
+    }
+
+    /// Go back to the previous route.
+    pub fn pop(self) -> Self {
+        self.cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.back(app);
+        });
+        refresh_after_navigation(self.cx.borrow_mut());
+        self
+    }
+
+    /// Go forward in history.
+    pub fn forward(self) -> Self {
+        self.cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.forward(app);
+        });
+        refresh_after_navigation(self.cx.borrow_mut());
+        self
+    }
+}
+
+// ============================================================================
+// Navigator
+// ============================================================================
+
+/// Navigation API for convenient route navigation.
+///
+/// Provides static methods for navigation operations:
+/// - `Navigator::push(cx, "/path")` — Navigate to a new page
+/// - `Navigator::pop(cx)` — Go back to previous page
+/// - `Navigator::replace(cx, "/path")` — Replace current page
+///
+/// All navigation methods run the full pipeline (guards, middleware).
+///
+/// # Ordering
+///
+/// A navigation runs to completion before any window can render it:
+/// guards, `before_navigation`, `on_exit` / `on_deactivate`, the commit,
+/// `on_activate` / `on_enter`, `after_navigation` and the middleware
+/// outcome callbacks all run inside one router update. Only after its last
+/// step does the router raise the flag the navigation methods wait for
+/// before refreshing windows, so the first frame that shows the new path
+/// also sees every global those hooks wrote. Work a hook schedules for
+/// later, e.g. with `cx.spawn`, is not covered.
+///
+/// # Example
+///
+/// ```ignore
+/// use gpui_navigator::Navigator;
+///
+/// Navigator::push(cx, "/users/123");
+/// Navigator::pop(cx);
+/// Navigator::replace(cx, "/login");
+/// ```
+pub struct Navigator;
+
+impl Navigator {
+    /// Get a [`NavigatorHandle`] for chained navigation calls.
+    pub fn of<C: BorrowAppContext + BorrowMut<App>>(cx: &mut C) -> NavigatorHandle<'_, C> {
+        NavigatorHandle { cx }
+    }
+
+    /// Navigate to a new path.
+    pub fn push(cx: &mut (impl BorrowAppContext + BorrowMut<App>), route: impl IntoRoute) {
+        let descriptor = route.into_route();
+        debug_log!("Navigator::push: pushing path '{}'", descriptor.path);
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.push(descriptor.path, app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+
+    /// Navigate to a new path once the current update completes.
+    ///
+    /// Safe to call while rendering, e.g. for a redirect computed in a
+    /// builder. Only the latest path queued before the update completes is
+    /// pushed; earlier ones are dropped.
+    pub fn defer_push(cx: &mut (impl BorrowAppContext + BorrowMut<App>), route: impl IntoRoute) {
+        let descriptor = route.into_route();
+        debug_log!("Navigator::defer_push: queueing path '{}'", descriptor.path);
+        let already_scheduled = cx.update_global::<GlobalRouter, _>(|router, _| {
+            router.deferred_push.replace(descriptor.path).is_some()
+        });
+        if already_scheduled {
+            return;
+        }
+        cx.borrow_mut().defer(|cx| {
+            let path = cx.update_global::<GlobalRouter, _>(|router, _| router.deferred_push.take());
+            if let Some(path) = path {
+                Self::push(cx, path);
+            }
+        });
+    }
+
+    /// Replace current path without adding to history.
+    pub fn replace(cx: &mut (impl BorrowAppContext + BorrowMut<App>), route: impl IntoRoute) {
+        let descriptor = route.into_route();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.replace(descriptor.path, app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+
+    /// Apply a sequence of pushes and replaces as one navigation; see
+    /// [`GlobalRouter::navigate_batch`].
+    ///
+    /// Windows refresh once, after the last op.
+    ///
+    /// # Errors
+    ///
+    /// Returns the position and outcome of the first op that did not go
+    /// through.
+    pub fn navigate_batch(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        ops: Vec<BatchOp>,
+    ) -> Result<String, BatchNavigationError> {
+        let result = cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.navigate_batch(ops, app)
+        });
+        refresh_after_navigation(cx.borrow_mut());
+        result
+    }
+
+    /// Navigate to a new path, refreshing only `window`.
+    ///
+    /// A window opened by [`push_new_window`](Self::push_new_window)
+    /// navigates its own history. Any other window navigates the shared one
+    /// without re-rendering the rest: another window rendering a
+    /// [`router_view`](crate::router_view) of the shared state shows the new
+    /// route on its next render.
+    pub fn push_in_window(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        window: impl Into<AnyWindowHandle>,
+        route: impl IntoRoute,
+    ) {
+        let window = window.into();
+        let descriptor = route.into_route();
+        debug_log!(
+            "Navigator::push_in_window: pushing path '{}'",
+            descriptor.path
+        );
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.in_window(window.window_id(), |router| {
+                router.push(descriptor.path, app);
+            });
+        });
+        if navigation_finished(cx.borrow_mut()) {
+            refresh_window(cx.borrow_mut(), window);
+        }
+    }
+
+    /// Replace current path, refreshing only `window`.
+    ///
+    /// See [`push_in_window`](Self::push_in_window).
+    pub fn replace_in_window(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        window: impl Into<AnyWindowHandle>,
+        route: impl IntoRoute,
+    ) {
+        let window = window.into();
+        let descriptor = route.into_route();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.in_window(window.window_id(), |router| {
+                router.replace(descriptor.path, app);
+            });
+        });
+        if navigation_finished(cx.borrow_mut()) {
+            refresh_window(cx.borrow_mut(), window);
+        }
+    }
+
+    /// Go back, refreshing only `window`.
+    ///
+    /// See [`push_in_window`](Self::push_in_window).
+    pub fn pop_in_window(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        window: impl Into<AnyWindowHandle>,
+    ) {
+        let window = window.into();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.in_window(window.window_id(), |router| router.back(app));
+        });
+        if navigation_finished(cx.borrow_mut()) {
+            refresh_window(cx.borrow_mut(), window);
+        }
+    }
+
+    /// Push a new path with associated [`HistoryState`] data.
+    pub fn push_with_state(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        route: impl IntoRoute,
+        state: HistoryState,
+    ) {
+        let descriptor = route.into_route();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.push_with_state(descriptor.path, state, app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+
+    /// Replace current path with associated [`HistoryState`] data.
+    pub fn replace_with_state(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        route: impl IntoRoute,
+        state: HistoryState,
+    ) {
+        let descriptor = route.into_route();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.replace_with_state(descriptor.path, state, app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+
+    /// Push a new path carrying a typed one-shot payload.
+    ///
+    /// See [`GlobalRouter::push_with_payload`].
+    pub fn push_with_payload<T: Any + Send + Sync>(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        route: impl IntoRoute,
+        payload: T,
+    ) {
+        let descriptor = route.into_route();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.push_with_payload(descriptor.path, payload, app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+
+    /// Take the current entry's payload of type `T`, consuming it.
+    ///
+    /// See [`GlobalRouter::take_payload`].
+    pub fn take_payload<T: Any + Send + Sync>(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+    ) -> Option<T> {
+        cx.update_global::<GlobalRouter, _>(|router, _| router.take_payload())
+    }
+
+    /// Return the value of type `T` loaded for the current route.
+    ///
+    /// See [`GlobalRouter::loader_data`].
+    pub fn loader_data<T: Any + Send + Sync>(cx: &App) -> Option<Arc<T>> {
+        cx.global::<GlobalRouter>().loader_data()
+    }
+
+    /// Return the current route's param of type `T`, parsed.
+    ///
+    /// See [`GlobalRouter::param`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParamTypeError`] if `T` is not registered, or its param
+    /// is missing or malformed.
+    pub fn param<T: RouteParamType>(cx: &App) -> Result<T, ParamTypeError> {
+        cx.global::<GlobalRouter>().param()
+    }
+
+    /// Return the value of type `T` left for the current leaf's navigation.
+    ///
+    /// See [`GlobalRouter::navigation_extension`].
+    pub fn navigation_extension<T: Any + Send + Sync>(cx: &App) -> Option<Arc<T>> {
+        cx.global::<GlobalRouter>().navigation_extension()
+    }
+
+    /// Save the scroll offset of the current entry.
+    ///
+    /// See [`GlobalRouter::save_scroll`].
+    pub fn save_scroll(cx: &mut (impl BorrowAppContext + BorrowMut<App>), x: f32, y: f32) {
+        cx.update_global::<GlobalRouter, _>(|router, _| router.save_scroll(x, y));
+    }
+
+    /// Return the scroll intent of the latest navigation.
+    ///
+    /// See [`GlobalRouter::scroll_intent`].
+    pub fn scroll_intent(cx: &App) -> Option<ScrollIntent> {
+        cx.global::<GlobalRouter>().scroll_intent()
+    }
+
+    /// Return the current [`HistoryEntry`] (path + optional state).
+    pub fn current_entry(cx: &App) -> HistoryEntry {
+        cx.global::<GlobalRouter>().current_entry().clone()
+    }
+
+    /// Answer the pending guard challenge and resume its navigation.
+    ///
+    /// See [`GlobalRouter::answer_challenge`].
+    #[cfg(feature = "guard")]
+    pub fn answer_challenge(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        response: impl Into<String>,
+    ) {
+        let response = response.into();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.answer_challenge(response, app);
+        });
+        // The prompt goes away whatever the answer, so refresh even when
+        // the navigation did not commit
+        if !navigation_finished(cx.borrow_mut()) {
+            cx.borrow_mut().refresh_windows();
+        }
+    }
+
+    /// Cancel the pending guard challenge, staying on the current path.
+    #[cfg(feature = "guard")]
+    pub fn cancel_challenge(cx: &mut (impl BorrowAppContext + BorrowMut<App>)) {
+        cx.update_global::<GlobalRouter, _>(|router, _| {
+            router.cancel_challenge();
+        });
+        cx.borrow_mut().refresh_windows();
+    }
+
+    /// Go back to the previous route.
+    pub fn pop(cx: &mut (impl BorrowAppContext + BorrowMut<App>)) {
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.back(app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+
+    /// Alias for [`pop`](Navigator::pop).
+    pub fn back(cx: &mut (impl BorrowAppContext + BorrowMut<App>)) {
+        Self::pop(cx);
+    }
+
+    /// Go back, or replace the current route with `fallback` when there is
+    /// no history. See [`GlobalRouter::back_or`].
+    pub fn back_or(cx: &mut (impl BorrowAppContext + BorrowMut<App>), fallback: impl IntoRoute) {
+        let descriptor = fallback.into_route();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.back_or(descriptor.path, app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+
+    /// Go forward in history.
+    pub fn forward(cx: &mut (impl BorrowAppContext + BorrowMut<App>)) {
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.forward(app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+
+    /// Capture the navigation state. See [`GlobalRouter::snapshot`].
+    pub fn snapshot(cx: &App) -> RouterSnapshot {
+        cx.global::<GlobalRouter>().snapshot()
+    }
+
+    /// Make partition `key` active. See [`GlobalRouter::switch_partition`].
+    pub fn switch_partition(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        key: &str,
+    ) -> NavigationResult {
+        let result = cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.switch_partition(key, app)
+        });
+        cx.borrow_mut().refresh_windows();
+        result
+    }
+
+    /// Open `path` in a new window with a navigation state of its own.
+    ///
+    /// The window's root is a [`RouterWindow`] on a fresh history navigated
+    /// to `path` through the full pipeline before the window opens; closing
+    /// the window drops it. The window it was opened from keeps showing its
+    /// own route. A target guards would block or redirect opens no window
+    /// and returns [`NavigationResult::Blocked`]. See
+    /// [`new_window`](crate::new_window).
+    pub fn push_new_window(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        path: impl Into<String>,
+        options: WindowOptions,
+    ) -> (NavigationResult, Option<WindowHandle<RouterWindow>>) {
+        let path = path.into();
+        debug_log!("Navigator::push_new_window: opening '{}'", path);
+        let (result, state) = cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.open_window_state(path, app)
+        });
+        let app: &mut App = cx.borrow_mut();
+        let finished = navigation_finished(app);
+        let Some(state) = state else {
+            return (result, None);
+        };
+        let opened = app.open_window(options, |window, cx| {
+            let id = window.window_handle().window_id();
+            cx.update_global::<GlobalRouter, _>(|router, _| router.adopt_window_state(id, state));
+            cx.new(|cx| RouterWindow::new(id, cx))
+        });
+        match opened {
+            Ok(handle) => {
+                if finished {
+                    refresh_window(app, handle.into());
+                }
+                (result, Some(handle))
+            }
+            Err(error) => {
+                error_log!("Could not open a window: {}", error);
+                let error = NavigationError::NavigationFailed {
+                    message: format!("Could not open a window: {error}"),
+                };
+                (NavigationResult::Error(error), None)
+            }
+        }
+    }
+
+    /// Re-apply a snapshot without running guards. See [`GlobalRouter::restore`].
+    pub fn restore(cx: &mut (impl BorrowAppContext + BorrowMut<App>), snapshot: RouterSnapshot) {
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.restore(snapshot, app);
+        });
+        cx.borrow_mut().refresh_windows();
+    }
+
+    /// Report user activity, restarting the current route's idle timeout.
+    ///
+    /// Call from a top-level input listener, or wrap the root element with
+    /// [`track_activity`](crate::timers::track_activity). Does nothing
+    /// before the router is initialized. See [`Route::idle_timeout`].
+    pub fn notify_activity(cx: &mut (impl BorrowAppContext + BorrowMut<App>)) {
+        let app: &mut App = cx.borrow_mut();
+        if app.try_global::<GlobalRouter>().is_none() {
+            return;
+        }
+        let now = app.background_executor().now();
+        app.update_global::<GlobalRouter, _>(|router, _| {
+            router.route_timers.notify_activity(now);
+        });
+    }
+
+    /// Get current path.
+    pub fn current_path(cx: &App) -> String {
+        cx.global::<GlobalRouter>().current_path().to_string()
+    }
+
+    /// Check whether the current path matches `pattern`, e.g. to show a
+    /// banner only on `/users/*`.
+    ///
+    /// `pattern` uses route syntax (`:param`, `*wildcard`) but need not be a
+    /// registered route. See [`path_matches`](crate::pattern::path_matches).
+    pub fn matches(cx: &App, pattern: &str) -> bool {
+        path_matches(cx.global::<GlobalRouter>().current_path(), pattern)
+    }
+
+    /// Check whether a navigation is in flight, e.g. to show a loading indicator.
+    ///
+    /// See [`GlobalRouter::pending_navigation`].
+    pub fn is_navigating(cx: &App) -> bool {
+        cx.global::<GlobalRouter>().pending_navigation().is_some()
+    }
+
+    /// Get the accumulated params at `depth` of the current match stack.
+    ///
+    /// See [`MatchStack::params_at_depth`].
+    pub fn params_at(cx: &App, depth: usize) -> Option<RouteParams> {
+        cx.global::<GlobalRouter>()
+            .match_stack()
+            .params_at_depth(depth)
+            .cloned()
+    }
+
+    /// Check if can go back.
+    pub fn can_pop(cx: &App) -> bool {
+        cx.global::<GlobalRouter>().can_go_back()
+    }
+
+    /// Alias for [`can_pop`](Navigator::can_pop).
+    pub fn can_go_back(cx: &App) -> bool {
+        Self::can_pop(cx)
+    }
+
+    /// Check if can go forward.
+    pub fn can_go_forward(cx: &App) -> bool {
+        cx.global::<GlobalRouter>().can_go_forward()
+    }
+
+    /// Navigate to a named route with parameters.
+    pub fn push_named(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        name: &str,
+        params: &RouteParams,
+    ) {
+        let name = name.to_string();
+        let params = params.clone();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.push_named(&name, &params, app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+
+    /// Get the name of the current route, see [`MatchStack::leaf_route_name`].
+    ///
+    /// Pass it to [`url_for`](Self::url_for) with other params to link to
+    /// the same route for another entity.
+    pub fn current_route_name(cx: &App) -> Option<String> {
+        let router = cx.global::<GlobalRouter>();
+        router.match_stack().leaf_route_name().map(str::to_string)
+    }
+
+    /// Render what navigating to `path` would show, without navigating.
+    ///
+    /// See [`GlobalRouter::render_preview`].
+    pub fn render_preview(
+        window: &mut Window,
+        cx: &mut App,
+        path: &str,
+        constraints: PreviewConstraints,
+    ) -> Option<AnyElement> {
+        GlobalRouter::render_preview(window, cx, path, constraints)
+    }
+
+    /// Generate URL for a named route.
+    pub fn url_for(cx: &App, name: &str, params: &RouteParams) -> Option<String> {
+        cx.global::<GlobalRouter>().url_for(name, params)
+    }
+
+    /// Generate URL for a named route, reporting missing params.
+    ///
+    /// # Errors
+    ///
+    /// See [`GlobalRouter::url_for_checked`].
+    pub fn url_for_checked(
+        cx: &App,
+        name: &str,
+        params: &RouteParams,
+    ) -> Result<String, Vec<String>> {
+        cx.global::<GlobalRouter>().url_for_checked(name, params)
+    }
+
+    /// Build the canonical URL of `target`.
+    ///
+    /// # Errors
+    ///
+    /// See [`GlobalRouter::canonical_url`].
+    pub fn canonical_url(cx: &App, target: CanonicalTarget<'_>) -> Result<String, UrlForError> {
+        cx.global::<GlobalRouter>().canonical_url(target)
+    }
+
+    /// Copy the canonical URL of the current location to the clipboard.
+    ///
+    /// Returns the copied URL; nothing is copied on error.
+    ///
+    /// # Errors
+    ///
+    /// See [`GlobalRouter::canonical_url`].
+    pub fn copy_current_url(cx: &App) -> Result<String, UrlForError> {
+        let url = Self::canonical_url(cx, CanonicalTarget::Current)?;
+        cx.write_to_clipboard(gpui::ClipboardItem::new_string(url.clone()));
+        Ok(url)
+    }
+
+    /// Navigate to a URL built by [`canonical_url`](Self::canonical_url).
+    ///
+    /// See [`GlobalRouter::open_url`].
+    pub fn open_url(cx: &mut (impl BorrowAppContext + BorrowMut<App>), url: &str) {
+        let url = url.to_string();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.open_url(&url, app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+
+    /// Set transition for the next navigation.
+    #[cfg(feature = "transition")]
+    pub fn set_next_transition(cx: &mut impl BorrowAppContext, transition: Transition) {
+        cx.update_global::<GlobalRouter, _>(|router, _| {
+            router.set_next_transition(transition);
+        });
+    }
+
+    /// Capture `bounds` as the start of the hero `id` for the next navigation.
+    ///
+    /// For programmatic navigations; [`RouterLink::hero`](crate::RouterLink::hero)
+    /// captures on click. See [`hero`](crate::hero).
+    #[cfg(feature = "transition")]
+    pub fn capture_hero(
+        cx: &mut impl BorrowAppContext,
+        id: impl Into<gpui::SharedString>,
+        bounds: gpui::Bounds<gpui::Pixels>,
+    ) {
+        let id = id.into();
+        cx.update_global::<GlobalRouter, _>(|router, _| {
+            router.heroes.capture(id, bounds);
+        });
+    }
+
+    /// Navigate with a specific transition.
+    #[cfg(feature = "transition")]
+    pub fn push_with_transition(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        route: impl IntoRoute,
+        transition: Transition,
+    ) {
+        let descriptor = route.into_route();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.push_with_transition(descriptor.path, transition, app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+
+    /// Replace with a specific transition.
+    #[cfg(feature = "transition")]
+    pub fn replace_with_transition(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        route: impl IntoRoute,
+        transition: Transition,
+    ) {
+        let descriptor = route.into_route();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.replace_with_transition(descriptor.path, transition, app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+
+    /// Push named route with a specific transition.
+    #[cfg(feature = "transition")]
+    pub fn push_named_with_transition(
+        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
+        name: &str,
+        params: &RouteParams,
+        transition: Transition,
+    ) {
+        let name = name.to_string();
+        let params = params.clone();
+        cx.update_global::<GlobalRouter, _>(|router, cx| {
+            let app: &App = cx.borrow_mut();
+            router.set_next_transition(transition);
+            router.push_named(&name, &params, app);
+        });
+        refresh_after_navigation(cx.borrow_mut());
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::test_support::{page, push_result};
+    use gpui::{IntoElement, TestAppContext};
+
+    #[gpui::test]
+    fn test_nav_push(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/users", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/users/:id", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        let initial_path = cx.read(Navigator::current_path);
+        assert_eq!(initial_path, "/");
+
+        cx.update(|cx| Navigator::push(cx, "/users"));
+        assert_eq!(cx.read(Navigator::current_path), "/users");
+
+        cx.update(|cx| Navigator::push(cx, "/users/123"));
+        assert_eq!(cx.read(Navigator::current_path), "/users/123");
+    }
+
+    #[gpui::test]
+    fn test_nav_back_forward(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/page1", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/page2", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        cx.update(|cx| {
+            Navigator::push(cx, "/page1");
+            Navigator::push(cx, "/page2");
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/page2");
+        assert!(cx.read(Navigator::can_pop));
+
+        cx.update(Navigator::pop);
+        assert_eq!(cx.read(Navigator::current_path), "/page1");
+        assert!(cx.read(Navigator::can_pop));
+        assert!(cx.read(Navigator::can_go_forward));
+
+        cx.update(Navigator::forward);
+        assert_eq!(cx.read(Navigator::current_path), "/page2");
+        assert!(!cx.read(Navigator::can_go_forward));
+    }
+
+    #[gpui::test]
+    fn test_nav_replace(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/login", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/home", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        cx.update(|cx| {
+            Navigator::push(cx, "/login");
+            Navigator::replace(cx, "/home");
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+
+        cx.update(Navigator::pop);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    #[gpui::test]
+    fn test_nav_can_go_back_boundaries(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        assert!(!cx.read(Navigator::can_pop));
+
+        cx.update(|cx| Navigator::push(cx, "/page1"));
+        assert!(cx.read(Navigator::can_pop));
+
+        cx.update(Navigator::pop);
+        assert!(!cx.read(Navigator::can_pop));
+    }
+
+    #[gpui::test]
+    fn test_nav_back_or_falls_back_without_history(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/items"));
+                router.add_route(page("/items/:id"));
+            });
+        });
+
+        // With history: a plain back
+        cx.update(|cx| Navigator::push(cx, "/items/7"));
+        cx.update(|cx| Navigator::back_or(cx, "/items"));
+        assert_eq!(cx.read(Navigator::current_path), "/");
+        assert!(cx.read(Navigator::can_go_forward));
+
+        // Opened directly, as from a deep link: nothing to go back to
+        cx.update(|cx| Navigator::replace(cx, "/items/7"));
+        assert!(!cx.read(Navigator::can_pop));
+        cx.update(|cx| Navigator::back_or(cx, "/items"));
+        assert_eq!(cx.read(Navigator::current_path), "/items");
+        assert!(!cx.read(Navigator::can_pop));
+    }
+
+    #[gpui::test]
+    fn test_nav_current_route_name_round_trips_url_for(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/users/:id").name("user-profile"));
+                router.add_route(page("/settings").name("settings").child(page("profile")));
+            });
+        });
+        assert_eq!(cx.read(Navigator::current_route_name), None);
+
+        cx.update(|cx| Navigator::push(cx, "/users/42"));
+        let name = cx.read(Navigator::current_route_name).unwrap();
+        assert_eq!(name, "user-profile");
+        let mut params = RouteParams::new();
+        params.insert("id", "7");
+        assert_eq!(
+            cx.read(|cx| Navigator::url_for(cx, &name, &params)),
+            Some("/users/7".to_string())
+        );
+
+        // The leaf's own name, not its ancestor's
+        cx.update(|cx| Navigator::push(cx, "/settings/profile"));
+        assert_eq!(cx.read(Navigator::current_route_name), None);
+    }
+
+    #[gpui::test]
+    fn test_nav_multiple_pushes(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/step1", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/step2", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/step3", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        cx.update(|cx| {
+            Navigator::push(cx, "/step1");
+            Navigator::push(cx, "/step2");
+            Navigator::push(cx, "/step3");
+        });
+
+        assert_eq!(cx.read(Navigator::current_path), "/step3");
+
+        cx.update(Navigator::pop);
+        assert_eq!(cx.read(Navigator::current_path), "/step2");
+
+        cx.update(Navigator::pop);
+        assert_eq!(cx.read(Navigator::current_path), "/step1");
+
+        cx.update(Navigator::pop);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    #[gpui::test]
+    fn test_nav_with_route_parameters(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/users/:id", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new(
+                    "/posts/:id/comments/:commentId",
+                    |_, _cx, _params| gpui::div().into_any_element(),
+                ));
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/users/42"));
+        assert_eq!(cx.read(Navigator::current_path), "/users/42");
+
+        cx.update(|cx| Navigator::push(cx, "/posts/123/comments/456"));
+        assert_eq!(cx.read(Navigator::current_path), "/posts/123/comments/456");
+    }
+
+    #[gpui::test]
+    fn test_navigator_matches_unregistered_patterns(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/users/:id/posts"));
+            });
+        });
+        let matches =
+            |cx: &mut TestAppContext, pattern: &str| cx.read(|cx| Navigator::matches(cx, pattern));
+
+        cx.update(|cx| Navigator::push(cx, "/users/42/posts"));
+        assert!(matches(cx, "/users/42/posts"));
+        assert!(matches(cx, "/users/:user/posts"));
+        assert!(matches(cx, "/users/*"));
+        assert!(!matches(cx, "/users/:id"));
+        assert!(!matches(cx, "/teams/*"));
+
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert!(matches(cx, "/"));
+        assert!(!matches(cx, "/users/*"));
+    }
+
+    #[gpui::test]
+    fn test_navigator_of_style(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/home", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/profile", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        cx.update(|cx| {
+            let _ = Navigator::of(cx).push("/home");
+        });
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+
+        cx.update(|cx| {
+            let _ = Navigator::of(cx).push("/profile").pop();
+        });
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+
+        cx.update(|cx| {
+            let _ = Navigator::of(cx).replace("/profile");
+        });
+        assert_eq!(cx.read(Navigator::current_path), "/profile");
+
+        assert!(cx.read(Navigator::can_pop));
+        cx.update(|cx| {
+            let _ = Navigator::of(cx).pop();
+        });
+        assert_eq!(cx.read(Navigator::current_path), "/");
+        assert!(!cx.read(Navigator::can_pop));
+    }
+
+    #[gpui::test]
+    fn test_string_into_route(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/home", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/home"));
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+
+        cx.update(|cx| Navigator::push(cx, String::from("/home")));
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+    }
+
+    // ========================================================================
+    // Not-found behavior tests
+    // ========================================================================
+
+    fn init_not_found_router(cx: &mut TestAppContext, behavior: NotFoundBehavior) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_not_found_behavior(behavior);
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/home", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+    }
+
+    #[gpui::test]
+    fn test_not_found_renders_by_default(cx: &mut TestAppContext) {
+        init_not_found_router(cx, NotFoundBehavior::default());
+
+        cx.update(|cx| Navigator::push(cx, "/missing"));
+        assert_eq!(cx.read(Navigator::current_path), "/missing");
+        assert!(cx.read(|cx| cx.global::<GlobalRouter>().match_stack().is_empty()));
+    }
+
+    #[gpui::test]
+    fn test_not_found_redirects_to_fallback(cx: &mut TestAppContext) {
+        init_not_found_router(cx, NotFoundBehavior::Redirect("/home".into()));
+
+        cx.update(|cx| Navigator::push(cx, "/missing"));
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+        assert!(cx.read(|cx| !cx.global::<GlobalRouter>().match_stack().is_empty()));
+
+        // The unmatched entry was replaced, so back returns to the start
+        cx.update(Navigator::back);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    #[gpui::test]
+    fn test_not_found_unmatched_fallback_stops(cx: &mut TestAppContext) {
+        init_not_found_router(cx, NotFoundBehavior::Redirect("/nowhere".into()));
+
+        let result = cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, cx| router.push("/missing".into(), cx))
+        });
+        assert!(result.is_not_found());
+        assert_eq!(cx.read(Navigator::current_path), "/nowhere");
+    }
+
+    #[gpui::test]
+    fn test_current_route_is_match_stack_leaf(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/users").children(vec![
+                    page(":id").children(vec![page("posts").into()]).into(),
+                ]));
+            });
+        });
+
+        for path in ["/users", "/users/7", "/users/7/posts"] {
+            cx.update(|cx| Navigator::push(cx, path));
+            cx.read(|cx| {
+                let router = cx.global::<GlobalRouter>();
+                let leaf = router.match_stack().leaf().unwrap();
+                let route = router.current_route().unwrap();
+                assert!(Arc::ptr_eq(route, &leaf.route), "{path}");
+                assert!(std::ptr::eq(router.leaf().unwrap(), leaf));
+
+                let current = router.current_match_immutable().unwrap();
+                assert_eq!(current.pattern, leaf.accumulated_pattern());
+                assert_eq!(&current.params, router.leaf_params().unwrap().all());
+            });
+        }
+
+        // The grandchild, not the `/users` route containing it
+        cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.current_route().unwrap().config.path, "posts");
+            assert_eq!(router.leaf_params().unwrap().get("id"), Some(&"7".into()));
+            assert_eq!(
+                router.current_match_immutable().unwrap().pattern,
+                "/users/:id/posts"
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_current_route_chain_is_root_to_leaf(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/").children(vec![page("users")
+                    .children(vec![page(":id").children(vec![page("posts").into()]).into()])
+                    .into()]));
+            });
+        });
+
+        let chain = |cx: &mut TestAppContext| {
+            cx.read(|cx| {
+                cx.global::<GlobalRouter>()
+                    .current_route_chain()
+                    .iter()
+                    .map(|route| route.config.path.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+        cx.update(|cx| Navigator::push(cx, "/users/7/posts"));
+        assert_eq!(chain(cx), ["/", "users", ":id", "posts"]);
+        cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            let chain = router.current_route_chain();
+            assert!(Arc::ptr_eq(
+                chain.last().unwrap(),
+                router.current_route().unwrap()
+            ));
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/nowhere"));
+        assert!(chain(cx).is_empty());
+    }
+
+    // ========================================================================
+    // Guard integration tests
+    // ========================================================================
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_guard_blocks_navigation(cx: &mut TestAppContext) {
+        use crate::AuthGuard;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(
+                    Route::new("/protected", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .guard(AuthGuard::new(|_| false, "/login")),
+                );
+                router.add_route(Route::new("/login", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        // Guard should redirect to /login
+        cx.update(|cx| Navigator::push(cx, "/protected"));
+
+        // We end up at /login (redirect), not /protected
+        assert_eq!(cx.read(Navigator::current_path), "/login");
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_guard_allows_navigation(cx: &mut TestAppContext) {
+        use crate::AuthGuard;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(
+                    Route::new("/dashboard", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .guard(AuthGuard::new(|_| true, "/login")),
+                );
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/dashboard"));
+        assert_eq!(cx.read(Navigator::current_path), "/dashboard");
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_guard_denies_navigation(cx: &mut TestAppContext) {
+        use crate::guard_fn;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(
+                    Route::new("/forbidden", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .guard(guard_fn(|_, _| NavigationAction::deny("No access"))),
+                );
+            });
         });
-        self.cx.borrow_mut().refresh_windows();
-        self
+
+        cx.update(|cx| Navigator::push(cx, "/forbidden"));
+        // Navigation was blocked, path should remain at "/"
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_parent_guard_blocks_child(cx: &mut TestAppContext) {
+        use crate::AuthGuard;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(
+                    Route::new("/dashboard", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .guard(AuthGuard::new(|_| false, "/login"))
+                    .child(Route::new("settings", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })),
+                );
+                router.add_route(Route::new("/login", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+
+        // Guard on /dashboard should also block /dashboard/settings
+        cx.update(|cx| Navigator::push(cx, "/dashboard/settings"));
+        assert_eq!(cx.read(Navigator::current_path), "/login");
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_children_guard_spares_the_parent(cx: &mut TestAppContext) {
+        use crate::AuthGuard;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/dashboard")
+                        .children_guard(AuthGuard::new(|_| false, "/login"))
+                        .child(page("x")),
+                );
+                router.add_route(page("/login"));
+            });
+        });
+
+        // The layout itself stays reachable
+        cx.update(|cx| Navigator::push(cx, "/dashboard"));
+        assert_eq!(cx.read(Navigator::current_path), "/dashboard");
+        assert!(cx.read(|cx| cx
+            .global::<GlobalRouter>()
+            .guard_order("/dashboard")
+            .is_empty()));
+
+        cx.update(|cx| Navigator::push(cx, "/dashboard/x"));
+        assert_eq!(cx.read(Navigator::current_path), "/login");
+    }
+
+    #[gpui::test]
+    fn test_snapshot_restore_returns_path_and_stack(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/users").child(page(":id")));
+                router.add_route(page("/settings"));
+            });
+        });
+        cx.update(|cx| Navigator::push(cx, "/users"));
+        cx.update(|cx| Navigator::push(cx, "/users/7"));
+        let snapshot = cx.read(Navigator::snapshot);
+        assert_eq!(snapshot.current_path(), "/users/7");
+
+        cx.update(Navigator::back);
+        cx.update(|cx| Navigator::push(cx, "/settings"));
+        assert_eq!(cx.read(Navigator::current_path), "/settings");
+
+        cx.update(|cx| Navigator::restore(cx, snapshot));
+        assert_eq!(cx.read(Navigator::current_path), "/users/7");
+        cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.state().history().len(), 3);
+            assert_eq!(router.state().history().current_index(), 2);
+            let stack = router.match_stack();
+            assert_eq!(stack.len(), 2);
+            assert_eq!(stack.leaf_pattern(), Some("/users/:id"));
+            assert_eq!(stack.params().get("id").map(String::as_str), Some("7"));
+        });
+
+        // History moves on from the restored cursor
+        cx.update(Navigator::back);
+        assert_eq!(cx.read(Navigator::current_path), "/users");
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_restore_skips_guards(cx: &mut TestAppContext) {
+        use crate::{guard_fn, NavigationAction};
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        static LOCKED: AtomicBool = AtomicBool::new(false);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/vault").guard(guard_fn(|_, _| {
+                    if LOCKED.load(Ordering::SeqCst) {
+                        NavigationAction::deny("locked")
+                    } else {
+                        NavigationAction::Continue
+                    }
+                })));
+            });
+        });
+        cx.update(|cx| Navigator::push(cx, "/vault"));
+        let snapshot = cx.read(Navigator::snapshot);
+        cx.update(Navigator::back);
+
+        LOCKED.store(true, Ordering::SeqCst);
+        cx.update(|cx| Navigator::push(cx, "/vault"));
+        assert_eq!(cx.read(Navigator::current_path), "/");
+
+        cx.update(|cx| Navigator::restore(cx, snapshot));
+        assert_eq!(cx.read(Navigator::current_path), "/vault");
+    }
+
+    fn init_partition_router(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/users").child(page(":id")));
+                router.add_route(page("/settings"));
+                router.add_route(page("/draft").protect_unsaved("draft"));
+                assert!(router.create_partition("work"));
+            });
+        });
+    }
+
+    fn active_partition(cx: &mut TestAppContext) -> String {
+        cx.read(|cx| cx.global::<GlobalRouter>().active_partition().to_string())
+    }
+
+    #[gpui::test]
+    fn test_switch_partition_restores_each_world(cx: &mut TestAppContext) {
+        init_partition_router(cx);
+        cx.update(|cx| Navigator::push(cx, "/users"));
+        cx.update(|cx| Navigator::push(cx, "/users/7"));
+
+        let result = cx.update(|cx| Navigator::switch_partition(cx, "work"));
+        assert!(result.is_success());
+        assert_eq!(cx.read(Navigator::current_path), "/");
+        assert_eq!(active_partition(cx), "work");
+        assert!(!cx.read(|cx| cx.global::<GlobalRouter>().can_go_back()));
+        cx.update(|cx| Navigator::push(cx, "/settings"));
+
+        cx.update(|cx| Navigator::switch_partition(cx, GlobalRouter::DEFAULT_PARTITION));
+        assert_eq!(cx.read(Navigator::current_path), "/users/7");
+        cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.match_stack().leaf_pattern(), Some("/users/:id"));
+            assert_eq!(router.state().history().len(), 3);
+            assert_eq!(router.partition_keys(), ["default", "work"]);
+            let work = router.partition_snapshot("work").unwrap();
+            assert_eq!(work.current_path(), "/settings");
+        });
+
+        cx.update(|cx| Navigator::switch_partition(cx, "work"));
+        assert_eq!(cx.read(Navigator::current_path), "/settings");
+        cx.update(Navigator::back);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+
+        let unknown = cx.update(|cx| Navigator::switch_partition(cx, "home"));
+        assert!(unknown.is_error());
+        assert_eq!(active_partition(cx), "work");
+    }
+
+    #[gpui::test]
+    fn test_can_deactivate_blocks_partition_switch(cx: &mut TestAppContext) {
+        init_partition_router(cx);
+        cx.update(|cx| Navigator::push(cx, "/draft"));
+        cx.update(|cx| DirtyStateRegistry::mark_dirty(cx, "draft"));
+
+        let result = cx.update(|cx| Navigator::switch_partition(cx, "work"));
+        assert!(result.is_blocked());
+        assert_eq!(active_partition(cx), GlobalRouter::DEFAULT_PARTITION);
+        assert_eq!(cx.read(Navigator::current_path), "/draft");
+
+        cx.update(|cx| DirtyStateRegistry::mark_clean(cx, "draft"));
+        let result = cx.update(|cx| Navigator::switch_partition(cx, "work"));
+        assert!(result.is_success());
+    }
+
+    #[gpui::test]
+    fn test_drop_partition_evicts_its_caches(cx: &mut TestAppContext) {
+        use gpui::AppContext as _;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        init_partition_router(cx);
+        cx.update(|cx| Navigator::switch_partition(cx, "work"));
+        let released = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&released);
+        cx.update(|cx| {
+            let page = cx.new(|_| PrefetchPage);
+            cx.observe_release(&page, move |_, _| flag.store(true, Ordering::SeqCst))
+                .detach();
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.cache_component("route:/settings".into(), page.into());
+            });
+        });
+        let cached = |cx: &mut TestAppContext| {
+            cx.read(|cx| {
+                cx.global::<GlobalRouter>()
+                    .get_cached_component("route:/settings")
+                    .is_some()
+            })
+        };
+
+        // The cache travels with its partition
+        cx.update(|cx| Navigator::switch_partition(cx, GlobalRouter::DEFAULT_PARTITION));
+        assert!(!cached(cx));
+        cx.update(|cx| Navigator::switch_partition(cx, "work"));
+        assert!(cached(cx));
+        cx.update(|cx| Navigator::switch_partition(cx, GlobalRouter::DEFAULT_PARTITION));
+
+        let dropped = cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                // The active partition stays
+                assert!(!router.drop_partition(GlobalRouter::DEFAULT_PARTITION));
+                router.drop_partition("work")
+            })
+        });
+        assert!(dropped);
+        cx.run_until_parked();
+        assert!(released.load(Ordering::SeqCst));
+        assert_eq!(
+            cx.read(|cx| cx.global::<GlobalRouter>().partition_keys().len()),
+            1
+        );
+        assert!(cx
+            .update(|cx| Navigator::switch_partition(cx, "work"))
+            .is_error());
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_root_page_guard_only_guards_root(cx: &mut TestAppContext) {
+        use crate::AuthGuard;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/about"));
+                router.add_route(page("/login"));
+                router.add_route(page("/").guard(AuthGuard::new(|_| false, "/login")));
+                router.add_route(
+                    page("/app")
+                        .child(page("").guard(AuthGuard::new(|_| false, "/login")))
+                        .child(page("settings")),
+                );
+            });
+        });
+
+        // Pages only guard their own path, not siblings sharing a prefix
+        cx.update(|cx| Navigator::push(cx, "/about"));
+        assert_eq!(cx.read(Navigator::current_path), "/about");
+        cx.update(|cx| Navigator::push(cx, "/app/settings"));
+        assert_eq!(cx.read(Navigator::current_path), "/app/settings");
+
+        cx.update(|cx| Navigator::push(cx, "/app"));
+        assert_eq!(cx.read(Navigator::current_path), "/login");
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert_eq!(cx.read(Navigator::current_path), "/login");
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_root_layout_guard_guards_children(cx: &mut TestAppContext) {
+        use crate::{guard_fn, NavigationAction};
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(
+                    page("/")
+                        .guard(guard_fn(|_, request| {
+                            if request.to == "/foo" {
+                                NavigationAction::deny("members only")
+                            } else {
+                                NavigationAction::Continue
+                            }
+                        }))
+                        .child(page("foo"))
+                        .child(page("bar")),
+                );
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/bar"));
+        assert_eq!(cx.read(Navigator::current_path), "/bar");
+        cx.update(|cx| Navigator::push(cx, "/foo"));
+        assert_eq!(cx.read(Navigator::current_path), "/bar");
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_redirect_loop_protection(cx: &mut TestAppContext) {
+        use crate::guard_fn;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                // /a redirects to /b, /b redirects to /a — infinite loop
+                router.add_route(
+                    Route::new("/a", |_, _cx, _params| gpui::div().into_any_element())
+                        .guard(guard_fn(|_, _| NavigationAction::redirect("/b"))),
+                );
+                router.add_route(
+                    Route::new("/b", |_, _cx, _params| gpui::div().into_any_element())
+                        .guard(guard_fn(|_, _| NavigationAction::redirect("/a"))),
+                );
+            });
+        });
+
+        // Should not infinite loop — stays at "/"
+        cx.update(|cx| Navigator::push(cx, "/a"));
+        // Path stays at "/" because the redirect loop is detected and blocked
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    fn redirect_if_routes(router: &mut GlobalRouter) {
+        router.add_route(page("/"));
+        router.add_route(page("/archive/:id"));
+        router.add_route(page("/projects/:id").redirect_if(|_, params| {
+            let id = params.get("id")?;
+            id.starts_with("old-").then(|| format!("/archive/{id}"))
+        }));
+        // /ping and /pong send every navigation to each other
+        router.add_route(page("/ping").redirect_if(|_, _| Some("/pong".into())));
+        router.add_route(page("/pong").redirect_if(|_, _| Some("/ping".into())));
+    }
+
+    #[gpui::test]
+    fn test_redirect_if_uses_matched_params(cx: &mut TestAppContext) {
+        cx.update(|cx| init_router(cx, redirect_if_routes));
+
+        let result = push_result(cx, "/projects/old-7");
+        assert!(result.is_success());
+        assert_eq!(cx.read(Navigator::current_path), "/archive/old-7");
+        // The redirecting path never entered history
+        cx.update(Navigator::back);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+
+        #[cfg(feature = "explain")]
+        cx.read(|cx| {
+            let explanation = cx.router().explain_navigation(cx, "/projects/old-8");
+            assert_eq!(
+                explanation.hops[0].cause,
+                crate::explain::RedirectCause::Route {
+                    pattern: "/projects/:id".to_string()
+                }
+            );
+            assert_eq!(explanation.landed_on(), Some("/archive/old-8"));
+        });
+    }
+
+    #[gpui::test]
+    fn test_redirect_if_none_renders_route(cx: &mut TestAppContext) {
+        cx.update(|cx| init_router(cx, redirect_if_routes));
+
+        cx.update(|cx| Navigator::push(cx, "/projects/42"));
+        assert_eq!(cx.read(Navigator::current_path), "/projects/42");
+        cx.read(|cx| {
+            let stack = cx.router().match_stack();
+            assert_eq!(stack.leaf_pattern(), Some("/projects/:id"));
+        });
+    }
+
+    #[gpui::test]
+    fn test_redirect_if_loop_is_blocked(cx: &mut TestAppContext) {
+        cx.update(|cx| init_router(cx, redirect_if_routes));
+
+        let result = push_result(cx, "/ping");
+        assert!(result.is_blocked());
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    // ========================================================================
+    // Middleware integration tests
+    // ========================================================================
+
+    #[gpui::test]
+    #[cfg(feature = "middleware")]
+    fn test_middleware_runs_during_navigation(cx: &mut TestAppContext) {
+        use crate::middleware_fn;
+        use std::sync::{Arc, Mutex};
+
+        let calls = Arc::new(Mutex::new(Vec::<String>::new()));
+        let before_calls = calls.clone();
+        let after_calls = calls.clone();
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(
+                    Route::new("/page", |_, _cx, _params| gpui::div().into_any_element())
+                        .middleware(middleware_fn(
+                            move |_cx, req| {
+                                before_calls
+                                    .lock()
+                                    .unwrap()
+                                    .push(format!("before:{}", req.to));
+                            },
+                            move |_cx, req| {
+                                after_calls
+                                    .lock()
+                                    .unwrap()
+                                    .push(format!("after:{}", req.to));
+                            },
+                        )),
+                );
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/page"));
+        assert_eq!(cx.read(Navigator::current_path), "/page");
+
+        let log = calls.lock().unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0], "before:/page");
+        assert_eq!(log[1], "after:/page");
+        drop(log);
     }
 
-    /// Replace current path without adding to history.
-    pub fn replace(self, route: impl IntoRoute) -> Self {
-        let descriptor = route.into_route();
-        self.cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.replace(descriptor.path, app);
+    #[gpui::test]
+    fn test_on_activate_and_on_deactivate_follow_the_leaf(cx: &mut TestAppContext) {
+        use crate::RouteLifecycle;
+
+        struct DenyExit;
+
+        impl RouteLifecycle for DenyExit {
+            fn on_enter(&self, _cx: &App, _request: &NavigationRequest) -> NavigationAction {
+                NavigationAction::Continue
+            }
+
+            fn on_exit(&self, _cx: &App) -> NavigationAction {
+                NavigationAction::deny("locked")
+            }
+
+            fn can_deactivate(&self, _cx: &App) -> NavigationAction {
+                NavigationAction::Continue
+            }
+        }
+
+        let log = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let tracked = |path: &'static str| {
+            let (on, off) = (Arc::clone(&log), Arc::clone(&log));
+            page(path)
+                .on_activate(move |_, params| {
+                    let id = params.get("id").map_or("-", String::as_str).to_string();
+                    on.lock().unwrap().push(format!("activate {path} {id}"));
+                })
+                .on_deactivate(move |_| off.lock().unwrap().push(format!("deactivate {path}")))
+        };
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(tracked("/users/:id"));
+                router.add_route(tracked("/locked").lifecycle(DenyExit));
+            });
         });
-        self.cx.borrow_mut().refresh_windows();
-        self
+        let take = || std::mem::take(&mut *log.lock().unwrap());
+
+        cx.update(|cx| Navigator::push(cx, "/users/1"));
+        assert_eq!(take(), ["activate /users/:id 1"]);
+
+        // A param change re-enters the leaf, like `on_exit` / `on_enter`
+        cx.update(|cx| Navigator::push(cx, "/users/2"));
+        assert_eq!(take(), ["deactivate /users/:id", "activate /users/:id 2"]);
+
+        cx.update(|cx| Navigator::push(cx, "/locked"));
+        assert_eq!(take(), ["deactivate /users/:id", "activate /locked -"]);
+
+        // The lifecycle keeps the navigation from leaving, so no deactivation
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert_eq!(cx.read(Navigator::current_path), "/locked");
+        assert!(take().is_empty());
     }
 
-    /// Go back to the previous route.
-    pub fn pop(self) -> Self {
-        self.cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.back(app);
+    // ========================================================================
+    // Locale tests
+    // ========================================================================
+
+    fn init_localized_router(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_locales(["en", "fr"]);
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(
+                    Route::new("/dashboard", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .name("dashboard"),
+                );
+                router.add_route(
+                    Route::new("/users/:id", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .name("user"),
+                );
+            });
         });
-        self.cx.borrow_mut().refresh_windows();
-        self
     }
 
-    /// Go forward in history.
-    pub fn forward(self) -> Self {
-        self.cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.forward(app);
+    #[gpui::test]
+    fn test_locale_prefix_is_stripped_into_param(cx: &mut TestAppContext) {
+        init_localized_router(cx);
+
+        cx.update(|cx| Navigator::push(cx, "/fr/dashboard"));
+        assert_eq!(cx.read(Navigator::current_path), "/fr/dashboard");
+
+        cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            let leaf = router.match_stack().leaf().expect("dashboard should match");
+            assert_eq!(leaf.route.config.path, "/dashboard");
+            assert_eq!(leaf.params.get("locale"), Some(&"fr".to_string()));
+            assert_eq!(router.current_locale(), Some("fr"));
         });
-        self.cx.borrow_mut().refresh_windows();
-        self
     }
-}
 
-// ============================================================================
-// Navigator
-// ============================================================================
+    #[gpui::test]
+    fn test_locale_is_preserved_across_navigation(cx: &mut TestAppContext) {
+        init_localized_router(cx);
 
-/// Navigation API for convenient route navigation.
-///
-/// Provides static methods for navigation operations:
-/// - `Navigator::push(cx, "/path")` — Navigate to a new page
-/// - `Navigator::pop(cx)` — Go back to previous page
-/// - `Navigator::replace(cx, "/path")` — Replace current page
-///
-/// All navigation methods run the full pipeline (guards, middleware).
-///
-/// # Example
-///
-/// ```ignore
-/// use gpui_navigator::Navigator;
-///
-/// Navigator::push(cx, "/users/123");
-/// Navigator::pop(cx);
-/// Navigator::replace(cx, "/login");
-/// ```
-pub struct Navigator;
+        cx.update(|cx| Navigator::push(cx, "/fr/dashboard"));
+        cx.update(|cx| Navigator::push(cx, "/users/7"));
+        assert_eq!(cx.read(Navigator::current_path), "/fr/users/7");
 
-impl Navigator {
-    /// Get a [`NavigatorHandle`] for chained navigation calls.
-    pub fn of<C: BorrowAppContext + BorrowMut<App>>(cx: &mut C) -> NavigatorHandle<'_, C> {
-        NavigatorHandle { cx }
+        // An explicit locale overrides the current one
+        cx.update(|cx| Navigator::push(cx, "/en/dashboard"));
+        assert_eq!(cx.read(Navigator::current_path), "/en/dashboard");
+
+        // History traversal keeps stored paths untouched
+        cx.update(Navigator::pop);
+        assert_eq!(cx.read(Navigator::current_path), "/fr/users/7");
     }
 
-    /// Navigate to a new path.
-    pub fn push(cx: &mut (impl BorrowAppContext + BorrowMut<App>), route: impl IntoRoute) {
-        let descriptor = route.into_route();
-        debug_log!("Navigator::push: pushing path '{}'", descriptor.path);
-        cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.push(descriptor.path, app);
-        });
-        cx.borrow_mut().refresh_windows();
+    #[gpui::test]
+    fn test_url_for_prefixes_current_locale(cx: &mut TestAppContext) {
+        init_localized_router(cx);
+
+        let mut params = RouteParams::new();
+        params.insert("id", "42");
+
+        assert_eq!(
+            cx.read(|cx| Navigator::url_for(cx, "user", &params)),
+            Some("/users/42".to_string())
+        );
+
+        cx.update(|cx| Navigator::push(cx, "/fr/dashboard"));
+        assert_eq!(
+            cx.read(|cx| Navigator::url_for(cx, "user", &params)),
+            Some("/fr/users/42".to_string())
+        );
     }
 
-    /// Replace current path without adding to history.
-    pub fn replace(cx: &mut (impl BorrowAppContext + BorrowMut<App>), route: impl IntoRoute) {
-        let descriptor = route.into_route();
-        cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.replace(descriptor.path, app);
+    #[gpui::test]
+    fn test_default_locale_without_prefix(cx: &mut TestAppContext) {
+        init_localized_router(cx);
+        cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.set_default_locale("en"));
         });
-        cx.borrow_mut().refresh_windows();
-    }
 
-    /// Push a new path with associated [`HistoryState`] data.
-    pub fn push_with_state(
-        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
-        route: impl IntoRoute,
-        state: HistoryState,
-    ) {
-        let descriptor = route.into_route();
-        cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.push_with_state(descriptor.path, state, app);
+        cx.update(|cx| Navigator::push(cx, "/dashboard"));
+        assert_eq!(cx.read(Navigator::current_path), "/dashboard");
+
+        cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            let leaf = router.match_stack().leaf().expect("dashboard should match");
+            assert_eq!(leaf.params.get("locale"), Some(&"en".to_string()));
+            assert_eq!(router.current_locale(), Some("en"));
+            assert_eq!(
+                router.url_for("dashboard", &RouteParams::new()),
+                Some("/dashboard".to_string())
+            );
         });
-        cx.borrow_mut().refresh_windows();
     }
 
-    /// Replace current path with associated [`HistoryState`] data.
-    pub fn replace_with_state(
-        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
-        route: impl IntoRoute,
-        state: HistoryState,
-    ) {
-        let descriptor = route.into_route();
-        cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.replace_with_state(descriptor.path, state, app);
+    #[gpui::test]
+    fn test_canonical_url_encodes_and_localizes(cx: &mut TestAppContext) {
+        init_localized_router(cx);
+        cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.set_url_base("myapp://app/"));
         });
-        cx.borrow_mut().refresh_windows();
+        let canonical = |cx: &mut TestAppContext, target: CanonicalTarget<'_>| {
+            cx.read(|cx| Navigator::canonical_url(cx, target))
+        };
+        let mut params = RouteParams::new();
+        params.insert("id", "x/y z");
+
+        assert_eq!(
+            canonical(cx, CanonicalTarget::Path("//users/a b/")),
+            Ok("myapp://app/users/a%20b".to_string())
+        );
+        // Already-encoded segments are not encoded twice
+        assert_eq!(
+            canonical(cx, CanonicalTarget::Path("/users/a%20b")),
+            Ok("myapp://app/users/a%20b".to_string())
+        );
+
+        cx.update(|cx| Navigator::push(cx, "/fr/users/7"));
+        assert_eq!(
+            canonical(cx, CanonicalTarget::Current),
+            Ok("myapp://app/fr/users/7".to_string())
+        );
+        assert_eq!(
+            canonical(cx, CanonicalTarget::Path("/dashboard")),
+            Ok("myapp://app/fr/dashboard".to_string())
+        );
+        assert_eq!(
+            canonical(
+                cx,
+                CanonicalTarget::Named {
+                    name: "user",
+                    params: &params
+                }
+            ),
+            Ok("myapp://app/fr/users/x%2Fy%20z".to_string())
+        );
     }
 
-    /// Return the current [`HistoryEntry`] (path + optional state).
-    pub fn current_entry(cx: &App) -> HistoryEntry {
-        cx.global::<GlobalRouter>().current_entry().clone()
+    #[gpui::test]
+    fn test_canonical_url_errors(cx: &mut TestAppContext) {
+        init_localized_router(cx);
+        let canonical = |cx: &mut TestAppContext, target: CanonicalTarget<'_>| {
+            cx.read(|cx| Navigator::canonical_url(cx, target))
+        };
+
+        assert_eq!(
+            canonical(
+                cx,
+                CanonicalTarget::Named {
+                    name: "missing",
+                    params: &RouteParams::new()
+                }
+            ),
+            Err(UrlForError::UnknownName {
+                name: "missing".into()
+            })
+        );
+        assert_eq!(
+            canonical(
+                cx,
+                CanonicalTarget::Named {
+                    name: "user",
+                    params: &RouteParams::new()
+                }
+            ),
+            Err(UrlForError::MissingParams {
+                name: "user".into(),
+                params: vec!["id".into()],
+            })
+        );
+        assert_eq!(
+            canonical(cx, CanonicalTarget::Path("/nowhere/")),
+            Err(UrlForError::NoMatch {
+                path: "/nowhere".into()
+            })
+        );
     }
 
-    /// Go back to the previous route.
-    pub fn pop(cx: &mut (impl BorrowAppContext + BorrowMut<App>)) {
-        cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.back(app);
+    #[gpui::test]
+    fn test_copied_url_round_trips_to_same_match_stack(cx: &mut TestAppContext) {
+        init_localized_router(cx);
+        cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.set_url_base("myapp://app"));
         });
-        cx.borrow_mut().refresh_windows();
-    }
+        let stack = |cx: &mut TestAppContext| {
+            cx.read(|cx| {
+                cx.global::<GlobalRouter>()
+                    .match_stack()
+                    .entries()
+                    .iter()
+                    .map(|entry| {
+                        (
+                            entry.accumulated_pattern().to_string(),
+                            entry.params.clone(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
 
-    /// Alias for [`pop`](Navigator::pop).
-    pub fn back(cx: &mut (impl BorrowAppContext + BorrowMut<App>)) {
-        Self::pop(cx);
-    }
+        cx.update(|cx| Navigator::push(cx, "/fr//users/a%20b%2Fc/"));
+        let before = stack(cx);
+        let url = cx
+            .read(Navigator::copy_current_url)
+            .expect("current path matches");
+        assert_eq!(url, "myapp://app/fr/users/a%20b%2Fc");
+        assert_eq!(
+            cx.read(|cx| cx.read_from_clipboard().and_then(|item| item.text())),
+            Some(url.clone())
+        );
 
-    /// Go forward in history.
-    pub fn forward(cx: &mut (impl BorrowAppContext + BorrowMut<App>)) {
-        cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.forward(app);
+        cx.update(|cx| Navigator::push(cx, "/en/dashboard"));
+        cx.update(|cx| Navigator::open_url(cx, &url));
+        assert_eq!(stack(cx), before);
+        assert_eq!(cx.read(Navigator::current_path), "/fr/users/a%20b%2Fc");
+
+        // Foreign URLs are not opened
+        let result = cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, cx| {
+                router.open_url("https://example.com/users/1", cx)
+            })
         });
-        cx.borrow_mut().refresh_windows();
+        assert!(result.is_error());
+        assert_eq!(cx.read(Navigator::current_path), "/fr/users/a%20b%2Fc");
     }
 
-    /// Get current path.
-    pub fn current_path(cx: &App) -> String {
-        cx.global::<GlobalRouter>().current_path().to_string()
-    }
+    #[test]
+    fn test_split_locale_ignores_unknown_segments() {
+        let mut router = GlobalRouter::new();
+        router.set_locales(["en", "fr"]);
 
-    /// Check if can go back.
-    pub fn can_pop(cx: &App) -> bool {
-        cx.global::<GlobalRouter>().can_go_back()
+        assert_eq!(
+            router.split_locale("/fr/dashboard"),
+            (Some("fr"), "/dashboard")
+        );
+        assert_eq!(router.split_locale("/fr"), (Some("fr"), ""));
+        assert_eq!(router.split_locale("/french/x"), (None, "/french/x"));
+        assert_eq!(router.split_locale("/de/x"), (None, "/de/x"));
     }
 
-    /// Alias for [`can_pop`](Navigator::can_pop).
-    pub fn can_go_back(cx: &App) -> bool {
-        Self::can_pop(cx)
-    }
+    struct PrefetchPage;
 
-    /// Check if can go forward.
-    pub fn can_go_forward(cx: &App) -> bool {
-        cx.global::<GlobalRouter>().can_go_forward()
+    impl gpui::Render for PrefetchPage {
+        fn render(
+            &mut self,
+            _window: &mut Window,
+            _cx: &mut gpui::Context<'_, Self>,
+        ) -> impl IntoElement {
+            gpui::div()
+        }
     }
 
-    /// Navigate to a named route with parameters.
-    pub fn push_named(
-        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
-        name: &str,
-        params: &RouteParams,
-    ) {
-        let name = name.to_string();
-        let params = params.clone();
-        cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.push_named(&name, &params, app);
+    #[gpui::test]
+    fn test_component_cache_evicts_least_recently_used(cx: &mut TestAppContext) {
+        use gpui::AppContext as _;
+
+        cx.update(|cx| {
+            let mut router = GlobalRouter::new();
+            router.set_cache_policy(CachePolicy::new().component_capacity(2));
+            router.cache_component("a".into(), cx.new(|_| PrefetchPage).into());
+            router.cache_component("b".into(), cx.new(|_| PrefetchPage).into());
+
+            // Using "a" makes "b" the least recently used
+            assert!(router.use_cached_component("a").is_some());
+            router.cache_component("c".into(), cx.new(|_| PrefetchPage).into());
+            assert!(router.get_cached_component("a").is_some());
+            assert!(router.get_cached_component("b").is_none());
+            assert!(router.get_cached_component("c").is_some());
+
+            assert!(router.use_cached_component("b").is_none());
+            let stats = router.cache_stats();
+            assert_eq!(
+                (
+                    stats.component_hits,
+                    stats.component_misses,
+                    stats.component_evictions
+                ),
+                (1, 1, 1)
+            );
+
+            // Shrinking the policy evicts from the least recently used end
+            router.set_cache_policy(CachePolicy::new().component_capacity(1));
+            assert!(router.get_cached_component("a").is_none());
+            assert!(router.get_cached_component("c").is_some());
+            assert_eq!(router.cache_stats().component_evictions, 2);
         });
-        cx.borrow_mut().refresh_windows();
     }
 
-    /// Generate URL for a named route.
-    pub fn url_for(cx: &App, name: &str, params: &RouteParams) -> Option<String> {
-        cx.global::<GlobalRouter>().url_for(name, params)
-    }
+    #[gpui::test]
+    fn test_component_state_survives_navigation(cx: &mut TestAppContext) {
+        struct Counter(usize);
+
+        impl gpui::Render for Counter {
+            fn render(
+                &mut self,
+                _window: &mut Window,
+                _cx: &mut gpui::Context<'_, Self>,
+            ) -> impl IntoElement {
+                gpui::div()
+            }
+        }
 
-    /// Set transition for the next navigation.
-    #[cfg(feature = "transition")]
-    pub fn set_next_transition(cx: &mut impl BorrowAppContext, transition: Transition) {
-        cx.update_global::<GlobalRouter, _>(|router, _| {
-            router.set_next_transition(transition);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(Route::component("/counter", || Counter(0)));
+                router.add_route(page("/other"));
+            });
         });
+        // Render the leaf and return its cached entity
+        let render_leaf = |cx: &mut TestAppContext| {
+            cx.add_empty_window().update(|window, cx| {
+                let entry = cx.global::<GlobalRouter>().leaf().unwrap().clone();
+                let _ = entry.build(window, cx);
+                let key = entry.route.component_cache_key(&entry).unwrap();
+                let view = cx.global::<GlobalRouter>().get_cached_component(&key);
+                view.unwrap().clone().downcast::<Counter>().ok().unwrap()
+            })
+        };
+
+        cx.update(|cx| Navigator::push(cx, "/counter"));
+        let counter = render_leaf(cx);
+        cx.update(|cx| counter.update(cx, |counter, _| counter.0 = 5));
+
+        cx.update(|cx| Navigator::push(cx, "/other"));
+        cx.update(|cx| Navigator::push(cx, "/counter"));
+        let again = render_leaf(cx);
+        assert_eq!(again.entity_id(), counter.entity_id());
+        assert_eq!(cx.read(|cx| again.read(cx).0), 5);
+        assert_eq!(cx.read(|cx| cx.router().cache_stats().component_hits), 1);
+
+        // Clearing the caches rebuilds the component with fresh state
+        cx.update(|cx| cx.update_router(|router, _| router.clear_caches()));
+        let fresh = render_leaf(cx);
+        assert_ne!(fresh.entity_id(), counter.entity_id());
+        assert_eq!(cx.read(|cx| fresh.read(cx).0), 0);
     }
 
-    /// Navigate with a specific transition.
-    #[cfg(feature = "transition")]
-    pub fn push_with_transition(
-        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
-        route: impl IntoRoute,
-        transition: Transition,
-    ) {
-        let descriptor = route.into_route();
-        cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.push_with_transition(descriptor.path, transition, app);
+    #[gpui::test]
+    fn test_component_keys_follow_match_entry(cx: &mut TestAppContext) {
+        struct Editor;
+
+        impl gpui::Render for Editor {
+            fn render(
+                &mut self,
+                _window: &mut Window,
+                _cx: &mut gpui::Context<'_, Self>,
+            ) -> impl IntoElement {
+                gpui::div()
+            }
+        }
+
+        let layout = |path: &str| Route::view(path, || gpui::div().into_any_element());
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                // The same relative path and component type under two parents
+                router.add_route(layout("/a").child(Route::component("edit", || Editor)));
+                router.add_route(layout("/b").child(Route::component("edit", || Editor)));
+            });
         });
-        cx.borrow_mut().refresh_windows();
-    }
+        let render_leaf = |cx: &mut TestAppContext, path: &str| {
+            cx.update(|cx| Navigator::push(cx, path));
+            cx.add_empty_window().update(|window, cx| {
+                let entry = cx.global::<GlobalRouter>().leaf().unwrap().clone();
+                let _ = entry.build(window, cx);
+                let key = entry.route.component_cache_key(&entry).unwrap();
+                let view = cx.global::<GlobalRouter>().get_cached_component(&key);
+                (key, view.unwrap().entity_id())
+            })
+        };
 
-    /// Replace with a specific transition.
-    #[cfg(feature = "transition")]
-    pub fn replace_with_transition(
-        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
-        route: impl IntoRoute,
-        transition: Transition,
-    ) {
-        let descriptor = route.into_route();
-        cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.replace_with_transition(descriptor.path, transition, app);
-        });
-        cx.borrow_mut().refresh_windows();
+        let (a_key, a) = render_leaf(cx, "/a/edit");
+        let (b_key, b) = render_leaf(cx, "/b/edit");
+        assert!(a_key.ends_with("@1:/a/edit"));
+        assert_ne!(a_key, b_key);
+        assert_ne!(a, b);
+        assert_eq!(render_leaf(cx, "/a/edit"), (a_key, a));
     }
 
-    /// Push named route with a specific transition.
-    #[cfg(feature = "transition")]
-    pub fn push_named_with_transition(
-        cx: &mut (impl BorrowAppContext + BorrowMut<App>),
-        name: &str,
-        params: &RouteParams,
-        transition: Transition,
-    ) {
-        let name = name.to_string();
-        let params = params.clone();
-        cx.update_global::<GlobalRouter, _>(|router, cx| {
-            let app: &App = cx.borrow_mut();
-            router.set_next_transition(transition);
-            router.push_named(&name, &params, app);
+    #[gpui::test]
+    fn test_pinned_component_survives_eviction_pressure(cx: &mut TestAppContext) {
+        use gpui::AppContext as _;
+
+        cx.update(|cx| {
+            let mut router = GlobalRouter::new();
+            router.set_cache_policy(CachePolicy::new().component_capacity(2));
+            let mut cache = |router: &mut GlobalRouter, key: &str| {
+                router.cache_component(key.into(), cx.new(|_| PrefetchPage).into());
+            };
+            cache(&mut router, "editor");
+            assert!(router.pin_component("editor"));
+
+            // The pinned editor is the oldest entry, yet its neighbours go
+            for key in ["a", "b", "c"] {
+                cache(&mut router, key);
+            }
+            assert!(router.get_cached_component("editor").is_some());
+            assert!(router.get_cached_component("a").is_none());
+            assert_eq!(router.cache_stats().component_evictions, 1);
+
+            // Unpinned, it counts again: the least recently used entry goes
+            assert!(router.use_cached_component("editor").is_some());
+            assert!(router.unpin_component("editor"));
+            assert!(router.get_cached_component("b").is_none());
+            assert!(router.get_cached_component("editor").is_some());
+
+            // ...and it is evicted like any other
+            cache(&mut router, "d");
+            cache(&mut router, "e");
+            assert!(router.get_cached_component("editor").is_none());
+            assert!(!router.unpin_component("editor"));
+            assert_eq!(router.cache_stats().component_evictions, 4);
         });
-        cx.borrow_mut().refresh_windows();
     }
-}
 
-// ============================================================================
-// Tests
-// ============================================================================
+    #[gpui::test]
+    fn test_pinned_component_capacity(cx: &mut TestAppContext) {
+        use gpui::AppContext as _;
 
-#[cfg(test)]
-#[allow(clippy::needless_pass_by_ref_mut)]
-mod tests {
-    use super::*;
-    use gpui::{IntoElement, TestAppContext};
+        cx.update(|cx| {
+            let mut router = GlobalRouter::new();
+            router.set_cache_policy(CachePolicy::new().pinned_component_capacity(1));
+            router.cache_component("a".into(), cx.new(|_| PrefetchPage).into());
+            router.cache_component("b".into(), cx.new(|_| PrefetchPage).into());
+
+            assert!(router.pin_component("a"));
+            assert!(!router.pin_component("b"));
+            assert!(!router.pin_component("missing"));
+            assert!(router.unpin_component("a"));
+            assert!(router.pin_component("b"));
+
+            // Lowering the cap unpins
+            router.set_cache_policy(CachePolicy::new().pinned_component_capacity(0));
+            let entries = router.component_cache_entries();
+            assert!(entries.iter().all(|entry| !entry.pinned));
+            assert!(!router.pin_component("a"));
+        });
+    }
 
     #[gpui::test]
-    fn test_nav_push(cx: &mut TestAppContext) {
+    fn test_component_cache_entries_reflect_pins_and_recency(cx: &mut TestAppContext) {
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/users", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/users/:id", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                router.add_route(page("/"));
+                router.add_route(Route::component("/editor", || PrefetchPage).pin_component(true));
+                router.add_route(Route::component("/preview", || PrefetchPage));
             });
         });
+        let visit = |cx: &mut TestAppContext, path: &str| {
+            cx.update(|cx| Navigator::push(cx, path));
+            cx.add_empty_window().update(|window, cx| {
+                let entry = cx.global::<GlobalRouter>().leaf().unwrap().clone();
+                let _ = entry.build(window, cx);
+            });
+        };
+        let listing = |cx: &mut TestAppContext| {
+            cx.read(|cx| cx.router().component_cache_entries())
+                .into_iter()
+                .map(|entry| (entry.route_pattern, entry.pinned))
+                .collect::<Vec<_>>()
+        };
 
-        let initial_path = cx.read(Navigator::current_path);
-        assert_eq!(initial_path, "/");
+        visit(cx, "/editor");
+        visit(cx, "/preview");
+        assert_eq!(
+            listing(cx),
+            [
+                (Some("/preview".to_string()), false),
+                (Some("/editor".to_string()), true),
+            ]
+        );
 
-        cx.update(|cx| Navigator::push(cx, "/users"));
-        assert_eq!(cx.read(Navigator::current_path), "/users");
+        visit(cx, "/editor");
+        let entries = cx.read(|cx| cx.router().component_cache_entries());
+        assert_eq!(entries[0].route_pattern.as_deref(), Some("/editor"));
+        assert!(entries[0].last_access >= entries[1].last_access);
 
-        cx.update(|cx| Navigator::push(cx, "/users/123"));
-        assert_eq!(cx.read(Navigator::current_path), "/users/123");
+        // Explicit eviction drops pinned entries too
+        let key = entries[0].key.clone();
+        assert!(cx.update(|cx| cx.update_router(|router, _| router.evict_component(&key))));
+        assert_eq!(listing(cx), [(Some("/preview".to_string()), false)]);
     }
 
     #[gpui::test]
-    fn test_nav_back_forward(cx: &mut TestAppContext) {
+    fn test_prefetch_warms_component_cache(cx: &mut TestAppContext) {
         cx.update(|cx| {
             init_router(cx, |router| {
                 router.add_route(Route::new("/", |_, _cx, _params| {
                     gpui::div().into_any_element()
                 }));
-                router.add_route(Route::new("/page1", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/page2", |_, _cx, _params| {
+                router.add_route(Route::component("/heavy", || PrefetchPage));
+                router.add_route(Route::new("/plain", |_, _cx, _params| {
                     gpui::div().into_any_element()
                 }));
             });
         });
 
-        cx.update(|cx| {
-            Navigator::push(cx, "/page1");
-            Navigator::push(cx, "/page2");
+        let window = cx.add_empty_window();
+        let created = window.update(|window, cx| GlobalRouter::prefetch(window, cx, "/heavy"));
+        assert_eq!(created, 1);
+
+        let key = cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            let stack = router.resolve_path("/heavy");
+            let entry = stack.leaf().unwrap();
+            entry.route.component_cache_key(entry).unwrap()
         });
+        assert!(cx.read(|cx| cx
+            .global::<GlobalRouter>()
+            .get_cached_component(&key)
+            .is_some()));
 
-        assert_eq!(cx.read(Navigator::current_path), "/page2");
-        assert!(cx.read(Navigator::can_pop));
+        // Navigation state is untouched
+        assert_eq!(cx.read(Navigator::current_path), "/");
 
-        cx.update(Navigator::pop);
-        assert_eq!(cx.read(Navigator::current_path), "/page1");
-        assert!(cx.read(Navigator::can_pop));
-        assert!(cx.read(Navigator::can_go_forward));
+        // Debounced, and already cached once the window passes
+        let window = cx.add_empty_window();
+        let again = window.update(|window, cx| GlobalRouter::prefetch(window, cx, "/heavy"));
+        assert_eq!(again, 0);
 
-        cx.update(Navigator::forward);
-        assert_eq!(cx.read(Navigator::current_path), "/page2");
-        assert!(!cx.read(Navigator::can_go_forward));
+        // Builder routes have no cache to warm
+        let window = cx.add_empty_window();
+        let plain = window.update(|window, cx| GlobalRouter::prefetch(window, cx, "/plain"));
+        assert_eq!(plain, 0);
     }
 
     #[gpui::test]
-    fn test_nav_replace(cx: &mut TestAppContext) {
+    fn test_component_with_dep_rebuilds_on_change(cx: &mut TestAppContext) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Theme(u64);
+        impl gpui::Global for Theme {}
+
+        let created = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&created);
         cx.update(|cx| {
-            init_router(cx, |router| {
+            cx.set_global(Theme(1));
+            init_router(cx, move |router| {
                 router.add_route(Route::new("/", |_, _cx, _params| {
                     gpui::div().into_any_element()
                 }));
-                router.add_route(Route::new("/login", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/home", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                router.add_route(Route::component_with_dep(
+                    "/themed",
+                    |cx| cx.global::<Theme>().0,
+                    move |_| {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        PrefetchPage
+                    },
+                ));
             });
         });
 
+        let key = |cx: &mut TestAppContext| {
+            cx.read(|cx| {
+                let stack = cx.global::<GlobalRouter>().resolve_path("/themed");
+                let entry = stack.leaf().unwrap();
+                entry.route.component_cache_key_in(cx, entry).unwrap()
+            })
+        };
+        let build = |cx: &mut TestAppContext| {
+            let stack = cx.read(|cx| cx.global::<GlobalRouter>().resolve_path("/themed"));
+            let entry = stack.leaf().unwrap().clone();
+            let window = cx.add_empty_window();
+            window.update(|window, cx| {
+                entry.build(window, cx).unwrap();
+            });
+        };
+
+        let light = key(cx);
+        build(cx);
+        build(cx);
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+
+        cx.update(|cx| cx.set_global(Theme(2)));
+        let dark = key(cx);
+        assert_ne!(dark, light);
+        build(cx);
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+        assert!(cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            router.get_cached_component(&light).is_some()
+                && router.get_cached_component(&dark).is_some()
+        }));
+
+        // Back to the first value: the same key, its instance reused
+        cx.update(|cx| cx.set_global(Theme(1)));
+        assert_eq!(key(cx), light);
+        build(cx);
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+    }
+
+    #[gpui::test]
+    fn test_render_preview_leaves_navigation_untouched(cx: &mut TestAppContext) {
         cx.update(|cx| {
-            Navigator::push(cx, "/login");
-            Navigator::replace(cx, "/home");
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::component("/heavy", || PrefetchPage));
+            });
         });
 
-        assert_eq!(cx.read(Navigator::current_path), "/home");
+        let constraints = PreviewConstraints::new(gpui::size(gpui::px(320.), gpui::px(200.)));
+        let window = cx.add_empty_window();
+        let rendered = window.update(|window, cx| {
+            Navigator::render_preview(window, cx, "/heavy", constraints).is_some()
+        });
+        assert!(rendered);
+        let missing = window.update(|window, cx| {
+            Navigator::render_preview(window, cx, "/missing", constraints).is_some()
+        });
+        assert!(!missing);
 
-        cx.update(Navigator::pop);
         assert_eq!(cx.read(Navigator::current_path), "/");
+        cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.state().history().len(), 1);
+            assert_eq!(router.match_stack().len(), 1);
+            // The preview's entity never reaches the component cache
+            let stack = router.resolve_path("/heavy");
+            let entry = stack.leaf().unwrap();
+            let key = entry.route.component_cache_key(entry).unwrap();
+            assert!(router.get_cached_component(&key).is_none());
+        });
     }
 
     #[gpui::test]
-    fn test_nav_can_go_back_boundaries(cx: &mut TestAppContext) {
+    fn test_render_preview_builds_leaf_or_chain(cx: &mut TestAppContext) {
+        use crate::resolve::{current_parent_depth, set_parent_depth};
+
+        let built = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let layout_log = Arc::clone(&built);
+        let page_log = Arc::clone(&built);
         cx.update(|cx| {
-            init_router(cx, |router| {
+            init_router(cx, move |router| {
                 router.add_route(Route::new("/", |_, _cx, _params| {
                     gpui::div().into_any_element()
                 }));
+                router.add_route(
+                    Route::new("/users", move |window, cx, _params| {
+                        layout_log.lock().unwrap().push("layout".to_string());
+                        crate::render_router_outlet(window, cx, None)
+                    })
+                    .child(Route::new(":id", move |_, _cx, params| {
+                        let id = params.get("id").cloned().unwrap_or_default();
+                        page_log.lock().unwrap().push(format!("user {id}"));
+                        gpui::div().into_any_element()
+                    })),
+                );
             });
         });
 
-        assert!(!cx.read(Navigator::can_pop));
-
-        cx.update(|cx| Navigator::push(cx, "/page1"));
-        assert!(cx.read(Navigator::can_pop));
+        let leaf = PreviewConstraints::new(gpui::size(gpui::px(320.), gpui::px(200.)));
+        let window = cx.add_empty_window();
+        window.update(|window, cx| {
+            // Previews build in the middle of another route's render
+            set_parent_depth(3);
+            assert!(Navigator::render_preview(window, cx, "/users/7", leaf).is_some());
+            assert_eq!(current_parent_depth(), Some(3));
+        });
+        assert_eq!(*built.lock().unwrap(), ["user 7"]);
 
-        cx.update(Navigator::pop);
-        assert!(!cx.read(Navigator::can_pop));
+        built.lock().unwrap().clear();
+        window.update(|window, cx| {
+            Navigator::render_preview(window, cx, "/users/8", leaf.with_chain()).unwrap();
+        });
+        assert_eq!(*built.lock().unwrap(), ["layout", "user 8"]);
+        assert_eq!(cx.read(Navigator::current_path), "/");
     }
 
+    #[cfg(feature = "guard")]
     #[gpui::test]
-    fn test_nav_multiple_pushes(cx: &mut TestAppContext) {
+    fn test_render_preview_refused_by_guard(cx: &mut TestAppContext) {
+        use crate::guards::RouteGuard;
+
+        struct PreviewGuard {
+            allow: bool,
+            allow_preview: bool,
+        }
+
+        impl RouteGuard for PreviewGuard {
+            fn check(&self, _cx: &App, _request: &NavigationRequest) -> NavigationAction {
+                if self.allow {
+                    NavigationAction::Continue
+                } else {
+                    NavigationAction::deny("locked")
+                }
+            }
+
+            fn allow_preview(&self) -> bool {
+                self.allow_preview
+            }
+        }
+
+        let guarded = |path: &str, allow, allow_preview| {
+            page(path).guard(PreviewGuard {
+                allow,
+                allow_preview,
+            })
+        };
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/step1", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/step2", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/step3", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                router.add_route(page("/"));
+                router.add_route(guarded("/open", true, true));
+                router.add_route(guarded("/confirm", true, false).child(page("step")));
+                router.add_route(guarded("/locked", false, true));
             });
         });
 
+        let constraints = PreviewConstraints::new(gpui::size(gpui::px(320.), gpui::px(200.)));
+        let window = cx.add_empty_window();
+        let previews = |window: &mut gpui::VisualTestContext, path: &'static str| {
+            window.update(|window, cx| {
+                Navigator::render_preview(window, cx, path, constraints).is_some()
+            })
+        };
+        assert!(previews(window, "/open"));
+        // Refusal applies to the routes below the guard too
+        assert!(!previews(window, "/confirm"));
+        assert!(!previews(window, "/confirm/step"));
+        assert!(!previews(window, "/locked"));
+        // The guard only vetoes previews; navigating still works
+        cx.update(|cx| Navigator::push(cx, "/confirm"));
+        assert_eq!(cx.read(Navigator::current_path), "/confirm");
+    }
+
+    /// Router whose history, most recent first, is `/c`, `/a`, `/plain`,
+    /// `/b`, `/gone`, `/`; returns the log of component creations.
+    fn init_warming_router(cx: &mut TestAppContext) -> Arc<std::sync::Mutex<Vec<&'static str>>> {
+        let created = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let component = |path: &'static str| {
+            let created = Arc::clone(&created);
+            Route::component(path, move || {
+                created.lock().unwrap().push(path);
+                PrefetchPage
+            })
+        };
+        let routes = vec![
+            Route::view("/", || gpui::div().into_any_element()),
+            Route::view("/plain", || gpui::div().into_any_element()),
+            component("/a"),
+            component("/b"),
+            component("/c"),
+        ];
         cx.update(|cx| {
-            Navigator::push(cx, "/step1");
-            Navigator::push(cx, "/step2");
-            Navigator::push(cx, "/step3");
+            init_router(cx, |router| {
+                router.add_routes(routes);
+                for path in ["/gone", "/b", "/plain", "/a", "/c"] {
+                    router.state_mut().push(path.to_string());
+                }
+            });
         });
+        created
+    }
 
-        assert_eq!(cx.read(Navigator::current_path), "/step3");
+    #[gpui::test]
+    fn test_warm_from_history_one_path_per_tick(cx: &mut TestAppContext) {
+        let created = init_warming_router(cx);
+        cx.update(|cx| GlobalRouter::warm_from_history(cx, 5));
+        let status = |cx: &mut TestAppContext| cx.read(|cx| cx.router().warming_status());
+        assert_eq!(
+            status(cx),
+            WarmingStatus {
+                total: 5,
+                done: 0,
+                created: 0,
+                active: true,
+            }
+        );
 
-        cx.update(Navigator::pop);
-        assert_eq!(cx.read(Navigator::current_path), "/step2");
+        cx.executor().advance_clock(WARM_INTERVAL);
+        cx.run_until_parked();
+        assert_eq!(*created.lock().unwrap(), ["/c"]);
+        assert_eq!(status(cx).done, 1);
+
+        cx.executor().advance_clock(WARM_INTERVAL * 10);
+        cx.run_until_parked();
+        // `/plain` has no component, `/gone` no route
+        assert_eq!(*created.lock().unwrap(), ["/c", "/a", "/b"]);
+        assert_eq!(
+            status(cx),
+            WarmingStatus {
+                total: 5,
+                done: 5,
+                created: 3,
+                active: false,
+            }
+        );
 
-        cx.update(Navigator::pop);
-        assert_eq!(cx.read(Navigator::current_path), "/step1");
+        // Navigating to a warmed path uses the cached entity
+        cx.update(|cx| Navigator::push(cx, "/a"));
+        let window = cx.add_empty_window();
+        window.update(|window, cx| {
+            let entry = cx.global::<GlobalRouter>().leaf().unwrap().clone();
+            let _ = entry.build(window, cx);
+        });
+        assert_eq!(created.lock().unwrap().len(), 3);
+    }
 
-        cx.update(Navigator::pop);
-        assert_eq!(cx.read(Navigator::current_path), "/");
+    #[gpui::test]
+    fn test_warming_inserts_cold_within_capacity(cx: &mut TestAppContext) {
+        use gpui::AppContext as _;
+
+        let created = init_warming_router(cx);
+        cx.update(|cx| {
+            let hot = cx.new(|_| PrefetchPage).into();
+            cx.update_router(|router, _| {
+                router.set_cache_policy(CachePolicy::new().component_capacity(2));
+                router.cache_component("hot".into(), hot);
+            });
+            GlobalRouter::warm_from_history(cx, 5);
+        });
+        cx.executor().advance_clock(WARM_INTERVAL * 10);
+        cx.run_until_parked();
+
+        // The cache filled up after `/c`; nothing was evicted for warming
+        assert_eq!(*created.lock().unwrap(), ["/c"]);
+        assert_eq!(
+            cx.read(|cx| cx.router().cache_stats().component_evictions),
+            0
+        );
+
+        // The warmed entity is colder than the one in use
+        cx.update(|cx| {
+            let next = cx.new(|_| PrefetchPage).into();
+            cx.update_router(|router, _| {
+                router.cache_component("next".into(), next);
+                assert!(router.get_cached_component("hot").is_some());
+                assert_eq!(router.component_cache.len(), 2);
+            });
+        });
+        assert_eq!(
+            cx.read(|cx| cx.router().cache_stats().component_evictions),
+            1
+        );
     }
 
     #[gpui::test]
-    fn test_nav_with_route_parameters(cx: &mut TestAppContext) {
+    fn test_cancel_warming_stops_further_paths(cx: &mut TestAppContext) {
+        let created = init_warming_router(cx);
+        cx.update(|cx| GlobalRouter::warm_from_history(cx, 5));
+        cx.executor().advance_clock(WARM_INTERVAL);
+        cx.run_until_parked();
+        assert_eq!(*created.lock().unwrap(), ["/c"]);
+
+        cx.update(|cx| cx.update_router(|router, _| router.cancel_warming()));
+        cx.executor().advance_clock(WARM_INTERVAL * 10);
+        cx.run_until_parked();
+        assert_eq!(*created.lock().unwrap(), ["/c"]);
+        let status = cx.read(|cx| cx.router().warming_status());
+        assert_eq!((status.done, status.active), (1, false));
+    }
+
+    #[test]
+    fn test_begin_prefetch_debounces_per_path() {
+        let mut router = GlobalRouter::new();
+        let start = Instant::now();
+
+        assert!(router.begin_prefetch("/a", start));
+        assert!(!router.begin_prefetch("/a", start + Duration::from_millis(10)));
+        assert!(router.begin_prefetch("/b", start + Duration::from_millis(10)));
+        assert!(router.begin_prefetch("/a", start + PREFETCH_DEBOUNCE));
+    }
+
+    #[gpui::test]
+    fn test_push_evicts_components_of_truncated_forward_paths(cx: &mut TestAppContext) {
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/users/:id", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new(
-                    "/posts/:id/comments/:commentId",
-                    |_, _cx, _params| gpui::div().into_any_element(),
+                router.add_route(Route::component("/a", || PrefetchPage));
+                router.add_route(Route::component("/b", || PrefetchPage));
+                router.add_route(Route::component("/c", || PrefetchPage));
+            });
+            Navigator::push(cx, "/a");
+            Navigator::push(cx, "/b");
+        });
+
+        for path in ["/a", "/b"] {
+            let window = cx.add_empty_window();
+            window.update(|window, cx| GlobalRouter::prefetch(window, cx, path));
+        }
+
+        let key_of = |cx: &mut TestAppContext, path: &'static str| {
+            cx.read(|cx| {
+                let router = cx.global::<GlobalRouter>();
+                let stack = router.resolve_path(path);
+                let entry = stack.leaf().unwrap();
+                entry.route.component_cache_key(entry).unwrap()
+            })
+        };
+        let key_a = key_of(cx, "/a");
+        let key_b = key_of(cx, "/b");
+        let cached = |cx: &mut TestAppContext, key: &str| {
+            cx.read(|cx| {
+                cx.global::<GlobalRouter>()
+                    .get_cached_component(key)
+                    .is_some()
+            })
+        };
+        assert!(cached(cx, &key_b));
+
+        // Going back keeps /b reachable through forward
+        cx.update(Navigator::back);
+        assert!(cached(cx, &key_b));
+
+        // Pushing /c truncates /b from the forward stack
+        cx.update(|cx| Navigator::push(cx, "/c"));
+        assert_eq!(cx.read(Navigator::current_path), "/c");
+        assert!(!cached(cx, &key_b));
+        assert!(cached(cx, &key_a));
+    }
+
+    #[gpui::test]
+    fn test_push_evicts_every_dependency_of_truncated_components(cx: &mut TestAppContext) {
+        struct Theme(u64);
+
+        impl gpui::Global for Theme {}
+
+        cx.update(|cx| {
+            cx.set_global(Theme(1));
+            init_router(cx, |router| {
+                router.add_route(Route::component("/a", || PrefetchPage));
+                router.add_route(Route::component_with_dep(
+                    "/b",
+                    |cx| cx.global::<Theme>().0,
+                    |_| PrefetchPage,
                 ));
+                router.add_route(Route::component("/c", || PrefetchPage));
             });
+            Navigator::push(cx, "/a");
+            Navigator::push(cx, "/b");
         });
 
-        cx.update(|cx| Navigator::push(cx, "/users/42"));
-        assert_eq!(cx.read(Navigator::current_path), "/users/42");
+        let window = cx.add_empty_window();
+        let mut keys = Vec::new();
+        for theme in [1, 2] {
+            window.update(|window, cx| {
+                cx.set_global(Theme(theme));
+                let stack = cx.global::<GlobalRouter>().resolve_path("/b");
+                let entry = stack.leaf().unwrap();
+                let _ = entry.build(window, cx);
+                keys.push(entry.route.component_cache_key_in(cx, entry).unwrap());
+            });
+        }
+        let cached = |cx: &mut TestAppContext, key: &str| {
+            cx.read(|cx| {
+                cx.global::<GlobalRouter>()
+                    .get_cached_component(key)
+                    .is_some()
+            })
+        };
+        assert_ne!(keys[0], keys[1]);
+        assert!(keys.iter().all(|key| cached(cx, key)));
 
-        cx.update(|cx| Navigator::push(cx, "/posts/123/comments/456"));
-        assert_eq!(cx.read(Navigator::current_path), "/posts/123/comments/456");
+        cx.update(Navigator::back);
+        cx.update(|cx| Navigator::push(cx, "/c"));
+        assert!(keys.iter().all(|key| !cached(cx, key)));
     }
 
     #[gpui::test]
-    fn test_navigator_of_style(cx: &mut TestAppContext) {
+    fn test_push_with_payload_is_taken_on_arrival(cx: &mut TestAppContext) {
+        #[derive(Debug, PartialEq)]
+        struct Draft {
+            title: String,
+        }
+
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/home", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/profile", |_, _cx, _params| {
+                router.add_route(Route::new("/", |_, _, _| gpui::div().into_any_element()));
+                router.add_route(Route::new("/compose", |_, _, _| {
                     gpui::div().into_any_element()
                 }));
             });
         });
 
-        cx.update(|cx| {
-            let _ = Navigator::of(cx).push("/home");
+        let draft = || Draft {
+            title: "Hello".to_string(),
+        };
+        cx.update(|cx| Navigator::push_with_payload(cx, "/compose", draft()));
+        assert_eq!(cx.read(Navigator::current_path), "/compose");
+
+        // Wrong type leaves it in place; taking consumes it
+        assert_eq!(cx.update(Navigator::take_payload::<u32>), None);
+        assert_eq!(cx.update(Navigator::take_payload), Some(draft()));
+        assert_eq!(cx.update(Navigator::take_payload::<Draft>), None);
+
+        // An untaken payload waits on its entry
+        cx.update(|cx| Navigator::push_with_payload(cx, "/compose", draft()));
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert_eq!(cx.update(Navigator::take_payload::<Draft>), None);
+        cx.update(Navigator::back);
+        assert_eq!(cx.update(Navigator::take_payload), Some(draft()));
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "middleware")]
+    fn test_navigate_with_state_resolves_once(cx: &mut TestAppContext) {
+        use crate::middleware_fn;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static BEFORE: AtomicUsize = AtomicUsize::new(0);
+        static AFTER: AtomicUsize = AtomicUsize::new(0);
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/form").middleware(middleware_fn(
+                    |_, _| {
+                        BEFORE.fetch_add(1, Ordering::SeqCst);
+                    },
+                    |_, _| {
+                        AFTER.fetch_add(1, Ordering::SeqCst);
+                    },
+                )));
+            });
+        });
+
+        // (resolutions, cache clears, before middleware, after middleware)
+        let counts = |cx: &mut TestAppContext| {
+            cx.read(|cx| {
+                let router = cx.global::<GlobalRouter>();
+                #[cfg(feature = "cache")]
+                let clears = router.cache_stats().invalidations;
+                #[cfg(not(feature = "cache"))]
+                let clears = 0;
+                (
+                    router.resolutions,
+                    clears,
+                    BEFORE.load(Ordering::SeqCst),
+                    AFTER.load(Ordering::SeqCst),
+                )
+            })
+        };
+        let cache_clear = usize::from(cfg!(feature = "cache"));
+        let mut state = HistoryState::new();
+        state.set("draft".to_string(), "hello".to_string());
+
+        let (resolutions, clears, before, after) = counts(cx);
+        let result = cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, cx| {
+                router.push_with_state("/form".to_string(), state.clone(), cx)
+            })
         });
-        assert_eq!(cx.read(Navigator::current_path), "/home");
+        assert!(result.is_success());
+        assert_eq!(
+            counts(cx),
+            (resolutions + 1, clears + cache_clear, before + 1, after + 1)
+        );
 
-        cx.update(|cx| {
-            let _ = Navigator::of(cx).push("/profile").pop();
+        let (resolutions, clears, before, after) = counts(cx);
+        let result = cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, cx| {
+                router.replace_with_state("/form".to_string(), state.clone(), cx)
+            })
         });
-        assert_eq!(cx.read(Navigator::current_path), "/home");
+        assert!(result.is_success());
+        assert_eq!(
+            counts(cx),
+            (resolutions + 1, clears + cache_clear, before + 1, after + 1)
+        );
 
+        cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.current_entry().state.as_ref(), Some(&state));
+            assert_eq!(router.state().history().len(), 2);
+        });
+    }
+
+    #[gpui::test]
+    fn test_deferred_push_runs_after_update_and_latest_wins(cx: &mut TestAppContext) {
         cx.update(|cx| {
-            let _ = Navigator::of(cx).replace("/profile");
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/first"));
+                router.add_route(page("/second"));
+            });
         });
-        assert_eq!(cx.read(Navigator::current_path), "/profile");
 
-        assert!(cx.read(Navigator::can_pop));
         cx.update(|cx| {
-            let _ = Navigator::of(cx).pop();
+            Navigator::defer_push(cx, "/first");
+            Navigator::defer_push(cx, "/second");
+            // Nothing navigates until the update completes
+            assert_eq!(Navigator::current_path(cx), "/");
+            assert_eq!(cx.router().deferred_push(), Some("/second"));
         });
+
+        assert_eq!(cx.read(Navigator::current_path), "/second");
+        assert!(cx.read(|cx| cx.router().deferred_push().is_none()));
+        // The dropped push never reached history
+        cx.update(Navigator::back);
         assert_eq!(cx.read(Navigator::current_path), "/");
-        assert!(!cx.read(Navigator::can_pop));
     }
 
     #[gpui::test]
-    fn test_string_into_route(cx: &mut TestAppContext) {
+    fn test_deferred_push_is_pending_until_drained(cx: &mut TestAppContext) {
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(Route::new("/home", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                router.add_route(page("/"));
+                router.add_route(page("/next"));
             });
         });
+        assert!(!cx.read(Navigator::is_navigating));
 
-        cx.update(|cx| Navigator::push(cx, "/home"));
-        assert_eq!(cx.read(Navigator::current_path), "/home");
+        cx.update(|cx| {
+            Navigator::defer_push(cx, "/next");
+            assert!(Navigator::is_navigating(cx));
+            assert_eq!(cx.router().pending_navigation(), Some("/next"));
+        });
 
-        cx.update(|cx| Navigator::push(cx, String::from("/home")));
-        assert_eq!(cx.read(Navigator::current_path), "/home");
+        assert_eq!(cx.read(Navigator::current_path), "/next");
+        assert!(!cx.read(Navigator::is_navigating));
+        assert!(cx.read(|cx| cx.router().pending_navigation().is_none()));
     }
 
-    // ========================================================================
-    // Guard integration tests
-    // ========================================================================
+    #[test]
+    fn test_on_route_registered_reports_full_paths_of_nested_tree() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+
+        let mut router = GlobalRouter::new();
+        router.set_on_route_registered(move |info| sink.lock().unwrap().push(info.clone()));
+        router.add_route(page("/"));
+        router.add_route(
+            page("/dashboard")
+                .name("dashboard")
+                .children(vec![
+                    page("").into(),
+                    page("settings").name("settings").into(),
+                    page(":id").children(vec![page("edit").into()]).into(),
+                ])
+                .named_outlet("sidebar", vec![page("nav").into()]),
+        );
+
+        let info = |full_path: &str, name: Option<&str>, depth, outlet: Option<&str>| RouteInfo {
+            full_path: full_path.to_string(),
+            name: name.map(str::to_string),
+            depth,
+            outlet: outlet.map(str::to_string),
+        };
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                info("/", None, 0, None),
+                info("/dashboard", Some("dashboard"), 0, None),
+                info("/dashboard", None, 1, None),
+                info("/dashboard/settings", Some("settings"), 1, None),
+                info("/dashboard/:id", None, 1, None),
+                info("/dashboard/:id/edit", None, 2, None),
+                info("/dashboard/nav", None, 1, Some("sidebar")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_batch_resolves_once() {
+        let mut router = GlobalRouter::new();
+        router.batch(|router| {
+            for i in 0..50 {
+                router.add_route(page(&format!("/page{i}")));
+            }
+            // Registered, but not resolved yet
+            assert_eq!(router.resolutions, 0);
+        });
+        assert_eq!(router.resolutions, 1);
+        assert_eq!(router.state().routes().len(), 50);
+
+        router.add_routes((50..60).map(|i| page(&format!("/page{i}"))));
+        assert_eq!(router.resolutions, 2);
+
+        // An empty batch has nothing to flush
+        router.batch(|_| {});
+        assert_eq!(router.resolutions, 2);
+    }
 
     #[gpui::test]
     #[cfg(feature = "guard")]
-    fn test_guard_blocks_navigation(cx: &mut TestAppContext) {
-        use crate::AuthGuard;
+    fn test_add_route_replaces_identical_pattern(cx: &mut TestAppContext) {
+        use crate::guard_fn;
 
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                router.add_route(page("/"));
                 router.add_route(
-                    Route::new("/protected", |_, _cx, _params| {
-                        gpui::div().into_any_element()
-                    })
-                    .guard(AuthGuard::new(|_| false, "/login")),
+                    page("/admin")
+                        .name("admin.old")
+                        .guard(guard_fn(|_, _| NavigationAction::deny("Old definition"))),
                 );
-                router.add_route(Route::new("/login", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                // Setup running twice, within the same batch
+                router.add_route(page("admin/").name("admin").child(page("users")));
             });
         });
 
-        // Guard should redirect to /login
-        cx.update(|cx| Navigator::push(cx, "/protected"));
+        cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            let paths: Vec<_> = router
+                .state()
+                .routes()
+                .iter()
+                .map(|route| route.config.path.as_str())
+                .collect();
+            assert_eq!(paths, ["/", "admin/"]);
+            assert!(!router.named_routes.contains("admin.old"));
+            assert_eq!(router.named_routes.get("admin"), Some("admin/"));
+        });
 
-        // We end up at /login (redirect), not /protected
-        assert_eq!(cx.read(Navigator::current_path), "/login");
+        // The old guard is gone with its route
+        cx.update(|cx| Navigator::push(cx, "/admin/users"));
+        assert_eq!(cx.read(Navigator::current_path), "/admin/users");
+
+        // Replacing the route on screen re-resolves the match stack
+        cx.update(|cx| cx.update_router(|router, _| router.add_route(page("/admin"))));
+        cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.state().routes().len(), 2);
+            assert!(router.match_stack().is_empty());
+            assert!(!router.named_routes.contains("admin"));
+        });
     }
 
-    #[gpui::test]
-    #[cfg(feature = "guard")]
-    fn test_guard_allows_navigation(cx: &mut TestAppContext) {
-        use crate::AuthGuard;
+    #[test]
+    fn test_add_route_strict_rejects_identical_pattern() {
+        let mut router = GlobalRouter::new();
+        router.add_route(page("/inbox").name("inbox"));
+        let error = router
+            .add_route_strict(page("inbox/").name("mail"))
+            .unwrap_err();
+        assert_eq!(error.path, "/inbox");
+        assert!(!router.named_routes.contains("mail"));
+
+        assert!(router.add_route_strict(page("/inbox/:id")).is_ok());
+        assert_eq!(router.state().routes().len(), 2);
+    }
 
+    #[gpui::test]
+    fn test_navigation_inside_batch_sees_added_routes(cx: &mut TestAppContext) {
         cx.update(|cx| {
-            init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                router.add_route(
-                    Route::new("/dashboard", |_, _cx, _params| {
-                        gpui::div().into_any_element()
-                    })
-                    .guard(AuthGuard::new(|_| true, "/login")),
-                );
+            let mut router = GlobalRouter::new();
+            router.batch(|router| {
+                router.add_route(page("/a"));
+                assert!(router.push("/a".to_string(), cx).is_success());
+                assert_eq!(router.match_stack().leaf_url(), Some("/a"));
+
+                router.add_route(page("/"));
+                assert!(router.push("/".to_string(), cx).is_success());
+                assert_eq!(router.match_stack().leaf_url(), Some("/"));
+                assert!(router.back(cx).is_some_and(|result| result.is_success()));
             });
+            assert_eq!(router.current_path(), "/a");
+            assert_eq!(router.match_stack().leaf_url(), Some("/a"));
         });
+    }
 
-        cx.update(|cx| Navigator::push(cx, "/dashboard"));
-        assert_eq!(cx.read(Navigator::current_path), "/dashboard");
+    #[test]
+    fn test_batch_closes_when_closure_panics() {
+        let mut router = GlobalRouter::new();
+        let caught = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            router.batch(|router| {
+                router.add_route(page("/a"));
+                panic!("configure failed");
+            });
+        }));
+        assert!(caught.is_err());
+        assert_eq!(router.batch_depth, 0);
+
+        // The next change flushes the route the panicking batch deferred
+        router.add_route(page("/"));
+        assert!(!router.batch_pending);
+        assert_eq!(router.match_stack().leaf_url(), Some("/"));
     }
 
-    #[gpui::test]
     #[cfg(feature = "guard")]
-    fn test_guard_denies_navigation(cx: &mut TestAppContext) {
+    fn init_probe_router(cx: &mut TestAppContext) {
         use crate::guard_fn;
+        let page = |path: &str| Route::new(path, |_, _, _| gpui::div().into_any_element());
 
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                router.add_route(page("/"));
+                router.add_route(page("/login"));
+                router.add_route(page("/open"));
                 router.add_route(
-                    Route::new("/forbidden", |_, _cx, _params| {
-                        gpui::div().into_any_element()
-                    })
-                    .guard(guard_fn(|_, _| NavigationAction::deny("No access"))),
+                    page("/denied").guard(guard_fn(|_, _| NavigationAction::deny("Admins only"))),
+                );
+                router.add_route(page("/admin").guard(guard_fn(|_, _| {
+                    NavigationAction::Redirect {
+                        to: "/login".to_string(),
+                        reason: Some("Sign in first".to_string()),
+                    }
+                })));
+                router.add_route(
+                    page("/loop").guard(guard_fn(|_, _| NavigationAction::redirect("/loop"))),
                 );
             });
         });
-
-        cx.update(|cx| Navigator::push(cx, "/forbidden"));
-        // Navigation was blocked, path should remain at "/"
-        assert_eq!(cx.read(Navigator::current_path), "/");
     }
 
     #[gpui::test]
     #[cfg(feature = "guard")]
-    fn test_parent_guard_blocks_child(cx: &mut TestAppContext) {
-        use crate::AuthGuard;
+    fn test_probe_matches_real_navigation(cx: &mut TestAppContext) {
+        init_probe_router(cx);
+
+        for path in ["/open", "/denied", "/admin", "/loop"] {
+            let probe = cx.read(|cx| can_navigate(cx, path));
+            cx.update(|cx| Navigator::replace(cx, "/"));
+            cx.update(|cx| Navigator::push(cx, path));
+            let landed = cx.read(Navigator::current_path);
+
+            assert_eq!(probe.allowed, landed == path, "{path}: {probe:?}");
+            let expected_landing =
+                probe
+                    .redirect
+                    .as_deref()
+                    .unwrap_or(if probe.allowed { path } else { "/" });
+            assert_eq!(landed, expected_landing, "{path}: {probe:?}");
+        }
+
+        let probe = |path: &str| cx.read(|cx| can_navigate(cx, path));
+        assert_eq!(probe("/open"), NavigationProbe::allowed());
+        assert_eq!(
+            probe("/denied"),
+            NavigationProbe::blocked("Admins only".into())
+        );
+        assert_eq!(
+            probe("/admin"),
+            NavigationProbe {
+                allowed: false,
+                reason: Some("Sign in first".to_string()),
+                redirect: Some("/login".to_string()),
+                challenge: None,
+            }
+        );
+        assert!(probe("/loop").reason.unwrap().contains("Redirect loop"));
+    }
+
+    #[gpui::test]
+    #[cfg(all(feature = "guard", feature = "middleware"))]
+    fn test_probe_has_no_side_effects(cx: &mut TestAppContext) {
+        use crate::{middleware_fn, RouteGuard};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CHECKS: AtomicUsize = AtomicUsize::new(0);
+        static MIDDLEWARE: AtomicUsize = AtomicUsize::new(0);
+
+        /// Counts real checks and answers probes without counting.
+        struct AttemptLimit;
+
+        impl RouteGuard for AttemptLimit {
+            fn check(&self, _cx: &App, _request: &NavigationRequest) -> NavigationAction {
+                CHECKS.fetch_add(1, Ordering::SeqCst);
+                NavigationAction::Continue
+            }
+
+            fn probe(&self, _cx: &App, _request: &NavigationRequest) -> NavigationAction {
+                NavigationAction::Continue
+            }
+        }
 
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                router.add_route(Route::new("/", |_, _, _| gpui::div().into_any_element()));
                 router.add_route(
-                    Route::new("/dashboard", |_, _cx, _params| {
-                        gpui::div().into_any_element()
-                    })
-                    .guard(AuthGuard::new(|_| false, "/login"))
-                    .child(
-                        Route::new("settings", |_, _cx, _params| gpui::div().into_any_element())
-                            .into(),
-                    ),
+                    Route::new("/page", |_, _, _| gpui::div().into_any_element())
+                        .guard(AttemptLimit)
+                        .middleware(middleware_fn(
+                            |_, _| {
+                                MIDDLEWARE.fetch_add(1, Ordering::SeqCst);
+                            },
+                            |_, _| {
+                                MIDDLEWARE.fetch_add(1, Ordering::SeqCst);
+                            },
+                        )),
                 );
-                router.add_route(Route::new("/login", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
             });
         });
 
-        // Guard on /dashboard should also block /dashboard/settings
-        cx.update(|cx| Navigator::push(cx, "/dashboard/settings"));
-        assert_eq!(cx.read(Navigator::current_path), "/login");
+        let history_len = |cx: &mut TestAppContext| {
+            cx.read(|cx| cx.global::<GlobalRouter>().state().history().len())
+        };
+        let before = history_len(cx);
+        for _ in 0..3 {
+            assert!(cx.read(|cx| can_navigate(cx, "/page")).allowed);
+        }
+
+        assert_eq!(CHECKS.load(Ordering::SeqCst), 0);
+        assert_eq!(MIDDLEWARE.load(Ordering::SeqCst), 0);
+        assert_eq!(history_len(cx), before);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+
+        cx.update(|cx| Navigator::push(cx, "/page"));
+        assert_eq!(CHECKS.load(Ordering::SeqCst), 1);
+        assert_eq!(MIDDLEWARE.load(Ordering::SeqCst), 2);
     }
 
     #[gpui::test]
-    #[cfg(feature = "guard")]
-    fn test_redirect_loop_protection(cx: &mut TestAppContext) {
-        use crate::guard_fn;
+    fn test_push_in_window_refreshes_only_that_window(cx: &mut TestAppContext) {
+        use gpui::{Context, Render};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Counts its renders.
+        struct Counted(Arc<AtomicUsize>);
+
+        impl Render for Counted {
+            fn render(&mut self, _: &mut Window, _: &mut Context<'_, Self>) -> impl IntoElement {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                gpui::div()
+            }
+        }
 
         cx.update(|cx| {
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
-                // /a redirects to /b, /b redirects to /a — infinite loop
-                router.add_route(
-                    Route::new("/a", |_, _cx, _params| gpui::div().into_any_element())
-                        .guard(guard_fn(|_, _| NavigationAction::redirect("/b"))),
-                );
-                router.add_route(
-                    Route::new("/b", |_, _cx, _params| gpui::div().into_any_element())
-                        .guard(guard_fn(|_, _| NavigationAction::redirect("/a"))),
-                );
+                router.add_route(page("/"));
+                router.add_route(page("/a"));
+                router.add_route(page("/b"));
             });
         });
+        let target_renders = Arc::new(AtomicUsize::new(0));
+        let other_renders = Arc::new(AtomicUsize::new(0));
+        let target = cx.add_window({
+            let renders = Arc::clone(&target_renders);
+            move |_, _| Counted(renders)
+        });
+        cx.add_window({
+            let renders = Arc::clone(&other_renders);
+            move |_, _| Counted(renders)
+        });
+        cx.run_until_parked();
+        let renders = || {
+            (
+                target_renders.load(Ordering::SeqCst),
+                other_renders.load(Ordering::SeqCst),
+            )
+        };
 
-        // Should not infinite loop — stays at "/"
-        cx.update(|cx| Navigator::push(cx, "/a"));
-        // Path stays at "/" because the redirect loop is detected and blocked
-        assert_eq!(cx.read(Navigator::current_path), "/");
+        let (target_before, other_before) = renders();
+        cx.update(|cx| Navigator::push_in_window(cx, target, "/a"));
+        cx.run_until_parked();
+        assert_eq!(cx.read(Navigator::current_path), "/a");
+        let (target_after, other_after) = renders();
+        assert!(target_after > target_before);
+        assert_eq!(other_after, other_before);
+
+        // The plain methods still refresh every window
+        cx.update(|cx| Navigator::push(cx, "/b"));
+        cx.run_until_parked();
+        assert!(renders().1 > other_after);
     }
 
-    // ========================================================================
-    // Middleware integration tests
-    // ========================================================================
-
     #[gpui::test]
     #[cfg(feature = "middleware")]
-    fn test_middleware_runs_during_navigation(cx: &mut TestAppContext) {
-        use crate::middleware_fn;
-        use std::sync::{Arc, Mutex};
+    fn test_first_render_sees_after_navigation_effects(cx: &mut TestAppContext) {
+        use crate::{middleware_fn, router_view};
+        use gpui::{Context, Global, Render};
+        use std::sync::Mutex;
 
-        let calls = Arc::new(Mutex::new(Vec::<String>::new()));
-        let before_calls = calls.clone();
-        let after_calls = calls.clone();
+        /// Written by the middleware, read by the destination's builder.
+        struct Marker(Mutex<&'static str>);
+
+        impl Global for Marker {}
+
+        struct Shell;
+
+        impl Render for Shell {
+            fn render(
+                &mut self,
+                window: &mut Window,
+                cx: &mut Context<'_, Self>,
+            ) -> impl IntoElement {
+                router_view(window, cx)
+            }
+        }
 
+        let seen: Arc<Mutex<Vec<&'static str>>> = Arc::default();
+        let page_seen = Arc::clone(&seen);
         cx.update(|cx| {
+            cx.set_global(Marker(Mutex::new("stale")));
             init_router(cx, |router| {
-                router.add_route(Route::new("/", |_, _cx, _params| {
-                    gpui::div().into_any_element()
-                }));
+                router.add_route(Route::view("/", || gpui::div().into_any_element()));
                 router.add_route(
-                    Route::new("/page", |_, _cx, _params| gpui::div().into_any_element())
-                        .middleware(middleware_fn(
-                            move |_cx, req| {
-                                before_calls
-                                    .lock()
-                                    .unwrap()
-                                    .push(format!("before:{}", req.to));
-                            },
-                            move |_cx, req| {
-                                after_calls
-                                    .lock()
-                                    .unwrap()
-                                    .push(format!("after:{}", req.to));
-                            },
-                        )),
+                    Route::new("/dest", move |_, cx, _| {
+                        let marker = *cx.global::<Marker>().0.lock().unwrap();
+                        page_seen.lock().unwrap().push(marker);
+                        gpui::div().into_any_element()
+                    })
+                    .middleware(middleware_fn(
+                        |_, _| {},
+                        |cx, _| *cx.global::<Marker>().0.lock().unwrap() = "arrived",
+                    )),
                 );
             });
         });
+        cx.add_window(|_, _| Shell);
+        cx.run_until_parked();
 
-        cx.update(|cx| Navigator::push(cx, "/page"));
-        assert_eq!(cx.read(Navigator::current_path), "/page");
+        cx.update(|cx| Navigator::push(cx, "/dest"));
+        // The Navigator consumed the flag the pipeline raised last
+        assert!(!cx.update(|cx| cx.update_router(|router, _| router.take_refresh())));
+        cx.run_until_parked();
 
-        let log = calls.lock().unwrap();
-        assert_eq!(log.len(), 2);
-        assert_eq!(log[0], "before:/page");
-        assert_eq!(log[1], "after:/page");
-        drop(log);
+        let seen = seen.lock().unwrap().clone();
+        assert!(!seen.is_empty());
+        assert!(seen.iter().all(|marker| *marker == "arrived"));
     }
 
-    // ========================================================================
-    // path_matches_prefix unit tests
-    // ========================================================================
+    #[gpui::test]
+    fn test_scroll_intent_follows_navigation_op(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/feed"));
+                router.add_route(page("/post"));
+            });
+        });
 
-    #[test]
-    fn test_path_matches_prefix_exact() {
-        assert!(path_matches_prefix("dashboard", "dashboard"));
-    }
+        // Manual by default: the app scrolls on its own
+        cx.update(|cx| Navigator::push(cx, "/feed"));
+        assert_eq!(cx.read(Navigator::scroll_intent), None);
 
-    #[test]
-    fn test_path_matches_prefix_child() {
-        assert!(path_matches_prefix("dashboard/settings", "dashboard"));
-    }
+        cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.set_scroll_restoration(ScrollRestoration::Auto);
+            });
+        });
+        cx.update(|cx| Navigator::save_scroll(cx, 0.0, 240.0));
+        cx.update(|cx| Navigator::push(cx, "/post"));
+        assert_eq!(cx.read(Navigator::scroll_intent), Some(ScrollIntent::Top));
 
-    #[test]
-    fn test_path_matches_prefix_no_match() {
-        assert!(!path_matches_prefix("other", "dashboard"));
+        cx.update(Navigator::pop);
+        assert_eq!(
+            cx.read(Navigator::scroll_intent),
+            Some(ScrollIntent::Restore(0.0, 240.0))
+        );
+
+        // Nothing was saved on /post
+        cx.update(Navigator::forward);
+        assert_eq!(cx.read(Navigator::scroll_intent), Some(ScrollIntent::Top));
     }
 
-    #[test]
-    fn test_path_matches_prefix_with_param() {
-        assert!(path_matches_prefix("users/123", "users/:id"));
-        assert!(path_matches_prefix("users/123/posts", "users/:id"));
+    #[gpui::test]
+    fn test_navigate_batch_resolves_once(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/inbox"));
+                router.add_route(page("/inbox/:id"));
+            });
+        });
+        let resolutions = cx.read(|cx| cx.global::<GlobalRouter>().resolutions);
+
+        let ops = vec![
+            BatchOp::Push("/inbox".to_string()),
+            BatchOp::Push("/inbox/1".to_string()),
+            BatchOp::Push("/inbox/2".to_string()),
+        ];
+        let result = cx.update(|cx| Navigator::navigate_batch(cx, ops));
+        assert_eq!(result.unwrap(), "/inbox/2");
+
+        cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.resolutions, resolutions + 1);
+            let paths: Vec<&str> = router
+                .state()
+                .history()
+                .entries()
+                .iter()
+                .map(|entry| entry.path.as_str())
+                .collect();
+            assert_eq!(paths, ["/", "/inbox", "/inbox/1", "/inbox/2"]);
+            assert_eq!(router.leaf().unwrap().params.get("id").unwrap(), "2");
+        });
+
+        // A replace rewrites the entry the op before it pushed
+        let ops = vec![
+            BatchOp::Push("/inbox/3".to_string()),
+            BatchOp::Replace("/inbox/4".to_string()),
+        ];
+        cx.update(|cx| Navigator::navigate_batch(cx, ops)).unwrap();
+        cx.update(Navigator::back);
+        assert_eq!(cx.read(Navigator::current_path), "/inbox/2");
     }
 
-    #[test]
-    fn test_path_matches_prefix_shorter_path() {
-        assert!(!path_matches_prefix("users", "users/123"));
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_navigate_batch_stops_at_blocked_op(cx: &mut TestAppContext) {
+        use crate::{guard_fn, NavigationAction};
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/a"));
+                router.add_route(
+                    page("/b").guard(guard_fn(|_, _| NavigationAction::deny("Not yet"))),
+                );
+                router.add_route(page("/c"));
+            });
+        });
+
+        let ops = vec![
+            BatchOp::Push("/a".to_string()),
+            BatchOp::Push("/b".to_string()),
+            BatchOp::Push("/c".to_string()),
+        ];
+        let error = cx
+            .update(|cx| Navigator::navigate_batch(cx, ops))
+            .unwrap_err();
+        assert_eq!(error.index, 1);
+        assert_eq!(error.path, "/b");
+        assert!(matches!(
+            error.error,
+            NavigationError::GuardBlocked { ref reason } if reason == "Not yet"
+        ));
+
+        // The entry pushed for /a is rolled back
+        assert_eq!(cx.read(Navigator::current_path), "/");
+        assert!(!cx.read(Navigator::can_go_back));
     }
 }
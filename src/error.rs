@@ -7,8 +7,11 @@
 //!   (`Success`, `NotFound`, `Blocked`, `Error`).
 //! - [`NavigationError`] — a detailed error variant (route not found, guard
 //!   blocked, invalid params, etc.).
+//! - [`RouteError`] — why one route of the match stack could not render.
 //! - [`ErrorHandlers`] — a builder for registering custom 404 and error page
 //!   renderers.
+//! - [`NotFoundBehavior`] — whether unmatched paths render the 404 page or
+//!   redirect to a fallback.
 //!
 //! # Examples
 //!
@@ -25,6 +28,9 @@
 //! assert_eq!(blocked.redirect_path(), Some("/login"));
 //! ```
 
+use crate::outlet_policy::OwnerTag;
+use crate::resolve::MAX_DEPTH;
+use crate::NavigationDirection;
 use gpui::{AnyElement, App};
 use std::fmt;
 use std::sync::Arc;
@@ -57,6 +63,13 @@ pub enum NavigationResult {
         /// Optional redirect path suggested by the guard.
         redirect: Option<String>,
     },
+    /// Navigation suspended by a guard challenge, awaiting an answer.
+    Challenged {
+        /// The path the suspended navigation targets.
+        path: String,
+        /// Identifier of the challenge the guard issued.
+        challenge_id: String,
+    },
     /// Navigation error
     Error(NavigationError),
 }
@@ -92,6 +105,14 @@ pub enum NavigationError {
         message: String,
     },
 
+    /// Navigation suspended by a guard challenge that was not answered.
+    Challenged {
+        /// The path the suspended navigation targets.
+        path: String,
+        /// Identifier of the challenge the guard issued.
+        challenge_id: String,
+    },
+
     /// Custom application-specific error.
     Custom {
         /// Error message.
@@ -114,6 +135,9 @@ impl fmt::Display for NavigationError {
             Self::NavigationFailed { message } => {
                 write!(f, "Navigation failed: {message}")
             }
+            Self::Challenged { path, challenge_id } => {
+                write!(f, "Navigation to {path} awaits challenge '{challenge_id}'")
+            }
             Self::Custom { message } => {
                 write!(f, "{message}")
             }
@@ -123,6 +147,279 @@ impl fmt::Display for NavigationError {
 
 impl std::error::Error for NavigationError {}
 
+impl NavigationError {
+    /// Short name of the variant, e.g. `"route_not_found"`.
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::RouteNotFound { .. } => "route_not_found",
+            Self::GuardBlocked { .. } => "guard_blocked",
+            Self::InvalidParams { .. } => "invalid_params",
+            Self::NavigationFailed { .. } => "navigation_failed",
+            Self::Challenged { .. } => "challenged",
+            Self::Custom { .. } => "custom",
+        }
+    }
+}
+
+// ============================================================================
+// Route Errors
+// ============================================================================
+
+/// Why one route of the match stack could not render.
+///
+/// Stored on the failed [`MatchEntry`](crate::MatchEntry) and handed to the
+/// nearest [`Route::error_boundary`](crate::Route::error_boundary).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RouteError {
+    /// The route's loader returned an error.
+    LoaderFailed {
+        /// Display text of the loader's error.
+        message: String,
+        /// [`NavigationError::kind`] of the loader's error.
+        source_kind: &'static str,
+    },
+    /// A [`Route::validate_param`](crate::Route::validate_param) validator,
+    /// or a [registered param type](crate::GlobalRouter::register_param_type),
+    /// rejected a param.
+    InvalidParam {
+        /// Name of the param.
+        name: String,
+        /// Value that was rejected.
+        value: String,
+        /// Why the validator rejected it.
+        reason: String,
+    },
+    /// The route has no builder.
+    BuilderMissing,
+    /// The route's builder panicked.
+    Panicked {
+        /// The panic message.
+        message: String,
+    },
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LoaderFailed { message, .. } => write!(f, "Loader failed: {message}"),
+            Self::InvalidParam {
+                name,
+                value,
+                reason,
+            } => write!(f, "Invalid param '{name}' = '{value}': {reason}"),
+            Self::BuilderMissing => write!(f, "Route has no builder"),
+            Self::Panicked { message } => write!(f, "Route panicked while rendering: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+impl RouteError {
+    /// Whether the error comes from the loader stage (loader or param
+    /// validators) rather than from rendering.
+    #[must_use]
+    pub const fn is_load_error(&self) -> bool {
+        matches!(self, Self::LoaderFailed { .. } | Self::InvalidParam { .. })
+    }
+}
+
+// ============================================================================
+// URL Errors
+// ============================================================================
+
+/// Why [`GlobalRouter::canonical_url`](crate::GlobalRouter::canonical_url)
+/// could not build a URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UrlForError {
+    /// No route is registered under this name.
+    UnknownName {
+        /// The name that was asked for.
+        name: String,
+    },
+    /// The named route's pattern declares params that were not given.
+    MissingParams {
+        /// The route name.
+        name: String,
+        /// Names of the missing params, in pattern order.
+        params: Vec<String>,
+    },
+    /// The path matches no route.
+    NoMatch {
+        /// The normalized path.
+        path: String,
+    },
+}
+
+impl fmt::Display for UrlForError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownName { name } => write!(f, "Unknown route name: '{name}'"),
+            Self::MissingParams { name, params } => {
+                write!(f, "Route '{name}' is missing params: {}", params.join(", "))
+            }
+            Self::NoMatch { path } => write!(f, "No route matches '{path}'"),
+        }
+    }
+}
+
+impl std::error::Error for UrlForError {}
+
+// ============================================================================
+// Registration Errors
+// ============================================================================
+
+/// A route declares a named outlet reserved for another owner.
+///
+/// Returned by [`GlobalRouter::add_route_tagged`](crate::GlobalRouter::add_route_tagged),
+/// see [`crate::outlet_policy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReservedOutletError {
+    /// Full path of the route declaring the outlet.
+    pub route: String,
+    /// The reserved outlet name.
+    pub outlet: String,
+    /// Owner the outlet is reserved for.
+    pub owner: OwnerTag,
+    /// Tag the route was registered under.
+    pub tag: OwnerTag,
+}
+
+impl fmt::Display for ReservedOutletError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Route '{}' ({}) declares outlet '{}' reserved for '{}'",
+            self.route, self.tag, self.outlet, self.owner
+        )
+    }
+}
+
+impl std::error::Error for ReservedOutletError {}
+
+/// A route with the same pattern is already registered.
+///
+/// Returned by [`GlobalRouter::add_route_strict`](crate::GlobalRouter::add_route_strict);
+/// [`add_route`](crate::GlobalRouter::add_route) replaces the earlier route
+/// instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlreadyRegisteredError {
+    /// Path of the route already registered.
+    pub path: String,
+}
+
+impl fmt::Display for AlreadyRegisteredError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Route '{}' is already registered", self.path)
+    }
+}
+
+impl std::error::Error for AlreadyRegisteredError {}
+
+/// An op of a navigation batch that did not go through.
+///
+/// Returned by [`GlobalRouter::navigate_batch`](crate::GlobalRouter::navigate_batch),
+/// which stops at the op and puts the history back as it was.
+#[derive(Debug, Clone)]
+pub struct BatchNavigationError {
+    /// Position of the op in the batch.
+    pub index: usize,
+    /// Path the op asked for.
+    pub path: String,
+    /// Why the op did not go through.
+    pub error: NavigationError,
+}
+
+impl fmt::Display for BatchNavigationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Batch op {} to '{}' failed: {}",
+            self.index, self.path, self.error
+        )
+    }
+}
+
+impl std::error::Error for BatchNavigationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+// ============================================================================
+// History Errors
+// ============================================================================
+
+/// Why [`RouterState::back`](crate::RouterState::back) or
+/// [`forward`](crate::RouterState::forward) did not move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HistoryError {
+    /// The cursor is already at the oldest entry going back, or at the
+    /// newest going forward.
+    AtBoundary {
+        /// The direction that was asked for.
+        direction: NavigationDirection,
+    },
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AtBoundary {
+                direction: NavigationDirection::Back,
+            } => write!(f, "No history entry to go back to"),
+            Self::AtBoundary {
+                direction: NavigationDirection::Forward | NavigationDirection::Replace,
+            } => write!(f, "No history entry to go forward to"),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+impl From<HistoryError> for NavigationError {
+    fn from(error: HistoryError) -> Self {
+        Self::NavigationFailed {
+            message: error.to_string(),
+        }
+    }
+}
+
+/// A route tree that resolution cannot walk in full.
+///
+/// Returned by [`check_route_tree`](crate::resolve::check_route_tree).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteTreeError {
+    /// A route is its own descendant.
+    Cycle {
+        /// Full path of the route, where it was found below itself.
+        route: String,
+    },
+    /// A route is nested at [`MAX_DEPTH`] or deeper and never matches.
+    TooDeep {
+        /// Full path of the first route past the limit.
+        route: String,
+    },
+}
+
+impl fmt::Display for RouteTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycle { route } => write!(f, "Route '{route}' is its own descendant"),
+            Self::TooDeep { route } => write!(
+                f,
+                "Route '{route}' is nested deeper than {MAX_DEPTH} levels and never matches"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RouteTreeError {}
+
 impl NavigationResult {
     /// Check if navigation was successful
     #[must_use]
@@ -142,6 +439,12 @@ impl NavigationResult {
         matches!(self, Self::Blocked { .. })
     }
 
+    /// Check if navigation is waiting for a challenge answer
+    #[must_use]
+    pub const fn is_challenged(&self) -> bool {
+        matches!(self, Self::Challenged { .. })
+    }
+
     /// Check if there was an error
     #[must_use]
     pub const fn is_error(&self) -> bool {
@@ -159,6 +462,46 @@ impl NavigationResult {
             _ => None,
         }
     }
+
+    /// Convert into a `Result` for `?`-based error handling
+    ///
+    /// `Success` yields the path navigated to. Every other outcome becomes
+    /// the matching [`NavigationError`]; a blocked navigation's suggested
+    /// redirect is dropped, read it with
+    /// [`redirect_path`](Self::redirect_path) first if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error for any outcome other than `Success`.
+    pub fn into_result(self) -> Result<String, NavigationError> {
+        match self {
+            Self::Success { path } => Ok(path),
+            Self::NotFound { path } => Err(NavigationError::RouteNotFound { path }),
+            Self::Blocked { reason, .. } => Err(NavigationError::GuardBlocked { reason }),
+            Self::Challenged { path, challenge_id } => {
+                Err(NavigationError::Challenged { path, challenge_id })
+            }
+            Self::Error(error) => Err(error),
+        }
+    }
+}
+
+// ============================================================================
+// Not-Found Behavior
+// ============================================================================
+
+/// What the router does when a navigation target matches no route.
+///
+/// Set via
+/// [`GlobalRouter::set_not_found_behavior`](crate::context::GlobalRouter::set_not_found_behavior).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NotFoundBehavior {
+    /// Keep the unmatched path and render the 404 page (default).
+    #[default]
+    Render,
+    /// Replace the unmatched path with this fallback path.
+    Redirect(String),
 }
 
 // ============================================================================
@@ -292,6 +635,85 @@ mod tests {
         assert_eq!(error.to_string(), "Route not found: /test");
     }
 
+    #[test]
+    fn test_navigation_error_display_has_context() {
+        let cases = [
+            (
+                NavigationError::GuardBlocked {
+                    reason: "not signed in".into(),
+                },
+                vec!["blocked", "not signed in"],
+            ),
+            (
+                NavigationError::InvalidParams {
+                    message: "missing param 'id'".into(),
+                },
+                vec!["Invalid parameters", "'id'"],
+            ),
+            (
+                NavigationError::NavigationFailed {
+                    message: "redirect loop".into(),
+                },
+                vec!["failed", "redirect loop"],
+            ),
+            (
+                NavigationError::Challenged {
+                    path: "/billing".into(),
+                    challenge_id: "2fa".into(),
+                },
+                vec!["/billing", "'2fa'"],
+            ),
+            (
+                NavigationError::Custom {
+                    message: "quota exceeded".into(),
+                },
+                vec!["quota exceeded"],
+            ),
+        ];
+        for (error, expected) in cases {
+            let message = error.to_string();
+            for part in expected {
+                assert!(message.contains(part), "{message:?} lacks {part:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_into_result_feeds_question_mark() {
+        fn open(result: NavigationResult) -> Result<String, Box<dyn std::error::Error>> {
+            let path = result.into_result()?;
+            Ok(path)
+        }
+
+        let ok = open(NavigationResult::Success {
+            path: "/home".into(),
+        });
+        assert_eq!(ok.unwrap(), "/home");
+
+        let err = open(NavigationResult::NotFound {
+            path: "/missing".into(),
+        });
+        assert_eq!(err.unwrap_err().to_string(), "Route not found: /missing");
+
+        let blocked = NavigationResult::Blocked {
+            reason: "read only".into(),
+            redirect: Some("/login".into()),
+        };
+        assert!(matches!(
+            blocked.into_result(),
+            Err(NavigationError::GuardBlocked { reason }) if reason == "read only"
+        ));
+
+        let challenged = NavigationResult::Challenged {
+            path: "/billing".into(),
+            challenge_id: "2fa".into(),
+        };
+        assert!(matches!(
+            challenged.into_result(),
+            Err(NavigationError::Challenged { .. })
+        ));
+    }
+
     #[test]
     fn test_error_handlers_creation() {
         let handlers = ErrorHandlers::new();
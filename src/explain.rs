@@ -0,0 +1,335 @@
+//! Dry-run explanations of a navigation.
+//!
+//! [`GlobalRouter::explain_navigation`](crate::GlobalRouter::explain_navigation)
+//! walks the navigation pipeline for a path without performing it, and
+//! reports why it would end where it does: every guard consulted, every
+//! lifecycle decision, each redirect hop with its cause, the final outcome
+//! and the match stack it would render.
+//!
+//! ```no_run
+//! use gpui_navigator::GlobalRouter;
+//!
+//! fn why(cx: &gpui::App) {
+//!     let explanation = cx.global::<GlobalRouter>().explain_navigation(cx, "/admin");
+//!     for hop in &explanation.hops {
+//!         println!("{} -> {} ({:?})", hop.from, hop.to, hop.cause);
+//!     }
+//!     println!("{:?}", explanation.outcome);
+//! }
+//! ```
+//!
+//! Guards answer through [`RouteGuard::probe`](crate::RouteGuard::probe) and
+//! lifecycle hooks through their `probe_*` variants, so nothing runs with
+//! side effects: history, middleware, loaders and pending challenges are left
+//! untouched. `on_exit` is cleanup and is not consulted.
+//!
+//! The whole module compiles out with the `explain` feature.
+
+use crate::error::NavigationResult;
+use crate::lifecycle::NavigationAction;
+
+/// Structured trace of a dry-run navigation, see
+/// [`GlobalRouter::explain_navigation`](crate::GlobalRouter::explain_navigation).
+#[derive(Debug, Clone)]
+pub struct NavigationExplanation {
+    /// Path the explanation was asked for
+    pub requested: String,
+    /// Guards consulted, in evaluation order across all hops
+    pub guards: Vec<GuardDecision>,
+    /// Lifecycle hooks consulted, in pipeline order across all hops
+    pub lifecycle: Vec<LifecycleDecision>,
+    /// Redirects followed on the way, in order
+    pub hops: Vec<RedirectHop>,
+    /// What the real navigation would return
+    pub outcome: NavigationResult,
+    /// Accumulated patterns of the match stack for the last target, root to leaf
+    pub patterns: Vec<String>,
+}
+
+impl NavigationExplanation {
+    pub(crate) fn new(requested: &str) -> Self {
+        Self {
+            requested: requested.to_string(),
+            guards: Vec::new(),
+            lifecycle: Vec::new(),
+            hops: Vec::new(),
+            outcome: NavigationResult::Success {
+                path: requested.to_string(),
+            },
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Path the navigation would land on, if it succeeds.
+    #[must_use]
+    pub fn landed_on(&self) -> Option<&str> {
+        match &self.outcome {
+            NavigationResult::Success { path } => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// One guard consulted during an explanation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuardDecision {
+    /// Navigation target the guard was asked about
+    pub path: String,
+    /// [`RouteGuard::name`](crate::RouteGuard::name) of the guard
+    pub guard: &'static str,
+    /// Accumulated pattern of the route declaring the guard
+    pub route: String,
+    /// What the guard answered
+    pub action: NavigationAction,
+}
+
+/// Lifecycle hook consulted during an explanation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LifecycleHook {
+    /// [`RouteLifecycle::can_deactivate`](crate::RouteLifecycle::can_deactivate) of the route being left
+    CanDeactivate,
+    /// [`RouteLifecycle::on_enter`](crate::RouteLifecycle::on_enter) of the route being entered
+    OnEnter,
+}
+
+/// One lifecycle decision made during an explanation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LifecycleDecision {
+    /// Navigation target at the time
+    pub path: String,
+    /// Accumulated pattern of the route whose hook answered
+    pub route: String,
+    /// Which hook answered
+    pub hook: LifecycleHook,
+    /// What the hook answered
+    pub action: NavigationAction,
+}
+
+/// Why a redirect hop happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RedirectCause {
+    /// [`Route::redirect_if`](crate::Route::redirect_if) of a matched route redirected
+    Route {
+        /// Accumulated pattern of the redirecting route
+        pattern: String,
+    },
+    /// A guard redirected
+    Guard {
+        /// Name of the redirecting guard
+        guard: &'static str,
+        /// Reason the guard gave
+        reason: Option<String>,
+    },
+    /// `can_deactivate` of the route being left redirected
+    CanDeactivate {
+        /// Reason the hook gave
+        reason: Option<String>,
+    },
+    /// `on_enter` of the entered route redirected
+    OnEnter {
+        /// Reason the hook gave
+        reason: Option<String>,
+    },
+    /// No route matched and [`NotFoundBehavior::Redirect`](crate::NotFoundBehavior::Redirect) applied
+    NotFound,
+}
+
+impl RedirectCause {
+    /// Check whether the hop happens after the navigation was committed.
+    ///
+    /// `on_enter` and not-found redirects leave the intermediate path in
+    /// history; route, guard and `can_deactivate` redirects never reach it.
+    #[must_use]
+    pub const fn commits(&self) -> bool {
+        matches!(self, Self::OnEnter { .. } | Self::NotFound)
+    }
+}
+
+/// One redirect followed during an explanation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectHop {
+    /// Target that was redirected away from
+    pub from: String,
+    /// Target redirected to
+    pub to: String,
+    /// What caused the redirect
+    pub cause: RedirectCause,
+}
+
+#[cfg(all(test, feature = "guard"))]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::test_support::page;
+    use crate::{
+        init_router, DirtyStateRegistry, GlobalRouter, NavigationRequest, Navigator, RouteLifecycle,
+    };
+    use gpui::{App, TestAppContext};
+
+    fn init_explain_router(cx: &mut TestAppContext) {
+        use crate::{guard_fn, AuthGuard};
+
+        /// Sends every entry on to `/v3`, and says so when probed.
+        struct Moved;
+
+        impl RouteLifecycle for Moved {
+            fn on_enter(&self, _cx: &App, _request: &NavigationRequest) -> NavigationAction {
+                NavigationAction::redirect_with_reason("/v3", "moved again")
+            }
+
+            fn on_exit(&self, _cx: &App) -> NavigationAction {
+                NavigationAction::Continue
+            }
+
+            fn can_deactivate(&self, _cx: &App) -> NavigationAction {
+                NavigationAction::Continue
+            }
+
+            fn probe_enter(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+                self.on_enter(cx, request)
+            }
+        }
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/docs")
+                        .guard(guard_fn(|_, _| NavigationAction::Continue))
+                        .child(page(":page")),
+                );
+                router.add_route(
+                    page("/denied").guard(guard_fn(|_, _| NavigationAction::deny("Admins only"))),
+                );
+                router.add_route(page("/v1").guard(AuthGuard::new(|_| false, "/v2")));
+                router.add_route(page("/v2").lifecycle(Moved));
+                router.add_route(page("/v3"));
+                router.add_route(page("/form").protect_unsaved("form"));
+            });
+        });
+    }
+
+    fn explain(cx: &mut TestAppContext, path: &str) -> NavigationExplanation {
+        cx.read(|cx| cx.global::<GlobalRouter>().explain_navigation(cx, path))
+    }
+
+    #[gpui::test]
+    fn test_explain_allowed_path(cx: &mut TestAppContext) {
+        init_explain_router(cx);
+
+        let explanation = explain(cx, "/docs/intro");
+        assert_eq!(explanation.requested, "/docs/intro");
+        assert_eq!(explanation.landed_on(), Some("/docs/intro"));
+        assert_eq!(explanation.guards.len(), 1);
+        assert_eq!(explanation.guards[0].route, "/docs");
+        assert_eq!(explanation.guards[0].action, NavigationAction::Continue);
+        assert!(explanation.lifecycle.is_empty());
+        assert!(explanation.hops.is_empty());
+        assert_eq!(explanation.patterns, ["/docs", "/docs/:page"]);
+    }
+
+    #[gpui::test]
+    fn test_explain_denied_path(cx: &mut TestAppContext) {
+        init_explain_router(cx);
+
+        let explanation = explain(cx, "/denied");
+        assert!(
+            matches!(&explanation.outcome, NavigationResult::Blocked { reason, redirect: None } if reason == "Admins only"),
+            "{explanation:?}"
+        );
+        assert_eq!(explanation.guards.len(), 1);
+        assert_eq!(explanation.guards[0].path, "/denied");
+        assert_eq!(
+            explanation.guards[0].action,
+            NavigationAction::deny("Admins only")
+        );
+        assert!(explanation.hops.is_empty());
+        // The stack the guard kept the app from rendering
+        assert_eq!(explanation.patterns, ["/denied"]);
+    }
+
+    #[gpui::test]
+    fn test_explain_two_hop_redirect(cx: &mut TestAppContext) {
+        init_explain_router(cx);
+
+        let explanation = explain(cx, "/v1");
+        let hops: Vec<_> = explanation
+            .hops
+            .iter()
+            .map(|hop| (hop.from.as_str(), hop.to.as_str(), hop.cause.commits()))
+            .collect();
+        assert_eq!(hops, [("/v1", "/v2", false), ("/v2", "/v3", true)]);
+        assert!(matches!(
+            explanation.hops[0].cause,
+            RedirectCause::Guard {
+                guard: "AuthGuard",
+                ..
+            }
+        ));
+        assert_eq!(
+            explanation.hops[1].cause,
+            RedirectCause::OnEnter {
+                reason: Some("moved again".to_string())
+            }
+        );
+        // `/v2` was entered, so its `can_deactivate` answers the last hop
+        let lifecycle: Vec<_> = explanation
+            .lifecycle
+            .iter()
+            .map(|decision| {
+                (
+                    decision.hook,
+                    decision.route.as_str(),
+                    decision.path.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            lifecycle,
+            [
+                (LifecycleHook::OnEnter, "/v2", "/v2"),
+                (LifecycleHook::CanDeactivate, "/v2", "/v3")
+            ]
+        );
+        assert_eq!(explanation.landed_on(), Some("/v3"));
+        assert_eq!(explanation.patterns, ["/v3"]);
+
+        // Nothing happened, and the real navigation agrees
+        assert_eq!(cx.read(Navigator::current_path), "/");
+        cx.update(|cx| Navigator::push(cx, "/v1"));
+        assert_eq!(cx.read(Navigator::current_path), "/v3");
+    }
+
+    #[gpui::test]
+    fn test_explain_unsaved_changes_block(cx: &mut TestAppContext) {
+        init_explain_router(cx);
+        cx.update(|cx| Navigator::push(cx, "/form"));
+        cx.update(|cx| DirtyStateRegistry::mark_dirty(cx, "form"));
+        let history_len = |cx: &mut TestAppContext| {
+            cx.read(|cx| cx.global::<GlobalRouter>().state().history().len())
+        };
+        let before = history_len(cx);
+
+        let explanation = explain(cx, "/v3");
+        assert!(
+            matches!(&explanation.outcome, NavigationResult::Blocked { reason, .. } if reason == "You have unsaved changes"),
+            "{explanation:?}"
+        );
+        assert!(explanation.guards.is_empty());
+        assert_eq!(
+            explanation.lifecycle,
+            [LifecycleDecision {
+                path: "/v3".to_string(),
+                route: "/form".to_string(),
+                hook: LifecycleHook::CanDeactivate,
+                action: NavigationAction::deny("You have unsaved changes"),
+            }]
+        );
+
+        assert_eq!(history_len(cx), before);
+        assert_eq!(cx.read(Navigator::current_path), "/form");
+        assert!(cx.read(|cx| DirtyStateRegistry::is_dirty(cx, Some("form"))));
+    }
+}
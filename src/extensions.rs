@@ -0,0 +1,119 @@
+//! Per-navigation scratch data.
+//!
+//! Guards and middleware often compute something the destination page needs
+//! too (decoded token claims, a record fetched for an ownership check). They
+//! can leave it on the request instead of making the page compute it again:
+//!
+//! ```no_run
+//! use gpui_navigator::{guard_fn, NavigationAction, Navigator, Route};
+//! use gpui::*;
+//!
+//! struct Claims {
+//!     user: String,
+//! }
+//!
+//! Route::new("/profile", |_, cx, _params| {
+//!     let user = Navigator::navigation_extension::<Claims>(cx)
+//!         .map(|claims| claims.user.clone())
+//!         .unwrap_or_default();
+//!     div().child(user).into_any_element()
+//! })
+//! .guard(guard_fn(|_cx, request| {
+//!     request.extensions().insert(Claims { user: "ada".into() });
+//!     NavigationAction::Continue
+//! }));
+//! ```
+//!
+//! Values inserted while the pipeline runs are moved onto the leaf
+//! [`MatchEntry`](crate::MatchEntry) when the navigation commits, and stay
+//! readable through [`MatchEntry::navigation_extensions`](crate::MatchEntry::navigation_extensions)
+//! for as long as that entry is in the match stack. Blocked navigations,
+//! and the requests a redirect abandons, drop theirs.
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Type-map of values, at most one per type.
+///
+/// Writable through a shared reference, since guards and middleware only
+/// see `&NavigationRequest`. Clones share the stored values but not later
+/// insertions.
+#[derive(Default, Clone)]
+pub struct Extensions {
+    values: RefCell<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl Extensions {
+    /// Store `value`, replacing any earlier value of the same type.
+    pub fn insert<T: Any + Send + Sync>(&self, value: T) {
+        self.values
+            .borrow_mut()
+            .insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Return the value of type `T`, if one was inserted.
+    #[must_use]
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        let value = Arc::clone(self.values.borrow().get(&TypeId::of::<T>())?);
+        value.downcast().ok()
+    }
+
+    /// Return `true` if a value of type `T` was inserted.
+    #[must_use]
+    pub fn contains<T: Any + Send + Sync>(&self) -> bool {
+        self.values.borrow().contains_key(&TypeId::of::<T>())
+    }
+
+    /// Return `true` if nothing was inserted.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.borrow().is_empty()
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.values.borrow().len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Claims(&'static str);
+
+    #[test]
+    fn test_insert_and_get_by_type() {
+        let extensions = Extensions::default();
+        assert!(extensions.is_empty());
+        assert_eq!(extensions.get::<Claims>(), None);
+
+        extensions.insert(Claims("ada"));
+        extensions.insert(7_u32);
+        extensions.insert(Claims("grace"));
+        assert_eq!(
+            extensions.get::<Claims>().as_deref(),
+            Some(&Claims("grace"))
+        );
+        assert_eq!(extensions.get::<u32>().as_deref(), Some(&7));
+        assert!(!extensions.contains::<u64>());
+    }
+
+    #[test]
+    fn test_clone_shares_values_not_insertions() {
+        let extensions = Extensions::default();
+        extensions.insert(Claims("ada"));
+        let copy = extensions.clone();
+        copy.insert(7_u32);
+
+        assert_eq!(copy.get::<Claims>().as_deref(), Some(&Claims("ada")));
+        assert!(!extensions.contains::<u32>());
+    }
+}
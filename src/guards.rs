@@ -28,6 +28,33 @@
 //! The first non-[`Continue`](crate::NavigationAction::Continue) result
 //! short-circuits evaluation.
 //!
+//! Ties are broken deterministically, independent of registration order:
+//!
+//! 1. priority, highest first
+//! 2. depth of the route the guard is attached to, parents first
+//! 3. declaration index of the guard on its route, first attached first
+//! 4. accumulated route pattern, lexicographically
+//!
+//! [`Route::guard_with_priority`](crate::Route::guard_with_priority)
+//! overrides a guard's priority at attachment time, and
+//! [`GlobalRouter::guard_order`](crate::GlobalRouter::guard_order) shows the
+//! resulting order for a path.
+//!
+//! # Probing
+//!
+//! [`GlobalRouter::can_navigate`](crate::GlobalRouter::can_navigate) runs the
+//! same guards, in the same order, without navigating — e.g. to hide links
+//! the user cannot follow. It calls [`RouteGuard::probe`], which defaults to
+//! `check`, so guards must be free of side effects or override `probe`.
+//!
+//! # Caching
+//!
+//! A guard whose [`RouteGuard::cacheable`] returns `true` runs at most once
+//! per target path within one navigation attempt (redirect hops included):
+//! if the same guard protects both a parent and a child route, the second
+//! occurrence reuses the first result. Results are keyed by
+//! [`RouteGuard::name`] and dropped when the navigation ends.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -40,8 +67,11 @@
 //! ```
 
 use crate::lifecycle::NavigationAction;
-use crate::NavigationRequest;
+use crate::logging::current_navigation;
+use crate::{trace_log, NavigationRequest};
 use gpui::App;
+use std::cell::RefCell;
+use std::collections::HashMap;
 
 // ============================================================================
 // RouteGuard trait
@@ -92,6 +122,15 @@ pub trait RouteGuard: Send + Sync + 'static {
     /// - [`NavigationAction::Redirect`] to redirect to a different path
     fn check(&self, cx: &App, request: &NavigationRequest) -> NavigationAction;
 
+    /// Answer a dry run from [`GlobalRouter::can_navigate`](crate::GlobalRouter::can_navigate).
+    ///
+    /// Probes may run on every render (one per guarded link), so they must
+    /// not have side effects. Defaults to [`check`](Self::check); override it
+    /// if `check` records attempts or otherwise mutates state.
+    fn probe(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+        self.check(cx, request)
+    }
+
     /// Guard name for debugging and error messages.
     fn name(&self) -> &'static str {
         "RouteGuard"
@@ -101,6 +140,49 @@ pub trait RouteGuard: Send + Sync + 'static {
     fn priority(&self) -> i32 {
         0
     }
+
+    /// Whether the result of [`check`](Self::check) may be reused within
+    /// one navigation. Default is `false`.
+    ///
+    /// Results are keyed by [`name`](Self::name) and target path, so
+    /// cacheable guards need names that tell them apart.
+    fn cacheable(&self) -> bool {
+        false
+    }
+
+    /// Whether copies of this guard collected for one navigation run only
+    /// once. Default is `false`.
+    ///
+    /// Guards attached at several levels of the route tree all run by
+    /// default. A deduplicated guard runs at its first position in the
+    /// [guard order](crate::GlobalRouter::guard_order); later copies with
+    /// the same [`name`](Self::name) and [`dedup_key`](Self::dedup_key) are
+    /// dropped.
+    fn deduplicate(&self) -> bool {
+        false
+    }
+
+    /// Tells apart deduplicated guards sharing a [`name`](Self::name), e.g.
+    /// role guards for different roles. Default is the name.
+    fn dedup_key(&self) -> &'static str {
+        self.name()
+    }
+
+    /// Whether the target may be shown in a
+    /// [preview](crate::GlobalRouter::render_preview). Default is `true`.
+    ///
+    /// Previews render without navigating, so guards protecting content
+    /// that must not appear before they ran, e.g. behind a confirmation
+    /// step, return `false`.
+    fn allow_preview(&self) -> bool {
+        true
+    }
+
+    /// What the guard requires, for generated documentation such as
+    /// [`help_entries`](crate::help::help_entries). Default is nothing.
+    fn requirements(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 // ============================================================================
@@ -144,6 +226,86 @@ where
     }
 }
 
+/// Create a guard from a fallible closure.
+///
+/// `Ok(())` continues navigation; `Err(reason)` denies it with `reason`.
+///
+/// # Example
+///
+/// ```no_run
+/// use gpui_navigator::guard_try;
+///
+/// let guard = guard_try(|_cx, request| {
+///     if request.to.starts_with("/admin") {
+///         return Err("Admins only".to_string());
+///     }
+///     Ok(())
+/// });
+/// ```
+pub const fn guard_try<F>(f: F) -> TryGuard<F>
+where
+    F: Fn(&App, &NavigationRequest) -> Result<(), String> + Send + Sync + 'static,
+{
+    TryGuard { f }
+}
+
+/// Guard created by [`guard_try`].
+pub struct TryGuard<F> {
+    f: F,
+}
+
+impl<F> RouteGuard for TryGuard<F>
+where
+    F: Fn(&App, &NavigationRequest) -> Result<(), String> + Send + Sync + 'static,
+{
+    fn check(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+        match (self.f)(cx, request) {
+            Ok(()) => NavigationAction::Continue,
+            Err(reason) => NavigationAction::deny(reason),
+        }
+    }
+}
+
+/// Create a guard from a fallible closure that redirects on error.
+///
+/// `Ok(())` continues navigation; `Err(reason)` redirects to `redirect`,
+/// keeping `reason` on the redirect.
+///
+/// # Example
+///
+/// ```no_run
+/// use gpui_navigator::guard_redirect_on_err;
+///
+/// let guard = guard_redirect_on_err(
+///     |_cx, _request| Err("Session expired".to_string()),
+///     "/login",
+/// );
+/// ```
+pub const fn guard_redirect_on_err<F>(f: F, redirect: &'static str) -> RedirectOnErrGuard<F>
+where
+    F: Fn(&App, &NavigationRequest) -> Result<(), String> + Send + Sync + 'static,
+{
+    RedirectOnErrGuard { f, redirect }
+}
+
+/// Guard created by [`guard_redirect_on_err`].
+pub struct RedirectOnErrGuard<F> {
+    f: F,
+    redirect: &'static str,
+}
+
+impl<F> RouteGuard for RedirectOnErrGuard<F>
+where
+    F: Fn(&App, &NavigationRequest) -> Result<(), String> + Send + Sync + 'static,
+{
+    fn check(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+        match (self.f)(cx, request) {
+            Ok(()) => NavigationAction::Continue,
+            Err(reason) => NavigationAction::redirect_with_reason(self.redirect, reason),
+        }
+    }
+}
+
 // ============================================================================
 // AuthGuard
 // ============================================================================
@@ -213,6 +375,10 @@ impl RouteGuard for AuthGuard {
     fn priority(&self) -> i32 {
         100
     }
+
+    fn requirements(&self) -> Vec<String> {
+        vec!["authenticated".to_string()]
+    }
 }
 
 // ============================================================================
@@ -288,6 +454,10 @@ impl RouteGuard for RoleGuard {
     fn priority(&self) -> i32 {
         90
     }
+
+    fn requirements(&self) -> Vec<String> {
+        vec![format!("role:{}", self.required_role)]
+    }
 }
 
 // ============================================================================
@@ -364,6 +534,10 @@ impl RouteGuard for PermissionGuard {
     fn priority(&self) -> i32 {
         80
     }
+
+    fn requirements(&self) -> Vec<String> {
+        vec![self.permission.clone()]
+    }
 }
 
 // ============================================================================
@@ -404,19 +578,30 @@ impl Guards {
     }
 }
 
-impl RouteGuard for Guards {
-    fn check(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+impl Guards {
+    /// Evaluate the guards in priority order; the first non-`Continue` wins.
+    fn evaluate(&self, run: impl Fn(&dyn RouteGuard) -> NavigationAction) -> NavigationAction {
         let mut sorted: Vec<_> = self.guards.iter().collect();
         sorted.sort_by_key(|g| std::cmp::Reverse(g.priority()));
 
         for guard in sorted {
-            let result = guard.check(cx, request);
+            let result = run(guard.as_ref());
             if !matches!(result, NavigationAction::Continue) {
                 return result;
             }
         }
         NavigationAction::Continue
     }
+}
+
+impl RouteGuard for Guards {
+    fn check(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+        self.evaluate(|guard| guard.check(cx, request))
+    }
+
+    fn probe(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+        self.evaluate(|guard| guard.probe(cx, request))
+    }
 
     fn name(&self) -> &'static str {
         "Guards"
@@ -425,6 +610,14 @@ impl RouteGuard for Guards {
     fn priority(&self) -> i32 {
         self.guards.iter().map(|g| g.priority()).max().unwrap_or(0)
     }
+
+    fn requirements(&self) -> Vec<String> {
+        self.guards.iter().flat_map(|g| g.requirements()).collect()
+    }
+
+    fn allow_preview(&self) -> bool {
+        self.guards.iter().all(|g| g.allow_preview())
+    }
 }
 
 /// Builder for [`Guards`] with fluent API.
@@ -466,7 +659,7 @@ impl Default for GuardBuilder {
 ///
 /// - `Continue` becomes `Deny`
 /// - `Deny` becomes `Continue`
-/// - `Redirect` is preserved as-is
+/// - `Redirect` and `Challenge` are preserved as-is
 ///
 /// # Example
 ///
@@ -489,16 +682,28 @@ impl NotGuard {
     }
 }
 
-impl RouteGuard for NotGuard {
-    fn check(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
-        match self.guard.check(cx, request) {
+impl NotGuard {
+    fn invert(action: NavigationAction) -> NavigationAction {
+        match action {
             NavigationAction::Continue => {
                 NavigationAction::deny("Inverted: guard allowed but NOT expected")
             }
             NavigationAction::Deny { .. } => NavigationAction::Continue,
-            redirect @ NavigationAction::Redirect { .. } => redirect,
+            other @ (NavigationAction::Redirect { .. } | NavigationAction::Challenge { .. }) => {
+                other
+            }
         }
     }
+}
+
+impl RouteGuard for NotGuard {
+    fn check(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+        Self::invert(self.guard.check(cx, request))
+    }
+
+    fn probe(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+        Self::invert(self.guard.probe(cx, request))
+    }
 
     fn name(&self) -> &'static str {
         "NotGuard"
@@ -507,6 +712,165 @@ impl RouteGuard for NotGuard {
     fn priority(&self) -> i32 {
         self.guard.priority()
     }
+
+    fn allow_preview(&self) -> bool {
+        self.guard.allow_preview()
+    }
+}
+
+// ============================================================================
+// Priority override & ordering
+// ============================================================================
+
+/// Wraps a guard to report a priority chosen at attachment time.
+pub(crate) struct PrioritizedGuard {
+    guard: Box<dyn RouteGuard>,
+    priority: i32,
+}
+
+impl PrioritizedGuard {
+    pub(crate) fn new<G: RouteGuard>(guard: G, priority: i32) -> Self {
+        Self {
+            guard: Box::new(guard),
+            priority,
+        }
+    }
+}
+
+impl RouteGuard for PrioritizedGuard {
+    fn check(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+        self.guard.check(cx, request)
+    }
+
+    fn probe(&self, cx: &App, request: &NavigationRequest) -> NavigationAction {
+        self.guard.probe(cx, request)
+    }
+
+    fn name(&self) -> &'static str {
+        self.guard.name()
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    fn requirements(&self) -> Vec<String> {
+        self.guard.requirements()
+    }
+
+    fn cacheable(&self) -> bool {
+        self.guard.cacheable()
+    }
+
+    fn allow_preview(&self) -> bool {
+        self.guard.allow_preview()
+    }
+}
+
+/// One guard in the evaluation order for a path, as reported by
+/// [`GlobalRouter::guard_order`](crate::GlobalRouter::guard_order).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardOrderEntry {
+    /// Guard name from [`RouteGuard::name`].
+    pub name: &'static str,
+    /// Effective priority (including any attachment-time override).
+    pub priority: i32,
+    /// Depth of the route the guard is attached to (0 = top-level route).
+    pub depth: usize,
+    /// Position of the guard among its route's guards.
+    pub index: usize,
+    /// Accumulated pattern of the route the guard is attached to.
+    pub route: String,
+}
+
+// ============================================================================
+// Per-navigation result cache
+// ============================================================================
+
+/// Results of cacheable guards for the navigation identified by `navigation`.
+struct ResultCache {
+    navigation: u32,
+    results: HashMap<(&'static str, String), NavigationAction>,
+}
+
+thread_local! {
+    /// Cache of the navigation currently running on this thread.
+    static RESULT_CACHE: RefCell<Option<ResultCache>> = const { RefCell::new(None) };
+}
+
+/// Keeps cacheable guard results alive for the running navigation.
+///
+/// Entered next to the navigation span. The first scope of a navigation
+/// installs an empty cache; scopes of its redirect hops share it. Dropping
+/// the first scope puts back whatever was cached before, so a navigation
+/// started from inside a guard gets its own cache.
+pub(crate) struct GuardCacheScope {
+    /// Whether this scope installed the cache and must restore on drop.
+    owner: bool,
+    previous: Option<ResultCache>,
+}
+
+impl GuardCacheScope {
+    pub(crate) fn enter() -> Self {
+        let mut scope = Self {
+            owner: false,
+            previous: None,
+        };
+        let Some(navigation) = current_navigation().map(|id| id.id()) else {
+            return scope;
+        };
+        RESULT_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if cache.as_ref().is_some_and(|c| c.navigation == navigation) {
+                return;
+            }
+            scope.owner = true;
+            scope.previous = cache.replace(ResultCache {
+                navigation,
+                results: HashMap::new(),
+            });
+        });
+        scope
+    }
+}
+
+impl Drop for GuardCacheScope {
+    fn drop(&mut self) {
+        if self.owner {
+            RESULT_CACHE.with(|cache| *cache.borrow_mut() = self.previous.take());
+        }
+    }
+}
+
+/// Run `guard.check`, reusing an earlier result of this navigation if the
+/// guard is [`cacheable`](RouteGuard::cacheable).
+pub(crate) fn check_cached(
+    guard: &dyn RouteGuard,
+    cx: &App,
+    request: &NavigationRequest,
+) -> NavigationAction {
+    if !guard.cacheable() {
+        return guard.check(cx, request);
+    }
+    let key = (guard.name(), request.to.clone());
+    let hit = RESULT_CACHE.with(|cache| {
+        cache
+            .borrow()
+            .as_ref()
+            .and_then(|c| c.results.get(&key).cloned())
+    });
+    if let Some(action) = hit {
+        trace_log!("Guard '{}' for '{}' answered from cache", key.0, key.1);
+        return action;
+    }
+
+    let action = guard.check(cx, request);
+    RESULT_CACHE.with(|cache| {
+        if let Some(cache) = cache.borrow_mut().as_mut() {
+            cache.results.insert(key, action.clone());
+        }
+    });
+    action
 }
 
 // ============================================================================
@@ -517,6 +881,13 @@ impl RouteGuard for NotGuard {
 #[allow(clippy::needless_pass_by_ref_mut)]
 mod tests {
     use super::*;
+    use crate::context::MAX_CHALLENGE_ROUNDS;
+    use crate::test_support::{page, push_result};
+    use crate::{
+        init_router, GlobalRouter, NavigationResult, Navigator, PendingChallenge, Route, UseRouter,
+    };
+    use gpui::{BorrowAppContext, IntoElement, TestAppContext, Window};
+    use std::sync::Arc;
 
     fn make_request(path: &str) -> NavigationRequest {
         NavigationRequest::new(path.to_string())
@@ -531,6 +902,47 @@ mod tests {
         assert_eq!(guard.priority(), 0);
     }
 
+    #[gpui::test]
+    fn test_guard_try_ok_continues(cx: &mut gpui::TestAppContext) {
+        let guard = guard_try(|_, _| Ok(()));
+        let request = make_request("/test");
+        let result = cx.update(|cx| guard.check(cx, &request));
+        assert!(result.is_continue());
+    }
+
+    #[gpui::test]
+    fn test_guard_try_err_denies_with_reason(cx: &mut gpui::TestAppContext) {
+        let guard = guard_try(|_, request| Err(format!("No access to {}", request.to)));
+        let request = make_request("/admin");
+        let result = cx.update(|cx| guard.check(cx, &request));
+        match result {
+            NavigationAction::Deny { reason } => assert_eq!(reason, "No access to /admin"),
+            _ => panic!("Expected Deny"),
+        }
+    }
+
+    #[gpui::test]
+    fn test_guard_redirect_on_err_ok_continues(cx: &mut gpui::TestAppContext) {
+        let guard = guard_redirect_on_err(|_, _| Ok(()), "/login");
+        let request = make_request("/test");
+        let result = cx.update(|cx| guard.check(cx, &request));
+        assert!(result.is_continue());
+    }
+
+    #[gpui::test]
+    fn test_guard_redirect_on_err_err_redirects(cx: &mut gpui::TestAppContext) {
+        let guard = guard_redirect_on_err(|_, _| Err("Session expired".to_string()), "/login");
+        let request = make_request("/test");
+        let result = cx.update(|cx| guard.check(cx, &request));
+        assert_eq!(result.redirect_path(), Some("/login"));
+        match result {
+            NavigationAction::Redirect { reason, .. } => {
+                assert_eq!(reason.as_deref(), Some("Session expired"));
+            }
+            _ => panic!("Expected Redirect"),
+        }
+    }
+
     // --- AuthGuard ---
 
     #[gpui::test]
@@ -686,4 +1098,435 @@ mod tests {
         assert!(result.is_redirect());
         assert_eq!(result.redirect_path(), Some("/somewhere"));
     }
+
+    // --- Guard order tests ---
+
+    fn nested_guard_routes(child_priority: Option<i32>) -> Route {
+        let child = Route::new("settings", |_, _cx, _params| gpui::div().into_any_element());
+        let child_guard = guard_fn(|_, _| NavigationAction::redirect("/child-wins"));
+        let child = match child_priority {
+            Some(priority) => child.guard_with_priority(child_guard, priority),
+            None => child.guard(child_guard),
+        };
+        Route::new("/account", |_, _cx, _params| gpui::div().into_any_element())
+            .guard(guard_fn(|_, request| {
+                if request.to.starts_with("/account") {
+                    NavigationAction::redirect("/parent-wins")
+                } else {
+                    NavigationAction::Continue
+                }
+            }))
+            .child(child)
+    }
+
+    fn init_guard_order_router(cx: &mut TestAppContext, child_priority: Option<i32>) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(nested_guard_routes(child_priority));
+                router.add_route(Route::new("/parent-wins", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(Route::new("/child-wins", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+            });
+        });
+    }
+
+    #[gpui::test]
+    fn test_equal_priority_guards_parent_runs_first(cx: &mut TestAppContext) {
+        init_guard_order_router(cx, None);
+
+        for _ in 0..3 {
+            cx.update(|cx| Navigator::push(cx, "/account/settings"));
+            assert_eq!(cx.read(Navigator::current_path), "/parent-wins");
+            cx.update(|cx| Navigator::push(cx, "/"));
+        }
+
+        let order = cx.read(|cx| cx.global::<GlobalRouter>().guard_order("/account/settings"));
+        let routes: Vec<_> = order.iter().map(|e| (e.route.as_str(), e.depth)).collect();
+        assert_eq!(routes, [("/account", 0), ("/account/settings", 1)]);
+    }
+
+    #[gpui::test]
+    fn test_guard_with_priority_overrides_winner(cx: &mut TestAppContext) {
+        init_guard_order_router(cx, Some(10));
+
+        cx.update(|cx| Navigator::push(cx, "/account/settings"));
+        assert_eq!(cx.read(Navigator::current_path), "/child-wins");
+
+        let order = cx.read(|cx| cx.global::<GlobalRouter>().guard_order("/account/settings"));
+        assert_eq!(order[0].priority, 10);
+        assert_eq!(order[0].route, "/account/settings");
+    }
+
+    #[test]
+    fn test_guard_order_independent_of_registration_order() {
+        let layout = || {
+            Route::new("/", |_, _cx, _params| gpui::div().into_any_element())
+                .guard(guard_fn(|_, _| NavigationAction::Continue))
+                .child(Route::new("reports", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }))
+        };
+        let reports = || {
+            Route::new("/reports", |_, _cx, _params| gpui::div().into_any_element())
+                .guard(guard_fn(|_, _| NavigationAction::Continue))
+                .guard(guard_fn(|_, _| NavigationAction::Continue))
+        };
+
+        let mut forward = GlobalRouter::new();
+        forward.add_route(layout());
+        forward.add_route(reports());
+
+        let mut shuffled = GlobalRouter::new();
+        shuffled.add_route(reports());
+        shuffled.add_route(layout());
+
+        let order = forward.guard_order("/reports");
+        assert_eq!(order, shuffled.guard_order("/reports"));
+        let keys: Vec<_> = order
+            .iter()
+            .map(|e| (e.depth, e.index, e.route.as_str()))
+            .collect();
+        assert_eq!(keys, [(0, 0, "/"), (0, 0, "/reports"), (0, 1, "/reports")]);
+    }
+
+    // --- Challenge tests ---
+
+    fn init_challenge_router(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(
+                    Route::new("/vault", |_, _cx, _params| gpui::div().into_any_element()).guard(
+                        guard_fn(|_, request| match request.challenge_response() {
+                            Some("1234") => NavigationAction::Continue,
+                            _ => NavigationAction::challenge("otp", "Enter your code"),
+                        }),
+                    ),
+                );
+            });
+        });
+    }
+
+    fn answer_result(cx: &mut TestAppContext, response: &str) -> Option<NavigationResult> {
+        cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, cx| {
+                router.answer_challenge(response.to_string(), cx)
+            })
+        })
+    }
+
+    #[gpui::test]
+    fn test_guard_challenge_suspends_navigation(cx: &mut TestAppContext) {
+        init_challenge_router(cx);
+
+        let result = push_result(cx, "/vault");
+        assert!(result.is_challenged());
+        assert_eq!(cx.read(Navigator::current_path), "/");
+
+        let (path, id, payload, round) = cx.read(|cx| {
+            let pending = cx.global::<GlobalRouter>().pending_challenge().unwrap();
+            (
+                pending.path().to_string(),
+                pending.challenge_id().to_string(),
+                pending.payload().to_string(),
+                pending.round(),
+            )
+        });
+        assert_eq!(path, "/vault");
+        assert_eq!(id, "otp");
+        assert_eq!(payload, "Enter your code");
+        assert_eq!(round, 1);
+        // A parked navigation counts as in flight
+        assert!(cx.read(Navigator::is_navigating));
+    }
+
+    #[gpui::test]
+    fn test_guard_challenge_correct_answer_resumes(cx: &mut TestAppContext) {
+        init_challenge_router(cx);
+        push_result(cx, "/vault");
+
+        cx.update(|cx| Navigator::answer_challenge(cx, "1234"));
+        assert_eq!(cx.read(Navigator::current_path), "/vault");
+        assert!(cx.read(Navigator::can_go_back));
+        assert!(cx.read(|cx| cx.global::<GlobalRouter>().pending_challenge().is_none()));
+    }
+
+    #[cfg(feature = "transition")]
+    #[gpui::test]
+    fn test_guard_challenge_answer_reaches_transition_hooks(cx: &mut TestAppContext) {
+        init_challenge_router(cx);
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = std::sync::Arc::clone(&seen);
+        cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.on_transition(move |event, _| {
+                    sink.lock().unwrap().push(event.to_path.clone());
+                });
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/vault"));
+        assert!(seen.lock().unwrap().is_empty());
+        cx.update(|cx| Navigator::answer_challenge(cx, "1234"));
+        assert_eq!(*seen.lock().unwrap(), ["/vault"]);
+    }
+
+    #[gpui::test]
+    fn test_guard_challenge_wrong_answers_deny_after_bound(cx: &mut TestAppContext) {
+        init_challenge_router(cx);
+        push_result(cx, "/vault");
+
+        for round in 2..=MAX_CHALLENGE_ROUNDS {
+            let result = answer_result(cx, "0000").unwrap();
+            assert!(result.is_challenged());
+            let current = cx.read(|cx| {
+                cx.global::<GlobalRouter>()
+                    .pending_challenge()
+                    .map(PendingChallenge::round)
+            });
+            assert_eq!(current, Some(round));
+        }
+
+        let result = answer_result(cx, "0000").unwrap();
+        assert!(result.is_blocked());
+        assert_eq!(cx.read(Navigator::current_path), "/");
+        assert!(cx.read(|cx| cx.global::<GlobalRouter>().pending_challenge().is_none()));
+        assert!(answer_result(cx, "1234").is_none());
+    }
+
+    #[gpui::test]
+    fn test_guard_challenge_cancel_leaves_history(cx: &mut TestAppContext) {
+        init_challenge_router(cx);
+        push_result(cx, "/vault");
+
+        cx.update(Navigator::cancel_challenge);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+        assert!(!cx.read(Navigator::can_go_back));
+        assert!(cx.read(|cx| cx.global::<GlobalRouter>().pending_challenge().is_none()));
+    }
+
+    #[gpui::test]
+    fn test_guard_challenge_superseded_by_navigation(cx: &mut TestAppContext) {
+        init_challenge_router(cx);
+        push_result(cx, "/vault");
+
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert!(cx.read(|cx| cx.global::<GlobalRouter>().pending_challenge().is_none()));
+        assert!(answer_result(cx, "1234").is_none());
+    }
+
+    // --- Guard extension and caching tests ---
+
+    #[gpui::test]
+    fn test_guard_extensions_reach_the_leaf(cx: &mut TestAppContext) {
+        use crate::router_view;
+        use gpui::{Context, Render};
+        use std::sync::Mutex;
+
+        struct Claims {
+            user: &'static str,
+        }
+
+        struct Root;
+
+        impl Render for Root {
+            fn render(&mut self, _: &mut Window, _: &mut Context<'_, Self>) -> impl IntoElement {
+                gpui::div()
+            }
+        }
+
+        let log: Arc<Mutex<Vec<String>>> = Arc::default();
+        let guard_log = Arc::clone(&log);
+        let page_log = Arc::clone(&log);
+        let decode = || {
+            guard_fn(|_, request| {
+                request.extensions().insert(Claims { user: "ada" });
+                NavigationAction::Continue
+            })
+        };
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    Route::new("/profile", move |_, cx, _| {
+                        let claims = Navigator::navigation_extension::<Claims>(cx);
+                        let user = claims.map_or("none", |claims| claims.user);
+                        page_log.lock().unwrap().push(format!("page {user}"));
+                        gpui::div().into_any_element()
+                    })
+                    // Declared first, but runs after the higher-priority decoder
+                    .guard(guard_fn(move |_, request| {
+                        let claims = request.extensions().get::<Claims>();
+                        let user = claims.map_or("none", |claims| claims.user);
+                        guard_log.lock().unwrap().push(format!("owner {user}"));
+                        NavigationAction::Continue
+                    }))
+                    .guard_with_priority(decode(), 10),
+                );
+                router.add_route(
+                    page("/private")
+                        .guard_with_priority(decode(), 10)
+                        .guard(guard_fn(|_, _| NavigationAction::deny("no"))),
+                );
+                router.add_route(page("/other"));
+            });
+        });
+        let claims = |cx: &mut TestAppContext| {
+            cx.read(Navigator::navigation_extension::<Claims>)
+                .map(|claims| claims.user)
+        };
+
+        cx.update(|cx| Navigator::push(cx, "/profile"));
+        let window = cx.add_window(|_, _| Root);
+        window
+            .update(cx, |_, window, cx| {
+                let _ = router_view(window, cx);
+            })
+            .unwrap();
+        assert_eq!(*log.lock().unwrap(), ["owner ada", "page ada"]);
+        assert_eq!(claims(cx), Some("ada"));
+
+        // The next navigation replaces the entry, and its data with it
+        cx.update(|cx| Navigator::push(cx, "/other"));
+        assert_eq!(claims(cx), None);
+
+        // A blocked navigation drops what its guards left
+        cx.update(|cx| Navigator::push(cx, "/private"));
+        assert_eq!(cx.read(Navigator::current_path), "/other");
+        assert_eq!(claims(cx), None);
+    }
+
+    #[gpui::test]
+    fn test_cacheable_guard_runs_once_per_navigation(cx: &mut TestAppContext) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct CountingGuard {
+            name: &'static str,
+            cacheable: bool,
+            calls: Arc<AtomicUsize>,
+        }
+
+        impl RouteGuard for CountingGuard {
+            fn check(&self, _cx: &App, _request: &NavigationRequest) -> NavigationAction {
+                self.calls.fetch_add(1, Ordering::SeqCst);
+                NavigationAction::Continue
+            }
+
+            fn name(&self) -> &'static str {
+                self.name
+            }
+
+            fn cacheable(&self) -> bool {
+                self.cacheable
+            }
+        }
+
+        let cached = Arc::new(AtomicUsize::new(0));
+        let uncached = Arc::new(AtomicUsize::new(0));
+        let guard = |name, cacheable, calls: &Arc<AtomicUsize>| CountingGuard {
+            name,
+            cacheable,
+            calls: Arc::clone(calls),
+        };
+        let guarded = |route: Route| {
+            route
+                .guard(guard("session", true, &cached))
+                .guard(guard("audit", false, &uncached))
+        };
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    guarded(page("/admin")).children(vec![guarded(page("users")).into()]),
+                );
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/admin/users"));
+        assert_eq!(cx.read(Navigator::current_path), "/admin/users");
+        assert_eq!(cached.load(Ordering::SeqCst), 1);
+        assert_eq!(uncached.load(Ordering::SeqCst), 2);
+
+        // The cache does not outlive the navigation
+        cx.update(|cx| Navigator::push(cx, "/"));
+        cx.update(|cx| Navigator::push(cx, "/admin/users"));
+        assert_eq!(cached.load(Ordering::SeqCst), 2);
+        assert_eq!(uncached.load(Ordering::SeqCst), 4);
+    }
+
+    #[gpui::test]
+    fn test_deduplicated_guard_runs_once(cx: &mut TestAppContext) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct SessionGuard {
+            key: &'static str,
+            deduplicate: bool,
+            calls: Arc<AtomicUsize>,
+        }
+
+        impl RouteGuard for SessionGuard {
+            fn check(&self, _cx: &App, _request: &NavigationRequest) -> NavigationAction {
+                self.calls.fetch_add(1, Ordering::SeqCst);
+                NavigationAction::Continue
+            }
+
+            fn name(&self) -> &'static str {
+                "session"
+            }
+
+            fn deduplicate(&self) -> bool {
+                self.deduplicate
+            }
+
+            fn dedup_key(&self) -> &'static str {
+                self.key
+            }
+        }
+
+        let shared = Arc::new(AtomicUsize::new(0));
+        let keyed = Arc::new(AtomicUsize::new(0));
+        let plain = Arc::new(AtomicUsize::new(0));
+        let guard = |key, deduplicate, calls: &Arc<AtomicUsize>| SessionGuard {
+            key,
+            deduplicate,
+            calls: Arc::clone(calls),
+        };
+        let guarded = |route: Route, key| {
+            route
+                .guard(guard("session", true, &shared))
+                .guard(guard(key, true, &keyed))
+                .guard(guard("session", false, &plain))
+        };
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    guarded(page("/admin"), "admin").children(vec![guarded(
+                        page("users"),
+                        "users",
+                    )
+                    .into()]),
+                );
+            });
+        });
+
+        let order = cx.read(|cx| cx.router().guard_order("/admin/users"));
+        assert_eq!(order.len(), 5);
+        cx.update(|cx| Navigator::push(cx, "/admin/users"));
+        assert_eq!(cx.read(Navigator::current_path), "/admin/users");
+        assert_eq!(shared.load(Ordering::SeqCst), 1);
+        // Other keys are other guards, and plain copies all run
+        assert_eq!(keyed.load(Ordering::SeqCst), 2);
+        assert_eq!(plain.load(Ordering::SeqCst), 2);
+    }
 }
@@ -0,0 +1,223 @@
+//! Route table introspection for generated help screens.
+//!
+//! [`help_entries`] turns the route table into one [`HelpEntry`] per
+//! navigable route, so an in-app Help screen lists what the app actually
+//! registers instead of a hand-maintained table. It reads:
+//!
+//! | Source | Field |
+//! |--------|-------|
+//! | `.meta("title", ...)` | [`HelpEntry::title`] |
+//! | `.meta("shortcut", ...)` | [`HelpEntry::shortcut`] |
+//! | [`Route::describe`] | [`HelpEntry::description`] |
+//! | [`RouteGuard::requirements`](crate::RouteGuard::requirements) of the route's guards and its ancestors' | [`HelpEntry::requirements`] |
+//!
+//! [`help_screen`](crate::help_screen) renders the entries as a grouped
+//! list.
+//!
+//! ```
+//! use gpui::IntoElement;
+//! use gpui_navigator::help::help_entries;
+//! use gpui_navigator::Route;
+//! use std::sync::Arc;
+//!
+//! let page = |path: &str| Route::view(path, || gpui::div().into_any_element());
+//! let routes = vec![Arc::new(
+//!     page("/settings")
+//!         .meta("title", "Settings")
+//!         .meta("shortcut", "cmd-,")
+//!         .child(page("users/:id").describe("Edit one user")),
+//! )];
+//!
+//! let entries = help_entries(&routes);
+//! assert_eq!(entries[0].title.as_deref(), Some("Settings"));
+//! assert!(entries[0].clickable);
+//! assert_eq!(entries[1].pattern, "/settings/users/:id");
+//! assert!(!entries[1].clickable);
+//! ```
+
+use crate::nested::build_child_path;
+use crate::pattern::{Pattern, Segment};
+use crate::route::Route;
+use std::sync::Arc;
+
+/// Meta key read into [`HelpEntry::title`].
+pub const TITLE_META: &str = "title";
+
+/// Meta key read into [`HelpEntry::shortcut`].
+pub const SHORTCUT_META: &str = "shortcut";
+
+/// One route as listed on a help screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpEntry {
+    /// Full pattern of the top-level route this entry belongs to
+    pub group: String,
+    /// Full pattern from the root (e.g. `/settings/users/:id`)
+    pub pattern: String,
+    /// The route's `title` meta
+    pub title: Option<String>,
+    /// Text set with [`Route::describe`]
+    pub description: Option<String>,
+    /// The route's `shortcut` meta
+    pub shortcut: Option<String>,
+    /// Requirements of the route's guards and its ancestors', first seen first, without duplicates
+    pub requirements: Vec<String>,
+    /// Whether the pattern has no params or wildcards, so it can be linked to as is
+    pub clickable: bool,
+}
+
+/// List every navigable route of `routes` for a help screen.
+///
+/// Entries are grouped by their top-level route, groups sorted by pattern;
+/// within a group, routes keep their declaration order, parents before
+/// children. Index and pathless routes share their parent's URL and are
+/// not listed, nor are named-outlet children.
+#[must_use]
+pub fn help_entries(routes: &[Arc<Route>]) -> Vec<HelpEntry> {
+    let mut entries = Vec::new();
+    for route in routes {
+        collect(route, "/", None, &[], &mut entries);
+    }
+    entries.sort_by(|a, b| a.group.cmp(&b.group));
+    entries
+}
+
+/// Append the entries of `route` and its descendants to `out`.
+fn collect(
+    route: &Route,
+    parent: &str,
+    group: Option<&str>,
+    inherited: &[String],
+    out: &mut Vec<HelpEntry>,
+) {
+    let pattern = build_child_path(parent, &route.config.path).into_owned();
+    let top_level = group.is_none();
+    let group = group.unwrap_or(&pattern).to_string();
+
+    #[cfg_attr(not(feature = "guard"), allow(unused_mut))]
+    let mut requirements = inherited.to_vec();
+    #[cfg(feature = "guard")]
+    extend_unique(
+        &mut requirements,
+        route.guards.iter().flat_map(|guard| guard.requirements()),
+    );
+
+    if top_level || !route.config.pattern().is_empty() {
+        out.push(HelpEntry {
+            group: group.clone(),
+            clickable: is_static(&pattern),
+            title: route.config.meta.get(TITLE_META).cloned(),
+            description: route.description.clone(),
+            shortcut: route.config.meta.get(SHORTCUT_META).cloned(),
+            requirements: requirements.clone(),
+            pattern: pattern.clone(),
+        });
+    }
+
+    #[cfg(feature = "guard")]
+    extend_unique(
+        &mut requirements,
+        route
+            .children_guards
+            .iter()
+            .flat_map(|guard| guard.requirements()),
+    );
+    for child in &route.children {
+        collect(child, &pattern, Some(&group), &requirements, out);
+    }
+}
+
+/// Push the items of `new` that `list` does not hold yet.
+#[cfg(feature = "guard")]
+fn extend_unique(list: &mut Vec<String>, new: impl IntoIterator<Item = String>) {
+    for item in new {
+        if !list.contains(&item) {
+            list.push(item);
+        }
+    }
+}
+
+/// Whether `pattern` has only static segments.
+fn is_static(pattern: &str) -> bool {
+    Pattern::parse(pattern).is_ok_and(|pattern| {
+        pattern
+            .segments()
+            .iter()
+            .all(|segment| matches!(segment, Segment::Static(_) | Segment::Empty))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::page;
+
+    fn fixture() -> Vec<Arc<Route>> {
+        let settings = page("/settings")
+            .meta(TITLE_META, "Settings")
+            .meta(SHORTCUT_META, "cmd-,")
+            .child(page(""))
+            .child(page("profile").describe("Your public profile"))
+            .child(page("users/:id"));
+        #[cfg(feature = "guard")]
+        let settings = settings
+            .guard(crate::AuthGuard::new(|_| true, "/login"))
+            .children_guard(crate::PermissionGuard::new(|_, _| true, "settings.edit"));
+        vec![
+            Arc::new(settings),
+            Arc::new(page("/about").meta(TITLE_META, "About")),
+            Arc::new(page("/files/*path")),
+        ]
+    }
+
+    #[test]
+    fn test_entries_grouped_by_top_level_route() {
+        let entries = help_entries(&fixture());
+        let listed: Vec<(&str, &str)> = entries
+            .iter()
+            .map(|entry| (entry.group.as_str(), entry.pattern.as_str()))
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                ("/about", "/about"),
+                ("/files/*path", "/files/*path"),
+                ("/settings", "/settings"),
+                ("/settings", "/settings/profile"),
+                ("/settings", "/settings/users/:id"),
+            ]
+        );
+        assert_eq!(entries[2].title.as_deref(), Some("Settings"));
+        assert_eq!(entries[2].shortcut.as_deref(), Some("cmd-,"));
+        assert_eq!(
+            entries[3].description.as_deref(),
+            Some("Your public profile")
+        );
+    }
+
+    #[test]
+    fn test_only_static_patterns_are_clickable() {
+        let clickable: Vec<bool> = help_entries(&fixture())
+            .iter()
+            .map(|entry| entry.clickable)
+            .collect();
+        assert_eq!(clickable, [true, false, true, true, false]);
+    }
+
+    #[test]
+    #[cfg(feature = "guard")]
+    fn test_requirements_inherited_and_deduplicated() {
+        let routes = fixture();
+        let entries = help_entries(&routes);
+        assert_eq!(entries[0].requirements, Vec::<String>::new());
+        // The children-only guard spares the layout itself
+        assert_eq!(entries[2].requirements, ["authenticated"]);
+        assert_eq!(entries[4].requirements, ["authenticated", "settings.edit"]);
+
+        let nested = vec![Arc::new(
+            page("/admin")
+                .guard(crate::AuthGuard::new(|_| true, "/login"))
+                .child(page("audit").guard(crate::AuthGuard::new(|_| true, "/login"))),
+        )];
+        assert_eq!(help_entries(&nested)[1].requirements, ["authenticated"]);
+    }
+}
@@ -0,0 +1,433 @@
+//! Shared-element ("hero") transitions between routes.
+//!
+//! Wrap the element a navigation starts from with [`hero_source`] and its
+//! counterpart on the destination with [`hero_target`], under the same id.
+//! Sources report their bounds every frame; a navigation flies a hero when
+//! the bounds of its id were captured just before it, either by a
+//! [`RouterLink::hero`](crate::RouterLink::hero) click or by
+//! [`Navigator::capture_hero`](crate::Navigator::capture_hero):
+//!
+//! ```no_run
+//! use gpui_navigator::hero::{hero_source, hero_target};
+//! use gpui_navigator::{Route, RouterLink};
+//! use gpui::*;
+//!
+//! struct PhotoGrid;
+//!
+//! impl Render for PhotoGrid {
+//!     fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+//!         let thumbnail = hero_source(cx, "photo-7", div().size_16());
+//!         RouterLink::new("/photos/7")
+//!             .hero("photo-7")
+//!             .child(thumbnail)
+//!             .build(cx)
+//!     }
+//! }
+//!
+//! Route::new("/photos/:id", |_, cx, _params| {
+//!     hero_target(cx, "photo-7", div().size_64())
+//! });
+//! ```
+//!
+//! Once the target has been laid out, it leaves a placeholder of its size
+//! and is drawn in an overlay instead, moving from the captured bounds to
+//! its own over [`GlobalRouter::hero_duration`]. The source stays hidden
+//! meanwhile, so neither runs the outlet's enter or exit transition. A
+//! capture whose target never shows up expires after the same duration and
+//! the navigation animates as usual.
+
+use crate::context::GlobalRouter;
+use crate::trace_log;
+use crate::transition::ease_in_out_cubic;
+use gpui::prelude::FluentBuilder as _;
+use gpui::{
+    anchored, canvas, deferred, div, point, px, size, Animation, AnimationExt, AnyElement, App,
+    BorrowAppContext, Bounds, ElementId, IntoElement, ParentElement, Pixels, SharedString, Styled,
+};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Hero flight duration unless set with
+/// [`GlobalRouter::set_hero_duration`].
+pub(crate) const DEFAULT_HERO_DURATION: Duration = Duration::from_millis(300);
+
+/// Where the hero of one id is in its flight.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum HeroPhase {
+    /// Captured, waiting for the target's first layout.
+    Pending,
+    /// Moving from the captured bounds to the target's.
+    Flying {
+        from: Bounds<Pixels>,
+        to: Bounds<Pixels>,
+    },
+}
+
+/// Flight of one hero started by a navigation.
+#[derive(Debug, Clone, Copy)]
+struct HeroFlight {
+    from: Bounds<Pixels>,
+    to: Option<Bounds<Pixels>>,
+    /// When the flight was captured, or took off once paired.
+    since: Instant,
+}
+
+/// Bounds reported by hero elements and the flights pairing them.
+#[derive(Debug, Clone)]
+pub(crate) struct HeroRegistry {
+    /// Last bounds each source reported.
+    sources: HashMap<SharedString, Bounds<Pixels>>,
+    /// Bounds captured for the next navigation.
+    captured: HashMap<SharedString, Bounds<Pixels>>,
+    flights: HashMap<SharedString, HeroFlight>,
+    duration: Duration,
+    /// Bumped per navigation so each flight gets fresh animation state.
+    generation: u64,
+}
+
+impl Default for HeroRegistry {
+    fn default() -> Self {
+        Self {
+            sources: HashMap::new(),
+            captured: HashMap::new(),
+            flights: HashMap::new(),
+            duration: DEFAULT_HERO_DURATION,
+            generation: 0,
+        }
+    }
+}
+
+impl HeroRegistry {
+    pub(crate) const fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub(crate) fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
+    pub(crate) const fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Record where the source `id` was laid out this frame.
+    pub(crate) fn report_source(&mut self, id: SharedString, bounds: Bounds<Pixels>) {
+        self.sources.insert(id, bounds);
+    }
+
+    /// Capture `bounds` as the start of `id`'s flight on the next navigation.
+    pub(crate) fn capture(&mut self, id: SharedString, bounds: Bounds<Pixels>) {
+        trace_log!("Captured hero '{}' at {:?}", id, bounds);
+        self.captured.insert(id, bounds);
+    }
+
+    /// Capture the last reported bounds of the source `id`, if any.
+    pub(crate) fn capture_source(&mut self, id: &SharedString) -> bool {
+        let Some(bounds) = self.sources.get(id).copied() else {
+            trace_log!("Hero source '{}' was never laid out, not captured", id);
+            return false;
+        };
+        self.capture(id.clone(), bounds);
+        true
+    }
+
+    /// Turn the captures into flights for a navigation committed at `now`.
+    ///
+    /// Flights of the previous navigation are dropped, as are source bounds:
+    /// the new page reports its own.
+    pub(crate) fn begin(&mut self, now: Instant) {
+        self.generation = self.generation.wrapping_add(1);
+        self.sources.clear();
+        self.flights = self
+            .captured
+            .drain()
+            .map(|(id, from)| {
+                let flight = HeroFlight {
+                    from,
+                    to: None,
+                    since: now,
+                };
+                (id, flight)
+            })
+            .collect();
+    }
+
+    /// Record where the target `id` was laid out, pairing a pending flight.
+    ///
+    /// Returns `true` if this paired the flight, which then takes off at `now`.
+    pub(crate) fn report_target(
+        &mut self,
+        id: &SharedString,
+        bounds: Bounds<Pixels>,
+        now: Instant,
+    ) -> bool {
+        if self.phase(id, now) != Some(HeroPhase::Pending) {
+            return false;
+        }
+        let Some(flight) = self.flights.get_mut(id) else {
+            return false;
+        };
+        flight.to = Some(bounds);
+        flight.since = now;
+        trace_log!("Hero '{}' paired, flying to {:?}", id, bounds);
+        true
+    }
+
+    /// Phase of `id`'s flight at `now`, `None` once it landed or expired.
+    pub(crate) fn phase(&self, id: &SharedString, now: Instant) -> Option<HeroPhase> {
+        let flight = self.flights.get(id)?;
+        if now.saturating_duration_since(flight.since) >= self.duration {
+            return None;
+        }
+        Some(
+            flight
+                .to
+                .map_or(HeroPhase::Pending, |to| HeroPhase::Flying {
+                    from: flight.from,
+                    to,
+                }),
+        )
+    }
+}
+
+/// Bounds a fraction `t` of the way from `from` to `to`.
+///
+/// `t` is clamped to `0.0..=1.0`.
+pub(crate) fn interpolate_bounds(
+    from: Bounds<Pixels>,
+    to: Bounds<Pixels>,
+    t: f32,
+) -> Bounds<Pixels> {
+    let t = t.clamp(0.0, 1.0);
+    let lerp = |a: Pixels, b: Pixels| a + (b - a) * t;
+    Bounds {
+        origin: point(
+            lerp(from.origin.x, to.origin.x),
+            lerp(from.origin.y, to.origin.y),
+        ),
+        size: size(
+            lerp(from.size.width, to.size.width),
+            lerp(from.size.height, to.size.height),
+        ),
+    }
+}
+
+// ============================================================================
+// Elements
+// ============================================================================
+
+/// Wrap `element` with a canvas reporting its bounds during prepaint.
+fn measured(
+    element: impl IntoElement,
+    report: impl FnOnce(Bounds<Pixels>, &mut gpui::Window, &mut App) + 'static,
+) -> gpui::Div {
+    div().relative().child(element).child(
+        canvas(report, |_, (), _, _| {})
+            .absolute()
+            .top_0()
+            .left_0()
+            .size_full(),
+    )
+}
+
+fn phase(cx: &App, id: &SharedString) -> Option<HeroPhase> {
+    cx.try_global::<GlobalRouter>()?
+        .heroes()
+        .phase(id, Instant::now())
+}
+
+/// Mark `element` as the hero `id` a navigation can start from.
+///
+/// Its bounds are reported every frame, for
+/// [`RouterLink::hero`](crate::RouterLink::hero) to capture. While the hero
+/// of `id` is flying, the element is hidden so it does not run the outlet's
+/// exit transition.
+pub fn hero_source(cx: &App, id: impl Into<SharedString>, element: impl IntoElement) -> AnyElement {
+    let id = id.into();
+    let flying = matches!(phase(cx, &id), Some(HeroPhase::Flying { .. }));
+    let element = div().child(element).when(flying, |this| this.opacity(0.));
+    measured(element, move |bounds, _, cx| {
+        if cx.try_global::<GlobalRouter>().is_some() {
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.heroes_mut().report_source(id, bounds);
+            });
+        }
+    })
+    .into_any_element()
+}
+
+/// Mark `element` as the hero `id` a navigation lands on.
+///
+/// Without a flight for `id` the element renders as is. Otherwise it stays
+/// invisible until its first layout, then flies from the captured bounds to
+/// its own in an overlay, leaving a placeholder of its size in place.
+pub fn hero_target(cx: &App, id: impl Into<SharedString>, element: impl IntoElement) -> AnyElement {
+    let id = id.into();
+    match phase(cx, &id) {
+        None => element.into_any_element(),
+        Some(HeroPhase::Pending) => {
+            let hidden = div().child(element).opacity(0.);
+            measured(hidden, move |bounds, window, cx| {
+                let paired = cx.update_global::<GlobalRouter, _>(|router, _| {
+                    router
+                        .heroes_mut()
+                        .report_target(&id, bounds, Instant::now())
+                });
+                if paired {
+                    window.refresh();
+                }
+            })
+            .into_any_element()
+        }
+        Some(HeroPhase::Flying { from, to }) => {
+            let heroes = cx.global::<GlobalRouter>().heroes();
+            let animation_id = ElementId::Name(format!("hero-{id}-{}", heroes.generation()).into());
+            let ghost = div().absolute().child(element).with_animation(
+                animation_id,
+                Animation::new(heroes.duration()).with_easing(ease_in_out_cubic),
+                move |ghost, delta| {
+                    let bounds = interpolate_bounds(from, to, delta);
+                    ghost
+                        .left(bounds.origin.x)
+                        .top(bounds.origin.y)
+                        .w(bounds.size.width)
+                        .h(bounds.size.height)
+                },
+            );
+            let placeholder = div().w(to.size.width).h(to.size.height);
+            // Keep drawing frames until the flight lands and the target returns
+            measured(placeholder, |_, window, _| window.request_animation_frame())
+                .child(deferred(
+                    anchored().position(point(px(0.), px(0.))).child(ghost),
+                ))
+                .into_any_element()
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::test_support::page;
+    use crate::{init_router, Navigator};
+    use gpui::TestAppContext;
+
+    fn bounds(x: f32, y: f32, w: f32, h: f32) -> Bounds<Pixels> {
+        Bounds {
+            origin: point(px(x), px(y)),
+            size: size(px(w), px(h)),
+        }
+    }
+
+    fn id(id: &'static str) -> SharedString {
+        SharedString::from(id)
+    }
+
+    #[test]
+    fn test_interpolate_bounds() {
+        let from = bounds(0., 10., 40., 40.);
+        let to = bounds(100., 110., 240., 140.);
+        assert_eq!(interpolate_bounds(from, to, 0.), from);
+        assert_eq!(interpolate_bounds(from, to, 1.), to);
+        assert_eq!(
+            interpolate_bounds(from, to, 0.5),
+            bounds(50., 60., 140., 90.)
+        );
+        // Out-of-range progress stays on the segment
+        assert_eq!(interpolate_bounds(from, to, -1.), from);
+        assert_eq!(interpolate_bounds(from, to, 2.), to);
+    }
+
+    #[test]
+    fn test_capture_needs_a_reported_source() {
+        let mut heroes = HeroRegistry::default();
+        assert!(!heroes.capture_source(&id("photo")));
+
+        heroes.report_source(id("photo"), bounds(1., 2., 3., 4.));
+        assert!(heroes.capture_source(&id("photo")));
+
+        let now = Instant::now();
+        heroes.begin(now);
+        assert_eq!(heroes.phase(&id("photo"), now), Some(HeroPhase::Pending));
+        // Sources of the old page are forgotten
+        assert!(!heroes.capture_source(&id("photo")));
+    }
+
+    #[test]
+    fn test_pairing_and_landing() {
+        let mut heroes = HeroRegistry::default();
+        let from = bounds(0., 0., 10., 10.);
+        let to = bounds(50., 50., 100., 100.);
+        let start = Instant::now();
+        heroes.capture(id("photo"), from);
+        heroes.begin(start);
+
+        // Targets of other ids do not pair
+        assert!(!heroes.report_target(&id("other"), to, start));
+
+        let paired = start + Duration::from_millis(100);
+        assert!(heroes.report_target(&id("photo"), to, paired));
+        assert!(!heroes.report_target(&id("photo"), to, paired));
+        assert_eq!(
+            heroes.phase(&id("photo"), paired),
+            Some(HeroPhase::Flying { from, to })
+        );
+
+        // The flight lasts the hero duration from pairing, not from capture
+        let almost = paired + Duration::from_millis(299);
+        assert!(heroes.phase(&id("photo"), almost).is_some());
+        assert_eq!(
+            heroes.phase(&id("photo"), paired + DEFAULT_HERO_DURATION),
+            None
+        );
+    }
+
+    #[test]
+    fn test_unpaired_capture_expires() {
+        let mut heroes = HeroRegistry::default();
+        heroes.set_duration(Duration::from_millis(50));
+        let start = Instant::now();
+        heroes.capture(id("photo"), bounds(0., 0., 10., 10.));
+        heroes.begin(start);
+
+        let late = start + Duration::from_millis(50);
+        assert_eq!(heroes.phase(&id("photo"), late), None);
+        assert!(!heroes.report_target(&id("photo"), bounds(0., 0., 1., 1.), late));
+    }
+
+    #[test]
+    fn test_each_navigation_starts_over() {
+        let mut heroes = HeroRegistry::default();
+        let now = Instant::now();
+        heroes.capture(id("photo"), bounds(0., 0., 10., 10.));
+        heroes.begin(now);
+        let first = heroes.generation();
+
+        // Nothing captured: the previous flight is dropped
+        heroes.begin(now);
+        assert_eq!(heroes.phase(&id("photo"), now), None);
+        assert_ne!(heroes.generation(), first);
+    }
+
+    #[gpui::test]
+    fn test_navigation_turns_captures_into_flights(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/photos"));
+                router.add_route(page("/photos/:id"));
+            });
+        });
+        let pending = |cx: &mut TestAppContext| {
+            cx.read(|cx| phase(cx, &id("photo-7")) == Some(HeroPhase::Pending))
+        };
+
+        cx.update(|cx| Navigator::capture_hero(cx, "photo-7", bounds(0., 0., 64., 64.)));
+        assert!(!pending(cx));
+        cx.update(|cx| Navigator::push(cx, "/photos/7"));
+        assert!(pending(cx));
+
+        cx.update(|cx| Navigator::push(cx, "/photos"));
+        assert!(!pending(cx));
+    }
+}
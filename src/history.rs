@@ -9,9 +9,14 @@
 //! - **Truncation** — forward entries are discarded on push (browser semantics).
 //! - **Size limit** — configurable maximum via [`History::with_max_size`].
 //! - **State persistence** — attach arbitrary key-value data (scroll position,
-//!   form state, etc.) to each entry.
+//!   form state, etc.) to each entry; with the `serde` feature, typed values
+//!   via `HistoryState::set_value` / `get_value`.
 //! - **Serialization** — [`entries`](History::entries) /
 //!   [`restore`](History::restore) for save/load workflows.
+//! - **Payloads** — one-shot typed values handed to the destination of a
+//!   navigation (see [`HistoryEntry::take_payload`]).
+//! - **Scroll offsets** — per-entry offsets behind [`ScrollRestoration`]
+//!   (see [`HistoryEntry::set_scroll_offset`]).
 //!
 //! # Examples
 //!
@@ -30,6 +35,9 @@
 //! ```
 
 use crate::{NavigationDirection, RouteChangeEvent};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
 
 /// Navigation history entry
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -38,23 +46,101 @@ pub struct HistoryEntry {
     pub path: String,
     /// Optional state data associated with this entry
     pub state: Option<HistoryState>,
+    /// Typed one-shot payloads, at most one per type
+    payloads: Payloads,
 }
 
 impl HistoryEntry {
     /// Create a new history entry
     #[must_use]
-    pub const fn new(path: String) -> Self {
-        Self { path, state: None }
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            state: None,
+            payloads: Payloads::default(),
+        }
     }
 
     /// Create with state
     #[must_use]
-    pub const fn with_state(path: String, state: HistoryState) -> Self {
+    pub fn with_state(path: String, state: HistoryState) -> Self {
         Self {
             path,
             state: Some(state),
+            payloads: Payloads::default(),
         }
     }
+
+    /// Attach a payload, replacing any earlier payload of the same type.
+    ///
+    /// Payloads live on the entry until [taken](Self::take_payload) or until
+    /// the entry itself is dropped (replaced, truncated by a push, evicted by
+    /// the size limit). They are not carried over by `clone()`, and are
+    /// ignored by `==`.
+    pub fn set_payload<T: Any + Send + Sync>(&mut self, payload: T) {
+        self.payloads.0.insert(TypeId::of::<T>(), Box::new(payload));
+    }
+
+    /// Remove and return the payload of type `T`, if any.
+    pub fn take_payload<T: Any + Send + Sync>(&mut self) -> Option<T> {
+        let payload = self.payloads.0.remove(&TypeId::of::<T>())?;
+        payload.downcast().ok().map(|payload| *payload)
+    }
+
+    /// Return `true` if a payload of type `T` is attached.
+    #[must_use]
+    pub fn has_payload<T: Any + Send + Sync>(&self) -> bool {
+        self.payloads.0.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Store the scroll offset of this entry in its [`HistoryState`].
+    ///
+    /// Kept under the `scrollX` / `scrollY` keys, so the offset is saved and
+    /// restored with the rest of the entry's state.
+    pub fn set_scroll_offset(&mut self, x: f32, y: f32) {
+        let state = self.state.get_or_insert_with(HistoryState::new);
+        state.set(SCROLL_X.to_string(), x.to_string());
+        state.set(SCROLL_Y.to_string(), y.to_string());
+    }
+
+    /// Return the scroll offset stored by [`set_scroll_offset`](Self::set_scroll_offset).
+    #[must_use]
+    pub fn scroll_offset(&self) -> Option<(f32, f32)> {
+        let state = self.state.as_ref()?;
+        let x = state.get(SCROLL_X)?.parse().ok()?;
+        let y = state.get(SCROLL_Y)?.parse().ok()?;
+        Some((x, y))
+    }
+}
+
+/// [`HistoryState`] key of the stored horizontal scroll offset.
+const SCROLL_X: &str = "scrollX";
+/// [`HistoryState`] key of the stored vertical scroll offset.
+const SCROLL_Y: &str = "scrollY";
+
+/// Type-map of entry payloads. Payloads are owned by one entry only, so
+/// clones start empty and equality ignores them.
+#[derive(Default)]
+struct Payloads(HashMap<TypeId, Box<dyn Any + Send + Sync>>);
+
+impl Clone for Payloads {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl PartialEq for Payloads {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Payloads {}
+
+impl fmt::Debug for Payloads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Payloads({})", self.0.len())
+    }
 }
 
 /// State data for history entries
@@ -86,6 +172,33 @@ impl HistoryState {
     pub fn get(&self, key: &str) -> Option<&String> {
         self.data.get(key)
     }
+
+    /// Set a value of any serializable type, stored as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of a value that cannot be serialized; the state is
+    /// left unchanged.
+    #[cfg(feature = "serde")]
+    pub fn set_value<T: serde::Serialize + ?Sized>(
+        &mut self,
+        key: String,
+        value: &T,
+    ) -> Result<(), serde_json::Error> {
+        let json = serde_json::to_string(value)?;
+        self.data.insert(key, json);
+        Ok(())
+    }
+
+    /// Get a value stored with [`set_value`](Self::set_value).
+    ///
+    /// Returns `None` if the key is absent or its value does not
+    /// deserialize into `T`.
+    #[cfg(feature = "serde")]
+    #[must_use]
+    pub fn get_value<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<T> {
+        serde_json::from_str(self.data.get(key)?).ok()
+    }
 }
 
 impl Default for HistoryState {
@@ -94,6 +207,40 @@ impl Default for HistoryState {
     }
 }
 
+// ============================================================================
+// Scroll restoration
+// ============================================================================
+
+/// Whether the router computes a [`ScrollIntent`] for each navigation.
+///
+/// Set via
+/// [`GlobalRouter::set_scroll_restoration`](crate::context::GlobalRouter::set_scroll_restoration).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ScrollRestoration {
+    /// Back / forward restore the entry's stored offset; push and replace
+    /// scroll to the top.
+    Auto,
+    /// No intent is computed; the app scrolls on its own (default).
+    #[default]
+    Manual,
+}
+
+/// What a scrollable container should do after a navigation.
+///
+/// The router cannot scroll GPUI elements itself. The container reads the
+/// intent from
+/// [`GlobalRouter::scroll_intent`](crate::context::GlobalRouter::scroll_intent),
+/// typically from a `cx.observe_global::<GlobalRouter>` observer, and applies
+/// it to its scroll handle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollIntent {
+    /// Scroll to the offset stored on the entry, as `(x, y)`.
+    Restore(f32, f32),
+    /// Scroll to the top.
+    Top,
+}
+
 /// Navigation history stack with configurable size limit.
 ///
 /// Follows browser-like semantics: pushing a new entry truncates any forward
@@ -141,6 +288,11 @@ impl History {
         &self.entries[self.current]
     }
 
+    /// Return a mutable reference to the current [`HistoryEntry`].
+    pub fn current_entry_mut(&mut self) -> &mut HistoryEntry {
+        &mut self.entries[self.current]
+    }
+
     /// Push a new path onto history
     ///
     /// This truncates any forward history and adds the new entry
@@ -440,6 +592,37 @@ mod tests {
         assert_eq!(saved_state.get("scrollY"), Some(&"100".to_string()));
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_history_state_typed_values() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Draft {
+            title: String,
+            tags: Vec<String>,
+            words: u32,
+        }
+
+        let draft = Draft {
+            title: "Release notes".to_string(),
+            tags: vec!["docs".to_string()],
+            words: 250,
+        };
+        let mut state = HistoryState::new();
+        state.set_value("draft".to_string(), &draft).unwrap();
+        state.set("note".to_string(), "plain".to_string());
+
+        let mut history = History::new("/".to_string());
+        history.push_with_state("/editor".to_string(), state);
+        let state = history.current_entry().state.as_ref().unwrap();
+
+        assert_eq!(state.get_value::<Draft>("draft"), Some(draft));
+        // Raw strings stay readable, and mismatched types read as absent
+        assert!(state.get("draft").unwrap().contains("Release notes"));
+        assert_eq!(state.get("note"), Some(&"plain".to_string()));
+        assert_eq!(state.get_value::<u32>("draft"), None);
+        assert_eq!(state.get_value::<Draft>("missing"), None);
+    }
+
     #[test]
     fn test_history_max_size() {
         let mut history = History::with_max_size("/".to_string(), 3);
@@ -500,4 +683,44 @@ mod tests {
         assert!(!history.can_go_back());
         assert!(!history.can_go_forward());
     }
+
+    #[test]
+    fn test_entry_payload_is_taken_once() {
+        #[derive(Debug, PartialEq)]
+        struct Draft(&'static str);
+
+        let mut history = History::new("/".to_string());
+        history.push("/compose".to_string());
+        history.current_entry_mut().set_payload(Draft("hello"));
+        history.current_entry_mut().set_payload(7_u32);
+
+        assert!(history.current_entry().has_payload::<Draft>());
+        assert!(history
+            .current_entry()
+            .clone()
+            .take_payload::<Draft>()
+            .is_none());
+        assert_eq!(
+            history.current_entry_mut().take_payload(),
+            Some(Draft("hello"))
+        );
+        assert_eq!(history.current_entry_mut().take_payload::<Draft>(), None);
+        assert_eq!(history.current_entry_mut().take_payload(), Some(7_u32));
+
+        // Dropped with the entry
+        history.current_entry_mut().set_payload(Draft("lost"));
+        history.replace("/compose".to_string());
+        assert!(!history.current_entry().has_payload::<Draft>());
+    }
+
+    #[test]
+    fn test_entry_scroll_offset_lives_in_state() {
+        let mut entry = HistoryEntry::new("/feed".to_string());
+        assert_eq!(entry.scroll_offset(), None);
+
+        entry.set_scroll_offset(0.0, 240.5);
+        assert_eq!(entry.scroll_offset(), Some((0.0, 240.5)));
+        let state = entry.state.as_ref().unwrap();
+        assert_eq!(state.get("scrollY"), Some(&"240.5".to_string()));
+    }
 }
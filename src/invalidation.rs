@@ -0,0 +1,365 @@
+//! Reacting to the current route disappearing from the route table.
+//!
+//! Routes can change at runtime: a plugin unloads with
+//! [`GlobalRouter::remove_route`](crate::GlobalRouter::remove_route), a
+//! feature flag turns one off with
+//! [`set_route_enabled`](crate::GlobalRouter::set_route_enabled), or setup
+//! code registers a route again and
+//! [`add_route`](crate::GlobalRouter::add_route) replaces it with a
+//! definition lacking some children. When the path on screen then resolves
+//! to fewer routes, outlets would silently render blanks. Instead the router
+//! reports a [`CurrentRouteInvalidated`] to the hook set with
+//! [`set_on_route_invalidated`](crate::GlobalRouter::set_on_route_invalidated)
+//! and to the middleware of the routes the path resolved to (see
+//! [`RouteMiddleware::on_route_invalidated`](crate::RouteMiddleware::on_route_invalidated)),
+//! keeps it readable from
+//! [`current_route_invalidated`](crate::GlobalRouter::current_route_invalidated)
+//! until the next navigation, and moves as its [`InvalidationPolicy`] says.
+//!
+//! Route table changes happen without an app context, so the rest waits for
+//! [`GlobalRouter::settle`](crate::GlobalRouter::settle), which the
+//! `Navigator`, `router_view` and `update_router` run: middleware are told,
+//! unsaved scopes of the routes that left are cleared, and refetch tasks,
+//! timers and loaders follow the new match stack.
+//!
+//! ```
+//! use gpui::IntoElement;
+//! use gpui_navigator::invalidation::InvalidationPolicy;
+//! use gpui_navigator::{GlobalRouter, Route};
+//!
+//! let page = |path: &str| Route::view(path, || gpui::div().into_any_element());
+//! let mut router = GlobalRouter::new();
+//! router.set_invalidation_policy(InvalidationPolicy::NavigateTo("/".into()));
+//! router.add_route(page("/"));
+//! router.add_route(page("/plugin").child(page("panel")));
+//! // ... the user navigates to /plugin/panel, then the plugin unloads:
+//! router.remove_route("/plugin");
+//! ```
+//!
+//! Only route table changes are reported, never navigations, and only when
+//! a route the current path resolved to is gone. Routes added elsewhere in
+//! the table do not count.
+
+use crate::resolve::{MatchEntry, MatchStack};
+use std::sync::Arc;
+
+/// Why the current route stopped resolving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InvalidationReason {
+    /// A top-level route was removed with
+    /// [`remove_route`](crate::GlobalRouter::remove_route).
+    RouteRemoved,
+    /// A top-level route was replaced by
+    /// [`add_route`](crate::GlobalRouter::add_route) with a definition the
+    /// current path no longer fully resolves to.
+    RouteReplaced,
+    /// A top-level route was switched off with
+    /// [`set_route_enabled`](crate::GlobalRouter::set_route_enabled).
+    Disabled,
+}
+
+/// The current path lost some of the routes it resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrentRouteInvalidated {
+    /// The path on screen when its routes went away
+    pub path: String,
+    /// Depth of the shallowest match stack level that is gone or now
+    /// resolves to another route. A layout whose own URL still resolves to
+    /// it is not counted, even when the full path no longer reaches it
+    pub lost_at_depth: usize,
+    /// The route table change that caused it
+    pub reason: InvalidationReason,
+}
+
+/// Where the router goes when the current route is invalidated.
+///
+/// The move goes straight to the history: guards and navigation middleware
+/// do not run, as route table changes happen without an app context. Its
+/// tasks and loaders start when the router [settles](crate::GlobalRouter::settle).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum InvalidationPolicy {
+    /// Stay on the path; the app reacts to the event itself.
+    #[default]
+    Stay,
+    /// Replace the current history entry with this path.
+    NavigateTo(String),
+    /// Go back in history, or stay if there is nothing to go back to.
+    Back,
+}
+
+/// Hook invoked when the current route is invalidated.
+pub(crate) type RouteInvalidatedFn = Arc<dyn Fn(&CurrentRouteInvalidated) + Send + Sync>;
+
+/// The shallowest depth at which `current` no longer resolves like
+/// `previous`, both resolved for the same path.
+pub(crate) fn lost_depth(previous: &MatchStack, current: &MatchStack) -> Option<usize> {
+    previous.entries().iter().position(|entry| {
+        current.at_depth(entry.depth).map_or(true, |now| {
+            now.accumulated_pattern() != entry.accumulated_pattern()
+        })
+    })
+}
+
+/// Like [`lost_depth`], but a level `current` no longer reaches only counts
+/// as lost when its own URL, passed to `resolve`, stops resolving to it: a
+/// layout still routed above a lost child is not gone.
+pub(crate) fn gone_depth(
+    previous: &MatchStack,
+    current: &MatchStack,
+    resolve: impl Fn(&str) -> MatchStack,
+) -> Option<usize> {
+    let lost = lost_depth(previous, current)?;
+    let still_routed = |entry: &MatchEntry| {
+        resolve(entry.accumulated_url())
+            .at_depth(entry.depth)
+            .is_some_and(|now| now.accumulated_pattern() == entry.accumulated_pattern())
+    };
+    Some(
+        previous.entries()[lost..]
+            .iter()
+            .find(|entry| !still_routed(entry))
+            .map_or(lost, |entry| entry.depth),
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::lifecycle::DirtyStateRegistry;
+    use crate::resolve::resolve_match_stack;
+    use crate::test_support::page;
+    use crate::{init_router, GlobalRouter, Navigator, UseRouter};
+    use gpui::{BorrowAppContext, IntoElement, TestAppContext};
+    use std::sync::Mutex;
+
+    /// Router on `/settings/labs/dark-mode`, after `/`, recording invalidations.
+    fn init_labs_router(
+        cx: &mut TestAppContext,
+        policy: InvalidationPolicy,
+    ) -> Arc<Mutex<Vec<CurrentRouteInvalidated>>> {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_on_route_invalidated(move |event| {
+                    sink.lock().unwrap().push(event.clone());
+                });
+                router.set_invalidation_policy(policy);
+                router.add_route(page("/"));
+                router.add_route(page("/plugin"));
+                router.add_route(page("/settings").child(page("labs").child(page(":flag"))));
+            });
+        });
+        cx.update(|cx| Navigator::push(cx, "/settings/labs/dark-mode"));
+        seen
+    }
+
+    #[test]
+    fn test_lost_depth_finds_first_changed_level() {
+        let full = vec![Arc::new(
+            page("/settings").child(page("labs").child(page(":flag"))),
+        )];
+        let trimmed = vec![Arc::new(page("/settings").child(page("labs")))];
+        let path = "/settings/labs/dark-mode";
+
+        let previous = resolve_match_stack(&full, path);
+        assert_eq!(
+            lost_depth(&previous, &resolve_match_stack(&full, path)),
+            None
+        );
+        assert_eq!(
+            lost_depth(&previous, &resolve_match_stack(&trimmed, path)),
+            Some(0)
+        );
+        assert_eq!(lost_depth(&previous, &MatchStack::new()), Some(0));
+
+        // A scoped not-found handler keeps the levels above it
+        let handled = vec![Arc::new(
+            page("/settings")
+                .child(page("labs").not_found(|_, _, _| gpui::div().into_any_element())),
+        )];
+        assert_eq!(
+            lost_depth(&previous, &resolve_match_stack(&handled, path)),
+            Some(2)
+        );
+
+        // Only what the path resolved to before counts
+        let deeper = resolve_match_stack(&full, "/settings/labs");
+        assert_eq!(
+            lost_depth(&deeper, &resolve_match_stack(&trimmed, "/settings/labs")),
+            None
+        );
+    }
+
+    #[gpui::test]
+    fn test_route_removal_invalidates_current_route(cx: &mut TestAppContext) {
+        let seen = init_labs_router(cx, InvalidationPolicy::Stay);
+
+        // Unrelated changes and navigations report nothing
+        cx.update(|cx| {
+            cx.update_router(|router, _| {
+                assert!(router.remove_route("/plugin"));
+                assert!(!router.remove_route("/missing"));
+                router.add_route(page("/about"));
+            });
+        });
+        cx.update(|cx| Navigator::push(cx, "/about"));
+        cx.update(Navigator::pop);
+        assert!(seen.lock().unwrap().is_empty());
+
+        // The leaf goes away; a scoped not-found keeps the layouts above it
+        cx.update(|cx| {
+            cx.update_router(|router, _| {
+                router.add_route(
+                    page("/settings")
+                        .child(page("labs").not_found(|_, _, _| gpui::div().into_any_element())),
+                );
+            });
+        });
+        let expected = CurrentRouteInvalidated {
+            path: "/settings/labs/dark-mode".to_string(),
+            lost_at_depth: 2,
+            reason: InvalidationReason::RouteReplaced,
+        };
+        assert_eq!(*seen.lock().unwrap(), std::slice::from_ref(&expected));
+        cx.read(|cx| {
+            let router = cx.global::<GlobalRouter>();
+            assert_eq!(router.current_route_invalidated(), Some(&expected));
+            assert_eq!(router.current_path(), "/settings/labs/dark-mode");
+        });
+
+        cx.update(|cx| cx.update_router(|router, _| router.remove_route("settings")));
+        assert_eq!(seen.lock().unwrap()[1].lost_at_depth, 0);
+        assert_eq!(
+            seen.lock().unwrap()[1].reason,
+            InvalidationReason::RouteRemoved
+        );
+        assert!(cx.read(|cx| cx.router().match_stack().is_empty()));
+
+        // The next navigation clears the flag
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert!(cx.read(|cx| cx.router().current_route_invalidated().is_none()));
+    }
+
+    #[gpui::test]
+    fn test_invalidation_policy_navigate_to(cx: &mut TestAppContext) {
+        let seen = init_labs_router(cx, InvalidationPolicy::NavigateTo("/plugin".into()));
+        cx.update(|cx| cx.update_router(|router, _| router.remove_route("/settings")));
+
+        assert_eq!(seen.lock().unwrap().len(), 1);
+        assert_eq!(cx.read(Navigator::current_path), "/plugin");
+        assert!(!cx.read(|cx| cx.router().match_stack().is_empty()));
+        // Replaced, not pushed
+        cx.update(Navigator::pop);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    #[gpui::test]
+    fn test_invalidation_policy_back(cx: &mut TestAppContext) {
+        let seen = init_labs_router(cx, InvalidationPolicy::Back);
+        cx.update(|cx| cx.update_router(|router, _| router.remove_route("/settings")));
+
+        assert_eq!(seen.lock().unwrap().len(), 1);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+        // Still readable after moving away
+        assert!(cx.read(|cx| cx.router().current_route_invalidated().is_some()));
+    }
+
+    #[gpui::test]
+    fn test_disabling_route_invalidates_current_route(cx: &mut TestAppContext) {
+        let seen = init_labs_router(cx, InvalidationPolicy::Stay);
+
+        // Unrelated or unknown routes report nothing
+        cx.update(|cx| {
+            cx.update_router(|router, _| {
+                assert!(router.set_route_enabled("/plugin", false));
+                assert!(!router.set_route_enabled("/missing", false));
+                assert!(!router.is_route_enabled("/plugin"));
+            });
+        });
+        assert!(seen.lock().unwrap().is_empty());
+
+        cx.update(|cx| cx.update_router(|router, _| router.set_route_enabled("settings/", false)));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [CurrentRouteInvalidated {
+                path: "/settings/labs/dark-mode".to_string(),
+                lost_at_depth: 0,
+                reason: InvalidationReason::Disabled,
+            }]
+        );
+        assert!(cx.read(|cx| cx.router().match_stack().is_empty()));
+
+        // Enabled again, the path resolves as before and nothing is reported
+        cx.update(|cx| cx.update_router(|router, _| router.set_route_enabled("/settings", true)));
+        assert_eq!(seen.lock().unwrap().len(), 1);
+        assert_eq!(cx.read(|cx| cx.router().match_stack().len()), 3);
+        assert!(cx.read(|cx| cx.router().is_route_enabled("/settings")));
+    }
+
+    #[gpui::test]
+    fn test_policy_move_settles_like_a_navigation(cx: &mut TestAppContext) {
+        struct Home;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.set_invalidation_policy(InvalidationPolicy::NavigateTo("/home".into()));
+                router.add_route(page("/home").loader(|_, _| Ok(Home)));
+                router.add_route(page("/editor").protect_unsaved("draft"));
+            });
+        });
+        cx.update(|cx| Navigator::push(cx, "/editor"));
+        cx.update(|cx| DirtyStateRegistry::mark_dirty(cx, "draft"));
+
+        // Without an app context the move waits to be settled
+        cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| router.remove_route("/editor"));
+        });
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+        assert!(cx.read(|cx| cx.router().loader_data::<Home>().is_none()));
+
+        cx.update(|cx| cx.update_router(|router, cx| router.settle(cx)));
+        assert!(cx.read(|cx| cx.router().loader_data::<Home>().is_some()));
+        assert!(!cx.read(|cx| DirtyStateRegistry::is_dirty(cx, Some("draft"))));
+    }
+
+    #[cfg(feature = "middleware")]
+    #[gpui::test]
+    fn test_middleware_hear_of_invalidation(cx: &mut TestAppContext) {
+        use crate::{NavigationRequest, RouteMiddleware};
+        use gpui::App;
+
+        struct Watch(Arc<Mutex<Vec<(&'static str, usize)>>>, &'static str);
+
+        impl RouteMiddleware for Watch {
+            fn before_navigation(&self, _cx: &App, _request: &NavigationRequest) {}
+
+            fn after_navigation(&self, _cx: &App, _request: &NavigationRequest) {}
+
+            fn on_route_invalidated(&self, _cx: &App, event: &CurrentRouteInvalidated) {
+                self.0.lock().unwrap().push((self.1, event.lost_at_depth));
+            }
+        }
+
+        let told = Arc::new(Mutex::new(Vec::new()));
+        let (labs, about) = (Arc::clone(&told), Arc::clone(&told));
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/about").middleware(Watch(about, "about")));
+                router.add_route(
+                    page("/settings").child(page("labs").middleware(Watch(labs, "labs"))),
+                );
+            });
+        });
+        cx.update(|cx| Navigator::push(cx, "/settings/labs"));
+
+        cx.update(|cx| {
+            cx.update_router(|router, _| {
+                router.add_route(page("/settings").child(page("general")));
+            });
+        });
+        assert_eq!(*told.lock().unwrap(), [("labs", 1)]);
+    }
+}
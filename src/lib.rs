@@ -104,10 +104,14 @@
 //! |--------------|---------|-------------|
 //! | `guard`      | yes     | Route guards (`AuthGuard`, `RoleGuard`, `PermissionGuard`) |
 //! | `middleware`  | yes     | Before/after navigation hooks |
-//! | `transition` | yes     | Fade, slide animations on route change |
+//! | `transition` | yes     | Fade, slide animations on route change, `GlobalRouter::on_transition` hooks |
 //! | `cache`      | yes     | LRU cache for route resolution (depends on `lru`) |
+//! | `explain`    | yes     | Dry-run navigation explanations (`GlobalRouter::explain_navigation`) |
 //! | `log`        | yes     | Logging via the `log` crate |
 //! | `tracing`    | no      | Logging via `tracing` (mutually exclusive with `log`) |
+//! | `scaffold`   | no      | Ready-made app shell (`NavScaffold`) with sidebar and debug panel |
+//! | `serde`      | no      | Export and import of route preferences (`RoutePrefs::export`), typed `HistoryState` values |
+//! | `test-util`  | no      | Test helpers such as `MatchStack::assert_paths`, `GlobalRouter::set_match_stack_for_test` and the `render_log` of what outlets render |
 
 #![doc(html_root_url = "https://docs.rs/gpui_navigator/0.1.4")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
@@ -117,8 +121,7 @@
 // Logging abstraction
 pub mod logging;
 
-// Cache (optional)
-#[cfg(feature = "cache")]
+// Caches (route resolution cache optional)
 pub mod cache;
 
 // Core routing modules
@@ -130,6 +133,10 @@ pub mod state;
 
 // Error handling
 pub mod error;
+#[cfg(feature = "explain")]
+pub mod explain;
+pub mod extensions;
+pub mod help;
 
 // Route lifecycle
 pub mod lifecycle;
@@ -140,45 +147,123 @@ pub mod guards;
 
 // Transitions
 #[cfg(feature = "transition")]
+pub mod hero;
+#[cfg(feature = "transition")]
 pub mod transition;
 
 // Other modules
+pub mod boundary;
+pub mod invalidation;
+pub mod loader;
 pub mod nested;
+pub mod new_window;
+pub mod notifications;
+pub mod outlet_policy;
 pub mod params;
+pub mod pattern;
+pub mod preview;
+pub mod progress;
+pub mod query;
+pub mod refetch;
+#[cfg(any(test, feature = "test-util"))]
+pub mod render_log;
 pub mod resolve;
+pub mod route_env;
+pub mod route_prefs;
+pub mod route_snapshot;
+pub mod route_state;
+#[cfg(feature = "scaffold")]
+pub mod scaffold;
+pub mod slots;
+pub mod timers;
+pub mod warming;
 pub mod widgets;
 
 // Context module (router context integration)
 mod context;
 
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod test_support;
+
 // Re-export main types for convenient access
+pub use boundary::{ErrorBoundaryFn, RetryHandle};
+pub use cache::{CachePolicy, CacheStats, ComponentCacheEntryInfo};
 #[cfg(feature = "cache")]
-pub use cache::{CacheStats, RouteCache, RouteId};
+pub use cache::{RouteCache, RouteId};
+#[cfg(feature = "guard")]
+pub use context::PendingChallenge;
 pub use context::{
-    current_path, init_router, navigate, GlobalRouter, NavigationRequest, Navigator,
-    NavigatorHandle, UseRouter,
+    can_navigate, current_path, init_router, navigate, BatchOp, CanonicalTarget, GlobalRouter,
+    NavigationProbe, NavigationRequest, Navigator, NavigatorHandle, RouteInfo, RouteRegisteredFn,
+    UseRouter,
 };
-pub use error::{ErrorHandler, ErrorHandlers, NavigationError, NavigationResult, NotFoundHandler};
+pub use error::{
+    AlreadyRegisteredError, BatchNavigationError, ErrorHandler, ErrorHandlers, HistoryError,
+    NavigationError, NavigationResult, NotFoundBehavior, NotFoundHandler, ReservedOutletError,
+    RouteError, RouteTreeError, UrlForError,
+};
+#[cfg(feature = "explain")]
+pub use explain::{
+    GuardDecision, LifecycleDecision, LifecycleHook, NavigationExplanation, RedirectCause,
+    RedirectHop,
+};
+pub use extensions::Extensions;
 #[cfg(feature = "guard")]
 pub use guards::{
-    guard_fn, AuthGuard, GuardBuilder, Guards, NotGuard, PermissionGuard, RoleGuard, RouteGuard,
+    guard_fn, guard_redirect_on_err, guard_try, AuthGuard, GuardBuilder, GuardOrderEntry, Guards,
+    NotGuard, PermissionGuard, RoleGuard, RouteGuard,
+};
+pub use help::HelpEntry;
+#[cfg(feature = "transition")]
+pub use hero::{hero_source, hero_target};
+pub use history::{History, HistoryEntry, HistoryState, ScrollIntent, ScrollRestoration};
+pub use invalidation::{CurrentRouteInvalidated, InvalidationPolicy, InvalidationReason};
+pub use lifecycle::{
+    DirtyStateRegistry, NavigationAction, RouteLifecycle, UnsavedChangesLifecycle,
 };
-pub use history::{History, HistoryEntry, HistoryState};
-pub use lifecycle::{NavigationAction, RouteLifecycle};
+pub use loader::LoaderResult;
+pub use logging::NavigationId;
 #[cfg(feature = "middleware")]
-pub use middleware::{middleware_fn, RouteMiddleware};
+pub use middleware::{middleware_fn, MiddlewareOrdering, RouteMiddleware};
 pub use nested::{build_child_path, extract_param_name, normalize_path, resolve_child_route};
-pub use params::{QueryParams, RouteParams};
-pub use resolve::{resolve_match_stack, MatchEntry, MatchStack};
+pub use new_window::{Modifier, RouterWindow};
+pub use notifications::{NotificationConfig, NotificationKind, RouterNotification};
+pub use outlet_policy::OwnerTag;
+pub use params::{
+    ParamTypeError, QueryParams, RouteParamType, RouteParams, RouteParamsBuilder, UnknownParam,
+};
+pub use pattern::{path_matches, MissingParam, Pattern, PatternError};
+pub use preview::{HoverPhase, HoverPreview, PreviewConstraints};
+pub use progress::{NavigationProgress, NavigationStage, ProgressHook};
+pub use query::{
+    ExtractedQuery, QueryError, QueryErrorReason, QueryFieldBuilder, QuerySchema, QuerySpec,
+    QueryType, QueryValue, QueryViolation,
+};
+pub use refetch::{RefetchFn, RouterFocus};
+pub use resolve::{check_route_tree, resolve_match_stack, MatchEntry, MatchStack, UNMATCHED_PARAM};
 pub use route::{
     validate_route_path, BuilderFn, IntoRoute, NamedRoute, NamedRouteRegistry, PageRoute, Route,
-    RouteConfig, RouteDescriptor,
+    RouteConfig, RouteDescriptor, RouteFlag, RouteFlags,
 };
-pub use state::RouterState;
+pub use route_env::{use_route_env, RouteEnv};
+pub use route_prefs::{set_route_pref, use_route_pref, RoutePref, RoutePrefs};
+pub use route_snapshot::{RouteSnapshot, RouteSnapshotHandle};
+pub use route_state::{use_route_state, use_route_state_shared, RouteStateHandle};
+#[cfg(feature = "scaffold")]
+pub use scaffold::{DebugSnapshot, NavItem, NavScaffold, NavShell, SidebarModel};
+pub use slots::{render_slot, render_slots};
+pub use state::{HistoryMutation, RouterSnapshot, RouterState};
+pub use timers::{track_activity, IdleTarget};
 #[cfg(feature = "transition")]
-pub use transition::{SlideDirection, Transition, TransitionConfig};
+pub use transition::{
+    NavigationSource, SlideDirection, Transition, TransitionConfig, TransitionEvent,
+    TransitionHookId, TransitionSources,
+};
+pub use warming::WarmingStatus;
 pub use widgets::{
-    render_router_outlet, router_link, router_outlet, router_outlet_named, router_view,
+    help_screen, navigation_progress_bar, render_router_outlet, router_link,
+    router_notification_banner, router_outlet, router_outlet_at, router_outlet_named, router_view,
     DefaultPages, RouterLink, RouterOutlet, RouterView,
 };
 
@@ -200,8 +285,10 @@ use std::collections::HashMap;
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RouteMatch {
-    /// The matched path
+    /// The matched path (the concrete URL, e.g. `/users/123`)
     pub path: String,
+    /// The route pattern that matched (e.g. `/users/:id`), empty if unknown
+    pub pattern: String,
     /// Extracted route parameters (e.g., `:id` -> "123")
     pub params: HashMap<String, String>,
     /// Parsed query string parameters
@@ -214,11 +301,19 @@ impl RouteMatch {
     pub fn new(path: String) -> Self {
         Self {
             path,
+            pattern: String::new(),
             params: HashMap::new(),
             query: HashMap::new(),
         }
     }
 
+    /// Set the route pattern that produced this match.
+    #[must_use]
+    pub fn with_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = pattern.into();
+        self
+    }
+
     /// Add a route parameter to the match.
     #[must_use]
     pub fn with_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
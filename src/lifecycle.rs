@@ -1,6 +1,6 @@
 //! Route lifecycle hooks and navigation action types.
 //!
-//! This module defines two key abstractions:
+//! This module defines the following abstractions:
 //!
 //! - [`NavigationAction`] — the unified result type returned by guards, lifecycle
 //!   hooks, and middleware. It describes whether navigation should continue, be
@@ -8,21 +8,30 @@
 //! - [`RouteLifecycle`] — a trait for running code at key points in the navigation
 //!   process: entering a route, exiting a route, and checking whether the user
 //!   can leave (e.g. unsaved changes prompt).
+//! - [`DirtyStateRegistry`] and [`UnsavedChangesLifecycle`] — a ready-made
+//!   "unsaved changes" guard built on `can_deactivate`
+//!   (see [`Route::protect_unsaved`](crate::Route::protect_unsaved)).
 //!
 //! # Navigation pipeline
 //!
 //! When a navigation request is made, the router executes steps in this order:
 //!
 //! 1. **Guards** — decide if navigation is allowed (see [`guards`](crate::guards))
-//! 2. **`can_deactivate`** — current route's lifecycle check
+//! 2. **`can_deactivate`** — lifecycle check of every level being left
 //! 3. **Middleware `before`** — cross-cutting pre-navigation logic
-//! 4. **`on_exit`** — current route's cleanup
+//! 4. **`on_exit`** — cleanup of the levels being left
 //! 5. **Navigation** — the route change itself
 //! 6. **`on_enter`** — new route's setup
 //! 7. **Middleware `after`** — cross-cutting post-navigation logic
+//!
+//! A navigation leaves the current leaf, and every layout above it that the
+//! target does not share. Steps 2 and 4 ask those levels deepest first and
+//! stop at the first that does not continue.
 
 use crate::NavigationRequest;
-use gpui::App;
+use gpui::{App, Global};
+use std::cell::RefCell;
+use std::collections::HashSet;
 
 // ============================================================================
 // NavigationAction — unified result for guards, lifecycle, middleware
@@ -63,6 +72,19 @@ pub enum NavigationAction {
         /// Optional human-readable reason for redirecting.
         reason: Option<String>,
     },
+
+    /// Suspend navigation until the app answers a challenge (step-up auth, 2FA).
+    ///
+    /// Only meaningful when returned by a guard: the router stores a
+    /// [`PendingChallenge`](crate::PendingChallenge) and re-invokes the same
+    /// guard once the app calls
+    /// [`Navigator::answer_challenge`](crate::Navigator::answer_challenge).
+    Challenge {
+        /// Application-defined identifier of the challenge kind.
+        challenge_id: String,
+        /// Data the app needs to render the challenge UI.
+        payload: String,
+    },
 }
 
 impl NavigationAction {
@@ -95,6 +117,14 @@ impl NavigationAction {
         }
     }
 
+    /// Create a result that suspends navigation until a challenge is answered.
+    pub fn challenge(challenge_id: impl Into<String>, payload: impl Into<String>) -> Self {
+        Self::Challenge {
+            challenge_id: challenge_id.into(),
+            payload: payload.into(),
+        }
+    }
+
     /// Check if this action allows navigation to continue.
     #[must_use]
     pub const fn is_continue(&self) -> bool {
@@ -113,6 +143,12 @@ impl NavigationAction {
         matches!(self, Self::Redirect { .. })
     }
 
+    /// Check if this action requests a challenge.
+    #[must_use]
+    pub const fn is_challenge(&self) -> bool {
+        matches!(self, Self::Challenge { .. })
+    }
+
     /// Get the redirect path, if this is a redirect action.
     #[must_use]
     pub fn redirect_path(&self) -> Option<&str> {
@@ -192,6 +228,144 @@ pub trait RouteLifecycle: Send + Sync + 'static {
     /// Use this to check for unsaved changes or confirm navigation away.
     /// Return [`NavigationAction::deny`] to prevent navigation.
     fn can_deactivate(&self, cx: &App) -> NavigationAction;
+
+    /// Answer a dry run of [`can_deactivate`](Self::can_deactivate) from
+    /// [`GlobalRouter::explain_navigation`](crate::GlobalRouter::explain_navigation).
+    ///
+    /// Must not have side effects. Defaults to `can_deactivate`; override it
+    /// if that prompts the user or otherwise mutates state.
+    fn probe_can_deactivate(&self, cx: &App) -> NavigationAction {
+        self.can_deactivate(cx)
+    }
+
+    /// Answer a dry run of [`on_enter`](Self::on_enter).
+    ///
+    /// `on_enter` usually does setup work, so the default answers
+    /// [`NavigationAction::Continue`]; override it to let explanations see a
+    /// redirect the hook would return.
+    fn probe_enter(&self, _cx: &App, _request: &NavigationRequest) -> NavigationAction {
+        NavigationAction::Continue
+    }
+}
+
+// ============================================================================
+// Unsaved changes
+// ============================================================================
+
+/// Global registry of scopes (forms, editors) with unsaved changes.
+///
+/// Scope keys are app-defined strings, so several forms on different routes
+/// can be tracked independently. A scope attached to a route with
+/// [`Route::protect_unsaved`](crate::Route::protect_unsaved) is cleared
+/// automatically once that route leaves the match stack.
+///
+/// # Example
+///
+/// ```no_run
+/// use gpui_navigator::DirtyStateRegistry;
+///
+/// fn on_edit(cx: &mut gpui::App) {
+///     DirtyStateRegistry::mark_dirty(cx, "profile-form");
+/// }
+///
+/// fn on_save(cx: &mut gpui::App) {
+///     DirtyStateRegistry::mark_clean(cx, "profile-form");
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct DirtyStateRegistry {
+    // Interior mutability lets the navigation pipeline, which only holds
+    // `&App`, clear scopes of routes it navigates away from.
+    dirty: RefCell<HashSet<String>>,
+}
+
+impl Global for DirtyStateRegistry {}
+
+impl DirtyStateRegistry {
+    /// Mark `scope` as having unsaved changes.
+    pub fn mark_dirty(cx: &mut App, scope: impl Into<String>) {
+        cx.default_global::<Self>()
+            .dirty
+            .borrow_mut()
+            .insert(scope.into());
+    }
+
+    /// Mark `scope` as saved (no unsaved changes).
+    pub fn mark_clean(cx: &App, scope: &str) {
+        if let Some(registry) = cx.try_global::<Self>() {
+            registry.dirty.borrow_mut().remove(scope);
+        }
+    }
+
+    /// Check whether `scope` has unsaved changes, or any scope when `None`.
+    #[must_use]
+    pub fn is_dirty(cx: &App, scope: Option<&str>) -> bool {
+        cx.try_global::<Self>().is_some_and(|registry| {
+            let dirty = registry.dirty.borrow();
+            scope.map_or(!dirty.is_empty(), |scope| dirty.contains(scope))
+        })
+    }
+}
+
+/// Lifecycle that denies leaving a route while its scope is dirty.
+///
+/// Attach it with [`Route::protect_unsaved`](crate::Route::protect_unsaved),
+/// or directly via [`Route::lifecycle`](crate::Route::lifecycle).
+///
+/// # Example
+///
+/// ```no_run
+/// use gpui_navigator::{Route, UnsavedChangesLifecycle};
+/// use gpui::*;
+///
+/// Route::new("/settings", |_, _cx, _params| div().into_any_element())
+///     .lifecycle(UnsavedChangesLifecycle::new("settings").message("Discard your edits?"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct UnsavedChangesLifecycle {
+    scope: String,
+    message: String,
+}
+
+impl UnsavedChangesLifecycle {
+    /// Create a lifecycle guarding `scope` with the default message.
+    pub fn new(scope: impl Into<String>) -> Self {
+        Self {
+            scope: scope.into(),
+            message: "You have unsaved changes".to_string(),
+        }
+    }
+
+    /// Set the reason reported when navigation is blocked.
+    #[must_use]
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Return the scope key this lifecycle checks.
+    #[must_use]
+    pub fn scope(&self) -> &str {
+        &self.scope
+    }
+}
+
+impl RouteLifecycle for UnsavedChangesLifecycle {
+    fn on_enter(&self, _cx: &App, _request: &NavigationRequest) -> NavigationAction {
+        NavigationAction::Continue
+    }
+
+    fn on_exit(&self, _cx: &App) -> NavigationAction {
+        NavigationAction::Continue
+    }
+
+    fn can_deactivate(&self, cx: &App) -> NavigationAction {
+        if DirtyStateRegistry::is_dirty(cx, Some(&self.scope)) {
+            NavigationAction::deny(self.message.clone())
+        } else {
+            NavigationAction::Continue
+        }
+    }
 }
 
 // ============================================================================
@@ -202,6 +376,9 @@ pub trait RouteLifecycle: Send + Sync + 'static {
 #[allow(clippy::needless_pass_by_ref_mut)]
 mod tests {
     use super::*;
+    use crate::test_support::push_result;
+    use crate::{init_router, NavigationResult, Navigator, Route};
+    use gpui::{IntoElement, TestAppContext};
 
     // --- NavigationAction tests ---
 
@@ -254,6 +431,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_navigation_action_challenge() {
+        let action = NavigationAction::challenge("otp", "Enter the 6-digit code");
+        assert!(action.is_challenge());
+        assert!(!action.is_continue());
+        assert_eq!(action.redirect_path(), None);
+    }
+
     #[test]
     fn test_navigation_action_equality() {
         assert_eq!(NavigationAction::Continue, NavigationAction::Continue);
@@ -344,4 +529,194 @@ mod tests {
         let result = cx.update(|cx| lifecycle.can_deactivate(cx));
         assert!(result.is_deny());
     }
+
+    /// Logs every exit hook under the route's name.
+    struct ExitLog {
+        name: &'static str,
+        log: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl RouteLifecycle for ExitLog {
+        fn on_enter(&self, _cx: &App, _request: &NavigationRequest) -> NavigationAction {
+            NavigationAction::Continue
+        }
+
+        fn on_exit(&self, _cx: &App) -> NavigationAction {
+            self.log.lock().unwrap().push(format!("exit {}", self.name));
+            NavigationAction::Continue
+        }
+
+        fn can_deactivate(&self, _cx: &App) -> NavigationAction {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("leave {}", self.name));
+            NavigationAction::Continue
+        }
+    }
+
+    #[gpui::test]
+    fn test_exit_hooks_run_for_every_level_left(cx: &mut gpui::TestAppContext) {
+        let log = std::sync::Arc::default();
+        let page = |path: &str, name: &'static str| {
+            Route::view(path, || gpui::div().into_any_element()).lifecycle(ExitLog {
+                name,
+                log: std::sync::Arc::clone(&log),
+            })
+        };
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/", "home"));
+                router.add_route(
+                    page("/app", "app")
+                        .child(page("a", "a"))
+                        .child(page("b", "b")),
+                );
+            });
+        });
+        cx.update(|cx| Navigator::push(cx, "/app/b"));
+        log.lock().unwrap().clear();
+
+        // The layout stays, only the leaf is left
+        cx.update(|cx| Navigator::push(cx, "/app/a"));
+        assert_eq!(*log.lock().unwrap(), ["leave b", "exit b"]);
+        cx.update(Navigator::pop);
+        log.lock().unwrap().clear();
+
+        // Leaving the layout asks it too, after the leaf
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["leave b", "leave app", "exit b", "exit app"]
+        );
+    }
+
+    // --- Unsaved changes tests ---
+
+    fn init_unsaved_router(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(
+                    Route::new("/form-a", |_, _cx, _params| gpui::div().into_any_element())
+                        .protect_unsaved("a"),
+                );
+                router.add_route(
+                    Route::new("/form-b", |_, _cx, _params| gpui::div().into_any_element())
+                        .lifecycle(UnsavedChangesLifecycle::new("b").message("Discard draft?")),
+                );
+                router.add_route(
+                    Route::new("/editor", |_, _cx, _params| gpui::div().into_any_element()).child(
+                        Route::new("draft", |_, _cx, _params| gpui::div().into_any_element())
+                            .protect_unsaved("draft"),
+                    ),
+                );
+                router.add_route(
+                    Route::new("/workspace", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .protect_unsaved("workspace")
+                    .child(Route::new("notes", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })),
+                );
+            });
+        });
+    }
+
+    #[gpui::test]
+    fn test_unsaved_dirty_blocks_navigation(cx: &mut TestAppContext) {
+        init_unsaved_router(cx);
+        cx.update(|cx| Navigator::push(cx, "/form-a"));
+        cx.update(|cx| DirtyStateRegistry::mark_dirty(cx, "a"));
+
+        let result = push_result(cx, "/");
+        assert!(result.is_blocked());
+        assert_eq!(cx.read(Navigator::current_path), "/form-a");
+    }
+
+    #[gpui::test]
+    fn test_unsaved_clean_allows_navigation(cx: &mut TestAppContext) {
+        init_unsaved_router(cx);
+        cx.update(|cx| Navigator::push(cx, "/form-a"));
+        cx.update(|cx| DirtyStateRegistry::mark_dirty(cx, "a"));
+        cx.update(|cx| DirtyStateRegistry::mark_clean(cx, "a"));
+
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    #[gpui::test]
+    fn test_unsaved_message_becomes_blocked_reason(cx: &mut TestAppContext) {
+        init_unsaved_router(cx);
+        cx.update(|cx| Navigator::push(cx, "/form-b"));
+        cx.update(|cx| DirtyStateRegistry::mark_dirty(cx, "b"));
+
+        match push_result(cx, "/") {
+            NavigationResult::Blocked { reason, .. } => assert_eq!(reason, "Discard draft?"),
+            other => panic!("Expected Blocked, got {other:?}"),
+        }
+    }
+
+    #[gpui::test]
+    fn test_unsaved_scopes_are_independent(cx: &mut TestAppContext) {
+        init_unsaved_router(cx);
+        cx.update(|cx| DirtyStateRegistry::mark_dirty(cx, "a"));
+        assert!(cx.read(|cx| DirtyStateRegistry::is_dirty(cx, Some("a"))));
+        assert!(!cx.read(|cx| DirtyStateRegistry::is_dirty(cx, Some("b"))));
+        assert!(cx.read(|cx| DirtyStateRegistry::is_dirty(cx, None)));
+
+        // Scope "a" being dirty doesn't block leaving the "b" form
+        cx.update(|cx| Navigator::push(cx, "/form-b"));
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    #[gpui::test]
+    fn test_unsaved_nested_leaf_blocks_navigation(cx: &mut TestAppContext) {
+        init_unsaved_router(cx);
+        cx.update(|cx| Navigator::push(cx, "/editor/draft"));
+        cx.update(|cx| DirtyStateRegistry::mark_dirty(cx, "draft"));
+
+        // The leaf's lifecycle is consulted, not the top-level route's
+        let result = push_result(cx, "/");
+        assert!(result.is_blocked());
+        assert_eq!(cx.read(Navigator::current_path), "/editor/draft");
+    }
+
+    #[gpui::test]
+    fn test_unsaved_layout_scope_blocks_leaving(cx: &mut TestAppContext) {
+        init_unsaved_router(cx);
+        cx.update(|cx| Navigator::push(cx, "/workspace/notes"));
+        cx.update(|cx| DirtyStateRegistry::mark_dirty(cx, "workspace"));
+
+        // The dirty layout is asked too when a navigation leaves it
+        assert!(push_result(cx, "/").is_blocked());
+        assert_eq!(cx.read(Navigator::current_path), "/workspace/notes");
+
+        // Staying inside the layout is fine, and keeps its scope
+        cx.update(|cx| Navigator::push(cx, "/workspace"));
+        assert_eq!(cx.read(Navigator::current_path), "/workspace");
+        assert!(cx.read(|cx| DirtyStateRegistry::is_dirty(cx, Some("workspace"))));
+    }
+
+    #[gpui::test]
+    fn test_unsaved_scope_clears_when_forced_away(cx: &mut TestAppContext) {
+        init_unsaved_router(cx);
+        let home = cx.read(Navigator::snapshot);
+        cx.update(|cx| Navigator::push(cx, "/workspace/notes"));
+        cx.update(|cx| DirtyStateRegistry::mark_dirty(cx, "workspace"));
+
+        // A restore does not ask the lifecycle, so the form it bypassed
+        // must not block every later navigation
+        cx.update(|cx| Navigator::restore(cx, home));
+        assert_eq!(cx.read(Navigator::current_path), "/");
+        assert!(!cx.read(|cx| DirtyStateRegistry::is_dirty(cx, Some("workspace"))));
+
+        cx.update(|cx| Navigator::push(cx, "/workspace/notes"));
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
 }
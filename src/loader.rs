@@ -0,0 +1,368 @@
+//! Data loaded before a route renders.
+//!
+//! [`Route::loader`](crate::Route::loader) attaches a synchronous loader that
+//! the navigation pipeline runs once the match stack for the new path is
+//! resolved, before `on_enter` hooks and before anything renders. Loaders run
+//! root to leaf, each with its entry's accumulated params.
+//!
+//! Loaded values live on the [`GlobalRouter`](crate::GlobalRouter) next to
+//! the match stack they were loaded for, and are replaced by the next
+//! navigation — going back to an entry runs its loaders again. Components
+//! read them synchronously with
+//! [`Navigator::loader_data`](crate::Navigator::loader_data), which returns
+//! the value of the requested type loaded nearest the leaf.
+//!
+//! [`Route::validate_param`](crate::Route::validate_param) validators run in
+//! the same stage, just before the route's loader, after the params the
+//! route captured have been checked against their
+//! [registered types](crate::GlobalRouter::register_param_type).
+//!
+//! A loader or validator that fails stops the loaders below it and records a
+//! [`RouteError`] on the route's match stack entry. The nearest
+//! [`Route::error_boundary`](crate::Route::error_boundary) renders in its
+//! place; without one, the route renders the error page (the registered
+//! [`ErrorHandlers`](crate::ErrorHandlers) error handler, or the default one)
+//! in place of its builder. The navigation itself still succeeds.
+
+use crate::error::{NavigationError, RouteError};
+use crate::params::ParamTypes;
+use crate::resolve::{MatchEntry, MatchStack};
+use crate::route::Route;
+use crate::{debug_log, warn_log, RouteParams};
+use gpui::App;
+use std::any::Any;
+use std::sync::Arc;
+
+/// Outcome of a [`Route::loader`](crate::Route::loader).
+pub type LoaderResult<T> = Result<T, NavigationError>;
+
+/// Type-erased loader stored on a [`Route`].
+pub(crate) type LoaderFn =
+    Arc<dyn Fn(&App, &RouteParams) -> LoaderResult<Arc<dyn Any + Send + Sync>> + Send + Sync>;
+
+/// Param validator stored on a [`Route`].
+pub(crate) type ParamValidatorFn = Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+/// Why one route's loader stage failed.
+#[derive(Clone)]
+struct Failed {
+    /// Handed to the error handlers when no boundary catches it.
+    error: NavigationError,
+    /// Recorded on the route's match stack entry.
+    route_error: RouteError,
+}
+
+/// What one route's loader stage produced.
+#[derive(Clone)]
+struct Loaded {
+    depth: usize,
+    route: Arc<Route>,
+    /// The loaded value, `None` for routes with validators only.
+    outcome: Result<Option<Arc<dyn Any + Send + Sync>>, Failed>,
+}
+
+/// Loader outcomes for the current match stack, root to leaf.
+#[derive(Clone, Default)]
+pub(crate) struct LoaderData {
+    loaded: Vec<Loaded>,
+}
+
+impl LoaderData {
+    /// Run the validators and loaders of `stack`, root to leaf, stopping at
+    /// the first error.
+    ///
+    /// `progress` is told `(completed, total)` before the first loader and
+    /// after each one.
+    pub(crate) fn load(
+        stack: &MatchStack,
+        types: &ParamTypes,
+        cx: &App,
+        progress: impl FnMut(usize, usize),
+    ) -> Self {
+        Self {
+            loaded: run(stack.entries(), types, cx, progress),
+        }
+    }
+
+    /// Run the loader stage again from `depth` down, keeping what the
+    /// levels above it loaded.
+    pub(crate) fn retry_from(
+        &mut self,
+        stack: &MatchStack,
+        depth: usize,
+        types: &ParamTypes,
+        cx: &App,
+    ) {
+        self.loaded.retain(|loaded| loaded.depth < depth);
+        let below = stack.entries().get(depth..).unwrap_or_default();
+        self.loaded.extend(run(below, types, cx, |_, _| {}));
+    }
+
+    /// The value of type `T` loaded nearest the leaf.
+    pub(crate) fn get<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.loaded.iter().rev().find_map(|loaded| {
+            let value = loaded.outcome.as_ref().ok()?.as_ref()?;
+            Arc::clone(value).downcast().ok()
+        })
+    }
+
+    /// The error `route`'s loader stage failed with, if it did.
+    pub(crate) fn error_for(&self, route: &Route) -> Option<&NavigationError> {
+        self.loaded
+            .iter()
+            .find(|loaded| std::ptr::eq(Arc::as_ptr(&loaded.route), route))
+            .and_then(|loaded| loaded.outcome.as_ref().err())
+            .map(|failed| &failed.error)
+    }
+
+    /// Errors to record on the match stack, by depth.
+    pub(crate) fn route_errors(&self) -> impl Iterator<Item = (usize, &RouteError)> {
+        self.loaded.iter().filter_map(|loaded| {
+            let failed = loaded.outcome.as_ref().err()?;
+            Some((loaded.depth, &failed.route_error))
+        })
+    }
+}
+
+/// Run the loader stage of `entries`, stopping at the first error.
+fn run(
+    entries: &[MatchEntry],
+    types: &ParamTypes,
+    cx: &App,
+    mut progress: impl FnMut(usize, usize),
+) -> Vec<Loaded> {
+    let staged: Vec<_> = entries
+        .iter()
+        .filter(|entry| {
+            entry.route.loader.is_some()
+                || !entry.route.param_validators.is_empty()
+                || entry
+                    .own_params()
+                    .iter()
+                    .any(|(key, _)| types.contains(key))
+        })
+        .collect();
+    let total = staged.len();
+    if total > 0 {
+        progress(0, total);
+    }
+
+    let mut results = Vec::new();
+    for (completed, entry) in staged.into_iter().enumerate() {
+        let outcome = validate(entry, types).and_then(|()| {
+            let Some(loader) = &entry.route.loader else {
+                return Ok(None);
+            };
+            debug_log!("Running loader of route '{}'", entry.route.config.path);
+            loader(cx, &entry.params).map(Some).map_err(|error| {
+                warn_log!(
+                    "Loader of route '{}' failed: {}",
+                    entry.route.config.path,
+                    error
+                );
+                Failed {
+                    route_error: RouteError::LoaderFailed {
+                        message: error.to_string(),
+                        source_kind: error.kind(),
+                    },
+                    error,
+                }
+            })
+        });
+        let failed = outcome.is_err();
+        results.push(Loaded {
+            depth: entry.depth,
+            route: Arc::clone(&entry.route),
+            outcome,
+        });
+        progress(completed + 1, total);
+        if failed {
+            break;
+        }
+    }
+    results
+}
+
+/// Check the params `entry` captured against their registered types, then
+/// run its param validators.
+fn validate(entry: &MatchEntry, param_types: &ParamTypes) -> Result<(), Failed> {
+    let mut typed: Vec<_> = entry
+        .own_params()
+        .iter()
+        .filter(|(key, _)| param_types.contains(key))
+        .collect();
+    typed.sort_unstable();
+    let typed = typed
+        .into_iter()
+        .map(|(name, value)| (name, value, param_types.validate(name, value)));
+    let validated = entry
+        .route
+        .param_validators
+        .iter()
+        .filter_map(|(name, validator)| {
+            let value = entry.params.get(name)?;
+            Some((name, value, validator(value)))
+        });
+
+    for (name, value, outcome) in typed.chain(validated) {
+        if let Err(reason) = outcome {
+            warn_log!(
+                "Param '{}' of route '{}' rejected: {}",
+                name,
+                entry.route.config.path,
+                reason
+            );
+            let route_error = RouteError::InvalidParam {
+                name: name.clone(),
+                value: value.clone(),
+                reason,
+            };
+            return Err(Failed {
+                error: NavigationError::InvalidParams {
+                    message: route_error.to_string(),
+                },
+                route_error,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use crate::error::{ErrorHandlers, NavigationError};
+    use crate::test_support::page;
+    use crate::{init_router, router_view, Navigator, Route};
+    use gpui::{div, Context, IntoElement, Render, TestAppContext, Window};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, PartialEq)]
+    struct User(String);
+
+    #[derive(Debug, PartialEq)]
+    struct Layout(&'static str);
+
+    #[gpui::test]
+    fn test_loader_data_is_available_after_navigation(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                let user =
+                    page(":id").loader(|_, params| Ok(User(params.get("id").cloned().unwrap())));
+                router.add_route(
+                    page("/users")
+                        .loader(|_, _| Ok(Layout("users")))
+                        .children(vec![user.into()]),
+                );
+            });
+        });
+        assert_eq!(cx.read(Navigator::loader_data::<User>), None);
+
+        cx.update(|cx| Navigator::push(cx, "/users/7"));
+        let user = cx.read(Navigator::loader_data::<User>);
+        assert_eq!(user.as_deref(), Some(&User("7".into())));
+        let layout = cx.read(Navigator::loader_data::<Layout>);
+        assert_eq!(layout.as_deref(), Some(&Layout("users")));
+
+        // Data belongs to the match stack it was loaded for
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert_eq!(cx.read(Navigator::loader_data::<User>), None);
+        cx.update(Navigator::pop);
+        let user = cx.read(Navigator::loader_data::<User>);
+        assert_eq!(user.as_deref(), Some(&User("7".into())));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct OrderId(u64);
+
+    impl crate::RouteParamType for OrderId {
+        const KEY: &'static str = "orderId";
+
+        fn parse(value: &str) -> Result<Self, String> {
+            value.parse().map(OrderId).map_err(|e| format!("{e}"))
+        }
+    }
+
+    #[gpui::test]
+    fn test_registered_param_type_rejects_malformed_value(cx: &mut TestAppContext) {
+        static LOADED: AtomicBool = AtomicBool::new(false);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.register_param_type::<OrderId>();
+                router.add_route(page("/"));
+                router.add_route(page("/orders/:orderId").loader(|_, _| {
+                    LOADED.store(true, Ordering::SeqCst);
+                    Ok(Layout("order"))
+                }));
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/orders/12"));
+        assert!(LOADED.swap(false, Ordering::SeqCst));
+        assert_eq!(cx.read(Navigator::param::<OrderId>), Ok(OrderId(12)));
+
+        cx.update(|cx| Navigator::push(cx, "/orders/twelve"));
+        assert_eq!(cx.read(Navigator::current_path), "/orders/twelve");
+        assert!(!LOADED.load(Ordering::SeqCst));
+        let error = cx.read(|cx| {
+            cx.global::<crate::GlobalRouter>()
+                .leaf()
+                .and_then(|leaf| leaf.error().cloned())
+        });
+        assert!(matches!(
+            error,
+            Some(crate::RouteError::InvalidParam { ref name, ref value, .. })
+                if name == "orderId" && value == "twelve"
+        ));
+    }
+
+    struct Root;
+
+    impl Render for Root {
+        fn render(&mut self, _: &mut Window, _: &mut Context<'_, Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    #[gpui::test]
+    fn test_failed_loader_renders_error_page(cx: &mut TestAppContext) {
+        static BUILT: AtomicBool = AtomicBool::new(false);
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&errors);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    Route::new("/report", |_, _, _| {
+                        BUILT.store(true, Ordering::SeqCst);
+                        div().into_any_element()
+                    })
+                    .loader(|_, _| -> super::LoaderResult<User> {
+                        Err(NavigationError::Custom {
+                            message: "report unavailable".into(),
+                        })
+                    }),
+                );
+                router.set_error_handlers(ErrorHandlers::new().on_error(move |_, error| {
+                    sink.lock().unwrap().push(error.to_string());
+                    div().into_any_element()
+                }));
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/report"));
+        assert_eq!(cx.read(Navigator::current_path), "/report");
+
+        let window = cx.add_window(|_, _| Root);
+        window
+            .update(cx, |_, window, cx| {
+                let _ = router_view(window, cx);
+            })
+            .unwrap();
+
+        assert!(!BUILT.load(Ordering::SeqCst));
+        assert_eq!(*errors.lock().unwrap(), ["report unavailable"]);
+    }
+}
@@ -28,73 +28,203 @@
 //! warn_log!("Guard returned unexpected value");
 //! error_log!("Failed to resolve route: {}", err);
 //! ```
+//!
+//! # Navigation spans
+//!
+//! Every top-level navigation gets a [`NavigationId`] (`nav#42`); redirects
+//! it triggers are hops of the same navigation (`nav#42.1`, `nav#42.2`).
+//! While a navigation runs, the `log` backend prefixes every line with the
+//! id (`[nav#42.1] ...`) and the `tracing` backend records it as the `id`
+//! field of a `navigation` span. Application code running inside the
+//! navigation (guards, middleware, lifecycle hooks) can read it with
+//! [`GlobalRouter::current_navigation_id`](crate::GlobalRouter::current_navigation_id).
+
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// ============================================================================
+// Navigation spans
+// ============================================================================
+
+/// Correlation id of a navigation, used to join log lines.
+///
+/// Displayed as `nav#{id}` for the navigation itself and `nav#{id}.{hop}`
+/// for the redirects it triggers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NavigationId {
+    id: u32,
+    hop: u32,
+}
+
+impl NavigationId {
+    /// Return the id shared by a navigation and all its redirect hops.
+    #[must_use]
+    pub const fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Return the redirect hop (0 for the top-level navigation).
+    #[must_use]
+    pub const fn hop(&self) -> u32 {
+        self.hop
+    }
+}
+
+impl fmt::Display for NavigationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.hop == 0 {
+            write!(f, "nav#{}", self.id)
+        } else {
+            write!(f, "nav#{}.{}", self.id, self.hop)
+        }
+    }
+}
+
+static NEXT_NAVIGATION_ID: AtomicU32 = AtomicU32::new(1);
+
+thread_local! {
+    /// Navigation currently running on this thread, if any.
+    static ACTIVE_NAVIGATION: Cell<Option<NavigationId>> = const { Cell::new(None) };
+}
+
+/// Return the navigation currently running on this thread, if any.
+///
+/// Used by the logging macros; prefer
+/// [`GlobalRouter::current_navigation_id`](crate::GlobalRouter::current_navigation_id).
+#[must_use]
+pub fn current_navigation() -> Option<NavigationId> {
+    ACTIVE_NAVIGATION.with(Cell::get)
+}
+
+/// Marks a navigation as active on this thread until dropped.
+pub(crate) struct NavigationSpan {
+    previous: Option<NavigationId>,
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+impl NavigationSpan {
+    /// Enter a navigation span.
+    ///
+    /// `hop` 0 starts a new navigation; a non-zero `hop` inside a running
+    /// navigation is a redirect hop of it.
+    pub(crate) fn enter(hop: usize) -> Self {
+        let previous = current_navigation();
+        let id = match previous {
+            Some(parent) if hop > 0 => NavigationId {
+                id: parent.id,
+                hop: u32::try_from(hop).unwrap_or(u32::MAX),
+            },
+            _ => NavigationId {
+                id: NEXT_NAVIGATION_ID.fetch_add(1, Ordering::Relaxed),
+                hop: 0,
+            },
+        };
+        ACTIVE_NAVIGATION.with(|active| active.set(Some(id)));
+
+        Self {
+            previous,
+            #[cfg(feature = "tracing")]
+            _span: tracing::debug_span!("navigation", id = %id).entered(),
+        }
+    }
+}
+
+impl Drop for NavigationSpan {
+    fn drop(&mut self) {
+        ACTIVE_NAVIGATION.with(|active| active.set(self.previous));
+    }
+}
+
+// ============================================================================
+// Macros
+// ============================================================================
+
+/// Forward to a `log` macro, prefixed with the active navigation id.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_in_navigation {
+    ($level:ident, $($arg:tt)*) => {
+        if let Some(nav) = $crate::logging::current_navigation() {
+            ::log::$level!("[{}] {}", nav, format_args!($($arg)*));
+        } else {
+            ::log::$level!($($arg)*);
+        }
+    };
+}
 
 /// Emit a **trace**-level log message.
 ///
 /// Dispatches to `log::trace!` or `tracing::trace!` depending on the
-/// enabled feature flag. Accepts `format!`-style arguments.
+/// enabled feature flag. Accepts `format!`-style arguments. Inside a
+/// navigation, `log` output is prefixed with its [`NavigationId`].
 #[macro_export]
 macro_rules! trace_log {
     ($($arg:tt)*) => {
         #[cfg(feature = "tracing")]
         ::tracing::trace!($($arg)*);
         #[cfg(feature = "log")]
-        ::log::trace!($($arg)*);
+        $crate::__log_in_navigation!(trace, $($arg)*);
     };
 }
 
 /// Emit a **debug**-level log message.
 ///
 /// Dispatches to `log::debug!` or `tracing::debug!` depending on the
-/// enabled feature flag. Accepts `format!`-style arguments.
+/// enabled feature flag. Accepts `format!`-style arguments. Inside a
+/// navigation, `log` output is prefixed with its [`NavigationId`].
 #[macro_export]
 macro_rules! debug_log {
     ($($arg:tt)*) => {
         #[cfg(feature = "tracing")]
         ::tracing::debug!($($arg)*);
         #[cfg(feature = "log")]
-        ::log::debug!($($arg)*);
+        $crate::__log_in_navigation!(debug, $($arg)*);
     };
 }
 
 /// Emit an **info**-level log message.
 ///
 /// Dispatches to `log::info!` or `tracing::info!` depending on the
-/// enabled feature flag. Accepts `format!`-style arguments.
+/// enabled feature flag. Accepts `format!`-style arguments. Inside a
+/// navigation, `log` output is prefixed with its [`NavigationId`].
 #[macro_export]
 macro_rules! info_log {
     ($($arg:tt)*) => {
         #[cfg(feature = "tracing")]
         ::tracing::info!($($arg)*);
         #[cfg(feature = "log")]
-        ::log::info!($($arg)*);
+        $crate::__log_in_navigation!(info, $($arg)*);
     };
 }
 
 /// Emit a **warn**-level log message.
 ///
 /// Dispatches to `log::warn!` or `tracing::warn!` depending on the
-/// enabled feature flag. Accepts `format!`-style arguments.
+/// enabled feature flag. Accepts `format!`-style arguments. Inside a
+/// navigation, `log` output is prefixed with its [`NavigationId`].
 #[macro_export]
 macro_rules! warn_log {
     ($($arg:tt)*) => {
         #[cfg(feature = "tracing")]
         ::tracing::warn!($($arg)*);
         #[cfg(feature = "log")]
-        ::log::warn!($($arg)*);
+        $crate::__log_in_navigation!(warn, $($arg)*);
     };
 }
 
 /// Emit an **error**-level log message.
 ///
 /// Dispatches to `log::error!` or `tracing::error!` depending on the
-/// enabled feature flag. Accepts `format!`-style arguments.
+/// enabled feature flag. Accepts `format!`-style arguments. Inside a
+/// navigation, `log` output is prefixed with its [`NavigationId`].
 #[macro_export]
 macro_rules! error_log {
     ($($arg:tt)*) => {
         #[cfg(feature = "tracing")]
         ::tracing::error!($($arg)*);
         #[cfg(feature = "log")]
-        ::log::error!($($arg)*);
+        $crate::__log_in_navigation!(error, $($arg)*);
     };
 }
@@ -12,6 +12,44 @@
 //! (higher [`priority`](RouteMiddleware::priority) first) for `before_navigation`,
 //! and in reverse order for `after_navigation` (onion model).
 //!
+//! Only middleware of the routes in the target's resolved match stack run.
+//! With [`MiddlewareOrdering::ByDepthThenPriority`] (set through
+//! [`GlobalRouter::set_middleware_ordering`](crate::GlobalRouter::set_middleware_ordering))
+//! a child's middleware always run inside its ancestors', whatever their
+//! priorities: `before` from root to leaf, `after` from leaf to root.
+//!
+//! # Outcomes
+//!
+//! A navigation may take several passes through the pipeline: a guard or
+//! `on_enter` hook can redirect, and an unmatched path can fall back. Each
+//! pass that gets past the guards runs `before_navigation` for its target,
+//! and `after_navigation` only if that pass commits.
+//!
+//! Once the whole navigation settles, every middleware whose
+//! `before_navigation` ran during it is told the outcome exactly once:
+//! [`on_navigation_complete`](RouteMiddleware::on_navigation_complete) with
+//! the final [`NavigationResult`] on success, or
+//! [`on_navigation_failed`](RouteMiddleware::on_navigation_failed) with the
+//! [`NavigationError`] otherwise. Both receive the request the navigation
+//! started from, so a redirect chain shows as a `request.to` differing from
+//! the final result's path.
+//!
+//! # Groups
+//!
+//! Middleware may belong to a named [`group`](RouteMiddleware::group), such
+//! as `"analytics"`. [`GlobalRouter::set_middleware_group_enabled`](crate::GlobalRouter::set_middleware_group_enabled)
+//! switches a whole group off at runtime without touching the routes: its
+//! middleware are skipped, and not told outcomes, until it is enabled
+//! again. Groups start enabled; ungrouped middleware always run.
+//!
+//! # Invalidation
+//!
+//! When a route table change takes routes off the current path (see
+//! [`invalidation`](crate::invalidation)), the middleware of the routes the
+//! path resolved to before the change hear of it through
+//! [`on_route_invalidated`](RouteMiddleware::on_route_invalidated), in
+//! `after` order, once the router next has an app context.
+//!
 //! # Creating middleware
 //!
 //! | Approach | When to use |
@@ -37,6 +75,8 @@
 //! }
 //! ```
 
+use crate::error::{NavigationError, NavigationResult};
+use crate::invalidation::CurrentRouteInvalidated;
 use crate::NavigationRequest;
 use gpui::App;
 
@@ -75,8 +115,42 @@ pub trait RouteMiddleware: Send + Sync + 'static {
     fn before_navigation(&self, cx: &App, request: &NavigationRequest);
 
     /// Called after navigation completes successfully.
+    ///
+    /// Runs before any window renders the new path, so state written here
+    /// is visible to the destination's first render; see
+    /// [`Navigator`](crate::Navigator#ordering).
     fn after_navigation(&self, cx: &App, request: &NavigationRequest);
 
+    /// Called once the navigation succeeded, redirects included.
+    ///
+    /// `request` is the one the navigation started from; the path actually
+    /// navigated to is in `result`. See [Outcomes](self#outcomes).
+    fn on_navigation_complete(
+        &self,
+        _cx: &App,
+        _request: &NavigationRequest,
+        _result: &NavigationResult,
+    ) {
+    }
+
+    /// Called once the navigation ended without success: blocked, not
+    /// found, suspended by a challenge, or failed.
+    ///
+    /// `request` is the one the navigation started from. See
+    /// [Outcomes](self#outcomes).
+    fn on_navigation_failed(
+        &self,
+        _cx: &App,
+        _request: &NavigationRequest,
+        _error: &NavigationError,
+    ) {
+    }
+
+    /// Called when a route table change took routes off the current path.
+    ///
+    /// See [Invalidation](self#invalidation).
+    fn on_route_invalidated(&self, _cx: &App, _event: &CurrentRouteInvalidated) {}
+
     /// Middleware name for debugging.
     fn name(&self) -> &'static str {
         "RouteMiddleware"
@@ -86,6 +160,48 @@ pub trait RouteMiddleware: Send + Sync + 'static {
     fn priority(&self) -> i32 {
         0
     }
+
+    /// Group this middleware belongs to, if any. See [Groups](self#groups).
+    fn group(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+// ============================================================================
+// Ordering
+// ============================================================================
+
+/// How middleware of the routes in a match stack are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MiddlewareOrdering {
+    /// By priority alone: highest first for `before`, lowest first for
+    /// `after`. Ties keep root-to-leaf order.
+    #[default]
+    ByPriority,
+    /// By match stack depth first, priority within a depth: `before` runs
+    /// root to leaf, `after` leaf to root, so nesting is strict.
+    ByDepthThenPriority,
+}
+
+impl MiddlewareOrdering {
+    /// Sort `(middleware, priority, depth)` entries for the `before` phase,
+    /// or for the `after` phase when `after` is set.
+    pub(crate) fn sort<T>(self, entries: &mut [(T, i32, usize)], after: bool) {
+        match (self, after) {
+            (Self::ByPriority, false) => {
+                entries.sort_by_key(|(_, priority, _)| std::cmp::Reverse(*priority));
+            }
+            (Self::ByPriority, true) => {
+                entries.sort_by_key(|(_, priority, _)| *priority);
+            }
+            (Self::ByDepthThenPriority, false) => {
+                entries.sort_by_key(|(_, priority, depth)| (*depth, std::cmp::Reverse(*priority)));
+            }
+            (Self::ByDepthThenPriority, true) => {
+                entries.sort_by_key(|(_, priority, depth)| (std::cmp::Reverse(*depth), *priority));
+            }
+        }
+    }
 }
 
 // ============================================================================
@@ -115,13 +231,27 @@ where
     B: Fn(&App, &NavigationRequest) + Send + Sync + 'static,
     A: Fn(&App, &NavigationRequest) + Send + Sync + 'static,
 {
-    FnMiddleware { before, after }
+    FnMiddleware {
+        before,
+        after,
+        group: None,
+    }
 }
 
 /// Middleware created from two closures via [`middleware_fn`].
 pub struct FnMiddleware<B, A> {
     before: B,
     after: A,
+    group: Option<&'static str>,
+}
+
+impl<B, A> FnMiddleware<B, A> {
+    /// Put the middleware in `group`. See [Groups](self#groups).
+    #[must_use]
+    pub const fn in_group(mut self, group: &'static str) -> Self {
+        self.group = Some(group);
+        self
+    }
 }
 
 impl<B, A> RouteMiddleware for FnMiddleware<B, A>
@@ -136,6 +266,10 @@ where
     fn after_navigation(&self, cx: &App, request: &NavigationRequest) {
         (self.after)(cx, request);
     }
+
+    fn group(&self) -> Option<&'static str> {
+        self.group
+    }
 }
 
 // ============================================================================
@@ -146,7 +280,9 @@ where
 #[allow(clippy::needless_pass_by_ref_mut)]
 mod tests {
     use super::*;
-    use gpui::TestAppContext;
+    use crate::test_support::page;
+    use crate::{init_router, GlobalRouter, NavigationAction, Navigator, Route, UseRouter};
+    use gpui::{IntoElement, TestAppContext};
     use std::sync::{Arc, Mutex};
 
     struct TestMiddleware {
@@ -215,6 +351,16 @@ mod tests {
         assert_eq!(middleware.priority(), 0);
     }
 
+    #[test]
+    fn test_middleware_group() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        assert_eq!(TestMiddleware { calls }.group(), None);
+
+        let mw = middleware_fn(|_, _| {}, |_, _| {});
+        assert_eq!(mw.group(), None);
+        assert_eq!(mw.in_group("analytics").group(), Some("analytics"));
+    }
+
     #[gpui::test]
     fn test_middleware_fn_different_closures(cx: &mut TestAppContext) {
         let before_calls = Arc::new(Mutex::new(Vec::new()));
@@ -240,4 +386,362 @@ mod tests {
         assert_eq!(*before_calls.lock().unwrap(), vec!["B:/page"]);
         assert_eq!(*after_calls.lock().unwrap(), vec!["A:/page"]);
     }
+
+    #[gpui::test]
+    fn test_middleware_reads_matched_pattern(cx: &mut TestAppContext) {
+        let seen = Arc::new(Mutex::new(Vec::<Option<String>>::new()));
+        let before_seen = seen.clone();
+        let after_seen = seen.clone();
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _cx, _params| {
+                    gpui::div().into_any_element()
+                }));
+                router.add_route(
+                    Route::new("/users/:id", |_, _cx, _params| {
+                        gpui::div().into_any_element()
+                    })
+                    .middleware(middleware_fn(
+                        move |_cx, req| {
+                            before_seen
+                                .lock()
+                                .unwrap()
+                                .push(req.matched_pattern().map(str::to_string));
+                        },
+                        move |_cx, req| {
+                            after_seen
+                                .lock()
+                                .unwrap()
+                                .push(req.matched_pattern().map(str::to_string));
+                        },
+                    )),
+                );
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/users/8231"));
+
+        let seen = seen.lock().unwrap();
+        // Not yet resolved before navigation; resolved afterwards
+        assert_eq!(*seen, vec![None, Some("/users/:id".to_string())]);
+        drop(seen);
+    }
+
+    /// Middleware recording `phase:name` into a shared log.
+    struct RecordingMiddleware {
+        name: &'static str,
+        priority: i32,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl RouteMiddleware for RecordingMiddleware {
+        fn before_navigation(&self, _cx: &App, _request: &NavigationRequest) {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("before:{}", self.name));
+        }
+
+        fn after_navigation(&self, _cx: &App, _request: &NavigationRequest) {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("after:{}", self.name));
+        }
+
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn priority(&self) -> i32 {
+            self.priority
+        }
+    }
+
+    #[gpui::test]
+    fn test_middleware_ordering_modes(cx: &mut TestAppContext) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mw = |name, priority| RecordingMiddleware {
+            name,
+            priority,
+            log: Arc::clone(&log),
+        };
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/a")
+                        .middleware(mw("a", 0))
+                        .middleware(mw("a2", 10))
+                        .children(vec![page("b")
+                            .middleware(mw("b", 100))
+                            .children(vec![page("c").middleware(mw("c", 50)).into()])
+                            .into()]),
+                );
+            });
+        });
+
+        let run = |ordering| {
+            cx.update(|cx| {
+                cx.update_router(|router, _| router.set_middleware_ordering(ordering));
+                Navigator::replace(cx, "/");
+            });
+            log.lock().unwrap().clear();
+            cx.update(|cx| Navigator::push(cx, "/a/b/c"));
+            std::mem::take(&mut *log.lock().unwrap())
+        };
+
+        assert_eq!(
+            run(MiddlewareOrdering::ByPriority),
+            [
+                "before:b",
+                "before:c",
+                "before:a2",
+                "before:a",
+                "after:a",
+                "after:a2",
+                "after:c",
+                "after:b",
+            ]
+        );
+        assert_eq!(
+            run(MiddlewareOrdering::ByDepthThenPriority),
+            [
+                "before:a2",
+                "before:a",
+                "before:b",
+                "before:c",
+                "after:c",
+                "after:b",
+                "after:a",
+                "after:a2",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_disabled_middleware_group_is_skipped(cx: &mut TestAppContext) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mw = |name: &'static str| {
+            let log = Arc::clone(&log);
+            middleware_fn(move |_, _| log.lock().unwrap().push(name), |_, _| {})
+        };
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/a")
+                        .middleware(mw("analytics").in_group("analytics"))
+                        .middleware(mw("audit").in_group("audit"))
+                        .middleware(mw("plain")),
+                );
+            });
+        });
+        let visit = |cx: &mut TestAppContext| {
+            cx.update(|cx| {
+                Navigator::replace(cx, "/");
+                Navigator::push(cx, "/a");
+            });
+            let mut ran = std::mem::take(&mut *log.lock().unwrap());
+            ran.sort_unstable();
+            ran
+        };
+
+        assert_eq!(visit(cx), ["analytics", "audit", "plain"]);
+
+        cx.update(|cx| {
+            cx.update_router(|router, _| router.set_middleware_group_enabled("analytics", false));
+        });
+        assert!(!cx.read(|cx| cx
+            .global::<GlobalRouter>()
+            .is_middleware_group_enabled("analytics")));
+        assert_eq!(visit(cx), ["audit", "plain"]);
+
+        cx.update(|cx| {
+            cx.update_router(|router, _| router.set_middleware_group_enabled("analytics", true));
+        });
+        assert_eq!(visit(cx), ["analytics", "audit", "plain"]);
+    }
+
+    #[gpui::test]
+    fn test_middleware_of_unmatched_branches_does_not_run(cx: &mut TestAppContext) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mw = |name| RecordingMiddleware {
+            name,
+            priority: 0,
+            log: Arc::clone(&log),
+        };
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                // A leaf "/" used to prefix-match every path
+                router.add_route(page("/").middleware(mw("root")));
+                router.add_route(page("/users/new").middleware(mw("new")));
+                router.add_route(page("/users/:id").middleware(mw("user")));
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/users/42"));
+        assert_eq!(*log.lock().unwrap(), ["before:user", "after:user"]);
+    }
+
+    /// Middleware recording its phases and outcomes with their paths.
+    struct OutcomeMiddleware {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl OutcomeMiddleware {
+        fn push(&self, entry: &str) {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{} {entry}", self.name));
+        }
+    }
+
+    impl RouteMiddleware for OutcomeMiddleware {
+        fn before_navigation(&self, _cx: &App, request: &NavigationRequest) {
+            self.push(&format!("before {}", request.to));
+        }
+
+        fn after_navigation(&self, _cx: &App, request: &NavigationRequest) {
+            self.push(&format!("after {}", request.to));
+        }
+
+        fn on_navigation_complete(
+            &self,
+            _cx: &App,
+            request: &NavigationRequest,
+            result: &NavigationResult,
+        ) {
+            if let NavigationResult::Success { path } = result {
+                self.push(&format!("complete {} -> {path}", request.to));
+            }
+        }
+
+        fn on_navigation_failed(
+            &self,
+            _cx: &App,
+            request: &NavigationRequest,
+            error: &NavigationError,
+        ) {
+            self.push(&format!("failed {}: {error}", request.to));
+        }
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_middleware_outcome_sees_redirected_path(cx: &mut TestAppContext) {
+        use crate::guard_fn;
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mw = |name| OutcomeMiddleware {
+            name,
+            log: Arc::clone(&log),
+        };
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/app")
+                        .middleware(mw("app"))
+                        .children(vec![page("new").into()]),
+                );
+                router.add_route(
+                    page("/legacy")
+                        .middleware(mw("legacy"))
+                        .guard(guard_fn(|_, _| NavigationAction::redirect("/app/new"))),
+                );
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/legacy"));
+        assert_eq!(cx.read(Navigator::current_path), "/app/new");
+        // The redirecting route's middleware never ran, so it hears nothing
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "app before /app/new",
+                "app after /app/new",
+                "app complete /legacy -> /app/new",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_middleware_outcome_follows_lifecycle(cx: &mut TestAppContext) {
+        use crate::RouteLifecycle;
+
+        struct Hooks {
+            redirect: Option<&'static str>,
+            deny_exit: bool,
+        }
+
+        impl RouteLifecycle for Hooks {
+            fn on_enter(&self, _cx: &App, _request: &NavigationRequest) -> NavigationAction {
+                self.redirect
+                    .map_or(NavigationAction::Continue, NavigationAction::redirect)
+            }
+
+            fn on_exit(&self, _cx: &App) -> NavigationAction {
+                if self.deny_exit {
+                    NavigationAction::deny("locked")
+                } else {
+                    NavigationAction::Continue
+                }
+            }
+
+            fn can_deactivate(&self, _cx: &App) -> NavigationAction {
+                NavigationAction::Continue
+            }
+        }
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mw = |name| OutcomeMiddleware {
+            name,
+            log: Arc::clone(&log),
+        };
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/a").middleware(mw("a")).lifecycle(Hooks {
+                    redirect: Some("/b"),
+                    deny_exit: false,
+                }));
+                router.add_route(page("/b").middleware(mw("b")));
+                router.add_route(page("/locked").lifecycle(Hooks {
+                    redirect: None,
+                    deny_exit: true,
+                }));
+            });
+        });
+
+        // `a` ran before the on_enter redirect, so it hears the final path
+        // although its after_navigation never ran
+        cx.update(|cx| Navigator::push(cx, "/a"));
+        assert_eq!(
+            std::mem::take(&mut *log.lock().unwrap()),
+            [
+                "a before /a",
+                "b before /b",
+                "b after /b",
+                "b complete /a -> /b",
+                "a complete /a -> /b",
+            ]
+        );
+
+        cx.update(|cx| Navigator::push(cx, "/locked"));
+        cx.update(|cx| Navigator::push(cx, "/b"));
+        assert_eq!(cx.read(Navigator::current_path), "/locked");
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["b before /b", "b failed /b: Navigation blocked: locked"]
+        );
+    }
 }
@@ -63,7 +63,9 @@ pub type ResolvedChildRoute = (Arc<Route>, RouteParams);
 
 /// Normalize a path for consistent comparison
 ///
-/// Ensures paths have a leading slash and no trailing slash (unless root).
+/// Ensures paths have a leading slash, no trailing slash (unless root) and
+/// no repeated slashes. Segments are kept as they are: percent-escapes are
+/// decoded in captured param values during matching, never here.
 /// Returns `Cow<str>` to avoid allocation when path is already normalized.
 ///
 /// # Examples
@@ -74,6 +76,7 @@ pub type ResolvedChildRoute = (Arc<Route>, RouteParams);
 /// assert_eq!(normalize_path("/dashboard"), "/dashboard");
 /// assert_eq!(normalize_path("dashboard"), "/dashboard");
 /// assert_eq!(normalize_path("/dashboard/"), "/dashboard");
+/// assert_eq!(normalize_path("/users//42"), "/users/42");
 /// assert_eq!(normalize_path("/"), "/");
 /// assert_eq!(normalize_path(""), "/");
 /// ```
@@ -92,17 +95,17 @@ pub fn normalize_path(path: &'_ str) -> Cow<'_, str> {
     let has_leading = path.starts_with('/');
     let has_trailing = path.ends_with('/');
 
-    // Already normalized: has leading, no trailing
-    if has_leading && !has_trailing {
+    // Already normalized: has leading, no trailing, no empty segments
+    if has_leading && !has_trailing && !path.contains("//") {
         return Cow::Borrowed(path);
     }
 
     // Need to normalize
-    let trimmed = path.trim_matches('/');
-    if trimmed.is_empty() {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    if segments.is_empty() {
         Cow::Borrowed("/")
     } else {
-        Cow::Owned(format!("/{trimmed}"))
+        Cow::Owned(format!("/{}", segments.join("/")))
     }
 }
 
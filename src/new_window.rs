@@ -0,0 +1,245 @@
+//! Opening a route in a new window.
+//!
+//! [`Navigator::push_new_window`](crate::Navigator::push_new_window) opens a
+//! GPUI window whose root, a [`RouterWindow`], renders
+//! [`router_view`](crate::router_view) already on the target path. The window
+//! gets a navigation state of its own, keyed by its [`WindowId`], so its
+//! history, component cache and route state are separate from the window it
+//! was opened from, while the route table is shared:
+//!
+//! ```ignore
+//! let (result, window) =
+//!     Navigator::push_new_window(cx, "/document/42", WindowOptions::default());
+//! ```
+//!
+//! Guards are probed before anything opens: a blocked target returns
+//! [`NavigationResult::Blocked`](crate::NavigationResult::Blocked) and no
+//! window. Otherwise a fresh history navigates to the target through the
+//! full pipeline and the window opens on it.
+//!
+//! The router keeps that state by window: the window's
+//! [`router_view`](crate::router_view) renders its own route, links clicked
+//! in it and [`Navigator::push_in_window`](crate::Navigator::push_in_window)
+//! navigate it, and only it is refreshed. The window it was opened from
+//! keeps the shared state, which the plain `Navigator` methods navigate.
+//! Focus plays no part, so no lifecycle hook runs when switching windows.
+//! Closing the window drops its state, releasing the components it cached.
+//!
+//! A [`RouterLink`](crate::RouterLink) opens its target this way when
+//! clicked with a [`Modifier`] held, see
+//! [`open_in_new_window_on`](crate::RouterLink::open_in_new_window_on).
+
+use crate::context::GlobalRouter;
+use crate::router_view;
+use gpui::{
+    div, BorrowAppContext, Context, IntoElement, Modifiers, ParentElement, Render, Styled, Window,
+    WindowId,
+};
+
+/// Modifier key that makes a [`RouterLink`](crate::RouterLink) open its
+/// target in a new window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    /// The Control key
+    Ctrl,
+    /// The Alt (Option) key
+    Alt,
+    /// The Shift key
+    Shift,
+    /// The platform key: Command on macOS, Windows key on Windows
+    Platform,
+}
+
+impl Modifier {
+    /// Check whether this key is held in `modifiers`.
+    #[must_use]
+    pub const fn is_held(self, modifiers: Modifiers) -> bool {
+        match self {
+            Self::Ctrl => modifiers.control,
+            Self::Alt => modifiers.alt,
+            Self::Shift => modifiers.shift,
+            Self::Platform => modifiers.platform,
+        }
+    }
+}
+
+/// Root of a window opened by
+/// [`Navigator::push_new_window`](crate::Navigator::push_new_window).
+///
+/// Renders [`router_view`] on the window's own navigation state and drops
+/// that state when released. See the [module docs](self).
+pub struct RouterWindow {
+    window: WindowId,
+}
+
+impl RouterWindow {
+    pub(crate) fn new(window: WindowId, cx: &Context<'_, Self>) -> Self {
+        cx.on_release(|this, cx| {
+            if cx.try_global::<GlobalRouter>().is_some() {
+                cx.update_global::<GlobalRouter, _>(|router, _| {
+                    router.close_window_state(this.window);
+                });
+            }
+        })
+        .detach();
+        Self { window }
+    }
+
+    /// Id of the window this is the root of.
+    #[must_use]
+    pub const fn window_id(&self) -> WindowId {
+        self.window
+    }
+}
+
+impl Render for RouterWindow {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        div().size_full().child(router_view(window, cx))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::test_support::page;
+    use crate::{init_router, Navigator, Route, UseRouter};
+    use gpui::{App, TestAppContext, WindowOptions};
+    use std::sync::{Arc, Mutex};
+
+    /// Paths rendered so far, with the window that rendered them.
+    type Renders = Arc<Mutex<Vec<(WindowId, String)>>>;
+
+    fn init_document_router(cx: &mut TestAppContext) -> Renders {
+        let renders = Renders::default();
+        let page_renders = Arc::clone(&renders);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/home"));
+                router.add_route(Route::new("/document/:id", move |window, cx, _| {
+                    let path = cx.global::<GlobalRouter>().current_path().to_string();
+                    let id = window.window_handle().window_id();
+                    page_renders.lock().unwrap().push((id, path));
+                    div().into_any_element()
+                }));
+                #[cfg(feature = "guard")]
+                router.add_route(page("/admin").guard(crate::guard_fn(|_, _| {
+                    crate::NavigationAction::deny("Admins only")
+                })));
+            });
+        });
+        cx.update(|cx| Navigator::push(cx, "/home"));
+        renders
+    }
+
+    #[gpui::test]
+    fn test_new_window_navigates_on_its_own(cx: &mut TestAppContext) {
+        init_document_router(cx);
+        let windows = cx.read(|cx| cx.windows().len());
+
+        let (result, window) = cx
+            .update(|cx| Navigator::push_new_window(cx, "/document/42", WindowOptions::default()));
+        assert!(result.is_success());
+        let window = window.unwrap();
+        let id = window.read_with(cx, |root, _| root.window_id()).unwrap();
+        assert_eq!(id, window.window_id());
+        assert_eq!(cx.read(|cx| cx.windows().len()), windows + 1);
+        cx.read(|cx| {
+            let router = cx.router();
+            assert!(router.has_window_state(id));
+            assert_eq!(router.window_path(id), Some("/document/42"));
+            // The opener's state is untouched
+            assert_eq!(router.current_path(), "/home");
+        });
+
+        // Each side navigates only its own history
+        cx.update(|cx| Navigator::push_in_window(cx, window, "/document/7"));
+        cx.update(|cx| Navigator::push(cx, "/"));
+        cx.read(|cx| {
+            let router = cx.router();
+            assert_eq!(router.window_path(id), Some("/document/7"));
+            assert_eq!(router.current_path(), "/");
+        });
+        cx.update(|cx| Navigator::pop_in_window(cx, window));
+        assert_eq!(
+            cx.read(|cx| cx.router().window_path(id).map(str::to_string)),
+            Some("/document/42".to_string())
+        );
+
+        // Closing the window drops its state
+        window
+            .update(cx, |_, window, _| window.remove_window())
+            .unwrap();
+        cx.run_until_parked();
+        cx.read(|cx| {
+            let router = cx.router();
+            assert!(!router.has_window_state(id));
+            assert_eq!(router.current_path(), "/");
+        });
+    }
+
+    #[gpui::test]
+    fn test_each_window_renders_its_own_route(cx: &mut TestAppContext) {
+        struct Shell;
+
+        impl Render for Shell {
+            fn render(
+                &mut self,
+                window: &mut Window,
+                cx: &mut Context<'_, Self>,
+            ) -> impl IntoElement {
+                router_view(window, cx)
+            }
+        }
+
+        let renders = init_document_router(cx);
+        cx.update(|cx| Navigator::push(cx, "/document/1"));
+        let opener = cx.add_window(|_, _| Shell);
+        cx.run_until_parked();
+
+        let (_, window) = cx
+            .update(|cx| Navigator::push_new_window(cx, "/document/42", WindowOptions::default()));
+        let window = window.unwrap();
+        cx.update(App::refresh_windows);
+        cx.run_until_parked();
+
+        let renders = renders.lock().unwrap();
+        let last = |id: WindowId| {
+            renders
+                .iter()
+                .rev()
+                .find(|(window, _)| *window == id)
+                .map(|(_, path)| path.as_str())
+        };
+        assert_eq!(last(opener.window_id()), Some("/document/1"));
+        assert_eq!(last(window.window_id()), Some("/document/42"));
+        // Between renders the shared state is live again
+        assert_eq!(cx.read(Navigator::current_path), "/document/1");
+    }
+
+    #[cfg(feature = "guard")]
+    #[gpui::test]
+    fn test_blocked_target_opens_no_window(cx: &mut TestAppContext) {
+        init_document_router(cx);
+        let windows = cx.read(|cx| cx.windows().len());
+
+        let (result, window) =
+            cx.update(|cx| Navigator::push_new_window(cx, "/admin", WindowOptions::default()));
+        assert!(matches!(result, crate::NavigationResult::Blocked { .. }));
+        assert!(window.is_none());
+        assert_eq!(cx.read(|cx| cx.windows().len()), windows);
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+    }
+
+    #[test]
+    fn test_modifier_reads_its_key() {
+        let ctrl = Modifiers {
+            control: true,
+            ..Modifiers::default()
+        };
+        assert!(Modifier::Ctrl.is_held(ctrl));
+        assert!(!Modifier::Platform.is_held(ctrl));
+        assert!(!Modifier::Shift.is_held(Modifiers::default()));
+    }
+}
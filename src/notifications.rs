@@ -0,0 +1,394 @@
+//! Navigation outcomes worth telling the user about.
+//!
+//! A navigation a guard blocks, or one to a path no route matches, changes
+//! nothing on screen. After
+//! [`GlobalRouter::enable_outcome_notifications`](crate::GlobalRouter::enable_outcome_notifications)
+//! the router keeps the latest such outcome as a [`RouterNotification`]:
+//! drop [`router_notification_banner`](crate::router_notification_banner)
+//! into the root layout once, or read
+//! [`current_notification`](crate::GlobalRouter::current_notification) to
+//! feed your own toasts.
+//!
+//! ```
+//! use gpui_navigator::notifications::NotificationConfig;
+//! use gpui_navigator::GlobalRouter;
+//! use std::time::Duration;
+//!
+//! let mut router = GlobalRouter::new();
+//! router.enable_outcome_notifications(NotificationConfig {
+//!     show_redirects: true,
+//!     auto_dismiss: Duration::from_secs(3),
+//!     ..NotificationConfig::default()
+//! });
+//! assert!(router.current_notification().is_none());
+//! ```
+//!
+//! A blocked navigation reports the guard's or lifecycle hook's reason
+//! verbatim. The next successful navigation replaces whatever is shown, and
+//! a notification expires once shown for
+//! [`auto_dismiss`](NotificationConfig::auto_dismiss).
+
+use crate::error::{NavigationError, NavigationResult};
+use std::time::{Duration, Instant};
+
+/// Which outcomes become notifications, and for how long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotificationConfig {
+    /// Report navigations blocked by guards or lifecycle hooks
+    pub show_blocked: bool,
+    /// Report navigations to paths no route matches
+    pub show_not_found: bool,
+    /// Report navigations that landed elsewhere after a redirect
+    pub show_redirects: bool,
+    /// How long a notification stays current
+    pub auto_dismiss: Duration,
+}
+
+impl Default for NotificationConfig {
+    /// Blocked and not-found navigations, dismissed after five seconds.
+    fn default() -> Self {
+        Self {
+            show_blocked: true,
+            show_not_found: true,
+            show_redirects: false,
+            auto_dismiss: Duration::from_secs(5),
+        }
+    }
+}
+
+/// What a [`RouterNotification`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NotificationKind {
+    /// A guard or lifecycle hook blocked the navigation
+    Blocked,
+    /// No route matches the path
+    NotFound,
+    /// The navigation was redirected and succeeded elsewhere
+    Redirected,
+}
+
+/// A navigation outcome to show the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouterNotification {
+    /// Distinguishes notifications recorded for the same path
+    pub id: u64,
+    /// What happened
+    pub kind: NotificationKind,
+    /// Text to show; a block reason as the guard or hook gave it
+    pub message: String,
+    /// The path the navigation asked for
+    pub path: String,
+    /// When the outcome was recorded
+    pub created_at: Instant,
+}
+
+/// The latest notification and the config it was recorded under.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Notifications {
+    /// `None` while notifications are disabled
+    config: Option<NotificationConfig>,
+    current: Option<RouterNotification>,
+    next_id: u64,
+    /// Notification whose auto-dismiss timer is already running
+    timer_armed: Option<u64>,
+}
+
+impl Notifications {
+    pub(crate) fn enable(&mut self, config: NotificationConfig) {
+        self.config = Some(config);
+    }
+
+    pub(crate) fn disable(&mut self) {
+        self.config = None;
+        self.current = None;
+    }
+
+    /// Record the outcome of a navigation to `requested`, which went
+    /// through at least one redirect if `redirected`.
+    pub(crate) fn record(
+        &mut self,
+        requested: &str,
+        result: &NavigationResult,
+        redirected: bool,
+        now: Instant,
+    ) {
+        let Some(config) = self.config else {
+            return;
+        };
+        let outcome = match result {
+            NavigationResult::Success { path } => {
+                // A later success makes any earlier outcome stale
+                self.current = None;
+                (redirected && config.show_redirects).then(|| {
+                    (
+                        NotificationKind::Redirected,
+                        format!("Redirected to {path}"),
+                    )
+                })
+            }
+            NavigationResult::Blocked { reason, .. } => config
+                .show_blocked
+                .then(|| (NotificationKind::Blocked, reason.clone())),
+            NavigationResult::NotFound { path }
+            | NavigationResult::Error(NavigationError::RouteNotFound { path }) => config
+                .show_not_found
+                .then(|| (NotificationKind::NotFound, format!("No page at {path}"))),
+            NavigationResult::Challenged { .. } | NavigationResult::Error(_) => None,
+        };
+        let Some((kind, message)) = outcome else {
+            return;
+        };
+        self.next_id += 1;
+        self.current = Some(RouterNotification {
+            id: self.next_id,
+            kind,
+            message,
+            path: requested.to_string(),
+            created_at: now,
+        });
+    }
+
+    /// The notification still current at `now`.
+    pub(crate) fn current(&self, now: Instant) -> Option<&RouterNotification> {
+        let config = self.config?;
+        self.current.as_ref().filter(|notification| {
+            now.duration_since(notification.created_at) < config.auto_dismiss
+        })
+    }
+
+    /// Drop the current notification; returns whether there was one.
+    pub(crate) fn dismiss(&mut self) -> bool {
+        self.current.take().is_some()
+    }
+
+    /// Drop notification `id` if it is still the current one.
+    pub(crate) fn expire(&mut self, id: u64) -> bool {
+        if self.current.as_ref().map(|notification| notification.id) != Some(id) {
+            return false;
+        }
+        self.current = None;
+        true
+    }
+
+    /// Id and remaining lifetime of the current notification, the first
+    /// time it is asked for, so its auto-dismiss timer starts once.
+    pub(crate) fn arm_timer(&mut self, now: Instant) -> Option<(u64, Duration)> {
+        let notification = self.current(now)?;
+        if self.timer_armed == Some(notification.id) {
+            return None;
+        }
+        let id = notification.id;
+        let remaining = self
+            .config?
+            .auto_dismiss
+            .saturating_sub(now.duration_since(notification.created_at));
+        self.timer_armed = Some(id);
+        Some((id, remaining))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+
+    fn enabled(config: NotificationConfig) -> Notifications {
+        let mut notifications = Notifications::default();
+        notifications.enable(config);
+        notifications
+    }
+
+    fn blocked(reason: &str) -> NavigationResult {
+        NavigationResult::Blocked {
+            reason: reason.to_string(),
+            redirect: None,
+        }
+    }
+
+    #[test]
+    fn test_blocked_and_not_found_recorded_until_success() {
+        let mut notifications = enabled(NotificationConfig::default());
+        let now = Instant::now();
+
+        notifications.record("/admin", &blocked("Missing role: admin"), false, now);
+        let current = notifications.current(now).unwrap();
+        assert_eq!(current.kind, NotificationKind::Blocked);
+        assert_eq!(current.message, "Missing role: admin");
+        assert_eq!(current.path, "/admin");
+
+        let missing = NavigationResult::NotFound {
+            path: "/nowhere".to_string(),
+        };
+        notifications.record("/nowhere", &missing, false, now);
+        assert_eq!(
+            notifications.current(now).unwrap().kind,
+            NotificationKind::NotFound
+        );
+
+        let success = NavigationResult::Success {
+            path: "/home".to_string(),
+        };
+        notifications.record("/home", &success, false, now);
+        assert!(notifications.current(now).is_none());
+    }
+
+    #[test]
+    fn test_auto_dismiss_after_configured_duration() {
+        let mut notifications = enabled(NotificationConfig {
+            auto_dismiss: Duration::from_secs(3),
+            ..NotificationConfig::default()
+        });
+        let start = Instant::now();
+        notifications.record("/admin", &blocked("No"), false, start);
+
+        let later = start + Duration::from_secs(1);
+        assert!(notifications.current(later).is_some());
+        let (id, remaining) = notifications.arm_timer(later).unwrap();
+        assert_eq!(remaining, Duration::from_secs(2));
+        // The timer starts once per notification
+        assert_eq!(notifications.arm_timer(later), None);
+
+        assert!(notifications
+            .current(start + Duration::from_secs(3))
+            .is_none());
+        assert!(notifications.expire(id));
+        assert!(!notifications.expire(id));
+    }
+
+    #[test]
+    fn test_redirects_reported_only_when_configured() {
+        let redirected = NavigationResult::Success {
+            path: "/login".to_string(),
+        };
+        let now = Instant::now();
+
+        let mut quiet = enabled(NotificationConfig::default());
+        quiet.record("/account", &redirected, true, now);
+        assert!(quiet.current(now).is_none());
+
+        let mut chatty = enabled(NotificationConfig {
+            show_redirects: true,
+            show_blocked: false,
+            ..NotificationConfig::default()
+        });
+        chatty.record("/account", &redirected, true, now);
+        let current = chatty.current(now).unwrap();
+        assert_eq!(current.kind, NotificationKind::Redirected);
+        assert_eq!(current.message, "Redirected to /login");
+        assert!(chatty.dismiss());
+        chatty.record("/admin", &blocked("No"), false, now);
+        assert!(chatty.current(now).is_none());
+
+        // Disabled: nothing is recorded
+        let mut off = Notifications::default();
+        off.record("/admin", &blocked("No"), false, now);
+        assert!(off.current(now).is_none());
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_outcome_notifications(cx: &mut gpui::TestAppContext) {
+        use crate::test_support::page;
+        use crate::{guard_fn, init_router, GlobalRouter, NavigationAction, Navigator, UseRouter};
+        use gpui::TestAppContext;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.enable_outcome_notifications(NotificationConfig::default());
+                router.add_route(page("/"));
+                router.add_route(page("/home"));
+                router.add_route(
+                    page("/vault")
+                        .guard(guard_fn(|_, _| NavigationAction::deny("Vault is locked"))),
+                );
+                router.add_route(page("/old").redirect_if(|_, _| Some("/home".to_string())));
+            });
+        });
+        let current = |cx: &mut TestAppContext| {
+            cx.read(|cx| {
+                cx.global::<GlobalRouter>()
+                    .current_notification()
+                    .map(|n| (n.kind, n.message.clone(), n.path.clone()))
+            })
+        };
+
+        cx.update(|cx| Navigator::push(cx, "/vault"));
+        assert_eq!(
+            current(cx),
+            Some((
+                NotificationKind::Blocked,
+                "Vault is locked".to_string(),
+                "/vault".to_string()
+            ))
+        );
+
+        cx.update(|cx| Navigator::push(cx, "/nowhere"));
+        assert_eq!(
+            current(cx).map(|(kind, _, path)| (kind, path)),
+            Some((NotificationKind::NotFound, "/nowhere".to_string()))
+        );
+
+        // Success clears it; a redirect is not reported by default
+        cx.update(|cx| Navigator::push(cx, "/old"));
+        assert_eq!(cx.read(Navigator::current_path), "/home");
+        assert_eq!(current(cx), None);
+
+        cx.update(|cx| Navigator::push(cx, "/vault"));
+        assert!(cx.update(|cx| cx.update_router(|router, _| router.dismiss_notification())));
+        assert_eq!(current(cx), None);
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_answered_challenge_notifications(cx: &mut gpui::TestAppContext) {
+        use crate::test_support::page;
+        use crate::{guard_fn, init_router, GlobalRouter, NavigationAction, Navigator};
+        use gpui::TestAppContext;
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.enable_outcome_notifications(NotificationConfig::default());
+                router.add_route(page("/"));
+                router.add_route(page("/vault").guard(guard_fn(|_, request| {
+                    match request.challenge_response() {
+                        None => NavigationAction::challenge("otp", "Enter your code"),
+                        Some("1234") => NavigationAction::Continue,
+                        Some(_) => NavigationAction::deny("Wrong code"),
+                    }
+                })));
+            });
+        });
+        let current = |cx: &mut TestAppContext| {
+            cx.read(|cx| {
+                cx.global::<GlobalRouter>()
+                    .current_notification()
+                    .map(|n| (n.kind, n.message.clone(), n.path.clone()))
+            })
+        };
+
+        cx.update(|cx| Navigator::push(cx, "/nowhere"));
+        assert_eq!(
+            current(cx).map(|(kind, _, _)| kind),
+            Some(NotificationKind::NotFound)
+        );
+
+        // The denial reached through an answer replaces the stale outcome
+        cx.update(|cx| Navigator::push(cx, "/vault"));
+        cx.update(|cx| Navigator::answer_challenge(cx, "0000"));
+        assert_eq!(
+            current(cx),
+            Some((
+                NotificationKind::Blocked,
+                "Wrong code".to_string(),
+                "/vault".to_string()
+            ))
+        );
+
+        // And an answer that lets the navigation through clears it
+        cx.update(|cx| Navigator::push(cx, "/vault"));
+        cx.update(|cx| Navigator::answer_challenge(cx, "1234"));
+        assert_eq!(cx.read(Navigator::current_path), "/vault");
+        assert_eq!(current(cx), None);
+    }
+}
@@ -0,0 +1,179 @@
+//! Ownership of named outlets.
+//!
+//! Apps that load routes from third parties (plugins, extensions) can keep
+//! them out of the chrome: [`GlobalRouter::reserve_outlet_names`](crate::GlobalRouter::reserve_outlet_names)
+//! hands outlet names like `"titlebar"` to one [`OwnerTag`], and routes
+//! registered with [`GlobalRouter::add_route_tagged`](crate::GlobalRouter::add_route_tagged)
+//! under another tag may not fill them.
+//!
+//! ```
+//! use gpui::IntoElement;
+//! use gpui_navigator::outlet_policy::OwnerTag;
+//! use gpui_navigator::{GlobalRouter, Route};
+//! use std::sync::Arc;
+//!
+//! let page = |path: &str| Route::view(path, || gpui::div().into_any_element());
+//! let mut router = GlobalRouter::new();
+//! router.reserve_outlet_names(["titlebar", "statusbar"], &OwnerTag::new("core"));
+//!
+//! let plugin = page("/plugin").named_outlet("titlebar", vec![Arc::new(page(""))]);
+//! let rejected = router.add_route_tagged(plugin, OwnerTag::new("plugin"));
+//! assert_eq!(rejected.unwrap_err().outlet, "titlebar");
+//! ```
+//!
+//! Routes registered with plain [`add_route`](crate::GlobalRouter::add_route)
+//! belong to [`OwnerTag::APP`], which may fill every outlet. Reservations
+//! made after a route was registered are enforced when named outlets
+//! resolve: a named child contributed by a non-owning tag is skipped.
+
+use crate::error::ReservedOutletError;
+use crate::nested::build_child_path;
+use crate::resolve::MatchStack;
+use crate::route::Route;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Who registered a route.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OwnerTag(Cow<'static, str>);
+
+impl OwnerTag {
+    /// The application itself, permitted to fill every outlet.
+    pub const APP: Self = Self(Cow::Borrowed("app"));
+
+    /// Create a tag.
+    #[must_use]
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self(name.into())
+    }
+
+    /// Return the tag's name.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for OwnerTag {
+    fn default() -> Self {
+        Self::APP
+    }
+}
+
+impl fmt::Display for OwnerTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Reserved outlet names and their owners.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OutletPolicy {
+    reserved: HashMap<String, OwnerTag>,
+}
+
+impl OutletPolicy {
+    /// Reserve `name` for `owner`, replacing an earlier reservation.
+    pub(crate) fn reserve(&mut self, name: String, owner: OwnerTag) {
+        self.reserved.insert(name, owner);
+    }
+
+    /// Check whether routes of `tag` may fill the outlet `name`.
+    pub(crate) fn permits(&self, name: &str, tag: &OwnerTag) -> bool {
+        *tag == OwnerTag::APP || !self.reserved.get(name).is_some_and(|owner| owner != tag)
+    }
+
+    /// Check `route` and its descendants, registered under `tag`.
+    pub(crate) fn check(&self, route: &Route, tag: &OwnerTag) -> Result<(), ReservedOutletError> {
+        self.check_tree(route, "/", tag)
+    }
+
+    fn check_tree(
+        &self,
+        route: &Route,
+        parent: &str,
+        tag: &OwnerTag,
+    ) -> Result<(), ReservedOutletError> {
+        let path = build_child_path(parent, &route.config.path);
+        for (name, children) in &route.named_children {
+            if !self.permits(name, tag) {
+                return Err(ReservedOutletError {
+                    route: path.into_owned(),
+                    outlet: name.clone(),
+                    owner: self.reserved[name].clone(),
+                    tag: tag.clone(),
+                });
+            }
+            for child in children {
+                self.check_tree(child, &path, tag)?;
+            }
+        }
+        for child in &route.children {
+            self.check_tree(child, &path, tag)?;
+        }
+        Ok(())
+    }
+}
+
+/// Tag of the top-level route of `stack`, which its descendants share.
+pub(crate) fn stack_owner(stack: &MatchStack) -> OwnerTag {
+    stack
+        .at_depth(0)
+        .and_then(|root| root.route.owner.clone())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::test_support::page;
+    use crate::GlobalRouter;
+    use gpui::TestAppContext;
+
+    #[test]
+    fn test_add_route_tagged_rejects_reserved_outlet() {
+        let mut router = GlobalRouter::new();
+        router.reserve_outlet_names(["titlebar"], &OwnerTag::new("core"));
+
+        let plugin =
+            page("/plugin").child(page("panel").named_outlet("titlebar", vec![page("").into()]));
+        let error = router
+            .add_route_tagged(plugin, OwnerTag::new("plugin"))
+            .unwrap_err();
+        assert_eq!(error.route, "/plugin/panel");
+        assert_eq!(error.outlet, "titlebar");
+        assert_eq!(error.owner, OwnerTag::new("core"));
+        assert!(router.state().routes().is_empty());
+
+        // The owner itself and the app may fill it; other outlets stay open
+        let core = page("/core").named_outlet("titlebar", vec![page("").into()]);
+        assert!(router.add_route_tagged(core, OwnerTag::new("core")).is_ok());
+        router.add_route(page("/app").named_outlet("titlebar", vec![page("").into()]));
+        let sidebar = page("/extra").named_outlet("sidebar", vec![page("").into()]);
+        assert!(router
+            .add_route_tagged(sidebar, OwnerTag::new("plugin"))
+            .is_ok());
+        assert_eq!(router.state().routes().len(), 3);
+    }
+
+    #[gpui::test]
+    fn test_late_reservation_filters_named_outlet(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let mut router = GlobalRouter::new();
+            router.add_route(page("/app").named_outlet("titlebar", vec![page("").into()]));
+            let plugin = page("/plugin").named_outlet("titlebar", vec![page("").into()]);
+            assert!(router
+                .add_route_tagged(plugin, OwnerTag::new("plugin"))
+                .is_ok());
+            router.reserve_outlet_names(["titlebar"], &OwnerTag::new("core"));
+
+            assert!(router.push("/plugin".to_string(), cx).is_success());
+            assert!(router.resolve_named_outlet(1, "titlebar").is_none());
+
+            assert!(router.push("/app".to_string(), cx).is_success());
+            assert!(router.resolve_named_outlet(1, "titlebar").is_some());
+        });
+    }
+}
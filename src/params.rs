@@ -26,7 +26,11 @@
 //! assert_eq!(query.get("sort"), Some(&"name".to_string()));
 //! ```
 
+use crate::pattern::Pattern;
+use std::any::{type_name, TypeId};
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::Index;
 
 /// Route parameters extracted from path segments
 ///
@@ -43,9 +47,16 @@ use std::collections::HashMap;
 /// assert_eq!(params.get("id"), Some(&"123".to_string()));
 /// assert_eq!(params.get_as::<i32>("id"), Some(123));
 /// ```
+///
+/// Params resolved for a nested route also remember what each ancestor
+/// level captured, so a value shadowed by a child (or hidden by
+/// [`Route::isolate_params`](crate::Route::isolate_params)) stays
+/// reachable through [`ancestor`](Self::ancestor).
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct RouteParams {
     params: HashMap<String, String>,
+    /// Params captured by each ancestor level, indexed by depth.
+    ancestors: Vec<HashMap<String, String>>,
 }
 
 impl RouteParams {
@@ -59,7 +70,41 @@ impl RouteParams {
     /// Create from an existing `HashMap`.
     #[must_use] 
     pub const fn from_map(params: HashMap<String, String>) -> Self {
-        Self { params }
+        Self {
+            params,
+            ancestors: Vec::new(),
+        }
+    }
+
+    /// Start building params checked against the `:params` of `pattern`.
+    ///
+    /// The pattern is parsed once; every [`set`](RouteParamsBuilder::set)
+    /// rejects a key it does not declare, catching `userId` vs `userid`
+    /// typos before they reach `push_named`. A pattern that does not parse
+    /// declares nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gpui_navigator::RouteParams;
+    ///
+    /// let params = RouteParams::for_route("/users/:userId")
+    ///     .set("userId", "42")
+    ///     .unwrap()
+    ///     .build();
+    /// assert_eq!(params.get("userId"), Some(&"42".to_string()));
+    ///
+    /// let err = RouteParams::for_route("/users/:userId").set("userid", "42").unwrap_err();
+    /// assert_eq!(err.key, "userid");
+    /// ```
+    pub fn for_route(pattern: &str) -> RouteParamsBuilder {
+        let declared = Pattern::parse(pattern)
+            .map(|pattern| pattern.param_names().map(str::to_string).collect())
+            .unwrap_or_default();
+        RouteParamsBuilder {
+            declared,
+            params: Self::new(),
+        }
     }
 
     /// Get a parameter value by key.
@@ -79,6 +124,25 @@ impl RouteParams {
         self.params.get(key)?.parse().ok()
     }
 
+    /// Get the param of a [`RouteParamType`], parsed from its key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParamTypeError::Missing`] if there is no value under
+    /// [`T::KEY`](RouteParamType::KEY), or [`ParamTypeError::Invalid`] if the
+    /// value does not parse.
+    pub fn get_typed<T: RouteParamType>(&self) -> Result<T, ParamTypeError> {
+        let value = self
+            .params
+            .get(T::KEY)
+            .ok_or(ParamTypeError::Missing { key: T::KEY })?;
+        T::parse(value).map_err(|reason| ParamTypeError::Invalid {
+            key: T::KEY,
+            value: value.clone(),
+            reason,
+        })
+    }
+
     /// Insert or overwrite a parameter.
     pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
         self.params.insert(key.into(), value.into());
@@ -95,6 +159,67 @@ impl RouteParams {
         self.params.contains_key(key)
     }
 
+    /// Get a parameter value by key, ignoring ASCII case.
+    ///
+    /// An exact match is returned when present; otherwise this scans every
+    /// parameter, so it is **O(n)** in the number of params. If several keys
+    /// differ only in case, the lexicographically smallest one wins.
+    ///
+    /// Intended for migrating code that gets param casing wrong and for
+    /// external systems with their own conventions — prefer [`get`](Self::get).
+    #[must_use]
+    pub fn get_ci(&self, key: &str) -> Option<&String> {
+        if let Some(value) = self.params.get(key) {
+            return Some(value);
+        }
+        self.params
+            .iter()
+            .filter(|(k, _)| k.eq_ignore_ascii_case(key))
+            .min_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, v)| v)
+    }
+
+    /// Return `true` if a key matching `key` ignoring ASCII case is present.
+    ///
+    /// **O(n)** in the number of params, like [`get_ci`](Self::get_ci).
+    #[must_use]
+    pub fn contains_ci(&self, key: &str) -> bool {
+        self.params.contains_key(key) || self.params.keys().any(|k| k.eq_ignore_ascii_case(key))
+    }
+
+    /// Get the value `key` had at the nearest ancestor level that captured it.
+    ///
+    /// Unlike [`get`](Self::get), this skips the route's own params and
+    /// ignores overrides, so under `/posts/:id` → `comments/:id` the
+    /// comment builder sees the comment id via `get("id")` and the post id
+    /// via `ancestor("id")`.
+    #[must_use]
+    pub fn ancestor(&self, key: &str) -> Option<&String> {
+        self.ancestors.iter().rev().find_map(|frame| frame.get(key))
+    }
+
+    /// Get the value `key` was captured with by the ancestor at `depth`.
+    #[must_use]
+    pub fn ancestor_at(&self, depth: usize, key: &str) -> Option<&String> {
+        self.ancestors.get(depth)?.get(key)
+    }
+
+    /// Params as a level below this one inherits them, with `frame` (what
+    /// this level captured) appended to the ancestor chain.
+    pub(crate) fn descend(&self, frame: &Self) -> Self {
+        let mut child = self.clone();
+        child.ancestors.push(frame.params.clone());
+        child
+    }
+
+    /// The ancestor chain alone, without any inherited values.
+    pub(crate) fn isolated(&self) -> Self {
+        Self {
+            params: HashMap::new(),
+            ancestors: self.ancestors.clone(),
+        }
+    }
+
     /// Get a reference to the underlying parameter map.
     #[must_use] 
     pub const fn all(&self) -> &HashMap<String, String> {
@@ -111,6 +236,36 @@ impl RouteParams {
         self.params.iter()
     }
 
+    /// Return the params as `(key, value)` pairs sorted by key.
+    ///
+    /// The inverse of the `From<Vec<(String, String)>>` conversion. Only the
+    /// merged view is kept; values shadowed in
+    /// [`ancestor`](Self::ancestor) levels are dropped.
+    #[must_use]
+    pub fn into_pairs(self) -> Vec<(String, String)> {
+        let mut pairs: Vec<_> = self.params.into_iter().collect();
+        pairs.sort_unstable();
+        pairs
+    }
+
+    /// The params as `key=value` pairs sorted by key and joined with `&`,
+    /// both sides encoded, so equal params always give the same string.
+    pub(crate) fn canonical(&self) -> String {
+        let mut pairs: Vec<_> = self.params.iter().collect();
+        pairs.sort_unstable();
+        pairs
+            .into_iter()
+            .map(|(key, value)| {
+                format!(
+                    "{}={}",
+                    encode_uri_component(key),
+                    encode_uri_component(value)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
     /// Return `true` if there are no parameters.
     #[must_use] 
     pub fn is_empty(&self) -> bool {
@@ -165,6 +320,9 @@ impl RouteParams {
     /// - `:paramName` - Dynamic segment that matches any value
     /// - `literal` - Static segment that must match exactly
     ///
+    /// See [`Pattern`] for the full syntax. A pattern that does not parse
+    /// matches nothing.
+    ///
     /// # Example
     ///
     /// ```
@@ -183,33 +341,256 @@ impl RouteParams {
     /// ```
     #[must_use] 
     pub fn from_path(path: &str, pattern: &str) -> Self {
-        let mut params = Self::new();
+        let Ok(pattern) = Pattern::parse(pattern) else {
+            return Self::new();
+        };
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        // Must consume every segment
+        match pattern.match_segments(&segments) {
+            Some((consumed, params)) if consumed == segments.len() => params,
+            _ => Self::new(),
+        }
+    }
+}
+
+impl Index<&str> for RouteParams {
+    type Output = String;
+
+    /// Get the value of a param the route is known to capture.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is absent. The message names the key and the keys
+    /// that are present; use [`get`](Self::get) when absence is possible.
+    fn index(&self, key: &str) -> &String {
+        self.params.get(key).unwrap_or_else(|| {
+            let mut available: Vec<&str> = self.params.keys().map(String::as_str).collect();
+            available.sort_unstable();
+            panic!("route param '{key}' is not present (available: {available:?})")
+        })
+    }
+}
+
+impl From<Vec<(String, String)>> for RouteParams {
+    /// Collect `(key, value)` pairs; a key given twice keeps its last value.
+    fn from(pairs: Vec<(String, String)>) -> Self {
+        Self::from_map(pairs.into_iter().collect())
+    }
+}
+
+// ============================================================================
+// Checked builder
+// ============================================================================
 
-        // Split path and pattern into segments
-        let path_segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
-        let pattern_segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+/// Builds [`RouteParams`] accepting only the keys a route pattern declares.
+///
+/// Created by [`RouteParams::for_route`].
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct RouteParamsBuilder {
+    /// Param names the pattern declares, in pattern order.
+    declared: Vec<String>,
+    params: RouteParams,
+}
 
-        // Must have same number of segments
-        if path_segments.len() != pattern_segments.len() {
-            return params;
+impl RouteParamsBuilder {
+    /// Set a declared param.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UnknownParam`] if the pattern does not declare `key`.
+    pub fn set(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Result<Self, UnknownParam> {
+        let key = key.into();
+        if !self.declared.contains(&key) {
+            return Err(UnknownParam {
+                key,
+                declared: self.declared,
+            });
         }
+        self.params.insert(key, value);
+        Ok(self)
+    }
+
+    /// Names of the params the pattern declares, in pattern order.
+    #[must_use]
+    pub fn declared(&self) -> &[String] {
+        &self.declared
+    }
+
+    /// Finish building.
+    #[must_use]
+    pub fn build(self) -> RouteParams {
+        self.params
+    }
+}
+
+/// A key passed to [`RouteParamsBuilder::set`] that the pattern does not declare.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownParam {
+    /// The rejected key.
+    pub key: String,
+    /// Param names the pattern declares, in pattern order.
+    pub declared: Vec<String>,
+}
+
+impl fmt::Display for UnknownParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Route param '{}' is not declared (declared: {})",
+            self.key,
+            self.declared.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownParam {}
+
+// ============================================================================
+// Param types
+// ============================================================================
+
+/// A domain type one route param parses into, such as a `UserId` newtype.
+///
+/// Read it with [`RouteParams::get_typed`], or with
+/// [`Navigator::param`](crate::Navigator::param), which also checks that the
+/// type is registered. Registered with
+/// [`GlobalRouter::register_param_type`](crate::GlobalRouter::register_param_type),
+/// the type validates its param during navigation too.
+///
+/// ```
+/// use gpui_navigator::{RouteParamType, RouteParams};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct UserId(u64);
+///
+/// impl RouteParamType for UserId {
+///     const KEY: &'static str = "userId";
+///
+///     fn parse(value: &str) -> Result<Self, String> {
+///         value.parse().map(UserId).map_err(|e| format!("{e}"))
+///     }
+/// }
+///
+/// let params = RouteParams::from_path("/users/42", "/users/:userId");
+/// assert_eq!(params.get_typed::<UserId>(), Ok(UserId(42)));
+/// ```
+pub trait RouteParamType: Sized + 'static {
+    /// Name of the param the type is read from.
+    const KEY: &'static str;
+
+    /// Parse a param value.
+    ///
+    /// # Errors
+    ///
+    /// Returns why `value` is not a valid `Self`.
+    fn parse(value: &str) -> Result<Self, String>;
+}
 
-        // Match each segment
-        for (path_seg, pattern_seg) in path_segments.iter().zip(pattern_segments.iter()) {
-            if let Some(param_name) = pattern_seg.strip_prefix(':') {
-                // Dynamic segment - extract parameter
-                // Handle type constraints like :id<i32> -> extract "id"
-                let param_name = param_name
-                    .find('<')
-                    .map_or(param_name, |pos| &param_name[..pos]);
-                params.insert(param_name.to_string(), (*path_seg).to_string());
-            } else if pattern_seg != path_seg {
-                // Static segment mismatch - no match
-                return Self::new();
+/// Why a [`RouteParamType`] could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParamTypeError {
+    /// The type is not registered with
+    /// [`GlobalRouter::register_param_type`](crate::GlobalRouter::register_param_type).
+    NotRegistered {
+        /// The type's param name.
+        key: &'static str,
+        /// Name of the type.
+        type_name: &'static str,
+    },
+    /// The params have no value under the type's key.
+    Missing {
+        /// The type's param name.
+        key: &'static str,
+    },
+    /// The value did not parse.
+    Invalid {
+        /// The type's param name.
+        key: &'static str,
+        /// Value that was rejected.
+        value: String,
+        /// Why the type rejected it.
+        reason: String,
+    },
+}
+
+impl fmt::Display for ParamTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotRegistered { key, type_name } => write!(
+                f,
+                "Route param type '{type_name}' (param '{key}') is not registered"
+            ),
+            Self::Missing { key } => write!(f, "Route param '{key}' is not present"),
+            Self::Invalid { key, value, reason } => {
+                write!(f, "Invalid route param '{key}' = '{value}': {reason}")
             }
         }
+    }
+}
+
+impl std::error::Error for ParamTypeError {}
+
+/// [`RouteParamType`]s registered with the router, by key.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ParamTypes {
+    by_key: HashMap<&'static str, RegisteredType>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RegisteredType {
+    type_id: TypeId,
+    type_name: &'static str,
+    validate: fn(&str) -> Result<(), String>,
+}
+
+impl ParamTypes {
+    /// Register `T`, returning the name of a type it replaces under the same key.
+    pub(crate) fn register<T: RouteParamType>(&mut self) -> Option<&'static str> {
+        let registered = RegisteredType {
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+            validate: |value| T::parse(value).map(drop),
+        };
+        self.by_key
+            .insert(T::KEY, registered)
+            .map(|replaced| replaced.type_name)
+    }
+
+    /// Whether `T` is the type registered under its key.
+    pub(crate) fn is_registered<T: RouteParamType>(&self) -> bool {
+        self.by_key
+            .get(T::KEY)
+            .is_some_and(|registered| registered.type_id == TypeId::of::<T>())
+    }
 
-        params
+    /// Whether a type is registered under `key`.
+    pub(crate) fn contains(&self, key: &str) -> bool {
+        self.by_key.contains_key(key)
+    }
+
+    /// Check `value` against the type registered under `key`, if any.
+    pub(crate) fn validate(&self, key: &str, value: &str) -> Result<(), String> {
+        self.by_key
+            .get(key)
+            .map_or(Ok(()), |registered| (registered.validate)(value))
+    }
+
+    /// Read `T` from `params`, if `T` is registered.
+    pub(crate) fn get<T: RouteParamType>(&self, params: &RouteParams) -> Result<T, ParamTypeError> {
+        if !self.is_registered::<T>() {
+            return Err(ParamTypeError::NotRegistered {
+                key: T::KEY,
+                type_name: type_name::<T>(),
+            });
+        }
+        params.get_typed()
     }
 }
 
@@ -218,8 +599,12 @@ impl RouteParams {
 // ============================================================================
 
 #[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
 mod tests {
     use super::*;
+    use crate::test_support::page;
+    use crate::{init_router, Navigator, UseRouter};
+    use gpui::TestAppContext;
 
     // Route parameters tests
 
@@ -233,6 +618,33 @@ mod tests {
         assert!(!params.contains("missing"));
     }
 
+    #[test]
+    fn test_route_params_get_ci() {
+        let mut params = RouteParams::new();
+        params.insert("userId", "42");
+
+        assert_eq!(params.get_ci("userid"), Some(&"42".to_string()));
+        assert_eq!(params.get_ci("USERID"), Some(&"42".to_string()));
+        assert_eq!(params.get_ci("userId"), Some(&"42".to_string()));
+        assert!(params.get_ci("user").is_none());
+        assert!(params.get("userid").is_none());
+
+        assert!(params.contains_ci("userid"));
+        assert!(params.contains_ci("USERID"));
+        assert!(!params.contains_ci("user_id"));
+    }
+
+    #[test]
+    fn test_route_params_get_ci_prefers_exact_match() {
+        let mut params = RouteParams::new();
+        params.insert("userid", "lower");
+        params.insert("userId", "camel");
+
+        assert_eq!(params.get_ci("userId"), Some(&"camel".to_string()));
+        assert_eq!(params.get_ci("userid"), Some(&"lower".to_string()));
+        assert_eq!(params.get_ci("USERID"), Some(&"camel".to_string()));
+    }
+
     #[test]
     fn test_route_params_get_as() {
         let mut params = RouteParams::new();
@@ -245,6 +657,84 @@ mod tests {
         assert_eq!(params.get_as::<i32>("missing"), None);
     }
 
+    #[derive(Debug, PartialEq)]
+    struct UserId(u64);
+
+    impl RouteParamType for UserId {
+        const KEY: &'static str = "userId";
+
+        fn parse(value: &str) -> Result<Self, String> {
+            value.parse().map(UserId).map_err(|e| format!("{e}"))
+        }
+    }
+
+    #[test]
+    fn test_route_params_get_typed() {
+        let mut params = RouteParams::new();
+        assert_eq!(
+            params.get_typed::<UserId>(),
+            Err(ParamTypeError::Missing { key: "userId" })
+        );
+
+        params.insert("userId", "42");
+        assert_eq!(params.get_typed::<UserId>(), Ok(UserId(42)));
+
+        params.insert("userId", "abc");
+        let error = params.get_typed::<UserId>().unwrap_err();
+        assert!(matches!(
+            &error,
+            ParamTypeError::Invalid { key: "userId", value, .. } if value == "abc"
+        ));
+        assert!(error.to_string().contains("'userId' = 'abc'"));
+    }
+
+    #[test]
+    fn test_param_types_registry() {
+        let mut types = ParamTypes::default();
+        let params = RouteParams::from_path("/users/7", "/users/:userId");
+        let error = types.get::<UserId>(&params).unwrap_err();
+        assert!(matches!(
+            error,
+            ParamTypeError::NotRegistered { key: "userId", .. }
+        ));
+        assert!(error.to_string().contains("UserId"));
+
+        assert_eq!(types.register::<UserId>(), None);
+        assert!(types.contains("userId"));
+        assert_eq!(types.get::<UserId>(&params), Ok(UserId(7)));
+        assert_eq!(types.validate("userId", "7"), Ok(()));
+        assert!(types.validate("userId", "seven").is_err());
+        // Keys without a type pass
+        assert_eq!(types.validate("slug", "anything"), Ok(()));
+    }
+
+    #[gpui::test]
+    fn test_param_requires_registered_type(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/users/:userId"));
+            });
+        });
+        cx.update(|cx| Navigator::push(cx, "/users/42"));
+
+        let error = cx.read(Navigator::param::<UserId>).unwrap_err();
+        assert!(matches!(
+            error,
+            ParamTypeError::NotRegistered { key: "userId", .. }
+        ));
+        assert!(error.to_string().contains("is not registered"));
+
+        cx.update(|cx| cx.update_router(|router, _| router.register_param_type::<UserId>()));
+        assert_eq!(cx.read(Navigator::param::<UserId>), Ok(UserId(42)));
+
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert_eq!(
+            cx.read(Navigator::param::<UserId>),
+            Err(ParamTypeError::Missing { key: "userId" })
+        );
+    }
+
     #[test]
     fn test_route_params_from_map() {
         let mut map = HashMap::new();
@@ -257,6 +747,34 @@ mod tests {
         assert_eq!(params.get_as::<i32>("age"), Some(30));
     }
 
+    #[test]
+    fn test_route_params_pairs_round_trip() {
+        let pairs = vec![
+            ("tab".to_string(), "posts".to_string()),
+            ("id".to_string(), "42".to_string()),
+            ("org".to_string(), "acme".to_string()),
+        ];
+        let params = RouteParams::from(pairs.clone());
+        assert_eq!(params.len(), 3);
+        assert_eq!(params.get("id"), Some(&"42".to_string()));
+
+        // Sorted by key, every key kept
+        let mut sorted = pairs;
+        sorted.sort();
+        assert_eq!(params.clone().into_pairs(), sorted);
+        assert_eq!(RouteParams::from(params.clone().into_pairs()), params);
+
+        // A repeated key keeps its last value
+        let params = RouteParams::from(vec![
+            ("id".to_string(), "1".to_string()),
+            ("id".to_string(), "2".to_string()),
+        ]);
+        assert_eq!(
+            params.into_pairs(),
+            vec![("id".to_string(), "2".to_string())]
+        );
+    }
+
     #[test]
     fn test_route_params_set() {
         let mut params = RouteParams::new();
@@ -286,6 +804,51 @@ mod tests {
         assert_eq!(count, 2);
     }
 
+    #[test]
+    fn test_route_params_index() {
+        let mut params = RouteParams::new();
+        params.insert("id", "42");
+        params.insert("slug", "intro");
+
+        assert_eq!(params["id"], "42");
+
+        let panic = std::panic::catch_unwind(|| params["missing"].clone()).unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert_eq!(
+            message,
+            "route param 'missing' is not present (available: [\"id\", \"slug\"])"
+        );
+    }
+
+    #[test]
+    fn test_for_route_accepts_declared_keys() {
+        let params = RouteParams::for_route("/users/:userId/posts/:postId")
+            .set("userId", "1")
+            .and_then(|builder| builder.set("postId", "2"))
+            .unwrap()
+            .build();
+        assert_eq!(params.get("userId"), Some(&"1".to_string()));
+        assert_eq!(params.get("postId"), Some(&"2".to_string()));
+    }
+
+    #[test]
+    fn test_for_route_rejects_undeclared_key() {
+        let err = RouteParams::for_route("/users/:userId")
+            .set("userid", "1")
+            .unwrap_err();
+        assert_eq!(
+            err,
+            UnknownParam {
+                key: "userid".into(),
+                declared: vec!["userId".into()],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Route param 'userid' is not declared (declared: userId)"
+        );
+    }
+
     #[test]
     fn test_route_params_empty() {
         let params = RouteParams::new();
@@ -446,7 +1009,7 @@ impl QueryParams {
 ///
 /// Encodes non-unreserved characters as percent-encoded UTF-8 bytes,
 /// correctly handling multi-byte Unicode characters.
-fn encode_uri_component(s: &str) -> String {
+pub(crate) fn encode_uri_component(s: &str) -> String {
     use std::fmt::Write;
     let mut result = String::with_capacity(s.len());
     for c in s.chars() {
@@ -463,24 +1026,110 @@ fn encode_uri_component(s: &str) -> String {
     result
 }
 
+/// Encode a path segment for a canonical URL.
+///
+/// Like [`encode_uri_component`] but keeps the sub-delimiters, `:` and `@`
+/// a path segment may carry as they are, since static route segments match
+/// the path literally. `/`, `?`, `#` and `%` are always encoded.
+pub(crate) fn encode_path_segment(s: &str) -> String {
+    use std::fmt::Write;
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '_' | '.' | '~' => result.push(c),
+            '!' | '$' | '&' | '\'' | '(' | ')' | '*' | '+' | ',' | ';' | '=' | ':' | '@' => {
+                result.push(c);
+            }
+            _ => {
+                let mut buf = [0u8; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    let _ = write!(result, "%{byte:02X}");
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Encode a param value as a path segment, the inverse of
+/// [`decode_param_value`].
+///
+/// Like [`encode_path_segment`], except that the `%2F` and `%5C` escapes a
+/// captured value keeps are passed through, so filling a pattern with the
+/// params it captured gives back the same path.
+pub(crate) fn encode_param_value(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(at) = rest.find('%') {
+        result.push_str(&encode_path_segment(&rest[..at]));
+        let separator = rest
+            .get(at + 1..at + 3)
+            .filter(|hex| hex.eq_ignore_ascii_case("2f") || hex.eq_ignore_ascii_case("5c"));
+        if let Some(hex) = separator {
+            result.push('%');
+            result.push_str(hex);
+            rest = &rest[at + 3..];
+        } else {
+            result.push_str("%25");
+            rest = &rest[at + 1..];
+        }
+    }
+    result.push_str(&encode_path_segment(rest));
+    result
+}
+
 /// Simple URI component decoding
 ///
 /// Decodes percent-encoded UTF-8 byte sequences back into characters.
 /// Also handles `+` as space (form encoding).
-fn decode_uri_component(s: &str) -> String {
+pub(crate) fn decode_uri_component(s: &str) -> String {
+    percent_decode(s, true, false)
+}
+
+/// Decode a path segment captured as a param value.
+///
+/// Unlike query values, `+` is literal in a path. Returns the segment
+/// unchanged if it holds no escape.
+pub(crate) fn decode_path_segment(s: &str) -> std::borrow::Cow<'_, str> {
+    if s.contains('%') {
+        std::borrow::Cow::Owned(percent_decode(s, false, false))
+    } else {
+        std::borrow::Cow::Borrowed(s)
+    }
+}
+
+/// Decode a captured param value like [`decode_path_segment`], except that
+/// `%2F` and `%5C` stay encoded: a value never gains a path separator the
+/// matched segments did not have, so `..%2fsecret` cannot become
+/// `../secret` in app code.
+pub(crate) fn decode_param_value(s: &str) -> std::borrow::Cow<'_, str> {
+    if s.contains('%') {
+        std::borrow::Cow::Owned(percent_decode(s, false, true))
+    } else {
+        std::borrow::Cow::Borrowed(s)
+    }
+}
+
+/// Decode `%XX` escapes (and `+` as space if `plus_as_space`), replacing
+/// invalid UTF-8 and keeping malformed escapes as they are. With
+/// `keep_separators`, escaped `/` and `\` are kept as they are too.
+fn percent_decode(s: &str, plus_as_space: bool, keep_separators: bool) -> String {
     let mut bytes = Vec::with_capacity(s.len());
     let mut chars = s.chars();
 
     while let Some(c) = chars.next() {
         if c == '%' {
             let hex: String = chars.by_ref().take(2).collect();
-            if let Ok(byte) = u8::from_str_radix(&hex, 16) {
-                bytes.push(byte);
-            } else {
-                bytes.push(b'%');
-                bytes.extend_from_slice(hex.as_bytes());
+            match u8::from_str_radix(&hex, 16) {
+                Ok(byte) if !(keep_separators && matches!(byte, b'/' | b'\\')) => {
+                    bytes.push(byte);
+                }
+                _ => {
+                    bytes.push(b'%');
+                    bytes.extend_from_slice(hex.as_bytes());
+                }
             }
-        } else if c == '+' {
+        } else if c == '+' && plus_as_space {
             bytes.push(b' ');
         } else {
             let mut buf = [0u8; 4];
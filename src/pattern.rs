@@ -0,0 +1,782 @@
+//! Compiled route path patterns.
+//!
+//! A route path such as `/users/:id<u32>/files/*rest` is parsed once, when
+//! its [`RouteConfig`](crate::RouteConfig) is created, into a [`Pattern`]:
+//! a list of [`Segment`]s that resolution, guard collection, route matching
+//! and URL generation all read, instead of re-splitting the string.
+//!
+//! # Syntax
+//!
+//! | Segment | Meaning |
+//! |---------|---------|
+//! | `users` | Static: matches exactly `users` |
+//! | `:id` | Param: matches any one segment, captured as `id` |
+//! | `:id<u32>` | Param with a constraint (`:id{u32}` is accepted too) |
+//! | `:name.pdf` / `img_:id` | Param with literal text around it: matches `report.pdf`, capturing `report` |
+//! | `*` / `*rest` | Wildcard: matches the rest of the path, captured as `rest` if named |
+//! | *(empty path)* | Empty: the pattern of an index or layout route |
+//!
+//! Leading, trailing and repeated slashes are ignored. Constraints are
+//! recorded on the segment but not enforced by matching. Static segments
+//! and the literals around a param match the path literally, while captured
+//! param and wildcard values are percent-decoded (`%20` becomes a space),
+//! except for escaped separators: `%2F` and `%5C` stay encoded.
+//!
+//! A param name runs up to the first character that is not alphanumeric or
+//! `_`; the rest of the segment is its literal suffix, so `:slug-draft`
+//! matches `intro-draft`, capturing `intro` as `slug`. A segment holds at
+//! most one param.
+//!
+//! ```
+//! use gpui_navigator::pattern::{Pattern, Segment};
+//!
+//! let pattern = Pattern::parse("/users/:id<u32>").unwrap();
+//! assert_eq!(pattern.segments()[1], Segment::Param {
+//!     name: "id".into(),
+//!     constraint: Some("u32".into()),
+//! });
+//!
+//! let (consumed, params) = pattern.match_segments(&["users", "7", "posts"]).unwrap();
+//! assert_eq!(consumed, 2);
+//! assert_eq!(params.get("id"), Some(&"7".to_string()));
+//! ```
+
+use crate::params::{decode_param_value, encode_param_value, RouteParams};
+use gpui::SharedString;
+use std::borrow::Cow;
+use std::fmt;
+
+/// One segment of a compiled [`Pattern`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// Matches exactly this text.
+    Static(SharedString),
+    /// Matches any one segment and captures it.
+    Param {
+        /// Name the segment is captured under.
+        name: SharedString,
+        /// Text between `<` and `>`, if any.
+        constraint: Option<SharedString>,
+    },
+    /// Matches one segment that starts with `prefix` and ends with `suffix`,
+    /// and captures the non-empty text between them.
+    Affixed {
+        /// Literal text before the param.
+        prefix: SharedString,
+        /// Name the text between the literals is captured under.
+        name: SharedString,
+        /// Text between `<` and `>`, if any.
+        constraint: Option<SharedString>,
+        /// Literal text after the param.
+        suffix: SharedString,
+    },
+    /// Matches the rest of the path, zero or more segments.
+    Wildcard {
+        /// Name the rest is captured under, if any.
+        name: Option<SharedString>,
+    },
+    /// The empty pattern: matches without consuming a segment.
+    Empty,
+}
+
+/// Why a path could not be compiled into a [`Pattern`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PatternError {
+    /// A `:` segment has no name before its constraint, or no name at all.
+    EmptyParamName {
+        /// The offending segment.
+        segment: String,
+    },
+    /// A constraint contains another constraint opener.
+    NestedConstraint {
+        /// The offending segment.
+        segment: String,
+    },
+    /// A constraint is not closed at the end of its segment.
+    UnclosedConstraint {
+        /// The offending segment.
+        segment: String,
+    },
+    /// A wildcard is followed by more segments.
+    WildcardNotLast {
+        /// The offending segment.
+        segment: String,
+    },
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyParamName { segment } => {
+                write!(f, "Route parameter name cannot be empty: '{segment}'")
+            }
+            Self::NestedConstraint { segment } => {
+                write!(f, "Route parameter constraint cannot nest: '{segment}'")
+            }
+            Self::UnclosedConstraint { segment } => write!(
+                f,
+                "Route parameter constraint must close at the end of the segment: '{segment}'"
+            ),
+            Self::WildcardNotLast { segment } => {
+                write!(f, "Wildcard must be the last segment: '{segment}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// The params a pattern declares but [`Pattern::fill`] was not given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingParam {
+    /// Names of the missing params, in pattern order.
+    pub names: Vec<String>,
+}
+
+impl fmt::Display for MissingParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Missing route params: {}", self.names.join(", "))
+    }
+}
+
+impl std::error::Error for MissingParam {}
+
+/// A route path compiled into [`Segment`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    segments: Vec<Segment>,
+}
+
+impl Pattern {
+    /// Compile `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PatternError`] for an unnamed param, a nested or unclosed
+    /// constraint, or a wildcard that is not the last segment.
+    pub fn parse(path: &str) -> Result<Self, PatternError> {
+        let raw: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        if raw.is_empty() {
+            return Ok(Self {
+                segments: vec![Segment::Empty],
+            });
+        }
+
+        let mut segments = Vec::with_capacity(raw.len());
+        for (index, segment) in raw.iter().enumerate() {
+            let parsed = parse_segment(segment)?;
+            if matches!(parsed, Segment::Wildcard { .. }) && index + 1 < raw.len() {
+                return Err(PatternError::WildcardNotLast {
+                    segment: (*segment).to_string(),
+                });
+            }
+            segments.push(parsed);
+        }
+        Ok(Self { segments })
+    }
+
+    /// The compiled segments, root first.
+    #[must_use]
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Whether this is the empty pattern of an index or layout route.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.segments == [Segment::Empty]
+    }
+
+    /// Names of the params and named wildcards, in pattern order.
+    pub fn param_names(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Param { name, .. }
+            | Segment::Affixed { name, .. }
+            | Segment::Wildcard {
+                name: Some(name), ..
+            } => Some(name.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// Match the pattern against the start of `segments`.
+    ///
+    /// Returns how many segments it consumed and the params it captured, or
+    /// `None` if it does not match. A wildcard consumes everything left and,
+    /// when named, captures it joined with `/` (nothing if no segment is left).
+    /// Captured values are percent-decoded except for `%2F` and `%5C`;
+    /// static segments match literally.
+    #[must_use]
+    pub fn match_segments(&self, segments: &[&str]) -> Option<(usize, RouteParams)> {
+        let mut params = RouteParams::new();
+        let consumed = self.walk(segments, |name, value| {
+            params.insert(name, decode_param_value(value));
+        })?;
+        Some((consumed, params))
+    }
+
+    /// Whether the pattern matches the start of `segments`, as the path of
+    /// an ancestor route would.
+    #[must_use]
+    pub fn is_prefix_of(&self, segments: &[&str]) -> bool {
+        self.prefix_len(segments).is_some()
+    }
+
+    /// Whether the pattern matches all of `segments`, not only a prefix.
+    #[must_use]
+    pub fn matches_all(&self, segments: &[&str]) -> bool {
+        self.prefix_len(segments) == Some(segments.len())
+    }
+
+    /// How many of `segments` the pattern consumes, without capturing.
+    pub(crate) fn prefix_len(&self, segments: &[&str]) -> Option<usize> {
+        self.walk(segments, |_, _| {})
+    }
+
+    fn walk(&self, segments: &[&str], mut capture: impl FnMut(&str, &str)) -> Option<usize> {
+        let mut consumed = 0;
+        for segment in &self.segments {
+            match segment {
+                Segment::Empty => {}
+                Segment::Wildcard { name } => {
+                    let rest = &segments[consumed..];
+                    if let Some(name) = name {
+                        if !rest.is_empty() {
+                            capture(name, &rest.join("/"));
+                        }
+                    }
+                    return Some(segments.len());
+                }
+                Segment::Static(text) => {
+                    if segments.get(consumed) != Some(&text.as_ref()) {
+                        return None;
+                    }
+                    consumed += 1;
+                }
+                Segment::Param { name, .. } => {
+                    capture(name, segments.get(consumed)?);
+                    consumed += 1;
+                }
+                Segment::Affixed {
+                    prefix,
+                    name,
+                    suffix,
+                    ..
+                } => {
+                    let value = segments
+                        .get(consumed)?
+                        .strip_prefix(prefix.as_ref())?
+                        .strip_suffix(suffix.as_ref())?;
+                    if value.is_empty() {
+                        return None;
+                    }
+                    capture(name, value);
+                    consumed += 1;
+                }
+            }
+        }
+        Some(consumed)
+    }
+
+    /// Build a `/`-rooted URL by substituting `params` into the pattern.
+    ///
+    /// An unnamed wildcard is kept as `*`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the names of the params and named wildcards `params` lacks.
+    pub fn fill(&self, params: &RouteParams) -> Result<String, MissingParam> {
+        self.fill_with(params, false)
+    }
+
+    /// Like [`fill`](Self::fill), but percent-encodes the substituted values.
+    ///
+    /// A param value is encoded as one segment (`/` becomes `%2F`), while a
+    /// wildcard value keeps its `/` separators. The `%2F` and `%5C` escapes a
+    /// captured value keeps are passed through, so filling a pattern with the
+    /// params it matched gives back the matched path.
+    pub(crate) fn fill_encoded(&self, params: &RouteParams) -> Result<String, MissingParam> {
+        self.fill_with(params, true)
+    }
+
+    fn fill_with(&self, params: &RouteParams, encode: bool) -> Result<String, MissingParam> {
+        let mut missing = Vec::new();
+        let mut parts: Vec<Cow<'_, str>> = Vec::with_capacity(self.segments.len());
+        for segment in &self.segments {
+            let (prefix, name, suffix, wildcard) = match segment {
+                Segment::Empty => continue,
+                Segment::Static(text) => {
+                    parts.push(Cow::Borrowed(text));
+                    continue;
+                }
+                Segment::Wildcard { name: None } => {
+                    parts.push(Cow::Borrowed("*"));
+                    continue;
+                }
+                Segment::Param { name, .. } => ("", name, "", false),
+                Segment::Affixed {
+                    prefix,
+                    name,
+                    suffix,
+                    ..
+                } => (prefix.as_ref(), name, suffix.as_ref(), false),
+                Segment::Wildcard {
+                    name: Some(name), ..
+                } => ("", name, "", true),
+            };
+            let value = match params.get(name) {
+                Some(value) if !encode => Cow::Borrowed(value.as_str()),
+                Some(value) if wildcard => Cow::Owned(
+                    value
+                        .split('/')
+                        .map(encode_param_value)
+                        .collect::<Vec<_>>()
+                        .join("/"),
+                ),
+                Some(value) => Cow::Owned(encode_param_value(value)),
+                None => {
+                    missing.push(name.to_string());
+                    continue;
+                }
+            };
+            if prefix.is_empty() && suffix.is_empty() {
+                parts.push(value);
+            } else {
+                parts.push(Cow::Owned(format!("{prefix}{value}{suffix}")));
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(format!("/{}", parts.join("/")))
+        } else {
+            Err(MissingParam { names: missing })
+        }
+    }
+
+    /// How specific the pattern is; higher wins between overlapping patterns.
+    ///
+    /// Each static segment scores 4, a param with a constraint or literal
+    /// text around it 3, a bare param 2 and a wildcard 1, so `/users/new` (8)
+    /// outranks `/files/:name.pdf` (7), which outranks `/users/:id` (6).
+    #[must_use]
+    pub fn specificity(&self) -> u32 {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Static(_) => 4,
+                Segment::Param {
+                    constraint: Some(_),
+                    ..
+                }
+                | Segment::Affixed { .. } => 3,
+                Segment::Param { .. } => 2,
+                Segment::Wildcard { .. } => 1,
+                Segment::Empty => 0,
+            })
+            .sum()
+    }
+}
+
+/// Whether `path` matches the route-style `pattern` in full.
+///
+/// `pattern` need not belong to a registered route, and an invalid one
+/// matches nothing. A wildcard matches zero or more segments, so
+/// `/users/*` matches `/users` too.
+///
+/// ```
+/// use gpui_navigator::pattern::path_matches;
+///
+/// assert!(path_matches("/users/7/posts", "/users/*"));
+/// assert!(path_matches("/users/7", "/users/:id"));
+/// assert!(!path_matches("/users/7/posts", "/users/:id"));
+/// ```
+#[must_use]
+pub fn path_matches(path: &str, pattern: &str) -> bool {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    Pattern::parse(pattern).is_ok_and(|pattern| pattern.matches_all(&segments))
+}
+
+/// Parse one non-empty segment.
+fn parse_segment(segment: &str) -> Result<Segment, PatternError> {
+    if let Some(name) = segment.strip_prefix('*') {
+        let name = (!name.is_empty()).then(|| SharedString::from(name.to_string()));
+        return Ok(Segment::Wildcard { name });
+    }
+    let Some(colon) = segment.find(':') else {
+        return Ok(Segment::Static(segment.to_string().into()));
+    };
+    let (prefix, param) = (&segment[..colon], &segment[colon + 1..]);
+
+    let error = |make: fn(String) -> PatternError| Err(make(segment.to_string()));
+    let end = param
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(param.len());
+    let (name, rest) = param.split_at(end);
+    if name.is_empty() {
+        return error(|segment| PatternError::EmptyParamName { segment });
+    }
+
+    let (constraint, suffix) = match rest.chars().next() {
+        Some(opener @ ('<' | '{')) => {
+            let closer = if opener == '<' { '>' } else { '}' };
+            let Some(constraint) = rest[1..].strip_suffix(closer) else {
+                return error(|segment| PatternError::UnclosedConstraint { segment });
+            };
+            if constraint.contains(opener) {
+                return error(|segment| PatternError::NestedConstraint { segment });
+            }
+            if constraint.contains(closer) {
+                return error(|segment| PatternError::UnclosedConstraint { segment });
+            }
+            (Some(constraint.to_string().into()), "")
+        }
+        _ => (None, rest),
+    };
+
+    let name = SharedString::from(name.to_string());
+    if prefix.is_empty() && suffix.is_empty() {
+        return Ok(Segment::Param { name, constraint });
+    }
+    Ok(Segment::Affixed {
+        prefix: prefix.to_string().into(),
+        name,
+        constraint,
+        suffix: suffix.to_string().into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(path: &str) -> Pattern {
+        Pattern::parse(path).unwrap()
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> RouteParams {
+        let mut params = RouteParams::new();
+        for (key, value) in pairs {
+            params.insert(*key, *value);
+        }
+        params
+    }
+
+    #[test]
+    fn test_parse_segments() {
+        assert_eq!(
+            parse("/users/:id<u32>/files/*rest").segments(),
+            [
+                Segment::Static("users".into()),
+                Segment::Param {
+                    name: "id".into(),
+                    constraint: Some("u32".into()),
+                },
+                Segment::Static("files".into()),
+                Segment::Wildcard {
+                    name: Some("rest".into()),
+                },
+            ]
+        );
+        assert_eq!(
+            parse(":id{uuid}").segments(),
+            [Segment::Param {
+                name: "id".into(),
+                constraint: Some("uuid".into()),
+            }]
+        );
+        assert_eq!(
+            parse(":id<regex:[0-9]+>").segments()[0],
+            Segment::Param {
+                name: "id".into(),
+                constraint: Some("regex:[0-9]+".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_normalizes_slashes() {
+        for path in ["", "/", "//"] {
+            assert!(parse(path).is_empty(), "{path:?}");
+        }
+        assert_eq!(parse("dashboard"), parse("/dashboard/"));
+        assert_eq!(parse("/a//b"), parse("a/b"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |path: &str| Pattern::parse(path).unwrap_err();
+        assert_eq!(
+            error("/users/:"),
+            PatternError::EmptyParamName {
+                segment: ":".into()
+            }
+        );
+        assert_eq!(
+            error("/users/:<u32>"),
+            PatternError::EmptyParamName {
+                segment: ":<u32>".into()
+            }
+        );
+        assert_eq!(
+            error("/users/:id<vec<u32>>"),
+            PatternError::NestedConstraint {
+                segment: ":id<vec<u32>>".into()
+            }
+        );
+        assert_eq!(
+            error(":id<u32"),
+            PatternError::UnclosedConstraint {
+                segment: ":id<u32".into()
+            }
+        );
+        assert_eq!(
+            error(":id<u32>x"),
+            PatternError::UnclosedConstraint {
+                segment: ":id<u32>x".into()
+            }
+        );
+        assert_eq!(
+            error("/files/*/meta"),
+            PatternError::WildcardNotLast {
+                segment: "*".into()
+            }
+        );
+        assert!(error("/users/:").to_string().contains("cannot be empty"));
+    }
+
+    #[test]
+    fn test_static_match() {
+        assert_eq!(
+            parse("/users").match_segments(&["users"]),
+            Some((1, params(&[])))
+        );
+        assert_eq!(parse("/users").match_segments(&["posts"]), None);
+        assert_eq!(parse("/users/new").match_segments(&["users"]), None);
+    }
+
+    #[test]
+    fn test_param_match() {
+        let (consumed, captured) = parse("/users/:id<i32>")
+            .match_segments(&["users", "123", "posts"])
+            .unwrap();
+        assert_eq!(consumed, 2);
+        assert_eq!(captured, params(&[("id", "123")]));
+    }
+
+    #[test]
+    fn test_affixed_param_match() {
+        assert_eq!(
+            parse("/files/:name.pdf").segments()[1],
+            Segment::Affixed {
+                prefix: "".into(),
+                name: "name".into(),
+                constraint: None,
+                suffix: ".pdf".into(),
+            }
+        );
+        let pdf = parse("/files/:name.pdf");
+        assert_eq!(
+            pdf.match_segments(&["files", "report.pdf"]),
+            Some((2, params(&[("name", "report")])))
+        );
+        assert_eq!(
+            pdf.match_segments(&["files", "my%20report.pdf"]),
+            Some((2, params(&[("name", "my report")])))
+        );
+        assert_eq!(pdf.match_segments(&["files", "notes.md"]), None);
+        // The param may not be empty
+        assert_eq!(pdf.match_segments(&["files", ".pdf"]), None);
+
+        let image = parse("/img_:id.png");
+        assert_eq!(
+            image.match_segments(&["img_42.png"]),
+            Some((1, params(&[("id", "42")])))
+        );
+        assert_eq!(image.match_segments(&["img42.png"]), None);
+        assert_eq!(image.match_segments(&["img_.png"]), None);
+
+        assert_eq!(
+            parse("/posts/:slug-draft").match_segments(&["posts", "intro-draft"]),
+            Some((2, params(&[("slug", "intro")])))
+        );
+        assert_eq!(
+            parse("v:version<u32>").segments()[0],
+            Segment::Affixed {
+                prefix: "v".into(),
+                name: "version".into(),
+                constraint: Some("u32".into()),
+                suffix: "".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        let files = parse("/files/*");
+        assert_eq!(
+            files.match_segments(&["files", "documents", "report.pdf"]),
+            Some((3, params(&[])))
+        );
+        assert_eq!(files.match_segments(&["files"]), Some((1, params(&[]))));
+        assert_eq!(files.match_segments(&["other", "path"]), None);
+
+        let named = parse("/files/*path");
+        assert_eq!(
+            named.match_segments(&["files", "a", "b"]),
+            Some((3, params(&[("path", "a/b")])))
+        );
+        assert_eq!(named.match_segments(&["files"]), Some((1, params(&[]))));
+    }
+
+    #[test]
+    fn test_captured_values_are_decoded() {
+        let user = parse("/users/:name");
+        assert_eq!(
+            user.match_segments(&["users", "%20bob"]),
+            Some((2, params(&[("name", " bob")])))
+        );
+        // `+` is literal in a path, and malformed escapes are kept
+        assert_eq!(
+            user.match_segments(&["users", "a+b%zz"]),
+            Some((2, params(&[("name", "a+b%zz")])))
+        );
+        // Static segments are never decoded
+        assert_eq!(user.match_segments(&["us%65rs", "bob"]), None);
+        // Escaped separators stay encoded
+        assert_eq!(
+            user.match_segments(&["users", "..%2fsecret%5Cx"]),
+            Some((2, params(&[("name", "..%2fsecret%5Cx")])))
+        );
+
+        assert_eq!(
+            parse("/files/*path").match_segments(&["files", "my%20docs", "a.txt"]),
+            Some((3, params(&[("path", "my docs/a.txt")])))
+        );
+    }
+
+    #[test]
+    fn test_empty_match() {
+        assert_eq!(parse("").match_segments(&["a"]), Some((0, params(&[]))));
+        assert_eq!(parse("").match_segments(&[]), Some((0, params(&[]))));
+    }
+
+    #[test]
+    fn test_is_prefix_of() {
+        assert!(parse("dashboard").is_prefix_of(&["dashboard"]));
+        assert!(parse("dashboard").is_prefix_of(&["dashboard", "settings"]));
+        assert!(!parse("dashboard").is_prefix_of(&["other"]));
+        assert!(parse("users/:id").is_prefix_of(&["users", "123"]));
+        assert!(parse("users/:id").is_prefix_of(&["users", "123", "posts"]));
+        assert!(!parse("users/123").is_prefix_of(&["users"]));
+        assert!(parse("").is_prefix_of(&["anything"]));
+    }
+
+    #[test]
+    fn test_fill() {
+        let captured = params(&[("id", "123"), ("action", "edit")]);
+        assert_eq!(
+            parse("/users/:id/:action").fill(&captured),
+            Ok("/users/123/edit".to_string())
+        );
+        assert_eq!(
+            parse("/users/:id<i32>").fill(&captured),
+            Ok("/users/123".to_string())
+        );
+        assert_eq!(parse("").fill(&captured), Ok("/".to_string()));
+        assert_eq!(
+            parse("/files/*").fill(&captured),
+            Ok("/files/*".to_string())
+        );
+        assert_eq!(
+            parse("/posts/:postId/comments/:commentId/*rest").fill(&params(&[("postId", "1")])),
+            Err(MissingParam {
+                names: vec!["commentId".into(), "rest".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_fill_affixed() {
+        let pattern = parse("/files/img_:name.pdf");
+        let url = pattern.fill(&params(&[("name", "report")])).unwrap();
+        assert_eq!(url, "/files/img_report.pdf");
+
+        let segments: Vec<&str> = url.split('/').filter(|s| !s.is_empty()).collect();
+        assert_eq!(
+            pattern.match_segments(&segments),
+            Some((2, params(&[("name", "report")])))
+        );
+        assert_eq!(
+            pattern.fill_encoded(&params(&[("name", "a b")])),
+            Ok("/files/img_a%20b.pdf".to_string())
+        );
+        assert_eq!(
+            pattern.fill(&params(&[])),
+            Err(MissingParam {
+                names: vec!["name".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_fill_encoded() {
+        let captured = params(&[("id", "a b/c"), ("rest", "x y/z")]);
+        assert_eq!(
+            parse("/users/:id/*rest").fill_encoded(&captured),
+            Ok("/users/a%20b%2Fc/x%20y/z".to_string())
+        );
+        // Characters a path segment may carry are kept
+        assert_eq!(
+            parse("/tags/:id").fill_encoded(&params(&[("id", "c++:v1@x")])),
+            Ok("/tags/c++:v1@x".to_string())
+        );
+        // Escaped separators a captured value keeps round-trip, other `%` is
+        // encoded
+        assert_eq!(
+            parse("/files/:name").fill_encoded(&params(&[("name", "..%2fa%5Cb 50%")])),
+            Ok("/files/..%2fa%5Cb%2050%25".to_string())
+        );
+    }
+
+    #[test]
+    fn test_specificity() {
+        assert!(parse("/users/new").specificity() > parse("/users/:id").specificity());
+        assert!(parse("/users/:id<u32>").specificity() > parse("/users/:id").specificity());
+        assert!(parse("/users/:id").specificity() > parse("/users/*").specificity());
+        // Literals around a param rank between static and bare param
+        assert!(parse("/files/index.pdf").specificity() > parse("/files/:name.pdf").specificity());
+        assert!(parse("/files/:name.pdf").specificity() > parse("/files/:name").specificity());
+        assert_eq!(parse("").specificity(), 0);
+    }
+
+    #[test]
+    fn test_path_matches() {
+        // Exact
+        assert!(path_matches("/settings/profile", "/settings/profile"));
+        assert!(path_matches("/", "/"));
+        assert!(path_matches("settings//profile/", "/settings/profile"));
+        // Param
+        assert!(path_matches("/users/7", "/users/:id"));
+        assert!(path_matches("/users/7", "/users/:id<u32>"));
+        // Wildcard, including an empty rest
+        assert!(path_matches("/files/a/b.txt", "/files/*path"));
+        assert!(path_matches("/users", "/users/*"));
+        assert!(path_matches("/anything/at/all", "*"));
+        // Non-matching: prefix only, too short, other text, invalid pattern
+        assert!(!path_matches("/users/7/posts", "/users/:id"));
+        assert!(!path_matches("/users", "/users/:id"));
+        assert!(!path_matches("/teams/7", "/users/:id"));
+        assert!(!path_matches("/settings", "/"));
+        assert!(!path_matches("/users/7", "/users/:"));
+    }
+
+    #[test]
+    fn test_param_names() {
+        let pattern = parse("/a/:x/b/:y<u8>/*rest");
+        assert_eq!(
+            pattern.param_names().collect::<Vec<_>>(),
+            ["x", "y", "rest"]
+        );
+    }
+}
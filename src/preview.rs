@@ -0,0 +1,241 @@
+//! Previews of a route's content without navigating.
+//!
+//! [`GlobalRouter::render_preview`](crate::GlobalRouter::render_preview)
+//! resolves a path as a navigation would, but only to render what it would
+//! show into a box of fixed size: history, the current match stack and the
+//! component cache are left alone. [`RouterLink::preview_on_hover`](crate::RouterLink::preview_on_hover)
+//! shows one in a popover once the pointer rests on a link.
+//!
+//! ```ignore
+//! let constraints = PreviewConstraints::new(size(px(320.), px(200.)));
+//! if let Some(preview) = Navigator::render_preview(window, cx, "/users/42", constraints) {
+//!     card = card.child(preview);
+//! }
+//! ```
+//!
+//! By default only the leaf route is built, without the layouts above it;
+//! [`with_chain`](PreviewConstraints::with_chain) builds from the root. In
+//! a chain preview, layouts render their children through
+//! [`render_router_outlet`](crate::render_router_outlet): a
+//! [`RouterOutlet`](crate::RouterOutlet) entity renders later in the frame,
+//! outside the preview, and shows the current route. Named outlets stay
+//! empty.
+//!
+//! A target guarded by a guard that returns `false` from
+//! [`RouteGuard::allow_preview`](crate::RouteGuard::allow_preview), or that
+//! the guards would not let a navigation reach, renders no preview.
+//! Component routes get a fresh entity per preview, never the cached one.
+
+use crate::resolve::{MatchEntry, MatchStack};
+use gpui::{Pixels, Size};
+use std::cell::RefCell;
+
+/// How [`render_preview`](crate::GlobalRouter::render_preview) renders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviewConstraints {
+    /// Size of the box the preview renders into; overflow is clipped
+    pub size: Size<Pixels>,
+    /// Whether to build the ancestor layouts too, not just the leaf route
+    pub include_chain: bool,
+}
+
+impl PreviewConstraints {
+    /// Preview the leaf route in a box of `size`.
+    #[must_use]
+    pub const fn new(size: Size<Pixels>) -> Self {
+        Self {
+            size,
+            include_chain: false,
+        }
+    }
+
+    /// Build the whole chain of routes, from the root layout down.
+    #[must_use]
+    pub const fn with_chain(mut self) -> Self {
+        self.include_chain = true;
+        self
+    }
+}
+
+/// The entry a preview starts building from: the root with `include_chain`,
+/// the leaf otherwise.
+pub(crate) fn preview_start(stack: &MatchStack, include_chain: bool) -> Option<&MatchEntry> {
+    if include_chain {
+        stack.root()
+    } else {
+        stack.leaf()
+    }
+}
+
+thread_local! {
+    /// Match stack of the preview being built, if any.
+    static PREVIEW_STACK: RefCell<Option<MatchStack>> = const { RefCell::new(None) };
+}
+
+/// Make outlets render `stack` instead of the current route until dropped.
+pub(crate) struct PreviewScope {
+    saved: Option<MatchStack>,
+}
+
+impl PreviewScope {
+    pub(crate) fn enter(stack: MatchStack) -> Self {
+        let saved = PREVIEW_STACK.with(|current| current.replace(Some(stack)));
+        Self { saved }
+    }
+}
+
+impl Drop for PreviewScope {
+    fn drop(&mut self) {
+        PREVIEW_STACK.with(|current| *current.borrow_mut() = self.saved.take());
+    }
+}
+
+/// Whether a preview is being built.
+pub(crate) fn in_preview() -> bool {
+    PREVIEW_STACK.with(|stack| stack.borrow().is_some())
+}
+
+/// The preview's entry at `depth`.
+pub(crate) fn preview_entry(depth: usize) -> Option<MatchEntry> {
+    PREVIEW_STACK.with(|stack| stack.borrow().as_ref()?.at_depth(depth).cloned())
+}
+
+/// Where a hover preview is in its delay.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HoverPhase {
+    /// Nothing hovered
+    #[default]
+    Idle,
+    /// A target is hovered, its delay not over yet
+    Pending,
+    /// The delay is over, the preview is visible
+    Showing,
+}
+
+/// Hover-to-preview state: a target shows once hovered for a delay, and
+/// hides when the pointer leaves.
+///
+/// One target at a time: hovering another replaces it. The delay itself
+/// runs outside, e.g. on an executor timer, and reports back with the token
+/// [`enter`](Self::enter) returned, so a timer outliving its hover does
+/// nothing. [`RouterLink::preview_on_hover`](crate::RouterLink::preview_on_hover)
+/// keeps one in the router.
+#[derive(Debug, Clone, Default)]
+pub struct HoverPreview {
+    target: Option<String>,
+    phase: HoverPhase,
+    /// Bumped on every enter and leave, so a stale timer is ignored.
+    token: u64,
+}
+
+impl HoverPreview {
+    /// Start the delay for `target`; returns the token to pass to
+    /// [`elapse`](Self::elapse), or `None` if `target` is already hovered.
+    pub fn enter(&mut self, target: &str) -> Option<u64> {
+        if self.target.as_deref() == Some(target) {
+            return None;
+        }
+        self.token += 1;
+        self.target = Some(target.to_string());
+        self.phase = HoverPhase::Pending;
+        Some(self.token)
+    }
+
+    /// End the delay started with `token`; returns whether the preview
+    /// became visible.
+    pub fn elapse(&mut self, token: u64) -> bool {
+        if token != self.token || self.phase != HoverPhase::Pending {
+            return false;
+        }
+        self.phase = HoverPhase::Showing;
+        true
+    }
+
+    /// Stop hovering `target`; returns whether its preview was visible.
+    pub fn leave(&mut self, target: &str) -> bool {
+        if self.target.as_deref() != Some(target) {
+            return false;
+        }
+        let was_showing = self.phase == HoverPhase::Showing;
+        self.token += 1;
+        self.target = None;
+        self.phase = HoverPhase::Idle;
+        was_showing
+    }
+
+    /// The hovered target, while one is pending or showing.
+    #[must_use]
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// Where the hover is in its delay.
+    #[must_use]
+    pub const fn phase(&self) -> HoverPhase {
+        self.phase
+    }
+
+    /// Return `true` if the preview of `target` is visible.
+    #[must_use]
+    pub fn is_showing(&self, target: &str) -> bool {
+        self.phase == HoverPhase::Showing && self.target.as_deref() == Some(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolve::resolve_match_stack;
+    use crate::test_support::page;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_hover_preview_shows_after_delay() {
+        let mut hover = HoverPreview::default();
+        let token = hover.enter("/users/1").unwrap();
+        assert_eq!(hover.phase(), HoverPhase::Pending);
+        assert!(!hover.is_showing("/users/1"));
+        // Moving within the link does not restart the delay
+        assert_eq!(hover.enter("/users/1"), None);
+
+        assert!(hover.elapse(token));
+        assert!(hover.is_showing("/users/1"));
+        assert!(!hover.elapse(token));
+
+        assert!(!hover.leave("/users/2"));
+        assert!(hover.leave("/users/1"));
+        assert_eq!(hover.phase(), HoverPhase::Idle);
+        assert_eq!(hover.target(), None);
+    }
+
+    #[test]
+    fn test_hover_preview_ignores_stale_timer() {
+        let mut hover = HoverPreview::default();
+        let first = hover.enter("/a").unwrap();
+        assert!(!hover.leave("/a"));
+        assert!(!hover.elapse(first));
+        assert_eq!(hover.phase(), HoverPhase::Idle);
+
+        // Hovering another target replaces the pending one
+        let second = hover.enter("/a").unwrap();
+        let third = hover.enter("/b").unwrap();
+        assert!(!hover.elapse(second));
+        assert!(hover.elapse(third));
+        assert!(hover.is_showing("/b"));
+        assert!(!hover.is_showing("/a"));
+    }
+
+    #[test]
+    fn test_preview_start_selects_leaf_or_root() {
+        let routes = vec![Arc::new(
+            page("/users").child(page(":id").child(page("posts"))),
+        )];
+        let stack = resolve_match_stack(&routes, "/users/7/posts");
+
+        let leaf = preview_start(&stack, false).unwrap();
+        assert_eq!(leaf.depth, 2);
+        assert_eq!(leaf.params.get("id").map(String::as_str), Some("7"));
+        assert_eq!(preview_start(&stack, true).unwrap().depth, 0);
+        assert!(preview_start(&MatchStack::new(), false).is_none());
+    }
+}
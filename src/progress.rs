@@ -0,0 +1,273 @@
+//! Navigation progress for top-of-window progress bars.
+//!
+//! While a navigation runs, the [`GlobalRouter`](crate::GlobalRouter) reports
+//! which pipeline stage it is in through
+//! [`navigation_progress`](crate::GlobalRouter::navigation_progress), with a
+//! 0.0–1.0 estimate that only grows within one navigation (redirect hops
+//! included). The slot is cleared once the navigation completes, is blocked
+//! or challenged, or is superseded by a newer one.
+//!
+//! A synchronous pipeline passes through every stage within one update, so
+//! code reading the slot during render only sees it while a stage holds the
+//! pipeline. To see every step, register a hook with
+//! [`set_on_navigation_progress`](crate::GlobalRouter::set_on_navigation_progress);
+//! [`navigation_progress_bar`](crate::navigation_progress_bar) renders the
+//! slot as a thin bar.
+
+use crate::trace_log;
+use std::sync::Arc;
+
+/// Pipeline stage a navigation is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NavigationStage {
+    /// Running the target's guards.
+    Guards,
+    /// Running `can_deactivate` / `on_exit` of the current route.
+    Lifecycle,
+    /// Updating history and resolving the match stack.
+    Resolving,
+    /// Running route loaders.
+    Loading {
+        /// Loaders that have finished.
+        completed: usize,
+        /// Loaders in the new match stack.
+        total: usize,
+    },
+    /// Entering the new route and running after-middleware.
+    Committing,
+}
+
+impl NavigationStage {
+    /// Progress estimate reached once this stage is entered.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn estimate(self) -> f32 {
+        match self {
+            Self::Guards => 0.1,
+            Self::Lifecycle => 0.25,
+            Self::Resolving => 0.4,
+            Self::Loading { total: 0, .. } => 0.5,
+            Self::Loading { completed, total } => {
+                0.4f32.mul_add(completed.min(total) as f32 / total as f32, 0.5)
+            }
+            Self::Committing => 0.95,
+        }
+    }
+}
+
+/// Where the navigation in flight currently is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavigationProgress {
+    path: String,
+    stage: NavigationStage,
+    estimate: f32,
+}
+
+impl NavigationProgress {
+    /// Path the navigation targets (the latest hop after a redirect).
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Stage the navigation is in.
+    #[must_use]
+    pub const fn stage(&self) -> NavigationStage {
+        self.stage
+    }
+
+    /// Completion estimate between 0.0 and 1.0.
+    #[must_use]
+    pub const fn estimate(&self) -> f32 {
+        self.estimate
+    }
+}
+
+/// Hook called on every progress change, with `None` once the slot clears.
+pub type ProgressHook = Arc<dyn Fn(Option<&NavigationProgress>) + Send + Sync>;
+
+/// Progress slot of the router and the hook watching it.
+#[derive(Clone, Default)]
+pub(crate) struct ProgressTracker {
+    current: Option<NavigationProgress>,
+    /// A navigation is running; stages outside one are not reported.
+    active: bool,
+    hook: Option<ProgressHook>,
+}
+
+impl ProgressTracker {
+    pub(crate) const fn current(&self) -> Option<&NavigationProgress> {
+        self.current.as_ref()
+    }
+
+    pub(crate) fn set_hook(&mut self, hook: ProgressHook) {
+        self.hook = Some(hook);
+    }
+
+    /// Start tracking a navigation, superseding any in flight.
+    pub(crate) fn begin(&mut self) {
+        self.clear();
+        self.active = true;
+    }
+
+    /// Move the navigation in flight to `stage`, now targeting `path`.
+    pub(crate) fn advance(&mut self, path: &str, stage: NavigationStage) {
+        if !self.active {
+            return;
+        }
+        let floor = self
+            .current
+            .as_ref()
+            .map_or(0.0, |current| current.estimate);
+        let progress = NavigationProgress {
+            path: path.to_string(),
+            stage,
+            estimate: stage.estimate().max(floor),
+        };
+        trace_log!(
+            "Navigation progress: '{}' {:?} ({:.2})",
+            progress.path,
+            progress.stage,
+            progress.estimate
+        );
+        if let Some(hook) = &self.hook {
+            hook(Some(&progress));
+        }
+        self.current = Some(progress);
+    }
+
+    /// Stop tracking and clear the slot.
+    pub(crate) fn finish(&mut self) {
+        self.clear();
+        self.active = false;
+    }
+
+    fn clear(&mut self) {
+        if self.current.take().is_some() {
+            if let Some(hook) = &self.hook {
+                hook(None);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::test_support::page;
+    use crate::{init_router, GlobalRouter, Navigator};
+    use gpui::{BorrowAppContext, TestAppContext};
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_estimates_only_grow_within_a_navigation() {
+        let mut tracker = ProgressTracker::default();
+        tracker.advance("/ignored", NavigationStage::Guards);
+        assert!(tracker.current().is_none());
+
+        tracker.begin();
+        let mut seen = Vec::new();
+        for stage in [
+            NavigationStage::Guards,
+            NavigationStage::Lifecycle,
+            NavigationStage::Resolving,
+            NavigationStage::Loading {
+                completed: 0,
+                total: 2,
+            },
+            NavigationStage::Loading {
+                completed: 2,
+                total: 2,
+            },
+            // A redirect hop starts over at the guards
+            NavigationStage::Guards,
+            NavigationStage::Committing,
+        ] {
+            tracker.advance("/a", stage);
+            seen.push(tracker.current().unwrap().estimate());
+        }
+        assert!(seen.windows(2).all(|pair| pair[0] <= pair[1]), "{seen:?}");
+        assert!((seen[4] - 0.9).abs() < f32::EPSILON);
+
+        tracker.finish();
+        assert!(tracker.current().is_none());
+
+        // The next navigation starts from scratch
+        tracker.begin();
+        tracker.advance("/b", NavigationStage::Guards);
+        assert!((tracker.current().unwrap().estimate() - 0.1).abs() < f32::EPSILON);
+    }
+
+    type Events = Arc<Mutex<Vec<Option<(String, NavigationStage, f32)>>>>;
+
+    fn record(cx: &mut TestAppContext) -> Events {
+        let events: Events = Arc::default();
+        let sink = Arc::clone(&events);
+        cx.update(|cx| {
+            cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.set_on_navigation_progress(move |progress| {
+                    sink.lock()
+                        .unwrap()
+                        .push(progress.map(|p| (p.path().to_string(), p.stage(), p.estimate())));
+                });
+            });
+        });
+        events
+    }
+
+    #[gpui::test]
+    fn test_router_reports_stages_and_clears(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/report").loader(|_, _| Ok(42_u32)));
+            });
+        });
+        let events = record(cx);
+
+        cx.update(|cx| Navigator::push(cx, "/report"));
+        let first = std::mem::take(&mut *events.lock().unwrap());
+        assert_eq!(first.last(), Some(&None));
+        let stages: Vec<_> = first.iter().flatten().map(|(_, stage, _)| *stage).collect();
+        assert!(stages.contains(&NavigationStage::Loading {
+            completed: 1,
+            total: 1
+        }));
+        assert_eq!(stages.last(), Some(&NavigationStage::Committing));
+        let estimates: Vec<f32> = first.iter().flatten().map(|(_, _, e)| *e).collect();
+        assert!(estimates.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(first.iter().flatten().all(|(path, _, _)| path == "/report"));
+        assert!(cx.read(|cx| cx.global::<GlobalRouter>().navigation_progress().is_none()));
+
+        // A new navigation starts low again
+        cx.update(|cx| Navigator::push(cx, "/"));
+        let second = events.lock().unwrap().clone();
+        let (_, _, estimate) = second[0].clone().unwrap();
+        assert!(estimate < *estimates.last().unwrap());
+        assert_eq!(second.last(), Some(&None));
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_blocked_navigation_clears_progress(cx: &mut TestAppContext) {
+        use crate::{guard_fn, NavigationAction};
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/admin").guard(guard_fn(|_, _| NavigationAction::deny("no access"))),
+                );
+            });
+        });
+        let events = record(cx);
+
+        cx.update(|cx| Navigator::push(cx, "/admin"));
+        let events = events.lock().unwrap().clone();
+        assert!(matches!(events[0], Some((_, NavigationStage::Guards, _))));
+        assert_eq!(events.last(), Some(&None));
+        assert!(cx.read(|cx| cx.global::<GlobalRouter>().navigation_progress().is_none()));
+    }
+}
@@ -0,0 +1,945 @@
+//! Declarative, typed query parameter extraction.
+//!
+//! [`QueryParams`] hands out raw strings; pages that read several typed
+//! parameters end up repeating the same parse-default-validate dance. A
+//! [`QuerySpec`] describes the expected parameters once, and
+//! [`extract`](QuerySpec::extract) turns a [`QueryParams`] into an
+//! [`ExtractedQuery`] holding already-parsed values.
+//!
+//! # Example
+//!
+//! ```
+//! use gpui_navigator::{QueryParams, QuerySpec};
+//!
+//! let spec = QuerySpec::new()
+//!     .field::<u32>("page")
+//!     .default(1)
+//!     .field::<String>("sort")
+//!     .one_of(["name", "date"])
+//!     .default("name")
+//!     .field_opt::<bool>("archived")
+//!     .field_vec::<String>("tag")
+//!     .build();
+//!
+//! let query = QueryParams::from_query_string("sort=date&tag=rust&tag=gpui");
+//! let extracted = spec.extract(&query).unwrap();
+//!
+//! assert_eq!(extracted.get::<u32>("page"), Some(&1));
+//! assert_eq!(extracted.get::<String>("sort").map(String::as_str), Some("date"));
+//! assert_eq!(extracted.get::<bool>("archived"), None);
+//! assert_eq!(extracted.get::<Vec<String>>("tag").map(Vec::len), Some(2));
+//!
+//! // Defaults are left out, the rest follows the spec's field order
+//! assert_eq!(extracted.to_query_string(), "sort=date&tag=rust&tag=gpui");
+//! ```
+
+use crate::debug_log;
+use crate::params::{decode_uri_component, encode_uri_component, QueryParams};
+use std::any::{Any, TypeId};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+// ============================================================================
+// QueryValue
+// ============================================================================
+
+/// A type that can be read from and written back to a query string.
+///
+/// Implemented for every `FromStr + Display` type whose parse error is
+/// displayable, which covers the integer, float, `bool` and `String` types.
+pub trait QueryValue: Clone + PartialEq + Send + Sync + 'static {
+    /// Parse a decoded query value, describing the failure on error.
+    ///
+    /// # Errors
+    ///
+    /// Returns a human-readable message when `raw` is not a valid value.
+    fn parse_query(raw: &str) -> Result<Self, String>;
+
+    /// Canonical query string form of the value (before encoding).
+    fn to_query(&self) -> String;
+}
+
+impl<T> QueryValue for T
+where
+    T: FromStr + fmt::Display + Clone + PartialEq + Send + Sync + 'static,
+    T::Err: fmt::Display,
+{
+    fn parse_query(raw: &str) -> Result<Self, String> {
+        raw.parse().map_err(|err: T::Err| err.to_string())
+    }
+
+    fn to_query(&self) -> String {
+        self.to_string()
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Why a query field could not be extracted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum QueryErrorReason {
+    /// A required field without a default was absent.
+    Missing,
+    /// The value did not parse as the field's type.
+    Parse {
+        /// The offending value
+        value: String,
+        /// The parser's error message
+        message: String,
+    },
+    /// The value parsed but is not in the field's [`one_of`](QueryFieldBuilder::one_of) set.
+    NotAllowed {
+        /// The offending value
+        value: String,
+        /// The allowed values, in declaration order
+        allowed: Vec<String>,
+    },
+}
+
+/// A query field that failed extraction, naming the field and the reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    field: String,
+    reason: QueryErrorReason,
+}
+
+impl QueryError {
+    /// Name of the failing field.
+    #[must_use]
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// Why the field failed.
+    #[must_use]
+    pub const fn reason(&self) -> &QueryErrorReason {
+        &self.reason
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "query parameter '{}': ", self.field)?;
+        match &self.reason {
+            QueryErrorReason::Missing => write!(f, "missing"),
+            QueryErrorReason::Parse { value, message } => {
+                write!(f, "cannot parse '{value}': {message}")
+            }
+            QueryErrorReason::NotAllowed { value, allowed } => {
+                write!(f, "'{value}' is not one of [{}]", allowed.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+// ============================================================================
+// Field specs
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Required,
+    Optional,
+    Multi,
+}
+
+struct TypedField<T> {
+    name: String,
+    kind: FieldKind,
+    defaults: Vec<T>,
+    allowed: Option<Vec<T>>,
+}
+
+impl<T: QueryValue> TypedField<T> {
+    fn error(&self, reason: QueryErrorReason) -> QueryError {
+        QueryError {
+            field: self.name.clone(),
+            reason,
+        }
+    }
+
+    fn parse(&self, raw: &str) -> Result<T, QueryError> {
+        let value = T::parse_query(raw).map_err(|message| {
+            self.error(QueryErrorReason::Parse {
+                value: raw.to_string(),
+                message,
+            })
+        })?;
+        match &self.allowed {
+            Some(allowed) if !allowed.contains(&value) => {
+                Err(self.error(QueryErrorReason::NotAllowed {
+                    value: raw.to_string(),
+                    allowed: allowed.iter().map(QueryValue::to_query).collect(),
+                }))
+            }
+            _ => Ok(value),
+        }
+    }
+
+    /// Parse a value supplied by the spec author, panicking on failure.
+    fn parse_declared(&self, raw: &str, what: &str) -> T {
+        T::parse_query(raw).unwrap_or_else(|message| {
+            panic!(
+                "{what} '{raw}' for query field '{}' does not parse: {message}",
+                self.name
+            )
+        })
+    }
+
+    fn check_defaults_allowed(&self) {
+        if let Some(allowed) = &self.allowed {
+            if let Some(default) = self.defaults.iter().find(|d| !allowed.contains(d)) {
+                panic!(
+                    "default '{}' for query field '{}' is not in its one_of set",
+                    default.to_query(),
+                    self.name
+                );
+            }
+        }
+    }
+}
+
+/// Type-erased view of a [`TypedField`], so one spec can hold many types.
+trait ErasedField: Send + Sync {
+    fn name(&self) -> &str;
+    fn extract(&self, raw: &[String], errors: &mut Vec<QueryError>) -> ExtractedField;
+}
+
+impl<T: QueryValue> ErasedField for TypedField<T> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn extract(&self, raw: &[String], errors: &mut Vec<QueryError>) -> ExtractedField {
+        let default_raw = self.defaults.iter().map(QueryValue::to_query).collect();
+
+        if self.kind == FieldKind::Multi {
+            let mut values: Vec<T> = raw
+                .iter()
+                .filter_map(|value| self.parse(value).map_err(|e| errors.push(e)).ok())
+                .collect();
+            if values.is_empty() {
+                values.clone_from(&self.defaults);
+            }
+            return ExtractedField {
+                name: self.name.clone(),
+                type_id: TypeId::of::<Vec<T>>(),
+                raw: values.iter().map(QueryValue::to_query).collect(),
+                value: Some(Box::new(values)),
+                default_raw,
+            };
+        }
+
+        let default = || self.defaults.first().cloned();
+        let value = if let Some(value) = raw.first() {
+            self.parse(value)
+                .map_err(|e| errors.push(e))
+                .ok()
+                .or_else(default)
+        } else {
+            let value = default();
+            if value.is_none() && self.kind == FieldKind::Required {
+                errors.push(self.error(QueryErrorReason::Missing));
+            }
+            value
+        };
+        ExtractedField {
+            name: self.name.clone(),
+            type_id: TypeId::of::<T>(),
+            raw: value.iter().map(QueryValue::to_query).collect(),
+            value: value.map(|v| Box::new(v) as Box<dyn Any + Send + Sync>),
+            default_raw,
+        }
+    }
+}
+
+// ============================================================================
+// QuerySpec
+// ============================================================================
+
+/// Declarative description of the query parameters a page expects.
+///
+/// Fields are added with [`field`](Self::field), [`field_opt`](Self::field_opt)
+/// and [`field_vec`](Self::field_vec); the returned [`QueryFieldBuilder`]
+/// configures the field just added and continues the chain.
+#[derive(Clone, Default)]
+pub struct QuerySpec {
+    fields: Vec<Arc<dyn ErasedField>>,
+}
+
+impl QuerySpec {
+    /// Create an empty spec.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a single-valued field. Without a [`default`](QueryFieldBuilder::default)
+    /// the field is required.
+    pub fn field<T: QueryValue>(self, name: impl Into<String>) -> QueryFieldBuilder<T> {
+        self.add(name.into(), FieldKind::Required)
+    }
+
+    /// Add a single-valued field that may be absent.
+    pub fn field_opt<T: QueryValue>(self, name: impl Into<String>) -> QueryFieldBuilder<T> {
+        self.add(name.into(), FieldKind::Optional)
+    }
+
+    /// Add a multi-valued field (`?tag=a&tag=b`), read back as `Vec<T>`.
+    pub fn field_vec<T: QueryValue>(self, name: impl Into<String>) -> QueryFieldBuilder<T> {
+        self.add(name.into(), FieldKind::Multi)
+    }
+
+    fn add<T: QueryValue>(self, name: String, kind: FieldKind) -> QueryFieldBuilder<T> {
+        assert!(
+            self.fields.iter().all(|field| field.name() != name),
+            "query field '{name}' declared twice"
+        );
+        QueryFieldBuilder {
+            spec: self,
+            field: TypedField {
+                name,
+                kind,
+                defaults: Vec::new(),
+                allowed: None,
+            },
+        }
+    }
+
+    /// Extract every field, failing on the first invalid one.
+    ///
+    /// Only the first value of a single-valued field is read.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`QueryError`] in field declaration order.
+    pub fn extract(&self, query: &QueryParams) -> Result<ExtractedQuery, QueryError> {
+        let (extracted, mut errors) = self.extract_lossy(query);
+        if errors.is_empty() {
+            Ok(extracted)
+        } else {
+            Err(errors.swap_remove(0))
+        }
+    }
+
+    /// Extract every field, substituting defaults for invalid values.
+    ///
+    /// Invalid values of a required field without a default leave it unset;
+    /// invalid entries of a multi-valued field are dropped. Every problem is
+    /// reported in the returned warnings, in field declaration order.
+    #[must_use]
+    pub fn extract_lossy(&self, query: &QueryParams) -> (ExtractedQuery, Vec<QueryError>) {
+        let mut errors = Vec::new();
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| {
+                let raw = query.get_all(field.name()).map_or(&[][..], Vec::as_slice);
+                field.extract(raw, &mut errors)
+            })
+            .collect();
+        (ExtractedQuery { fields }, errors)
+    }
+}
+
+impl fmt::Debug for QuerySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuerySpec")
+            .field(
+                "fields",
+                &self
+                    .fields
+                    .iter()
+                    .map(|field| field.name())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// Configures the field most recently added to a [`QuerySpec`].
+///
+/// Adding another field, [`build`](Self::build) or extracting finishes it.
+#[must_use = "finish the spec with `build()` or extract from it"]
+pub struct QueryFieldBuilder<T: QueryValue> {
+    spec: QuerySpec,
+    field: TypedField<T>,
+}
+
+impl<T: QueryValue> QueryFieldBuilder<T> {
+    /// Value used when the field is absent or invalid. The value is given in
+    /// query form (`1`, `"name"`) and parsed as `T`; on a multi-valued field
+    /// each call adds one default entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value does not parse as `T` or is outside the
+    /// [`one_of`](Self::one_of) set.
+    pub fn default(mut self, value: impl fmt::Display) -> Self {
+        let value = self.field.parse_declared(&value.to_string(), "default");
+        if self.field.kind != FieldKind::Multi {
+            self.field.defaults.clear();
+        }
+        self.field.defaults.push(value);
+        self.field.check_defaults_allowed();
+        self
+    }
+
+    /// Restrict the field to the given values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value does not parse as `T`, or if an already declared
+    /// default is not among them.
+    pub fn one_of<V: fmt::Display>(mut self, values: impl IntoIterator<Item = V>) -> Self {
+        let allowed = values
+            .into_iter()
+            .map(|value| {
+                self.field
+                    .parse_declared(&value.to_string(), "allowed value")
+            })
+            .collect();
+        self.field.allowed = Some(allowed);
+        self.field.check_defaults_allowed();
+        self
+    }
+
+    /// Finish this field and add a required one. See [`QuerySpec::field`].
+    pub fn field<U: QueryValue>(self, name: impl Into<String>) -> QueryFieldBuilder<U> {
+        self.build().field(name)
+    }
+
+    /// Finish this field and add an optional one. See [`QuerySpec::field_opt`].
+    pub fn field_opt<U: QueryValue>(self, name: impl Into<String>) -> QueryFieldBuilder<U> {
+        self.build().field_opt(name)
+    }
+
+    /// Finish this field and add a multi-valued one. See [`QuerySpec::field_vec`].
+    pub fn field_vec<U: QueryValue>(self, name: impl Into<String>) -> QueryFieldBuilder<U> {
+        self.build().field_vec(name)
+    }
+
+    /// Finish this field and return the spec.
+    #[must_use]
+    pub fn build(self) -> QuerySpec {
+        let mut spec = self.spec;
+        spec.fields.push(Arc::new(self.field));
+        spec
+    }
+
+    /// Finish the spec and [`extract`](QuerySpec::extract) from `query`.
+    ///
+    /// # Errors
+    ///
+    /// See [`QuerySpec::extract`].
+    pub fn extract(self, query: &QueryParams) -> Result<ExtractedQuery, QueryError> {
+        self.build().extract(query)
+    }
+
+    /// Finish the spec and [`extract_lossy`](QuerySpec::extract_lossy) from `query`.
+    #[must_use]
+    pub fn extract_lossy(self, query: &QueryParams) -> (ExtractedQuery, Vec<QueryError>) {
+        self.build().extract_lossy(query)
+    }
+}
+
+impl<T: QueryValue> From<QueryFieldBuilder<T>> for QuerySpec {
+    fn from(builder: QueryFieldBuilder<T>) -> Self {
+        builder.build()
+    }
+}
+
+// ============================================================================
+// QuerySchema
+// ============================================================================
+
+/// Value type of a [`QuerySchema`] field, named as in
+/// [`Route::query_schema`](crate::Route::query_schema).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum QueryType {
+    /// `"string"`: any value
+    String,
+    /// `"bool"`: `true` or `false`
+    Bool,
+    /// `"u32"`
+    U32,
+    /// `"u64"`
+    U64,
+    /// `"i32"`
+    I32,
+    /// `"i64"`
+    I64,
+    /// `"f64"`
+    F64,
+}
+
+impl QueryType {
+    /// Look up a type by name, `None` for unknown names.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "string" => Self::String,
+            "bool" => Self::Bool,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "i32" => Self::I32,
+            "i64" => Self::I64,
+            "f64" => Self::F64,
+            _ => return None,
+        })
+    }
+
+    /// Check that a decoded value parses as this type.
+    fn check(self, raw: &str) -> Result<(), String> {
+        match self {
+            Self::String => Ok(()),
+            Self::Bool => bool::parse_query(raw).map(drop),
+            Self::U32 => u32::parse_query(raw).map(drop),
+            Self::U64 => u64::parse_query(raw).map(drop),
+            Self::I32 => i32::parse_query(raw).map(drop),
+            Self::I64 => i64::parse_query(raw).map(drop),
+            Self::F64 => f64::parse_query(raw).map(drop),
+        }
+    }
+}
+
+/// What a [`QuerySchema`] does with a value that does not parse.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QueryViolation {
+    /// Drop the value and carry on.
+    #[default]
+    Strip,
+    /// Fail with a [`QueryError`].
+    Reject,
+}
+
+/// The query keys a route accepts and their value types.
+///
+/// Unlike a [`QuerySpec`], which extracts typed values for a page, a schema
+/// only filters a query string: [`apply`](Self::apply) keeps the declared
+/// keys whose values parse and drops everything else, in the original order.
+///
+/// ```
+/// use gpui_navigator::{QuerySchema, QueryViolation};
+///
+/// let schema = QuerySchema::new(&[("page", "u32"), ("sort", "string")]);
+///
+/// let kept = schema.apply("page=2&debug=1&sort=name", QueryViolation::Strip);
+/// assert_eq!(kept.unwrap(), "page=2&sort=name");
+///
+/// let err = schema.apply("page=two", QueryViolation::Reject).unwrap_err();
+/// assert_eq!(err.field(), "page");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuerySchema {
+    fields: Vec<(String, QueryType)>,
+}
+
+impl QuerySchema {
+    /// Create a schema from `(key, type name)` pairs. See [`QueryType`] for
+    /// the type names.
+    ///
+    /// # Panics
+    ///
+    /// Panics on an unknown type name or a key declared twice.
+    #[must_use]
+    pub fn new(fields: &[(&str, &str)]) -> Self {
+        let mut declared: Vec<(String, QueryType)> = Vec::with_capacity(fields.len());
+        for &(key, type_name) in fields {
+            let ty = QueryType::from_name(type_name)
+                .unwrap_or_else(|| panic!("unknown type '{type_name}' for query field '{key}'"));
+            assert!(
+                declared.iter().all(|(name, _)| name != key),
+                "query field '{key}' declared twice"
+            );
+            declared.push((key.to_string(), ty));
+        }
+        Self { fields: declared }
+    }
+
+    /// Type of a declared key.
+    #[must_use]
+    pub fn field_type(&self, key: &str) -> Option<QueryType> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|&(_, ty)| ty)
+    }
+
+    /// Filter a query string (without the leading `?`).
+    ///
+    /// Returns the pairs of declared keys whose values parse, unchanged and
+    /// in their original order. Unknown keys are always dropped; values that
+    /// do not parse are dropped or rejected according to `violation`.
+    ///
+    /// # Errors
+    ///
+    /// Under [`QueryViolation::Reject`], returns the first value that does
+    /// not parse.
+    pub fn apply(&self, query: &str, violation: QueryViolation) -> Result<String, QueryError> {
+        let mut kept = Vec::new();
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let key = decode_uri_component(key);
+            let Some(ty) = self.field_type(&key) else {
+                debug_log!("Dropping undeclared query parameter '{}'", key);
+                continue;
+            };
+            let value = decode_uri_component(value);
+            match ty.check(&value) {
+                Ok(()) => kept.push(pair),
+                Err(message) => {
+                    let error = QueryError {
+                        field: key,
+                        reason: QueryErrorReason::Parse { value, message },
+                    };
+                    if violation == QueryViolation::Reject {
+                        return Err(error);
+                    }
+                    debug_log!("Dropping {}", error);
+                }
+            }
+        }
+        Ok(kept.join("&"))
+    }
+}
+
+// ============================================================================
+// ExtractedQuery
+// ============================================================================
+
+struct ExtractedField {
+    name: String,
+    type_id: TypeId,
+    value: Option<Box<dyn Any + Send + Sync>>,
+    raw: Vec<String>,
+    default_raw: Vec<String>,
+}
+
+/// Typed values produced by [`QuerySpec::extract`], in field declaration order.
+pub struct ExtractedQuery {
+    fields: Vec<ExtractedField>,
+}
+
+impl ExtractedQuery {
+    fn find(&self, name: &str) -> Option<&ExtractedField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    fn find_typed_mut(&mut self, name: &str, type_id: TypeId) -> Option<&mut ExtractedField> {
+        self.fields
+            .iter_mut()
+            .find(|field| field.name == name && field.type_id == type_id)
+    }
+
+    /// Typed value of a field, without re-parsing.
+    ///
+    /// Multi-valued fields are read as `Vec<T>`. Returns `None` for unknown
+    /// fields, unset optional fields and type mismatches.
+    #[must_use]
+    pub fn get<T: 'static>(&self, name: &str) -> Option<&T> {
+        self.find(name)?.value.as_ref()?.downcast_ref()
+    }
+
+    /// Whether a field currently holds a value.
+    #[must_use]
+    pub fn is_set(&self, name: &str) -> bool {
+        self.find(name).is_some_and(|field| field.value.is_some())
+    }
+
+    /// Replace the value of a single-valued field.
+    ///
+    /// Returns `false` (and changes nothing) for unknown fields and type
+    /// mismatches. The value is not checked against `one_of`.
+    pub fn set<T: QueryValue>(&mut self, name: &str, value: T) -> bool {
+        let Some(field) = self.find_typed_mut(name, TypeId::of::<T>()) else {
+            return false;
+        };
+        field.raw = vec![value.to_query()];
+        field.value = Some(Box::new(value));
+        true
+    }
+
+    /// Replace the values of a multi-valued field.
+    ///
+    /// Returns `false` (and changes nothing) for unknown fields and type
+    /// mismatches. The values are not checked against `one_of`.
+    pub fn set_all<T: QueryValue>(
+        &mut self,
+        name: &str,
+        values: impl IntoIterator<Item = T>,
+    ) -> bool {
+        let Some(field) = self.find_typed_mut(name, TypeId::of::<Vec<T>>()) else {
+            return false;
+        };
+        let values: Vec<T> = values.into_iter().collect();
+        field.raw = values.iter().map(QueryValue::to_query).collect();
+        field.value = Some(Box::new(values));
+        true
+    }
+
+    /// Serialize back to a query string (without the leading `?`).
+    ///
+    /// Fields appear in declaration order, multi-valued entries in their own
+    /// order, so equal values always produce equal strings. Unset fields and
+    /// fields equal to their default are left out.
+    #[must_use]
+    pub fn to_query_string(&self) -> String {
+        self.fields
+            .iter()
+            .filter(|field| field.raw != field.default_raw)
+            .flat_map(|field| {
+                let key = encode_uri_component(&field.name);
+                field
+                    .raw
+                    .iter()
+                    .map(move |value| format!("{key}={}", encode_uri_component(value)))
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
+
+impl fmt::Debug for ExtractedQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.fields.iter().map(|field| (&field.name, &field.raw)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::error::NavigationError;
+    use crate::test_support::{page, push_result};
+    use crate::{init_router, NavigationResult, Navigator};
+    use gpui::TestAppContext;
+
+    fn listing_spec() -> QuerySpec {
+        QuerySpec::new()
+            .field::<u32>("page")
+            .default(1)
+            .field::<String>("sort")
+            .one_of(["name", "date"])
+            .default("name")
+            .field_opt::<bool>("archived")
+            .field_vec::<String>("tag")
+            .build()
+    }
+
+    fn query(s: &str) -> QueryParams {
+        QueryParams::from_query_string(s)
+    }
+
+    #[test]
+    fn test_defaults_fill_absent_fields() {
+        let extracted = listing_spec().extract(&query("")).unwrap();
+
+        assert_eq!(extracted.get::<u32>("page"), Some(&1));
+        assert_eq!(extracted.get::<String>("sort"), Some(&"name".to_string()));
+        assert_eq!(extracted.get::<bool>("archived"), None);
+        assert!(!extracted.is_set("archived"));
+        assert_eq!(extracted.get::<Vec<String>>("tag"), Some(&Vec::new()));
+        assert_eq!(extracted.to_query_string(), "");
+    }
+
+    #[test]
+    fn test_values_are_parsed_once_and_typed() {
+        let extracted = listing_spec()
+            .extract(&query("page=7&sort=date&archived=true"))
+            .unwrap();
+
+        assert_eq!(extracted.get::<u32>("page"), Some(&7));
+        assert_eq!(extracted.get::<bool>("archived"), Some(&true));
+        // Wrong type or unknown field
+        assert_eq!(extracted.get::<i64>("page"), None);
+        assert_eq!(extracted.get::<u32>("missing"), None);
+    }
+
+    #[test]
+    fn test_extract_reports_field_and_reason() {
+        let err = listing_spec().extract(&query("page=abc")).unwrap_err();
+        assert_eq!(err.field(), "page");
+        assert!(matches!(err.reason(), QueryErrorReason::Parse { value, .. } if value == "abc"));
+
+        let err = listing_spec().extract(&query("sort=size")).unwrap_err();
+        assert_eq!(err.field(), "sort");
+        assert_eq!(
+            err.reason(),
+            &QueryErrorReason::NotAllowed {
+                value: "size".to_string(),
+                allowed: vec!["name".to_string(), "date".to_string()],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "query parameter 'sort': 'size' is not one of [name, date]"
+        );
+    }
+
+    #[test]
+    fn test_required_field_without_default() {
+        let spec = QuerySpec::new().field::<u64>("id").build();
+
+        let err = spec.extract(&query("")).unwrap_err();
+        assert_eq!(err.field(), "id");
+        assert_eq!(err.reason(), &QueryErrorReason::Missing);
+
+        let (extracted, warnings) = spec.extract_lossy(&query(""));
+        assert_eq!(extracted.get::<u64>("id"), None);
+        assert_eq!(warnings, vec![err]);
+    }
+
+    #[test]
+    fn test_extract_lossy_substitutes_defaults() {
+        let (extracted, warnings) =
+            listing_spec().extract_lossy(&query("page=-3&sort=size&archived=maybe&tag=a"));
+
+        assert_eq!(extracted.get::<u32>("page"), Some(&1));
+        assert_eq!(extracted.get::<String>("sort"), Some(&"name".to_string()));
+        assert_eq!(extracted.get::<bool>("archived"), None);
+        assert_eq!(
+            extracted.get::<Vec<String>>("tag"),
+            Some(&vec!["a".to_string()])
+        );
+
+        let fields: Vec<&str> = warnings.iter().map(QueryError::field).collect();
+        assert_eq!(fields, ["page", "sort", "archived"]);
+    }
+
+    #[test]
+    fn test_multi_value_field() {
+        let spec = QuerySpec::new()
+            .field_vec::<u8>("level")
+            .one_of([1, 2, 3])
+            .default(1)
+            .default(2)
+            .build();
+
+        let extracted = spec.extract(&query("level=3&level=1")).unwrap();
+        assert_eq!(extracted.get::<Vec<u8>>("level"), Some(&vec![3, 1]));
+
+        let extracted = spec.extract(&query("")).unwrap();
+        assert_eq!(extracted.get::<Vec<u8>>("level"), Some(&vec![1, 2]));
+
+        // Invalid entries are dropped in lossy mode
+        let (extracted, warnings) = spec.extract_lossy(&query("level=9&level=2&level=x"));
+        assert_eq!(extracted.get::<Vec<u8>>("level"), Some(&vec![2]));
+        assert_eq!(warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_round_trip_follows_declaration_order() {
+        let spec = listing_spec();
+        let mut extracted = spec
+            .extract(&query("tag=b&archived=true&page=3&tag=a%20b"))
+            .unwrap();
+        assert_eq!(
+            extracted.to_query_string(),
+            "page=3&archived=true&tag=b&tag=a%20b"
+        );
+
+        assert!(extracted.set("page", 1_u32));
+        assert!(extracted.set("sort", "date".to_string()));
+        assert!(extracted.set_all("tag", ["x".to_string()]));
+        assert!(!extracted.set("page", "1".to_string()));
+        assert_eq!(extracted.to_query_string(), "sort=date&archived=true&tag=x");
+
+        let reparsed = spec.extract(&query(&extracted.to_query_string())).unwrap();
+        assert_eq!(reparsed.to_query_string(), extracted.to_query_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "not in its one_of set")]
+    fn test_default_outside_allowed_set_panics() {
+        let _ = QuerySpec::new()
+            .field::<String>("sort")
+            .one_of(["name", "date"])
+            .default("size");
+    }
+
+    #[test]
+    #[should_panic(expected = "declared twice")]
+    fn test_duplicate_field_panics() {
+        let _ = QuerySpec::new().field::<u32>("page").field::<u32>("page");
+    }
+
+    #[test]
+    fn test_schema_strips_unknown_keys_and_bad_values() {
+        let schema = QuerySchema::new(&[("page", "u32"), ("q", "string"), ("all", "bool")]);
+
+        let kept = schema.apply("q=a%20b&page=2&all=true", QueryViolation::Strip);
+        assert_eq!(kept.unwrap(), "q=a%20b&page=2&all=true");
+
+        let kept = schema.apply("page=-1&debug=1&q=x&all=yes", QueryViolation::Strip);
+        assert_eq!(kept.unwrap(), "q=x");
+
+        let err = schema
+            .apply("debug=1&page=-1", QueryViolation::Reject)
+            .unwrap_err();
+        assert_eq!(err.field(), "page");
+        assert!(matches!(err.reason(), QueryErrorReason::Parse { value, .. } if value == "-1"));
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown type 'uint'")]
+    fn test_schema_unknown_type_panics() {
+        let _ = QuerySchema::new(&[("page", "uint")]);
+    }
+
+    #[gpui::test]
+    fn test_query_schema_filters_navigation_query(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router
+                    .add_route(page("/items").query_schema(&[("page", "u32"), ("sort", "string")]));
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/items?page=2&sort=name"));
+        assert_eq!(cx.read(Navigator::current_path), "/items?page=2&sort=name");
+
+        // Unknown key stripped
+        cx.update(|cx| Navigator::push(cx, "/items?page=3&debug=1"));
+        assert_eq!(cx.read(Navigator::current_path), "/items?page=3");
+
+        // Type violation dropped, along with the now empty query
+        cx.update(|cx| Navigator::push(cx, "/items?page=two"));
+        assert_eq!(cx.read(Navigator::current_path), "/items");
+    }
+
+    #[gpui::test]
+    fn test_query_schema_reject_fails_navigation(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/items")
+                        .query_schema(&[("page", "u32")])
+                        .query_violation(QueryViolation::Reject),
+                );
+            });
+        });
+
+        assert!(push_result(cx, "/items?page=2&debug=1").is_success());
+        assert_eq!(cx.read(Navigator::current_path), "/items?page=2");
+
+        let result = push_result(cx, "/items?page=two");
+        assert!(matches!(
+            result,
+            NavigationResult::Error(NavigationError::InvalidParams { ref message })
+                if message.contains("'page'")
+        ));
+        assert_eq!(cx.read(Navigator::current_path), "/items?page=2");
+    }
+}
@@ -0,0 +1,274 @@
+//! Data refresh callbacks that run while a route is active.
+//!
+//! [`Route::refetch_on_focus`](crate::Route::refetch_on_focus) and
+//! [`Route::poll`](crate::Route::poll) attach callbacks that the
+//! [`GlobalRouter`] runs only while the route is in the match stack. A
+//! route becomes active when a navigation puts it in the stack and stays
+//! active while the same route stays at the same depth — a param change
+//! such as `/users/1` → `/users/2` keeps it active and hands the callbacks
+//! the new params. Leaving the stack stops polling and focus callbacks.
+//!
+//! The crate does not observe windows itself. Tell the router about focus
+//! through [`RouterFocus`], typically from a window activation observer:
+//!
+//! ```ignore
+//! use gpui_navigator::RouterFocus;
+//!
+//! cx.observe_window_activation(window, |_, window, cx| {
+//!     if window.is_window_active() {
+//!         cx.notify_app_focused();
+//!     }
+//! })
+//! .detach();
+//! ```
+
+use crate::context::GlobalRouter;
+use crate::resolve::MatchStack;
+use crate::route::Route;
+use crate::{debug_log, trace_log, RouteParams};
+use gpui::App;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Callback run by [`Route::refetch_on_focus`](crate::Route::refetch_on_focus)
+/// and [`Route::poll`](crate::Route::poll).
+pub type RefetchFn = Arc<dyn Fn(&App, &RouteParams) + Send + Sync>;
+
+// ============================================================================
+// Active routes
+// ============================================================================
+
+/// A route in the match stack that has refetch callbacks.
+#[derive(Clone)]
+struct ActiveRoute {
+    /// Identifies this activation; polls stop once it is gone.
+    token: u64,
+    depth: usize,
+    route: Arc<Route>,
+    /// Accumulated params of the entry, refreshed on every navigation.
+    params: RouteParams,
+}
+
+/// Routes whose refetch callbacks are currently running.
+#[derive(Clone, Default)]
+pub(crate) struct RouteTasks {
+    active: Vec<ActiveRoute>,
+    next_token: u64,
+}
+
+impl RouteTasks {
+    /// Bring the active set in line with `stack` after a navigation.
+    ///
+    /// Routes that left the stack are dropped, which stops their polls at
+    /// the next tick. Routes that stayed get the entry's current params.
+    /// Routes that entered start their polls.
+    pub(crate) fn sync(&mut self, stack: &MatchStack, cx: &App) {
+        self.active.retain_mut(|active| {
+            let entry = stack
+                .at_depth(active.depth)
+                .filter(|entry| Arc::ptr_eq(&entry.route, &active.route));
+            if let Some(entry) = entry {
+                active.params = entry.params.clone();
+                true
+            } else {
+                debug_log!(
+                    "Route '{}' left, stopping refetch",
+                    active.route.config.path
+                );
+                false
+            }
+        });
+
+        for entry in stack.entries() {
+            let route = &entry.route;
+            if route.refetch_on_focus.is_none() && route.polls.is_empty() {
+                continue;
+            }
+            let running = self
+                .active
+                .iter()
+                .any(|active| active.depth == entry.depth && Arc::ptr_eq(&active.route, route));
+            if running {
+                continue;
+            }
+
+            let token = self.next_token;
+            self.next_token += 1;
+            debug_log!("Route '{}' entered, starting refetch", route.config.path);
+            for (index, (interval, _)) in route.polls.iter().enumerate() {
+                spawn_poll(cx, token, index, *interval);
+            }
+            self.active.push(ActiveRoute {
+                token,
+                depth: entry.depth,
+                route: Arc::clone(route),
+                params: entry.params.clone(),
+            });
+        }
+    }
+
+    /// Poll `index` of the activation `token` with its current params, if
+    /// the activation is still running.
+    fn poll(&self, token: u64, index: usize) -> Option<(RefetchFn, RouteParams)> {
+        let active = self.active.iter().find(|active| active.token == token)?;
+        let (_, callback) = active.route.polls.get(index)?;
+        Some((Arc::clone(callback), active.params.clone()))
+    }
+
+    /// Focus callbacks of every active route, with their current params.
+    fn focus_callbacks(&self) -> Vec<(RefetchFn, RouteParams)> {
+        self.active
+            .iter()
+            .filter_map(|active| {
+                let callback = active.route.refetch_on_focus.as_ref()?;
+                Some((Arc::clone(callback), active.params.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Spawn the timer loop for poll `index` of activation `token`.
+///
+/// Each tick looks the activation up on the router; once it is gone (the
+/// route left the stack) the loop ends.
+fn spawn_poll(cx: &App, token: u64, index: usize, interval: Duration) {
+    cx.spawn(async move |cx| loop {
+        cx.background_executor().timer(interval).await;
+        let running = cx.update(|cx| {
+            let Some((callback, params)) = cx
+                .try_global::<GlobalRouter>()
+                .and_then(|router| router.route_tasks().poll(token, index))
+            else {
+                return false;
+            };
+            trace_log!("Polling route (activation {}, poll {})", token, index);
+            callback(cx, &params);
+            true
+        });
+        if !matches!(running, Ok(true)) {
+            break;
+        }
+    })
+    .detach();
+}
+
+// ============================================================================
+// RouterFocus
+// ============================================================================
+
+/// Tells the router that the app regained focus.
+///
+/// Implemented for [`App`]. Wire it to the app's window activation
+/// observer; the router runs the
+/// [`refetch_on_focus`](crate::Route::refetch_on_focus) callbacks of every
+/// active route.
+pub trait RouterFocus {
+    /// Run the focus callbacks of the routes in the match stack.
+    fn notify_app_focused(&mut self);
+}
+
+impl RouterFocus for App {
+    fn notify_app_focused(&mut self) {
+        let Some(router) = self.try_global::<GlobalRouter>() else {
+            return;
+        };
+        let callbacks = router.route_tasks().focus_callbacks();
+        debug_log!("App focused, running {} refetch callbacks", callbacks.len());
+        for (callback, params) in callbacks {
+            callback(self, &params);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::test_support::page;
+    use crate::{init_router, Navigator};
+    use gpui::TestAppContext;
+    use std::sync::Mutex;
+
+    fn advance(cx: &mut TestAppContext, secs: u64) {
+        cx.executor().advance_clock(Duration::from_secs(secs));
+        cx.run_until_parked();
+    }
+
+    #[gpui::test]
+    fn test_poll_runs_while_active(cx: &mut TestAppContext) {
+        let ticks = Arc::new(Mutex::new(0));
+        let counter = Arc::clone(&ticks);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/live").poll(Duration::from_secs(1), move |_, _| {
+                    *counter.lock().unwrap() += 1;
+                }));
+            });
+        });
+
+        // Not active yet
+        advance(cx, 2);
+        assert_eq!(*ticks.lock().unwrap(), 0);
+
+        cx.update(|cx| Navigator::push(cx, "/live"));
+        advance(cx, 3);
+        assert_eq!(*ticks.lock().unwrap(), 3);
+
+        cx.update(|cx| Navigator::push(cx, "/"));
+        advance(cx, 3);
+        assert_eq!(*ticks.lock().unwrap(), 3);
+    }
+
+    #[gpui::test]
+    fn test_focus_runs_callbacks_of_active_routes(cx: &mut TestAppContext) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/inbox/:folder").refetch_on_focus(move |_, params| {
+                    sink.lock().unwrap().push(params.get("folder").cloned());
+                }));
+            });
+        });
+
+        cx.update(RouterFocus::notify_app_focused);
+        assert!(seen.lock().unwrap().is_empty());
+
+        cx.update(|cx| Navigator::push(cx, "/inbox/unread"));
+        cx.update(RouterFocus::notify_app_focused);
+        assert_eq!(*seen.lock().unwrap(), [Some("unread".to_string())]);
+
+        cx.update(|cx| Navigator::push(cx, "/"));
+        cx.update(RouterFocus::notify_app_focused);
+        assert_eq!(seen.lock().unwrap().len(), 1);
+    }
+
+    #[gpui::test]
+    fn test_param_change_keeps_poll_with_new_params(cx: &mut TestAppContext) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/builds/:id").poll(
+                    Duration::from_secs(1),
+                    move |_, params| {
+                        sink.lock()
+                            .unwrap()
+                            .push(params.get("id").cloned().unwrap_or_default());
+                    },
+                ));
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/builds/1"));
+        advance(cx, 1);
+        cx.update(|cx| Navigator::push(cx, "/builds/2"));
+        advance(cx, 1);
+
+        // One poll loop, now reading the new params
+        assert_eq!(*seen.lock().unwrap(), ["1", "2"]);
+    }
+}
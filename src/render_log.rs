@@ -0,0 +1,322 @@
+//! A record of what outlets render, for end-to-end tests.
+//!
+//! Resolution tests check the match stack; they cannot tell whether
+//! [`RouterView`](crate::RouterView) and the outlets below it actually build
+//! the routes it holds once a window draws. While a [`RenderLog`] is
+//! recording, every outlet that builds a route logs a
+//! [`RenderEvent::Route`], and builders can log their own
+//! [`RenderEvent::Marker`] with [`mark`].
+//!
+//! ```ignore
+//! let log = RenderLog::start();
+//! cx.add_window(|_, _| RouterView::new());
+//! cx.run_until_parked();
+//! assert_eq!(log.take().routes(), [(0, None, "/"), (1, None, "users")]);
+//!
+//! cx.update(|cx| Navigator::push(cx, "/settings"));
+//! cx.run_until_parked();
+//! assert!(!log.take().has_marker("users"));
+//! ```
+//!
+//! Recording is per thread, which is where GPUI tests draw, and stops when
+//! the log is dropped. Without a log nothing is kept. Available in the
+//! crate's own tests and with the `test-util` feature.
+
+use crate::route::Route;
+use std::cell::RefCell;
+
+/// Something an outlet or builder rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderEvent {
+    /// An outlet built a route
+    Route {
+        /// Match stack depth the outlet renders
+        depth: usize,
+        /// Name of the outlet, `None` for default outlets
+        outlet: Option<String>,
+        /// Path of the route as declared, e.g. `:id`
+        path: String,
+    },
+    /// A builder or component called [`mark`]
+    Marker(String),
+}
+
+thread_local! {
+    /// Events since the last take, `None` while no log is recording.
+    static EVENTS: RefCell<Option<Vec<RenderEvent>>> = const { RefCell::new(None) };
+}
+
+/// Records render events on this thread until dropped.
+///
+/// Starting a log discards whatever an earlier one left unread.
+#[must_use]
+pub struct RenderLog {
+    _private: (),
+}
+
+impl RenderLog {
+    /// Start recording.
+    pub fn start() -> Self {
+        EVENTS.with(|events| *events.borrow_mut() = Some(Vec::new()));
+        Self { _private: () }
+    }
+
+    /// Take the events recorded since the last take.
+    #[must_use]
+    pub fn take(&self) -> Rendered {
+        let events = EVENTS.with(|events| events.borrow_mut().as_mut().map(std::mem::take));
+        Rendered {
+            events: events.unwrap_or_default(),
+        }
+    }
+}
+
+/// Events a [`RenderLog`] recorded, in render order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Rendered {
+    /// Every event, in render order
+    pub events: Vec<RenderEvent>,
+}
+
+impl Rendered {
+    /// The routes built, as `(depth, outlet, path)` sorted and without
+    /// repeats, so it does not matter how often or in which order GPUI
+    /// rendered them.
+    #[must_use]
+    pub fn routes(&self) -> Vec<(usize, Option<&str>, &str)> {
+        let mut routes: Vec<_> = self
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                RenderEvent::Route {
+                    depth,
+                    outlet,
+                    path,
+                } => Some((*depth, outlet.as_deref(), path.as_str())),
+                RenderEvent::Marker(_) => None,
+            })
+            .collect();
+        routes.sort_unstable();
+        routes.dedup();
+        routes
+    }
+
+    /// The markers, in render order.
+    #[must_use]
+    pub fn markers(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                RenderEvent::Marker(marker) => Some(marker.as_str()),
+                RenderEvent::Route { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Return `true` if `marker` was logged.
+    #[must_use]
+    pub fn has_marker(&self, marker: &str) -> bool {
+        self.markers().contains(&marker)
+    }
+}
+
+impl Drop for RenderLog {
+    fn drop(&mut self) {
+        EVENTS.with(|events| *events.borrow_mut() = None);
+    }
+}
+
+/// Log `marker` if a [`RenderLog`] is recording.
+///
+/// Call it from route builders and component `render` methods to prove
+/// they ran.
+pub fn mark(marker: impl Into<String>) {
+    push(|| RenderEvent::Marker(marker.into()));
+}
+
+/// Log that an outlet at `depth` built `route`.
+pub(crate) fn record_route(depth: usize, outlet: Option<&str>, route: &Route) {
+    push(|| RenderEvent::Route {
+        depth,
+        outlet: outlet.map(str::to_string),
+        path: route.config.path.clone(),
+    });
+}
+
+fn push(event: impl FnOnce() -> RenderEvent) {
+    EVENTS.with(|events| {
+        if let Some(events) = events.borrow_mut().as_mut() {
+            events.push(event());
+        }
+    });
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::{init_router, render_router_outlet, Navigator, RouterOutlet, RouterView};
+    use gpui::{
+        div, Context, ElementId, IntoElement, ParentElement, Render, TestAppContext, Window,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct Counter;
+
+    impl Render for Counter {
+        fn render(
+            &mut self,
+            _window: &mut Window,
+            _cx: &mut Context<'_, Self>,
+        ) -> impl IntoElement {
+            mark("counter");
+            div().child("counter")
+        }
+    }
+
+    /// `/` → `users` → `:id`, with `settings` and a `counter` component
+    /// beside `users` and a `side` named outlet in the root layout.
+    fn init_fixture(cx: &mut TestAppContext, created: Arc<AtomicUsize>) {
+        let page = |path: &str, marker: &'static str| {
+            Route::new(path, move |_, _, _| {
+                mark(marker);
+                div().child(marker).into_any_element()
+            })
+        };
+        let users = Route::new("users", |window, cx, _| {
+            mark("users");
+            // An entity outlet: it renders after this builder returns
+            let outlet =
+                window.use_keyed_state(ElementId::Name("users-outlet".into()), cx, |_, _| {
+                    RouterOutlet::new()
+                });
+            div().child(outlet).into_any_element()
+        })
+        .children(vec![Route::new(":id", |_, _, params| {
+            let marker = format!("user {}", &params["id"]);
+            mark(marker.clone());
+            div().child(marker).into_any_element()
+        })
+        .into()]);
+        let counter = Route::component("counter", move || {
+            created.fetch_add(1, Ordering::SeqCst);
+            Counter
+        });
+        let root = Route::new("/", |window, cx, _| {
+            mark("root");
+            // Named outlet first: the default outlet moves the depth on
+            let side = render_router_outlet(window, cx, Some("side"));
+            let main = render_router_outlet(window, cx, None);
+            div().child(side).child(main).into_any_element()
+        })
+        .named_outlet("side", vec![page("", "side").into()])
+        .children(vec![
+            page("", "home").into(),
+            users.into(),
+            page("settings", "settings").into(),
+            counter.into(),
+        ]);
+        cx.update(|cx| init_router(cx, |router| router.add_route(root)));
+    }
+
+    /// Draw the window and return what rendered, markers deduplicated.
+    fn draw(cx: &mut TestAppContext, log: &RenderLog) -> (Rendered, Vec<String>) {
+        cx.run_until_parked();
+        let rendered = log.take();
+        let mut markers: Vec<String> = rendered.markers().into_iter().map(String::from).collect();
+        markers.sort_unstable();
+        markers.dedup();
+        (rendered, markers)
+    }
+
+    #[gpui::test]
+    fn test_outlets_render_matched_routes(cx: &mut TestAppContext) {
+        init_fixture(cx, Arc::default());
+        let log = RenderLog::start();
+        cx.add_window(|_, _| RouterView::new());
+
+        // Initial render: the root layout, its index and the named outlet
+        let (rendered, markers) = draw(cx, &log);
+        assert_eq!(
+            rendered.routes(),
+            [(0, None, "/"), (1, None, ""), (1, Some("side"), "")]
+        );
+        assert_eq!(markers, ["home", "root", "side"]);
+
+        // Deeper: the entity outlet in `users` renders depth 2
+        cx.update(|cx| Navigator::push(cx, "/users/1"));
+        let (rendered, markers) = draw(cx, &log);
+        assert_eq!(
+            rendered.routes(),
+            [
+                (0, None, "/"),
+                (1, None, "users"),
+                (1, Some("side"), ""),
+                (2, None, ":id")
+            ]
+        );
+        assert_eq!(markers, ["root", "side", "user 1", "users"]);
+
+        // Same route, other params
+        cx.update(|cx| Navigator::push(cx, "/users/2"));
+        let (_, markers) = draw(cx, &log);
+        assert_eq!(markers, ["root", "side", "user 2", "users"]);
+
+        // Sibling swap at depth 1
+        cx.update(|cx| Navigator::push(cx, "/settings"));
+        let (rendered, markers) = draw(cx, &log);
+        assert_eq!(
+            rendered.routes(),
+            [(0, None, "/"), (1, None, "settings"), (1, Some("side"), "")]
+        );
+        assert_eq!(markers, ["root", "settings", "side"]);
+
+        // Back: the entity outlet is created again and finds its depth
+        cx.update(Navigator::back);
+        let (rendered, markers) = draw(cx, &log);
+        assert!(rendered.routes().contains(&(2, None, ":id")));
+        assert_eq!(markers, ["root", "side", "user 2", "users"]);
+    }
+
+    #[gpui::test]
+    fn test_component_route_renders_cached_entity(cx: &mut TestAppContext) {
+        let created = Arc::new(AtomicUsize::new(0));
+        init_fixture(cx, Arc::clone(&created));
+        let log = RenderLog::start();
+        cx.add_window(|_, _| RouterView::new());
+        cx.run_until_parked();
+
+        let mut renders = 0;
+        for _ in 0..2 {
+            cx.update(|cx| Navigator::push(cx, "/counter"));
+            let (rendered, _) = draw(cx, &log);
+            assert!(rendered.routes().contains(&(1, None, "counter")));
+            renders += rendered
+                .markers()
+                .iter()
+                .filter(|marker| **marker == "counter")
+                .count();
+
+            cx.update(|cx| Navigator::push(cx, "/settings"));
+            assert!(!draw(cx, &log).0.has_marker("counter"));
+        }
+
+        // Rendered on both visits from the one cached entity
+        assert!(renders >= 2);
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_nothing_recorded_without_log() {
+        mark("early");
+        let log = RenderLog::start();
+        mark("kept");
+        assert_eq!(log.take().markers(), ["kept"]);
+        assert!(log.take().events.is_empty());
+        drop(log);
+
+        mark("late");
+        assert!(RenderLog::start().take().events.is_empty());
+    }
+}
@@ -35,6 +35,20 @@
 //! [2] Route(":id")        params={id: "42"}         ← outlet depth 2
 //! ```
 //!
+//! # The Root Route
+//!
+//! `/` has no segments of its own, so it resolves like any pathless route:
+//! - Without children it is a page and matches `/` only.
+//! - With children it is a layout wrapping every path its children match.
+//!   At `/` the stack holds the layout followed by its index child (`""` or
+//!   `"index"`), which renders in the layout's outlet; without an index
+//!   child the stack ends at the layout and its outlet stays empty.
+//! - A path none of its children match backtracks past the layout, so a
+//!   top-level `/foo` registered next to it still matches on its own.
+//!
+//! Guards follow the same split: those on a `/` page run for `/` only, those
+//! on a `/` layout for every path.
+//!
 //! # Depth Tracking
 //!
 //! Outlets discover their depth via a thread-local counter:
@@ -42,10 +56,17 @@
 //! - Each outlet sets depth = `parent_depth` + 1 and renders `match_stack[depth]`
 //! - Works for both functional (`render_router_outlet`) and entity (`RouterOutlet`) APIs
 
-use crate::nested::{normalize_path, trim_slashes};
-use crate::route::Route;
+use crate::error::{RouteError, RouteTreeError};
+use crate::extensions::Extensions;
+use crate::nested::{build_child_path, trim_slashes};
+use crate::route::{Route, RouteFlag};
+use crate::route_env::RouteEnv;
 use crate::{debug_log, trace_log, warn_log, RouteParams};
+use gpui::{AnyElement, App, SharedString, Window};
 use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 // ============================================================================
@@ -144,11 +165,53 @@ pub fn set_parent_depth(depth: usize) {
 /// Reset outlet tracking state to "no parent".
 ///
 /// Called by `router_view()` at the start of a render cycle,
-/// or between render passes to ensure clean state.
+/// or between render passes to ensure clean state. It overwrites whatever
+/// an earlier (possibly panicked) frame left behind, never fails, and is
+/// safe to call re-entrantly from inside a builder.
 pub fn reset_outlet_depth() {
     PARENT_DEPTH.with(|p| p.set(None));
 }
 
+/// Snapshot of the outlet depth state, put back if a panic unwinds past it.
+///
+/// Create one before running a route builder. If the builder panics, call
+/// [`restore`](Self::restore) after catching it — or let the guard drop
+/// while unwinding — so the depths set by half-rendered children do not
+/// leak into siblings or later frames. On normal exit nothing is restored:
+/// deferred child entities still need the depth the builder set.
+#[must_use]
+pub struct OutletDepthGuard {
+    saved: Option<usize>,
+}
+
+impl OutletDepthGuard {
+    /// Capture the current outlet depth state.
+    pub fn new() -> Self {
+        Self {
+            saved: current_parent_depth(),
+        }
+    }
+
+    /// Put the captured depth state back.
+    pub fn restore(&self) {
+        PARENT_DEPTH.with(|p| p.set(self.saved));
+    }
+}
+
+impl Default for OutletDepthGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for OutletDepthGuard {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.restore();
+        }
+    }
+}
+
 /// Get current outlet depth without modifying state. Used by named outlets.
 #[must_use]
 pub fn current_outlet_depth() -> usize {
@@ -171,10 +234,141 @@ pub fn current_parent_depth() -> Option<usize> {
 pub struct MatchEntry {
     /// The matched route at this level
     pub route: Arc<Route>,
-    /// Accumulated params (includes all params from parent levels + this level)
+    /// Accumulated params (includes all params from parent levels + this level,
+    /// unless the route isolates its params)
     pub params: RouteParams,
     /// Depth in the hierarchy (0 = root/top-level route)
     pub depth: usize,
+    /// Accumulated route pattern up to this level (e.g. `/users/:id`).
+    pattern: String,
+    /// Accumulated matched URL up to this level (e.g. `/users/42`).
+    url: String,
+    /// Params captured by this level alone.
+    frame: RouteParams,
+    /// Why this level could not render, if it failed.
+    error: Option<RouteError>,
+    /// Scratch data of the navigation that committed this entry (leaf only).
+    extensions: Extensions,
+    /// Environment values of this route and its ancestors, nearest first.
+    env: RouteEnv,
+    /// Identity of this level, see [`key`](Self::key).
+    key: SharedString,
+    /// Hash of `key`.
+    key_hash: u64,
+}
+
+impl MatchEntry {
+    /// Return the accumulated route pattern up to and including this level.
+    ///
+    /// Patterns of ancestors are joined the same way the resolver consumed
+    /// them, so `/users` → `:id` → `posts` yields `/users/:id/posts`.
+    /// Pathless layouts and index routes contribute nothing and report the
+    /// pattern of their parent.
+    #[must_use]
+    pub fn accumulated_pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Return the accumulated URL up to and including this level.
+    ///
+    /// This is [`accumulated_pattern`](Self::accumulated_pattern) with the
+    /// param values of this entry substituted (e.g. `/users/42/posts`).
+    #[must_use]
+    pub fn accumulated_url(&self) -> &str {
+        &self.url
+    }
+
+    /// Return the params captured by this level alone.
+    ///
+    /// [`params`](Self::params) is the merged view a builder receives; this
+    /// is only what this route's own segments captured (already prefixed
+    /// by [`Route::param_prefix`]). Layouts and index routes capture
+    /// nothing.
+    #[must_use]
+    pub const fn own_params(&self) -> &RouteParams {
+        &self.frame
+    }
+
+    /// Return why this level could not render, if it failed.
+    ///
+    /// Set when its loader or a param validator fails during navigation, or
+    /// when its builder is missing or panics during render. Cleared by a
+    /// successful [`RetryHandle::retry`](crate::RetryHandle::retry); a new
+    /// navigation starts from a fresh stack.
+    #[must_use]
+    pub const fn error(&self) -> Option<&RouteError> {
+        self.error.as_ref()
+    }
+
+    /// Return the scratch data guards and middleware left for the navigation
+    /// that produced this entry.
+    ///
+    /// Only the leaf of a committed navigation carries any; see
+    /// [`NavigationRequest::extensions`](crate::NavigationRequest::extensions).
+    /// The data lives as long as the entry stays in the match stack.
+    #[must_use]
+    pub const fn navigation_extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    /// Check whether the route at this level has `flag` set.
+    #[must_use]
+    pub fn has_flag(&self, flag: RouteFlag) -> bool {
+        self.route.has_flag(flag)
+    }
+
+    /// Return the environment value of type `T` in effect at this level.
+    ///
+    /// Set by this route or, failing that, its nearest ancestor that sets
+    /// one; see [`Route::env`].
+    #[must_use]
+    pub fn env<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.env.get()
+    }
+
+    /// Return every environment value in effect at this level.
+    #[must_use]
+    pub const fn env_values(&self) -> &RouteEnv {
+        &self.env
+    }
+
+    /// Return the identity of this level, for element ids and keyed state.
+    ///
+    /// Derived from the accumulated pattern, the params sorted by key and
+    /// the depth, and computed once when the path resolves. Every entry of
+    /// a stack has its own key; resolving the same path again gives the
+    /// same keys, and other param values give other keys.
+    #[must_use]
+    pub fn key(&self) -> SharedString {
+        self.key.clone()
+    }
+
+    /// Return a hash of [`key`](Self::key), for use as a map key.
+    #[must_use]
+    pub const fn key_hash(&self) -> u64 {
+        self.key_hash
+    }
+
+    /// Build this level's route with its params, keying component routes
+    /// by [`key`](Self::key).
+    pub(crate) fn build(&self, window: &mut Window, cx: &mut App) -> Option<AnyElement> {
+        self.route.build_as(window, cx, &self.key, &self.params)
+    }
+}
+
+/// Identity key of a level at `depth` matched by `pattern` with `params`.
+///
+/// The scheme behind [`MatchEntry::key`], shared with levels resolved
+/// outside a match stack, such as named outlet content.
+pub(crate) fn entry_key(pattern: &str, params: &RouteParams, depth: usize) -> SharedString {
+    format!("{depth}:{pattern}?{}", params.canonical()).into()
+}
+
+/// Hash of an [`entry_key`], stable for the life of the process.
+fn key_hash(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
 }
 
 /// The full resolved route chain for the current path.
@@ -241,18 +435,118 @@ impl MatchStack {
         &self.entries
     }
 
+    /// Return the [`key`](MatchEntry::key) of every entry, root → leaf.
+    #[must_use]
+    pub fn keys(&self) -> Vec<SharedString> {
+        self.entries.iter().map(MatchEntry::key).collect()
+    }
+
+    /// Return the accumulated route pattern of the leaf entry.
+    ///
+    /// Useful for analytics, where page views should aggregate per pattern
+    /// (`/users/:id`) rather than per concrete URL.
+    #[must_use]
+    pub fn leaf_pattern(&self) -> Option<&str> {
+        self.leaf().map(MatchEntry::accumulated_pattern)
+    }
+
+    /// Return the accumulated URL of the leaf entry.
+    #[must_use]
+    pub fn leaf_url(&self) -> Option<&str> {
+        self.leaf().map(MatchEntry::accumulated_url)
+    }
+
+    /// Return the [`name`](crate::route::RouteConfig::name) of the leaf
+    /// entry's route, if it has one.
+    ///
+    /// Read from the matched route itself: a leaf without a name of its own
+    /// has none, even if an ancestor is named.
+    #[must_use]
+    pub fn leaf_route_name(&self) -> Option<&str> {
+        self.leaf()?.route.config.name.as_deref()
+    }
+
     /// Return the accumulated params at the deepest matched level.
     #[must_use]
     pub fn params(&self) -> RouteParams {
         self.leaf().map(|e| e.params.clone()).unwrap_or_default()
     }
 
+    /// Return the params available at `depth`, or `None` if out of range.
+    ///
+    /// These are *accumulated* params: they include every param captured by
+    /// ancestors up to and including that level, but nothing captured deeper.
+    /// A mid-tree layout uses this to read exactly the params of its level
+    /// rather than the leaf's.
+    #[must_use]
+    pub fn params_at_depth(&self, depth: usize) -> Option<&RouteParams> {
+        self.at_depth(depth).map(|entry| &entry.params)
+    }
+
+    /// Return the first error at `depth` or deeper, nearest the root first.
+    ///
+    /// A layout at `depth` uses this to tell whether anything it renders
+    /// failed.
+    #[must_use]
+    pub fn error_at_or_below(&self, depth: usize) -> Option<&RouteError> {
+        self.entries.iter().skip(depth).find_map(MatchEntry::error)
+    }
+
+    /// Record (or with `None`, clear) the error of the entry at `depth`.
+    ///
+    /// Returns whether the entry's error changed.
+    pub(crate) fn set_error(&mut self, depth: usize, error: Option<RouteError>) -> bool {
+        let Some(entry) = self.entries.get_mut(depth) else {
+            return false;
+        };
+        if entry.error == error {
+            return false;
+        }
+        entry.error = error;
+        true
+    }
+
     /// Return `true` if the stack contains an entry at the given `depth`.
     #[must_use]
     pub fn has_depth(&self, depth: usize) -> bool {
         depth < self.entries.len()
     }
 
+    /// Return how many leading levels this stack shares with `other`.
+    ///
+    /// A level is shared when both stacks matched the same route (by `Arc`
+    /// identity) with the same params, and every level above it is shared
+    /// too. Entries at depths below the returned value can keep their
+    /// state across a navigation between the two stacks; `0` means nothing
+    /// is shared, and equal stacks return their length.
+    ///
+    /// From `/a/x` to `/a/y` under an `/a` layout this is `1`: the layout
+    /// stays, only its child is rebuilt.
+    #[must_use]
+    pub fn common_ancestor_depth(&self, other: &Self) -> usize {
+        self.entries
+            .iter()
+            .zip(&other.entries)
+            .take_while(|(a, b)| Arc::ptr_eq(&a.route, &b.route) && a.params == b.params)
+            .count()
+    }
+
+    /// Hand a navigation's scratch data to the leaf entry.
+    pub(crate) fn set_leaf_extensions(&mut self, extensions: Extensions) {
+        if let Some(leaf) = self.entries.last_mut() {
+            leaf.extensions = extensions;
+        }
+    }
+
+    /// Insert a param into every entry, as if the root route had captured it.
+    ///
+    /// Used for values extracted before resolution (e.g. the locale prefix).
+    pub(crate) fn insert_param(&mut self, key: &str, value: &str) {
+        for entry in &mut self.entries {
+            entry.params.insert(key.to_string(), value.to_string());
+        }
+    }
+
     /// Return a multi-line human-readable representation (debug builds only).
     #[cfg(debug_assertions)]
     #[must_use]
@@ -284,14 +578,54 @@ impl MatchStack {
         }
         lines.join("\n")
     }
+
+    /// Assert that the routes of this stack have exactly the `expected`
+    /// config paths, root first.
+    ///
+    /// Available in this crate's tests and with the `test-util` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics on a mismatch, listing expected vs actual path per depth
+    /// followed by the full stack.
+    ///
+    /// ```ignore
+    /// let stack = resolve_match_stack(&routes, "/dashboard/settings");
+    /// stack.assert_paths(&["/dashboard", "settings"]);
+    /// ```
+    #[cfg(any(test, feature = "test-util"))]
+    #[track_caller]
+    pub fn assert_paths(&self, expected: &[&str]) {
+        let actual: Vec<&str> = self
+            .entries
+            .iter()
+            .map(|entry| entry.route.config.path.as_str())
+            .collect();
+        if actual == expected {
+            return;
+        }
+
+        let show = |path: Option<&&str>| path.map_or_else(|| "-".to_string(), |p| format!("{p:?}"));
+        let mut lines = vec!["MatchStack paths differ (expected | actual):".to_string()];
+        for depth in 0..expected.len().max(actual.len()) {
+            let (want, got) = (expected.get(depth), actual.get(depth));
+            let marker = if want == got { " " } else { ">" };
+            lines.push(format!("{marker} [{depth}] {} | {}", show(want), show(got)));
+        }
+        #[cfg(debug_assertions)]
+        lines.push(self.debug_string());
+        panic!("{}", lines.join("\n"));
+    }
 }
 
 // ============================================================================
 // Resolution Algorithm
 // ============================================================================
 
-/// Maximum nesting depth to prevent infinite recursion
-const MAX_DEPTH: usize = 16;
+/// Maximum nesting depth to prevent infinite recursion.
+///
+/// A resolved [`MatchStack`] never has more than this many entries.
+pub const MAX_DEPTH: usize = 16;
 
 /// Resolve the full match stack for a given path against the route tree.
 ///
@@ -306,6 +640,39 @@ const MAX_DEPTH: usize = 16;
 /// 4. At each level, push a `MatchEntry` into the stack
 /// 5. When segments exhausted, try index route (empty path child)
 ///
+/// # Guarantees
+///
+/// `path` is untrusted input (deep links), so for any path and route tree:
+///
+/// - resolution never panics and the stack never exceeds [`MAX_DEPTH`]
+///   entries; route trees nested deeper than that do not match
+/// - the result is either a complete match, a scoped not-found match (see
+///   below) or an empty stack, with depths contiguous from 0 and each
+///   level's params a superset of its parent's
+/// - empty segments are ignored (`/a//b` resolves like `/a/b`), so params
+///   never capture an empty value
+/// - segments are matched literally: `..`, percent-escapes such as `%2f`,
+///   null bytes and non-ASCII text are never decoded or interpreted;
+///   only captured param values are percent-decoded, after matching, and
+///   even then `%2F` and `%5C` stay encoded
+/// - resolution is deterministic, and `normalize_path(path)` resolves
+///   exactly like `path`
+///
+/// # Scoped not-found
+///
+/// When no route matches the whole path, the deepest route with a
+/// [`Route::not_found`] handler whose own segments matched is used instead:
+/// the stack holds its ancestor chain, the route itself, and a synthetic
+/// entry rendering the handler with the unmatched remainder in the
+/// [`UNMATCHED_PARAM`] param. Any complete match, anywhere in the tree,
+/// wins over a scoped not-found. Without one, the stack is empty and the
+/// global 404 page renders.
+///
+/// A [`Route::strict_params`] route without children that matches its own
+/// segments with more left over ends the search: routes after it are not
+/// tried, and only the scoped not-found handlers of its ancestors (or its
+/// own) are still considered.
+///
 /// # Examples
 ///
 /// ```ignore
@@ -316,17 +683,32 @@ const MAX_DEPTH: usize = 16;
 /// ```
 #[must_use]
 pub fn resolve_match_stack(routes: &[Arc<Route>], path: &str) -> MatchStack {
-    let normalized = normalize_path(path);
-    let path_str = trim_slashes(&normalized);
-
-    let segments: Vec<&str> = if path_str.is_empty() {
-        vec![]
-    } else {
-        path_str.split('/').collect()
-    };
+    let segments = path_segments(path);
 
     let mut stack = MatchStack::new();
-    resolve_recursive(routes, &segments, 0, &RouteParams::new(), &mut stack);
+    let root = Prefix {
+        pattern: "/",
+        url: "/",
+        params: None,
+    };
+    let mut search = Search::default();
+    let matched = resolve_recursive(
+        routes,
+        &segments,
+        0,
+        &RouteParams::new(),
+        &root,
+        &mut stack,
+        &mut search,
+    );
+    if let (false, Some(partial)) = (matched, search.partial) {
+        debug_log!(
+            "No complete match for '{}', using scoped not-found at depth {}",
+            path,
+            partial.entries.len() - 1
+        );
+        stack.entries = partial.entries;
+    }
 
     if stack.is_empty() {
         warn_log!("No route matched path '{}'", path);
@@ -347,17 +729,177 @@ pub fn resolve_match_stack(routes: &[Arc<Route>], path: &str) -> MatchStack {
     stack
 }
 
+/// Split a request path into its non-empty segments.
+///
+/// Empty segments (`/a//b`, leading or trailing slashes) carry no meaning and
+/// are dropped, so a param can never capture an empty value. A query string
+/// takes no part in matching.
+fn path_segments(path: &str) -> Vec<&str> {
+    let path = path.split_once('?').map_or(path, |(path, _)| path);
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// Param holding the path remainder a scoped not-found entry could not match.
+///
+/// For `/settings/unknown/page` under a `/settings` route with a
+/// [`Route::not_found`] handler, the synthetic entry carries
+/// `__unmatched = "unknown/page"`.
+pub const UNMATCHED_PARAM: &str = "__unmatched";
+
+/// Deepest scoped not-found candidate seen while resolving.
+struct PartialMatch {
+    /// Ancestor chain, the handling route and the synthetic not-found entry.
+    entries: Vec<MatchEntry>,
+}
+
+/// State shared by every level of one resolution.
+#[derive(Default)]
+struct Search {
+    partial: Option<PartialMatch>,
+    /// A [`Route::strict_params`] route rejected the path: stop trying
+    /// other routes, only scoped not-found handlers are still offered it.
+    halted: bool,
+}
+
+/// Record a scoped not-found candidate for the route at the top of `stack`
+/// if it has a handler and is deeper than the current candidate.
+fn record_partial(
+    route: &Route,
+    unmatched: &[&str],
+    params: &RouteParams,
+    prefix: &Prefix<'_>,
+    stack: &MatchStack,
+    partial: &mut Option<PartialMatch>,
+) {
+    let Some(handler) = route.not_found.as_ref() else {
+        return;
+    };
+    let depth = stack.entries.len();
+    if partial
+        .as_ref()
+        .is_some_and(|partial| partial.entries.len() > depth)
+    {
+        return;
+    }
+
+    let remainder = unmatched.join("/");
+    let mut frame = RouteParams::new();
+    frame.insert(UNMATCHED_PARAM.to_string(), remainder.clone());
+    let mut params = params.clone();
+    params.insert(UNMATCHED_PARAM.to_string(), remainder.clone());
+
+    let mut entries = stack.entries.clone();
+    let env = inherited_env(&entries, handler);
+    let pattern = join_accumulated(prefix.pattern, "*");
+    let key = entry_key(&pattern, &params, depth);
+    entries.push(MatchEntry {
+        route: Arc::clone(handler),
+        params,
+        depth,
+        pattern,
+        url: join_accumulated(prefix.url, &remainder),
+        frame,
+        error: None,
+        extensions: Extensions::default(),
+        env,
+        key_hash: key_hash(&key),
+        key,
+    });
+    trace_log!(
+        "Scoped not-found candidate under '{}' at depth {}",
+        route.config.path,
+        depth
+    );
+    *partial = Some(PartialMatch { entries });
+}
+
+/// Pattern, URL and param namespace accumulated by the ancestors of the
+/// level being resolved.
+struct Prefix<'a> {
+    pattern: &'a str,
+    url: &'a str,
+    /// Nearest `param_prefix` of the route or an ancestor.
+    params: Option<&'a str>,
+}
+
+impl<'a> Prefix<'a> {
+    /// Param namespace in effect for `route`.
+    fn params_for(&self, route: &'a Route) -> Option<&'a str> {
+        route.param_prefix.as_deref().or(self.params)
+    }
+}
+
+/// Params handed to `route`: the inherited set, or only the ancestor chain
+/// when the route isolates its params.
+fn scoped_params(route: &Route, inherited: &RouteParams) -> RouteParams {
+    if route.isolate_params {
+        inherited.isolated()
+    } else {
+        inherited.clone()
+    }
+}
+
+/// Push an entry that captures no params of its own (layout or index).
+fn push_pathless(
+    route: &Arc<Route>,
+    depth: usize,
+    params: RouteParams,
+    prefix: &Prefix<'_>,
+    stack: &mut MatchStack,
+) {
+    let env = inherited_env(&stack.entries, route);
+    let key = entry_key(prefix.pattern, &params, depth);
+    stack.entries.push(MatchEntry {
+        route: Arc::clone(route),
+        params,
+        depth,
+        pattern: prefix.pattern.to_string(),
+        url: prefix.url.to_string(),
+        frame: RouteParams::new(),
+        error: None,
+        extensions: Extensions::default(),
+        env,
+        key_hash: key_hash(&key),
+        key,
+    });
+}
+
+/// Environment values of `route` below the ancestor `entries`.
+fn inherited_env(entries: &[MatchEntry], route: &Route) -> RouteEnv {
+    entries.last().map_or_else(
+        || route.config.env.clone(),
+        |parent| parent.env.inherit(&route.config.env),
+    )
+}
+
+/// Append `segment` to an accumulated `/`-rooted path.
+fn join_accumulated(base: &str, segment: &str) -> String {
+    if segment.is_empty() {
+        base.to_string()
+    } else if base == "/" {
+        format!("/{segment}")
+    } else {
+        format!("{base}/{segment}")
+    }
+}
+
 /// Recursive route matching with backtracking.
 ///
 /// Returns `true` if a complete match was found (all segments consumed or
-/// a valid leaf/index route was reached).
+/// a valid leaf/index route was reached). Routes whose children fail to
+/// match are offered to `search` as scoped not-found candidates. A strict
+/// leaf with segments left over halts the search instead of backtracking.
 #[allow(clippy::too_many_lines)]
 fn resolve_recursive(
     routes: &[Arc<Route>],
     remaining: &[&str],
     depth: usize,
     inherited_params: &RouteParams,
+    prefix: &Prefix<'_>,
     stack: &mut MatchStack,
+    search: &mut Search,
 ) -> bool {
     // Safety: prevent infinite recursion
     if depth >= MAX_DEPTH {
@@ -380,83 +922,88 @@ fn resolve_recursive(
 
         // === Try to match this route's segments ===
 
+        let route_pattern = route.config.pattern();
+
         // Case 1: Route has an empty path (index/layout route)
-        if route_path.is_empty() {
+        if route_pattern.is_empty() {
             // Empty-path route with children = layout route (matches anything)
             // Empty-path route without children = index route (matches only when no segments left)
+            let params = scoped_params(route, inherited_params);
+            let child_params = params.descend(&RouteParams::new());
+            let child_prefix = Prefix {
+                params: prefix.params_for(route),
+                ..*prefix
+            };
+
             if remaining.is_empty() {
                 // No segments left → this is an index/layout match
-                stack.entries.push(MatchEntry {
-                    route: Arc::clone(route),
-                    params: inherited_params.clone(),
-                    depth,
-                });
+                push_pathless(route, depth, params, prefix, stack);
 
                 // If layout with children, try to resolve index child
                 if !route.children.is_empty() {
-                    try_index_route(&route.children, depth + 1, inherited_params, stack);
+                    try_index_route(
+                        &route.children,
+                        depth + 1,
+                        &child_params,
+                        &child_prefix,
+                        stack,
+                    );
                 }
                 return true;
             }
 
             // Segments remain and route has children → layout route wrapping children
             if !route.children.is_empty() {
-                stack.entries.push(MatchEntry {
-                    route: Arc::clone(route),
-                    params: inherited_params.clone(),
-                    depth,
-                });
+                push_pathless(route, depth, params, prefix, stack);
 
                 if resolve_recursive(
                     &route.children,
                     remaining,
                     depth + 1,
-                    inherited_params,
+                    &child_params,
+                    &child_prefix,
                     stack,
+                    search,
                 ) {
                     return true;
                 }
 
                 // Children didn't match → backtrack
+                record_partial(
+                    route,
+                    remaining,
+                    &child_params,
+                    &child_prefix,
+                    stack,
+                    &mut search.partial,
+                );
                 stack.entries.pop();
+                if search.halted {
+                    return false;
+                }
             }
 
             continue;
         }
 
-        let route_segments: Vec<&str> = route_path.split('/').collect();
-
         // Case 2: Route has path segments → try to match against remaining path
-        if route_segments.len() > remaining.len() {
-            continue; // Not enough path segments
-        }
-
-        let mut params = inherited_params.clone();
-        let mut matched = true;
-
-        for (i, route_seg) in route_segments.iter().enumerate() {
-            if route_seg.starts_with(':') {
-                // Parameter segment → extract value
-                let param_name = route_seg.trim_start_matches(':');
-                // Strip constraint syntax: `:id<i32>` → `id`
-                let param_name = param_name
-                    .find('<')
-                    .map_or(param_name, |pos| &param_name[..pos]);
-                params.insert(param_name.to_string(), remaining[i].to_string());
-            } else if *route_seg == remaining[i] {
-                // Static segment → exact match
-            } else {
-                matched = false;
-                break;
-            }
-        }
-
-        if !matched {
+        let Some((consumed, captured)) = route_pattern.match_segments(remaining) else {
             continue;
+        };
+
+        let namespace = prefix.params_for(route);
+        let mut params = scoped_params(route, inherited_params);
+        let mut frame = RouteParams::new();
+        for (param_name, value) in captured.iter() {
+            let key = namespace.map_or_else(
+                || param_name.clone(),
+                |namespace| format!("{namespace}.{param_name}"),
+            );
+            params.insert(key.clone(), value.clone());
+            frame.insert(key, value.clone());
         }
 
         // Segments matched! Push entry.
-        let consumed = route_segments.len();
         let after = &remaining[consumed..];
 
         trace_log!(
@@ -466,35 +1013,88 @@ fn resolve_recursive(
             params.all()
         );
 
+        let pattern = join_accumulated(prefix.pattern, route_path);
+        let url = join_accumulated(prefix.url, &remaining[..consumed].join("/"));
+        let child_prefix = Prefix {
+            pattern: &pattern,
+            url: &url,
+            params: namespace,
+        };
+        let child_params = params.descend(&frame);
+
+        let env = inherited_env(&stack.entries, route);
+        let key = entry_key(&pattern, &params, depth);
         stack.entries.push(MatchEntry {
             route: Arc::clone(route),
-            params: params.clone(),
+            params,
             depth,
+            pattern: pattern.clone(),
+            url: url.clone(),
+            frame,
+            error: None,
+            extensions: Extensions::default(),
+            env,
+            key_hash: key_hash(&key),
+            key,
         });
 
         if after.is_empty() {
             // All segments consumed
             if !route.children.is_empty() {
                 // Has children → try to resolve index child
-                try_index_route(&route.children, depth + 1, &params, stack);
+                try_index_route(
+                    &route.children,
+                    depth + 1,
+                    &child_params,
+                    &child_prefix,
+                    stack,
+                );
             }
             return true;
         }
 
         // More segments remain → recurse into children
         if !route.children.is_empty()
-            && resolve_recursive(&route.children, after, depth + 1, &params, stack)
+            && resolve_recursive(
+                &route.children,
+                after,
+                depth + 1,
+                &child_params,
+                &child_prefix,
+                stack,
+                search,
+            )
         {
             return true;
         }
 
         // No children matched (or no children) → backtrack
+        record_partial(
+            route,
+            after,
+            &child_params,
+            &child_prefix,
+            stack,
+            &mut search.partial,
+        );
+        stack.entries.pop();
+        if route.strict_params && route.children.is_empty() {
+            debug_log!(
+                "Strict route '{}' at depth {} rejects {} extra segments",
+                route_path,
+                depth,
+                after.len()
+            );
+            search.halted = true;
+        }
+        if search.halted {
+            return false;
+        }
         trace_log!(
             "Backtracking from route '{}' at depth {}",
             route_path,
             depth
         );
-        stack.entries.pop();
     }
 
     false
@@ -508,23 +1108,38 @@ fn try_index_route(
     children: &[Arc<Route>],
     depth: usize,
     params: &RouteParams,
+    prefix: &Prefix<'_>,
     stack: &mut MatchStack,
 ) {
+    if depth >= MAX_DEPTH {
+        warn_log!(
+            "Maximum route nesting depth ({}) exceeded while resolving index routes",
+            MAX_DEPTH
+        );
+        return;
+    }
+
     // Priority 1: Empty path child
     for child in children {
-        let child_path = trim_slashes(&child.config.path);
-
-        if child_path.is_empty() {
+        if child.config.pattern().is_empty() {
             trace_log!("Index route (empty path) resolved at depth {}", depth);
-            stack.entries.push(MatchEntry {
-                route: Arc::clone(child),
-                params: params.clone(),
-                depth,
-            });
+            let child_params = scoped_params(child, params);
+            let grandchild_params = child_params.descend(&RouteParams::new());
+            push_pathless(child, depth, child_params, prefix, stack);
 
             // Recursively check if index route also has children with index
             if !child.children.is_empty() {
-                try_index_route(&child.children, depth + 1, params, stack);
+                let child_prefix = Prefix {
+                    params: prefix.params_for(child),
+                    ..*prefix
+                };
+                try_index_route(
+                    &child.children,
+                    depth + 1,
+                    &grandchild_params,
+                    &child_prefix,
+                    stack,
+                );
             }
             return;
         }
@@ -536,11 +1151,7 @@ fn try_index_route(
 
         if child_path == "index" {
             trace_log!("Index route ('index') resolved at depth {}", depth);
-            stack.entries.push(MatchEntry {
-                route: Arc::clone(child),
-                params: params.clone(),
-                depth,
-            });
+            push_pathless(child, depth, scoped_params(child, params), prefix, stack);
             return;
         }
     }
@@ -582,25 +1193,19 @@ pub fn resolve_named_outlet(
     }
 
     // For named outlets, resolve against remaining path segments
-    let normalized = normalize_path(current_path);
-    let path_str = trim_slashes(&normalized);
-    let all_segments: Vec<&str> = if path_str.is_empty() {
-        vec![]
-    } else {
-        path_str.split('/').collect()
-    };
+    let all_segments = path_segments(current_path);
 
     // Calculate how many segments the parent chain consumed
     let consumed = count_consumed_segments(match_stack, parent_depth);
     let remaining = &all_segments[consumed.min(all_segments.len())..];
 
     // Try to match a named child
-    let params = parent_entry.params.clone();
+    let params = parent_entry.params.descend(parent_entry.own_params());
 
     for child in named_children {
-        let child_path = trim_slashes(&child.config.path);
+        let pattern = child.config.pattern();
 
-        if child_path.is_empty() {
+        if pattern.is_empty() {
             // Index route for named outlet
             return Some((Arc::clone(child), params));
         }
@@ -609,24 +1214,9 @@ pub fn resolve_named_outlet(
             continue;
         }
 
-        // Simple single-segment match (named outlets are typically flat)
-        #[allow(clippy::redundant_clone)] // params is reused across loop iterations
-        if child_path == remaining[0] || child_path.starts_with(':') {
-            if child_path.starts_with(':') {
-                let name = child_path.trim_start_matches(':');
-                let mut child_params = params.clone();
-                child_params.insert(name.to_string(), remaining[0].to_string());
-                return Some((Arc::clone(child), child_params));
-            }
-            return Some((Arc::clone(child), params.clone()));
-        }
-    }
-
-    // Default: first child with empty path (if any)
-    for child in named_children {
-        let p = trim_slashes(&child.config.path);
-        if p.is_empty() {
-            return Some((Arc::clone(child), params));
+        // Prefix match (named outlets are typically flat)
+        if let Some((_, captured)) = pattern.match_segments(remaining) {
+            return Some((Arc::clone(child), RouteParams::merge(&params, &captured)));
         }
     }
 
@@ -634,16 +1224,124 @@ pub fn resolve_named_outlet(
 }
 
 /// Count how many path segments the match stack consumed up to a given depth.
+///
+/// Derived from the accumulated URL rather than the route paths: index
+/// routes (including ones named `index`) and pathless layouts consume
+/// nothing even though their config path may be non-empty.
 fn count_consumed_segments(stack: &MatchStack, up_to_depth: usize) -> usize {
-    let mut count = 0;
-    for entry in stack.entries().iter().take(up_to_depth + 1) {
-        let path = trim_slashes(&entry.route.config.path);
-        if !path.is_empty() {
-            count += path.split('/').count();
+    stack
+        .at_depth(up_to_depth)
+        .map_or(0, |entry| path_segments(entry.accumulated_url()).len())
+}
+
+// ============================================================================
+// Route Tree Checks
+// ============================================================================
+
+/// Check that resolution can walk all of `route`'s tree.
+///
+/// Walks children and named-outlet children once each, tracking routes by
+/// pointer, so a subtree shared by several parents is fine while a route
+/// reachable from itself is a cycle. Routes nested at [`MAX_DEPTH`] or
+/// deeper, counting `route` as depth 0, never match.
+///
+/// [`GlobalRouter::add_route`](crate::GlobalRouter::add_route) runs this
+/// check on every route it registers.
+///
+/// # Errors
+///
+/// Returns the first [`RouteTreeError`] found.
+pub fn check_route_tree(route: &Route) -> Result<(), RouteTreeError> {
+    TreeCheck::default().walk(route, "/", 0)
+}
+
+/// State of one [`check_route_tree`] walk.
+#[derive(Default)]
+struct TreeCheck {
+    /// Routes between the root and the route being checked.
+    ancestors: HashSet<*const Route>,
+    /// Deepest level each route was checked at.
+    checked: HashMap<*const Route, usize>,
+}
+
+impl TreeCheck {
+    fn walk(&mut self, route: &Route, parent: &str, depth: usize) -> Result<(), RouteTreeError> {
+        let key: *const Route = route;
+        let path = build_child_path(parent, &route.config.path);
+        if self.ancestors.contains(&key) {
+            return Err(RouteTreeError::Cycle {
+                route: path.into_owned(),
+            });
+        }
+        if depth >= MAX_DEPTH {
+            return Err(RouteTreeError::TooDeep {
+                route: path.into_owned(),
+            });
+        }
+        // A shared subtree checked at this depth or deeper is fine here too,
+        // so each route is walked at most MAX_DEPTH times
+        if self.checked.get(&key).is_some_and(|&seen| seen >= depth) {
+            return Ok(());
         }
+        self.checked.insert(key, depth);
+
+        self.ancestors.insert(key);
+        let children = route
+            .children
+            .iter()
+            .chain(route.named_children.values().flatten());
+        for child in children {
+            self.walk(child, &path, depth + 1)?;
+        }
+        self.ancestors.remove(&key);
+        Ok(())
     }
-    count
 }
 
-// Tests moved to tests/unit/resolve.rs to avoid compiler stack overflow
-// when compiling all tests in a single compilation unit.
+// Resolution tests live in tests/resolve_tests.rs to avoid compiler stack
+// overflow when compiling all tests in a single compilation unit; only the
+// test helpers are tested here.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::page;
+
+    fn dashboard() -> Vec<Arc<Route>> {
+        vec![Arc::new(
+            page("/dashboard")
+                .child(page(""))
+                .child(page("settings").child(page(":tab"))),
+        )]
+    }
+
+    #[test]
+    fn test_assert_paths_passes_on_matching_stack() {
+        let routes = dashboard();
+        resolve_match_stack(&routes, "/dashboard").assert_paths(&["/dashboard", ""]);
+        resolve_match_stack(&routes, "/dashboard/settings/users").assert_paths(&[
+            "/dashboard",
+            "settings",
+            ":tab",
+        ]);
+        resolve_match_stack(&routes, "/missing").assert_paths(&[]);
+    }
+
+    #[test]
+    fn test_assert_paths_message_shows_both_stacks() {
+        let routes = dashboard();
+        let stack = resolve_match_stack(&routes, "/dashboard/settings");
+        let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            stack.assert_paths(&["/dashboard", "profile", ":tab"]);
+        }))
+        .expect_err("mismatch panics");
+        let message = panic.downcast_ref::<String>().expect("formatted message");
+
+        assert!(message.starts_with("MatchStack paths differ (expected | actual):"));
+        assert!(message.contains("  [0] \"/dashboard\" | \"/dashboard\""));
+        assert!(message.contains("> [1] \"profile\" | \"settings\""));
+        assert!(message.contains("> [2] \":tab\" | -"));
+        #[cfg(debug_assertions)]
+        assert!(message.contains("[1] Route(\"settings\")"));
+    }
+}
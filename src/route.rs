@@ -49,18 +49,35 @@
 //! [`Route`] contains non-cloneable fields (guards, middleware, lifecycle hooks).
 //! Use [`RouteRef`] (`Arc<Route>`) to share routes cheaply across the route tree.
 
+use crate::boundary::{ErrorBoundaryFn, RetryHandle};
+use crate::error::RouteError;
 #[cfg(feature = "guard")]
 use crate::guards::RouteGuard;
 use crate::lifecycle::RouteLifecycle;
+use crate::loader::{LoaderFn, LoaderResult, ParamValidatorFn};
 #[cfg(feature = "middleware")]
 use crate::middleware::RouteMiddleware;
+use crate::outlet_policy::OwnerTag;
 use crate::params::RouteParams;
+use crate::pattern::{Pattern, PatternError};
+use crate::query::{QuerySchema, QueryViolation};
+use crate::refetch::RefetchFn;
+use crate::resolve::{entry_key, MatchEntry};
+use crate::route_env::RouteEnv;
+use crate::timers::IdleTarget;
 #[cfg(feature = "transition")]
 use crate::transition::TransitionConfig;
 use crate::{trace_log, warn_log, RouteMatch};
-use gpui::{AnyElement, AnyView, App, AppContext, BorrowAppContext, IntoElement, Render, Window};
+use gpui::{
+    AnyElement, AnyView, App, AppContext, BorrowAppContext, IntoElement, Render, SharedString,
+    Window,
+};
+use std::any::Any;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 // ============================================================================
 // NamedRouteRegistry
@@ -87,8 +104,8 @@ use std::sync::Arc;
 /// ```
 #[derive(Clone, Debug, Default)]
 pub struct NamedRouteRegistry {
-    /// Map of route names to path patterns
-    routes: HashMap<String, String>,
+    /// Map of route names to path patterns and their compiled form
+    routes: HashMap<String, (String, Pattern)>,
 }
 
 impl NamedRouteRegistry {
@@ -99,13 +116,37 @@ impl NamedRouteRegistry {
     }
 
     /// Register a named route
+    ///
+    /// A path that is not a valid [`Pattern`] is not registered; use
+    /// [`try_register`](Self::try_register) to learn why.
     pub fn register(&mut self, name: impl Into<String>, path: impl Into<String>) {
-        self.routes.insert(name.into(), path.into());
+        let name = name.into();
+        if let Err(e) = self.try_register(name.clone(), path) {
+            warn_log!("Named route '{}' not registered: {}", name, e);
+        }
+    }
+
+    /// Register a named route, reporting an invalid path
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`PatternError`] if `path` is not a valid [`Pattern`];
+    /// the name is then not registered.
+    pub fn try_register(
+        &mut self,
+        name: impl Into<String>,
+        path: impl Into<String>,
+    ) -> Result<(), PatternError> {
+        let path = path.into();
+        let pattern = Pattern::parse(&path)?;
+        self.routes.insert(name.into(), (path, pattern));
+        Ok(())
     }
 
     /// Get path pattern for a named route
+    #[must_use]
     pub fn get(&self, name: &str) -> Option<&str> {
-        self.routes.get(name).map(String::as_str)
+        self.routes.get(name).map(|(path, _)| path.as_str())
     }
 
     /// Check if a route name exists
@@ -130,10 +171,52 @@ impl NamedRouteRegistry {
     /// let url = registry.url_for("user.detail", &params).unwrap();
     /// assert_eq!(url, "/users/123");
     /// ```
+    ///
+    /// Returns `None` if the name is not registered or `params` lacks a
+    /// param the pattern declares; see [`url_for_checked`](Self::url_for_checked)
+    /// for which ones.
     #[must_use] 
     pub fn url_for(&self, name: &str, params: &RouteParams) -> Option<String> {
-        let pattern = self.get(name)?;
-        Some(substitute_params(pattern, params))
+        self.url_for_checked(name, params).ok()
+    }
+
+    /// Generate URL for a named route, reporting missing params
+    ///
+    /// # Errors
+    ///
+    /// Returns the names of the `:params` the pattern declares but `params`
+    /// does not contain, in pattern order. An unregistered name yields an
+    /// empty list.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gpui_navigator::{NamedRouteRegistry, RouteParams};
+    ///
+    /// let mut registry = NamedRouteRegistry::new();
+    /// registry.register("post.comment", "/posts/:postId/comments/:commentId");
+    ///
+    /// let mut params = RouteParams::new();
+    /// params.set("postId".to_string(), "42".to_string());
+    ///
+    /// let missing = registry.url_for_checked("post.comment", &params).unwrap_err();
+    /// assert_eq!(missing, ["commentId"]);
+    /// ```
+    pub fn url_for_checked(&self, name: &str, params: &RouteParams) -> Result<String, Vec<String>> {
+        let (_, pattern) = self.routes.get(name).ok_or_else(Vec::new)?;
+        pattern
+            .fill_encoded(params)
+            .map_err(|missing| missing.names)
+    }
+
+    /// Unregister a named route, returning whether it was registered
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.routes.remove(name).is_some()
+    }
+
+    /// Get the compiled pattern of a named route
+    pub(crate) fn pattern(&self, name: &str) -> Option<&Pattern> {
+        self.routes.get(name).map(|(_, pattern)| pattern)
     }
 
     /// Clear all registered routes
@@ -154,21 +237,6 @@ impl NamedRouteRegistry {
     }
 }
 
-/// Substitute route parameters in a path pattern
-///
-/// Replaces `:param` with actual values from `RouteParams`
-fn substitute_params(pattern: &str, params: &RouteParams) -> String {
-    let mut result = pattern.to_string();
-
-    // Replace :param with actual values
-    for (key, value) in params.iter() {
-        let placeholder = format!(":{key}");
-        result = result.replace(&placeholder, value);
-    }
-
-    result
-}
-
 // ============================================================================
 // Route Validation
 // ============================================================================
@@ -182,16 +250,17 @@ fn substitute_params(pattern: &str, params: &RouteParams) -> String {
 /// Returns `Err(String)` if the path violates any of the following rules:
 ///
 /// - Consecutive slashes (`//`) are not allowed
+/// - The path must compile into a [`Pattern`]
 /// - Parameter names (`:name`) must be non-empty and alphanumeric
 /// - Duplicate parameter names are not allowed
 ///
 /// Empty paths are valid (index routes), and trailing slashes are permitted.
 pub fn validate_route_path(path: &str) -> Result<(), String> {
-    // Empty path is allowed for index routes
-    if path.is_empty() {
-        return Ok(());
-    }
+    compile_route_path(path).map(drop)
+}
 
+/// Validate `path` and compile it into its [`Pattern`].
+fn compile_route_path(path: &str) -> Result<Pattern, String> {
     // Consecutive slashes check
     if path.contains("//") {
         warn_log!("Invalid route path '{}': consecutive slashes", path);
@@ -201,44 +270,142 @@ pub fn validate_route_path(path: &str) -> Result<(), String> {
     // Note: Trailing slashes are allowed for compatibility
     // They are normalized during route matching
 
-    // Extract and validate parameters
+    let pattern = Pattern::parse(path).map_err(|e| {
+        warn_log!("Invalid route path '{}': {}", path, e);
+        e.to_string()
+    })?;
+
+    // Validate parameter names
     let mut param_names = std::collections::HashSet::new();
-    for segment in path.split('/') {
-        if let Some(param) = segment.strip_prefix(':') {
-            // Check parameter name is not empty
-            if param.is_empty() {
-                warn_log!("Invalid route path '{}': empty parameter name", path);
-                return Err("Route parameter name cannot be empty".to_string());
-            }
+    for param_name in pattern.param_names() {
+        // Check parameter name is alphanumeric
+        if !param_name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            warn_log!(
+                "Invalid route path '{}': parameter '{}' has invalid characters",
+                path,
+                param_name
+            );
+            return Err(format!(
+                "Route parameter '{param_name}' must contain only alphanumeric characters and underscores"
+            ));
+        }
 
-            // Check for constraint syntax (:id{uuid})
-            let param_name = param.find('{').map_or(param, |pos| &param[..pos]);
+        // Check for duplicate parameters
+        if !param_names.insert(param_name) {
+            warn_log!(
+                "Invalid route path '{}': duplicate parameter '{}'",
+                path,
+                param_name
+            );
+            return Err(format!("Duplicate route parameter: '{param_name}'"));
+        }
+    }
 
-            // Check parameter name is alphanumeric
-            if !param_name.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                warn_log!(
-                    "Invalid route path '{}': parameter '{}' has invalid characters",
-                    path,
-                    param_name
-                );
-                return Err(format!(
-                    "Route parameter '{param_name}' must contain only alphanumeric characters and underscores"
-                ));
-            }
+    Ok(pattern)
+}
 
-            // Check for duplicate parameters
-            if !param_names.insert(param_name.to_string()) {
-                warn_log!(
-                    "Invalid route path '{}': duplicate parameter '{}'",
-                    path,
-                    param_name
-                );
-                return Err(format!("Duplicate route parameter: '{param_name}'"));
+// ============================================================================
+// RouteFlags
+// ============================================================================
+
+/// A boolean attribute of a route, kept in its [`RouteFlags`].
+///
+/// Cheaper than [`meta`](Route::meta) for yes/no attributes. Applications
+/// define their own with [`Custom`](Self::Custom).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RouteFlag {
+    /// Leave the route out of generated menus and navigation lists.
+    HiddenFromMenu,
+    /// The route needs a network connection.
+    RequiresConnection,
+    /// The route is experimental.
+    Experimental,
+    /// An application-defined flag, numbered below [`RouteFlag::MAX_CUSTOM`].
+    Custom(u8),
+}
+
+impl RouteFlag {
+    /// Number of [`Custom`](Self::Custom) flags available.
+    pub const MAX_CUSTOM: u8 = 32;
+
+    /// Bit of this flag: built-in flags take the low half, custom flags the
+    /// high half.
+    const fn bit(self) -> u64 {
+        match self {
+            Self::HiddenFromMenu => 1,
+            Self::RequiresConnection => 1 << 1,
+            Self::Experimental => 1 << 2,
+            Self::Custom(n) => {
+                assert!(n < Self::MAX_CUSTOM, "custom route flag out of range");
+                1 << (32 + n as u32)
             }
         }
     }
+}
+
+/// The [`RouteFlag`]s set on a route, as a bitset.
+///
+/// # Panics
+///
+/// Every method taking a flag panics on a [`RouteFlag::Custom`] numbered
+/// [`RouteFlag::MAX_CUSTOM`] or above.
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::{RouteFlag, RouteFlags};
+///
+/// let flags = RouteFlags::empty()
+///     .with(RouteFlag::Experimental)
+///     .with(RouteFlag::Custom(0));
+///
+/// assert!(flags.contains(RouteFlag::Custom(0)));
+/// assert!(!flags.contains(RouteFlag::HiddenFromMenu));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RouteFlags(u64);
+
+impl RouteFlags {
+    /// No flags set.
+    #[must_use]
+    pub const fn empty() -> Self {
+        Self(0)
+    }
 
-    Ok(())
+    /// These flags with `flag` set.
+    #[must_use]
+    pub const fn with(self, flag: RouteFlag) -> Self {
+        Self(self.0 | flag.bit())
+    }
+
+    /// Set `flag`.
+    pub fn insert(&mut self, flag: RouteFlag) {
+        self.0 |= flag.bit();
+    }
+
+    /// Clear `flag`.
+    pub fn remove(&mut self, flag: RouteFlag) {
+        self.0 &= !flag.bit();
+    }
+
+    /// Whether `flag` is set.
+    #[must_use]
+    pub const fn contains(self, flag: RouteFlag) -> bool {
+        self.0 & flag.bit() != 0
+    }
+
+    /// Whether no flag is set.
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl FromIterator<RouteFlag> for RouteFlags {
+    fn from_iter<I: IntoIterator<Item = RouteFlag>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::empty(), Self::with)
+    }
 }
 
 // ============================================================================
@@ -253,7 +420,8 @@ pub fn validate_route_path(path: &str) -> Result<(), String> {
 #[must_use]
 #[derive(Debug, Clone)]
 pub struct RouteConfig {
-    /// Route path pattern (e.g., "/users/:id")
+    /// Route path pattern (e.g., "/users/:id"), compiled into
+    /// [`pattern`](Self::pattern) at construction
     pub path: String,
     /// Route name (optional)
     pub name: Option<String>,
@@ -261,6 +429,12 @@ pub struct RouteConfig {
     pub children: Vec<Self>,
     /// Route metadata
     pub meta: HashMap<String, String>,
+    /// Boolean route attributes
+    pub flags: RouteFlags,
+    /// Values this route hands down to its descendants
+    pub env: RouteEnv,
+    /// `path`, compiled once
+    pattern: Pattern,
 }
 
 impl RouteConfig {
@@ -279,14 +453,9 @@ impl RouteConfig {
     /// Panics if the path is invalid. Use `try_new` for non-panicking validation.
     pub fn new(path: impl Into<String>) -> Self {
         let path_str = path.into();
-        if let Err(e) = validate_route_path(&path_str) {
-            panic!("Invalid route path '{path_str}': {e}");
-        }
-        Self {
-            path: path_str,
-            name: None,
-            children: Vec::new(),
-            meta: HashMap::new(),
+        match Self::try_new(path_str.as_str()) {
+            Ok(config) => config,
+            Err(e) => panic!("Invalid route path '{path_str}': {e}"),
         }
     }
 
@@ -299,15 +468,24 @@ impl RouteConfig {
     /// Returns `Err(String)` if the path fails [`validate_route_path`] validation.
     pub fn try_new(path: impl Into<String>) -> Result<Self, String> {
         let path_str = path.into();
-        validate_route_path(&path_str)?;
+        let pattern = compile_route_path(&path_str)?;
         Ok(Self {
             path: path_str,
             name: None,
             children: Vec::new(),
             meta: HashMap::new(),
+            flags: RouteFlags::empty(),
+            env: RouteEnv::default(),
+            pattern,
         })
     }
 
+    /// The compiled form of [`path`](Self::path)
+    #[must_use]
+    pub const fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
     /// Set route name
     pub fn name(mut self, name: impl Into<String>) -> Self {
         self.name = Some(name.into());
@@ -331,6 +509,12 @@ impl RouteConfig {
         self.meta.insert(key.into(), value.into());
         self
     }
+
+    /// Set a flag
+    pub fn flag(mut self, flag: RouteFlag) -> Self {
+        self.flags.insert(flag);
+        self
+    }
 }
 
 /// Type for route builder function
@@ -347,6 +531,30 @@ impl RouteConfig {
 pub type RouteBuilder =
     Arc<dyn Fn(&mut Window, &mut App, &RouteParams) -> AnyElement + Send + Sync>;
 
+/// Computes the component cache key a component route uses at the match
+/// stack level with the given [`MatchEntry::key`].
+pub(crate) type ComponentKeyFn = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Reads the external dependency a `component_with_dep` route is keyed by.
+pub(crate) type ComponentDepFn = Arc<dyn Fn(&App) -> u64 + Send + Sync>;
+
+/// Creates the entity of a component route without rendering it.
+pub(crate) type ComponentCreateFn = Arc<dyn Fn(&mut App, &RouteParams) -> AnyView + Send + Sync>;
+
+/// Side effect run when a route becomes the active leaf, with its params.
+pub(crate) type ActivateFn = Arc<dyn Fn(&App, &RouteParams) + Send + Sync>;
+
+/// Side effect run when a route stops being the active leaf.
+pub(crate) type DeactivateFn = Arc<dyn Fn(&App) + Send + Sync>;
+
+/// Decides from the matched params whether to send a navigation elsewhere.
+pub(crate) type RedirectIfFn = Arc<dyn Fn(&App, &RouteParams) -> Option<String> + Send + Sync>;
+
+/// Picks a route's transition for a specific navigation.
+#[cfg(feature = "transition")]
+pub(crate) type TransitionSelectFn =
+    Arc<dyn Fn(&crate::NavigationRequest) -> crate::transition::Transition + Send + Sync>;
+
 /// Shared route handle.
 ///
 /// A `Route` contains non-cloneable behavior (guards/middleware/lifecycle).
@@ -361,12 +569,17 @@ pub type RouteRef = Arc<Route>;
 fn get_or_create_cached_component<T: Render + 'static>(
     cx: &mut App,
     key: String,
+    route_pattern: &str,
+    pinned: &AtomicBool,
     create: impl FnOnce() -> T,
 ) -> AnyElement {
     // Check the global component cache first (survives across navigations)
-    if let Some(router) = cx.try_global::<crate::context::GlobalRouter>() {
-        if let Some(cached) = router.get_cached_component(&key) {
-            return cached.clone().into_any_element();
+    if cx.try_global::<crate::context::GlobalRouter>().is_some() {
+        let cached = cx.update_global::<crate::context::GlobalRouter, _>(
+            |router: &mut crate::context::GlobalRouter, _| router.use_cached_component(&key),
+        );
+        if let Some(cached) = cached {
+            return cached.into_any_element();
         }
     }
 
@@ -377,7 +590,8 @@ fn get_or_create_cached_component<T: Render + 'static>(
     if cx.try_global::<crate::context::GlobalRouter>().is_some() {
         cx.update_global::<crate::context::GlobalRouter, _>(
             |router: &mut crate::context::GlobalRouter, _| {
-                router.cache_component(key, view.clone());
+                let pin = pinned.load(Ordering::Relaxed);
+                router.cache_route_component(key, view.clone(), route_pattern, pin);
             },
         );
     }
@@ -385,6 +599,57 @@ fn get_or_create_cached_component<T: Render + 'static>(
     view.into_any_element()
 }
 
+thread_local! {
+    /// [`MatchEntry::key`] of the level an outlet is building.
+    static BUILD_KEY: RefCell<Option<SharedString>> = const { RefCell::new(None) };
+}
+
+/// Make component routes built until dropped cache their view under the
+/// match stack level `key`. Scopes nest.
+struct BuildKeyScope {
+    saved: Option<SharedString>,
+}
+
+impl BuildKeyScope {
+    fn enter(key: &SharedString) -> Self {
+        let saved = BUILD_KEY.with(|current| current.replace(Some(key.clone())));
+        Self { saved }
+    }
+}
+
+impl Drop for BuildKeyScope {
+    fn drop(&mut self) {
+        BUILD_KEY.with(|current| *current.borrow_mut() = self.saved.take());
+    }
+}
+
+/// The level key a component route at `path` is being built for: the one
+/// an outlet entered, or the route's own level at the root when it is
+/// built outside an outlet.
+fn build_key(path: &str, params: &RouteParams) -> SharedString {
+    BUILD_KEY
+        .with(|current| current.borrow().clone())
+        .unwrap_or_else(|| entry_key(path, params, 0))
+}
+
+/// A level key without its params, for components shared by all of them.
+fn without_params(key: &str) -> &str {
+    key.split_once('?').map_or(key, |(level, _)| level)
+}
+
+/// Extend the component cache key `key` with a dependency value.
+fn dependency_key(key: &str, dep: u64) -> String {
+    format!("{key}#dep={dep}")
+}
+
+/// Whether the component cache key `cached` is `key`, or `key` extended
+/// with any dependency value.
+pub(crate) fn is_key_or_dependency(cached: &str, key: &str) -> bool {
+    cached
+        .strip_prefix(key)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("#dep="))
+}
+
 /// A single route in the navigation tree.
 ///
 /// Combines a path pattern, an optional builder function, child routes, and
@@ -408,14 +673,74 @@ pub struct Route {
     /// Guards that control access to this route
     #[cfg(feature = "guard")]
     pub guards: Vec<Box<dyn RouteGuard>>,
+    /// Guards that control access to descendants only, not this route itself
+    #[cfg(feature = "guard")]
+    pub children_guards: Vec<Box<dyn RouteGuard>>,
     /// Middleware that runs before and after navigation to this route
     #[cfg(feature = "middleware")]
     pub middleware: Vec<Box<dyn RouteMiddleware>>,
     /// Lifecycle hooks for this route
     pub lifecycle: Option<Box<dyn RouteLifecycle>>,
+    /// Effect set by `on_activate()`, run alongside `RouteLifecycle::on_enter`
+    pub(crate) on_activate: Option<ActivateFn>,
+    /// Effect set by `on_deactivate()`, run alongside `RouteLifecycle::on_exit`
+    pub(crate) on_deactivate: Option<DeactivateFn>,
+    /// Dirty-state scope cleared when this route leaves the match stack
+    pub(crate) unsaved_scope: Option<String>,
+    /// Start this subtree from an empty set of inherited params
+    pub(crate) isolate_params: bool,
+    /// Extra segments after this leaf route are a not-found, not a backtrack
+    pub(crate) strict_params: bool,
+    /// Namespace for params captured in this subtree (`comment` → `comment.id`)
+    pub(crate) param_prefix: Option<String>,
+    /// Run when the app regains focus while this route is active
+    pub(crate) refetch_on_focus: Option<RefetchFn>,
+    /// Run every interval while this route is active
+    pub(crate) polls: Vec<(Duration, RefetchFn)>,
+    /// Load data before this route renders
+    pub(crate) loader: Option<LoaderFn>,
+    /// Param validators run just before the loader
+    pub(crate) param_validators: Vec<(String, ParamValidatorFn)>,
+    /// Query keys accepted on navigation to this route
+    pub(crate) query_schema: Option<QuerySchema>,
+    /// What the query schema does with a value that does not parse
+    pub(crate) query_violation: QueryViolation,
+    /// Renders errors of this route and its descendants
+    pub(crate) error_boundary: Option<ErrorBoundaryFn>,
+    /// Navigate away after this long without activity while this route is the leaf
+    pub(crate) idle_timeout: Option<(Duration, IdleTarget)>,
+    /// Push this path once after the delay while this route is active
+    pub(crate) auto_advance: Option<(Duration, String)>,
+    /// Send navigations that match this route elsewhere
+    pub(crate) redirect_if: Option<RedirectIfFn>,
+    /// Synthetic route rendered for paths this subtree cannot match
+    pub(crate) not_found: Option<RouteRef>,
+    /// Contributions to named slots of ancestor layouts
+    pub(crate) slots: HashMap<String, RouteBuilder>,
+    /// Help text set by `describe()`
+    pub(crate) description: Option<String>,
+    /// Tag this route was registered under with `add_route_tagged()`
+    pub(crate) owner: Option<OwnerTag>,
     /// Transition animation for this route
     #[cfg(feature = "transition")]
     pub transition: TransitionConfig,
+    /// Keep the previous content in the outlet until this route first builds
+    #[cfg(feature = "transition")]
+    pub hold_previous: bool,
+    /// Per-navigation transition selector set by `transition_for()`
+    #[cfg(feature = "transition")]
+    pub(crate) transition_for: Option<TransitionSelectFn>,
+    /// Cache key function for routes created by `component()` /
+    /// `component_with_params()` / `component_with_dep()`. `None` for plain builder routes.
+    pub(crate) component_key: Option<ComponentKeyFn>,
+    /// Entity factory of component routes, used to warm the component cache.
+    pub(crate) component_create: Option<ComponentCreateFn>,
+    /// Dependency of routes created by `component_with_dep()`, part of the
+    /// component cache key.
+    pub(crate) component_dep: Option<ComponentDepFn>,
+    /// Whether component entities of this route are pinned in the cache.
+    /// Shared with the builder, which is created before `pin_component()`.
+    pub(crate) component_pinned: Arc<AtomicBool>,
 }
 
 impl Route {
@@ -452,11 +777,41 @@ impl Route {
             named_children: HashMap::new(),
             #[cfg(feature = "guard")]
             guards: Vec::new(),
+            #[cfg(feature = "guard")]
+            children_guards: Vec::new(),
             #[cfg(feature = "middleware")]
             middleware: Vec::new(),
             lifecycle: None,
+            on_activate: None,
+            on_deactivate: None,
+            unsaved_scope: None,
+            isolate_params: false,
+            strict_params: false,
+            param_prefix: None,
+            refetch_on_focus: None,
+            polls: Vec::new(),
+            loader: None,
+            param_validators: Vec::new(),
+            query_schema: None,
+            query_violation: QueryViolation::Strip,
+            error_boundary: None,
+            idle_timeout: None,
+            auto_advance: None,
+            redirect_if: None,
+            owner: None,
+            not_found: None,
+            slots: HashMap::new(),
+            description: None,
             #[cfg(feature = "transition")]
             transition: TransitionConfig::default(),
+            #[cfg(feature = "transition")]
+            hold_previous: false,
+            #[cfg(feature = "transition")]
+            transition_for: None,
+            component_key: None,
+            component_create: None,
+            component_dep: None,
+            component_pinned: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -518,14 +873,24 @@ impl Route {
         F: Fn() -> T + Send + Sync + 'static + Clone,
     {
         let path_str = path.into();
-        let key_path = path_str.clone();
         let type_id = std::any::TypeId::of::<T>();
-
-        Self::new(path_str, move |_window, cx, _| {
-            let key = format!("route:{key_path}:{type_id:?}");
+        let component_key: ComponentKeyFn =
+            Arc::new(move |level| format!("route:{type_id:?}@{}", without_params(level)));
+        let key_of = Arc::clone(&component_key);
+        let warm_create = create.clone();
+        let pinned = Arc::new(AtomicBool::new(false));
+        let build_pinned = Arc::clone(&pinned);
+        let pattern = path_str.clone();
+
+        let mut route = Self::new(path_str, move |_window, cx, params| {
+            let key = key_of(&build_key(&pattern, params));
             let create_fn = create.clone();
-            get_or_create_cached_component(cx, key, create_fn)
-        })
+            get_or_create_cached_component(cx, key, &pattern, &build_pinned, create_fn)
+        });
+        route.component_key = Some(component_key);
+        route.component_pinned = pinned;
+        route.component_create = Some(Arc::new(move |cx, _| cx.new(|_| warm_create()).into()));
+        route
     }
 
     /// Create a stateful route with parameters
@@ -567,20 +932,92 @@ impl Route {
         F: Fn(&RouteParams) -> T + Send + Sync + 'static + Clone,
     {
         let path_str = path.into();
-        let key_path = path_str.clone();
         let type_id = std::any::TypeId::of::<T>();
-
-        Self::new(path_str, move |_window, cx, params| {
-            let params_key = params
-                .iter()
-                .map(|(k, v)| format!("{k}={v}"))
-                .collect::<Vec<_>>()
-                .join("&");
-            let key = format!("route:{key_path}:{type_id:?}?{params_key}");
+        let component_key: ComponentKeyFn =
+            Arc::new(move |level| format!("route:{type_id:?}@{level}"));
+        let key_of = Arc::clone(&component_key);
+        let warm_create = create.clone();
+        let pinned = Arc::new(AtomicBool::new(false));
+        let build_pinned = Arc::clone(&pinned);
+        let pattern = path_str.clone();
+
+        let mut route = Self::new(path_str, move |_window, cx, params| {
+            let key = key_of(&build_key(&pattern, params));
             let params_clone = params.clone();
             let create_fn = create.clone();
-            get_or_create_cached_component(cx, key, || create_fn(&params_clone))
-        })
+            get_or_create_cached_component(cx, key, &pattern, &build_pinned, || {
+                create_fn(&params_clone)
+            })
+        });
+        route.component_key = Some(component_key);
+        route.component_pinned = pinned;
+        route.component_create = Some(Arc::new(move |cx, params| {
+            cx.new(|_| warm_create(params)).into()
+        }));
+        route
+    }
+
+    /// Create a stateful route that rebuilds when an external dependency
+    /// changes
+    ///
+    /// Like `component_with_params()`, but the cache key also includes the
+    /// value `dep` reads from the app, e.g. a hash of the theme or locale.
+    /// While it stays the same the cached component is reused; once it
+    /// changes, the next render creates a fresh instance. Instances built
+    /// for earlier values stay cached until the cache evicts them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::{Route, RouteParams};
+    /// use gpui::*;
+    ///
+    /// struct Locale(u64);
+    /// impl Global for Locale {}
+    ///
+    /// struct InboxPage;
+    ///
+    /// impl Render for InboxPage {
+    ///     fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+    ///         div().child("Inbox")
+    ///     }
+    /// }
+    ///
+    /// Route::component_with_dep("/inbox", |cx| cx.global::<Locale>().0, |_| InboxPage);
+    /// ```
+    pub fn component_with_dep<T, D, F>(path: impl Into<String>, dep: D, create: F) -> Self
+    where
+        T: Render + 'static,
+        D: Fn(&App) -> u64 + Send + Sync + 'static,
+        F: Fn(&RouteParams) -> T + Send + Sync + 'static + Clone,
+    {
+        let path_str = path.into();
+        let type_id = std::any::TypeId::of::<T>();
+        let component_key: ComponentKeyFn =
+            Arc::new(move |level| format!("route:{type_id:?}@{level}"));
+        let key_of = Arc::clone(&component_key);
+        let dep: ComponentDepFn = Arc::new(dep);
+        let build_dep = Arc::clone(&dep);
+        let warm_create = create.clone();
+        let pinned = Arc::new(AtomicBool::new(false));
+        let build_pinned = Arc::clone(&pinned);
+        let pattern = path_str.clone();
+
+        let mut route = Self::new(path_str, move |_window, cx, params| {
+            let key = dependency_key(&key_of(&build_key(&pattern, params)), build_dep(cx));
+            let params_clone = params.clone();
+            let create_fn = create.clone();
+            get_or_create_cached_component(cx, key, &pattern, &build_pinned, || {
+                create_fn(&params_clone)
+            })
+        });
+        route.component_key = Some(component_key);
+        route.component_dep = Some(dep);
+        route.component_pinned = pinned;
+        route.component_create = Some(Arc::new(move |cx, params| {
+            cx.new(|_| warm_create(params)).into()
+        }));
+        route
     }
 
     /// Add child routes to this route
@@ -655,11 +1092,433 @@ impl Route {
             }
         }
 
-        self.children = children;
+        self.children = children;
+        self
+    }
+
+    /// Append a single child route
+    ///
+    /// Accepts a [`Route`] or an already shared [`RouteRef`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/dashboard", |_, _cx, _params| div().into_any_element())
+    ///     .child(Route::new("overview", |_, _cx, _params| div().into_any_element()))
+    ///     .child(Route::new("settings", |_, _cx, _params| div().into_any_element()));
+    /// ```
+    pub fn child(mut self, child: impl Into<RouteRef>) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
+    /// Append child routes from an iterator
+    ///
+    /// Unlike [`children`](Self::children), which replaces the list, this
+    /// keeps the children added so far.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/docs", |_, _cx, _params| div().into_any_element()).children_iter(
+    ///     ["intro", "install", "faq"]
+    ///         .map(|page| Route::new(page, |_, _cx, _params| div().into_any_element())),
+    /// );
+    /// ```
+    pub fn children_iter(
+        mut self,
+        children: impl IntoIterator<Item = impl Into<RouteRef>>,
+    ) -> Self {
+        self.children.extend(children.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set route name
+    ///
+    /// Named routes can be referenced by name instead of path.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.config.name = Some(name.into());
+        self
+    }
+
+    /// Add metadata to the route
+    ///
+    /// Metadata can be used for guards, analytics, titles, etc.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/admin", |_, _cx, _params| div().into_any_element())
+    ///     .meta("requiresAuth", "true")
+    ///     .meta("requiredRole", "admin")
+    ///     .meta("title", "Admin Panel");
+    /// ```
+    pub fn meta(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.meta.insert(key.into(), value.into());
+        self
+    }
+
+    /// Describe the route for generated help screens
+    ///
+    /// Reported as [`HelpEntry::description`](crate::help::HelpEntry::description)
+    /// by [`GlobalRouter::help_entries`](crate::GlobalRouter::help_entries).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/settings", |_, _cx, _params| div().into_any_element())
+    ///     .meta("title", "Settings")
+    ///     .describe("Preferences for the whole workspace");
+    /// ```
+    pub fn describe(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set a boolean flag on the route
+    ///
+    /// Read it back with [`has_flag`](Self::has_flag) or
+    /// [`MatchEntry::has_flag`](crate::MatchEntry::has_flag).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::{Route, RouteFlag};
+    /// use gpui::*;
+    ///
+    /// Route::new("/labs", |_, _cx, _params| div().into_any_element())
+    ///     .flag(RouteFlag::Experimental)
+    ///     .flag(RouteFlag::HiddenFromMenu);
+    /// ```
+    pub fn flag(mut self, flag: RouteFlag) -> Self {
+        self.config.flags.insert(flag);
+        self
+    }
+
+    /// Hand `value` down to this route and its descendants
+    ///
+    /// At most one value per type: a descendant setting its own value of
+    /// the same type overrides this one for its subtree. Read it with
+    /// [`use_route_env`](crate::use_route_env) or
+    /// [`MatchEntry::env`](crate::MatchEntry::env). See
+    /// [`route_env`](crate::route_env).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::{use_route_env, Route};
+    /// use gpui::*;
+    ///
+    /// #[derive(Clone)]
+    /// struct ReadOnly(bool);
+    ///
+    /// Route::view("/viewer", || div().into_any_element())
+    ///     .env(ReadOnly(true))
+    ///     .child(Route::new("docs/:id", |_, cx, _params| {
+    ///         let read_only = use_route_env::<ReadOnly>(cx).is_some_and(|r| r.0);
+    ///         div().child(if read_only { "Viewing" } else { "Editing" }).into_any_element()
+    ///     }));
+    /// ```
+    pub fn env<T: Clone + Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.config.env.insert(value);
+        self
+    }
+
+    /// Check whether the route has `flag` set
+    #[must_use]
+    pub const fn has_flag(&self, flag: RouteFlag) -> bool {
+        self.config.flags.contains(flag)
+    }
+
+    /// Stop this route and its descendants from inheriting ancestor params
+    ///
+    /// With `true`, the params this route's builder receives hold only what
+    /// the subtree itself captured. Ancestor values stay reachable through
+    /// [`RouteParams::ancestor`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("comments/:id", |_, _cx, params| {
+    ///     let comment = params.get("id");          // the comment id
+    ///     let post = params.ancestor("id");        // the post id
+    ///     div().into_any_element()
+    /// })
+    /// .isolate_params(true);
+    /// ```
+    pub const fn isolate_params(mut self, isolate: bool) -> Self {
+        self.isolate_params = isolate;
+        self
+    }
+
+    /// Treat extra segments after this route as not found
+    ///
+    /// A route without children that matches all of its own segments while
+    /// segments remain does not match, and resolution backtracks: a later,
+    /// less specific route such as `/users/*` may then take the path. With
+    /// `true`, resolution stops there instead, so `/users/42/extra` ends in
+    /// the not-found page (or the nearest ancestor's
+    /// [`not_found`](Self::not_found) handler) rather than a surprising
+    /// match. Routes with children are unaffected.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/users/:id", |_, _cx, _params| div().into_any_element())
+    ///     .strict_params(true);
+    /// ```
+    pub const fn strict_params(mut self, strict: bool) -> Self {
+        self.strict_params = strict;
+        self
+    }
+
+    /// Store the params captured by this route and its descendants under `prefix`
+    ///
+    /// `:id` captured inside a subtree prefixed `comment` is stored as
+    /// `comment.id`, so it no longer overrides an `id` captured above. The
+    /// nearest prefix wins when subtrees nest. Named outlet children are
+    /// not affected.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("comments/:id", |_, _cx, params| {
+    ///     let comment = params.get("comment.id");
+    ///     let post = params.get("id");
+    ///     div().into_any_element()
+    /// })
+    /// .param_prefix("comment");
+    /// ```
+    pub fn param_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.param_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Refresh data when the app regains focus while this route is active
+    ///
+    /// `refetch` receives the route's accumulated params. Focus is reported
+    /// by the app through [`RouterFocus`](crate::RouterFocus). Setting it
+    /// again replaces the earlier callback.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/inbox", |_, _cx, _params| div().into_any_element())
+    ///     .refetch_on_focus(|_cx, _params| {
+    ///         // reload the message list
+    ///     });
+    /// ```
+    pub fn refetch_on_focus<F>(mut self, refetch: F) -> Self
+    where
+        F: Fn(&App, &RouteParams) + Send + Sync + 'static,
+    {
+        self.refetch_on_focus = Some(Arc::new(refetch));
+        self
+    }
+
+    /// Run `refetch` every `interval` while this route is active
+    ///
+    /// Polling starts when the route enters the match stack and stops once
+    /// it leaves; a param change that keeps the route matched keeps the
+    /// poll running with the new params. Several polls may be attached.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    /// use std::time::Duration;
+    ///
+    /// Route::new("/builds/:id", |_, _cx, _params| div().into_any_element())
+    ///     .poll(Duration::from_secs(5), |_cx, params| {
+    ///         let _build = params.get("id");
+    ///     });
+    /// ```
+    pub fn poll<F>(mut self, interval: Duration, refetch: F) -> Self
+    where
+        F: Fn(&App, &RouteParams) + Send + Sync + 'static,
+    {
+        self.polls.push((interval, Arc::new(refetch)));
+        self
+    }
+
+    /// Navigate to `target` after `timeout` without user activity
+    ///
+    /// The timeout is armed while this route is the leaf of the match stack
+    /// and restarts on every
+    /// [`Navigator::notify_activity`](crate::Navigator::notify_activity).
+    /// If the navigation it starts does not complete, e.g. because a guard
+    /// blocks the target, it is disarmed until the route becomes the leaf
+    /// again. See [`timers`](crate::timers).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::{IdleTarget, Route};
+    /// use gpui::*;
+    /// use std::time::Duration;
+    ///
+    /// Route::new("/catalog", |_, _cx, _params| div().into_any_element())
+    ///     .idle_timeout(Duration::from_secs(90), IdleTarget::Path("/".into()));
+    /// ```
+    pub fn idle_timeout(mut self, timeout: Duration, target: IdleTarget) -> Self {
+        self.idle_timeout = Some((timeout, target));
+        self
+    }
+
+    /// Push `path` once `after` has passed, unless the route left first
+    ///
+    /// The countdown starts when the route enters the match stack. Setting
+    /// it again replaces the earlier one.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    /// use std::time::Duration;
+    ///
+    /// Route::new("/wizard/intro", |_, _cx, _params| div().into_any_element())
+    ///     .auto_advance(Duration::from_secs(10), "/wizard/details");
+    /// ```
+    pub fn auto_advance(mut self, after: Duration, path: impl Into<String>) -> Self {
+        self.auto_advance = Some((after, path.into()));
+        self
+    }
+
+    /// Redirect navigations matching this route to the path `redirect` returns
+    ///
+    /// Runs before guards, with the params the target path matched, for
+    /// every route in its match stack from the root down; the first `Some`
+    /// wins. The matched path never enters history: the redirect target is
+    /// pushed (or replaces, for a replace) in its place. `None` lets the
+    /// navigation continue. Redirect chains count towards the same loop
+    /// limit as guard redirects. Setting it again replaces the earlier one.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/projects/:id", |_, _cx, _params| div().into_any_element())
+    ///     .redirect_if(|_cx, params| {
+    ///         let id = params.get("id")?;
+    ///         id.starts_with("legacy-").then(|| format!("/archive/{id}"))
+    ///     });
+    /// ```
+    pub fn redirect_if<F>(mut self, redirect: F) -> Self
+    where
+        F: Fn(&App, &RouteParams) -> Option<String> + Send + Sync + 'static,
+    {
+        self.redirect_if = Some(Arc::new(redirect));
+        self
+    }
+
+    /// Load data for this route before it renders
+    ///
+    /// `loader` runs during navigation, after the new match stack is
+    /// resolved and before `on_enter` hooks, with the route's accumulated
+    /// params. The component reads the value synchronously with
+    /// [`Navigator::loader_data`](crate::Navigator::loader_data). If the
+    /// loader fails, the route renders the error page instead of its
+    /// builder. Setting it again replaces the earlier loader.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::{Navigator, Route};
+    /// use gpui::*;
+    ///
+    /// struct User {
+    ///     name: String,
+    /// }
+    ///
+    /// Route::new("/users/:id", |_, cx, _params| {
+    ///     let user = Navigator::loader_data::<User>(cx).unwrap();
+    ///     div().child(user.name.clone()).into_any_element()
+    /// })
+    /// .loader(|_cx, params| {
+    ///     Ok(User {
+    ///         name: format!("User {}", params.get("id").unwrap()),
+    ///     })
+    /// });
+    /// ```
+    pub fn loader<T, F>(mut self, loader: F) -> Self
+    where
+        T: Any + Send + Sync,
+        F: Fn(&App, &RouteParams) -> LoaderResult<T> + Send + Sync + 'static,
+    {
+        self.loader = Some(Arc::new(move |cx, params| {
+            loader(cx, params).map(|value| Arc::new(value) as Arc<dyn Any + Send + Sync>)
+        }));
+        self
+    }
+
+    /// Reject values of param `name` that `validator` refuses
+    ///
+    /// Validators run during navigation, just before the route's
+    /// [`loader`](Self::loader). A rejected value stops the loader stage
+    /// and records [`RouteError::InvalidParam`](crate::RouteError::InvalidParam)
+    /// on the route's match stack entry, rendered like a failed loader.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/orders/:id", |_, _cx, _params| div().into_any_element())
+    ///     .validate_param("id", |value| {
+    ///         value.parse::<u64>().map(drop).map_err(|_| "not a number".to_string())
+    ///     });
+    /// ```
+    pub fn validate_param<F>(mut self, name: impl Into<String>, validator: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.param_validators
+            .push((name.into(), Arc::new(validator)));
         self
     }
 
-    /// Add a single child route
+    /// Accept only the given query keys on navigation to this route
+    ///
+    /// `fields` pairs each key with a [`QueryType`](crate::QueryType) name
+    /// (`"string"`, `"bool"`, `"u32"`, `"u64"`, `"i32"`, `"i64"`, `"f64"`).
+    /// When a push or replace targets this route (or, for a nested route,
+    /// its deepest matched route with a schema), unknown keys are logged
+    /// and stripped from the path before guards run. Values that do not
+    /// parse are stripped too, unless [`query_violation`](Self::query_violation)
+    /// says to reject them.
+    ///
+    /// # Panics
+    ///
+    /// Panics on an unknown type name or a key declared twice.
     ///
     /// # Example
     ///
@@ -667,40 +1526,102 @@ impl Route {
     /// use gpui_navigator::Route;
     /// use gpui::*;
     ///
-    /// Route::new("/dashboard", |_, _cx, _params| div().into_any_element())
-    ///     .child(Route::new("overview", |_, _cx, _params| div().into_any_element()).into())
-    ///     .child(Route::new("settings", |_, _cx, _params| div().into_any_element()).into());
+    /// // "/orders?page=2&debug=1" navigates to "/orders?page=2"
+    /// Route::new("/orders", |_, _cx, _params| div().into_any_element())
+    ///     .query_schema(&[("page", "u32"), ("sort", "string")]);
     /// ```
-    pub fn child(mut self, child: RouteRef) -> Self {
-        self.children.push(child);
+    pub fn query_schema(mut self, fields: &[(&str, &str)]) -> Self {
+        self.query_schema = Some(QuerySchema::new(fields));
         self
     }
 
-    /// Set route name
+    /// Choose what [`query_schema`](Self::query_schema) does with a value
+    /// that does not parse
     ///
-    /// Named routes can be referenced by name instead of path.
-    pub fn name(mut self, name: impl Into<String>) -> Self {
-        self.config.name = Some(name.into());
+    /// [`QueryViolation::Strip`] (the default) drops the pair and carries
+    /// on; [`QueryViolation::Reject`] fails the navigation with
+    /// [`NavigationError::InvalidParams`](crate::NavigationError::InvalidParams).
+    pub const fn query_violation(mut self, violation: QueryViolation) -> Self {
+        self.query_violation = violation;
         self
     }
 
-    /// Add metadata to the route
+    /// Render `boundary` in place of this route when it or a descendant fails
     ///
-    /// Metadata can be used for guards, analytics, titles, etc.
+    /// A failed loader, rejected param, missing builder or panicking builder
+    /// at this level or below is handed to the nearest boundary at or above
+    /// it, which renders instead of that route's builder. The
+    /// [`RetryHandle`](crate::RetryHandle) runs the failed stage again. See
+    /// [`boundary`](crate::boundary).
+    pub fn error_boundary<F>(mut self, boundary: F) -> Self
+    where
+        F: Fn(&RouteError, &RetryHandle, &mut Window, &mut App) -> AnyElement
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.error_boundary = Some(Arc::new(boundary));
+        self
+    }
+
+    /// Render `builder` for paths inside this route that no child matches
+    ///
+    /// When the route's own segments match but nothing below it matches the
+    /// rest of the path, the match stack keeps this route (and its
+    /// ancestors) and ends in a synthetic entry rendered by `builder` in this
+    /// route's outlet, so the surrounding layout stays on screen. The
+    /// unmatched remainder is in the
+    /// [`UNMATCHED_PARAM`](crate::resolve::UNMATCHED_PARAM) param. A route
+    /// that matches the whole path elsewhere still wins, and the deepest
+    /// applicable handler is used. Without one, the global 404 page renders.
     ///
     /// # Example
     ///
     /// ```no_run
+    /// use gpui_navigator::resolve::UNMATCHED_PARAM;
     /// use gpui_navigator::Route;
     /// use gpui::*;
     ///
-    /// Route::new("/admin", |_, _cx, _params| div().into_any_element())
-    ///     .meta("requiresAuth", "true")
-    ///     .meta("requiredRole", "admin")
-    ///     .meta("title", "Admin Panel");
+    /// Route::new("/settings", |_, _cx, _params| div().into_any_element())
+    ///     .child(Route::new("profile", |_, _cx, _params| div().into_any_element()))
+    ///     .not_found(|_, _cx, params| {
+    ///         let section = params.get(UNMATCHED_PARAM).cloned().unwrap_or_default();
+    ///         div().child(format!("Unknown section: {section}")).into_any_element()
+    ///     });
     /// ```
-    pub fn meta(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
-        self.config.meta.insert(key.into(), value.into());
+    pub fn not_found<F>(mut self, builder: F) -> Self
+    where
+        F: Fn(&mut Window, &mut App, &RouteParams) -> AnyElement + Send + Sync + 'static,
+    {
+        self.not_found = Some(Arc::new(Self::new("*", builder)));
+        self
+    }
+
+    /// Contribute `builder` to the slot `name` of an ancestor layout
+    ///
+    /// An ancestor's builder renders the slot with
+    /// [`render_slot`](crate::slots::render_slot) or
+    /// [`render_slots`](crate::slots::render_slots); `builder` runs with this
+    /// route's params while the route is in the match stack. Setting the same
+    /// slot again replaces the earlier contribution.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("editor/:file", |_, _cx, _params| div().into_any_element())
+    ///     .slot("toolbar", |_, _cx, params| {
+    ///         let file = params.get("file").cloned().unwrap_or_default();
+    ///         div().child(format!("Save {file}")).into_any_element()
+    ///     });
+    /// ```
+    pub fn slot<F>(mut self, name: impl Into<String>, builder: F) -> Self
+    where
+        F: Fn(&mut Window, &mut App, &RouteParams) -> AnyElement + Send + Sync + 'static,
+    {
+        self.slots.insert(name.into(), Arc::new(builder));
         self
     }
 
@@ -756,6 +1677,58 @@ impl Route {
         self
     }
 
+    /// Add a guard to this route with an explicit priority.
+    ///
+    /// Overrides the guard's own [`priority`](crate::guards::RouteGuard::priority)
+    /// when ordering it against other guards, without a wrapper type.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::{Route, guard_fn, NavigationAction};
+    /// use gpui::*;
+    ///
+    /// Route::new("/beta", |_, _cx, _params| div().into_any_element())
+    ///     .guard_with_priority(guard_fn(|_, _| NavigationAction::redirect("/waitlist")), 200);
+    /// ```
+    #[cfg(feature = "guard")]
+    pub fn guard_with_priority<G: crate::guards::RouteGuard>(
+        mut self,
+        guard: G,
+        priority: i32,
+    ) -> Self {
+        self.guards
+            .push(Box::new(crate::guards::PrioritizedGuard::new(
+                guard, priority,
+            )));
+        self
+    }
+
+    /// Add a guard that protects this route's descendants but not the route itself.
+    ///
+    /// Useful for a layout that is publicly viewable while its children
+    /// require auth. Unlike [`guard`](Self::guard), it is only collected when
+    /// the target lies below this route, never when it matches exactly.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::{Route, AuthGuard};
+    /// use gpui::*;
+    ///
+    /// fn is_authenticated(_cx: &App) -> bool { false }
+    ///
+    /// // `/dashboard` is open, `/dashboard/settings` requires login
+    /// Route::new("/dashboard", |_, _cx, _params| div().into_any_element())
+    ///     .children_guard(AuthGuard::new(is_authenticated, "/login"))
+    ///     .child(Route::new("settings", |_, _cx, _params| div().into_any_element()));
+    /// ```
+    #[cfg(feature = "guard")]
+    pub fn children_guard<G: crate::guards::RouteGuard>(mut self, guard: G) -> Self {
+        self.children_guards.push(Box::new(guard));
+        self
+    }
+
     /// Add multiple guards at once (pre-boxed).
     #[cfg(feature = "guard")]
     pub fn guards(mut self, guards: Vec<Box<dyn crate::guards::RouteGuard>>) -> Self {
@@ -812,6 +1785,76 @@ impl Route {
         self
     }
 
+    /// Run `activate` each time this route becomes the navigated-to leaf
+    ///
+    /// A side-effect-only alternative to [`RouteLifecycle::on_enter`]: it
+    /// runs at the same point, right before any lifecycle's `on_enter`, and
+    /// receives the route's accumulated params. Setting it again replaces
+    /// the earlier callback.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/users/:id", |_, _cx, _params| div().into_any_element())
+    ///     .on_activate(|_cx, _params| {
+    ///         // mark the user's notifications as seen
+    ///     })
+    ///     .on_deactivate(|_cx| {
+    ///         // stop the presence indicator
+    ///     });
+    /// ```
+    pub fn on_activate<F>(mut self, activate: F) -> Self
+    where
+        F: Fn(&App, &RouteParams) + Send + Sync + 'static,
+    {
+        self.on_activate = Some(Arc::new(activate));
+        self
+    }
+
+    /// Run `deactivate` each time a navigation leaves this route as the leaf
+    ///
+    /// Runs at the same point as [`RouteLifecycle::on_exit`], right after it
+    /// lets the navigation go on. Setting it again replaces the earlier
+    /// callback.
+    pub fn on_deactivate<F>(mut self, deactivate: F) -> Self
+    where
+        F: Fn(&App) + Send + Sync + 'static,
+    {
+        self.on_deactivate = Some(Arc::new(deactivate));
+        self
+    }
+
+    /// Block leaving this route while `scope` has unsaved changes
+    ///
+    /// Attaches an [`UnsavedChangesLifecycle`](crate::UnsavedChangesLifecycle)
+    /// (replacing any lifecycle set earlier), asked whenever a navigation
+    /// leaves the route, whether as the leaf or as a layout. When the route
+    /// leaves the match stack without being asked, as on
+    /// [`GlobalRouter::restore`](crate::GlobalRouter::restore) or when a
+    /// route table change [invalidates](crate::invalidation) it, `scope` is
+    /// cleared in the [`DirtyStateRegistry`](crate::DirtyStateRegistry), so
+    /// the form left behind doesn't block later navigations.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gpui_navigator::Route;
+    /// use gpui::*;
+    ///
+    /// Route::new("/profile/edit", |_, _cx, _params| div().into_any_element())
+    ///     .protect_unsaved("profile-form");
+    /// ```
+    pub fn protect_unsaved(self, scope: impl Into<String>) -> Self {
+        let scope = scope.into();
+        let lifecycle = crate::lifecycle::UnsavedChangesLifecycle::new(scope.clone());
+        let mut route = self.lifecycle(lifecycle);
+        route.unsaved_scope = Some(scope);
+        route
+    }
+
     /// Set the transition animation for this route
     ///
     /// # Example
@@ -828,6 +1871,63 @@ impl Route {
         self
     }
 
+    /// Choose this route's transition per navigation
+    ///
+    /// The closure receives the navigation request (`from` / `to`) and
+    /// overrides the static [`transition`](Self::transition) default, so a
+    /// route can slide one way when going deeper and the other way when
+    /// popping. A one-off override (`push_with_transition`) still wins.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use gpui_navigator::{Route, Transition};
+    /// use gpui::*;
+    ///
+    /// Route::new("/inbox", |_, _cx, _params| div().into_any_element())
+    ///     .transition_for(|request| {
+    ///         let deeper = request.from.as_deref().is_some_and(|from| request.to.starts_with(from));
+    ///         if deeper { Transition::slide_left(250) } else { Transition::slide_right(250) }
+    ///     });
+    /// ```
+    #[cfg(feature = "transition")]
+    pub fn transition_for<F>(mut self, select: F) -> Self
+    where
+        F: Fn(&crate::NavigationRequest) -> crate::transition::Transition + Send + Sync + 'static,
+    {
+        self.transition_for = Some(Arc::new(select));
+        self
+    }
+
+    /// Resolve the transition to use for `request`
+    ///
+    /// Priority: `override_next` > [`transition_for`](Self::transition_for)
+    /// selector > static default.
+    #[cfg(feature = "transition")]
+    #[must_use]
+    pub fn transition_for_request(
+        &self,
+        request: &crate::NavigationRequest,
+    ) -> crate::transition::Transition {
+        if let Some(ref transition) = self.transition.override_next {
+            return transition.clone();
+        }
+        self.transition_for
+            .as_ref()
+            .map_or_else(|| self.transition.default.clone(), |select| select(request))
+    }
+
+    /// Keep rendering the previous route's content until this route builds
+    ///
+    /// Avoids flashing an empty outlet between navigation commit and the new
+    /// content's first frame. See
+    /// [`GlobalRouter::set_stale_while_navigate`](crate::GlobalRouter::set_stale_while_navigate)
+    /// for the global switch and timeout.
+    #[cfg(feature = "transition")]
+    pub const fn hold_previous(mut self, hold: bool) -> Self {
+        self.hold_previous = hold;
+        self
+    }
+
     /// Get child routes for a named outlet
     ///
     /// Returns None if the outlet doesn't exist
@@ -849,10 +1949,96 @@ impl Route {
     /// Match a path against this route
     #[must_use] 
     pub fn matches(&self, path: &str) -> Option<RouteMatch> {
-        match_path(&self.config.path, path)
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let (consumed, params) = self.config.pattern().match_segments(&segments)?;
+        if consumed != segments.len() {
+            return None;
+        }
+
+        let mut route_match = RouteMatch::new(path.to_string())
+            .with_pattern(format!("/{}", self.config.path.trim_matches('/')));
+        route_match.params = params.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        Some(route_match)
+    }
+
+    /// Pin the component entities of this route in the cache.
+    ///
+    /// Pinned entities are never evicted to make room for others and do not
+    /// count toward [`CachePolicy::component_capacity`](crate::CachePolicy::component_capacity);
+    /// once [`CachePolicy::pinned_component_capacity`](crate::CachePolicy::pinned_component_capacity)
+    /// entities are pinned, further ones are cached unpinned. Use it for
+    /// expensive views like a main editor. Has no effect on routes not
+    /// created with [`component`](Self::component) or
+    /// [`component_with_params`](Self::component_with_params).
+    ///
+    /// See [`GlobalRouter::pin_component`](crate::GlobalRouter::pin_component)
+    /// for pinning at runtime.
+    pub fn pin_component(self, pin: bool) -> Self {
+        self.component_pinned.store(pin, Ordering::Relaxed);
+        self
+    }
+
+    /// Whether [`pin_component`](Self::pin_component) was set.
+    pub(crate) fn pins_component(&self) -> bool {
+        self.component_pinned.load(Ordering::Relaxed)
+    }
+
+    /// Return the component cache key this route uses as the match stack
+    /// level `entry`.
+    ///
+    /// Only routes created with [`component`](Self::component) or
+    /// [`component_with_params`](Self::component_with_params) cache their
+    /// view; for any other route this returns `None`. The key is derived
+    /// from [`MatchEntry::key`], without the params for
+    /// [`component`](Self::component) routes, whose one instance serves
+    /// every param value.
+    ///
+    /// For [`component_with_dep`](Self::component_with_dep) routes this is
+    /// the key without the dependency value; see
+    /// [`component_cache_key_in`](Self::component_cache_key_in).
+    #[must_use]
+    pub fn component_cache_key(&self, entry: &MatchEntry) -> Option<String> {
+        self.component_key.as_ref().map(|key| key(&entry.key()))
+    }
+
+    /// Return the component cache key this route uses as the match stack
+    /// level `entry` given the current state of `cx`, including the
+    /// dependency value of [`component_with_dep`](Self::component_with_dep)
+    /// routes.
+    #[must_use]
+    pub fn component_cache_key_in(&self, cx: &App, entry: &MatchEntry) -> Option<String> {
+        let key = self.component_cache_key(entry)?;
+        Some(match &self.component_dep {
+            Some(dep) => dependency_key(&key, dep(cx)),
+            None => key,
+        })
+    }
+
+    /// Create this component route's entity for `params` without rendering
+    /// or caching it; `None` for plain builder routes.
+    pub(crate) fn create_component(&self, cx: &mut App, params: &RouteParams) -> Option<AnyView> {
+        self.component_create
+            .as_ref()
+            .map(|create| create(cx, params))
+    }
+
+    /// Build the view for this route as the match stack level `key` (see
+    /// [`MatchEntry::key`]), which component routes cache their view under.
+    pub(crate) fn build_as(
+        &self,
+        window: &mut Window,
+        cx: &mut App,
+        key: &SharedString,
+        params: &RouteParams,
+    ) -> Option<AnyElement> {
+        let _key = BuildKeyScope::enter(key);
+        self.build(window, cx, params)
     }
 
     /// Build the view for this route
+    ///
+    /// Outside an outlet, a component route caches its view as if the
+    /// route were matched at the root.
     pub fn build(
         &self,
         window: &mut Window,
@@ -889,6 +2075,7 @@ impl std::fmt::Debug for Route {
         f.debug_struct("Route")
             .field("config", &self.config)
             .field("builder", &self.builder.is_some())
+            .field("component", &self.component_key.is_some())
             .field("children", &self.children.len())
             .field(
                 "named_children",
@@ -898,46 +2085,6 @@ impl std::fmt::Debug for Route {
     }
 }
 
-/// Match a path pattern against an actual path
-///
-/// Supports:
-/// - Static paths: `/users`
-/// - Dynamic segments: `/users/:id`
-/// - Wildcard: `/files/*`
-fn match_path(pattern: &str, path: &str) -> Option<RouteMatch> {
-    let pattern_iter = pattern.split('/').filter(|s| !s.is_empty());
-    let mut path_iter = path.split('/').filter(|s| !s.is_empty());
-
-    let mut route_match = RouteMatch::new(path.to_string());
-
-    for pattern_seg in pattern_iter {
-        if pattern_seg == "*" {
-            // Wildcard matches rest of path
-            return Some(route_match);
-        }
-
-        let Some(path_seg) = path_iter.next() else {
-            // Path exhausted before pattern — no match
-            return None;
-        };
-
-        if let Some(param_name) = pattern_seg.strip_prefix(':') {
-            route_match
-                .params
-                .insert(param_name.to_string(), path_seg.to_string());
-        } else if pattern_seg != path_seg {
-            return None;
-        }
-    }
-
-    // All pattern segments consumed — path must also be exhausted
-    if path_iter.next().is_some() {
-        return None;
-    }
-
-    Some(route_match)
-}
-
 // ============================================================================
 // Route Builder Utilities
 // ============================================================================
@@ -1146,6 +2293,8 @@ impl IntoRoute for NamedRoute {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pattern::Segment;
+    use crate::test_support::page;
 
     // NamedRouteRegistry tests
 
@@ -1207,6 +2356,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_url_for_affixed_param() {
+        let mut registry = NamedRouteRegistry::new();
+        registry.register("file.pdf", "/files/:name.pdf");
+
+        let mut params = RouteParams::new();
+        params.set("name".to_string(), "report".to_string());
+
+        assert_eq!(
+            registry.url_for("file.pdf", &params),
+            Some("/files/report.pdf".to_string())
+        );
+    }
+
+    #[test]
+    fn test_url_for_checked_complete_params() {
+        let mut registry = NamedRouteRegistry::new();
+        registry.register("user.post", "/users/:id<i32>/posts/:postId");
+
+        let mut params = RouteParams::new();
+        params.set("id".to_string(), "7".to_string());
+        params.set("postId".to_string(), "99".to_string());
+
+        assert_eq!(
+            registry.url_for_checked("user.post", &params),
+            Ok("/users/7/posts/99".to_string())
+        );
+    }
+
+    #[test]
+    fn test_url_for_missing_param() {
+        let mut registry = NamedRouteRegistry::new();
+        registry.register("user.post", "/users/:id/posts/:postId");
+
+        let mut params = RouteParams::new();
+        params.set("id".to_string(), "7".to_string());
+
+        assert_eq!(
+            registry.url_for_checked("user.post", &params),
+            Err(vec!["postId".to_string()])
+        );
+        assert_eq!(registry.url_for("user.post", &params), None);
+    }
+
+    #[test]
+    fn test_try_register_rejects_invalid_pattern() {
+        let mut registry = NamedRouteRegistry::new();
+        assert!(matches!(
+            registry.try_register("files", "/files/*rest/more"),
+            Err(PatternError::WildcardNotLast { .. })
+        ));
+        registry.register("files", "/files/*rest/more");
+        assert!(!registry.contains("files"));
+        assert!(registry.try_register("files", "/files/*rest").is_ok());
+        assert_eq!(registry.get("files"), Some("/files/*rest"));
+    }
+
+    #[test]
+    fn test_url_for_round_trips_captured_params() {
+        let mut registry = NamedRouteRegistry::new();
+        registry.register("user", "/users/:name");
+        registry.register("file", "/public/:file");
+
+        for (name, path) in [("user", "/users/%20bob"), ("file", "/public/..%2fsecret")] {
+            let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+            let (_, params) = registry
+                .pattern(name)
+                .unwrap()
+                .match_segments(&segments)
+                .unwrap();
+            assert_eq!(registry.url_for(name, &params), Some(path.to_string()));
+        }
+    }
+
     #[test]
     fn test_url_for_unknown_route() {
         let registry = NamedRouteRegistry::new();
@@ -1229,43 +2452,65 @@ mod tests {
         assert!(registry.is_empty());
     }
 
-    #[test]
-    fn test_substitute_params() {
-        let mut params = RouteParams::new();
-        params.set("id".to_string(), "123".to_string());
-        params.set("action".to_string(), "edit".to_string());
-
-        let result = substitute_params("/users/:id/:action", &params);
-        assert_eq!(result, "/users/123/edit");
-    }
-
     // Route tests
 
     #[test]
-    fn test_static_route() {
-        let result = match_path("/users", "/users");
-        assert!(result.is_some());
+    fn test_route_matches() {
+        let route_match = page("/users/:id").matches("/users/123").unwrap();
+        assert_eq!(route_match.params.get("id"), Some(&"123".to_string()));
+        assert_eq!(route_match.pattern, "/users/:id");
+        assert_eq!(route_match.path, "/users/123");
 
-        let result = match_path("/users", "/posts");
-        assert!(result.is_none());
+        assert!(page("/users").matches("/posts").is_none());
+        assert!(page("/users/:id").matches("/users/123/posts").is_none());
+        assert!(page("/files/*")
+            .matches("/files/documents/report.pdf")
+            .is_some());
     }
 
     #[test]
-    fn test_dynamic_route() {
-        let result = match_path("/users/:id", "/users/123");
-        assert!(result.is_some());
-
-        let route_match = result.unwrap();
-        assert_eq!(route_match.params.get("id"), Some(&"123".to_string()));
+    fn test_route_flags() {
+        let mut flags: RouteFlags = [RouteFlag::HiddenFromMenu, RouteFlag::Custom(31)]
+            .into_iter()
+            .collect();
+        assert!(flags.contains(RouteFlag::HiddenFromMenu));
+        assert!(flags.contains(RouteFlag::Custom(31)));
+        assert!(!flags.contains(RouteFlag::Custom(0)));
+        assert!(!flags.contains(RouteFlag::Experimental));
+
+        flags.remove(RouteFlag::HiddenFromMenu);
+        flags.remove(RouteFlag::Custom(31));
+        assert!(flags.is_empty());
+
+        let route = Route::view("/labs", || gpui::div().into_any_element())
+            .flag(RouteFlag::Experimental)
+            .flag(RouteFlag::Custom(3));
+        assert!(route.has_flag(RouteFlag::Experimental));
+        assert!(route.has_flag(RouteFlag::Custom(3)));
+        assert!(!route.has_flag(RouteFlag::RequiresConnection));
+        assert!(RouteConfig::new("/labs")
+            .flag(RouteFlag::Experimental)
+            .flags
+            .contains(RouteFlag::Experimental));
     }
 
     #[test]
-    fn test_wildcard_route() {
-        let result = match_path("/files/*", "/files/documents/report.pdf");
-        assert!(result.is_some());
+    #[should_panic(expected = "custom route flag out of range")]
+    fn test_custom_flag_out_of_range_panics() {
+        let _ = RouteFlags::empty().with(RouteFlag::Custom(RouteFlag::MAX_CUSTOM));
+    }
 
-        let result = match_path("/files/*", "/other/path");
-        assert!(result.is_none());
+    #[test]
+    fn test_route_config_compiles_pattern() {
+        let config = RouteConfig::new("/users/:id<i32>");
+        assert_eq!(
+            config.pattern().segments()[1],
+            Segment::Param {
+                name: "id".into(),
+                constraint: Some("i32".into()),
+            }
+        );
+        assert!(RouteConfig::try_new("/users/:id<vec<u8>>").is_err());
     }
 
     #[test]
@@ -1328,9 +2573,12 @@ mod tests {
 
     #[test]
     fn test_validate_invalid_parameter_name() {
-        let result = validate_route_path("/users/:user-id");
+        let result = validate_route_path("/files/*file-path");
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("alphanumeric"));
+
+        // A param name ends at the first other character, the rest is a suffix
+        assert!(validate_route_path("/users/:user-id").is_ok());
     }
 
     #[test]
@@ -1358,4 +2606,45 @@ mod tests {
     fn test_route_config_new_panics_on_invalid() {
         let _ = RouteConfig::new("/users//profile");
     }
+
+    // Child route builders
+
+    fn child_paths(route: &Route) -> Vec<&str> {
+        route
+            .children
+            .iter()
+            .map(|child| child.config.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_children_iter_and_child_match_children() {
+        let paths = ["", "profile", "security"];
+
+        let from_vec = page("/settings").children(paths.iter().map(|p| page(p).into()).collect());
+        let from_iter = page("/settings").children_iter(paths.map(page));
+        let from_child = page("/settings")
+            .child(page(""))
+            .child(Arc::new(page("profile")))
+            .child(page("security"));
+
+        assert_eq!(child_paths(&from_iter), child_paths(&from_vec));
+        assert_eq!(child_paths(&from_child), child_paths(&from_vec));
+    }
+
+    #[test]
+    fn test_children_iter_appends() {
+        let route = page("/docs")
+            .child(page("intro"))
+            .children_iter([page("install"), page("faq")])
+            .child(page("changelog"));
+        assert_eq!(
+            child_paths(&route),
+            ["intro", "install", "faq", "changelog"]
+        );
+
+        // `children` still replaces
+        let route = route.children(vec![page("only").into()]);
+        assert_eq!(child_paths(&route), ["only"]);
+    }
 }
@@ -0,0 +1,199 @@
+//! Static values routes hand down to their descendants.
+//!
+//! A route attaches values with [`Route::env`](crate::Route::env), at most
+//! one per type. Every route below it sees them, and a descendant setting a
+//! value of the same type overrides it for its own subtree: the nearest
+//! ancestor wins.
+//!
+//! ```
+//! use gpui::IntoElement;
+//! use gpui_navigator::{resolve_match_stack, Route};
+//! use std::sync::Arc;
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct ReadOnly(bool);
+//!
+//! let page = |path: &str| Route::view(path, || gpui::div().into_any_element());
+//! let routes = vec![Arc::new(
+//!     page("/docs")
+//!         .env(ReadOnly(false))
+//!         .child(page("drafts"))
+//!         .child(page("published").env(ReadOnly(true))),
+//! )];
+//!
+//! let stack = resolve_match_stack(&routes, "/docs/drafts");
+//! assert_eq!(stack.leaf().unwrap().env::<ReadOnly>(), Some(ReadOnly(false)));
+//! let stack = resolve_match_stack(&routes, "/docs/published");
+//! assert_eq!(stack.leaf().unwrap().env::<ReadOnly>(), Some(ReadOnly(true)));
+//! ```
+//!
+//! Values are plain data fixed when the route is defined. Each
+//! [`MatchEntry`](crate::MatchEntry) carries the values in effect at its
+//! level, merged once when the path resolves, so reading one is a map
+//! lookup. Builders read the current route's with [`use_route_env`]. While
+//! an outlet renders outgoing content for an exit animation, or holds
+//! stale content, that content reads the values of the stack it was
+//! rendered from.
+
+use crate::context::GlobalRouter;
+use gpui::App;
+use std::any::{Any, TypeId};
+#[cfg(feature = "transition")]
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Type-map of route environment values, at most one per type.
+///
+/// Clones share the stored values.
+#[derive(Clone, Default)]
+pub struct RouteEnv {
+    values: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl RouteEnv {
+    /// Return a copy of the value of type `T`, if one is set.
+    #[must_use]
+    pub fn get<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.values.get(&TypeId::of::<T>())?.downcast_ref().cloned()
+    }
+
+    /// Return `true` if a value of type `T` is set.
+    #[must_use]
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.values.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Return `true` if no value is set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Store `value`, replacing any earlier value of the same type.
+    pub(crate) fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        Arc::make_mut(&mut self.values).insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// The values a route with `own` values sees below an ancestor chain
+    /// that set `self`: `own` wins over inherited values of the same type.
+    pub(crate) fn inherit(&self, own: &Self) -> Self {
+        if own.is_empty() {
+            return self.clone();
+        }
+        if self.is_empty() {
+            return own.clone();
+        }
+        let mut values = (*self.values).clone();
+        values.extend(
+            own.values
+                .iter()
+                .map(|(ty, value)| (*ty, Arc::clone(value))),
+        );
+        Self {
+            values: Arc::new(values),
+        }
+    }
+}
+
+impl fmt::Debug for RouteEnv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteEnv")
+            .field("len", &self.values.len())
+            .finish()
+    }
+}
+
+#[cfg(feature = "transition")]
+thread_local! {
+    /// Values of the content being rebuilt by an outlet, while it builds.
+    static RENDER_ENV: RefCell<Option<RouteEnv>> = const { RefCell::new(None) };
+}
+
+/// Make [`use_route_env`] read `env` until dropped.
+///
+/// Outlets hold one while rebuilding content from an earlier stack, so it
+/// keeps the values it rendered with. Scopes nest.
+#[cfg(feature = "transition")]
+pub(crate) struct RenderEnvScope {
+    saved: Option<RouteEnv>,
+}
+
+#[cfg(feature = "transition")]
+impl RenderEnvScope {
+    pub(crate) fn enter(env: &RouteEnv) -> Self {
+        let saved = RENDER_ENV.with(|current| current.replace(Some(env.clone())));
+        Self { saved }
+    }
+}
+
+#[cfg(feature = "transition")]
+impl Drop for RenderEnvScope {
+    fn drop(&mut self) {
+        RENDER_ENV.with(|current| *current.borrow_mut() = self.saved.take());
+    }
+}
+
+/// Get the environment value of type `T` of the current route.
+///
+/// Reads the deepest matched route's values, inherited from its ancestors
+/// (see [`Route::env`](crate::Route::env)). Inside the builder of outgoing
+/// or held content, reads the values that content was rendered with.
+#[must_use]
+pub fn use_route_env<T: Clone + Send + Sync + 'static>(cx: &App) -> Option<T> {
+    #[cfg(feature = "transition")]
+    if let Some(value) = RENDER_ENV.with(|env| env.borrow().as_ref().map(RouteEnv::get::<T>)) {
+        return value;
+    }
+    cx.try_global::<GlobalRouter>()?.leaf()?.env::<T>()
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::test_support::page;
+    use crate::{init_router, Navigator};
+    use gpui::TestAppContext;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Theme(&'static str);
+
+    #[test]
+    fn test_inherit_prefers_own_values() {
+        let mut parent = RouteEnv::default();
+        parent.insert(Theme("light"));
+        parent.insert(7_u32);
+        let mut own = RouteEnv::default();
+        own.insert(Theme("dark"));
+
+        let env = parent.inherit(&own);
+        assert_eq!(env.get::<Theme>(), Some(Theme("dark")));
+        assert_eq!(env.get::<u32>(), Some(7));
+        // The parent's values are untouched
+        assert_eq!(parent.get::<Theme>(), Some(Theme("light")));
+        assert!(!env.contains::<bool>());
+    }
+
+    #[gpui::test]
+    fn test_use_route_env_reads_leaf(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/settings")
+                        .env(Theme("light"))
+                        .child(page("advanced").env(Theme("dark")))
+                        .child(page("general")),
+                );
+            });
+        });
+        assert_eq!(cx.read(use_route_env::<Theme>), None);
+
+        cx.update(|cx| Navigator::push(cx, "/settings/advanced"));
+        assert_eq!(cx.read(use_route_env::<Theme>), Some(Theme("dark")));
+        cx.update(|cx| Navigator::push(cx, "/settings/general"));
+        assert_eq!(cx.read(use_route_env::<Theme>), Some(Theme("light")));
+    }
+}
@@ -0,0 +1,383 @@
+//! Typed UI preferences keyed by route pattern.
+//!
+//! Some settings belong to a screen rather than to one URL: the density of
+//! the report grid, the zoom of a diagram view. [`RoutePrefs`] stores one
+//! value per type and route pattern on the [`GlobalRouter`], so
+//! `/reports/1` and `/reports/2` share the preference of `/reports/:id`.
+//!
+//! [`use_route_pref`] and [`set_route_pref`] read and write the preference
+//! of the current leaf route. Unlike [`use_route_state`](crate::use_route_state)
+//! slots, preferences are never evicted and survive
+//! [`switch_partition`](GlobalRouter::switch_partition); with the `serde`
+//! feature the whole store can be exported into the app's settings file
+//! and imported on the next start.
+//!
+//! Values are keyed by [`std::any::type_name`], so renaming or moving a
+//! preference type orphans what was stored under the old name.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use gpui_navigator::{set_route_pref, use_route_pref, Route};
+//!
+//! #[derive(Clone, Copy, Default, PartialEq)]
+//! enum Density {
+//!     #[default]
+//!     Comfortable,
+//!     Compact,
+//! }
+//!
+//! Route::new("/reports/:id", |_window, cx, _params| {
+//!     let density = use_route_pref::<Density>(cx);
+//!     div()
+//!         .child(report_grid(density))
+//!         .on_mouse_down(MouseButton::Left, |_, _, cx| {
+//!             set_route_pref(cx, Density::Compact);
+//!         })
+//!         .into_any_element()
+//! });
+//! ```
+
+use crate::context::{GlobalRouter, UseRouter};
+use crate::nested::build_child_path;
+use crate::route::Route;
+use gpui::App;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+/// A type that can be stored in [`RoutePrefs`].
+///
+/// Implemented for every `Clone + Default + 'static` type. With the `serde`
+/// feature, values must also implement `Serialize` and `DeserializeOwned`
+/// so the store can be exported.
+#[cfg(not(feature = "serde"))]
+pub trait RoutePref: Clone + Default + 'static {}
+
+#[cfg(not(feature = "serde"))]
+impl<T: Clone + Default + 'static> RoutePref for T {}
+
+/// A type that can be stored in [`RoutePrefs`].
+///
+/// Implemented for every `Clone + Default + Serialize + DeserializeOwned`
+/// type, so the store can be exported.
+#[cfg(feature = "serde")]
+pub trait RoutePref:
+    Clone + Default + serde::Serialize + serde::de::DeserializeOwned + 'static
+{
+}
+
+#[cfg(feature = "serde")]
+impl<T> RoutePref for T where
+    T: Clone + Default + serde::Serialize + serde::de::DeserializeOwned + 'static
+{
+}
+
+/// How a value is held: as itself, or as JSON when the store is exportable.
+#[cfg(not(feature = "serde"))]
+type Stored = std::rc::Rc<dyn std::any::Any>;
+
+#[cfg(feature = "serde")]
+type Stored = serde_json::Value;
+
+/// Key of the values of type `T`.
+fn type_key<T: RoutePref>() -> &'static str {
+    std::any::type_name::<T>()
+}
+
+// ============================================================================
+// RoutePrefs
+// ============================================================================
+
+/// Typed preferences, one value per type and route pattern.
+///
+/// Reading a preference that was never written yields `T::default()`.
+#[derive(Clone, Default)]
+pub struct RoutePrefs {
+    /// Pattern → type name → value.
+    prefs: BTreeMap<String, BTreeMap<String, Stored>>,
+}
+
+impl RoutePrefs {
+    /// Return the preference of type `T` for `pattern`.
+    #[must_use]
+    pub fn get<T: RoutePref>(&self, pattern: &str) -> T {
+        self.prefs
+            .get(pattern)
+            .and_then(|values| values.get(type_key::<T>()))
+            .and_then(decode)
+            .unwrap_or_default()
+    }
+
+    /// Set the preference of type `T` for `pattern`.
+    pub fn set<T: RoutePref>(&mut self, pattern: &str, value: T) {
+        let Some(value) = encode(value) else {
+            return;
+        };
+        self.prefs
+            .entry(pattern.to_string())
+            .or_default()
+            .insert(type_key::<T>().to_string(), value);
+    }
+
+    /// Remove the preference of type `T` for `pattern`, returning whether
+    /// one was stored.
+    pub fn remove<T: RoutePref>(&mut self, pattern: &str) -> bool {
+        let Some(values) = self.prefs.get_mut(pattern) else {
+            return false;
+        };
+        let removed = values.remove(type_key::<T>()).is_some();
+        if values.is_empty() {
+            self.prefs.remove(pattern);
+        }
+        removed
+    }
+
+    /// Iterate over the patterns that have preferences, in sorted order.
+    pub fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.prefs.keys().map(String::as_str)
+    }
+
+    /// Return `true` if no preference is stored.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.prefs.is_empty()
+    }
+
+    /// Drop every preference.
+    pub fn clear(&mut self) {
+        self.prefs.clear();
+    }
+
+    /// Export every preference as a JSON object of pattern → type name → value.
+    #[cfg(feature = "serde")]
+    #[must_use]
+    pub fn export(&self) -> serde_json::Value {
+        serde_json::to_value(&self.prefs).unwrap_or_default()
+    }
+
+    /// Replace every preference with the ones of an [`export`](Self::export).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` is not shaped like an export; the store
+    /// is left unchanged then.
+    #[cfg(feature = "serde")]
+    pub fn import(&mut self, value: serde_json::Value) -> Result<(), serde_json::Error> {
+        self.prefs = serde_json::from_value(value)?;
+        Ok(())
+    }
+
+    /// Drop the preferences of patterns not in `live`, returning those patterns.
+    pub(crate) fn prune(&mut self, live: &HashSet<String>) -> Vec<String> {
+        let orphaned: Vec<String> = self
+            .prefs
+            .keys()
+            .filter(|pattern| !live.contains(*pattern))
+            .cloned()
+            .collect();
+        for pattern in &orphaned {
+            self.prefs.remove(pattern);
+        }
+        orphaned
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+#[allow(clippy::unnecessary_wraps)]
+fn encode<T: RoutePref>(value: T) -> Option<Stored> {
+    Some(std::rc::Rc::new(value))
+}
+
+#[cfg(not(feature = "serde"))]
+fn decode<T: RoutePref>(stored: &Stored) -> Option<T> {
+    stored.downcast_ref::<T>().cloned()
+}
+
+#[cfg(feature = "serde")]
+fn encode<T: RoutePref>(value: T) -> Option<Stored> {
+    serde_json::to_value(value)
+        .map_err(|_err| {
+            crate::warn_log!(
+                "Route preference {} is not serializable: {}",
+                type_key::<T>(),
+                _err
+            );
+        })
+        .ok()
+}
+
+#[cfg(feature = "serde")]
+fn decode<T: RoutePref>(stored: &Stored) -> Option<T> {
+    // A value whose type changed shape since it was stored reads as default
+    T::deserialize(stored).ok()
+}
+
+impl fmt::Debug for RoutePrefs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.prefs
+                    .iter()
+                    .map(|(pattern, values)| (pattern, values.keys().collect::<Vec<_>>())),
+            )
+            .finish()
+    }
+}
+
+/// Accumulated pattern of every route in `routes` and their descendants.
+pub(crate) fn route_patterns(routes: &[Arc<Route>]) -> HashSet<String> {
+    fn collect(route: &Route, parent: &str, out: &mut HashSet<String>) {
+        let pattern = build_child_path(parent, &route.config.path).into_owned();
+        for child in &route.children {
+            collect(child, &pattern, out);
+        }
+        out.insert(pattern);
+    }
+    let mut patterns = HashSet::new();
+    for route in routes {
+        collect(route, "/", &mut patterns);
+    }
+    patterns
+}
+
+// ============================================================================
+// Hooks
+// ============================================================================
+
+/// Pattern of the current leaf route, or the current path if nothing matched.
+fn current_pattern(router: &GlobalRouter) -> String {
+    router
+        .match_stack()
+        .leaf_pattern()
+        .unwrap_or_else(|| router.current_path())
+        .to_string()
+}
+
+/// Get the preference of type `T` for the current leaf route's pattern.
+#[must_use]
+pub fn use_route_pref<T: RoutePref>(cx: &App) -> T {
+    let router = cx.router();
+    router.route_prefs().get(&current_pattern(router))
+}
+
+/// Set the preference of type `T` for the current leaf route's pattern.
+pub fn set_route_pref<T: RoutePref>(cx: &mut App, value: T) {
+    cx.update_router(|router, _| {
+        let pattern = current_pattern(router);
+        router.route_prefs_mut().set(&pattern, value);
+    });
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::test_support::page;
+    use crate::{init_router, Navigator};
+    use gpui::TestAppContext;
+
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    enum Density {
+        #[default]
+        Comfortable,
+        Compact,
+    }
+
+    #[test]
+    fn test_typed_round_trip() {
+        let mut prefs = RoutePrefs::default();
+        assert_eq!(prefs.get::<Density>("/reports/:id"), Density::Comfortable);
+
+        prefs.set("/reports/:id", Density::Compact);
+        prefs.set("/reports/:id", 1.5_f64);
+        assert_eq!(prefs.get::<Density>("/reports/:id"), Density::Compact);
+        assert!((prefs.get::<f64>("/reports/:id") - 1.5).abs() < f64::EPSILON);
+        // Other patterns and types are untouched
+        assert_eq!(prefs.get::<Density>("/dashboard"), Density::Comfortable);
+        assert_eq!(prefs.get::<u32>("/reports/:id"), 0);
+
+        assert!(prefs.remove::<Density>("/reports/:id"));
+        assert!(!prefs.remove::<Density>("/reports/:id"));
+        assert!(prefs.remove::<f64>("/reports/:id"));
+        assert!(prefs.is_empty());
+    }
+
+    #[gpui::test]
+    fn test_prefs_keyed_by_pattern_not_url(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/dashboard"));
+                router.add_route(page("/reports/:id"));
+            });
+            Navigator::push(cx, "/reports/1");
+            set_route_pref(cx, Density::Compact);
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/reports/2"));
+        assert_eq!(cx.read(use_route_pref::<Density>), Density::Compact);
+        cx.update(|cx| Navigator::push(cx, "/dashboard"));
+        assert_eq!(cx.read(use_route_pref::<Density>), Density::Comfortable);
+
+        let patterns: Vec<String> = cx.read(|cx| {
+            cx.router()
+                .route_prefs()
+                .patterns()
+                .map(str::to_string)
+                .collect()
+        });
+        assert_eq!(patterns, ["/reports/:id"]);
+    }
+
+    #[gpui::test]
+    fn test_prune_orphaned_prefs(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/settings").child(page("users/:id")));
+            });
+            cx.update_router(|router, _| {
+                let prefs = router.route_prefs_mut();
+                prefs.set("/settings/users/:id", Density::Compact);
+                prefs.set("/reports/:id", Density::Compact);
+                prefs.set("/old", 2_u32);
+            });
+        });
+
+        let pruned = cx.update(|cx| cx.update_router(|router, _| router.prune_orphaned_prefs()));
+        assert_eq!(pruned, ["/old", "/reports/:id"]);
+        cx.read(|cx| {
+            let prefs = cx.router().route_prefs();
+            assert_eq!(
+                prefs.patterns().collect::<Vec<_>>(),
+                ["/settings/users/:id"]
+            );
+            assert_eq!(
+                prefs.get::<Density>("/settings/users/:id"),
+                Density::Compact
+            );
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_export_import_round_trip() {
+        let mut prefs = RoutePrefs::default();
+        prefs.set("/reports/:id", Density::Compact);
+        prefs.set("/reports/:id", 3_u32);
+
+        let exported = prefs.export();
+        let restored_json = serde_json::to_string(&exported).unwrap();
+        let mut restored = RoutePrefs::default();
+        restored
+            .import(serde_json::from_str(&restored_json).unwrap())
+            .unwrap();
+        assert_eq!(restored.get::<Density>("/reports/:id"), Density::Compact);
+        assert_eq!(restored.get::<u32>("/reports/:id"), 3);
+
+        assert!(restored.import(serde_json::json!([1, 2])).is_err());
+        assert_eq!(restored.get::<u32>("/reports/:id"), 3);
+    }
+}
@@ -0,0 +1,154 @@
+//! Route state readable from background threads.
+//!
+//! [`GlobalRouter`](crate::GlobalRouter) lives in a GPUI global, reachable
+//! only on the main thread. [`GlobalRouter::snapshot_handle`](crate::GlobalRouter::snapshot_handle)
+//! hands out a [`RouteSnapshotHandle`]: a cheap, `Send + Sync` clone that
+//! background tasks keep and [`load`](RouteSnapshotHandle::load) whenever
+//! they need the current route, without touching GPUI.
+//!
+//! ```
+//! use gpui_navigator::GlobalRouter;
+//!
+//! let router = GlobalRouter::new();
+//! let handle = router.snapshot_handle();
+//! std::thread::spawn(move || {
+//!     let snapshot = handle.load();
+//!     if snapshot.path != "/inbox" {
+//!         // Not looking at the inbox: notify
+//!     }
+//! })
+//! .join()
+//! .unwrap();
+//! ```
+//!
+//! The snapshot is replaced as a whole once a navigation has committed its
+//! history entry and resolved its match stack, so a reader never sees a
+//! path with another route's params. It is eventually consistent: while a
+//! navigation runs its guards and `before` middleware, readers still see the
+//! previous route, and a navigation blocked there leaves it untouched. Each
+//! hop of a redirect publishes, as do restores, partition switches and route
+//! table changes.
+//!
+//! Not to be confused with [`RouterSnapshot`](crate::RouterSnapshot), the
+//! saved history that [`GlobalRouter::restore`](crate::GlobalRouter::restore)
+//! re-applies.
+
+use crate::params::RouteParams;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// The route on screen as of the latest committed navigation.
+///
+/// Plain data only: no routes, builders or other closures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteSnapshot {
+    /// Current path
+    pub path: String,
+    /// Accumulated pattern of the leaf route, `None` when nothing matched
+    pub pattern: Option<String>,
+    /// Accumulated params of the leaf route
+    pub params: RouteParams,
+    /// Number of navigations committed so far, restores and partition switches included
+    pub sequence: u64,
+    /// Whether `back` would succeed
+    pub can_go_back: bool,
+    /// Whether `forward` would succeed
+    pub can_go_forward: bool,
+}
+
+impl Default for RouteSnapshot {
+    /// The snapshot of a router that has not navigated yet.
+    fn default() -> Self {
+        Self {
+            path: "/".to_string(),
+            pattern: None,
+            params: RouteParams::new(),
+            sequence: 0,
+            can_go_back: false,
+            can_go_forward: false,
+        }
+    }
+}
+
+/// Shared, thread-safe access to the latest [`RouteSnapshot`].
+///
+/// Clones share the same snapshot.
+#[derive(Debug, Clone, Default)]
+pub struct RouteSnapshotHandle(Arc<RwLock<Arc<RouteSnapshot>>>);
+
+impl RouteSnapshotHandle {
+    /// Return the latest snapshot.
+    ///
+    /// Holds the lock only to clone an `Arc`, so it never waits on a
+    /// navigation.
+    #[must_use]
+    pub fn load(&self) -> Arc<RouteSnapshot> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the snapshot.
+    pub(crate) fn store(&self, snapshot: RouteSnapshot) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(snapshot);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use crate::test_support::page;
+    use crate::{init_router, GlobalRouter, Navigator};
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    fn test_snapshot_handle_follows_committed_navigation(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/users").child(page(":id")));
+            });
+        });
+        let handle = cx.read(|cx| cx.global::<GlobalRouter>().snapshot_handle());
+        let initial = handle.load();
+        assert_eq!(initial.path, "/");
+        assert_eq!(initial.pattern.as_deref(), Some("/"));
+
+        cx.update(|cx| Navigator::push(cx, "/users/7"));
+        let reader = handle.clone();
+        let snapshot = std::thread::spawn(move || reader.load()).join().unwrap();
+        assert_eq!(snapshot.path, "/users/7");
+        assert_eq!(snapshot.pattern.as_deref(), Some("/users/:id"));
+        assert_eq!(snapshot.params.get("id").map(String::as_str), Some("7"));
+        assert_eq!(snapshot.sequence, initial.sequence + 1);
+        assert!(snapshot.can_go_back);
+        assert!(!snapshot.can_go_forward);
+
+        cx.update(Navigator::back);
+        let snapshot = handle.load();
+        assert_eq!(snapshot.path, "/");
+        assert_eq!(snapshot.sequence, initial.sequence + 2);
+        assert!(snapshot.can_go_forward);
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_snapshot_handle_ignores_blocked_navigation(cx: &mut TestAppContext) {
+        use crate::{guard_fn, NavigationAction};
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/vault").guard(guard_fn(|_, _| NavigationAction::deny("Locked"))),
+                );
+            });
+        });
+        let handle = cx.read(|cx| cx.global::<GlobalRouter>().snapshot_handle());
+        let before = handle.load();
+
+        cx.update(|cx| Navigator::push(cx, "/vault"));
+        assert_eq!(cx.read(Navigator::current_path), "/");
+        assert_eq!(*handle.load(), *before);
+    }
+}
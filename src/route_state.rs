@@ -0,0 +1,430 @@
+//! Router-managed state scoped to a matched route.
+//!
+//! Builders are re-run on every render, so state created inside them is
+//! lost whenever the route is left. [`use_route_state`] hands out a slot
+//! stored on the [`GlobalRouter`] instead, keyed by the accumulated URL of
+//! the route whose builder is running plus a caller-chosen key. Leaving the
+//! route and coming back finds the slot again; a different param value
+//! (`/users/1` vs `/users/2`) gets its own slot.
+//!
+//! [`use_route_state_shared`] keys by the route pattern instead, so every
+//! param value of the route shares one slot.
+//!
+//! Slots are bounded: at most [`MAX_ROUTE_STATE`] are kept, the least
+//! recently used one is dropped first. URL-keyed slots of forward history
+//! entries that a push truncates are dropped along with their cached
+//! components.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! use gpui_navigator::{use_route_state, Route};
+//!
+//! Route::new("/inbox", |_window, cx, _params| {
+//!     let filter = use_route_state::<String>(cx, "filter");
+//!     let current = filter.get(cx);
+//!     div()
+//!         .child(format!("Filter: {current}"))
+//!         .on_mouse_down(MouseButton::Left, move |_, _, cx| {
+//!             filter.update(cx, |value| *value = "unread".into());
+//!         })
+//!         .into_any_element()
+//! });
+//! ```
+
+use crate::context::{GlobalRouter, UseRouter};
+use crate::resolve::current_parent_depth;
+use crate::trace_log;
+use gpui::App;
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// Maximum number of route state slots kept by the router.
+pub const MAX_ROUTE_STATE: usize = 128;
+
+// ============================================================================
+// Slot keys
+// ============================================================================
+
+/// What a slot is scoped to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum RouteStateScope {
+    /// Accumulated URL of the route (`/users/42`).
+    Url(String),
+    /// Accumulated pattern of the route (`/users/:id`).
+    Pattern(String),
+}
+
+/// Full key of one slot: its scope plus the caller's key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct SlotKey {
+    scope: RouteStateScope,
+    key: String,
+}
+
+// ============================================================================
+// RouteStateStore
+// ============================================================================
+
+/// Type-erased slots with least-recently-used eviction.
+#[derive(Clone, Default)]
+pub(crate) struct RouteStateStore {
+    slots: HashMap<SlotKey, Rc<dyn Any>>,
+    /// Slot keys from least to most recently used.
+    order: VecDeque<SlotKey>,
+}
+
+impl RouteStateStore {
+    /// Value of a slot, if it exists and holds a `T`.
+    fn get<T: Clone + 'static>(&self, key: &SlotKey) -> Option<T> {
+        self.slots.get(key)?.downcast_ref::<T>().cloned()
+    }
+
+    /// Mark a slot as most recently used.
+    fn touch(&mut self, key: &SlotKey) {
+        if let Some(index) = self.order.iter().position(|k| k == key) {
+            if let Some(key) = self.order.remove(index) {
+                self.order.push_back(key);
+            }
+        }
+    }
+
+    /// Store `value`, evicting the least recently used slots if full.
+    fn set<T: 'static>(&mut self, key: SlotKey, value: T) {
+        if self.slots.contains_key(&key) {
+            self.touch(&key);
+        } else {
+            while self.slots.len() >= MAX_ROUTE_STATE {
+                let Some(oldest) = self.order.pop_front() else {
+                    break;
+                };
+                trace_log!("Evicted route state '{}' of {:?}", oldest.key, oldest.scope);
+                self.slots.remove(&oldest);
+            }
+            self.order.push_back(key.clone());
+        }
+        self.slots.insert(key, Rc::new(value));
+    }
+
+    /// Drop every URL-keyed slot of `url`.
+    pub(crate) fn remove_url(&mut self, url: &str) {
+        let dropped = |key: &SlotKey| matches!(&key.scope, RouteStateScope::Url(u) if u == url);
+        self.slots.retain(|key, _| !dropped(key));
+        self.order.retain(|key| !dropped(key));
+    }
+
+    /// Drop every slot.
+    pub(crate) fn clear(&mut self) {
+        self.slots.clear();
+        self.order.clear();
+    }
+}
+
+impl fmt::Debug for RouteStateStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteStateStore")
+            .field("slots", &self.slots.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// ============================================================================
+// RouteStateHandle
+// ============================================================================
+
+/// Handle to one route state slot, returned by [`use_route_state`].
+///
+/// The handle remembers which route it was created for, so it can be moved
+/// into event handlers and used after the render that created it. Reading
+/// a slot that was never written (or was evicted) yields `T::default()`.
+pub struct RouteStateHandle<T> {
+    slot: SlotKey,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Clone + Default + 'static> RouteStateHandle<T> {
+    /// The URL or pattern this slot is scoped to.
+    #[must_use]
+    pub fn scope(&self) -> &str {
+        match &self.slot.scope {
+            RouteStateScope::Url(scope) | RouteStateScope::Pattern(scope) => scope,
+        }
+    }
+
+    /// The caller-chosen key of this slot.
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.slot.key
+    }
+
+    /// Current value of the slot.
+    #[must_use]
+    pub fn get(&self, cx: &App) -> T {
+        cx.router()
+            .route_state()
+            .get(&self.slot)
+            .unwrap_or_default()
+    }
+
+    /// Replace the value of the slot.
+    pub fn set(&self, cx: &mut App, value: T) {
+        let slot = self.slot.clone();
+        cx.update_router(|router, _| router.route_state_mut().set(slot, value));
+    }
+
+    /// Modify the value of the slot in place and write it back.
+    pub fn update<R>(&self, cx: &mut App, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut value = self.get(cx);
+        let result = f(&mut value);
+        self.set(cx, value);
+        result
+    }
+}
+
+impl<T> Clone for RouteStateHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: self.slot.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for RouteStateHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteStateHandle")
+            .field("scope", &self.slot.scope)
+            .field("key", &self.slot.key)
+            .finish()
+    }
+}
+
+// ============================================================================
+// Hooks
+// ============================================================================
+
+/// Get the state slot `key` of the route whose builder is running.
+///
+/// The slot is keyed by the accumulated URL of that route, so `/users/1`
+/// and `/users/2` get separate slots. Called outside a route builder, the
+/// deepest matched route is used.
+pub fn use_route_state<T: Clone + Default + 'static>(
+    cx: &mut App,
+    key: &str,
+) -> RouteStateHandle<T> {
+    slot_handle(cx, key, false)
+}
+
+/// Like [`use_route_state`], but keyed by the route pattern.
+///
+/// Every param value of the route (`/users/1`, `/users/2`, ...) shares
+/// one slot.
+pub fn use_route_state_shared<T: Clone + Default + 'static>(
+    cx: &mut App,
+    key: &str,
+) -> RouteStateHandle<T> {
+    slot_handle(cx, key, true)
+}
+
+fn slot_handle<T: Clone + Default + 'static>(
+    cx: &mut App,
+    key: &str,
+    by_pattern: bool,
+) -> RouteStateHandle<T> {
+    let slot = cx.update_router(|router, _| {
+        let scope = current_scope(router, by_pattern);
+        let slot = SlotKey {
+            scope,
+            key: key.to_string(),
+        };
+        router.route_state_mut().touch(&slot);
+        slot
+    });
+    RouteStateHandle {
+        slot,
+        _marker: PhantomData,
+    }
+}
+
+/// Scope of the route whose builder is running.
+fn current_scope(router: &GlobalRouter, by_pattern: bool) -> RouteStateScope {
+    let stack = router.match_stack();
+    let entry = current_parent_depth()
+        .and_then(|depth| stack.at_depth(depth))
+        .or_else(|| stack.leaf());
+    match entry {
+        Some(entry) if by_pattern => RouteStateScope::Pattern(entry.accumulated_pattern().into()),
+        Some(entry) => RouteStateScope::Url(entry.accumulated_url().into()),
+        None => RouteStateScope::Url(router.current_path().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::route::Route;
+    use crate::{init_router, router_view, Navigator};
+    use gpui::{div, Context, IntoElement, Render, TestAppContext, Window};
+    use std::sync::Mutex;
+
+    struct Root;
+
+    impl Render for Root {
+        fn render(&mut self, _: &mut Window, _: &mut Context<'_, Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    /// Render one frame of the current route and return the handle its builder took.
+    fn render(
+        cx: &mut TestAppContext,
+        seen: &'static Mutex<Option<RouteStateHandle<u32>>>,
+    ) -> RouteStateHandle<u32> {
+        let window = cx.add_window(|_, _| Root);
+        window
+            .update(cx, |_, window, cx| {
+                let _ = router_view(window, cx);
+            })
+            .unwrap();
+        seen.lock().unwrap().take().expect("builder ran")
+    }
+
+    fn counter_route(
+        path: &str,
+        shared: bool,
+        seen: &'static Mutex<Option<RouteStateHandle<u32>>>,
+    ) -> Route {
+        Route::new(path, move |_, cx, _| {
+            let handle = if shared {
+                use_route_state_shared(cx, "count")
+            } else {
+                use_route_state(cx, "count")
+            };
+            *seen.lock().unwrap() = Some(handle);
+            div().into_any_element()
+        })
+    }
+
+    #[gpui::test]
+    fn test_state_survives_navigating_away_and_back(cx: &mut TestAppContext) {
+        static SEEN: Mutex<Option<RouteStateHandle<u32>>> = Mutex::new(None);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(Route::new("/", |_, _, _| div().into_any_element()));
+                router.add_route(counter_route("/inbox", false, &SEEN));
+            });
+            Navigator::push(cx, "/inbox");
+        });
+
+        let handle = render(cx, &SEEN);
+        assert_eq!(handle.scope(), "/inbox");
+        assert_eq!(cx.read(|cx| handle.get(cx)), 0);
+        let returned = cx.update(|cx| {
+            handle.update(cx, |count| {
+                *count += 2;
+                *count
+            })
+        });
+        assert_eq!(returned, 2);
+
+        cx.update(|cx| Navigator::push(cx, "/"));
+        cx.update(|cx| Navigator::push(cx, "/inbox"));
+        let handle = render(cx, &SEEN);
+        assert_eq!(cx.read(|cx| handle.get(cx)), 2);
+
+        cx.update(|cx| cx.update_router(|router, _| router.clear_route_state()));
+        assert_eq!(cx.read(|cx| handle.get(cx)), 0);
+    }
+
+    #[gpui::test]
+    fn test_param_values_get_separate_slots(cx: &mut TestAppContext) {
+        static SEEN: Mutex<Option<RouteStateHandle<u32>>> = Mutex::new(None);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(counter_route("/users/:id", false, &SEEN));
+            });
+            Navigator::push(cx, "/users/1");
+        });
+
+        let first = render(cx, &SEEN);
+        cx.update(|cx| first.set(cx, 7));
+
+        cx.update(|cx| Navigator::push(cx, "/users/2"));
+        let second = render(cx, &SEEN);
+        assert_eq!(second.scope(), "/users/2");
+        assert_eq!(cx.read(|cx| second.get(cx)), 0);
+        assert_eq!(cx.read(|cx| first.get(cx)), 7);
+    }
+
+    #[gpui::test]
+    fn test_pattern_keyed_state_is_shared(cx: &mut TestAppContext) {
+        static SEEN: Mutex<Option<RouteStateHandle<u32>>> = Mutex::new(None);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(counter_route("/users/:id", true, &SEEN));
+            });
+            Navigator::push(cx, "/users/1");
+        });
+
+        let first = render(cx, &SEEN);
+        assert_eq!(first.scope(), "/users/:id");
+        cx.update(|cx| first.set(cx, 3));
+
+        cx.update(|cx| Navigator::push(cx, "/users/2"));
+        let second = render(cx, &SEEN);
+        assert_eq!(cx.read(|cx| second.get(cx)), 3);
+    }
+
+    #[gpui::test]
+    fn test_least_recently_used_slot_is_evicted(cx: &mut TestAppContext) {
+        static SEEN: Mutex<Option<RouteStateHandle<u32>>> = Mutex::new(None);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(counter_route("/items/:id", false, &SEEN));
+            });
+        });
+
+        let mut handles = Vec::new();
+        for id in 0..MAX_ROUTE_STATE {
+            cx.update(|cx| Navigator::push(cx, format!("/items/{id}")));
+            let handle = render(cx, &SEEN);
+            cx.update(|cx| handle.set(cx, 1));
+            handles.push(handle);
+        }
+
+        // Using the oldest slot again makes the second one least recent
+        cx.update(|cx| handles[0].set(cx, 2));
+        cx.update(|cx| Navigator::push(cx, "/items/new"));
+        let newest = render(cx, &SEEN);
+        cx.update(|cx| newest.set(cx, 1));
+
+        assert_eq!(cx.read(|cx| handles[0].get(cx)), 2);
+        assert_eq!(cx.read(|cx| handles[1].get(cx)), 0);
+        assert_eq!(cx.read(|cx| handles[2].get(cx)), 1);
+        assert_eq!(cx.read(|cx| newest.get(cx)), 1);
+    }
+
+    #[gpui::test]
+    fn test_truncated_forward_entries_drop_their_state(cx: &mut TestAppContext) {
+        static SEEN: Mutex<Option<RouteStateHandle<u32>>> = Mutex::new(None);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(counter_route("/items/:id", false, &SEEN));
+            });
+            Navigator::push(cx, "/items/1");
+            Navigator::push(cx, "/items/2");
+        });
+
+        let forward = render(cx, &SEEN);
+        cx.update(|cx| forward.set(cx, 5));
+
+        cx.update(|cx| {
+            Navigator::pop(cx);
+            Navigator::push(cx, "/items/3");
+        });
+        assert_eq!(cx.read(|cx| forward.get(cx)), 0);
+    }
+}
@@ -0,0 +1,650 @@
+//! Ready-made navigable application shell.
+//!
+//! [`NavScaffold`] bundles the pieces every example re-implements — a header
+//! showing the current path, a sidebar of navigation items, the content
+//! [`RouterOutlet`] and an optional debug panel — into one root entity:
+//!
+//! ```ignore
+//! cx.open_window(WindowOptions::default(), |_, cx| {
+//!     NavScaffold::new()
+//!         .title("My App")
+//!         .sidebar_from_routes("/")
+//!         .with_debug_panel(true)
+//!         .build(cx)
+//! });
+//! ```
+//!
+//! Every piece can be replaced with a closure ([`header`](NavScaffold::header),
+//! [`sidebar`](NavScaffold::sidebar), [`debug_panel`](NavScaffold::debug_panel)).
+//! The sidebar contents come from a [`SidebarModel`], built either from the
+//! registered routes or from an explicit item list.
+
+use crate::context::GlobalRouter;
+use crate::nested::{build_child_path, normalize_path};
+use crate::route::Route;
+use crate::widgets::RouterOutlet;
+use crate::Navigator;
+use gpui::prelude::FluentBuilder as _;
+#[allow(clippy::wildcard_imports)]
+use gpui::*;
+use std::sync::Arc;
+
+/// Route meta key used as the sidebar label when present.
+pub const TITLE_META_KEY: &str = "title";
+
+/// Custom header renderer: `(title, current_path)`.
+pub type HeaderFn = Box<dyn Fn(&str, &str) -> AnyElement>;
+
+/// Custom sidebar renderer: `(model, current_path)`.
+pub type SidebarFn = Box<dyn Fn(&SidebarModel, &str) -> AnyElement>;
+
+/// Custom debug panel renderer.
+pub type DebugPanelFn = Box<dyn Fn(&DebugSnapshot) -> AnyElement>;
+
+// ============================================================================
+// Sidebar model
+// ============================================================================
+
+/// A single sidebar entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavItem {
+    /// Absolute path navigated to on click
+    pub path: String,
+    /// Display label
+    pub label: String,
+}
+
+impl NavItem {
+    /// Create an item for `path` with an explicit label.
+    pub fn new(path: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            path: normalize_path(&path.into()).into_owned(),
+            label: label.into(),
+        }
+    }
+
+    /// Check whether this item is active for `current_path`.
+    ///
+    /// An item is active on its own path and on any path below it
+    /// (`/settings` is active for `/settings/profile`). The root item is
+    /// only active on `/` itself.
+    #[must_use]
+    pub fn is_active(&self, current_path: &str) -> bool {
+        let current = normalize_path(current_path);
+        if current == self.path {
+            return true;
+        }
+        self.path != "/"
+            && current
+                .strip_prefix(self.path.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+    }
+}
+
+/// The list of items a sidebar shows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SidebarModel {
+    items: Vec<NavItem>,
+}
+
+impl SidebarModel {
+    /// Create a model from explicit items.
+    #[must_use]
+    pub const fn new(items: Vec<NavItem>) -> Self {
+        Self { items }
+    }
+
+    /// Build a model from the routes directly below `base`.
+    ///
+    /// For `/` these are the top-level routes; otherwise the children of the
+    /// route whose full path is `base`. Index routes (empty path) and routes
+    /// with params or wildcards are skipped, since there is no single URL to
+    /// link to. Labels come from the `title` meta, then the route name, then
+    /// the last path segment.
+    #[must_use]
+    pub fn from_routes(routes: &[Arc<Route>], base: &str) -> Self {
+        let base = normalize_path(base);
+        let items = if base == "/" {
+            Self::items_for("/", routes)
+        } else {
+            find_route(routes, "/", &base)
+                .map(|route| Self::items_for(&base, &route.children))
+                .unwrap_or_default()
+        };
+        Self { items }
+    }
+
+    fn items_for(parent: &str, routes: &[Arc<Route>]) -> Vec<NavItem> {
+        routes
+            .iter()
+            .filter(|route| is_linkable(&route.config.path))
+            .map(|route| {
+                let path = build_child_path(parent, &route.config.path).into_owned();
+                let label = route_label(route, &path);
+                NavItem { path, label }
+            })
+            .collect()
+    }
+
+    /// Return the items in display order.
+    #[must_use]
+    pub fn items(&self) -> &[NavItem] {
+        &self.items
+    }
+
+    /// Check whether the model has no items.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Return the index of the active item for `current_path`.
+    ///
+    /// When several items match (`/` and `/settings` both prefix-match at
+    /// different depths), the one with the longest path wins.
+    #[must_use]
+    pub fn active_index(&self, current_path: &str) -> Option<usize> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.is_active(current_path))
+            .max_by_key(|(_, item)| item.path.len())
+            .map(|(index, _)| index)
+    }
+}
+
+fn is_linkable(path: &str) -> bool {
+    !path.is_empty() && !path.contains(':') && !path.contains('*')
+}
+
+fn find_route<'a>(routes: &'a [Arc<Route>], parent: &str, target: &str) -> Option<&'a Route> {
+    routes.iter().find_map(|route| {
+        let full = build_child_path(parent, &route.config.path);
+        if full == target {
+            Some(route.as_ref())
+        } else if target.starts_with(full.as_ref()) {
+            find_route(&route.children, &full, target)
+        } else {
+            None
+        }
+    })
+}
+
+fn route_label(route: &Route, path: &str) -> String {
+    if let Some(title) = route.config.meta.get(TITLE_META_KEY) {
+        return title.clone();
+    }
+    if let Some(name) = &route.config.name {
+        return name.clone();
+    }
+    match path.rsplit('/').next() {
+        Some("") | None => "Home".to_string(),
+        Some(segment) => {
+            let mut chars = segment.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        }
+    }
+}
+
+// ============================================================================
+// Debug snapshot
+// ============================================================================
+
+/// Router state shown by the debug panel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DebugSnapshot {
+    /// Current path
+    pub current_path: String,
+    /// Paths in the history stack, oldest first
+    pub history: Vec<String>,
+    /// Index of the current entry in `history`
+    pub history_index: usize,
+    /// Accumulated pattern of each matched route, root first
+    pub matched: Vec<String>,
+}
+
+impl DebugSnapshot {
+    /// Capture the state of `router`.
+    #[must_use]
+    pub fn capture(router: &GlobalRouter) -> Self {
+        let history = router.state().history();
+        Self {
+            current_path: router.current_path().to_string(),
+            history: history
+                .entries()
+                .iter()
+                .map(|entry| entry.path.clone())
+                .collect(),
+            history_index: history.current_index(),
+            matched: router
+                .match_stack()
+                .entries()
+                .iter()
+                .map(|entry| entry.accumulated_pattern().to_string())
+                .collect(),
+        }
+    }
+}
+
+// ============================================================================
+// NavScaffold
+// ============================================================================
+
+/// Where the sidebar takes its items from.
+enum SidebarSource {
+    None,
+    Routes(String),
+    Items(Vec<NavItem>),
+}
+
+/// Builder for a navigable application shell.
+///
+/// Configure it fluently, then call [`build`](Self::build) to get the root
+/// [`NavShell`] entity, which owns the content [`RouterOutlet`].
+#[must_use]
+pub struct NavScaffold {
+    title: SharedString,
+    sidebar_source: SidebarSource,
+    debug_panel_enabled: bool,
+    header_fn: Option<HeaderFn>,
+    sidebar_fn: Option<SidebarFn>,
+    debug_panel_fn: Option<DebugPanelFn>,
+}
+
+impl Default for NavScaffold {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NavScaffold {
+    /// Create a scaffold with no title, no sidebar and no debug panel.
+    pub fn new() -> Self {
+        Self {
+            title: SharedString::default(),
+            sidebar_source: SidebarSource::None,
+            debug_panel_enabled: false,
+            header_fn: None,
+            sidebar_fn: None,
+            debug_panel_fn: None,
+        }
+    }
+
+    /// Set the title shown in the header.
+    pub fn title(mut self, title: impl Into<SharedString>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Fill the sidebar from the routes below `base`.
+    ///
+    /// The model is rebuilt from the registered routes on every render (see
+    /// [`SidebarModel::from_routes`]), so routes added later show up too.
+    pub fn sidebar_from_routes(mut self, base: impl Into<String>) -> Self {
+        self.sidebar_source = SidebarSource::Routes(base.into());
+        self
+    }
+
+    /// Fill the sidebar with explicit items.
+    pub fn sidebar_items(mut self, items: Vec<NavItem>) -> Self {
+        self.sidebar_source = SidebarSource::Items(items);
+        self
+    }
+
+    /// Show or hide the debug panel.
+    pub const fn with_debug_panel(mut self, enabled: bool) -> Self {
+        self.debug_panel_enabled = enabled;
+        self
+    }
+
+    /// Replace the default header.
+    pub fn header(mut self, render: impl Fn(&str, &str) -> AnyElement + 'static) -> Self {
+        self.header_fn = Some(Box::new(render));
+        self
+    }
+
+    /// Replace the default sidebar.
+    pub fn sidebar(mut self, render: impl Fn(&SidebarModel, &str) -> AnyElement + 'static) -> Self {
+        self.sidebar_fn = Some(Box::new(render));
+        self
+    }
+
+    /// Replace the default debug panel. Implies `with_debug_panel(true)`.
+    pub fn debug_panel(mut self, render: impl Fn(&DebugSnapshot) -> AnyElement + 'static) -> Self {
+        self.debug_panel_fn = Some(Box::new(render));
+        self.debug_panel_enabled = true;
+        self
+    }
+
+    /// Create the root shell entity.
+    pub fn build(self, cx: &mut App) -> Entity<NavShell> {
+        cx.new(|cx| NavShell {
+            scaffold: self,
+            outlet: cx.new(|_| RouterOutlet::new()),
+        })
+    }
+
+    /// Resolve the sidebar model against the registered routes.
+    #[must_use]
+    pub fn sidebar_model(&self, routes: &[Arc<Route>]) -> Option<SidebarModel> {
+        match &self.sidebar_source {
+            SidebarSource::None => None,
+            SidebarSource::Routes(base) => Some(SidebarModel::from_routes(routes, base)),
+            SidebarSource::Items(items) => Some(SidebarModel::new(items.clone())),
+        }
+    }
+
+    fn render_header(&self, current_path: &str) -> AnyElement {
+        self.header_fn.as_ref().map_or_else(
+            || default_header(&self.title, current_path),
+            |render| render(&self.title, current_path),
+        )
+    }
+
+    fn render_sidebar(&self, model: &SidebarModel, current_path: &str) -> AnyElement {
+        self.sidebar_fn.as_ref().map_or_else(
+            || default_sidebar(model, current_path),
+            |render| render(model, current_path),
+        )
+    }
+
+    fn render_debug_panel(&self, snapshot: &DebugSnapshot) -> Option<AnyElement> {
+        if !self.debug_panel_enabled {
+            return None;
+        }
+        Some(
+            self.debug_panel_fn
+                .as_ref()
+                .map_or_else(|| default_debug_panel(snapshot), |render| render(snapshot)),
+        )
+    }
+}
+
+/// Root entity created by [`NavScaffold::build`].
+pub struct NavShell {
+    scaffold: NavScaffold,
+    outlet: Entity<RouterOutlet>,
+}
+
+impl NavShell {
+    /// Return the content outlet.
+    #[must_use]
+    pub const fn outlet(&self) -> &Entity<RouterOutlet> {
+        &self.outlet
+    }
+}
+
+impl Render for NavShell {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let (current_path, model, snapshot) = cx.try_global::<GlobalRouter>().map_or_else(
+            || ("/".to_string(), None, DebugSnapshot::default()),
+            |router| {
+                (
+                    router.current_path().to_string(),
+                    self.scaffold.sidebar_model(router.state().routes()),
+                    DebugSnapshot::capture(router),
+                )
+            },
+        );
+
+        let mut body = div().flex().flex_1().size_full();
+        if let Some(model) = model {
+            body = body.child(self.scaffold.render_sidebar(&model, &current_path));
+        }
+        body = body.child(div().flex_1().p_4().child(self.outlet.clone()));
+        if let Some(panel) = self.scaffold.render_debug_panel(&snapshot) {
+            body = body.child(panel);
+        }
+
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(rgb(0x1e_1e_1e))
+            .text_color(rgb(0xff_ff_ff))
+            .child(self.scaffold.render_header(&current_path))
+            .child(body)
+    }
+}
+
+// ============================================================================
+// Default renderers
+// ============================================================================
+
+fn default_header(title: &str, current_path: &str) -> AnyElement {
+    div()
+        .flex()
+        .justify_between()
+        .items_center()
+        .p_4()
+        .bg(rgb(0x2d_2d_2d))
+        .border_b_1()
+        .border_color(rgb(0x3e_3e_3e))
+        .child(div().text_xl().child(title.to_string()))
+        .child(
+            div()
+                .text_sm()
+                .text_color(rgb(0x88_88_88))
+                .child(current_path.to_string()),
+        )
+        .into_any_element()
+}
+
+fn default_sidebar(model: &SidebarModel, current_path: &str) -> AnyElement {
+    let active = model.active_index(current_path);
+    let mut sidebar = div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .w(px(200.))
+        .p_2()
+        .bg(rgb(0x25_25_25))
+        .border_r_1()
+        .border_color(rgb(0x3e_3e_3e));
+
+    for (index, item) in model.items().iter().enumerate() {
+        let path = item.path.clone();
+        sidebar = sidebar.child(
+            div()
+                .id(ElementId::Name(item.path.clone().into()))
+                .px_3()
+                .py_2()
+                .rounded_md()
+                .cursor_pointer()
+                .when(active == Some(index), |this| this.bg(rgb(0x00_66_cc)))
+                .hover(|style| style.bg(rgb(0x40_40_40)))
+                .on_click(move |_event, _window, cx| {
+                    Navigator::push(cx, path.clone());
+                })
+                .child(item.label.clone()),
+        );
+    }
+
+    sidebar.into_any_element()
+}
+
+fn default_debug_panel(snapshot: &DebugSnapshot) -> AnyElement {
+    let mut history = div().flex().flex_col().gap_1();
+    for (index, path) in snapshot.history.iter().enumerate() {
+        let marker = if index == snapshot.history_index {
+            "▶ "
+        } else {
+            "  "
+        };
+        history = history.child(format!("{marker}{path}"));
+    }
+
+    div()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .w(px(240.))
+        .p_3()
+        .bg(rgb(0x25_25_25))
+        .border_l_1()
+        .border_color(rgb(0x3e_3e_3e))
+        .text_sm()
+        .child(div().text_color(rgb(0x88_88_88)).child("History"))
+        .child(history)
+        .child(div().text_color(rgb(0x88_88_88)).child("Matched"))
+        .children(
+            snapshot
+                .matched
+                .iter()
+                .map(|pattern| div().child(pattern.clone())),
+        )
+        .into_any_element()
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::{
+        DebugSnapshot, GlobalRouter, NavItem, NavScaffold, Navigator, Route, SidebarModel,
+        TITLE_META_KEY,
+    };
+    use gpui::IntoElement;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    fn page(path: &str) -> Route {
+        Route::new(path, |_, _cx, _params| gpui::div().into_any_element())
+    }
+
+    fn routes() -> Vec<Arc<Route>> {
+        vec![
+            Arc::new(page("/").name("Home")),
+            Arc::new(page("/about").meta(TITLE_META_KEY, "About us")),
+            Arc::new(page("/users/:id")),
+            Arc::new(page("/settings").children(vec![
+                Arc::new(page("")),
+                Arc::new(page("profile")),
+                Arc::new(page("security").name("Security")),
+                Arc::new(page(":tab")),
+            ])),
+        ]
+    }
+
+    #[test]
+    fn test_sidebar_from_top_level_routes() {
+        let model = SidebarModel::from_routes(&routes(), "/");
+
+        assert_eq!(
+            model.items(),
+            &[
+                NavItem::new("/", "Home"),
+                NavItem::new("/about", "About us"),
+                NavItem::new("/settings", "Settings"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sidebar_from_child_routes() {
+        let model = SidebarModel::from_routes(&routes(), "/settings/");
+
+        assert_eq!(
+            model.items(),
+            &[
+                NavItem::new("/settings/profile", "Profile"),
+                NavItem::new("/settings/security", "Security"),
+            ]
+        );
+        assert!(SidebarModel::from_routes(&routes(), "/missing").is_empty());
+    }
+
+    #[test]
+    fn test_active_index_prefers_most_specific_item() {
+        let model = SidebarModel::from_routes(&routes(), "/");
+
+        assert_eq!(model.active_index("/"), Some(0));
+        assert_eq!(model.active_index("/settings/profile"), Some(2));
+        assert_eq!(model.active_index("/settingsx"), None);
+        assert_eq!(model.active_index("/users/1"), None);
+    }
+
+    #[test]
+    fn test_sidebar_source_resolution() {
+        assert!(NavScaffold::new().sidebar_model(&routes()).is_none());
+
+        let items = vec![NavItem::new("reports", "Reports")];
+        let model = NavScaffold::new()
+            .sidebar_from_routes("/")
+            .sidebar_items(items)
+            .sidebar_model(&routes())
+            .unwrap();
+        assert_eq!(model.items(), &[NavItem::new("/reports", "Reports")]);
+    }
+
+    #[test]
+    fn test_overrides_receive_scaffold_state() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+
+        let header_calls = Rc::clone(&calls);
+        let sidebar_calls = Rc::clone(&calls);
+        let panel_calls = Rc::clone(&calls);
+        let scaffold = NavScaffold::new()
+            .title("My App")
+            .header(move |title, path| {
+                header_calls
+                    .borrow_mut()
+                    .push(format!("header {title} {path}"));
+                gpui::div().into_any_element()
+            })
+            .sidebar(move |model, path| {
+                sidebar_calls
+                    .borrow_mut()
+                    .push(format!("sidebar {} {path}", model.items().len()));
+                gpui::div().into_any_element()
+            })
+            .debug_panel(move |snapshot| {
+                panel_calls
+                    .borrow_mut()
+                    .push(format!("panel {}", snapshot.current_path));
+                gpui::div().into_any_element()
+            });
+
+        let model = SidebarModel::new(vec![NavItem::new("/", "Home")]);
+        let snapshot = DebugSnapshot {
+            current_path: "/a".to_string(),
+            ..DebugSnapshot::default()
+        };
+        let _ = scaffold.render_header("/a");
+        let _ = scaffold.render_sidebar(&model, "/a");
+        assert!(scaffold.render_debug_panel(&snapshot).is_some());
+
+        assert_eq!(
+            *calls.borrow(),
+            ["header My App /a", "sidebar 1 /a", "panel /a"]
+        );
+    }
+
+    #[test]
+    fn test_debug_panel_hidden_by_default() {
+        let scaffold = NavScaffold::new();
+        assert!(scaffold
+            .render_debug_panel(&DebugSnapshot::default())
+            .is_none());
+    }
+
+    #[gpui::test]
+    fn test_snapshot_captures_history_and_matches(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| {
+            crate::init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/settings").children(vec![Arc::new(page("profile"))]));
+            });
+            Navigator::push(cx, "/settings/profile");
+        });
+
+        let snapshot = cx.read(|cx| DebugSnapshot::capture(cx.global::<GlobalRouter>()));
+
+        assert_eq!(snapshot.current_path, "/settings/profile");
+        assert_eq!(snapshot.history, ["/", "/settings/profile"]);
+        assert_eq!(snapshot.history_index, 1);
+        assert_eq!(snapshot.matched, ["/settings", "/settings/profile"]);
+    }
+}
@@ -0,0 +1,210 @@
+//! Layout slots filled by descendant routes.
+//!
+//! A layout owns regions such as a toolbar or a footer whose content depends
+//! on the page below it. The layout's builder renders a named slot with
+//! [`render_slot`] or [`render_slots`], and descendant routes contribute to
+//! it with [`Route::slot`](crate::Route::slot):
+//!
+//! ```no_run
+//! use gpui_navigator::slots::render_slot;
+//! use gpui_navigator::{render_router_outlet, Route};
+//! use gpui::*;
+//!
+//! Route::new("/", |window, cx, _params| {
+//!     div()
+//!         .children(render_slot(window, cx, "toolbar"))
+//!         .child(render_router_outlet(window, cx, None))
+//!         .into_any_element()
+//! })
+//! .child(
+//!     Route::new("editor", |_, _cx, _params| div().into_any_element())
+//!         .slot("toolbar", |_, _cx, _params| div().child("Save").into_any_element()),
+//! );
+//! ```
+//!
+//! Contributions come from the match stack entries below the layout calling
+//! the slot, found through the outlet depth of that caller, so they appear
+//! and disappear with the routes that declare them. Each runs with its own
+//! entry's params.
+
+use crate::context::GlobalRouter;
+use crate::params::RouteParams;
+use crate::resolve::current_parent_depth;
+use crate::route::RouteBuilder;
+use crate::trace_log;
+use gpui::{AnyElement, App, Window};
+use std::sync::Arc;
+
+/// Contributions to `name` below the calling layout, root to leaf.
+fn contributions(cx: &App, name: &str) -> Vec<(RouteBuilder, RouteParams)> {
+    let Some(router) = cx.try_global::<GlobalRouter>() else {
+        return Vec::new();
+    };
+    // Outside any outlet every entry counts as a descendant
+    let caller = current_parent_depth();
+    router
+        .match_stack()
+        .entries()
+        .iter()
+        .filter(|entry| caller.map_or(true, |depth| entry.depth > depth))
+        .filter_map(|entry| {
+            let builder = entry.route.slots.get(name)?;
+            Some((Arc::clone(builder), entry.params.clone()))
+        })
+        .collect()
+}
+
+/// Render the leaf-most contribution to the slot `name`.
+///
+/// Call from a layout's builder. Returns `None` when no descendant in the
+/// match stack contributes.
+pub fn render_slot(window: &mut Window, cx: &mut App, name: &str) -> Option<AnyElement> {
+    let (builder, params) = contributions(cx, name).pop()?;
+    trace_log!("Rendering slot '{}'", name);
+    Some(builder(window, cx, &params))
+}
+
+/// Render every contribution to the slot `name`, root to leaf.
+///
+/// Call from a layout's builder. Returns an empty `Vec` when no descendant
+/// in the match stack contributes.
+pub fn render_slots(window: &mut Window, cx: &mut App, name: &str) -> Vec<AnyElement> {
+    let contributions = contributions(cx, name);
+    trace_log!(
+        "Rendering {} contributions to slot '{}'",
+        contributions.len(),
+        name
+    );
+    contributions
+        .into_iter()
+        .map(|(builder, params)| builder(window, cx, &params))
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::test_support::page;
+    use crate::{init_router, router_view, Navigator, Route};
+    use gpui::{div, Context, IntoElement, Render, TestAppContext};
+    use std::sync::Mutex;
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    struct Root;
+
+    impl Render for Root {
+        fn render(&mut self, _: &mut Window, _: &mut Context<'_, Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    /// A contribution that logs `label` and the `file` param.
+    fn contribution(
+        log: &Log,
+        label: &'static str,
+    ) -> impl Fn(&mut Window, &mut App, &RouteParams) -> AnyElement {
+        let log = Arc::clone(log);
+        move |_, _, params| {
+            let file = params.get("file").cloned().unwrap_or_default();
+            log.lock().unwrap().push(format!("{label} {file}"));
+            div().into_any_element()
+        }
+    }
+
+    /// Root layout rendering the toolbar slot both ways, above
+    /// `/editor/:file` (with a `preview` child) and `/checkout`.
+    fn setup(cx: &mut TestAppContext) -> Log {
+        let log = Log::default();
+        let layout_log = Arc::clone(&log);
+        let preview = page("preview").slot("toolbar", contribution(&log, "preview"));
+        let editor = page("editor/:file")
+            .slot("toolbar", contribution(&log, "editor"))
+            .slot("footer", contribution(&log, "footer"))
+            .children(vec![preview.into()]);
+        let layout = Route::new("/", move |window, cx, _| {
+            layout_log.lock().unwrap().push("one".into());
+            let one = render_slot(window, cx, "toolbar");
+            layout_log.lock().unwrap().push("all".into());
+            let all = render_slots(window, cx, "toolbar");
+            layout_log
+                .lock()
+                .unwrap()
+                .push(format!("= {} {}", one.is_some(), all.len()));
+            div().into_any_element()
+        })
+        .children(vec![editor.into(), page("checkout").into()]);
+        cx.update(|cx| init_router(cx, |router| router.add_route(layout)));
+        log
+    }
+
+    fn render(cx: &mut TestAppContext, log: &Log) -> Vec<String> {
+        log.lock().unwrap().clear();
+        let window = cx.add_window(|_, _| Root);
+        window
+            .update(cx, |_, window, cx| {
+                let _ = router_view(window, cx);
+            })
+            .unwrap();
+        std::mem::take(&mut *log.lock().unwrap())
+    }
+
+    #[gpui::test]
+    fn test_leaf_most_wins_and_collect_all_is_root_to_leaf(cx: &mut TestAppContext) {
+        let log = setup(cx);
+        cx.update(|cx| Navigator::push(cx, "/editor/a/preview"));
+        assert_eq!(
+            render(cx, &log),
+            [
+                "one",
+                "preview a",
+                "all",
+                "editor a",
+                "preview a",
+                "= true 2"
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_contribution_receives_its_params(cx: &mut TestAppContext) {
+        let log = setup(cx);
+        cx.update(|cx| Navigator::push(cx, "/editor/notes.md"));
+        assert_eq!(
+            render(cx, &log),
+            [
+                "one",
+                "editor notes.md",
+                "all",
+                "editor notes.md",
+                "= true 1"
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_no_contribution_renders_nothing(cx: &mut TestAppContext) {
+        let log = setup(cx);
+        cx.update(|cx| Navigator::push(cx, "/checkout"));
+        assert_eq!(render(cx, &log), ["one", "all", "= false 0"]);
+    }
+
+    #[gpui::test]
+    fn test_contributions_follow_navigation(cx: &mut TestAppContext) {
+        let log = setup(cx);
+        cx.update(|cx| Navigator::push(cx, "/editor/a/preview"));
+        assert_eq!(render(cx, &log).len(), 6);
+
+        // The contributing child left the stack
+        cx.update(|cx| Navigator::push(cx, "/checkout"));
+        assert_eq!(render(cx, &log), ["one", "all", "= false 0"]);
+
+        // A different file contributes again, with its own params
+        cx.update(|cx| Navigator::push(cx, "/editor/b"));
+        assert_eq!(
+            render(cx, &log),
+            ["one", "editor b", "all", "editor b", "= true 1"]
+        );
+    }
+}
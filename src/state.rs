@@ -1,7 +1,7 @@
 //! Router state management.
 //!
 //! This module contains [`RouterState`] — the core data structure that holds
-//! navigation history, registered routes, and current match cache.
+//! navigation history, registered routes, and the resolved [`MatchStack`].
 //!
 //! [`RouterState`] is the low-level engine behind navigation. Higher-level APIs
 //! like [`GlobalRouter`](crate::context::GlobalRouter) and
@@ -14,25 +14,44 @@
 //! while [`back`](RouterState::back) / [`forward`](RouterState::forward) move
 //! the cursor without modifying the stack.
 //!
+//! Every mutation returns a [`HistoryMutation`] captured as it happens: the
+//! entry it left, the new cursor, the forward entries a push dropped and the
+//! [`RouteChangeEvent`]. Going back or forward past either end is a
+//! [`HistoryError::AtBoundary`], so there is no need to peek first.
+//!
 //! # Navigation cancellation (T009)
 //!
 //! An atomic navigation ID counter allows async guard checks to detect that a
 //! newer navigation has started and the current one should be discarded.
 //! Call [`start_navigation`](RouterState::start_navigation) to obtain an ID,
 //! then periodically check [`is_navigation_current`](RouterState::is_navigation_current).
-
+//!
+//! # What matched
+//!
+//! The [`MatchStack`] held here is the single answer to "what matched". The
+//! [`GlobalRouter`](crate::context::GlobalRouter) resolves it after every
+//! navigation, and [`current_route`](RouterState::current_route) and
+//! [`current_match_immutable`](RouterState::current_match_immutable) both
+//! read its leaf, so they agree with what the outlets render. A standalone
+//! state re-resolves its own routes after every history mutation and
+//! [`restore`](RouterState::restore); call [`resolve`](RouterState::resolve)
+//! after changing the routes.
+
+use crate::error::HistoryError;
 use crate::history::{History, HistoryEntry, HistoryState};
+use crate::pattern::Pattern;
+use crate::resolve::{resolve_match_stack, MatchEntry, MatchStack};
 use crate::route::Route;
-use crate::{debug_log, trace_log, RouteChangeEvent, RouteMatch, RouteParams};
-use std::collections::HashMap;
+use crate::{debug_log, trace_log, NavigationDirection, RouteChangeEvent, RouteMatch, RouteParams};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
-/// Core navigation state that tracks history, registered routes, and match cache.
+/// Core navigation state that tracks history, registered routes, and the match stack.
 ///
 /// This struct owns the navigation history stack and provides methods for
-/// pushing, replacing, and traversing entries. Route matching results are
-/// cached in a [`HashMap`] to avoid repeated tree walks within a single path.
+/// pushing, replacing, and traversing entries. Every history mutation
+/// re-resolves the [`MatchStack`] from the registered routes, so
+/// [`current_route`](Self::current_route) always follows the current path.
 ///
 /// # Examples
 ///
@@ -45,7 +64,7 @@ use std::sync::Arc;
 /// state.push("/users".to_string());
 /// assert_eq!(state.current_path(), "/users");
 ///
-/// state.back();
+/// state.back().unwrap();
 /// assert_eq!(state.current_path(), "/");
 /// ```
 #[derive(Debug)]
@@ -54,8 +73,8 @@ pub struct RouterState {
     history: History,
     /// Registered routes
     routes: Vec<Arc<Route>>,
-    /// Route cache
-    cache: HashMap<String, RouteMatch>,
+    /// Match stack resolved for the current path
+    match_stack: MatchStack,
     /// Current route parameters (for parameter inheritance in nested routing)
     current_params: RouteParams,
     /// Navigation ID counter for cancellation tracking (T009)
@@ -70,7 +89,7 @@ impl RouterState {
         Self {
             history: History::new("/".to_string()),
             routes: Vec::new(),
-            cache: HashMap::new(),
+            match_stack: MatchStack::new(),
             current_params: RouteParams::new(),
             navigation_id: Arc::new(AtomicUsize::new(0)),
         }
@@ -100,14 +119,71 @@ impl RouterState {
         self.navigation_id() == nav_id
     }
 
-    /// Register a route and invalidate the match cache.
+    /// Register a route.
     ///
-    /// Routes are stored in registration order. The first route whose pattern
-    /// matches the current path wins during [`current_match`](Self::current_match).
+    /// Routes are stored in registration order, and the first route whose
+    /// pattern matches wins during resolution. A route with the identical
+    /// pattern of a registered one replaces it. The match stack is not
+    /// re-resolved; call [`resolve`](Self::resolve) once routes are in place.
     pub fn add_route(&mut self, route: Route) {
-        trace_log!("RouterState: registered route '{}'", route.config.path);
-        self.routes.push(Arc::new(route));
-        self.cache.clear();
+        self.replace_route(route);
+    }
+
+    /// Register a route, replacing the route with the identical pattern if
+    /// there is one.
+    ///
+    /// The replacement takes the earlier route's place in the registration
+    /// order. Returns the replaced route.
+    pub(crate) fn replace_route(&mut self, route: Route) -> Option<Arc<Route>> {
+        let route = Arc::new(route);
+        let Some(slot) = self.registered(route.config.pattern()) else {
+            trace_log!("RouterState: registered route '{}'", route.config.path);
+            self.routes.push(route);
+            return None;
+        };
+        trace_log!("RouterState: replaced route '{}'", route.config.path);
+        Some(std::mem::replace(&mut self.routes[slot], route))
+    }
+
+    /// Unregister the route with the identical pattern of `path`, returning it.
+    pub(crate) fn remove_route(&mut self, path: &str) -> Option<Arc<Route>> {
+        let pattern = Pattern::parse(path).ok()?;
+        let slot = self.registered(&pattern)?;
+        trace_log!("RouterState: removed route '{}'", path);
+        Some(self.routes.remove(slot))
+    }
+
+    /// Return the index of the registered route with the pattern `pattern`.
+    pub(crate) fn registered(&self, pattern: &Pattern) -> Option<usize> {
+        self.routes
+            .iter()
+            .position(|route| route.config.pattern() == pattern)
+    }
+
+    /// Resolve the match stack for the current path from the registered routes.
+    pub fn resolve(&mut self) {
+        self.match_stack = resolve_match_stack(&self.routes, self.history.current_path());
+    }
+
+    /// Return the match stack resolved for the current path.
+    #[must_use]
+    pub const fn match_stack(&self) -> &MatchStack {
+        &self.match_stack
+    }
+
+    /// Replace the match stack, for owners that resolve paths themselves.
+    pub(crate) fn set_match_stack(&mut self, stack: MatchStack) {
+        self.match_stack = stack;
+    }
+
+    pub(crate) fn match_stack_mut(&mut self) -> &mut MatchStack {
+        &mut self.match_stack
+    }
+
+    /// Return the deepest entry of the match stack.
+    #[must_use]
+    pub fn leaf(&self) -> Option<&MatchEntry> {
+        self.match_stack.leaf()
     }
 
     /// Return the current path in the history stack.
@@ -133,167 +209,188 @@ impl RouterState {
         self.current_params = params;
     }
 
-    /// Find the [`RouteMatch`] for the current path, caching the result.
-    ///
-    /// On a cache miss the registered routes are iterated in order and the
-    /// first match is stored. Subsequent calls with the same path return
-    /// the cached value in O(1).
+    /// Deprecated caching lookup, now the same as
+    /// [`current_match_immutable`](Self::current_match_immutable).
+    #[deprecated(
+        since = "0.2.0",
+        note = "Use `leaf()` or `match_stack()`; the match is derived from the match stack"
+    )]
     pub fn current_match(&mut self) -> Option<RouteMatch> {
-        let path = self.current_path();
-
-        // Check cache first
-        if let Some(cached) = self.cache.get(path) {
-            return Some(cached.clone());
-        }
-
-        // Find matching route
-        for route in &self.routes {
-            if let Some(route_match) = route.matches(path) {
-                self.cache.insert(path.to_string(), route_match.clone());
-                return Some(route_match);
-            }
-        }
-
-        None
+        self.current_match_immutable()
     }
 
-    /// Get current route match without caching (immutable)
+    /// Return the [`RouteMatch`] of the match stack's leaf.
     ///
-    /// Use this when you need to access the current route from a non-mutable context,
-    /// such as in a GPUI Render implementation.
+    /// The pattern is the leaf's accumulated pattern and the params are its
+    /// accumulated params.
     #[must_use]
     pub fn current_match_immutable(&self) -> Option<RouteMatch> {
-        let path = self.current_path();
-
-        // Check cache first
-        if let Some(cached) = self.cache.get(path) {
-            return Some(cached.clone());
-        }
-
-        // Find matching route without caching
-        for route in &self.routes {
-            if let Some(route_match) = route.matches(path) {
-                return Some(route_match);
-            }
-        }
-
-        None
+        let leaf = self.match_stack.leaf()?;
+        let mut route_match = RouteMatch::new(self.current_path().to_string())
+            .with_pattern(leaf.accumulated_pattern());
+        route_match.params.clone_from(leaf.params.all());
+        Some(route_match)
     }
 
-    /// Get the first top-level Route that matches the current path.
+    /// Return the route of the match stack's leaf.
     ///
-    /// With `MatchStack` architecture, rendering uses `GlobalRouter::match_stack()`.
-    /// This method is kept for compatibility — it returns the first registered
-    /// route whose pattern matches the current path (exact or prefix).
+    /// For nested routes this is the deepest matched route (e.g. the `:id`
+    /// child of `/users`), not the top-level route containing it.
     #[must_use]
     pub fn current_route(&self) -> Option<&Arc<Route>> {
-        let path = self.current_path();
-        for route in &self.routes {
-            if route.matches(path).is_some() {
-                return Some(route);
-            }
-            // Also check if path is under this route (prefix match for nested routes)
-            let route_path = route.config.path.trim_matches('/');
-            let path_trimmed = path.trim_matches('/');
-            if !route_path.is_empty()
-                && path_trimmed.starts_with(route_path)
-                && (path_trimmed.len() == route_path.len()
-                    || path_trimmed[route_path.len()..].starts_with('/'))
-            {
-                return Some(route);
-            }
-            // Root route matches everything if it has children
-            if route_path.is_empty() && !route.children.is_empty() {
-                return Some(route);
-            }
-        }
-        None
+        self.match_stack.leaf().map(|leaf| &leaf.route)
     }
 
     /// Push a new path onto the history stack.
     ///
     /// Any forward history (entries after the current cursor) is truncated
-    /// before appending, mirroring browser `pushState` semantics.
-    ///
-    /// Returns a [`RouteChangeEvent`] describing the transition.
-    pub fn push(&mut self, path: String) -> RouteChangeEvent {
+    /// before appending, mirroring browser `pushState` semantics; the
+    /// returned [`HistoryMutation`] lists the dropped entries.
+    pub fn push(&mut self, path: String) -> HistoryMutation {
+        let mutation = self.push_unresolved(path);
+        self.resolve();
+        mutation
+    }
+
+    /// [`push`](Self::push) without re-resolving the match stack, for owners
+    /// that resolve paths themselves.
+    pub(crate) fn push_unresolved(&mut self, path: String) -> HistoryMutation {
+        let (previous, truncated) = self.before_push();
         let event = self.history.push(path);
         debug_log!(
-            "History push: '{}' → '{}' (stack size: {})",
-            event.from.as_deref().unwrap_or(""),
+            "History push: '{}' → '{}' (stack size: {}, {} forward entries dropped)",
+            previous.path,
             event.to,
-            self.history.len()
+            self.history.len(),
+            truncated.len()
         );
-        event
+        self.mutation(previous, truncated, event)
     }
 
     /// Push a new path with associated [`HistoryState`] data.
     ///
     /// Allows attaching arbitrary key-value state (scroll position, form data, etc.)
     /// to the history entry.
-    pub fn push_with_state(&mut self, path: String, state: HistoryState) -> RouteChangeEvent {
+    pub fn push_with_state(&mut self, path: String, state: HistoryState) -> HistoryMutation {
+        let (previous, truncated) = self.before_push();
         let event = self.history.push_with_state(path, state);
         debug_log!(
             "History push (with state): '{}' → '{}'",
-            event.from.as_deref().unwrap_or(""),
+            previous.path,
             event.to,
         );
-        event
+        self.resolve();
+        self.mutation(previous, truncated, event)
     }
 
     /// Replace the current history entry in-place without adding a new one.
     ///
     /// Useful for redirects where the intermediate path should not appear in
     /// the back-button history.
-    pub fn replace(&mut self, path: String) -> RouteChangeEvent {
+    pub fn replace(&mut self, path: String) -> HistoryMutation {
+        let mutation = self.replace_unresolved(path);
+        self.resolve();
+        mutation
+    }
+
+    /// [`replace`](Self::replace) without re-resolving the match stack.
+    pub(crate) fn replace_unresolved(&mut self, path: String) -> HistoryMutation {
+        let previous = self.history.current_entry().clone();
         let event = self.history.replace(path);
-        debug_log!(
-            "History replace: '{}' → '{}'",
-            event.from.as_deref().unwrap_or(""),
-            event.to,
-        );
-        event
+        debug_log!("History replace: '{}' → '{}'", previous.path, event.to);
+        self.mutation(previous, Vec::new(), event)
     }
 
     /// Replace the current history entry with associated [`HistoryState`] data.
-    pub fn replace_with_state(&mut self, path: String, state: HistoryState) -> RouteChangeEvent {
+    pub fn replace_with_state(&mut self, path: String, state: HistoryState) -> HistoryMutation {
+        let previous = self.history.current_entry().clone();
         let event = self.history.replace_with_state(path, state);
         debug_log!(
             "History replace (with state): '{}' → '{}'",
-            event.from.as_deref().unwrap_or(""),
+            previous.path,
             event.to,
         );
-        event
+        self.resolve();
+        self.mutation(previous, Vec::new(), event)
     }
 
     /// Move the cursor one step back in the history stack.
     ///
-    /// Returns `None` if already at the oldest entry.
-    pub fn back(&mut self) -> Option<RouteChangeEvent> {
-        let event = self.history.back()?;
+    /// # Errors
+    ///
+    /// Returns [`HistoryError::AtBoundary`] if already at the oldest entry.
+    pub fn back(&mut self) -> Result<HistoryMutation, HistoryError> {
+        let mutation = self.back_unresolved()?;
+        self.resolve();
+        Ok(mutation)
+    }
+
+    /// [`back`](Self::back) without re-resolving the match stack.
+    pub(crate) fn back_unresolved(&mut self) -> Result<HistoryMutation, HistoryError> {
+        let previous = self.history.current_entry().clone();
+        let event = self.history.back().ok_or(HistoryError::AtBoundary {
+            direction: NavigationDirection::Back,
+        })?;
         debug_log!(
             "History back: '{}' → '{}' (position {}/{})",
-            event.from.as_deref().unwrap_or(""),
+            previous.path,
             event.to,
             self.history.current_index(),
             self.history.len()
         );
-        Some(event)
+        Ok(self.mutation(previous, Vec::new(), event))
     }
 
     /// Move the cursor one step forward in the history stack.
     ///
-    /// Returns `None` if already at the newest entry.
-    pub fn forward(&mut self) -> Option<RouteChangeEvent> {
-        let event = self.history.forward()?;
+    /// # Errors
+    ///
+    /// Returns [`HistoryError::AtBoundary`] if already at the newest entry.
+    pub fn forward(&mut self) -> Result<HistoryMutation, HistoryError> {
+        let mutation = self.forward_unresolved()?;
+        self.resolve();
+        Ok(mutation)
+    }
+
+    /// [`forward`](Self::forward) without re-resolving the match stack.
+    pub(crate) fn forward_unresolved(&mut self) -> Result<HistoryMutation, HistoryError> {
+        let previous = self.history.current_entry().clone();
+        let event = self.history.forward().ok_or(HistoryError::AtBoundary {
+            direction: NavigationDirection::Forward,
+        })?;
         debug_log!(
             "History forward: '{}' → '{}' (position {}/{})",
-            event.from.as_deref().unwrap_or(""),
+            previous.path,
             event.to,
             self.history.current_index(),
             self.history.len()
         );
-        Some(event)
+        Ok(self.mutation(previous, Vec::new(), event))
+    }
+
+    /// The current entry and the paths of the forward entries, which a
+    /// push is about to drop.
+    fn before_push(&self) -> (HistoryEntry, Vec<String>) {
+        let forward = self.history.entries()[self.history.current_index() + 1..]
+            .iter()
+            .map(|entry| entry.path.clone())
+            .collect();
+        (self.history.current_entry().clone(), forward)
+    }
+
+    /// Describe a mutation that just left `previous` behind.
+    const fn mutation(
+        &self,
+        previous: HistoryEntry,
+        truncated: Vec<String>,
+        event: RouteChangeEvent,
+    ) -> HistoryMutation {
+        HistoryMutation {
+            previous,
+            index: self.history.current_index(),
+            truncated,
+            event,
+        }
     }
 
     /// Return `true` if [`back`](Self::back) would succeed.
@@ -326,10 +423,124 @@ impl RouterState {
         self.history.current_entry()
     }
 
-    /// Reset the history stack to a single `"/"` entry, clearing the match cache.
+    /// Return a mutable reference to the current [`HistoryEntry`].
+    pub fn current_entry_mut(&mut self) -> &mut HistoryEntry {
+        self.history.current_entry_mut()
+    }
+
+    /// Set the state of the current history entry, keeping its path and payloads.
+    ///
+    /// Pure history data: the match stack is not re-resolved and no event
+    /// is produced, so attaching state to a just-committed navigation is
+    /// invisible to the pipeline.
+    pub(crate) fn attach_state_to_current(&mut self, state: HistoryState) {
+        let entry = self.history.current_entry_mut();
+        trace_log!("Attaching state to history entry '{}'", entry.path);
+        entry.state = Some(state);
+    }
+
+    /// Return the underlying navigation [`History`] (read-only).
+    #[must_use]
+    pub const fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Capture the history stack and cursor.
+    ///
+    /// The snapshot is a clone of the history; routes and the match stack
+    /// are not part of it.
+    #[must_use]
+    pub fn snapshot(&self) -> RouterSnapshot {
+        RouterSnapshot {
+            history: self.history.clone(),
+        }
+    }
+
+    /// Put back the history captured by [`snapshot`](Self::snapshot) and
+    /// re-resolve the match stack.
+    pub fn restore(&mut self, snapshot: RouterSnapshot) {
+        self.restore_unresolved(snapshot);
+        self.resolve();
+    }
+
+    /// [`restore`](Self::restore) without re-resolving the match stack.
+    pub(crate) fn restore_unresolved(&mut self, snapshot: RouterSnapshot) {
+        debug_log!(
+            "History restored: '{}' (stack size: {})",
+            snapshot.current_path(),
+            snapshot.history.len()
+        );
+        self.history = snapshot.history;
+    }
+
+    /// Exchange history and match stack with `other`, keeping the routes.
+    pub(crate) fn swap_navigation(&mut self, other: &mut Self) {
+        std::mem::swap(&mut self.history, &mut other.history);
+        std::mem::swap(&mut self.match_stack, &mut other.match_stack);
+        std::mem::swap(&mut self.current_params, &mut other.current_params);
+    }
+
+    /// Reset the history stack to a single `"/"` entry and resolve it.
     pub fn clear(&mut self) {
         self.history.clear("/".to_string());
-        self.cache.clear();
+        self.resolve();
+    }
+}
+
+/// What a [`RouterState`] history mutation did.
+///
+/// Captured by the mutation itself, so it always agrees with the history
+/// it leaves behind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryMutation {
+    /// The entry that was current before, without its payloads
+    pub previous: HistoryEntry,
+    /// Cursor position of the new current entry
+    pub index: usize,
+    /// Paths of the forward entries a push dropped, oldest first
+    pub truncated: Vec<String>,
+    /// The route change
+    pub event: RouteChangeEvent,
+}
+
+impl HistoryMutation {
+    /// Path of the new current entry.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.event.to
+    }
+
+    /// Return `true` if the mutation dropped forward entries.
+    #[must_use]
+    pub fn truncated_forward(&self) -> bool {
+        !self.truncated.is_empty()
+    }
+
+    /// Number of forward entries the mutation dropped.
+    #[must_use]
+    pub fn truncated_count(&self) -> usize {
+        self.truncated.len()
+    }
+}
+
+/// History stack, cursor and current path captured by
+/// [`RouterState::snapshot`] or [`Navigator::snapshot`](crate::Navigator::snapshot).
+#[derive(Debug, Clone)]
+pub struct RouterSnapshot {
+    history: History,
+}
+
+impl RouterSnapshot {
+    /// Path that was current when the snapshot was taken.
+    #[must_use]
+    pub fn current_path(&self) -> &str {
+        self.history.current_path()
+    }
+
+    /// The captured history.
+    #[must_use]
+    pub const fn history(&self) -> &History {
+        &self.history
     }
 }
 
@@ -344,7 +555,7 @@ impl Clone for RouterState {
         Self {
             history: self.history.clone(),
             routes: self.routes.clone(),
-            cache: self.cache.clone(),
+            match_stack: self.match_stack.clone(),
             current_params: self.current_params.clone(),
             // Clone Arc, not the AtomicUsize value - share navigation_id across clones
             navigation_id: Arc::clone(&self.navigation_id),
@@ -355,6 +566,7 @@ impl Clone for RouterState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::page;
 
     #[test]
     fn test_navigation() {
@@ -368,10 +580,10 @@ mod tests {
         state.push("/users/123".to_string());
         assert_eq!(state.current_path(), "/users/123");
 
-        state.back();
+        state.back().unwrap();
         assert_eq!(state.current_path(), "/users");
 
-        state.forward();
+        state.forward().unwrap();
         assert_eq!(state.current_path(), "/users/123");
     }
 
@@ -385,4 +597,158 @@ mod tests {
         assert_eq!(state.current_path(), "/posts");
         assert_eq!(state.history.len(), 2);
     }
+
+    #[test]
+    fn test_push_and_replace_report_mutation() {
+        let mut state = RouterState::new();
+        let pushed = state.push("/users".to_string());
+        assert_eq!(pushed.previous.path, "/");
+        assert_eq!(pushed.index, 1);
+        assert_eq!(pushed.path(), "/users");
+        assert!(!pushed.truncated_forward());
+        assert_eq!(pushed.event.direction, NavigationDirection::Forward);
+
+        let replaced = state.replace("/posts".to_string());
+        assert_eq!(replaced.previous.path, "/users");
+        assert_eq!(replaced.index, 1);
+        assert_eq!(replaced.event.from.as_deref(), Some("/users"));
+        assert_eq!(replaced.event.direction, NavigationDirection::Replace);
+        assert!(!replaced.truncated_forward());
+    }
+
+    #[test]
+    fn test_push_reports_truncated_forward_entries() {
+        let mut state = RouterState::new();
+        state.push("/a".to_string());
+        state.push("/b".to_string());
+        state.push("/c".to_string());
+        state.back().unwrap();
+        state.back().unwrap();
+
+        let pushed = state.push_with_state("/d".to_string(), HistoryState::new());
+        assert_eq!(pushed.previous.path, "/a");
+        assert_eq!(pushed.truncated, ["/b", "/c"]);
+        assert_eq!(pushed.truncated_count(), 2);
+        assert_eq!(pushed.index, 2);
+        assert_eq!(state.history().len(), 3);
+    }
+
+    #[test]
+    fn test_back_and_forward_report_mutation() {
+        let mut state = RouterState::new();
+        state.push("/a".to_string());
+        state.current_entry_mut().set_payload(1_u8);
+
+        let back = state.back().unwrap();
+        assert_eq!(back.previous.path, "/a");
+        // The entry left behind keeps its payload; the copy does not
+        assert!(!back.previous.has_payload::<u8>());
+        assert_eq!(back.index, 0);
+        assert_eq!(back.path(), "/");
+        assert_eq!(back.event.direction, NavigationDirection::Back);
+
+        let forward = state.forward().unwrap();
+        assert_eq!(forward.previous.path, "/");
+        assert_eq!(forward.index, 1);
+        assert_eq!(forward.event.direction, NavigationDirection::Forward);
+        assert!(!forward.truncated_forward());
+        assert!(state.current_entry().has_payload::<u8>());
+    }
+
+    #[test]
+    fn test_back_and_forward_fail_at_boundaries() {
+        let mut state = RouterState::new();
+        assert_eq!(
+            state.back(),
+            Err(HistoryError::AtBoundary {
+                direction: NavigationDirection::Back
+            })
+        );
+        assert_eq!(
+            state.forward().unwrap_err().to_string(),
+            "No history entry to go forward to"
+        );
+        // A failed move leaves the history alone
+        assert_eq!(state.current_path(), "/");
+        assert_eq!(state.history().len(), 1);
+    }
+
+    #[test]
+    fn test_attach_state_keeps_entry() {
+        let mut state = RouterState::new();
+        state.push("/compose".to_string());
+        state.current_entry_mut().set_payload(7_u32);
+
+        let mut data = HistoryState::new();
+        data.set("draft".to_string(), "hello".to_string());
+        state.attach_state_to_current(data.clone());
+
+        assert_eq!(state.current_path(), "/compose");
+        assert_eq!(state.history.len(), 2);
+        assert_eq!(state.current_entry().state, Some(data));
+        // Unlike `replace_with_state`, the entry itself survives
+        assert_eq!(state.current_entry_mut().take_payload::<u32>(), Some(7));
+    }
+
+    #[test]
+    fn test_restore_puts_back_history_and_cursor() {
+        let mut state = RouterState::new();
+        state.push("/a".to_string());
+        state.push("/b".to_string());
+        state.back().unwrap();
+        let snapshot = state.snapshot();
+
+        state.push("/c".to_string());
+        assert!(!state.can_go_forward());
+
+        state.restore(snapshot.clone());
+        assert_eq!(state.current_path(), "/a");
+        assert_eq!(state.history().len(), 3);
+        assert_eq!(state.peek_forward_path(), Some("/b"));
+        assert_eq!(snapshot.history().current_index(), 1);
+    }
+
+    #[test]
+    fn test_current_route_follows_resolved_stack() {
+        let mut state = RouterState::new();
+        state.add_route(page("/settings").child(page("profile")));
+
+        state.push("/settings/profile".to_string());
+        assert_eq!(state.current_route().unwrap().config.path, "profile");
+        let current = state.current_match_immutable().unwrap();
+        assert_eq!(current.pattern, "/settings/profile");
+
+        state.replace("/settings".to_string());
+        assert_eq!(state.current_route().unwrap().config.path, "/settings");
+        state.back().unwrap();
+        assert!(state.current_route().is_none());
+        state.forward().unwrap();
+        assert_eq!(state.current_route().unwrap().config.path, "/settings");
+
+        let snapshot = state.snapshot();
+        state.push("/missing".to_string());
+        assert!(state.current_route().is_none());
+        state.restore(snapshot);
+        assert_eq!(
+            state.current_match_immutable().unwrap().pattern,
+            "/settings"
+        );
+    }
+
+    #[test]
+    fn test_identical_pattern_replaces_in_place() {
+        let mut state = RouterState::new();
+        state.add_route(page("/a"));
+        state.add_route(page("/b"));
+        assert!(state.replace_route(page("/a/:id")).is_none());
+
+        let old = state.replace_route(page("a/").child(page("x"))).unwrap();
+        assert!(old.children.is_empty());
+        let paths: Vec<_> = state
+            .routes()
+            .iter()
+            .map(|r| r.config.path.as_str())
+            .collect();
+        assert_eq!(paths, ["a/", "/b", "/a/:id"]);
+    }
 }
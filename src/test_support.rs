@@ -0,0 +1,16 @@
+//! Helpers shared by the crate's unit tests.
+
+use crate::{GlobalRouter, NavigationResult, Route};
+use gpui::{BorrowAppContext, IntoElement, TestAppContext};
+
+/// A route at `path` rendering an empty `div`.
+pub fn page(path: &str) -> Route {
+    Route::view(path, || gpui::div().into_any_element())
+}
+
+/// Push `path` and return the navigation's result.
+pub fn push_result(cx: &mut TestAppContext, path: &str) -> NavigationResult {
+    cx.update(|cx| {
+        cx.update_global::<GlobalRouter, _>(|router, cx| router.push(path.to_string(), cx))
+    })
+}
@@ -0,0 +1,404 @@
+//! Route timers for kiosk and wizard flows.
+//!
+//! [`Route::idle_timeout`](crate::Route::idle_timeout) navigates away from a
+//! route after a period without user activity. It is armed while the route
+//! is the leaf of the match stack, so excluding a subtree (say `/admin/**`)
+//! only means not configuring a timeout there. The crate does not observe
+//! input itself: call [`Navigator::notify_activity`](crate::Navigator::notify_activity)
+//! from a top-level input listener, or wrap the root element with
+//! [`track_activity`].
+//!
+//! [`Route::auto_advance`](crate::Route::auto_advance) navigates once after a
+//! fixed delay while the route stays in the match stack, e.g. to move a
+//! wizard to its next step.
+//!
+//! Both navigate through the full pipeline, and both are cancelled when
+//! their route leaves. A timer fires at most once per activation: if the
+//! navigation does not complete (a guard blocks the target, say), the timer
+//! is disarmed with a warning instead of trying again.
+
+use crate::context::GlobalRouter;
+use crate::resolve::MatchStack;
+use crate::route::Route;
+use crate::{debug_log, info_log, warn_log, Navigator};
+use gpui::{App, BorrowAppContext, InteractiveElement};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Where [`Route::idle_timeout`](crate::Route::idle_timeout) navigates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IdleTarget {
+    /// Push this path.
+    Path(String),
+    /// Go back in history.
+    Back,
+}
+
+// ============================================================================
+// Armed timers
+// ============================================================================
+
+/// Idle timeout of the current leaf.
+#[derive(Clone)]
+struct IdleTimer {
+    token: u64,
+    depth: usize,
+    route: Arc<Route>,
+    timeout: Duration,
+    target: IdleTarget,
+    last_activity: Instant,
+    /// Cleared once the timeout fired, so it never fires twice.
+    armed: bool,
+}
+
+/// Pending auto-advance of a route in the match stack.
+#[derive(Clone)]
+struct Advance {
+    token: u64,
+    depth: usize,
+    route: Arc<Route>,
+}
+
+/// Timers of the routes in the match stack.
+#[derive(Clone, Default)]
+pub(crate) struct RouteTimers {
+    idle: Option<IdleTimer>,
+    advances: Vec<Advance>,
+    next_token: u64,
+}
+
+impl RouteTimers {
+    /// Bring the armed timers in line with `stack` after a navigation.
+    ///
+    /// The idle timeout follows the leaf; auto-advances stay pending while
+    /// their route stays at the same depth.
+    pub(crate) fn sync(&mut self, stack: &MatchStack, cx: &App) {
+        let leaf = stack.leaf();
+        let same_leaf = self.idle.as_ref().is_some_and(|idle| {
+            leaf.is_some_and(|leaf| {
+                leaf.depth == idle.depth && Arc::ptr_eq(&leaf.route, &idle.route)
+            })
+        });
+        if !same_leaf {
+            if let Some(idle) = self.idle.take() {
+                debug_log!(
+                    "Route '{}' is no longer the leaf, cancelling idle timeout",
+                    idle.route.config.path
+                );
+            }
+            if let Some((leaf, (timeout, target))) =
+                leaf.and_then(|leaf| Some((leaf, leaf.route.idle_timeout.as_ref()?)))
+            {
+                let token = self.token();
+                debug_log!(
+                    "Arming {:?} idle timeout of route '{}'",
+                    timeout,
+                    leaf.route.config.path
+                );
+                spawn_idle(cx, token, *timeout);
+                self.idle = Some(IdleTimer {
+                    token,
+                    depth: leaf.depth,
+                    route: Arc::clone(&leaf.route),
+                    timeout: *timeout,
+                    target: target.clone(),
+                    last_activity: cx.background_executor().now(),
+                    armed: true,
+                });
+            }
+        }
+
+        self.advances.retain(|advance| {
+            let stayed = stack
+                .at_depth(advance.depth)
+                .is_some_and(|entry| Arc::ptr_eq(&entry.route, &advance.route));
+            if !stayed {
+                debug_log!(
+                    "Route '{}' left, cancelling auto-advance",
+                    advance.route.config.path
+                );
+            }
+            stayed
+        });
+        for entry in stack.entries() {
+            let Some((after, _)) = &entry.route.auto_advance else {
+                continue;
+            };
+            let pending = self.advances.iter().any(|advance| {
+                advance.depth == entry.depth && Arc::ptr_eq(&advance.route, &entry.route)
+            });
+            if pending {
+                continue;
+            }
+            let token = self.token();
+            spawn_advance(cx, token, *after);
+            self.advances.push(Advance {
+                token,
+                depth: entry.depth,
+                route: Arc::clone(&entry.route),
+            });
+        }
+    }
+
+    /// Restart the idle countdown from `now`.
+    pub(crate) fn notify_activity(&mut self, now: Instant) {
+        if let Some(idle) = &mut self.idle {
+            idle.last_activity = now;
+        }
+    }
+
+    fn token(&mut self) -> u64 {
+        let token = self.next_token;
+        self.next_token += 1;
+        token
+    }
+
+    /// When the idle timer `token` fires, if it is still armed.
+    fn idle_deadline(&self, token: u64) -> Option<Instant> {
+        let idle = self.idle.as_ref().filter(|idle| idle.token == token)?;
+        idle.armed.then(|| idle.last_activity + idle.timeout)
+    }
+
+    /// Disarm the idle timer `token`, returning its target.
+    fn fire_idle(&mut self, token: u64) -> Option<IdleTarget> {
+        let idle = self.idle.as_mut().filter(|idle| idle.token == token)?;
+        if !idle.armed {
+            return None;
+        }
+        idle.armed = false;
+        Some(idle.target.clone())
+    }
+
+    /// Remove the auto-advance `token`, returning its path.
+    fn fire_advance(&mut self, token: u64) -> Option<String> {
+        let index = self.advances.iter().position(|a| a.token == token)?;
+        let advance = self.advances.remove(index);
+        advance
+            .route
+            .auto_advance
+            .as_ref()
+            .map(|(_, path)| path.clone())
+    }
+}
+
+/// Spawn the countdown of idle timer `token`.
+///
+/// Activity only moves the deadline; the loop sleeps until the latest one
+/// and ends once the timer is gone or has fired.
+fn spawn_idle(cx: &App, token: u64, timeout: Duration) {
+    cx.spawn(async move |cx| {
+        let mut wait = timeout;
+        loop {
+            cx.background_executor().timer(wait).await;
+            let next = cx.update(|cx| {
+                let deadline = cx
+                    .try_global::<GlobalRouter>()?
+                    .route_timers()
+                    .idle_deadline(token)?;
+                let now = cx.background_executor().now();
+                if now < deadline {
+                    return Some(deadline - now);
+                }
+                fire_idle(cx, token);
+                None
+            });
+            match next {
+                Ok(Some(rest)) => wait = rest,
+                _ => break,
+            }
+        }
+    })
+    .detach();
+}
+
+fn fire_idle(cx: &mut App, token: u64) {
+    let fired = cx.update_global::<GlobalRouter, _>(|router, cx| {
+        let target = router.route_timers_mut().fire_idle(token)?;
+        info_log!("Idle timeout fired, navigating to {:?}", target);
+        let result = match &target {
+            IdleTarget::Path(path) => Some(router.push(path.clone(), cx)),
+            IdleTarget::Back => router.back(cx),
+        };
+        Some((target, result))
+    });
+    let Some((target, result)) = fired else {
+        return;
+    };
+    if !result
+        .as_ref()
+        .is_some_and(crate::NavigationResult::is_success)
+    {
+        warn_log!(
+            "Idle timeout target {:?} was not reached ({:?}), disarming",
+            target,
+            result
+        );
+    }
+    cx.refresh_windows();
+}
+
+/// Spawn the one-shot timer of auto-advance `token`.
+fn spawn_advance(cx: &App, token: u64, after: Duration) {
+    cx.spawn(async move |cx| {
+        cx.background_executor().timer(after).await;
+        let _ = cx.update(|cx| {
+            let fired = cx.update_global::<GlobalRouter, _>(|router, cx| {
+                let path = router.route_timers_mut().fire_advance(token)?;
+                info_log!("Auto-advancing to '{}'", path);
+                let result = router.push(path.clone(), cx);
+                Some((path, result))
+            });
+            let Some((path, result)) = fired else {
+                return;
+            };
+            if !result.is_success() {
+                warn_log!("Auto-advance to '{}' did not complete: {:?}", path, result);
+            }
+            cx.refresh_windows();
+        });
+    })
+    .detach();
+}
+
+// ============================================================================
+// Activity tracking
+// ============================================================================
+
+/// Report mouse and keyboard input inside `element` as user activity.
+///
+/// Wrap the window's root element so any input resets the idle timeout,
+/// as with [`Navigator::notify_activity`].
+pub fn track_activity<E: InteractiveElement>(element: E) -> E {
+    element
+        .capture_any_mouse_down(|_, _, cx| Navigator::notify_activity(cx))
+        .capture_key_down(|_, _, cx| Navigator::notify_activity(cx))
+        .on_mouse_move(|_, _, cx| Navigator::notify_activity(cx))
+        .on_scroll_wheel(|_, _, cx| Navigator::notify_activity(cx))
+}
+
+#[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
+mod tests {
+    use super::*;
+    use crate::init_router;
+    use crate::test_support::page;
+    use gpui::TestAppContext;
+
+    fn advance(cx: &mut TestAppContext, secs: u64) {
+        cx.executor().advance_clock(Duration::from_secs(secs));
+        cx.run_until_parked();
+    }
+
+    fn setup(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/catalog")
+                        .idle_timeout(Duration::from_secs(90), IdleTarget::Path("/".into())),
+                );
+                router.add_route(
+                    page("/cart").idle_timeout(Duration::from_secs(30), IdleTarget::Back),
+                );
+                router.add_route(page("/admin"));
+                router.add_route(
+                    page("/wizard/1").auto_advance(Duration::from_secs(10), "/wizard/2"),
+                );
+                router.add_route(page("/wizard/2"));
+            });
+        });
+    }
+
+    #[gpui::test]
+    fn test_idle_timeout_fires_after_inactivity(cx: &mut TestAppContext) {
+        setup(cx);
+        cx.update(|cx| Navigator::push(cx, "/catalog"));
+        advance(cx, 89);
+        assert_eq!(cx.read(Navigator::current_path), "/catalog");
+        advance(cx, 1);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+
+        // `Back` returns to the previous entry
+        cx.update(|cx| Navigator::push(cx, "/admin"));
+        cx.update(|cx| Navigator::push(cx, "/cart"));
+        advance(cx, 30);
+        assert_eq!(cx.read(Navigator::current_path), "/admin");
+    }
+
+    #[gpui::test]
+    fn test_activity_resets_idle_timeout(cx: &mut TestAppContext) {
+        setup(cx);
+        cx.update(|cx| Navigator::push(cx, "/catalog"));
+        advance(cx, 60);
+        cx.update(Navigator::notify_activity);
+        advance(cx, 60);
+        assert_eq!(cx.read(Navigator::current_path), "/catalog");
+        advance(cx, 30);
+        assert_eq!(cx.read(Navigator::current_path), "/");
+    }
+
+    #[gpui::test]
+    fn test_auto_advance_navigates_once(cx: &mut TestAppContext) {
+        setup(cx);
+        cx.update(|cx| Navigator::push(cx, "/wizard/1"));
+        advance(cx, 10);
+        assert_eq!(cx.read(Navigator::current_path), "/wizard/2");
+        advance(cx, 30);
+        assert_eq!(cx.read(Navigator::current_path), "/wizard/2");
+    }
+
+    #[gpui::test]
+    fn test_leaving_cancels_timers(cx: &mut TestAppContext) {
+        setup(cx);
+        cx.update(|cx| Navigator::push(cx, "/wizard/1"));
+        advance(cx, 5);
+        cx.update(|cx| Navigator::push(cx, "/admin"));
+        advance(cx, 20);
+        assert_eq!(cx.read(Navigator::current_path), "/admin");
+
+        cx.update(|cx| Navigator::push(cx, "/catalog"));
+        advance(cx, 45);
+        cx.update(|cx| Navigator::push(cx, "/admin"));
+        advance(cx, 120);
+        assert_eq!(cx.read(Navigator::current_path), "/admin");
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_blocked_target_disarms_after_one_attempt(cx: &mut TestAppContext) {
+        use crate::{guard_fn, NavigationAction};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/locked").guard(guard_fn(|_, _| {
+                    ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+                    NavigationAction::deny("locked")
+                })));
+                router.add_route(
+                    page("/catalog")
+                        .idle_timeout(Duration::from_secs(90), IdleTarget::Path("/locked".into())),
+                );
+            });
+        });
+
+        cx.update(|cx| Navigator::push(cx, "/catalog"));
+        advance(cx, 90);
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 1);
+        assert_eq!(cx.read(Navigator::current_path), "/catalog");
+
+        // Neither time nor activity re-arms it
+        cx.update(Navigator::notify_activity);
+        advance(cx, 300);
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 1);
+
+        // Entering the route again does
+        cx.update(|cx| Navigator::push(cx, "/"));
+        cx.update(|cx| Navigator::push(cx, "/catalog"));
+        advance(cx, 90);
+        assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 2);
+    }
+}
@@ -29,7 +29,9 @@
 //! Use [`TransitionConfig::set_override`] or `Navigator::push_with_transition`
 //! to override the default for a single navigation.
 
-use gpui::{div, px, Div, IntoElement, ParentElement, Styled};
+use crate::pattern::Pattern;
+use gpui::{div, px, App, Div, IntoElement, ParentElement, Styled};
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Direction for slide transitions
@@ -61,7 +63,7 @@ pub enum SlideDirection {
 /// let slide = Transition::slide_left(300);
 /// assert_eq!(slide.duration(), Duration::from_millis(300));
 /// ```
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum Transition {
     /// No transition animation
@@ -85,13 +87,13 @@ pub enum Transition {
 
 impl Transition {
     /// Create a cross-fade transition (old fades out, new fades in simultaneously)
-    #[must_use] 
+    #[must_use]
     pub const fn fade(duration_ms: u64) -> Self {
         Self::Fade { duration_ms }
     }
 
     /// Create a slide-left transition
-    #[must_use] 
+    #[must_use]
     pub const fn slide_left(duration_ms: u64) -> Self {
         Self::Slide {
             direction: SlideDirection::Left,
@@ -100,7 +102,7 @@ impl Transition {
     }
 
     /// Create a slide-right transition
-    #[must_use] 
+    #[must_use]
     pub const fn slide_right(duration_ms: u64) -> Self {
         Self::Slide {
             direction: SlideDirection::Right,
@@ -109,7 +111,7 @@ impl Transition {
     }
 
     /// Create a slide-up transition
-    #[must_use] 
+    #[must_use]
     pub const fn slide_up(duration_ms: u64) -> Self {
         Self::Slide {
             direction: SlideDirection::Up,
@@ -118,7 +120,7 @@ impl Transition {
     }
 
     /// Create a slide-down transition
-    #[must_use] 
+    #[must_use]
     pub const fn slide_down(duration_ms: u64) -> Self {
         Self::Slide {
             direction: SlideDirection::Down,
@@ -127,7 +129,7 @@ impl Transition {
     }
 
     /// Get the duration of this transition
-    #[must_use] 
+    #[must_use]
     pub const fn duration(&self) -> Duration {
         match self {
             Self::None => Duration::ZERO,
@@ -138,7 +140,7 @@ impl Transition {
     }
 
     /// Check if this is a no-op transition
-    #[must_use] 
+    #[must_use]
     pub const fn is_none(&self) -> bool {
         matches!(self, Self::None)
     }
@@ -168,7 +170,7 @@ impl Default for TransitionConfig {
 
 impl TransitionConfig {
     /// Create a new transition config with a default transition
-    #[must_use] 
+    #[must_use]
     pub const fn new(default: Transition) -> Self {
         Self {
             default,
@@ -177,7 +179,7 @@ impl TransitionConfig {
     }
 
     /// Get the active transition (override if set, otherwise default)
-    #[must_use] 
+    #[must_use]
     pub fn active(&self) -> &Transition {
         self.override_next.as_ref().unwrap_or(&self.default)
     }
@@ -193,12 +195,246 @@ impl TransitionConfig {
     }
 
     /// Check if there's an active override
-    #[must_use] 
+    #[must_use]
     pub const fn has_override(&self) -> bool {
         self.override_next.is_some()
     }
 }
 
+// ============================================================================
+// Transition sources
+// ============================================================================
+
+/// Kind of navigation that committed the current route.
+///
+/// Reported by [`GlobalRouter::navigation_source`](crate::GlobalRouter::navigation_source)
+/// and consulted through [`TransitionSources`] when an outlet picks its
+/// transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum NavigationSource {
+    /// The first navigation after init, with no previous content to leave
+    Initial,
+    /// A push (including named routes)
+    Push,
+    /// A replace
+    Replace,
+    /// A step back in history
+    Back,
+    /// A step forward in history
+    Forward,
+    /// A guard, lifecycle hook or not-found fallback redirected the navigation
+    Redirect,
+}
+
+/// Which navigation sources animate.
+///
+/// Install with [`GlobalRouter::set_transition_sources`](crate::GlobalRouter::set_transition_sources).
+/// A suppressed source renders instantly whatever transition the route
+/// configures; an explicit `push_with_transition` still animates. The
+/// default animates every source.
+///
+/// # Example
+///
+/// ```
+/// use gpui_navigator::transition::{NavigationSource, TransitionSources};
+///
+/// let sources = TransitionSources {
+///     back: false,
+///     forward: false,
+///     ..TransitionSources::default()
+/// };
+/// assert!(sources.allows(NavigationSource::Push));
+/// assert!(!sources.allows(NavigationSource::Back));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct TransitionSources {
+    /// Animate pushes
+    pub push: bool,
+    /// Animate replaces
+    pub replace: bool,
+    /// Animate steps back in history
+    pub back: bool,
+    /// Animate steps forward in history
+    pub forward: bool,
+    /// Animate navigations that landed through a redirect
+    pub redirect: bool,
+}
+
+impl Default for TransitionSources {
+    fn default() -> Self {
+        Self {
+            push: true,
+            replace: true,
+            back: true,
+            forward: true,
+            redirect: true,
+        }
+    }
+}
+
+impl TransitionSources {
+    /// Check whether navigations from `source` animate.
+    ///
+    /// The initial navigation never does.
+    #[must_use]
+    pub const fn allows(self, source: NavigationSource) -> bool {
+        match source {
+            NavigationSource::Initial => false,
+            NavigationSource::Push => self.push,
+            NavigationSource::Replace => self.replace,
+            NavigationSource::Back => self.back,
+            NavigationSource::Forward => self.forward,
+            NavigationSource::Redirect => self.redirect,
+        }
+    }
+
+    /// Pick the transition for a navigation from `source`.
+    ///
+    /// An `explicit` override wins over the policy, and `configured` (the
+    /// route's own choice) only runs for allowed sources. Nothing animates
+    /// for the initial navigation, override or not.
+    #[must_use]
+    pub fn select(
+        self,
+        source: NavigationSource,
+        explicit: Option<Transition>,
+        configured: impl FnOnce() -> Transition,
+    ) -> Transition {
+        match explicit {
+            _ if source == NavigationSource::Initial => Transition::None,
+            Some(transition) => transition,
+            None if self.allows(source) => configured(),
+            None => Transition::None,
+        }
+    }
+}
+
+// ============================================================================
+// Transition events
+// ============================================================================
+
+/// A committed navigation, as reported to
+/// [`GlobalRouter::on_transition`](crate::GlobalRouter::on_transition) hooks.
+///
+/// Lets chrome outside the router (a sidebar, the window title bar) start
+/// its own animation in step with the outlets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TransitionEvent {
+    /// Path before the navigation
+    pub from_path: String,
+    /// Path after the navigation
+    pub to_path: String,
+    /// Accumulated pattern of the previous leaf route, `None` when nothing matched
+    pub from_pattern: Option<String>,
+    /// Accumulated pattern of the new leaf route, `None` when nothing matched
+    pub to_pattern: Option<String>,
+    /// Shallowest depth of the match stack whose route or URL changed; the
+    /// outlets at this depth and below swap content
+    pub diverging_depth: usize,
+    /// Transition selected for the outlet at `diverging_depth`
+    pub transition: Transition,
+    /// How long the outlets that swap content animate: the longest of their
+    /// transitions, zero when nothing animates
+    pub duration: Duration,
+    /// Kind of navigation that committed
+    pub source: NavigationSource,
+}
+
+impl TransitionEvent {
+    /// Whether the navigation left the routes under `from_prefix` for the
+    /// routes under `to_prefix`.
+    ///
+    /// Prefixes are route-style patterns matched against the start of the
+    /// paths, so `"/projects/:id/editor"` covers every editor page of every
+    /// project. The previous path must lie under `from_prefix` and not under
+    /// `to_prefix`, the new one under `to_prefix`.
+    ///
+    /// ```
+    /// use gpui_navigator::transition::TransitionEvent;
+    ///
+    /// let mut event = TransitionEvent::default();
+    /// event.from_path = "/editor/42".to_string();
+    /// event.to_path = "/dashboard".to_string();
+    ///
+    /// assert!(event.family_changed("/editor", "/dashboard"));
+    /// assert!(!event.family_changed("/dashboard", "/editor"));
+    /// assert!(!event.family_changed("/editor", "/"));
+    /// ```
+    #[must_use]
+    pub fn family_changed(&self, from_prefix: &str, to_prefix: &str) -> bool {
+        let under = |path: &str, prefix: &str| {
+            let path = path.split_once('?').map_or(path, |(path, _)| path);
+            let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+            Pattern::parse(prefix).is_ok_and(|prefix| prefix.is_prefix_of(&segments))
+        };
+        under(&self.from_path, from_prefix)
+            && !under(&self.from_path, to_prefix)
+            && under(&self.to_path, to_prefix)
+    }
+}
+
+impl Default for TransitionEvent {
+    /// A navigation from `/` to `/` that animates nothing.
+    fn default() -> Self {
+        Self {
+            from_path: "/".to_string(),
+            to_path: "/".to_string(),
+            from_pattern: None,
+            to_pattern: None,
+            diverging_depth: 0,
+            transition: Transition::None,
+            duration: Duration::ZERO,
+            source: NavigationSource::Initial,
+        }
+    }
+}
+
+/// Identifies a hook added with
+/// [`GlobalRouter::on_transition`](crate::GlobalRouter::on_transition), for
+/// [`remove_transition_hook`](crate::GlobalRouter::remove_transition_hook).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TransitionHookId(u64);
+
+/// Hook called with every [`TransitionEvent`].
+pub(crate) type TransitionHookFn = Arc<dyn Fn(&TransitionEvent, &mut App) + Send + Sync>;
+
+/// Transition hooks, in the order they were added.
+#[derive(Clone, Default)]
+pub(crate) struct TransitionHooks {
+    hooks: Vec<(TransitionHookId, TransitionHookFn)>,
+    next_id: u64,
+}
+
+impl TransitionHooks {
+    pub(crate) fn add(&mut self, hook: TransitionHookFn) -> TransitionHookId {
+        let id = TransitionHookId(self.next_id);
+        self.next_id += 1;
+        self.hooks.push((id, hook));
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: TransitionHookId) -> bool {
+        let len = self.hooks.len();
+        self.hooks.retain(|(hook_id, _)| *hook_id != id);
+        self.hooks.len() != len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Clone the hooks out, so they can run without borrowing the router.
+    pub(crate) fn snapshot(&self) -> Vec<TransitionHookFn> {
+        self.hooks
+            .iter()
+            .map(|(_, hook)| Arc::clone(hook))
+            .collect()
+    }
+}
+
 // ============================================================================
 // Transition Builder
 // ============================================================================
@@ -253,7 +489,7 @@ pub fn apply_transition(element: impl IntoElement, transition: &Transition, prog
 }
 
 /// Cubic ease-in-out easing function (`t` in `0.0..=1.0`).
-#[must_use] 
+#[must_use]
 pub fn ease_in_out_cubic(t: f32) -> f32 {
     if t < 0.5 {
         4.0 * t * t * t
@@ -263,14 +499,65 @@ pub fn ease_in_out_cubic(t: f32) -> f32 {
 }
 
 /// Clamp `progress` to `0.0..=1.0` and apply [`ease_in_out_cubic`].
-#[must_use] 
+#[must_use]
 pub fn apply_easing(progress: f32) -> f32 {
     ease_in_out_cubic(progress.clamp(0.0, 1.0))
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_pass_by_ref_mut)]
 mod tests {
     use super::*;
+    use crate::test_support::page;
+    use crate::{init_router, GlobalRouter, NavigationRequest, Navigator, Route, UseRouter};
+    use gpui::{TestAppContext, Window};
+
+    #[test]
+    fn test_sources_select_by_source() {
+        let sources = TransitionSources {
+            push: true,
+            replace: false,
+            back: false,
+            forward: false,
+            redirect: false,
+        };
+        let fade = || Transition::fade(200);
+
+        assert_eq!(sources.select(NavigationSource::Push, None, fade), fade());
+        for source in [
+            NavigationSource::Replace,
+            NavigationSource::Back,
+            NavigationSource::Forward,
+            NavigationSource::Redirect,
+        ] {
+            assert_eq!(
+                sources.select(source, None, fade),
+                Transition::None,
+                "{source:?}"
+            );
+            // Explicit wins over the policy
+            assert_eq!(
+                sources.select(source, Some(Transition::slide_left(100)), fade),
+                Transition::slide_left(100)
+            );
+        }
+        assert!(TransitionSources::default().allows(NavigationSource::Back));
+    }
+
+    #[test]
+    fn test_sources_never_animate_initial_navigation() {
+        let sources = TransitionSources::default();
+        let configured = || -> Transition { panic!("initial navigation consulted the route") };
+        assert!(!sources.allows(NavigationSource::Initial));
+        assert_eq!(
+            sources.select(
+                NavigationSource::Initial,
+                Some(Transition::fade(100)),
+                configured
+            ),
+            Transition::None
+        );
+    }
 
     #[test]
     fn test_slide_direction() {
@@ -332,6 +619,24 @@ mod tests {
         assert_eq!(config.active().duration(), Duration::from_millis(200));
     }
 
+    #[test]
+    fn test_family_changed_follows_prefixes() {
+        let event = |from: &str, to: &str| TransitionEvent {
+            from_path: from.to_string(),
+            to_path: to.to_string(),
+            ..TransitionEvent::default()
+        };
+
+        let leaving = event("/projects/7/editor/main.rs", "/dashboard?tab=recent");
+        assert!(leaving.family_changed("/projects/:id/editor", "/dashboard"));
+        assert!(!leaving.family_changed("/dashboard", "/projects/:id/editor"));
+        // "/editorial" is not under "/editor"
+        assert!(!event("/editorial", "/dashboard").family_changed("/editor", "/dashboard"));
+        // Moving within one family is no change
+        assert!(!event("/editor/a", "/editor/b").family_changed("/editor", "/editor"));
+        assert!(!event("/editor/a", "/editor/b").family_changed("/", "/editor"));
+    }
+
     #[test]
     fn test_transition_helpers() {
         // Test all helper methods
@@ -341,4 +646,284 @@ mod tests {
         let _ = Transition::slide_up(300);
         let _ = Transition::slide_down(300);
     }
+
+    fn directional_route(path: &str) -> Route {
+        Route::new(path, |_, _cx, _params| gpui::div().into_any_element())
+            .transition(Transition::fade(100))
+            .transition_for(|request| {
+                let deeper = request
+                    .from
+                    .as_deref()
+                    .is_some_and(|from| request.to.len() > from.len());
+                if deeper {
+                    Transition::slide_left(200)
+                } else {
+                    Transition::slide_right(200)
+                }
+            })
+    }
+
+    #[test]
+    fn test_transition_for_selects_by_request() {
+        let route = directional_route("/inbox");
+
+        let forward = NavigationRequest::with_from("/inbox/42".into(), "/inbox".into());
+        assert_eq!(
+            route.transition_for_request(&forward),
+            Transition::slide_left(200)
+        );
+
+        let back = NavigationRequest::with_from("/inbox".into(), "/inbox/42".into());
+        assert_eq!(
+            route.transition_for_request(&back),
+            Transition::slide_right(200)
+        );
+
+        // Without a selector the static default applies
+        let plain = Route::new("/plain", |_, _cx, _params| gpui::div().into_any_element())
+            .transition(Transition::fade(100));
+        assert_eq!(
+            plain.transition_for_request(&forward),
+            Transition::fade(100)
+        );
+    }
+
+    #[gpui::test]
+    fn test_transition_for_uses_last_navigation(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(directional_route("/"));
+                router.add_route(directional_route("/inbox"));
+            });
+        });
+
+        let chosen = |cx: &mut TestAppContext| {
+            cx.read(|cx| {
+                let router = cx.global::<GlobalRouter>();
+                let entry = router.match_stack().leaf().unwrap();
+                entry
+                    .route
+                    .transition_for_request(router.last_navigation().unwrap())
+            })
+        };
+
+        cx.update(|cx| Navigator::push(cx, "/inbox"));
+        assert_eq!(chosen(cx), Transition::slide_left(200));
+
+        cx.update(Navigator::back);
+        assert_eq!(chosen(cx), Transition::slide_right(200));
+    }
+
+    #[gpui::test]
+    #[cfg(feature = "guard")]
+    fn test_transition_sources_follow_navigation_kind(cx: &mut TestAppContext) {
+        use crate::{guard_fn, NavigationAction};
+
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/a"));
+                router.add_route(page("/b"));
+                router.add_route(
+                    page("/old").guard(guard_fn(|_, _| NavigationAction::redirect("/b"))),
+                );
+                router.set_transition_sources(TransitionSources {
+                    push: true,
+                    replace: false,
+                    back: false,
+                    forward: false,
+                    redirect: false,
+                });
+            });
+        });
+        let selected = |cx: &mut TestAppContext| {
+            cx.read(|cx| {
+                let router = cx.global::<GlobalRouter>();
+                let source = router.navigation_source();
+                let transition = router
+                    .transition_sources()
+                    .select(source, None, || Transition::fade(200));
+                (source, transition)
+            })
+        };
+        assert_eq!(selected(cx).0, NavigationSource::Initial);
+
+        // Nothing to leave yet: the first navigation renders instantly
+        cx.update(|cx| Navigator::push(cx, "/a"));
+        assert_eq!(selected(cx), (NavigationSource::Initial, Transition::None));
+
+        cx.update(|cx| Navigator::push(cx, "/b"));
+        assert_eq!(
+            selected(cx),
+            (NavigationSource::Push, Transition::fade(200))
+        );
+
+        cx.update(Navigator::back);
+        assert_eq!(selected(cx), (NavigationSource::Back, Transition::None));
+
+        cx.update(Navigator::forward);
+        assert_eq!(selected(cx), (NavigationSource::Forward, Transition::None));
+
+        cx.update(|cx| Navigator::replace(cx, "/a"));
+        assert_eq!(selected(cx), (NavigationSource::Replace, Transition::None));
+
+        cx.update(|cx| Navigator::push(cx, "/old"));
+        assert_eq!(cx.read(Navigator::current_path), "/b");
+        assert_eq!(selected(cx), (NavigationSource::Redirect, Transition::None));
+    }
+
+    #[gpui::test]
+    fn test_transition_hook_reports_divergence_and_duration(cx: &mut TestAppContext) {
+        use std::sync::Mutex;
+
+        let events: Arc<Mutex<Vec<TransitionEvent>>> = Arc::default();
+        let sink = Arc::clone(&events);
+        let id = cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(
+                    page("/editor")
+                        .child(page("a"))
+                        .child(page("b").transition(Transition::fade(250))),
+                );
+            });
+            cx.update_router(|router, _| {
+                router.on_transition(move |event, _| sink.lock().unwrap().push(event.clone()))
+            })
+        });
+        cx.update(|cx| Navigator::push(cx, "/editor/a"));
+        cx.update(|cx| Navigator::push(cx, "/editor/b"));
+
+        {
+            let events = events.lock().unwrap().clone();
+            assert_eq!(events.len(), 2);
+            // The initial navigation replaces the whole stack and never animates
+            assert_eq!(events[0].diverging_depth, 0);
+            assert_eq!(events[0].duration, Duration::ZERO);
+
+            let event = &events[1];
+            assert_eq!(event.from_pattern.as_deref(), Some("/editor/a"));
+            assert_eq!(event.to_pattern.as_deref(), Some("/editor/b"));
+            assert_eq!(event.diverging_depth, 1);
+            assert_eq!(event.transition, Transition::fade(250));
+            assert_eq!(event.duration, Duration::from_millis(250));
+            assert_eq!(event.source, NavigationSource::Push);
+        }
+
+        assert!(cx.update(|cx| cx.update_router(|router, _| router.remove_transition_hook(id))));
+        assert!(!cx.update(|cx| cx.update_router(|router, _| router.remove_transition_hook(id))));
+        cx.update(|cx| Navigator::push(cx, "/"));
+        assert_eq!(events.lock().unwrap().len(), 2);
+    }
+
+    #[gpui::test]
+    fn test_transition_hook_runs_before_first_render(cx: &mut TestAppContext) {
+        use crate::router_view;
+        use gpui::{Context, Global, Render};
+        use std::sync::Mutex;
+
+        /// Written by the transition hook, read by the destination's builder.
+        struct Marker(&'static str);
+
+        impl Global for Marker {}
+
+        struct Shell;
+
+        impl Render for Shell {
+            fn render(
+                &mut self,
+                window: &mut Window,
+                cx: &mut Context<'_, Self>,
+            ) -> impl IntoElement {
+                router_view(window, cx)
+            }
+        }
+
+        let seen: Arc<Mutex<Vec<&'static str>>> = Arc::default();
+        let page_seen = Arc::clone(&seen);
+        cx.update(|cx| {
+            cx.set_global(Marker("stale"));
+            init_router(cx, |router| {
+                router.add_route(Route::view("/", || gpui::div().into_any_element()));
+                router.add_route(Route::new("/dashboard", move |_, cx, _| {
+                    page_seen.lock().unwrap().push(cx.global::<Marker>().0);
+                    gpui::div().into_any_element()
+                }));
+                router.on_transition(|event, cx| {
+                    if event.family_changed("/", "/dashboard") {
+                        cx.set_global(Marker("collapsed"));
+                    }
+                });
+            });
+        });
+        cx.add_window(|_, _| Shell);
+        cx.run_until_parked();
+
+        cx.update(|cx| Navigator::push(cx, "/dashboard"));
+        cx.run_until_parked();
+
+        let seen = seen.lock().unwrap().clone();
+        assert!(!seen.is_empty());
+        assert!(seen.iter().all(|marker| *marker == "collapsed"));
+    }
+
+    fn init_previous_stack_router(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            init_router(cx, |router| {
+                router.add_route(page("/"));
+                router.add_route(page("/plain"));
+                router.add_route(page("/fade").transition(Transition::fade(200)));
+            });
+            // The initial navigation never animates
+            Navigator::push(cx, "/plain");
+        });
+    }
+
+    fn previous_leaf(cx: &mut TestAppContext) -> Option<String> {
+        cx.read(|cx| {
+            let previous = cx.router().previous_stack()?;
+            previous.leaf_url().map(str::to_string)
+        })
+    }
+
+    #[gpui::test]
+    fn test_previous_stack_expires_after_transition(cx: &mut TestAppContext) {
+        init_previous_stack_router(cx);
+
+        cx.update(|cx| Navigator::push(cx, "/fade"));
+        assert_eq!(previous_leaf(cx).as_deref(), Some("/plain"));
+        assert!(cx.read(|cx| cx.router().previous_stack_expires().is_some()));
+
+        cx.executor().advance_clock(Duration::from_millis(199));
+        cx.run_until_parked();
+        assert_eq!(previous_leaf(cx).as_deref(), Some("/plain"));
+
+        cx.executor().advance_clock(Duration::from_millis(1));
+        cx.run_until_parked();
+        assert_eq!(previous_leaf(cx), None);
+        assert!(cx.read(|cx| cx.router().previous_stack_expires().is_none()));
+    }
+
+    #[gpui::test]
+    fn test_previous_stack_not_kept_without_transition(cx: &mut TestAppContext) {
+        init_previous_stack_router(cx);
+        assert_eq!(previous_leaf(cx), None);
+
+        cx.update(|cx| Navigator::push(cx, "/fade"));
+        // Leaving a faded route for one without a transition animates nothing
+        cx.update(|cx| Navigator::push(cx, "/plain"));
+        assert_eq!(previous_leaf(cx), None);
+
+        // A newer navigation's stack outlives the older timer
+        cx.update(|cx| Navigator::push(cx, "/fade"));
+        cx.executor().advance_clock(Duration::from_millis(150));
+        cx.update(|cx| Navigator::push(cx, "/plain"));
+        cx.update(|cx| Navigator::push(cx, "/fade"));
+        cx.executor().advance_clock(Duration::from_millis(100));
+        cx.run_until_parked();
+        assert_eq!(previous_leaf(cx).as_deref(), Some("/plain"));
+
+        cx.update(|cx| cx.update_router(|router, _| router.clear_previous_stack()));
+        assert_eq!(previous_leaf(cx), None);
+    }
 }
@@ -0,0 +1,159 @@
+//! Component cache warming from restored history.
+//!
+//! Users tend to return to the few screens they used last.
+//! [`GlobalRouter::warm_from_history`](crate::GlobalRouter::warm_from_history)
+//! takes the most recent distinct paths of the history and, one path per
+//! executor tick, builds the entities of their `Route::component` and
+//! `Route::component_with_params` routes into the component cache, so the
+//! first navigation to them renders instantly.
+//!
+//! Warmed entities go to the cold end of the cache: warming never evicts an
+//! entity, and stops adding once the cache is full. Paths that no longer
+//! resolve and routes that are not component-based are skipped.
+//!
+//! ```ignore
+//! // Right after restoring the history
+//! GlobalRouter::warm_from_history(cx, 3);
+//!
+//! // In a splash screen
+//! let status = cx.global::<GlobalRouter>().warming_status();
+//! if status.active {
+//!     div().child(format!("Preparing {}/{}", status.done, status.total))
+//! }
+//! ```
+
+use crate::history::History;
+use crate::trace_log;
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+/// Delay before each warming step, one path per step.
+pub(crate) const WARM_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Progress of the latest [`warm_from_history`](crate::GlobalRouter::warm_from_history).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WarmingStatus {
+    /// Paths queued for warming
+    pub total: usize,
+    /// Paths handled so far, including skipped ones
+    pub done: usize,
+    /// Component entities created
+    pub created: usize,
+    /// Whether paths are still queued; `false` once finished or cancelled
+    pub active: bool,
+}
+
+/// Paths still to warm and the progress made.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Warming {
+    queue: VecDeque<String>,
+    status: WarmingStatus,
+    /// Bumped on every start and cancel, so a stale task stops.
+    token: u64,
+}
+
+impl Warming {
+    /// Queue `paths`, replacing any warming in progress, and return the token
+    /// of the new run.
+    pub(crate) fn start(&mut self, paths: Vec<String>) -> u64 {
+        self.token += 1;
+        self.status = WarmingStatus {
+            total: paths.len(),
+            active: !paths.is_empty(),
+            ..WarmingStatus::default()
+        };
+        self.queue = paths.into();
+        self.token
+    }
+
+    /// Take the next path of run `token`, if it is still current.
+    pub(crate) fn next(&mut self, token: u64) -> Option<String> {
+        if token != self.token {
+            return None;
+        }
+        self.queue.pop_front()
+    }
+
+    /// Record a handled path of run `token`; returns whether paths remain.
+    pub(crate) fn finish_path(&mut self, token: u64, created: usize) -> bool {
+        if token != self.token {
+            return false;
+        }
+        self.status.done += 1;
+        self.status.created += created;
+        self.status.active = !self.queue.is_empty();
+        self.status.active
+    }
+
+    /// Drop the queued paths.
+    pub(crate) fn cancel(&mut self) {
+        if self.status.active {
+            trace_log!("Cache warming cancelled, {} path(s) left", self.queue.len());
+        }
+        self.token += 1;
+        self.queue.clear();
+        self.status.active = false;
+    }
+
+    pub(crate) const fn status(&self) -> WarmingStatus {
+        self.status
+    }
+}
+
+/// Up to `limit` distinct paths of `history`, most recent first.
+///
+/// Starts at the current entry and walks back; forward entries, visited
+/// before going back, come after, nearest first.
+pub(crate) fn recent_paths(history: &History, limit: usize) -> Vec<String> {
+    let entries = history.entries();
+    let current = history.current_index();
+    let backward = entries.iter().take(current + 1).rev();
+    let forward = entries.iter().skip(current + 1);
+
+    let mut seen = HashSet::new();
+    backward
+        .chain(forward)
+        .filter(|entry| seen.insert(entry.path.as_str()))
+        .take(limit)
+        .map(|entry| entry.path.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_paths_distinct_most_recent_first() {
+        let mut history = History::new("/".to_string());
+        for path in ["/a", "/b", "/a", "/c", "/d"] {
+            history.push(path.to_string());
+        }
+        history.back();
+        history.back();
+
+        assert_eq!(recent_paths(&history, 10), ["/a", "/b", "/", "/c", "/d"]);
+        assert_eq!(recent_paths(&history, 2), ["/a", "/b"]);
+    }
+
+    #[test]
+    fn test_cancel_stops_stale_run() {
+        let mut warming = Warming::default();
+        let token = warming.start(vec!["/a".into(), "/b".into()]);
+        assert_eq!(warming.next(token).as_deref(), Some("/a"));
+        assert!(warming.finish_path(token, 1));
+
+        warming.cancel();
+        assert_eq!(warming.next(token), None);
+        assert!(!warming.finish_path(token, 1));
+        assert_eq!(
+            warming.status(),
+            WarmingStatus {
+                total: 2,
+                done: 1,
+                created: 1,
+                active: false,
+            }
+        );
+    }
+}
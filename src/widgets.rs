@@ -11,6 +11,9 @@
 //! - [`RouterLink`] / [`router_link`] — clickable navigation link with
 //!   optional active-state styling.
 //! - [`DefaultPages`] — configurable fallback pages (404, loading, error).
+//! - [`navigation_progress_bar`] — thin bar tracking the navigation in flight.
+//! - [`router_notification_banner`] — the latest blocked or not-found
+//!   navigation, with a dismiss button.
 //!
 //! # Architecture (`MatchStack`)
 //!
@@ -25,21 +28,31 @@
 //!                                     RouterView  Outlet#1   Outlet#2
 //! ```
 
+use crate::boundary::RetryHandle;
 use crate::context::GlobalRouter;
+use crate::error::{NavigationError, RouteError};
+use crate::notifications::NotificationKind;
+use crate::preview::{
+    in_preview, preview_entry, preview_start, HoverPhase, PreviewConstraints, PreviewScope,
+};
 use crate::resolve::{
-    current_outlet_depth, enter_outlet, reset_outlet_depth, resolve_named_outlet, set_parent_depth,
+    current_outlet_depth, enter_outlet, entry_key, reset_outlet_depth, set_parent_depth,
+    MatchEntry, MatchStack, OutletDepthGuard,
 };
-use crate::{debug_log, trace_log};
+use crate::route::Route;
+use crate::RouteParams;
+use crate::{debug_log, error_log, trace_log};
 #[allow(clippy::wildcard_imports)]
 use gpui::*;
 
+#[cfg(feature = "transition")]
+use crate::route_env::{RenderEnvScope, RouteEnv};
 #[cfg(feature = "transition")]
 use crate::transition::{SlideDirection, Transition};
 
 #[cfg(feature = "transition")]
 use gpui::{Animation, AnimationExt};
 
-#[cfg(feature = "transition")]
 use std::time::Duration;
 
 // ============================================================================
@@ -62,6 +75,7 @@ pub struct RouterOutlet {
     /// Cached depth in the match stack. Computed once on first render via
     /// `enter_outlet()`, then reused on subsequent renders via `set_parent_depth()`.
     /// This avoids the thread-local `PARENT_DEPTH` growing stale between GPUI frames.
+    /// Set up front by [`RouterOutlet::at_depth`].
     depth: Option<usize>,
     /// Tracks the last rendered path for transition animations
     #[cfg(feature = "transition")]
@@ -75,6 +89,18 @@ pub struct RouterOutlet {
     /// When the current animation started
     #[cfg(feature = "transition")]
     transition_start: Option<std::time::Instant>,
+    /// Content rendered on the last frame, the exit side of the next transition
+    #[cfg(feature = "transition")]
+    shown: Option<OutletContent>,
+    /// Exit content of the running transition, captured when it started
+    #[cfg(feature = "transition")]
+    exit: Option<OutletContent>,
+    /// Stale-while-navigate state for `hold_previous` routes
+    #[cfg(feature = "transition")]
+    hold: HoldState,
+    /// Outlet-level transition, replacing the one of the route it renders
+    #[cfg(feature = "transition")]
+    transition: Option<Transition>,
 }
 
 impl Clone for RouterOutlet {
@@ -90,6 +116,14 @@ impl Clone for RouterOutlet {
             active_transition: self.active_transition.clone(),
             #[cfg(feature = "transition")]
             transition_start: self.transition_start,
+            #[cfg(feature = "transition")]
+            shown: self.shown.clone(),
+            #[cfg(feature = "transition")]
+            exit: self.exit.clone(),
+            #[cfg(feature = "transition")]
+            hold: self.hold.clone(),
+            #[cfg(feature = "transition")]
+            transition: self.transition.clone(),
         }
     }
 }
@@ -109,6 +143,28 @@ impl RouterOutlet {
             active_transition: None,
             #[cfg(feature = "transition")]
             transition_start: None,
+            #[cfg(feature = "transition")]
+            shown: None,
+            #[cfg(feature = "transition")]
+            exit: None,
+            #[cfg(feature = "transition")]
+            hold: HoldState::new(),
+            #[cfg(feature = "transition")]
+            transition: None,
+        }
+    }
+
+    /// Create an outlet pinned to `depth` in the match stack.
+    ///
+    /// The outlet skips `PARENT_DEPTH` discovery and always renders
+    /// `match_stack[depth]`; outlets inside it still see it as their parent.
+    /// Use it where discovery guesses wrong, e.g. an outlet rendered
+    /// conditionally or inside a deferred element outside the normal tree.
+    #[must_use]
+    pub fn at_depth(depth: usize) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Self::new()
         }
     }
 
@@ -125,8 +181,29 @@ impl RouterOutlet {
             active_transition: None,
             #[cfg(feature = "transition")]
             transition_start: None,
+            #[cfg(feature = "transition")]
+            shown: None,
+            #[cfg(feature = "transition")]
+            exit: None,
+            #[cfg(feature = "transition")]
+            hold: HoldState::new(),
+            #[cfg(feature = "transition")]
+            transition: None,
         }
     }
+
+    /// Animate this outlet's content changes with `transition`, whatever
+    /// the route it renders declares.
+    ///
+    /// A [`Navigator::push_with_transition`](crate::Navigator::push_with_transition)
+    /// override and a [`TransitionSources`](crate::transition::TransitionSources)
+    /// policy still take precedence in default outlets.
+    #[cfg(feature = "transition")]
+    #[must_use]
+    pub const fn transition(mut self, transition: Transition) -> Self {
+        self.transition = Some(transition);
+        self
+    }
 }
 
 impl Default for RouterOutlet {
@@ -137,7 +214,10 @@ impl Default for RouterOutlet {
 
 impl RouterOutlet {
     /// Render a named outlet (separate from the enter/exit depth tracking).
-    fn render_named(&self, window: &mut Window, cx: &mut Context<'_, Self>) -> AnyElement {
+    ///
+    /// Transitions run when the named child resolves to another route or
+    /// other params, not on every path change.
+    fn render_named(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) -> AnyElement {
         let name = self.name.as_deref().unwrap_or("");
 
         // Try child cache first (mutable borrow, then drop)
@@ -165,19 +245,35 @@ impl RouterOutlet {
             };
 
             let current_path = router.current_path().to_string();
-            let stack = router.match_stack();
             let depth = current_outlet_depth();
 
-            let resolved = resolve_named_outlet(stack, depth, name, &current_path);
+            let resolved = router.resolve_named_outlet(depth, name);
             if let Some((route, params)) = resolved {
-                Some((route, params, current_path))
+                let key = named_outlet_key(name, &route, &params, depth);
+                // Priority: outlet override > route's `transition_for`
+                // selector > route default
+                #[cfg(feature = "transition")]
+                let transition = (
+                    self.transition.clone().unwrap_or_else(|| {
+                        router.last_navigation().map_or_else(
+                            || route.transition.active().clone(),
+                            |request| route.transition_for_request(request),
+                        )
+                    }),
+                    key.clone(),
+                );
+                #[cfg(not(feature = "transition"))]
+                let transition = None::<()>;
+                Some((route, params, key, current_path, transition))
             } else {
                 trace_log!("Named outlet '{}': no matching route", name);
                 None
             }
         };
 
-        let Some((route, params, current_path)) = resolved else {
+        #[allow(clippy::used_underscore_binding)]
+        let Some((route, params, key, current_path, _transition)) = resolved
+        else {
             return div().into_any_element();
         };
 
@@ -193,11 +289,24 @@ impl RouterOutlet {
             });
         }
 
-        route.build(window, cx, &params).unwrap_or_else(|| {
+        #[cfg(any(test, feature = "test-util"))]
+        crate::render_log::record_route(current_outlet_depth(), Some(name), &route);
+        let element = build_route(&route, window, cx, &key, &params).unwrap_or_else(|| {
             div()
                 .child(format!("Route '{}' has no builder", route.config.path))
                 .into_any_element()
-        })
+        });
+
+        #[cfg(feature = "transition")]
+        {
+            let (transition, key) = _transition;
+            let content = OutletContent::new(route, params, key, cx);
+            let now = std::time::Instant::now();
+            if let Some(active) = self.track_named_transition(content, &transition, now) {
+                return self.animate(element, &active, window, cx);
+            }
+        }
+        element
     }
 
     /// Apply transition animation, managing animation state across frames.
@@ -206,61 +315,132 @@ impl RouterOutlet {
     /// occurred or an animation is still in progress, otherwise returns the
     /// element with `last_path` updated.
     #[cfg(feature = "transition")]
+    #[allow(clippy::too_many_arguments)]
     fn apply_transition(
         &mut self,
         element: AnyElement,
+        content: OutletContent,
         transition: &Transition,
         current_path: String,
         my_depth: usize,
         window: &mut Window,
         cx: &mut App,
     ) -> AnyElement {
-        let path_changed = current_path != self.last_path && !self.last_path.is_empty();
+        let now = std::time::Instant::now();
+        let Some(active) = self.track_transition(current_path, content, transition, now) else {
+            return element;
+        };
+        trace_log!(
+            "RouterOutlet depth {}: animating {:?} (counter={})",
+            my_depth,
+            active,
+            self.animation_counter
+        );
+        self.animate(element, &active, window, cx)
+    }
+
+    /// Layer `element` over the exit content of the `active` transition.
+    #[cfg(feature = "transition")]
+    fn animate(
+        &self,
+        element: AnyElement,
+        active: &Transition,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> AnyElement {
+        let exit_element = self.exit.clone().and_then(|exit| exit.build(window, cx));
+        let key = self
+            .shown
+            .as_ref()
+            .map_or_else(SharedString::default, |shown| shown.key.clone());
+        render_with_transition(element, exit_element, active, &key, self.animation_counter)
+    }
+
+    /// Advance the transition state for a frame rendering `content` at `path`.
+    ///
+    /// Returns the transition to animate this frame, if any; its exit side is
+    /// in `self.exit`. A path change starts a new transition whose exit is
+    /// whatever the outlet rendered last. When that change interrupts a
+    /// running transition, its enter side is treated as complete: the new
+    /// exit is the content that was entering, never the content it replaced,
+    /// and never an intermediate route the outlet did not render (a redirect
+    /// hop, or a second navigation between two frames).
+    #[cfg(feature = "transition")]
+    fn track_transition(
+        &mut self,
+        path: String,
+        content: OutletContent,
+        transition: &Transition,
+        now: std::time::Instant,
+    ) -> Option<Transition> {
+        let path_changed = path != self.last_path && !self.last_path.is_empty();
+        self.last_path = path;
+        self.track_content(path_changed, content, transition, now)
+    }
+
+    /// Advance the transition state for a frame of a named outlet.
+    ///
+    /// Like [`track_transition`](Self::track_transition), but the content
+    /// changed only when the named child resolves to another route or other
+    /// params than on the last frame: the path also changes when only the
+    /// default outlets' content does.
+    #[cfg(feature = "transition")]
+    fn track_named_transition(
+        &mut self,
+        content: OutletContent,
+        transition: &Transition,
+        now: std::time::Instant,
+    ) -> Option<Transition> {
+        let changed = self
+            .shown
+            .as_ref()
+            .is_some_and(|shown| !shown.same_as(&content));
+        self.track_content(changed, content, transition, now)
+    }
 
-        if path_changed {
+    /// Advance the transition state for a frame rendering `content`, which
+    /// differs from the last frame's if `changed`.
+    #[cfg(feature = "transition")]
+    fn track_content(
+        &mut self,
+        changed: bool,
+        content: OutletContent,
+        transition: &Transition,
+        now: std::time::Instant,
+    ) -> Option<Transition> {
+        if changed {
             self.animation_counter = self.animation_counter.wrapping_add(1);
-            self.last_path = current_path;
+            self.exit = self.shown.replace(content);
 
-            if !transition.is_none() {
-                debug_log!(
-                    "RouterOutlet depth {}: starting {:?} (counter={})",
-                    my_depth,
-                    transition,
-                    self.animation_counter
-                );
-                self.active_transition = Some(transition.clone());
-                self.transition_start = Some(std::time::Instant::now());
+            if transition.is_none() {
+                self.active_transition = None;
+                self.transition_start = None;
+                self.exit = None;
+                return None;
             }
-
-            let exit_element = build_exit_element(my_depth, window, cx);
-            return render_with_transition(
-                element,
-                exit_element,
+            debug_log!(
+                "RouterOutlet: starting {:?} (counter={})",
                 transition,
-                self.name.as_ref(),
-                self.animation_counter,
+                self.animation_counter
             );
+            self.active_transition = Some(transition.clone());
+            self.transition_start = Some(now);
+            return Some(transition.clone());
         }
 
+        self.shown = Some(content);
+
         // Animation still in progress — keep returning the wrapper
         if let (Some(active), Some(start)) = (&self.active_transition, self.transition_start) {
-            if start.elapsed() < active.duration() {
-                let exit_element = build_exit_element(my_depth, window, cx);
-                return render_with_transition(
-                    element,
-                    exit_element,
-                    active,
-                    self.name.as_ref(),
-                    self.animation_counter,
-                );
+            if now.saturating_duration_since(start) < active.duration() {
+                return Some(active.clone());
             }
             // Animation finished — clear state
             self.active_transition = None;
             self.transition_start = None;
+            self.exit = None;
         }
-
-        self.last_path = current_path;
-        element
+        None
     }
 }
 
@@ -287,9 +467,21 @@ pub fn router_outlet_named<V>(
     })
 }
 
+/// Create a cached `RouterOutlet` pinned to `depth` in the match stack
+pub fn router_outlet_at<V>(
+    window: &mut Window,
+    cx: &mut Context<'_, V>,
+    key: impl Into<String>,
+    depth: usize,
+) -> impl IntoElement {
+    window.use_keyed_state(ElementId::Name(key.into().into()), cx, move |_, _| {
+        RouterOutlet::at_depth(depth)
+    })
+}
+
 impl Render for RouterOutlet {
     fn render(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
-        // Named outlets don't use enter/exit — they resolve separately
+        // Named outlets resolve separately and track their own content
         if self.name.is_some() {
             return self.render_named(window, cx);
         }
@@ -299,8 +491,8 @@ impl Render for RouterOutlet {
         // First render: use enter_outlet() to discover depth from PARENT_DEPTH
         // thread-local and save it in self.depth for future renders.
         //
-        // Subsequent renders: use saved depth and just set PARENT_DEPTH for
-        // child outlets. This avoids the thread-local growing stale between
+        // Subsequent renders (and outlets pinned with `at_depth`): use saved
+        // depth and just set PARENT_DEPTH for child outlets. This avoids the thread-local growing stale between
         // GPUI render frames (Entity components persist across frames).
         let my_depth = if let Some(d) = self.depth {
             // Already know our depth — just set PARENT_DEPTH for children
@@ -352,52 +544,235 @@ impl Render for RouterOutlet {
                 entry.params.len()
             );
 
-            // Priority: GlobalRouter override > TransitionConfig override_next > route default
+            // Priority: GlobalRouter override > source policy > outlet
+            // override > TransitionConfig override_next > route's
+            // `transition_for` selector > route default
             #[cfg(feature = "transition")]
-            let transition =
-                Some(global_override.unwrap_or_else(|| entry.route.transition.active().clone()));
+            let transition = Some((
+                router.transition_sources().select(
+                    router.navigation_source(),
+                    global_override,
+                    || {
+                        self.transition.clone().unwrap_or_else(|| {
+                            router.last_navigation().map_or_else(
+                                || entry.route.transition.active().clone(),
+                                |request| entry.route.transition_for_request(request),
+                            )
+                        })
+                    },
+                ),
+                (entry.route.hold_previous || router.stale_while_navigate())
+                    .then(|| router.stale_timeout()),
+                entry.key(),
+            ));
             #[cfg(not(feature = "transition"))]
             let transition = None::<()>;
 
             (
                 std::sync::Arc::clone(&entry.route),
                 entry.params.clone(),
+                entry.key(),
                 current_path,
                 transition,
             )
         }; // router borrow ends here
 
         #[allow(clippy::used_underscore_binding)]
-        let (route, params, current_path, _transition) = resolved;
+        let (route, params, key, current_path, _transition) = resolved;
 
         // Build the route component. PARENT_DEPTH is already set to Some(my_depth),
         // so any RouterOutlet rendered inside this builder (even deferred by GPUI)
         // will correctly get depth = my_depth + 1.
-        let element = route.build(window, cx, &params).unwrap_or_else(|| {
-            div()
-                .child(format!("Route '{}' has no builder", route.config.path))
-                .into_any_element()
-        });
+        let element = build_entry(my_depth, &route, window, cx, &key, &params);
 
-        // Apply transition animation if applicable
+        // Hold stale content if requested, then apply transition animation
         #[cfg(feature = "transition")]
-        if let Some(transition) = _transition {
-            return self.apply_transition(element, &transition, current_path, my_depth, window, cx);
+        if let Some((transition, hold_timeout, key)) = _transition {
+            let element = match self.hold_previous(
+                element,
+                hold_timeout,
+                &current_path,
+                my_depth,
+                window,
+                cx,
+            ) {
+                Ok(element) => element,
+                Err(held) => return held,
+            };
+            let element = element.unwrap_or_else(|| missing_builder(&route));
+            let content = OutletContent::new(route, params, key, cx);
+            return self.apply_transition(
+                element,
+                content,
+                &transition,
+                current_path,
+                my_depth,
+                window,
+                cx,
+            );
         }
 
-        element
+        element.unwrap_or_else(|| missing_builder(&route))
+    }
+}
+
+/// Placeholder for routes without a builder.
+fn missing_builder(route: &crate::route::Route) -> AnyElement {
+    div()
+        .child(format!("Route '{}' has no builder", route.config.path))
+        .into_any_element()
+}
+
+// ============================================================================
+// Stale-while-navigate
+// ============================================================================
+
+/// What an outlet renders while its new route has not built yet.
+#[cfg(feature = "transition")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HoldPhase {
+    /// New content is ready — swap to it (starting the transition now).
+    Swap,
+    /// Keep rendering the previous entry's content.
+    Hold,
+    /// Held too long — show the loading page until the content is ready.
+    TimedOut,
+}
+
+/// Tracks how long an outlet has been holding stale content for a path.
+#[cfg(feature = "transition")]
+#[derive(Debug, Clone, Default)]
+struct HoldState {
+    /// Path whose content is being waited for, and when waiting started.
+    waiting: Option<(String, std::time::Instant)>,
+}
+
+#[cfg(feature = "transition")]
+impl HoldState {
+    const fn new() -> Self {
+        Self { waiting: None }
+    }
+
+    /// Decide what to render for `path` given whether its content is `ready`.
+    fn decide(
+        &mut self,
+        path: &str,
+        ready: bool,
+        timeout: Duration,
+        now: std::time::Instant,
+    ) -> HoldPhase {
+        if ready {
+            self.waiting = None;
+            return HoldPhase::Swap;
+        }
+        let since = match &self.waiting {
+            Some((waiting, since)) if waiting == path => *since,
+            _ => {
+                self.waiting = Some((path.to_string(), now));
+                now
+            }
+        };
+        if now.saturating_duration_since(since) >= timeout {
+            HoldPhase::TimedOut
+        } else {
+            HoldPhase::Hold
+        }
+    }
+
+    fn reset(&mut self) {
+        self.waiting = None;
     }
 }
 
-/// Build exit content from the previous match stack (old route at same depth).
+impl RouterOutlet {
+    /// Apply stale-while-navigate to a freshly built element.
+    ///
+    /// Returns `Ok` with the element to transition to, or `Err` with the
+    /// content to render instead (previous content or loading page) without
+    /// touching the transition state, so the transition clock starts at swap.
+    #[cfg(feature = "transition")]
+    fn hold_previous(
+        &mut self,
+        element: Option<AnyElement>,
+        hold_timeout: Option<Duration>,
+        current_path: &str,
+        my_depth: usize,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Result<Option<AnyElement>, AnyElement> {
+        let path_changed = current_path != self.last_path && !self.last_path.is_empty();
+        let Some(timeout) = hold_timeout.filter(|_| path_changed) else {
+            self.hold.reset();
+            return Ok(element);
+        };
+
+        let now = std::time::Instant::now();
+        match self
+            .hold
+            .decide(current_path, element.is_some(), timeout, now)
+        {
+            HoldPhase::Swap => Ok(element),
+            HoldPhase::Hold => {
+                trace_log!("RouterOutlet depth {}: holding previous content", my_depth);
+                // Keep polling the builder until it succeeds or the timeout hits
+                window.request_animation_frame();
+                Err(self
+                    .shown
+                    .clone()
+                    .and_then(|shown| shown.build(window, cx))
+                    .unwrap_or_else(|| DefaultPages::new().render_loading()))
+            }
+            HoldPhase::TimedOut => {
+                debug_log!(
+                    "RouterOutlet depth {}: hold timed out for '{}'",
+                    my_depth,
+                    current_path
+                );
+                Err(DefaultPages::new().render_loading())
+            }
+        }
+    }
+}
+
+/// Route content an outlet rendered, rebuilt for exit animations and held
+/// content so they show what was on screen.
+#[cfg(feature = "transition")]
+#[derive(Clone)]
+struct OutletContent {
+    route: std::sync::Arc<Route>,
+    params: RouteParams,
+    /// Environment of the leaf route when the content was rendered.
+    env: RouteEnv,
+    /// Identity of the level rendered, see [`MatchEntry::key`](crate::MatchEntry::key).
+    key: SharedString,
+}
+
 #[cfg(feature = "transition")]
-fn build_exit_element(depth: usize, window: &mut Window, cx: &mut App) -> Option<AnyElement> {
-    let router = cx.try_global::<GlobalRouter>()?;
-    let prev = router.previous_stack()?;
-    let entry = prev.at_depth(depth)?;
-    let route = std::sync::Arc::clone(&entry.route);
-    let params = entry.params.clone();
-    route.build(window, cx, &params)
+impl OutletContent {
+    /// Content of `route` rendered from the current match stack.
+    fn new(route: std::sync::Arc<Route>, params: RouteParams, key: SharedString, cx: &App) -> Self {
+        let env = cx
+            .try_global::<GlobalRouter>()
+            .and_then(GlobalRouter::leaf)
+            .map(|leaf| leaf.env_values().clone())
+            .unwrap_or_default();
+        Self {
+            route,
+            params,
+            env,
+            key,
+        }
+    }
+
+    /// Whether `other` is the same route with the same params.
+    fn same_as(&self, other: &Self) -> bool {
+        std::sync::Arc::ptr_eq(&self.route, &other.route) && self.params == other.params
+    }
+
+    fn build(&self, window: &mut Window, cx: &mut App) -> Option<AnyElement> {
+        let _env = RenderEnvScope::enter(&self.env);
+        build_route(&self.route, window, cx, &self.key, &self.params)
+    }
 }
 
 /// Render content with a cross-transition animation (enter + exit).
@@ -414,15 +789,14 @@ fn render_with_transition(
     enter_content: AnyElement,
     exit_content: Option<AnyElement>,
     transition: &Transition,
-    outlet_name: Option<&String>,
+    key: &str,
     counter: u32,
 ) -> AnyElement {
     match transition {
         Transition::Fade { duration_ms, .. } => {
             let duration = *duration_ms;
-            let enter_id =
-                SharedString::from(format!("outlet_fade_enter_{outlet_name:?}_{counter}"));
-            let exit_id = SharedString::from(format!("outlet_fade_exit_{outlet_name:?}_{counter}"));
+            let enter_id = SharedString::from(format!("{key}/fade-enter/{counter}"));
+            let exit_id = SharedString::from(format!("{key}/fade-exit/{counter}"));
 
             let mut container = div().relative().w_full().h_full();
 
@@ -469,10 +843,8 @@ fn render_with_transition(
             ..
         } => {
             let duration = *duration_ms;
-            let enter_id =
-                SharedString::from(format!("outlet_slide_enter_{outlet_name:?}_{counter}"));
-            let exit_id =
-                SharedString::from(format!("outlet_slide_exit_{outlet_name:?}_{counter}"));
+            let enter_id = SharedString::from(format!("{key}/slide-enter/{counter}"));
+            let exit_id = SharedString::from(format!("{key}/slide-exit/{counter}"));
 
             match direction {
                 SlideDirection::Left | SlideDirection::Right => {
@@ -594,6 +966,11 @@ fn render_with_transition(
 pub fn render_router_outlet(window: &mut Window, cx: &mut App, name: Option<&str>) -> AnyElement {
     // Named outlet: resolve separately (no enter/exit)
     if let Some(name) = name {
+        // Previews only fill default outlets
+        if in_preview() {
+            return div().into_any_element();
+        }
+
         // Try child cache first
         #[cfg(feature = "cache")]
         let cached = {
@@ -618,18 +995,18 @@ pub fn render_router_outlet(window: &mut Window, cx: &mut App, name: Option<&str
             };
 
             let current_path = router.current_path().to_string();
-            let stack = router.match_stack();
             let depth = current_outlet_depth();
 
-            if let Some((route, params)) = resolve_named_outlet(stack, depth, name, &current_path) {
-                Some((route, params, current_path))
+            if let Some((route, params)) = router.resolve_named_outlet(depth, name) {
+                let key = named_outlet_key(name, &route, &params, depth);
+                Some((route, params, key, current_path))
             } else {
                 trace_log!("render_router_outlet: named outlet '{}' not found", name);
                 None
             }
         };
 
-        let Some((route, params, current_path)) = resolved else {
+        let Some((route, params, key, current_path)) = resolved else {
             return div().into_any_element();
         };
 
@@ -645,14 +1022,21 @@ pub fn render_router_outlet(window: &mut Window, cx: &mut App, name: Option<&str
             });
         }
 
-        return route
-            .build(window, cx, &params)
+        #[cfg(any(test, feature = "test-util"))]
+        crate::render_log::record_route(current_outlet_depth(), Some(name), &route);
+        return build_route(&route, window, cx, &key, &params)
             .unwrap_or_else(|| div().into_any_element());
     }
 
     // Default outlet: PARENT_DEPTH determines depth automatically
     let my_depth = enter_outlet();
 
+    if in_preview() {
+        return preview_entry(my_depth)
+            .and_then(|entry| build_preview_route(&entry, window, cx))
+            .unwrap_or_else(|| div().into_any_element());
+    }
+
     let resolved = {
         let router = cx.try_global::<GlobalRouter>();
 
@@ -671,13 +1055,16 @@ pub fn render_router_outlet(window: &mut Window, cx: &mut App, name: Option<&str
             return div().into_any_element();
         };
 
-        (std::sync::Arc::clone(&entry.route), entry.params.clone())
+        (
+            std::sync::Arc::clone(&entry.route),
+            entry.params.clone(),
+            entry.key(),
+        )
     }; // router borrow ends here
 
-    let (route, params) = resolved;
+    let (route, params, key) = resolved;
 
-    route
-        .build(window, cx, &params)
+    build_entry(my_depth, &route, window, cx, &key, &params)
         .unwrap_or_else(|| div().into_any_element())
 }
 
@@ -717,10 +1104,35 @@ impl Render for RouterView {
 /// Resets outlet tracking to "no parent" and then calls `enter_outlet()` to
 /// render `match_stack[0]`. Child outlets inside the builder will see
 /// `PARENT_DEPTH = Some(0)` and render at depth 1, 2, 3...
+///
+/// The reset happens at the start of every render, so depth state left over
+/// by a frame that panicked mid-render never leaks into the next one. A
+/// builder that panics renders the error page instead.
 pub fn router_view<V>(window: &mut Window, cx: &mut Context<'_, V>) -> AnyElement {
     // Reset to "no parent" — ensures router_view always starts as root
     reset_outlet_depth();
 
+    // Render a window opened by `push_new_window` from its own state until
+    // this update ends; see `new_window`
+    let window_id = window.window_handle().window_id();
+    if cx.try_global::<GlobalRouter>().is_some()
+        && cx.update_global::<GlobalRouter, _>(|router, _| router.make_live(Some(window_id)))
+    {
+        cx.defer(|cx| {
+            if cx.try_global::<GlobalRouter>().is_some() {
+                cx.update_global::<GlobalRouter, _>(|router, _| router.make_live(None));
+            }
+        });
+    }
+
+    // Finish route table changes made since the last update
+    if cx
+        .try_global::<GlobalRouter>()
+        .is_some_and(GlobalRouter::is_unsettled)
+    {
+        cx.update_global::<GlobalRouter, _>(|router, cx| router.settle(cx));
+    }
+
     // Extract data from router, then drop borrow
     let resolved = {
         let router = cx.try_global::<GlobalRouter>();
@@ -749,34 +1161,537 @@ pub fn router_view<V>(window: &mut Window, cx: &mut Context<'_, V>) -> AnyElemen
         (
             std::sync::Arc::clone(&root_entry.route),
             root_entry.params.clone(),
+            root_entry.key(),
         )
     }; // router borrow ends here
 
-    let (route, params) = resolved;
+    let (route, params, key) = resolved;
 
     // enter_outlet: PARENT_DEPTH=None → depth=0, sets PARENT_DEPTH=Some(0)
     let _my_depth = enter_outlet();
 
-    route
-        .build(window, cx, &params)
+    build_entry(0, &route, window, cx, &key, &params)
         .unwrap_or_else(|| div().child("Root route has no builder").into_any_element())
 }
 
+/// Identity key of the content a named outlet at `depth` renders, in the
+/// [`MatchEntry::key`] scheme.
+fn named_outlet_key(name: &str, route: &Route, params: &RouteParams, depth: usize) -> SharedString {
+    entry_key(&format!("@{name}/{}", route.config.path), params, depth)
+}
+
+/// Build `route` as the level `key`, rendering the error page instead if
+/// its loader failed or its builder panics.
+///
+/// The panic is caught here so one broken page cannot take the window down,
+/// and the outlet depth state is restored to what it was before the build so
+/// half-rendered children do not leave it pointing at the wrong level. The
+/// error goes through the registered error handler, or the default error
+/// page if there is none.
+fn build_route(
+    route: &Route,
+    window: &mut Window,
+    cx: &mut App,
+    key: &SharedString,
+    params: &RouteParams,
+) -> Option<AnyElement> {
+    if let Some(element) = render_load_error(route, cx) {
+        return Some(element);
+    }
+    match catch_build(route, window, cx, key, params) {
+        Ok(element) => element,
+        Err(message) => Some(render_panic_page(cx, route, &message)),
+    }
+}
+
+/// Build `entry` for a preview: component routes get a fresh entity that
+/// bypasses the component cache, other routes build as [`build_route`].
+fn build_preview_route(
+    entry: &MatchEntry,
+    window: &mut Window,
+    cx: &mut App,
+) -> Option<AnyElement> {
+    if let Some(view) = entry.route.create_component(cx, &entry.params) {
+        return Some(view.into_any_element());
+    }
+    build_route(&entry.route, window, cx, &entry.key(), &entry.params)
+}
+
+/// Render `stack` as a preview in a box of the constrained size.
+///
+/// Outlets built meanwhile render `stack`, and the outlet depth state is
+/// put back afterwards, so the preview can be built in the middle of
+/// another route's render.
+pub(crate) fn build_preview(
+    stack: MatchStack,
+    constraints: PreviewConstraints,
+    window: &mut Window,
+    cx: &mut App,
+) -> AnyElement {
+    let depth_guard = OutletDepthGuard::new();
+    let start = preview_start(&stack, constraints.include_chain).cloned();
+    #[cfg(feature = "transition")]
+    let _env = stack
+        .leaf()
+        .map(|leaf| RenderEnvScope::enter(leaf.env_values()));
+    let _scope = PreviewScope::enter(stack);
+
+    let content = start.and_then(|entry| {
+        set_parent_depth(entry.depth);
+        build_preview_route(&entry, window, cx)
+    });
+    depth_guard.restore();
+
+    div()
+        .w(constraints.size.width)
+        .h(constraints.size.height)
+        .overflow_hidden()
+        .child(content.unwrap_or_else(|| div().into_any_element()))
+        .into_any_element()
+}
+
+/// Build the match stack entry at `depth`, like [`build_route`], routing
+/// failures to the nearest error boundary.
+///
+/// A failure the entry already carries renders the boundary instead of the
+/// builder. A builder that is missing or panics records its error on the
+/// entry; with a boundary above it, the window re-renders so the boundary
+/// takes over, otherwise it renders as [`build_route`] would.
+fn build_entry(
+    depth: usize,
+    route: &Route,
+    window: &mut Window,
+    cx: &mut App,
+    key: &SharedString,
+    params: &RouteParams,
+) -> Option<AnyElement> {
+    if let Some(element) = render_boundary(depth, window, cx) {
+        return Some(element);
+    }
+    if let Some(element) = render_load_error(route, cx) {
+        return Some(element);
+    }
+
+    #[cfg(any(test, feature = "test-util"))]
+    crate::render_log::record_route(depth, None, route);
+    let error = match catch_build(route, window, cx, key, params) {
+        Ok(Some(element)) => {
+            // A render failure from an earlier frame is over
+            if entry_error(depth, cx).is_some_and(|error| !error.is_load_error()) {
+                cx.update_global::<GlobalRouter, _>(|router, _| {
+                    router.record_render_error(depth, route, None);
+                });
+            }
+            return Some(element);
+        }
+        Ok(None) => RouteError::BuilderMissing,
+        Err(message) => RouteError::Panicked { message },
+    };
+    if cx.try_global::<GlobalRouter>().is_some() && entry_error(depth, cx).as_ref() != Some(&error)
+    {
+        cx.update_global::<GlobalRouter, _>(|router, _| {
+            router.record_render_error(depth, route, Some(error.clone()));
+        });
+    }
+    if entry_error(depth, cx).is_some() && has_boundary(depth, cx) {
+        if let Some(element) = render_boundary(depth, window, cx) {
+            return Some(element);
+        }
+        // A boundary above this level takes over on the next frame
+        window.refresh();
+        return Some(div().into_any_element());
+    }
+    match error {
+        RouteError::Panicked { message } => Some(render_panic_page(cx, route, &message)),
+        _ => None,
+    }
+}
+
+/// The error recorded on the entry at `depth`.
+fn entry_error(depth: usize, cx: &App) -> Option<RouteError> {
+    cx.try_global::<GlobalRouter>()?
+        .match_stack()
+        .at_depth(depth)?
+        .error()
+        .cloned()
+}
+
+/// Build `route` as the level `key`, catching a panic and returning its
+/// message.
+fn catch_build(
+    route: &Route,
+    window: &mut Window,
+    cx: &mut App,
+    key: &SharedString,
+    params: &RouteParams,
+) -> Result<Option<AnyElement>, String> {
+    let depth_guard = OutletDepthGuard::new();
+    let built = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        route.build_as(window, cx, key, params)
+    }));
+    built.map_err(|payload| {
+        depth_guard.restore();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(ToString::to_string)
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        error_log!(
+            "Builder for route '{}' panicked: {}",
+            route.config.path,
+            message
+        );
+        message
+    })
+}
+
+/// The error page for a failed loader stage of `route`, if it failed.
+fn render_load_error(route: &Route, cx: &App) -> Option<AnyElement> {
+    let error = cx
+        .try_global::<GlobalRouter>()
+        .and_then(|router| router.loader_error(route).cloned())?;
+    Some(render_error_page(cx, &error))
+}
+
+/// The error page for a builder of `route` that panicked with `message`.
+fn render_panic_page(cx: &App, route: &Route, message: &str) -> AnyElement {
+    let error = NavigationError::NavigationFailed {
+        message: format!(
+            "Route '{}' panicked while rendering: {message}",
+            route.config.path
+        ),
+    };
+    render_error_page(cx, &error)
+}
+
+/// Whether an error boundary sits at or above `depth`.
+fn has_boundary(depth: usize, cx: &App) -> bool {
+    cx.try_global::<GlobalRouter>().is_some_and(|router| {
+        router
+            .match_stack()
+            .entries()
+            .iter()
+            .take(depth + 1)
+            .any(|entry| entry.route.error_boundary.is_some())
+    })
+}
+
+/// Render the boundary of the entry at `depth`, if it is the nearest
+/// boundary of a failure at or below it.
+fn render_boundary(depth: usize, window: &mut Window, cx: &mut App) -> Option<AnyElement> {
+    let (boundary, error, retry) = {
+        let router = cx.try_global::<GlobalRouter>()?;
+        let entries = router.match_stack().entries();
+        let (failed, error) = entries
+            .iter()
+            .skip(depth)
+            .find_map(|entry| Some((entry, entry.error()?)))?;
+        let owner = entries[..=failed.depth]
+            .iter()
+            .rev()
+            .find(|entry| entry.route.error_boundary.is_some())?;
+        if owner.depth != depth {
+            return None;
+        }
+        debug_log!(
+            "Route '{}' failed ({}), rendering boundary of '{}'",
+            failed.route.config.path,
+            error,
+            owner.route.config.path
+        );
+        (
+            std::sync::Arc::clone(owner.route.error_boundary.as_ref()?),
+            error.clone(),
+            RetryHandle::new(failed.depth, std::sync::Arc::clone(&failed.route)),
+        )
+    }; // router borrow ends here
+    Some(boundary(&error, &retry, window, cx))
+}
+
+/// Render `error` through the registered error handler, or the default
+/// error page if there is none.
+fn render_error_page(cx: &App, error: &NavigationError) -> AnyElement {
+    cx.try_global::<GlobalRouter>()
+        .and_then(|router| router.error_handlers().render_error(cx, error))
+        .unwrap_or_else(|| DefaultPages::new().render_error(&error.to_string()))
+}
+
+// ============================================================================
+// Navigation progress bar
+// ============================================================================
+
+/// Thin bar showing the progress of the navigation in flight.
+///
+/// Renders a 2px bar whose width follows
+/// [`NavigationProgress::estimate`](crate::NavigationProgress::estimate),
+/// and nothing while no navigation is in flight. Place it at the top of the
+/// window layout.
+pub fn navigation_progress_bar(cx: &App) -> AnyElement {
+    let estimate = cx
+        .try_global::<GlobalRouter>()
+        .and_then(GlobalRouter::navigation_progress)
+        .map(crate::NavigationProgress::estimate);
+    let Some(estimate) = estimate else {
+        return div().into_any_element();
+    };
+
+    div()
+        .w_full()
+        .h(px(2.))
+        .child(div().h_full().w(relative(estimate)).bg(rgb(0x21_96_f3)))
+        .into_any_element()
+}
+
+// ============================================================================
+// Notification banner
+// ============================================================================
+
+/// Banner showing the router's current notification, see
+/// [`notifications`](crate::notifications).
+///
+/// Renders the message styled by kind with a dismiss button, and nothing
+/// while there is no notification. The banner re-renders the windows when
+/// the notification's auto-dismiss time is up. Place it once in the root
+/// layout, after enabling notifications with
+/// [`GlobalRouter::enable_outcome_notifications`].
+pub fn router_notification_banner(cx: &mut App) -> AnyElement {
+    let notification = cx
+        .try_global::<GlobalRouter>()
+        .and_then(GlobalRouter::current_notification)
+        .cloned();
+    let Some(notification) = notification else {
+        return div().into_any_element();
+    };
+
+    let timer = cx.update_global::<GlobalRouter, _>(|router, _| {
+        router
+            .notifications_mut()
+            .arm_timer(std::time::Instant::now())
+    });
+    if let Some((id, remaining)) = timer {
+        cx.spawn(async move |cx| {
+            cx.background_executor().timer(remaining).await;
+            let _ = cx.update(|cx| {
+                let expired = cx.update_global::<GlobalRouter, _>(|router, _| {
+                    router.notifications_mut().expire(id)
+                });
+                if expired {
+                    cx.refresh_windows();
+                }
+            });
+        })
+        .detach();
+    }
+
+    let (background, foreground) = match notification.kind {
+        NotificationKind::Blocked => (rgb(0xfd_ec_ea), rgb(0xb7_1c_1c)),
+        NotificationKind::NotFound => (rgb(0xff_f4_e5), rgb(0xe6_51_00)),
+        NotificationKind::Redirected => (rgb(0xe3_f2_fd), rgb(0x0d_47_a1)),
+    };
+    div()
+        .w_full()
+        .flex()
+        .items_center()
+        .justify_between()
+        .px_4()
+        .py_2()
+        .bg(background)
+        .text_color(foreground)
+        .text_sm()
+        .child(notification.message)
+        .child(div().cursor_pointer().px_2().child("×").on_mouse_down(
+            MouseButton::Left,
+            |_event, _window, cx| {
+                cx.update_global::<GlobalRouter, _>(|router, _| router.dismiss_notification());
+                cx.refresh_windows();
+            },
+        ))
+        .into_any_element()
+}
+
+// ============================================================================
+// Help screen
+// ============================================================================
+
+/// Grouped list of every navigable route, for an in-app Help screen.
+///
+/// Renders [`GlobalRouter::help_entries`] one group per top-level route.
+/// Each entry shows its title (or pattern), shortcut, requirements and
+/// description; routes without params link to themselves, parameterized
+/// ones are listed but not clickable.
+pub fn help_screen<V: 'static>(cx: &mut Context<'_, V>) -> Div {
+    let entries = cx
+        .try_global::<GlobalRouter>()
+        .map(GlobalRouter::help_entries)
+        .unwrap_or_default();
+
+    let mut screen = div().flex().flex_col().gap_4();
+    let mut rest = entries.as_slice();
+    while let Some(first) = rest.first() {
+        let len = rest.iter().take_while(|e| e.group == first.group).count();
+        let (group, tail) = rest.split_at(len);
+        rest = tail;
+        let mut list = div().flex().flex_col().gap_1().child(
+            div()
+                .font_weight(FontWeight::BOLD)
+                .child(group[0].group.clone()),
+        );
+        for entry in group {
+            let label = entry.title.clone().unwrap_or_else(|| entry.pattern.clone());
+            let name = if entry.clickable {
+                router_link(cx, entry.pattern.clone(), label)
+            } else {
+                div().text_color(rgb(0x66_66_66)).child(label)
+            };
+            let mut row = div().flex().gap_2().child(name);
+            if entry.title.is_some() {
+                row = row.child(div().text_sm().child(entry.pattern.clone()));
+            }
+            if let Some(shortcut) = &entry.shortcut {
+                row = row.child(div().text_sm().child(shortcut.clone()));
+            }
+            if !entry.requirements.is_empty() {
+                row = row.child(
+                    div()
+                        .text_sm()
+                        .text_color(rgb(0x99_66_00))
+                        .child(entry.requirements.join(", ")),
+                );
+            }
+            let mut item = div().flex().flex_col().child(row);
+            if let Some(description) = &entry.description {
+                item = item.child(
+                    div()
+                        .text_sm()
+                        .text_color(rgb(0x66_66_66))
+                        .child(description.clone()),
+                );
+            }
+            list = list.child(item);
+        }
+        screen = screen.child(list);
+    }
+    screen
+}
+
 // ============================================================================
 // RouterLink
 // ============================================================================
 
-use crate::Navigator;
+use crate::new_window::Modifier;
+use crate::{NavigationProbe, Navigator};
+
+/// What a [`RouterLink`] does when guards would block its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockedBehavior {
+    /// Render normally, without probing.
+    Show,
+    /// Render nothing.
+    Hide,
+    /// Render without a click handler, optionally with the reason as tooltip.
+    Disable { tooltip: bool },
+}
+
+/// How a [`RouterLink`] renders after its target was probed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LinkState {
+    Enabled,
+    Hidden,
+    Disabled { tooltip: Option<SharedString> },
+}
+
+/// Tooltip explaining why a disabled [`RouterLink`] cannot be followed.
+struct BlockedLinkTooltip {
+    text: SharedString,
+}
+
+impl Render for BlockedLinkTooltip {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<'_, Self>) -> impl IntoElement {
+        div()
+            .px_2()
+            .py_1()
+            .rounded_md()
+            .bg(rgb(0x33_33_33))
+            .text_color(rgb(0xff_ff_ff))
+            .text_sm()
+            .child(self.text.clone())
+    }
+}
+
+/// Popover content of a [`RouterLink::preview_on_hover`] link.
+struct LinkPreview {
+    path: SharedString,
+    size: Size<Pixels>,
+}
+
+impl Render for LinkPreview {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let constraints = PreviewConstraints::new(self.size);
+        let Some(preview) = GlobalRouter::render_preview(window, cx, &self.path, constraints)
+        else {
+            return div().into_any_element();
+        };
+        div()
+            .rounded_md()
+            .border_1()
+            .border_color(rgb(0xdd_dd_dd))
+            .bg(rgb(0xff_ff_ff))
+            .shadow_lg()
+            .overflow_hidden()
+            .child(preview)
+            .into_any_element()
+    }
+}
+
+/// Track the pointer entering or leaving a link to `path` and show its
+/// preview once it stayed for `delay`.
+fn hover_link_preview(cx: &mut App, path: &str, hovered: bool, delay: Duration) {
+    if cx.try_global::<GlobalRouter>().is_none() {
+        return;
+    }
+    let (token, hid) = cx.update_global::<GlobalRouter, _>(|router, _| {
+        let hover = router.hover_preview_mut();
+        if !hovered {
+            return (None, hover.leave(path));
+        }
+        let was_showing = hover.phase() == HoverPhase::Showing;
+        let token = hover.enter(path);
+        (token, was_showing && token.is_some())
+    });
+    if hid {
+        cx.refresh_windows();
+    }
+    let Some(token) = token else {
+        return;
+    };
+    cx.spawn(async move |cx| {
+        cx.background_executor().timer(delay).await;
+        let _ = cx.update(|cx| {
+            let shown = cx.update_global::<GlobalRouter, _>(|router, _| {
+                router.hover_preview_mut().elapse(token)
+            });
+            if shown {
+                cx.refresh_windows();
+            }
+        });
+    })
+    .detach();
+}
 
 /// A clickable link component that navigates to a route on click.
 ///
-/// Supports optional active-state styling via [`active_class`](Self::active_class).
+/// Style the container with [`style`](Self::style) in every state, and on
+/// top of that with [`active_class`](Self::active_class) when active.
+/// Links can hide or disable themselves when guards would block the target,
+/// see [`hide_if_blocked`](Self::hide_if_blocked) and
+/// [`disable_if_blocked`](Self::disable_if_blocked).
 ///
 /// # Examples
 ///
 /// ```ignore
 /// RouterLink::new("/settings")
 ///     .child("Settings")
+///     .style(|div| div.px_3().py_2().rounded_md())
 ///     .active_class(|div| div.text_color(gpui::rgb(0x21_96_f3)))
 ///     .build(cx)
 /// ```
@@ -784,10 +1699,23 @@ use crate::Navigator;
 pub struct RouterLink {
     /// Target route path
     path: SharedString,
+    /// Optional custom styling in every state
+    style: Option<Box<dyn Fn(Div) -> Div>>,
     /// Optional custom styling when link is active
     active_class: Option<Box<dyn Fn(Div) -> Div>>,
     /// Child elements
     children: Vec<AnyElement>,
+    /// Warm the target's component cache when the pointer moves over the link
+    prefetch_on_hover: bool,
+    /// Hover delay and size of the target's preview popover
+    preview: Option<(Duration, Size<Pixels>)>,
+    /// Rendering when guards would block the target
+    blocked: BlockedBehavior,
+    /// Modifier that opens the target in a new window when held on click
+    new_window_on: Option<Modifier>,
+    /// Hero source captured on click, flying to its target on the new page
+    #[cfg(feature = "transition")]
+    hero: Option<SharedString>,
 }
 
 impl RouterLink {
@@ -795,8 +1723,15 @@ impl RouterLink {
     pub fn new(path: impl Into<SharedString>) -> Self {
         Self {
             path: path.into(),
+            style: None,
             active_class: None,
             children: Vec::new(),
+            prefetch_on_hover: false,
+            preview: None,
+            blocked: BlockedBehavior::Show,
+            new_window_on: None,
+            #[cfg(feature = "transition")]
+            hero: None,
         }
     }
 
@@ -806,36 +1741,218 @@ impl RouterLink {
         self
     }
 
+    /// Set custom styling applied in every state.
+    ///
+    /// Runs before [`active_class`](Self::active_class), which can override
+    /// it when the link is active.
+    pub fn style(mut self, style: impl Fn(Div) -> Div + 'static) -> Self {
+        self.style = Some(Box::new(style));
+        self
+    }
+
     /// Set custom styling for when this link is active (current route)
     pub fn active_class(mut self, style: impl Fn(Div) -> Div + 'static) -> Self {
         self.active_class = Some(Box::new(style));
         self
     }
 
+    /// Prefetch the target route while the pointer hovers the link.
+    ///
+    /// Hovering calls [`GlobalRouter::prefetch`] for the link's path, so
+    /// component routes behind it are already built when the link is
+    /// clicked. Prefetching is debounced and skips cached components.
+    pub const fn prefetch_on_hover(mut self, enabled: bool) -> Self {
+        self.prefetch_on_hover = enabled;
+        self
+    }
+
+    /// Show a preview of the target in a popover once the pointer rests on
+    /// the link for `delay`.
+    ///
+    /// The preview is [`GlobalRouter::render_preview`] of the link's path,
+    /// leaf route only, in a box of `size` anchored below the link. It hides
+    /// when the pointer leaves or the link is clicked. One preview shows at
+    /// a time; a target that [renders no preview](crate::preview) shows no
+    /// popover.
+    pub const fn preview_on_hover(mut self, delay: Duration, size: Size<Pixels>) -> Self {
+        self.preview = Some((delay, size));
+        self
+    }
+
+    /// Open the target in a new window when the link is clicked with
+    /// `modifier` held.
+    ///
+    /// Such clicks call [`Navigator::push_new_window`] with default
+    /// [`WindowOptions`] instead of [`Navigator::push`]. See
+    /// [`new_window`](crate::new_window).
+    pub const fn open_in_new_window_on(mut self, modifier: Modifier) -> Self {
+        self.new_window_on = Some(modifier);
+        self
+    }
+
+    /// Whether a click with `modifiers` held opens a new window.
+    fn opens_new_window(new_window_on: Option<Modifier>, modifiers: Modifiers) -> bool {
+        new_window_on.is_some_and(|modifier| modifier.is_held(modifiers))
+    }
+
+    /// Fly the hero `id` to the target page when the link is clicked.
+    ///
+    /// The click captures the bounds last reported by
+    /// [`hero_source`](crate::hero::hero_source) for `id`, so wrap the
+    /// element that should fly with it. See [`hero`](crate::hero).
+    #[cfg(feature = "transition")]
+    pub fn hero(mut self, id: impl Into<SharedString>) -> Self {
+        self.hero = Some(id.into());
+        self
+    }
+
+    /// Render nothing when guards would block the target.
+    ///
+    /// The target is probed with [`GlobalRouter::can_navigate`] at build
+    /// time; a guard redirect counts as blocked, a challenge does not.
+    pub const fn hide_if_blocked(mut self) -> Self {
+        self.blocked = BlockedBehavior::Hide;
+        self
+    }
+
+    /// Render the link dimmed and unclickable when guards would block the
+    /// target, showing the guard's reason as a tooltip if `tooltip_from_reason`.
+    ///
+    /// Probing works as for [`hide_if_blocked`](Self::hide_if_blocked).
+    pub const fn disable_if_blocked(mut self, tooltip_from_reason: bool) -> Self {
+        self.blocked = BlockedBehavior::Disable {
+            tooltip: tooltip_from_reason,
+        };
+        self
+    }
+
+    /// Derive how the link renders from the probe of its target.
+    fn state(&self, probe: Option<&NavigationProbe>) -> LinkState {
+        let Some(probe) = probe.filter(|probe| !probe.allowed) else {
+            return LinkState::Enabled;
+        };
+        match self.blocked {
+            BlockedBehavior::Show => LinkState::Enabled,
+            BlockedBehavior::Hide => LinkState::Hidden,
+            BlockedBehavior::Disable { tooltip } => LinkState::Disabled {
+                tooltip: tooltip.then(|| {
+                    probe
+                        .reason
+                        .clone()
+                        .or_else(|| {
+                            probe
+                                .redirect
+                                .as_ref()
+                                .map(|to| format!("Redirects to {to}"))
+                        })
+                        .unwrap_or_else(|| "Not available".to_string())
+                        .into()
+                }),
+            },
+        }
+    }
+
+    /// Apply [`style`](Self::style), then [`active_class`](Self::active_class)
+    /// if `is_active`.
+    fn decorate(&self, mut link: Div, is_active: bool) -> Div {
+        if let Some(style) = &self.style {
+            link = style(link);
+        }
+        if is_active {
+            if let Some(active_fn) = &self.active_class {
+                link = active_fn(link);
+            }
+        }
+        link
+    }
+
     /// Build the link element with the given context
     pub fn build<V: 'static>(self, cx: &mut Context<'_, V>) -> Div {
         let path = self.path.clone();
         let current_path = Navigator::current_path(cx);
         let is_active = current_path == path.as_ref();
 
+        let probe = (self.blocked != BlockedBehavior::Show)
+            .then(|| GlobalRouter::can_navigate(cx.global::<GlobalRouter>(), cx, &path));
+        match self.state(probe.as_ref()) {
+            LinkState::Enabled => {}
+            LinkState::Hidden => return div().hidden(),
+            LinkState::Disabled { tooltip } => return self.build_disabled(tooltip),
+        }
+
+        #[cfg(feature = "transition")]
+        let hero = self.hero.clone();
+        let previews = self.preview.is_some();
+        let new_window_on = self.new_window_on;
         let mut link = div().cursor_pointer().on_mouse_down(
             MouseButton::Left,
-            cx.listener(move |_view, _event, _window, cx| {
-                Navigator::push(cx, path.to_string());
+            cx.listener(move |_view, event: &MouseDownEvent, window, cx| {
+                #[cfg(feature = "transition")]
+                if let Some(id) = &hero {
+                    cx.update_global::<GlobalRouter, _>(|router, _| {
+                        router.heroes_mut().capture_source(id);
+                    });
+                }
+                if previews {
+                    cx.update_global::<GlobalRouter, _>(|router, _| {
+                        router.hover_preview_mut().leave(&path);
+                    });
+                }
+                if Self::opens_new_window(new_window_on, event.modifiers) {
+                    Navigator::push_new_window(cx, path.to_string(), WindowOptions::default());
+                } else {
+                    push_from(window, cx, path.to_string());
+                }
             }),
         );
 
-        if is_active {
-            if let Some(active_fn) = self.active_class {
-                link = active_fn(link);
-            }
+        if self.prefetch_on_hover {
+            let path = self.path.clone();
+            link = link.on_mouse_move(move |_event, window, cx| {
+                GlobalRouter::prefetch(window, cx, &path);
+            });
         }
 
+        link = self.decorate(link, is_active);
         for child in self.children {
             link = link.child(child);
         }
 
-        link
+        let Some((delay, size)) = self.preview else {
+            return link;
+        };
+        // Hover tracking needs a stateful element; wrap it so callers still get a `Div`
+        let id = SharedString::from(format!("router-link-preview:{}", self.path));
+        let hovered_path = self.path.clone();
+        let mut wrapper = div().child(link.id(id).on_hover(move |hovered, _window, cx| {
+            hover_link_preview(cx, &hovered_path, *hovered, delay);
+        }));
+        let hover = cx.global::<GlobalRouter>().hover_preview();
+        if hover.is_showing(&self.path) {
+            let popover = cx.new(|_| LinkPreview {
+                path: self.path.clone(),
+                size,
+            });
+            wrapper = wrapper.child(deferred(anchored().snap_to_window().child(popover)));
+        }
+        wrapper
+    }
+
+    /// Build the dimmed, unclickable form of the link.
+    fn build_disabled(self, tooltip: Option<SharedString>) -> Div {
+        let mut link = self.decorate(div(), false).opacity(0.5);
+        for child in self.children {
+            link = link.child(child);
+        }
+        let Some(text) = tooltip else {
+            return link;
+        };
+        // Tooltips need a stateful element; wrap it so callers still get a `Div`
+        let id = SharedString::from(format!("router-link-blocked:{}", self.path));
+        div().child(link.id(id).tooltip(move |_window, cx| {
+            let text = text.clone();
+            cx.new(|_| BlockedLinkTooltip { text }).into()
+        }))
     }
 }
 
@@ -863,12 +1980,26 @@ pub fn router_link<V: 'static>(
         .child(label_str)
         .on_mouse_down(
             MouseButton::Left,
-            cx.listener(move |_view, _event, _window, cx| {
-                Navigator::push(cx, path_str.to_string());
+            cx.listener(move |_view, _event, window, cx| {
+                push_from(window, cx, path_str.to_string());
             }),
         )
 }
 
+/// Push `path` for a link clicked in `window`: on the window's own state if
+/// [`Navigator::push_new_window`] opened it, on the shared one otherwise.
+fn push_from(window: &Window, cx: &mut App, path: String) {
+    let handle = window.window_handle();
+    if cx
+        .global::<GlobalRouter>()
+        .has_window_state(handle.window_id())
+    {
+        Navigator::push_in_window(cx, handle, path);
+    } else {
+        Navigator::push(cx, path);
+    }
+}
+
 // ============================================================================
 // Default Pages System
 // ============================================================================
@@ -1050,7 +2181,7 @@ fn default_error_page(message: &str) -> impl IntoElement {
 
 #[cfg(test)]
 mod tests {
-    use super::RouterOutlet;
+    use super::{RouterLink, RouterOutlet};
 
     #[test]
     fn test_outlet_creation() {
@@ -1069,4 +2200,588 @@ mod tests {
         let named = RouterOutlet::named("main");
         assert_eq!(named.name, Some("main".to_string()));
     }
+
+    mod panics {
+        use super::super::router_view;
+        use crate::resolve::{current_parent_depth, set_parent_depth};
+        use crate::{init_router, Route};
+        use gpui::{div, Context, IntoElement, Render, TestAppContext, Window};
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Mutex;
+
+        struct Root;
+
+        impl Render for Root {
+            fn render(&mut self, _: &mut Window, _: &mut Context<'_, Self>) -> impl IntoElement {
+                div()
+            }
+        }
+
+        #[gpui::test]
+        fn test_router_view_starts_at_root_after_builder_panic(cx: &mut TestAppContext) {
+            static PANICKED: AtomicBool = AtomicBool::new(false);
+            static SEEN: Mutex<Vec<Option<usize>>> = Mutex::new(Vec::new());
+
+            cx.update(|cx| {
+                init_router(cx, |router| {
+                    router.add_route(Route::new("/", |_, _, _| {
+                        SEEN.lock().unwrap().push(current_parent_depth());
+                        if !PANICKED.swap(true, Ordering::SeqCst) {
+                            set_parent_depth(7);
+                            panic!("builder failed");
+                        }
+                        div().into_any_element()
+                    }));
+                });
+            });
+
+            let window = cx.add_window(|_, _| Root);
+            window
+                .update(cx, |_, window, cx| {
+                    // The panic is contained and the depth restored to the root
+                    let _ = router_view(window, cx);
+                    assert_eq!(current_parent_depth(), Some(0));
+
+                    // A frame that died mid-render left a stale depth behind
+                    set_parent_depth(5);
+                    let _ = router_view(window, cx);
+                })
+                .unwrap();
+
+            assert_eq!(*SEEN.lock().unwrap(), [Some(0), Some(0)]);
+        }
+    }
+
+    mod pinned {
+        use super::super::RouterOutlet;
+        use crate::resolve::{current_parent_depth, reset_outlet_depth};
+        use crate::{init_router, Navigator, Route};
+        use gpui::{div, IntoElement, Render, TestAppContext};
+        use std::sync::Mutex;
+
+        #[test]
+        fn test_at_depth_pins_depth() {
+            let outlet = RouterOutlet::at_depth(2);
+            assert_eq!(outlet.depth, Some(2));
+            assert!(outlet.name.is_none());
+        }
+
+        #[gpui::test]
+        fn test_pinned_outlet_renders_its_depth(cx: &mut TestAppContext) {
+            static SEEN: Mutex<Vec<(&str, Option<usize>)>> = Mutex::new(Vec::new());
+
+            let record = |path: &'static str| {
+                Route::new(path, move |_, _, _| {
+                    SEEN.lock().unwrap().push((path, current_parent_depth()));
+                    div().into_any_element()
+                })
+            };
+            cx.update(|cx| {
+                init_router(cx, |router| {
+                    router.add_route(record("/"));
+                    router.add_route(record("/projects").children(vec![record(":id").into()]));
+                });
+            });
+            cx.update(|cx| Navigator::push(cx, "/projects/7"));
+
+            let window = cx.add_window(|_, _| RouterOutlet::at_depth(1));
+            window
+                .update(cx, |outlet, window, cx| {
+                    // Discovery would claim depth 0 here
+                    reset_outlet_depth();
+                    let _ = outlet.render(window, cx);
+                })
+                .unwrap();
+
+            // Only the leaf rendered, with the pinned depth as its children's parent
+            let seen = SEEN.lock().unwrap().clone();
+            assert!(!seen.is_empty());
+            assert!(
+                seen.iter().all(|entry| *entry == (":id", Some(1))),
+                "{seen:?}"
+            );
+        }
+    }
+
+    mod synthetic_stack {
+        use super::super::RouterOutlet;
+        use crate::resolve::{reset_outlet_depth, resolve_match_stack};
+        use crate::{init_router, GlobalRouter, Navigator, Route};
+        use gpui::{div, BorrowAppContext, IntoElement, Render, TestAppContext};
+        use std::sync::Mutex;
+
+        #[gpui::test]
+        fn test_outlet_renders_installed_stack(cx: &mut TestAppContext) {
+            static SEEN: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+            cx.update(|cx| {
+                init_router(cx, |router| {
+                    router.add_route(Route::view("/", || div().into_any_element()));
+                    router.add_route(Route::view("/projects", || div().into_any_element()).child(
+                        Route::new(":id", |_, _, params| {
+                            SEEN.lock().unwrap().push(params["id"].clone());
+                            div().into_any_element()
+                        }),
+                    ));
+                });
+            });
+            cx.update(|cx| {
+                cx.update_global::<GlobalRouter, _>(|router, _| {
+                    let stack = resolve_match_stack(router.state().routes(), "/projects/7");
+                    router.set_match_stack_for_test(stack);
+                });
+            });
+
+            cx.read(|cx| {
+                let stack = cx.global::<GlobalRouter>().match_stack();
+                assert_eq!(stack.len(), 2);
+                assert_eq!(stack.at_depth(0).unwrap().route.config.path, "/projects");
+                assert_eq!(stack.leaf().unwrap().accumulated_pattern(), "/projects/:id");
+                assert!(stack.at_depth(2).is_none());
+            });
+            // Installed without navigating
+            assert_eq!(cx.read(Navigator::current_path), "/");
+
+            let window = cx.add_window(|_, _| RouterOutlet::at_depth(1));
+            window
+                .update(cx, |outlet, window, cx| {
+                    reset_outlet_depth();
+                    let _ = outlet.render(window, cx);
+                })
+                .unwrap();
+            let seen = SEEN.lock().unwrap().clone();
+            assert!(!seen.is_empty());
+            assert!(seen.iter().all(|id| id == "7"), "{seen:?}");
+        }
+    }
+
+    #[cfg(feature = "transition")]
+    mod env {
+        use super::super::RouterOutlet;
+        use crate::resolve::reset_outlet_depth;
+        use crate::transition::Transition;
+        use crate::{init_router, use_route_env, Navigator, Route};
+        use gpui::{div, IntoElement, Render, TestAppContext};
+        use std::sync::Mutex;
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct Accent(&'static str);
+
+        #[gpui::test]
+        fn test_exit_content_keeps_previous_env(cx: &mut TestAppContext) {
+            static SEEN: Mutex<Vec<(&str, Option<Accent>)>> = Mutex::new(Vec::new());
+
+            let record = |path: &'static str, accent: &'static str| {
+                Route::new(path, move |_, cx, _| {
+                    SEEN.lock()
+                        .unwrap()
+                        .push((path, use_route_env::<Accent>(cx)));
+                    div().into_any_element()
+                })
+                .env(Accent(accent))
+                .transition(Transition::fade(300))
+            };
+            cx.update(|cx| {
+                init_router(cx, |router| {
+                    router.add_route(record("/admin", "red"));
+                    router.add_route(record("/viewer", "blue"));
+                });
+            });
+            let window = cx.add_window(|_, _| RouterOutlet::at_depth(0));
+            let render = |cx: &mut TestAppContext| {
+                window
+                    .update(cx, |outlet, window, cx| {
+                        reset_outlet_depth();
+                        let _ = outlet.render(window, cx);
+                    })
+                    .unwrap();
+            };
+
+            cx.update(|cx| Navigator::push(cx, "/admin"));
+            render(cx);
+            cx.update(|cx| Navigator::push(cx, "/viewer"));
+            SEEN.lock().unwrap().clear();
+            render(cx);
+
+            // The outgoing admin page still sees its own accent
+            let seen = SEEN.lock().unwrap().clone();
+            assert!(seen.contains(&("/admin", Some(Accent("red")))), "{seen:?}");
+            assert!(
+                seen.contains(&("/viewer", Some(Accent("blue")))),
+                "{seen:?}"
+            );
+            assert!(
+                !seen.contains(&("/admin", Some(Accent("blue")))),
+                "{seen:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "transition")]
+    mod interruption {
+        use super::super::{OutletContent, RouterOutlet};
+        use crate::route::Route;
+        use crate::route_env::RouteEnv;
+        use crate::transition::Transition;
+        use crate::RouteParams;
+        use gpui::{div, IntoElement};
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+
+        fn content(path: &str) -> OutletContent {
+            OutletContent {
+                route: Arc::new(Route::new(path, |_, _, _| div().into_any_element())),
+                params: RouteParams::new(),
+                env: RouteEnv::default(),
+                key: path.to_string().into(),
+            }
+        }
+
+        /// Render one frame of `path` at `at` ms, returning whether it animates.
+        fn frame(outlet: &mut RouterOutlet, path: &str, start: Instant, at: u64) -> bool {
+            let fade = Transition::fade(300);
+            let now = start + Duration::from_millis(at);
+            outlet
+                .track_transition(path.to_string(), content(path), &fade, now)
+                .is_some()
+        }
+
+        fn exit_path(outlet: &RouterOutlet) -> Option<&str> {
+            outlet
+                .exit
+                .as_ref()
+                .map(|exit| exit.route.config.path.as_str())
+        }
+
+        #[test]
+        fn test_interrupting_navigation_exits_from_entering_content() {
+            let start = Instant::now();
+            let mut outlet = RouterOutlet::new();
+
+            assert!(!frame(&mut outlet, "/a", start, 0));
+            assert!(frame(&mut outlet, "/b", start, 10));
+            assert_eq!(exit_path(&outlet), Some("/a"));
+            assert!(frame(&mut outlet, "/b", start, 100));
+            assert_eq!(exit_path(&outlet), Some("/a"));
+
+            // B → C while A → B is running: B snaps in and exits, A is gone
+            let counter = outlet.animation_counter;
+            assert!(frame(&mut outlet, "/c", start, 150));
+            assert_eq!(exit_path(&outlet), Some("/b"));
+            assert_eq!(outlet.animation_counter, counter + 1);
+
+            // The new animation runs its full duration from the interruption
+            assert!(frame(&mut outlet, "/c", start, 400));
+            assert!(!frame(&mut outlet, "/c", start, 450));
+            assert_eq!(exit_path(&outlet), None);
+        }
+
+        #[test]
+        fn test_unrendered_route_is_never_exit_content() {
+            let start = Instant::now();
+            let mut outlet = RouterOutlet::new();
+            assert!(!frame(&mut outlet, "/a", start, 0));
+
+            // Navigations to /b and then /c land before the next frame
+            assert!(frame(&mut outlet, "/c", start, 10));
+            assert_eq!(exit_path(&outlet), Some("/a"));
+
+            // Rapid back-and-forth: each exit is the content on screen
+            assert!(frame(&mut outlet, "/a", start, 20));
+            assert_eq!(exit_path(&outlet), Some("/c"));
+            assert!(frame(&mut outlet, "/c", start, 30));
+            assert_eq!(exit_path(&outlet), Some("/a"));
+        }
+
+        #[test]
+        fn test_no_transition_cancels_running_animation() {
+            let start = Instant::now();
+            let mut outlet = RouterOutlet::new();
+            assert!(!frame(&mut outlet, "/a", start, 0));
+            assert!(frame(&mut outlet, "/b", start, 10));
+
+            let none = Transition::None;
+            let now = start + Duration::from_millis(20);
+            assert!(outlet
+                .track_transition("/c".to_string(), content("/c"), &none, now)
+                .is_none());
+            assert!(outlet.active_transition.is_none());
+            assert_eq!(exit_path(&outlet), None);
+            assert!(!frame(&mut outlet, "/c", start, 30));
+        }
+    }
+
+    #[cfg(feature = "transition")]
+    mod named {
+        use super::super::{OutletContent, RouterOutlet};
+        use crate::route::Route;
+        use crate::route_env::RouteEnv;
+        use crate::transition::Transition;
+        use crate::RouteParams;
+        use gpui::{div, IntoElement};
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+
+        fn route(path: &str) -> Arc<Route> {
+            Arc::new(Route::new(path, |_, _, _| div().into_any_element()))
+        }
+
+        fn content(route: &Arc<Route>, filter: &str) -> OutletContent {
+            let mut params = RouteParams::new();
+            params.insert("filter", filter);
+            OutletContent {
+                route: Arc::clone(route),
+                key: crate::resolve::entry_key(&route.config.path, &params, 0),
+                params,
+                env: RouteEnv::default(),
+            }
+        }
+
+        /// Render one frame showing `content` at `at` ms, returning whether it animates.
+        fn frame(
+            outlet: &mut RouterOutlet,
+            content: OutletContent,
+            start: Instant,
+            at: u64,
+        ) -> bool {
+            let now = start + Duration::from_millis(at);
+            outlet
+                .track_named_transition(content, &Transition::fade(300), now)
+                .is_some()
+        }
+
+        fn exit_filter(outlet: &RouterOutlet) -> Option<&str> {
+            outlet
+                .exit
+                .as_ref()
+                .and_then(|exit| exit.params.get("filter"))
+                .map(String::as_str)
+        }
+
+        #[test]
+        fn test_same_named_child_does_not_animate_across_paths() {
+            let start = Instant::now();
+            let mut outlet = RouterOutlet::named("sidebar");
+            let filters = route("filters");
+
+            // The path changes (/inbox → /inbox/42) but the sidebar does not
+            assert!(!frame(&mut outlet, content(&filters, "all"), start, 0));
+            assert!(!frame(&mut outlet, content(&filters, "all"), start, 10));
+            assert!(outlet.active_transition.is_none());
+
+            // Same pattern, but another parent's child: the content changed
+            let other = route("filters");
+            assert!(frame(&mut outlet, content(&other, "all"), start, 20));
+        }
+
+        #[test]
+        fn test_named_exit_is_the_previously_resolved_child() {
+            let start = Instant::now();
+            let mut outlet = RouterOutlet::named("sidebar");
+            let filters = route("filters");
+
+            assert!(!frame(&mut outlet, content(&filters, "all"), start, 0));
+            assert!(frame(&mut outlet, content(&filters, "unread"), start, 10));
+            assert_eq!(exit_filter(&outlet), Some("all"));
+            assert!(frame(&mut outlet, content(&filters, "unread"), start, 100));
+            assert_eq!(exit_filter(&outlet), Some("all"));
+
+            // Interrupted: the entering child exits, the one it replaced is gone
+            assert!(frame(&mut outlet, content(&filters, "starred"), start, 150));
+            assert_eq!(exit_filter(&outlet), Some("unread"));
+            assert!(!frame(
+                &mut outlet,
+                content(&filters, "starred"),
+                start,
+                500
+            ));
+            assert_eq!(exit_filter(&outlet), None);
+        }
+    }
+
+    #[cfg(feature = "transition")]
+    mod hold {
+        use super::super::{HoldPhase, HoldState};
+        use std::time::{Duration, Instant};
+
+        const TIMEOUT: Duration = Duration::from_millis(500);
+
+        #[test]
+        fn test_hold_until_ready_then_swap() {
+            let mut hold = HoldState::new();
+            let start = Instant::now();
+
+            assert_eq!(hold.decide("/next", false, TIMEOUT, start), HoldPhase::Hold);
+            let later = start + Duration::from_millis(100);
+            assert_eq!(hold.decide("/next", false, TIMEOUT, later), HoldPhase::Hold);
+            assert_eq!(hold.decide("/next", true, TIMEOUT, later), HoldPhase::Swap);
+            assert!(hold.waiting.is_none());
+        }
+
+        #[test]
+        fn test_hold_times_out_to_loading() {
+            let mut hold = HoldState::new();
+            let start = Instant::now();
+
+            assert_eq!(hold.decide("/slow", false, TIMEOUT, start), HoldPhase::Hold);
+            let expired = start + TIMEOUT;
+            assert_eq!(
+                hold.decide("/slow", false, TIMEOUT, expired),
+                HoldPhase::TimedOut
+            );
+            // Still showing loading until the content is ready
+            let later = expired + Duration::from_millis(100);
+            assert_eq!(
+                hold.decide("/slow", false, TIMEOUT, later),
+                HoldPhase::TimedOut
+            );
+            assert_eq!(hold.decide("/slow", true, TIMEOUT, later), HoldPhase::Swap);
+        }
+
+        #[test]
+        fn test_hold_restarts_for_new_target() {
+            let mut hold = HoldState::new();
+            let start = Instant::now();
+
+            assert_eq!(hold.decide("/a", false, TIMEOUT, start), HoldPhase::Hold);
+            let later = start + TIMEOUT;
+            // A different target gets its own timeout window
+            assert_eq!(hold.decide("/b", false, TIMEOUT, later), HoldPhase::Hold);
+            assert_eq!(
+                hold.decide("/b", false, TIMEOUT, later + TIMEOUT),
+                HoldPhase::TimedOut
+            );
+        }
+
+        #[test]
+        fn test_hold_ready_immediately_swaps() {
+            let mut hold = HoldState::new();
+            assert_eq!(
+                hold.decide("/fast", true, TIMEOUT, Instant::now()),
+                HoldPhase::Swap
+            );
+            assert!(hold.waiting.is_none());
+        }
+    }
+
+    #[test]
+    fn test_router_link_prefetch_flag() {
+        let link = RouterLink::new("/heavy");
+        assert!(!link.prefetch_on_hover);
+
+        let link = link.prefetch_on_hover(true);
+        assert!(link.prefetch_on_hover);
+    }
+
+    #[test]
+    fn test_router_link_new_window_modifier() {
+        use crate::Modifier;
+        use gpui::Modifiers;
+
+        let ctrl = Modifiers {
+            control: true,
+            ..Modifiers::default()
+        };
+        let link = RouterLink::new("/document/42");
+        assert!(!RouterLink::opens_new_window(link.new_window_on, ctrl));
+
+        let link = link.open_in_new_window_on(Modifier::Ctrl);
+        assert!(RouterLink::opens_new_window(link.new_window_on, ctrl));
+        assert!(!RouterLink::opens_new_window(
+            link.new_window_on,
+            Modifiers::default()
+        ));
+        assert!(!RouterLink::opens_new_window(
+            link.new_window_on,
+            Modifiers {
+                alt: true,
+                ..Modifiers::default()
+            }
+        ));
+    }
+
+    #[test]
+    fn test_router_link_style_runs_before_active_class() {
+        use gpui::{div, rgb, Styled};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let (style_calls, active_calls) = (Rc::clone(&calls), Rc::clone(&calls));
+        let link = RouterLink::new("/inbox")
+            .style(move |div| {
+                style_calls.borrow_mut().push("style");
+                div.px_3().py_2().rounded_md()
+            })
+            .active_class(move |div| {
+                active_calls.borrow_mut().push("active");
+                div.bg(rgb(0x21_96_f3))
+            });
+
+        let _ = link.decorate(div(), false);
+        assert_eq!(*calls.borrow(), ["style"]);
+
+        calls.borrow_mut().clear();
+        let _ = link.decorate(div(), true);
+        assert_eq!(*calls.borrow(), ["style", "active"]);
+    }
+
+    #[test]
+    fn test_router_link_blocked_states() {
+        use super::LinkState;
+        use crate::NavigationProbe;
+
+        let allowed = NavigationProbe {
+            allowed: true,
+            reason: None,
+            redirect: None,
+            challenge: None,
+        };
+        let denied = NavigationProbe {
+            allowed: false,
+            reason: Some("Admins only".to_string()),
+            ..allowed.clone()
+        };
+        let redirected = NavigationProbe {
+            allowed: false,
+            redirect: Some("/login".to_string()),
+            ..allowed.clone()
+        };
+        let challenged = NavigationProbe {
+            challenge: Some("confirm".to_string()),
+            ..allowed.clone()
+        };
+
+        // Plain links never probe and always render
+        let plain = RouterLink::new("/admin");
+        assert_eq!(plain.state(None), LinkState::Enabled);
+        assert_eq!(plain.state(Some(&denied)), LinkState::Enabled);
+
+        let hide = RouterLink::new("/admin").hide_if_blocked();
+        assert_eq!(hide.state(Some(&allowed)), LinkState::Enabled);
+        assert_eq!(hide.state(Some(&challenged)), LinkState::Enabled);
+        assert_eq!(hide.state(Some(&denied)), LinkState::Hidden);
+        assert_eq!(hide.state(Some(&redirected)), LinkState::Hidden);
+
+        let disable = RouterLink::new("/admin").disable_if_blocked(true);
+        assert_eq!(disable.state(Some(&allowed)), LinkState::Enabled);
+        assert_eq!(
+            disable.state(Some(&denied)),
+            LinkState::Disabled {
+                tooltip: Some("Admins only".into())
+            }
+        );
+        assert_eq!(
+            disable.state(Some(&redirected)),
+            LinkState::Disabled {
+                tooltip: Some("Redirects to /login".into())
+            }
+        );
+
+        let silent = RouterLink::new("/admin").disable_if_blocked(false);
+        assert_eq!(
+            silent.state(Some(&denied)),
+            LinkState::Disabled { tooltip: None }
+        );
+    }
 }
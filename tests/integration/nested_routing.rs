@@ -134,8 +134,8 @@ mod nested_routing_integration {
     // Actual behavior: GPUI's use_keyed_state preserves entity across navigation
     //
     // ARCHITECTURE VERIFICATION:
-    // - Route::component() uses use_keyed_state with key "route:{path}" (src/route.rs:402)
-    // - Route::component_with_params() uses "route:{path}?{params}" (src/route.rs:461)
+    // - Route::component() caches under "route:{type}@{depth}:{pattern}", the entry key without params
+    // - Route::component_with_params() caches under "route:{type}@{entry key}"
     // - GPUI manages entity lifecycle and LRU eviction internally
     // - See examples/nested_demo.rs:211-371 for CounterPage implementation
     //
@@ -0,0 +1,134 @@
+//! Navigation span tests
+//!
+//! Installs a capturing `log` logger (one per test binary, so these tests live
+//! in their own crate) and checks that every line emitted by a navigation
+//! carries its id, with redirect restarts counted as hops.
+
+#![cfg(all(feature = "log", feature = "guard"))]
+#![allow(clippy::needless_pass_by_ref_mut)]
+
+use gpui::{div, IntoElement, ParentElement, TestAppContext};
+use gpui_navigator::*;
+use std::sync::{Arc, Mutex, Once};
+use std::thread::{self, ThreadId};
+
+struct CaptureLogger;
+
+static LINES: Mutex<Vec<(ThreadId, String)>> = Mutex::new(Vec::new());
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        LINES
+            .lock()
+            .unwrap()
+            .push((thread::current().id(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the logger and drop anything this thread logged so far.
+fn start_capture() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        log::set_logger(&CaptureLogger).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+    });
+    take_lines();
+}
+
+/// Return and clear the lines logged by this thread.
+fn take_lines() -> Vec<String> {
+    let me = thread::current().id();
+    let mine: Vec<_> = {
+        let mut lines = LINES.lock().unwrap();
+        let (mine, rest) = lines.drain(..).partition(|(id, _)| *id == me);
+        *lines = rest;
+        mine
+    };
+    mine.into_iter().map(|(_, line)| line).collect()
+}
+
+/// Parse the `[nav#id]` / `[nav#id.hop]` prefix of a line.
+fn span_of(line: &str) -> Option<(u32, u32)> {
+    let rest = line.strip_prefix("[nav#")?;
+    let tag = &rest[..rest.find(']')?];
+    Some(match tag.split_once('.') {
+        Some((id, hop)) => (id.parse().ok()?, hop.parse().ok()?),
+        None => (tag.parse().ok()?, 0),
+    })
+}
+
+fn page(path: &str) -> Route {
+    Route::new(path, |_, _, _| div().child("page").into_any_element())
+}
+
+#[gpui::test]
+fn test_navigation_lines_share_id_and_count_redirect_hops(cx: &mut TestAppContext) {
+    let seen = Arc::new(Mutex::new(None));
+    let seen_in_guard = Arc::clone(&seen);
+    cx.update(|cx| {
+        init_router(cx, |router| {
+            router.add_route(page("/"));
+            router.add_route(page("/a").guard(guard_fn(|_, _| NavigationAction::redirect("/b"))));
+            router.add_route(page("/b").guard(guard_fn(|_, _| NavigationAction::redirect("/c"))));
+            router.add_route(page("/c").guard(guard_fn(move |_, _| {
+                *seen_in_guard.lock().unwrap() = GlobalRouter::current_navigation_id();
+                NavigationAction::Continue
+            })));
+        });
+    });
+
+    start_capture();
+    cx.update(|cx| Navigator::push(cx, "/a"));
+    let lines = take_lines();
+    assert_eq!(cx.read(Navigator::current_path), "/c");
+
+    // The `Navigator` wrapper logs before the router starts the navigation
+    let spans: Vec<(u32, u32)> = lines
+        .iter()
+        .filter(|line| !line.starts_with("Navigator::"))
+        .map(|line| span_of(line).unwrap_or_else(|| panic!("untagged line: {line}")))
+        .collect();
+    let id = spans[0].0;
+    assert!(spans.iter().all(|(line_id, _)| *line_id == id));
+
+    // Hops only move forward: 0 for /a, 1 for the redirect to /b, 2 for /c
+    let mut hops: Vec<u32> = spans.iter().map(|(_, hop)| *hop).collect();
+    assert!(hops.windows(2).all(|pair| pair[0] <= pair[1]));
+    hops.dedup();
+    assert_eq!(hops, [0, 1, 2]);
+
+    let seen = seen
+        .lock()
+        .unwrap()
+        .expect("guard ran inside the navigation");
+    assert_eq!((seen.id(), seen.hop()), (id, 2));
+    assert_eq!(seen.to_string(), format!("nav#{id}.2"));
+    assert!(GlobalRouter::current_navigation_id().is_none());
+}
+
+#[gpui::test]
+fn test_each_navigation_gets_a_new_id(cx: &mut TestAppContext) {
+    cx.update(|cx| {
+        init_router(cx, |router| {
+            router.add_route(page("/"));
+            router.add_route(page("/one"));
+            router.add_route(page("/two"));
+        });
+    });
+
+    start_capture();
+    cx.update(|cx| Navigator::push(cx, "/one"));
+    let first = take_lines().iter().find_map(|line| span_of(line)).unwrap();
+    cx.update(|cx| Navigator::push(cx, "/two"));
+    let second = take_lines().iter().find_map(|line| span_of(line)).unwrap();
+
+    assert_eq!(first.1, 0);
+    assert_eq!(second.1, 0);
+    assert!(second.0 > first.0);
+}
@@ -0,0 +1,185 @@
+//! Property tests for `MatchStack` resolution.
+//!
+//! Generates random route trees (static, param, wildcard and index routes,
+//! bounded depth and width) and random — often malformed — paths, and checks
+//! the guarantees documented on [`resolve_match_stack`]:
+//!
+//! - resolution never panics and never exceeds [`MAX_DEPTH`] levels
+//! - depths are contiguous from 0 and each entry is a child of the previous
+//! - params at depth `d` are a superset of the params at depth `d - 1`
+//! - no param captures an empty value
+//! - resolution is idempotent, and `normalize_path(path)` resolves like `path`
+//! - empty segments are ignored: `/a//b` resolves like `/a/b`
+
+use gpui::{div, AnyElement, App, IntoElement, ParentElement, Window};
+use gpui_navigator::resolve::{resolve_match_stack, MatchStack, MAX_DEPTH};
+use gpui_navigator::route::Route;
+use gpui_navigator::{normalize_path, RouteParams};
+use proptest::prelude::*;
+use std::sync::Arc;
+
+fn dummy(_window: &mut Window, _cx: &mut App, _params: &RouteParams) -> AnyElement {
+    div().child("test").into_any_element()
+}
+
+/// Route tree description, turned into real routes by [`build`].
+#[derive(Debug, Clone)]
+struct RouteSpec {
+    path: String,
+    children: Vec<Self>,
+}
+
+fn build(spec: &RouteSpec) -> Arc<Route> {
+    let route = Route::new(spec.path.clone(), dummy);
+    if spec.children.is_empty() {
+        Arc::new(route)
+    } else {
+        Arc::new(route.children(spec.children.iter().map(build).collect()))
+    }
+}
+
+fn route_path() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just(String::new()),
+        Just("index".to_string()),
+        Just("*".to_string()),
+        "[abc]{1,2}",
+        "[abc]{1,2}/[abc]{1,2}",
+        "[abc]/:id",
+        ":slug",
+        ":id",
+    ]
+}
+
+fn route_tree() -> impl Strategy<Value = RouteSpec> {
+    let leaf = route_path().prop_map(|path| RouteSpec {
+        path,
+        children: Vec::new(),
+    });
+    leaf.prop_recursive(4, 48, 4, |inner| {
+        (route_path(), prop::collection::vec(inner, 1..4))
+            .prop_map(|(path, children)| RouteSpec { path, children })
+    })
+}
+
+fn routes() -> impl Strategy<Value = Vec<Arc<Route>>> {
+    prop::collection::vec(route_tree(), 1..5).prop_map(|specs| specs.iter().map(build).collect())
+}
+
+fn request_path() -> impl Strategy<Value = String> {
+    let segment = prop_oneof![
+        Just(String::new()),
+        Just("..".to_string()),
+        Just("..%2f".to_string()),
+        Just("\0".to_string()),
+        Just("🦀".to_string()),
+        Just("index".to_string()),
+        Just("*".to_string()),
+        "[abc]{1,2}",
+        "[0-9]{1,3}",
+    ];
+    (
+        prop::collection::vec(segment, 0..24),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(|(segments, leading, trailing)| {
+            let mut path = segments.join("/");
+            if leading {
+                path.insert(0, '/');
+            }
+            if trailing {
+                path.push('/');
+            }
+            path
+        })
+}
+
+/// Route identity, depth, sorted params, pattern and URL of one entry.
+type EntrySignature = (usize, usize, Vec<(String, String)>, String, String);
+
+/// Comparable summary of a stack.
+fn signature(stack: &MatchStack) -> Vec<EntrySignature> {
+    stack
+        .entries()
+        .iter()
+        .map(|entry| {
+            let mut params: Vec<_> = entry
+                .params
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            params.sort();
+            (
+                Arc::as_ptr(&entry.route) as usize,
+                entry.depth,
+                params,
+                entry.accumulated_pattern().to_string(),
+                entry.accumulated_url().to_string(),
+            )
+        })
+        .collect()
+}
+
+fn assert_consistent(routes: &[Arc<Route>], stack: &MatchStack) {
+    assert!(stack.len() <= MAX_DEPTH, "stack deeper than MAX_DEPTH");
+
+    let mut siblings = routes;
+    let mut previous: Option<&RouteParams> = None;
+    for (index, entry) in stack.entries().iter().enumerate() {
+        assert_eq!(entry.depth, index, "depths must be contiguous from 0");
+        assert!(
+            siblings
+                .iter()
+                .any(|route| Arc::ptr_eq(route, &entry.route)),
+            "entry at depth {index} is not a child of the previous entry"
+        );
+        if let Some(parent) = previous {
+            for (key, _) in parent.iter() {
+                assert!(
+                    entry.params.contains(key),
+                    "param '{key}' lost at depth {index}"
+                );
+            }
+        }
+        assert!(
+            entry.params.iter().all(|(_, value)| !value.is_empty()),
+            "empty param value at depth {index}"
+        );
+        previous = Some(&entry.params);
+        siblings = &entry.route.children;
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn resolution_is_consistent(routes in routes(), path in request_path()) {
+        let stack = resolve_match_stack(&routes, &path);
+        assert_consistent(&routes, &stack);
+    }
+
+    #[test]
+    fn resolution_is_idempotent(routes in routes(), path in request_path()) {
+        let first = resolve_match_stack(&routes, &path);
+        let second = resolve_match_stack(&routes, &path);
+        prop_assert_eq!(signature(&first), signature(&second));
+    }
+
+    #[test]
+    fn normalized_path_resolves_identically(routes in routes(), path in request_path()) {
+        let raw = resolve_match_stack(&routes, &path);
+        let normalized = resolve_match_stack(&routes, &normalize_path(&path));
+        prop_assert_eq!(signature(&raw), signature(&normalized));
+    }
+
+    #[test]
+    fn empty_segments_are_ignored(routes in routes(), path in request_path()) {
+        let collapsed: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let collapsed = format!("/{}", collapsed.join("/"));
+        let raw = resolve_match_stack(&routes, &path);
+        let clean = resolve_match_stack(&routes, &collapsed);
+        prop_assert_eq!(signature(&raw), signature(&clean));
+    }
+}
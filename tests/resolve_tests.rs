@@ -6,7 +6,7 @@
 use gpui::{div, AnyElement, App, IntoElement, ParentElement, Window};
 use gpui_navigator::resolve::*;
 use gpui_navigator::route::Route;
-use gpui_navigator::RouteParams;
+use gpui_navigator::{RouteParams, RouteTreeError};
 use std::sync::Arc;
 
 fn dummy(_window: &mut Window, _cx: &mut App, _params: &RouteParams) -> AnyElement {
@@ -96,6 +96,79 @@ fn test_root_layout_with_children() {
     assert_eq!(stack.at_depth(2).unwrap().route.config.path, "settings");
 }
 
+#[test]
+fn test_root_as_leaf() {
+    let routes = vec![
+        Arc::new(Route::new("/", dummy)),
+        Arc::new(Route::new("/foo", dummy)),
+    ];
+
+    let stack = resolve_match_stack(&routes, "/");
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack.leaf_pattern(), Some("/"));
+
+    // A `/` page does not wrap its siblings
+    let stack = resolve_match_stack(&routes, "/foo");
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack.at_depth(0).unwrap().route.config.path, "/foo");
+
+    let routes = vec![Arc::new(Route::new("/", dummy))];
+    assert!(resolve_match_stack(&routes, "/foo").is_empty());
+}
+
+#[test]
+fn test_root_as_layout_with_index() {
+    for index in ["", "index"] {
+        let routes = vec![Arc::new(Route::new("/", dummy).children(vec![
+            Arc::new(Route::new("foo", dummy)),
+            Arc::new(Route::new(index, dummy)),
+        ]))];
+
+        // The index child fills the root layout's outlet
+        let stack = resolve_match_stack(&routes, "/");
+        assert_eq!(stack.len(), 2, "index {index:?}");
+        assert_eq!(stack.at_depth(0).unwrap().route.config.path, "/");
+        assert_eq!(stack.at_depth(1).unwrap().route.config.path, index);
+        assert_eq!(stack.leaf_pattern(), Some("/"));
+        assert_eq!(stack.leaf_url(), Some("/"));
+    }
+
+    // Without an index child the stack ends at the layout
+    let routes = vec![Arc::new(
+        Route::new("/", dummy).children(vec![Arc::new(Route::new("foo", dummy))]),
+    )];
+    let stack = resolve_match_stack(&routes, "/");
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack.at_depth(0).unwrap().route.config.path, "/");
+}
+
+#[test]
+fn test_root_as_layout_wrapping_foo() {
+    for child in ["foo", "/foo"] {
+        let routes = vec![Arc::new(Route::new("/", dummy).children(vec![
+            Arc::new(Route::new("", dummy)),
+            Arc::new(Route::new(child, dummy)),
+        ]))];
+
+        let stack = resolve_match_stack(&routes, "/foo");
+        assert_eq!(stack.len(), 2, "child {child:?}");
+        assert_eq!(stack.at_depth(0).unwrap().route.config.path, "/");
+        assert_eq!(stack.at_depth(0).unwrap().accumulated_url(), "/");
+        assert_eq!(stack.at_depth(1).unwrap().route.config.path, child);
+        assert_eq!(stack.leaf_pattern(), Some("/foo"));
+        assert_eq!(stack.leaf_url(), Some("/foo"));
+    }
+
+    // Paths the layout's children miss fall through to top-level siblings
+    let routes = vec![
+        Arc::new(Route::new("/", dummy).children(vec![Arc::new(Route::new("foo", dummy))])),
+        Arc::new(Route::new("/bar", dummy)),
+    ];
+    let stack = resolve_match_stack(&routes, "/bar");
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack.at_depth(0).unwrap().route.config.path, "/bar");
+}
+
 #[test]
 fn test_parameter_routes() {
     let routes = vec![Arc::new(Route::new("/users/:id", dummy))];
@@ -108,6 +181,65 @@ fn test_parameter_routes() {
     );
 }
 
+#[test]
+fn test_encoded_parameter_is_decoded() {
+    let routes = vec![Arc::new(Route::new("/users/:id", dummy))];
+
+    let stack = resolve_match_stack(&routes, "/users/%20bob");
+    let entry = stack.at_depth(0).unwrap();
+    assert_eq!(entry.params.get("id"), Some(&" bob".to_string()));
+    // The URL keeps what was matched
+    assert_eq!(entry.accumulated_url(), "/users/%20bob");
+
+    let collapsed = resolve_match_stack(&routes, "/users//42");
+    assert_eq!(
+        collapsed.at_depth(0).unwrap().params.get("id"),
+        Some(&"42".to_string())
+    );
+}
+
+#[test]
+fn test_suffix_routes_fall_through() {
+    let routes = vec![
+        Arc::new(Route::new("/files/:name.pdf", dummy)),
+        Arc::new(Route::new("/files/:name.md", dummy)),
+        Arc::new(Route::new("/files/:name", dummy)),
+    ];
+
+    let resolved = |path: &str| {
+        let stack = resolve_match_stack(&routes, path);
+        let entry = stack.at_depth(0).unwrap();
+        (
+            entry.route.config.path.clone(),
+            entry.params.get("name").cloned().unwrap(),
+        )
+    };
+    assert_eq!(
+        resolved("/files/report.pdf"),
+        ("/files/:name.pdf".to_string(), "report".to_string())
+    );
+    assert_eq!(
+        resolved("/files/notes.md"),
+        ("/files/:name.md".to_string(), "notes".to_string())
+    );
+    assert_eq!(
+        resolved("/files/data.csv"),
+        ("/files/:name".to_string(), "data.csv".to_string())
+    );
+}
+
+#[test]
+fn test_query_string_ignored_when_matching() {
+    let routes = vec![Arc::new(
+        Route::new("/users", dummy).child(Route::new(":id", dummy)),
+    )];
+
+    let stack = resolve_match_stack(&routes, "/users/42?tab=posts");
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack.params().get("id"), Some(&"42".to_string()));
+    assert_eq!(resolve_match_stack(&routes, "/users?page=2").len(), 1);
+}
+
 #[test]
 fn test_nested_parameters() {
     let routes = vec![Arc::new(
@@ -257,6 +389,32 @@ fn test_depth_tracking_basic() {
     assert_eq!(current_parent_depth(), Some(2));
 }
 
+#[test]
+fn test_depth_guard_restores_only_on_panic() {
+    reset_outlet_depth();
+    set_parent_depth(1);
+
+    // A builder that set a deeper level and then panicked
+    let result = std::panic::catch_unwind(|| {
+        let _guard = OutletDepthGuard::new();
+        set_parent_depth(4);
+        panic!("builder failed");
+    });
+    assert!(result.is_err());
+    assert_eq!(current_parent_depth(), Some(1));
+
+    // Normal exit keeps the depth the builder set for deferred children
+    {
+        let _guard = OutletDepthGuard::new();
+        set_parent_depth(2);
+    }
+    assert_eq!(current_parent_depth(), Some(2));
+
+    reset_outlet_depth();
+    reset_outlet_depth();
+    assert_eq!(current_parent_depth(), None);
+}
+
 // ---- Pattern 1: router_view + outlets (nested routing) ----
 
 #[test]
@@ -443,3 +601,723 @@ fn test_empty_match_stack() {
     assert!(stack.max_depth().is_none());
     assert!(stack.params().is_empty());
 }
+
+// ---- accumulated pattern / URL tests ----
+
+#[test]
+fn test_accumulated_pattern_with_params_at_multiple_depths() {
+    let routes = vec![Arc::new(Route::new("/users/:userId", dummy).children(
+        vec![Arc::new(Route::new("posts/:postId", dummy).children(vec![
+            Arc::new(Route::new("comments", dummy)),
+        ]))],
+    ))];
+
+    let stack = resolve_match_stack(&routes, "/users/42/posts/7/comments");
+    assert_eq!(stack.len(), 3);
+
+    let patterns: Vec<&str> = stack
+        .entries()
+        .iter()
+        .map(MatchEntry::accumulated_pattern)
+        .collect();
+    assert_eq!(
+        patterns,
+        vec![
+            "/users/:userId",
+            "/users/:userId/posts/:postId",
+            "/users/:userId/posts/:postId/comments",
+        ]
+    );
+
+    let urls: Vec<&str> = stack
+        .entries()
+        .iter()
+        .map(MatchEntry::accumulated_url)
+        .collect();
+    assert_eq!(
+        urls,
+        vec![
+            "/users/42",
+            "/users/42/posts/7",
+            "/users/42/posts/7/comments"
+        ]
+    );
+
+    assert_eq!(
+        stack.leaf_pattern(),
+        Some("/users/:userId/posts/:postId/comments")
+    );
+    assert_eq!(stack.leaf_url(), Some("/users/42/posts/7/comments"));
+}
+
+#[test]
+fn test_accumulated_pattern_multi_segment_child() {
+    let routes =
+        vec![Arc::new(Route::new("/api/v1", dummy).children(vec![
+            Arc::new(Route::new("users/:id/edit", dummy)),
+        ]))];
+
+    let stack = resolve_match_stack(&routes, "/api/v1/users/5/edit");
+    assert_eq!(stack.at_depth(0).unwrap().accumulated_pattern(), "/api/v1");
+    assert_eq!(stack.leaf_pattern(), Some("/api/v1/users/:id/edit"));
+    assert_eq!(stack.leaf_url(), Some("/api/v1/users/5/edit"));
+}
+
+#[test]
+fn test_accumulated_pattern_pathless_layout() {
+    let routes = vec![Arc::new(Route::new("/", dummy).children(vec![Arc::new(
+        Route::new("", dummy).children(vec![Arc::new(Route::new("items/:id", dummy))]),
+    )]))];
+
+    let stack = resolve_match_stack(&routes, "/items/3");
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack.at_depth(0).unwrap().accumulated_pattern(), "/");
+    // Pathless layout contributes nothing
+    assert_eq!(stack.at_depth(1).unwrap().accumulated_pattern(), "/");
+    assert_eq!(stack.at_depth(1).unwrap().accumulated_url(), "/");
+    assert_eq!(stack.leaf_pattern(), Some("/items/:id"));
+    assert_eq!(stack.leaf_url(), Some("/items/3"));
+}
+
+#[test]
+fn test_accumulated_pattern_index_route_inherits_parent() {
+    let routes = vec![Arc::new(Route::new("/projects/:id", dummy).children(vec![
+        Arc::new(Route::new("", dummy)),
+        Arc::new(Route::new("settings", dummy)),
+    ]))];
+
+    let stack = resolve_match_stack(&routes, "/projects/9");
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack.at_depth(1).unwrap().route.config.path, "");
+    assert_eq!(stack.leaf_pattern(), Some("/projects/:id"));
+    assert_eq!(stack.leaf_url(), Some("/projects/9"));
+}
+
+#[test]
+fn test_accumulated_pattern_empty_stack() {
+    let stack = MatchStack::new();
+    assert!(stack.leaf_pattern().is_none());
+    assert!(stack.leaf_url().is_none());
+}
+
+#[test]
+fn test_params_at_depth_three_level_tree() {
+    let routes = vec![Arc::new(Route::new("/orgs/:org", dummy).children(vec![
+        Arc::new(
+            Route::new("projects/:project", dummy)
+                .children(vec![Arc::new(Route::new("issues/:issue", dummy))]),
+        ),
+    ]))];
+
+    let stack = resolve_match_stack(&routes, "/orgs/acme/projects/gpui/issues/7");
+    assert_eq!(stack.len(), 3);
+
+    let root = stack.params_at_depth(0).unwrap();
+    assert_eq!(root.get("org"), Some(&"acme".to_string()));
+    assert!(root.get("project").is_none());
+
+    let mid = stack.params_at_depth(1).unwrap();
+    assert_eq!(mid.get("org"), Some(&"acme".to_string()));
+    assert_eq!(mid.get("project"), Some(&"gpui".to_string()));
+    assert!(mid.get("issue").is_none());
+
+    let leaf = stack.params();
+    assert_eq!(leaf.get("issue"), Some(&"7".to_string()));
+    assert_eq!(leaf.get("project"), Some(&"gpui".to_string()));
+
+    assert!(stack.params_at_depth(3).is_none());
+}
+
+// ---- adversarial paths ----
+//
+// Deep links are user input. Resolution never panics, never builds a stack
+// deeper than MAX_DEPTH, ignores empty segments and never decodes or
+// interprets segments: `..`, `%2f`, null bytes and emoji are literal text.
+
+#[test]
+fn test_only_slashes_resolves_as_root() {
+    let routes = vec![Arc::new(Route::new("/", dummy))];
+
+    let stack = resolve_match_stack(&routes, "///");
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack.leaf_url(), Some("/"));
+}
+
+#[test]
+fn test_empty_segments_are_ignored() {
+    let routes = vec![Arc::new(
+        Route::new("/a", dummy).children(vec![Arc::new(Route::new(":id", dummy))]),
+    )];
+
+    let stack = resolve_match_stack(&routes, "/a//b");
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack.params().get("id"), Some(&"b".to_string()));
+    assert_eq!(stack.leaf_url(), Some("/a/b"));
+
+    // An empty segment is never captured as a param value
+    let stack = resolve_match_stack(&routes, "/a//");
+    assert_eq!(stack.len(), 1);
+    assert!(stack.params().get("id").is_none());
+}
+
+#[test]
+fn test_null_bytes_are_literal() {
+    let routes = vec![
+        Arc::new(Route::new("/a", dummy)),
+        Arc::new(Route::new("/files/:name", dummy)),
+    ];
+
+    assert!(resolve_match_stack(&routes, "/a\0").is_empty());
+
+    let stack = resolve_match_stack(&routes, "/files/x\0y");
+    assert_eq!(stack.params().get("name"), Some(&"x\0y".to_string()));
+}
+
+#[test]
+fn test_ten_thousand_segments_is_bounded() {
+    let mut chain = Route::new("a", dummy);
+    for _ in 0..20 {
+        chain = Route::new("a", dummy).children(vec![Arc::new(chain)]);
+    }
+    let routes = vec![Arc::new(chain), Arc::new(Route::new("/", dummy))];
+    let path = "/a".repeat(10_000);
+
+    // The chain is deeper than MAX_DEPTH, so no complete match exists
+    let stack = resolve_match_stack(&routes, &path);
+    assert!(stack.is_empty());
+
+    let stack = resolve_match_stack(&routes, &"/a".repeat(MAX_DEPTH));
+    assert_eq!(stack.len(), MAX_DEPTH);
+}
+
+#[test]
+fn test_index_chain_stops_at_max_depth() {
+    let mut chain = Route::new("", dummy);
+    for _ in 0..20 {
+        chain = Route::new("", dummy).children(vec![Arc::new(chain)]);
+    }
+    let routes = vec![Arc::new(
+        Route::new("/", dummy).children(vec![Arc::new(chain)]),
+    )];
+
+    let stack = resolve_match_stack(&routes, "/");
+    assert_eq!(stack.len(), MAX_DEPTH);
+}
+
+#[test]
+fn test_traversal_segments_are_not_interpreted() {
+    let routes = vec![
+        Arc::new(Route::new("/secret", dummy)),
+        Arc::new(Route::new("/public/:file", dummy)),
+    ];
+
+    // `..` does not climb: there is no route for /public/../secret
+    assert!(resolve_match_stack(&routes, "/public/../secret").is_empty());
+
+    // Percent-encoding is not decoded: `%2f` is not a separator
+    let stack = resolve_match_stack(&routes, "/public/..%2fsecret");
+    assert_eq!(stack.leaf_pattern(), Some("/public/:file"));
+    assert_eq!(stack.params().get("file"), Some(&"..%2fsecret".to_string()));
+}
+
+#[test]
+fn test_emoji_segments() {
+    let routes = vec![
+        Arc::new(Route::new("/🦀", dummy)),
+        Arc::new(Route::new("/users/:id", dummy)),
+    ];
+
+    let stack = resolve_match_stack(&routes, "/🦀");
+    assert_eq!(stack.leaf_pattern(), Some("/🦀"));
+
+    let stack = resolve_match_stack(&routes, "/users/🦀");
+    assert_eq!(stack.params().get("id"), Some(&"🦀".to_string()));
+}
+
+#[test]
+fn test_named_outlet_ignores_empty_segments() {
+    let routes = vec![Arc::new(
+        Route::new("/app", dummy)
+            .children(vec![Arc::new(Route::new("details", dummy))])
+            .named_outlet("side", vec![Arc::new(Route::new(":panel", dummy))]),
+    )];
+
+    let stack = resolve_match_stack(&routes, "/app//details");
+    assert_eq!(stack.len(), 2);
+
+    let (_, params) = resolve_named_outlet(&stack, 1, "side", "/app//details").unwrap();
+    assert_eq!(params.get("panel"), Some(&"details".to_string()));
+}
+
+// ---- param namespaces ----
+
+/// `comments/:id` subtree mounted under `/posts/:id`, configured by `configure`.
+fn comments_under_posts(configure: impl Fn(Route) -> Route) -> Vec<Arc<Route>> {
+    let comments = configure(Route::new("comments/:id", dummy))
+        .children(vec![Arc::new(Route::new("replies/:replyId", dummy))]);
+    vec![Arc::new(
+        Route::new("/posts/:id", dummy).children(vec![Arc::new(comments)]),
+    )]
+}
+
+#[test]
+fn test_shadowed_param_stays_reachable_through_ancestor() {
+    let routes = comments_under_posts(|route| route);
+
+    let stack = resolve_match_stack(&routes, "/posts/1/comments/9");
+    let comment = stack.at_depth(1).unwrap();
+    assert_eq!(comment.params.get("id"), Some(&"9".to_string()));
+    assert_eq!(comment.params.ancestor("id"), Some(&"1".to_string()));
+    assert_eq!(comment.params.ancestor_at(0, "id"), Some(&"1".to_string()));
+    assert_eq!(comment.own_params().get("id"), Some(&"9".to_string()));
+    assert_eq!(comment.own_params().len(), 1);
+}
+
+#[test]
+fn test_param_prefix_applies_to_subtree() {
+    let routes = comments_under_posts(|route| route.param_prefix("comment"));
+
+    let stack = resolve_match_stack(&routes, "/posts/1/comments/9/replies/3");
+    let reply = stack.at_depth(2).unwrap();
+    assert_eq!(reply.params.get("id"), Some(&"1".to_string()));
+    assert_eq!(reply.params.get("comment.id"), Some(&"9".to_string()));
+    assert_eq!(reply.params.get("comment.replyId"), Some(&"3".to_string()));
+    assert!(reply.params.get("replyId").is_none());
+    assert_eq!(
+        stack.at_depth(1).unwrap().own_params().get("comment.id"),
+        Some(&"9".to_string())
+    );
+}
+
+#[test]
+fn test_isolated_subtree_does_not_inherit() {
+    let routes = comments_under_posts(|route| route.isolate_params(true));
+
+    let stack = resolve_match_stack(&routes, "/posts/1/comments/9/replies/3");
+    let comment = stack.at_depth(1).unwrap();
+    assert_eq!(comment.params.len(), 1);
+    assert_eq!(comment.params.get("id"), Some(&"9".to_string()));
+    assert_eq!(comment.params.ancestor("id"), Some(&"1".to_string()));
+
+    // Descendants inherit from the isolated route, not from above it
+    let reply = stack.at_depth(2).unwrap();
+    assert_eq!(reply.params.get("id"), Some(&"9".to_string()));
+    assert_eq!(reply.params.get("replyId"), Some(&"3".to_string()));
+    assert_eq!(reply.params.ancestor_at(0, "id"), Some(&"1".to_string()));
+    assert_eq!(reply.params.ancestor_at(1, "id"), Some(&"9".to_string()));
+}
+
+#[test]
+fn test_merged_params_unchanged_without_namespacing() {
+    let routes = vec![Arc::new(Route::new("/users/:userId", dummy).children(
+        vec![
+            Arc::new(Route::new("", dummy)),
+            Arc::new(Route::new("posts/:postId", dummy)),
+        ],
+    ))];
+
+    let stack = resolve_match_stack(&routes, "/users/42/posts/7");
+    let post = stack.at_depth(1).unwrap();
+    let mut merged: Vec<_> = post.params.iter().collect();
+    merged.sort();
+    assert_eq!(
+        merged,
+        [
+            (&"postId".to_string(), &"7".to_string()),
+            (&"userId".to_string(), &"42".to_string()),
+        ]
+    );
+
+    // Index routes inherit everything and capture nothing
+    let stack = resolve_match_stack(&routes, "/users/42");
+    let index = stack.at_depth(1).unwrap();
+    assert_eq!(index.params.get("userId"), Some(&"42".to_string()));
+    assert!(index.own_params().is_empty());
+}
+
+// ---- common_ancestor_depth ----
+
+fn layout_routes() -> Vec<Arc<Route>> {
+    vec![
+        Arc::new(Route::new("/a", dummy).children(vec![
+            Arc::new(Route::new("x", dummy)),
+            Arc::new(Route::new("y", dummy)),
+        ])),
+        Arc::new(
+            Route::new("/users/:id", dummy).children(vec![Arc::new(Route::new("posts", dummy))]),
+        ),
+        Arc::new(Route::new("/b", dummy)),
+    ]
+}
+
+#[test]
+fn test_common_ancestor_depth_fully_shared() {
+    let routes = layout_routes();
+    let first = resolve_match_stack(&routes, "/a/x");
+    let second = resolve_match_stack(&routes, "/a/x");
+    assert_eq!(first.common_ancestor_depth(&second), 2);
+    assert_eq!(first.common_ancestor_depth(&first), first.len());
+}
+
+#[test]
+fn test_common_ancestor_depth_partially_shared() {
+    let routes = layout_routes();
+    let x = resolve_match_stack(&routes, "/a/x");
+    let y = resolve_match_stack(&routes, "/a/y");
+    let layout_only = resolve_match_stack(&routes, "/a");
+    assert_eq!(x.common_ancestor_depth(&y), 1);
+    assert_eq!(x.common_ancestor_depth(&layout_only), 1);
+    assert_eq!(layout_only.common_ancestor_depth(&x), 1);
+}
+
+#[test]
+fn test_common_ancestor_depth_disjoint() {
+    let routes = layout_routes();
+    let a = resolve_match_stack(&routes, "/a/x");
+    let b = resolve_match_stack(&routes, "/b");
+    assert_eq!(a.common_ancestor_depth(&b), 0);
+    assert_eq!(a.common_ancestor_depth(&MatchStack::new()), 0);
+
+    // Same routes, different params: nothing is shared
+    let user1 = resolve_match_stack(&routes, "/users/1/posts");
+    let user2 = resolve_match_stack(&routes, "/users/2/posts");
+    assert_eq!(user1.common_ancestor_depth(&user2), 0);
+
+    // Equal-looking routes built separately are different routes
+    let rebuilt = resolve_match_stack(&layout_routes(), "/a/x");
+    assert_eq!(a.common_ancestor_depth(&rebuilt), 0);
+}
+
+// ---- scoped not-found tests ----
+
+fn settings_routes() -> Vec<Arc<Route>> {
+    vec![
+        Arc::new(Route::new("/", dummy)),
+        Arc::new(
+            Route::new("/settings", dummy)
+                .children(vec![
+                    Arc::new(Route::new("profile", dummy)),
+                    Arc::new(
+                        Route::new("security", dummy)
+                            .children(vec![Arc::new(Route::new("keys", dummy))])
+                            .not_found(dummy),
+                    ),
+                ])
+                .not_found(dummy),
+        ),
+        Arc::new(Route::new("/settings/legacy/export", dummy)),
+    ]
+}
+
+#[test]
+fn test_scoped_not_found_keeps_parent_layout() {
+    let stack = resolve_match_stack(&settings_routes(), "/settings/unknown/page");
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack.at_depth(0).unwrap().route.config.path, "/settings");
+
+    let leaf = stack.leaf().unwrap();
+    assert_eq!(leaf.depth, 1);
+    assert_eq!(
+        leaf.params.get(UNMATCHED_PARAM).map(String::as_str),
+        Some("unknown/page")
+    );
+    assert_eq!(leaf.accumulated_pattern(), "/settings/*");
+    assert_eq!(leaf.accumulated_url(), "/settings/unknown/page");
+}
+
+#[test]
+fn test_complete_match_beats_scoped_not_found() {
+    // `/settings` cannot match `legacy/export`, but a later sibling can
+    let stack = resolve_match_stack(&settings_routes(), "/settings/legacy/export");
+    assert_eq!(stack.len(), 1);
+    assert_eq!(
+        stack.leaf().unwrap().route.config.path,
+        "/settings/legacy/export"
+    );
+    assert!(stack.leaf().unwrap().params.get(UNMATCHED_PARAM).is_none());
+}
+
+#[test]
+fn test_deepest_scoped_not_found_wins() {
+    let stack = resolve_match_stack(&settings_routes(), "/settings/security/tokens");
+    assert_eq!(stack.len(), 3);
+    assert_eq!(stack.at_depth(1).unwrap().route.config.path, "security");
+    assert_eq!(
+        stack
+            .leaf()
+            .unwrap()
+            .params
+            .get(UNMATCHED_PARAM)
+            .map(String::as_str),
+        Some("tokens")
+    );
+
+    // Outside any scoped handler the stack stays empty for the global 404
+    assert!(resolve_match_stack(&settings_routes(), "/nowhere").is_empty());
+}
+
+#[test]
+fn test_flags_read_through_match_stack() {
+    use gpui_navigator::RouteFlag;
+
+    let routes = vec![Arc::new(
+        Route::new("/settings", dummy)
+            .flag(RouteFlag::RequiresConnection)
+            .children(vec![
+                Arc::new(Route::new("", dummy)),
+                Arc::new(
+                    Route::new("labs", dummy)
+                        .flag(RouteFlag::Experimental)
+                        .flag(RouteFlag::HiddenFromMenu)
+                        .flag(RouteFlag::Custom(7)),
+                ),
+            ]),
+    )];
+
+    let stack = resolve_match_stack(&routes, "/settings/labs");
+    let (layout, leaf) = (stack.at_depth(0).unwrap(), stack.leaf().unwrap());
+    assert!(layout.has_flag(RouteFlag::RequiresConnection));
+    assert!(!layout.has_flag(RouteFlag::Experimental));
+    assert!(leaf.has_flag(RouteFlag::Experimental));
+    assert!(leaf.has_flag(RouteFlag::HiddenFromMenu));
+    assert!(leaf.has_flag(RouteFlag::Custom(7)));
+    // Flags belong to their route, not to its descendants
+    assert!(!leaf.has_flag(RouteFlag::RequiresConnection));
+
+    let index = resolve_match_stack(&routes, "/settings");
+    assert_eq!(index.len(), 2);
+    assert!(index.leaf().unwrap().route.config.flags.is_empty());
+}
+
+// ---- route env tests ----
+
+#[derive(Clone, Debug, PartialEq)]
+struct Theme(&'static str);
+
+#[derive(Clone, Debug, PartialEq)]
+struct ReadOnly(bool);
+
+#[test]
+fn test_env_nearest_ancestor_wins() {
+    let routes = vec![Arc::new(
+        Route::new("/docs", dummy)
+            .env(Theme("light"))
+            .env(ReadOnly(false))
+            .child(
+                Route::new("archive", dummy)
+                    .env(ReadOnly(true))
+                    .child(Route::new(":id", dummy)),
+            )
+            .not_found(dummy),
+    )];
+
+    let stack = resolve_match_stack(&routes, "/docs/archive/7");
+    let leaf = stack.leaf().unwrap();
+    assert_eq!(leaf.env::<ReadOnly>(), Some(ReadOnly(true)));
+    assert_eq!(leaf.env::<Theme>(), Some(Theme("light")));
+    // Each level keeps the values in effect there
+    assert_eq!(
+        stack.at_depth(0).unwrap().env::<ReadOnly>(),
+        Some(ReadOnly(false))
+    );
+    assert_eq!(
+        stack.at_depth(1).unwrap().env::<ReadOnly>(),
+        Some(ReadOnly(true))
+    );
+
+    // The scoped not-found entry inherits from its handler
+    let missing = resolve_match_stack(&routes, "/docs/nowhere");
+    assert_eq!(missing.leaf().unwrap().env::<Theme>(), Some(Theme("light")));
+}
+
+#[test]
+fn test_env_absent_is_none() {
+    let routes = vec![
+        Arc::new(Route::new("/plain", dummy)),
+        Arc::new(
+            Route::new("/docs", dummy)
+                .env(Theme("dark"))
+                .child(Route::new("", dummy)),
+        ),
+    ];
+
+    let plain = resolve_match_stack(&routes, "/plain");
+    assert_eq!(plain.leaf().unwrap().env::<Theme>(), None);
+    assert!(plain.leaf().unwrap().env_values().is_empty());
+
+    let docs = resolve_match_stack(&routes, "/docs");
+    assert_eq!(docs.leaf().unwrap().env::<Theme>(), Some(Theme("dark")));
+    assert_eq!(docs.leaf().unwrap().env::<ReadOnly>(), None);
+}
+
+// ---- check_route_tree tests ----
+
+/// `levels` routes, each the only child of the previous one.
+fn chain(levels: usize) -> Route {
+    (1..levels).fold(Route::new("leaf", dummy), |child, _| {
+        Route::new("level", dummy).child(child)
+    })
+}
+
+/// `levels` routes, each holding the next one as child and in an outlet.
+fn lattice(levels: usize) -> Arc<Route> {
+    (1..levels).fold(Arc::new(Route::new("x", dummy)), |below, _| {
+        Arc::new(
+            Route::new("x", dummy)
+                .child(Arc::clone(&below))
+                .named_outlet("side", vec![below]),
+        )
+    })
+}
+
+#[test]
+fn test_check_route_tree_rejects_too_deep_nesting() {
+    assert_eq!(check_route_tree(&chain(MAX_DEPTH)), Ok(()));
+
+    let error = check_route_tree(&chain(MAX_DEPTH + 1)).unwrap_err();
+    let expected = format!("{}/leaf", "/level".repeat(MAX_DEPTH));
+    assert_eq!(error, RouteTreeError::TooDeep { route: expected });
+}
+
+#[test]
+fn test_check_route_tree_walks_shared_subtrees_once() {
+    // 2^15 paths through 16 routes. Cycles cannot be built through the
+    // builder API; sharing is how a tree reaches one route twice.
+    assert_eq!(check_route_tree(&lattice(MAX_DEPTH)), Ok(()));
+
+    // Reaching a shared subtree one level deeper checks it again
+    let shared = lattice(MAX_DEPTH - 1);
+    let tree = Route::new("/top", dummy)
+        .child(Arc::clone(&shared))
+        .child(Route::new("pad", dummy).child(shared));
+    assert!(matches!(
+        check_route_tree(&tree),
+        Err(RouteTreeError::TooDeep { route }) if route.starts_with("/top/pad/x/")
+    ));
+}
+
+// ---- entry key tests ----
+
+fn repo_routes() -> Vec<Arc<Route>> {
+    vec![Arc::new(Route::new("/org", dummy).children(vec![
+        Arc::new(Route::new(":org", dummy).children(vec![Arc::new(
+            Route::new("repo/:repo", dummy).children(vec![
+                Arc::new(Route::new("", dummy)),
+                Arc::new(Route::new("issues/:issue", dummy)),
+            ]),
+        )])),
+    ]))]
+}
+
+#[test]
+fn test_entry_keys_stable_across_resolutions() {
+    let routes = repo_routes();
+    let path = "/org/acme/repo/site/issues/7";
+
+    let first = resolve_match_stack(&routes, path);
+    let second = resolve_match_stack(&routes, path);
+    assert_eq!(first.keys(), second.keys());
+    for (a, b) in first.entries().iter().zip(second.entries()) {
+        assert_eq!(a.key_hash(), b.key_hash());
+    }
+    // Params are ordered by name, whatever order they were captured in
+    assert_eq!(
+        first.leaf().unwrap().key().as_ref(),
+        "3:/org/:org/repo/:repo/issues/:issue?issue=7&org=acme&repo=site"
+    );
+}
+
+#[test]
+fn test_entry_keys_unique_within_stack() {
+    // The index route repeats the pattern of its parent one level down
+    let routes = repo_routes();
+    let stack = resolve_match_stack(&routes, "/org/acme/repo/site");
+    assert_eq!(stack.len(), 4);
+    assert_eq!(
+        stack.at_depth(2).unwrap().accumulated_pattern(),
+        stack.at_depth(3).unwrap().accumulated_pattern()
+    );
+
+    let keys = stack.keys();
+    let unique: std::collections::HashSet<_> = keys.iter().collect();
+    assert_eq!(unique.len(), keys.len());
+    let hashes: std::collections::HashSet<_> =
+        stack.entries().iter().map(MatchEntry::key_hash).collect();
+    assert_eq!(hashes.len(), keys.len());
+}
+
+#[test]
+fn test_entry_keys_distinct_across_param_values() {
+    let routes = repo_routes();
+    let seven = resolve_match_stack(&routes, "/org/acme/repo/site/issues/7");
+    let eight = resolve_match_stack(&routes, "/org/acme/repo/site/issues/8");
+
+    assert_ne!(seven.leaf().unwrap().key(), eight.leaf().unwrap().key());
+    assert_ne!(
+        seven.leaf().unwrap().key_hash(),
+        eight.leaf().unwrap().key_hash()
+    );
+    // Levels above the changed param keep their identity
+    assert_eq!(seven.keys()[..3], eight.keys()[..3]);
+}
+
+// ---- strict params tests ----
+
+/// `/users/:id`, with a less specific `/users/*rest` fallback after it.
+fn user_routes(strict: bool) -> Vec<Arc<Route>> {
+    vec![
+        Arc::new(
+            Route::new("/users", dummy)
+                .children(vec![Arc::new(
+                    Route::new(":id", dummy).strict_params(strict),
+                )])
+                .not_found(dummy),
+        ),
+        Arc::new(Route::new("/users/*rest", dummy)),
+    ]
+}
+
+#[test]
+fn test_non_strict_route_backtracks_on_extra_segments() {
+    let stack = resolve_match_stack(&user_routes(false), "/users/42/extra");
+    assert_eq!(stack.len(), 1);
+    let leaf = stack.leaf().unwrap();
+    assert_eq!(leaf.route.config.path, "/users/*rest");
+    assert_eq!(
+        leaf.params.get("rest").map(String::as_str),
+        Some("42/extra")
+    );
+}
+
+#[test]
+fn test_strict_route_rejects_extra_segments() {
+    let stack = resolve_match_stack(&user_routes(true), "/users/42/extra");
+    // The fallback is never tried; the parent's handler takes the path
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack.at_depth(0).unwrap().route.config.path, "/users");
+    assert_eq!(
+        stack
+            .leaf()
+            .unwrap()
+            .params
+            .get(UNMATCHED_PARAM)
+            .map(String::as_str),
+        Some("42/extra")
+    );
+
+    // Without a scoped handler the global 404 renders
+    let routes = vec![
+        Arc::new(Route::new("/users/:id", dummy).strict_params(true)),
+        Arc::new(Route::new("/users/*rest", dummy)),
+    ];
+    assert!(resolve_match_stack(&routes, "/users/42/extra").is_empty());
+}
+
+#[test]
+fn test_strict_route_matches_exact_path() {
+    let stack = resolve_match_stack(&user_routes(true), "/users/42");
+    assert_eq!(stack.len(), 2);
+    let leaf = stack.leaf().unwrap();
+    assert_eq!(leaf.route.config.path, ":id");
+    assert_eq!(leaf.params.get("id").map(String::as_str), Some("42"));
+}
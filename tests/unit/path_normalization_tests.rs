@@ -54,3 +54,12 @@ fn test_normalize_with_parameters() {
     assert_eq!(normalize_path("users/:id"), "/users/:id");
     assert_eq!(normalize_path("/users/:id/"), "/users/:id");
 }
+
+#[test]
+fn test_normalize_collapses_duplicate_slashes() {
+    // Repeated slashes inside the path collapse to one
+    assert_eq!(normalize_path("/users//42"), "/users/42");
+    assert_eq!(normalize_path("users///42/"), "/users/42");
+    // Percent-escapes are left for matching to decode
+    assert_eq!(normalize_path("/users/%20bob"), "/users/%20bob");
+}